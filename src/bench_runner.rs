@@ -0,0 +1,622 @@
+//! `tonic bench`: times the public zero-argument `bench_*` functions of
+//! `*_bench.tn` files and summarizes them in a Benchee-style table.
+//!
+//! Each benchmark is calibrated to a batch size whose run takes at least
+//! [`MIN_SAMPLE_TIME`], warmed up, and then sampled batch by batch until the
+//! time budget is spent. Samples are per-iteration times, so the statistics
+//! do not depend on the batch size.
+
+use crate::interop::capture_host_output_with_stdin;
+use crate::ir::{lower_ast_to_ir, IrProgram};
+use crate::manifest::inject_optional_stdlib;
+use crate::runtime::evaluate_named_function_with_args;
+use crate::test_runner::{
+    analyze_suite_source, discover_source_files, NativeSuite, TestBackend, TestRunnerError,
+};
+use serde_json::{json, Value};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+/// Batches are grown until one takes at least this long, so that timer
+/// resolution and call overhead stay small next to the measured work.
+const MIN_SAMPLE_TIME: Duration = Duration::from_millis(1);
+
+/// Upper bound for the calibrated batch size.
+const MAX_BATCH_ITERATIONS: u64 = 1 << 30;
+
+#[derive(Debug, Clone)]
+pub(crate) struct BenchOptions {
+    pub filter: Option<String>,
+    pub warmup: Duration,
+    pub time: Duration,
+    pub backend: TestBackend,
+}
+
+impl Default for BenchOptions {
+    fn default() -> Self {
+        Self {
+            filter: None,
+            warmup: Duration::from_secs(2),
+            time: Duration::from_secs(5),
+            backend: TestBackend::Interpreter,
+        }
+    }
+}
+
+/// A bench file after stdlib injection and lowering.
+struct BenchSuite {
+    path: PathBuf,
+    source: String,
+    ir: IrProgram,
+    /// Qualified `Module.bench_name` functions, in name order.
+    functions: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct BenchStats {
+    pub samples: usize,
+    /// Iterations per sample.
+    pub batch: u64,
+    pub mean_ns: f64,
+    pub median_ns: f64,
+    pub p99_ns: f64,
+    pub std_dev_ns: f64,
+    pub min_ns: f64,
+    pub max_ns: f64,
+}
+
+impl BenchStats {
+    /// Statistics of per-iteration sample times in nanoseconds.
+    pub(crate) fn from_samples(mut samples: Vec<f64>, batch: u64) -> Self {
+        samples.sort_by(|left, right| left.total_cmp(right));
+        let count = samples.len().max(1) as f64;
+        let mean = samples.iter().sum::<f64>() / count;
+        let variance = if samples.len() > 1 {
+            samples
+                .iter()
+                .map(|sample| (sample - mean).powi(2))
+                .sum::<f64>()
+                / (count - 1.0)
+        } else {
+            0.0
+        };
+        let median = match samples.len() {
+            0 => 0.0,
+            len if len % 2 == 0 => (samples[len / 2 - 1] + samples[len / 2]) / 2.0,
+            len => samples[len / 2],
+        };
+        // Nearest-rank percentile.
+        let p99_rank = ((samples.len() as f64 * 0.99).ceil() as usize).max(1);
+
+        Self {
+            samples: samples.len(),
+            batch,
+            mean_ns: mean,
+            median_ns: median,
+            p99_ns: samples.get(p99_rank - 1).copied().unwrap_or(0.0),
+            std_dev_ns: variance.sqrt(),
+            min_ns: samples.first().copied().unwrap_or(0.0),
+            max_ns: samples.last().copied().unwrap_or(0.0),
+        }
+    }
+
+    /// Iterations per second.
+    pub(crate) fn ips(&self) -> f64 {
+        if self.mean_ns > 0.0 {
+            1e9 / self.mean_ns
+        } else {
+            0.0
+        }
+    }
+
+    /// Standard deviation as a percentage of the mean.
+    pub(crate) fn deviation_percent(&self) -> f64 {
+        if self.mean_ns > 0.0 {
+            self.std_dev_ns / self.mean_ns * 100.0
+        } else {
+            0.0
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct BenchResult {
+    pub id: String,
+    pub file: PathBuf,
+    pub outcome: Result<BenchStats, String>,
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct BenchReport {
+    pub backend: TestBackend,
+    pub results: Vec<BenchResult>,
+}
+
+impl BenchReport {
+    pub(crate) fn errors(&self) -> usize {
+        self.results
+            .iter()
+            .filter(|result| result.outcome.is_err())
+            .count()
+    }
+
+    fn measured(&self) -> Vec<(&str, &BenchStats)> {
+        self.results
+            .iter()
+            .filter_map(|result| match &result.outcome {
+                Ok(stats) => Some((result.id.as_str(), stats)),
+                Err(_) => None,
+            })
+            .collect()
+    }
+
+    /// The results table, the fastest-first comparison and any errors.
+    pub(crate) fn render_text(&self) -> Vec<String> {
+        let measured = self.measured();
+        let width = self
+            .results
+            .iter()
+            .map(|result| result.id.chars().count())
+            .max()
+            .unwrap_or(0)
+            .max("Name".len())
+            + 2;
+
+        let mut lines = Vec::new();
+        if !measured.is_empty() {
+            lines.push(format!(
+                "{:<width$}{:>11}{:>15}{:>13}{:>15}{:>15}",
+                "Name", "ips", "average", "deviation", "median", "99th %"
+            ));
+            for (id, stats) in &measured {
+                lines.push(format!(
+                    "{id:<width$}{:>11}{:>15}{:>13}{:>15}{:>15}",
+                    format_ips(stats.ips()),
+                    format_duration_ns(stats.mean_ns),
+                    format!("±{:.2}%", stats.deviation_percent()),
+                    format_duration_ns(stats.median_ns),
+                    format_duration_ns(stats.p99_ns),
+                ));
+            }
+        }
+
+        if measured.len() > 1 {
+            let mut ranked = measured.clone();
+            ranked.sort_by(|left, right| left.1.mean_ns.total_cmp(&right.1.mean_ns));
+            let fastest = ranked[0].1;
+            lines.push(String::new());
+            lines.push("Comparison:".to_string());
+            for (index, (id, stats)) in ranked.iter().enumerate() {
+                let ips = format!("{id:<width$}{:>11}", format_ips(stats.ips()));
+                if index == 0 {
+                    lines.push(ips);
+                } else {
+                    lines.push(format!(
+                        "{ips} - {:.2}x slower +{}",
+                        stats.mean_ns / fastest.mean_ns.max(f64::MIN_POSITIVE),
+                        format_duration_ns(stats.mean_ns - fastest.mean_ns)
+                    ));
+                }
+            }
+        }
+
+        let failed = self
+            .results
+            .iter()
+            .filter_map(|result| result.outcome.as_ref().err().map(|error| (result, error)))
+            .collect::<Vec<_>>();
+        if !failed.is_empty() {
+            if !lines.is_empty() {
+                lines.push(String::new());
+            }
+            lines.push("Errors:".to_string());
+            for (result, error) in failed {
+                lines.push(format!("{} ({})", result.id, result.file.display()));
+                for line in error.lines() {
+                    lines.push(format!("  {line}"));
+                }
+            }
+        }
+
+        lines
+    }
+
+    /// The `--save-baseline` file: per-benchmark statistics in run order.
+    pub(crate) fn render_baseline(&self) -> Value {
+        let benchmarks = self
+            .measured()
+            .into_iter()
+            .map(|(id, stats)| {
+                json!({
+                    "id": id,
+                    "ips": stats.ips(),
+                    "mean_ns": stats.mean_ns,
+                    "median_ns": stats.median_ns,
+                    "p99_ns": stats.p99_ns,
+                    "std_dev_ns": stats.std_dev_ns,
+                    "min_ns": stats.min_ns,
+                    "max_ns": stats.max_ns,
+                    "samples": stats.samples,
+                    "batch": stats.batch,
+                })
+            })
+            .collect::<Vec<_>>();
+        json!({
+            "backend": self.backend.name(),
+            "benchmarks": benchmarks,
+        })
+    }
+
+    /// Compare average times against a saved baseline.
+    pub(crate) fn compare(&self, baseline: &Baseline) -> BaselineComparison {
+        let entries = self
+            .measured()
+            .into_iter()
+            .map(|(id, stats)| {
+                let previous = baseline
+                    .benchmarks
+                    .iter()
+                    .find(|(baseline_id, _)| baseline_id == id)
+                    .map(|(_, mean_ns)| *mean_ns);
+                BaselineEntry {
+                    id: id.to_string(),
+                    previous_ns: previous,
+                    current_ns: stats.mean_ns,
+                }
+            })
+            .collect();
+        BaselineComparison { entries }
+    }
+}
+
+/// Average times read from a `--save-baseline` file.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct Baseline {
+    pub backend: Option<String>,
+    pub benchmarks: Vec<(String, f64)>,
+}
+
+impl Baseline {
+    pub(crate) fn load(path: &Path) -> Result<Self, String> {
+        let contents = std::fs::read_to_string(path)
+            .map_err(|error| format!("failed to read baseline {}: {error}", path.display()))?;
+        let value: Value = serde_json::from_str(&contents)
+            .map_err(|error| format!("failed to parse baseline {}: {error}", path.display()))?;
+        let invalid = || {
+            format!(
+                "invalid baseline {}: expected a \"benchmarks\" list of {{\"id\", \"mean_ns\"}} entries",
+                path.display()
+            )
+        };
+
+        let benchmarks = value["benchmarks"]
+            .as_array()
+            .ok_or_else(invalid)?
+            .iter()
+            .map(
+                |entry| match (entry["id"].as_str(), entry["mean_ns"].as_f64()) {
+                    (Some(id), Some(mean_ns)) => Ok((id.to_string(), mean_ns)),
+                    _ => Err(invalid()),
+                },
+            )
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(Self {
+            backend: value["backend"].as_str().map(str::to_string),
+            benchmarks,
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct BaselineEntry {
+    pub id: String,
+    /// `None` for benchmarks the baseline does not have.
+    pub previous_ns: Option<f64>,
+    pub current_ns: f64,
+}
+
+impl BaselineEntry {
+    /// Change of the average time in percent; positive is slower.
+    pub(crate) fn change_percent(&self) -> Option<f64> {
+        self.previous_ns
+            .filter(|previous| *previous > 0.0)
+            .map(|previous| (self.current_ns - previous) / previous * 100.0)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct BaselineComparison {
+    pub entries: Vec<BaselineEntry>,
+}
+
+impl BaselineComparison {
+    /// Benchmarks whose average time grew by more than `max_percent`.
+    pub(crate) fn regressions(&self, max_percent: f64) -> Vec<&BaselineEntry> {
+        self.entries
+            .iter()
+            .filter(|entry| {
+                entry
+                    .change_percent()
+                    .is_some_and(|change| change > max_percent)
+            })
+            .collect()
+    }
+
+    pub(crate) fn render_text(&self, path: &Path, max_regression: Option<f64>) -> Vec<String> {
+        let width = self
+            .entries
+            .iter()
+            .map(|entry| entry.id.chars().count())
+            .max()
+            .unwrap_or(0)
+            + 2;
+
+        let mut lines = vec![format!("Baseline ({}):", path.display())];
+        for entry in &self.entries {
+            let current = format_duration_ns(entry.current_ns);
+            let line = match (entry.previous_ns, entry.change_percent()) {
+                (Some(previous), Some(change)) => {
+                    let regressed = max_regression.is_some_and(|max| change > max);
+                    format!(
+                        "{:<width$}{:>15} -> {:>12}  {change:+.2}%{}",
+                        entry.id,
+                        format_duration_ns(previous),
+                        current,
+                        if regressed { " (regression)" } else { "" }
+                    )
+                }
+                _ => format!("{:<width$}{:>15} -> {current:>12}  new", entry.id, "-"),
+            };
+            lines.push(line);
+        }
+        lines
+    }
+}
+
+pub(crate) fn is_bench_file_name(path: &Path) -> bool {
+    path.extension().and_then(|extension| extension.to_str()) == Some("tn")
+        && path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .is_some_and(|name| name.ends_with("_bench.tn"))
+}
+
+/// Compile every bench file under `path` first, so that source errors are
+/// reported before any timing starts, then run the selected benchmarks.
+/// `on_start` is called with each benchmark's id before it runs.
+pub(crate) fn run_benchmarks(
+    path: &Path,
+    options: &BenchOptions,
+    mut on_start: impl FnMut(&str),
+) -> Result<BenchReport, TestRunnerError> {
+    let suites = discover_source_files(path, is_bench_file_name)?
+        .iter()
+        .map(|file| load_bench_suite(file, options.filter.as_deref()))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let mut results = Vec::new();
+    for suite in suites.iter().filter(|suite| !suite.functions.is_empty()) {
+        let native = if options.backend.needs_native() {
+            match NativeSuite::build(&suite.path, &suite.source, &suite.ir) {
+                Ok(native) => Some(native),
+                Err(error) => {
+                    results.extend(suite.functions.iter().map(|function| BenchResult {
+                        id: function.clone(),
+                        file: suite.path.clone(),
+                        outcome: Err(error.clone()),
+                    }));
+                    continue;
+                }
+            }
+        } else {
+            None
+        };
+
+        for function in &suite.functions {
+            on_start(function);
+            let outcome = match &native {
+                Some(native) => native
+                    .bench(function)
+                    .and_then(|mut bench| measure(options, |n| bench.measure(n))),
+                None => {
+                    let (outcome, _output) = capture_host_output_with_stdin(None, || {
+                        measure(options, |iterations| {
+                            let started = Instant::now();
+                            for _ in 0..iterations {
+                                evaluate_named_function_with_args(&suite.ir, function, &[])
+                                    .map_err(|error| error.to_string())?;
+                            }
+                            Ok(started.elapsed())
+                        })
+                    });
+                    outcome
+                }
+            };
+            results.push(BenchResult {
+                id: function.clone(),
+                file: suite.path.clone(),
+                outcome,
+            });
+        }
+    }
+
+    Ok(BenchReport {
+        backend: options.backend,
+        results,
+    })
+}
+
+fn load_bench_suite(path: &Path, filter: Option<&str>) -> Result<BenchSuite, TestRunnerError> {
+    let filename = Some(path.display().to_string());
+    let mut source = std::fs::read_to_string(path).map_err(|error| {
+        TestRunnerError::Failure(format!(
+            "failed to read source file {}: {error}",
+            path.display()
+        ))
+    })?;
+    inject_optional_stdlib(&mut source)
+        .map_err(|error| TestRunnerError::Failure(format!("stdlib injection failed: {error}")))?;
+
+    let ast = analyze_suite_source(&filename, &source)?;
+    let mut functions = ast
+        .modules
+        .iter()
+        .flat_map(|module| {
+            module
+                .functions
+                .iter()
+                .filter(|function| {
+                    !function.is_private()
+                        && function.params.is_empty()
+                        && function.name.starts_with("bench_")
+                })
+                .map(move |function| format!("{}.{}", module.name, function.name))
+        })
+        .filter(|id| filter.is_none_or(|pattern| id.contains(pattern)))
+        .collect::<Vec<_>>();
+    functions.sort();
+
+    let ir = lower_ast_to_ir(&ast).map_err(|error| TestRunnerError::Failure(error.to_string()))?;
+    Ok(BenchSuite {
+        path: path.to_path_buf(),
+        source,
+        ir,
+        functions,
+    })
+}
+
+/// Calibrate, warm up and sample one benchmark. `run_batch` runs the
+/// benchmark the given number of times and returns the elapsed time.
+fn measure(
+    options: &BenchOptions,
+    mut run_batch: impl FnMut(u64) -> Result<Duration, String>,
+) -> Result<BenchStats, String> {
+    let warmup_started = Instant::now();
+    let mut batch = 1;
+    loop {
+        let elapsed = run_batch(batch)?;
+        if elapsed >= MIN_SAMPLE_TIME || batch >= MAX_BATCH_ITERATIONS {
+            break;
+        }
+        batch *= 2;
+    }
+    while warmup_started.elapsed() < options.warmup {
+        run_batch(batch)?;
+    }
+
+    let started = Instant::now();
+    let mut samples = Vec::new();
+    loop {
+        let elapsed = run_batch(batch)?;
+        samples.push(elapsed.as_nanos() as f64 / batch as f64);
+        if started.elapsed() >= options.time {
+            break;
+        }
+    }
+    Ok(BenchStats::from_samples(samples, batch))
+}
+
+/// `1.23 K` style iterations per second.
+pub(crate) fn format_ips(ips: f64) -> String {
+    let (value, unit) = if ips >= 1e9 {
+        (ips / 1e9, " G")
+    } else if ips >= 1e6 {
+        (ips / 1e6, " M")
+    } else if ips >= 1e3 {
+        (ips / 1e3, " K")
+    } else {
+        (ips, "")
+    };
+    format!("{value:.2}{unit}")
+}
+
+/// A duration in the largest unit that keeps the value at or above 1.
+pub(crate) fn format_duration_ns(nanos: f64) -> String {
+    let (value, unit) = if nanos >= 1e9 {
+        (nanos / 1e9, "s")
+    } else if nanos >= 1e6 {
+        (nanos / 1e6, "ms")
+    } else if nanos >= 1e3 {
+        (nanos / 1e3, "μs")
+    } else {
+        (nanos, "ns")
+    };
+    format!("{value:.2} {unit}")
+}
+
+/// `2 s`, `0.5 s`.
+pub(crate) fn format_seconds(duration: Duration) -> String {
+    format!("{} s", duration.as_secs_f64())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stats(mean_ns: f64) -> BenchStats {
+        BenchStats::from_samples(vec![mean_ns], 1)
+    }
+
+    #[test]
+    fn stats_use_nearest_rank_percentiles_and_sample_deviation() {
+        let samples = (1..=200).map(f64::from).collect::<Vec<_>>();
+        let stats = BenchStats::from_samples(samples, 4);
+
+        assert_eq!(stats.samples, 200);
+        assert_eq!(stats.mean_ns, 100.5);
+        assert_eq!(stats.median_ns, 100.5);
+        assert_eq!(stats.p99_ns, 198.0);
+        assert_eq!((stats.min_ns, stats.max_ns), (1.0, 200.0));
+        assert!((stats.std_dev_ns - 57.88).abs() < 0.01);
+        assert_eq!(format_ips(stats.ips()), "9.95 M");
+    }
+
+    #[test]
+    fn report_ranks_benchmarks_and_flags_baseline_regressions() {
+        let report = BenchReport {
+            backend: TestBackend::Interpreter,
+            results: vec![
+                BenchResult {
+                    id: "ListBench.bench_append".to_string(),
+                    file: PathBuf::from("list_bench.tn"),
+                    outcome: Ok(stats(2_500_000.0)),
+                },
+                BenchResult {
+                    id: "ListBench.bench_map".to_string(),
+                    file: PathBuf::from("list_bench.tn"),
+                    outcome: Ok(stats(1_250.0)),
+                },
+            ],
+        };
+
+        assert_eq!(
+            report.render_text(),
+            vec![
+                "Name                            ips        average    deviation         median         99th %",
+                "ListBench.bench_append       400.00        2.50 ms       ±0.00%        2.50 ms        2.50 ms",
+                "ListBench.bench_map        800.00 K        1.25 μs       ±0.00%        1.25 μs        1.25 μs",
+                "",
+                "Comparison:",
+                "ListBench.bench_map        800.00 K",
+                "ListBench.bench_append       400.00 - 2000.00x slower +2.50 ms",
+            ]
+        );
+
+        let baseline = Baseline {
+            backend: Some("interpreter".to_string()),
+            benchmarks: vec![("ListBench.bench_append".to_string(), 2_000_000.0)],
+        };
+        let comparison = report.compare(&baseline);
+        assert_eq!(comparison.regressions(20.0).len(), 1);
+        assert!(comparison.regressions(30.0).is_empty());
+        assert_eq!(
+            comparison.render_text(Path::new("bench.json"), Some(20.0)),
+            vec![
+                "Baseline (bench.json):",
+                "ListBench.bench_append          2.00 ms ->      2.50 ms  +25.00% (regression)",
+                "ListBench.bench_map                   - ->      1.25 μs  new",
+            ]
+        );
+    }
+}
@@ -89,6 +89,7 @@ pub fn calculate_calibrated_threshold(value: f64, margin_pct: u64) -> u64 {
     (value * margin_multiplier).ceil() as u64 + 1
 }
 
+/// Workloads run with `TONIC_SHARED_CACHE=0`, so this is the cache they use.
 pub fn clear_cache() {
     let _ = fs::remove_dir_all(".tonic/cache");
 }
@@ -127,6 +128,7 @@ fn measure_rss(prepared: &PreparedWorkload, workload: &Workload) -> Option<u64>
         .arg("-v")
         .arg(&prepared.executable)
         .args(&prepared.args)
+        .env("TONIC_SHARED_CACHE", "0")
         .output()
         .ok()?;
 
@@ -260,6 +262,7 @@ pub fn run_workload(
         }
         let output = Command::new(&prepared.executable)
             .args(&prepared.args)
+            .env("TONIC_SHARED_CACHE", "0")
             .output()
             .map_err(|error| {
                 format!(
@@ -287,6 +290,7 @@ pub fn run_workload(
         let start = Instant::now();
         let output = Command::new(&prepared.executable)
             .args(&prepared.args)
+            .env("TONIC_SHARED_CACHE", "0")
             .output()
             .map_err(|error| {
                 format!(
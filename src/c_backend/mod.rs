@@ -22,6 +22,7 @@ mod stubs_results;
 mod stubs_try;
 mod stubs_types;
 mod terminator;
+mod test_harness;
 
 pub(crate) use error::CBackendError;
 
@@ -34,6 +35,7 @@ use dispatcher::emit_dispatcher;
 use funcs::emit_function;
 use groups::{group_functions, group_requires_dispatcher};
 use stubs::{emit_header, emit_runtime_stubs};
+use test_harness::emit_test_harness_entrypoint;
 
 /// Lower a MIR program to a self-contained C source file.
 ///
@@ -46,6 +48,35 @@ pub(crate) fn lower_mir_to_c(
     mir: &MirProgram,
     source_path: &str,
     source: &str,
+) -> Result<String, CBackendError> {
+    lower_program(mir, source_path, source, Entrypoint::Main)
+}
+
+/// Lower a test suite for `tonic test --backend native`: the same program,
+/// with a `main()` that calls the test function named on the command line.
+///
+/// Test suites carry stdlib helpers such as `Assert.assert_raises` whether
+/// or not a test uses them, so `try` expressions the backend cannot compile
+/// fail when reached instead of failing the whole suite.
+pub(crate) fn lower_mir_to_c_test_harness(
+    mir: &MirProgram,
+    source_path: &str,
+    source: &str,
+) -> Result<String, CBackendError> {
+    lower_program(mir, source_path, source, Entrypoint::TestHarness)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Entrypoint {
+    Main,
+    TestHarness,
+}
+
+fn lower_program(
+    mir: &MirProgram,
+    source_path: &str,
+    source: &str,
+    entrypoint: Entrypoint,
 ) -> Result<String, CBackendError> {
     let groups = group_functions(mir);
     let mut callable_symbols = BTreeMap::<(String, usize), String>::new();
@@ -73,7 +104,13 @@ pub(crate) fn lower_mir_to_c(
 
     emit_header(&mut out);
     emit_forward_declarations(&groups, mir, &clause_symbols, &callable_symbols, &mut out);
-    emit_runtime_stubs(mir, source_path, source, &mut out)?;
+    emit_runtime_stubs(
+        mir,
+        source_path,
+        source,
+        entrypoint == Entrypoint::TestHarness,
+        &mut out,
+    )?;
 
     for group in &groups {
         let use_dispatcher = group_requires_dispatcher(group, mir);
@@ -98,7 +135,10 @@ pub(crate) fn lower_mir_to_c(
         emit_dispatcher(group, mir, &clause_symbols, &callable_symbols, &mut out)?;
     }
 
-    emit_main_entrypoint(&callable_symbols, &mut out);
+    match entrypoint {
+        Entrypoint::Main => emit_main_entrypoint(&callable_symbols, &mut out),
+        Entrypoint::TestHarness => emit_test_harness_entrypoint(&callable_symbols, &mut out),
+    }
 
     Ok(out)
 }
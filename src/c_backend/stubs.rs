@@ -44,11 +44,13 @@ pub(super) fn emit_header(out: &mut String) {
 /// Emit runtime helper definitions for the generated C program.
 ///
 /// Task 05 helpers are implemented inline; unsupported helpers remain explicit
-/// abort stubs so failures stay deterministic.
+/// abort stubs so failures stay deterministic. With `lenient_try`, `try`
+/// expressions that cannot be compiled become runtime failures.
 pub(super) fn emit_runtime_stubs(
     mir: &MirProgram,
     source_path: &str,
     source: &str,
+    lenient_try: bool,
     out: &mut String,
 ) -> Result<(), CBackendError> {
     emit_stubs_types(source_path, source, out);
@@ -84,7 +86,7 @@ static TnVal tn_runtime_host_call_with_offset(size_t offset, TnVal count, ...) {
     );
     emit_stubs_results(out);
     emit_runtime_pattern_helpers(mir, out)?;
-    emit_runtime_try_helpers(mir, out)?;
+    emit_runtime_try_helpers(mir, lenient_try, out)?;
     emit_runtime_for_helpers(mir, source_path, source, out)?;
     emit_compiled_closure_helpers(mir, out)?;
     Ok(())
@@ -3293,7 +3293,7 @@ static TnVal tn_runtime_host_call_varargs_impl(TnVal count, va_list vargs) {\n",
       free(args);
       return tn_runtime_const_atom((TnVal)(intptr_t)"ok");
     }
-    const char *msg = (argc == 3) ? tn_expect_host_string_arg("Assert.assert", args[2], 2) : "assertion failed: expected truthy value";
+    const char *msg = tn_host_assert_message("Assert.assert", args, argc, 2, "assertion failed: expected truthy value");
     TnVal assert_atom = tn_runtime_const_atom((TnVal)(intptr_t)"assertion_failed");
     TnVal type_atom = tn_runtime_const_atom((TnVal)(intptr_t)"assert");
     TnVal msg_val = tn_runtime_const_string((TnVal)(intptr_t)msg);
@@ -3317,7 +3317,7 @@ static TnVal tn_runtime_host_call_varargs_impl(TnVal count, va_list vargs) {\n",
       free(args);
       return tn_runtime_const_atom((TnVal)(intptr_t)"ok");
     }
-    const char *msg = (argc == 3) ? tn_expect_host_string_arg("Assert.refute", args[2], 2) : "refute failed: expected falsy value";
+    const char *msg = tn_host_assert_message("Assert.refute", args, argc, 2, "refute failed: expected falsy value");
     TnVal assert_atom = tn_runtime_const_atom((TnVal)(intptr_t)"assertion_failed");
     TnVal type_atom = tn_runtime_const_atom((TnVal)(intptr_t)"refute");
     TnVal msg_val = tn_runtime_const_string((TnVal)(intptr_t)msg);
@@ -3338,7 +3338,7 @@ static TnVal tn_runtime_host_call_varargs_impl(TnVal count, va_list vargs) {\n",
       free(args);
       return tn_runtime_const_atom((TnVal)(intptr_t)"ok");
     }
-    const char *msg = (argc == 4) ? tn_expect_host_string_arg("Assert.assert_equal", args[3], 3) : "values are not equal";
+    const char *msg = tn_host_assert_message("Assert.assert_equal", args, argc, 3, "values are not equal");
     TnVal af_atom = tn_runtime_const_atom((TnVal)(intptr_t)"assertion_failed");
     TnVal detail = tn_runtime_make_list_varargs((TnVal)4,
       tn_runtime_make_tuple(tn_runtime_const_atom((TnVal)(intptr_t)"type"), tn_runtime_const_atom((TnVal)(intptr_t)"assert_equal")),
@@ -3362,7 +3362,7 @@ static TnVal tn_runtime_host_call_varargs_impl(TnVal count, va_list vargs) {\n",
       free(args);
       return tn_runtime_const_atom((TnVal)(intptr_t)"ok");
     }
-    const char *msg = (argc == 4) ? tn_expect_host_string_arg("Assert.assert_not_equal", args[3], 3) : "values should not be equal";
+    const char *msg = tn_host_assert_message("Assert.assert_not_equal", args, argc, 3, "values should not be equal");
     TnVal af_atom = tn_runtime_const_atom((TnVal)(intptr_t)"assertion_failed");
     TnVal detail = tn_runtime_make_list_varargs((TnVal)4,
       tn_runtime_make_tuple(tn_runtime_const_atom((TnVal)(intptr_t)"type"), tn_runtime_const_atom((TnVal)(intptr_t)"assert_not_equal")),
@@ -3401,7 +3401,7 @@ static TnVal tn_runtime_host_call_varargs_impl(TnVal count, va_list vargs) {\n",
       free(args);
       return tn_runtime_const_atom((TnVal)(intptr_t)"ok");
     }
-    const char *msg = (argc == 4) ? tn_expect_host_string_arg("Assert.assert_contains", args[3], 3) : "element not found in container";
+    const char *msg = tn_host_assert_message("Assert.assert_contains", args, argc, 3, "element not found in container");
     TnVal af_atom = tn_runtime_const_atom((TnVal)(intptr_t)"assertion_failed");
     TnVal detail = tn_runtime_make_list_varargs((TnVal)4,
       tn_runtime_make_tuple(tn_runtime_const_atom((TnVal)(intptr_t)"type"), tn_runtime_const_atom((TnVal)(intptr_t)"assert_contains")),
@@ -3439,7 +3439,7 @@ static TnVal tn_runtime_host_call_varargs_impl(TnVal count, va_list vargs) {\n",
       free(args);
       return tn_runtime_const_atom((TnVal)(intptr_t)"ok");
     }
-    const char *msg = (argc == 5) ? tn_expect_host_string_arg("Assert.assert_in_delta", args[4], 4) : "values are not within delta";
+    const char *msg = tn_host_assert_message("Assert.assert_in_delta", args, argc, 4, "values are not within delta");
     TnVal af_atom = tn_runtime_const_atom((TnVal)(intptr_t)"assertion_failed");
     TnVal detail = tn_runtime_make_list_varargs((TnVal)5,
       tn_runtime_make_tuple(tn_runtime_const_atom((TnVal)(intptr_t)"type"), tn_runtime_const_atom((TnVal)(intptr_t)"assert_in_delta")),
@@ -3458,7 +3458,7 @@ static TnVal tn_runtime_host_call_varargs_impl(TnVal count, va_list vargs) {\n",
     if (argc > 2) {
       return tn_runtime_failf("host error: Assert.skip expects 0-1 arguments, found %zu", argc - 1);
     }
-    const char *reason = (argc == 2) ? tn_expect_host_string_arg("Assert.skip", args[1], 1) : "";
+    const char *reason = tn_host_assert_message("Assert.skip", args, argc, 1, "");
     TnVal skip_atom = tn_runtime_const_atom((TnVal)(intptr_t)"test_skipped");
     TnVal reason_val = tn_runtime_const_string((TnVal)(intptr_t)reason);
     TnVal detail = tn_runtime_make_tuple(skip_atom, reason_val);
@@ -3503,7 +3503,7 @@ static TnVal tn_runtime_host_call_varargs_impl(TnVal count, va_list vargs) {\n",
         return tn_runtime_const_atom((TnVal)(intptr_t)"ok");
       }
     }
-    const char *msg = (argc == 4) ? tn_expect_host_string_arg("Assert.assert_match", args[3], 3) : "values do not match";
+    const char *msg = tn_host_assert_message("Assert.assert_match", args, argc, 3, "values do not match");
     TnVal af_atom = tn_runtime_const_atom((TnVal)(intptr_t)"assertion_failed");
     TnVal detail = tn_runtime_make_list_varargs((TnVal)4,
       tn_runtime_make_tuple(tn_runtime_const_atom((TnVal)(intptr_t)"type"), tn_runtime_const_atom((TnVal)(intptr_t)"assert_match")),
@@ -155,6 +155,18 @@ static const char *tn_expect_host_string_arg(const char *function, TnVal value,
   return obj->as.text.text;
 }
 
+/* Optional Assert message argument: nil or missing means the default. */
+static const char *tn_host_assert_message(const char *function, TnVal *args, size_t argc, size_t index, const char *fallback) {
+  if (index >= argc) {
+    return fallback;
+  }
+  TnObj *obj = tn_get_obj(args[index]);
+  if (obj != NULL && obj->kind == TN_OBJ_NIL) {
+    return fallback;
+  }
+  return tn_expect_host_string_arg(function, args[index], index);
+}
+
 static int tn_host_list_contains(const TnObj *list, TnVal value) {
   for (size_t i = 0; i < list->as.list.len; i += 1) {
     if (tn_runtime_value_equal(list->as.list.items[i], value)) {
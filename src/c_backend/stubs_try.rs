@@ -5,6 +5,7 @@ use crate::mir::{MirInstruction, MirProgram};
 
 use super::error::CBackendError;
 use super::hash::{hash_ir_op_i64, hash_pattern_i64};
+use super::stubs::c_string_literal;
 
 #[path = "stubs_try_ops.rs"]
 mod ops;
@@ -18,13 +19,26 @@ pub(super) struct TrySpec {
 
 pub(super) fn emit_runtime_try_helpers(
     mir: &MirProgram,
+    lenient: bool,
     out: &mut String,
 ) -> Result<(), CBackendError> {
     let try_specs = collect_try_specs(mir)?;
 
     out.push_str("/* compiled try helpers */\n");
     for (index, try_spec) in try_specs.iter().enumerate() {
-        emit_runtime_try_case(index, try_spec, out)?;
+        let mut case = String::new();
+        match emit_runtime_try_case(index, try_spec, &mut case) {
+            Ok(()) => out.push_str(&case),
+            Err(error) if lenient => {
+                let message = c_string_literal(&format!(
+                    "native backend cannot compile this try expression: {error}"
+                ));
+                out.push_str(&format!(
+                    "static TnVal tn_runtime_try_case_{index}(void) {{\n  return tn_runtime_fail({message});\n}}\n\n"
+                ));
+            }
+            Err(error) => return Err(error),
+        }
     }
 
     out.push_str("static TnVal tn_runtime_try(TnVal op_hash) {\n");
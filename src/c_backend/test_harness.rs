@@ -0,0 +1,319 @@
+use std::collections::BTreeMap;
+
+/// Emit the `main()` of a `tonic test --backend native` binary.
+///
+/// `<binary> <function> <args-path> <result-path>` reads the arguments from
+/// `args-path`, calls the zero- or one-argument `function` and writes its
+/// return value to `result-path`. Values use a length-prefixed encoding that
+/// `test_native.rs` decodes:
+///
+/// - `i<int>;`, `f<float>;`, `b0`/`b1`, `n`
+/// - `s<len>:<bytes>`, `a<len>:<bytes>` for strings and atoms
+/// - `o<term>`, `e<term>` for `ok(...)`/`err(...)`, `t<term><term>` for tuples
+/// - `r<term><term>` for ranges, `c<arity>;` for closures (results only)
+/// - `l<n>:`, `y<n>:` followed by n items for lists and binaries
+/// - `m<n>:`, `k<n>:` followed by n key/value pairs for maps and keywords
+///
+/// `<binary> --bench <function>` is the `tonic bench` mode: it reads
+/// iteration counts from stdin, one per line, calls the zero-argument
+/// `function` that many times and answers each count with `bench: <ns>` on
+/// stderr, collecting garbage between batches. A count of 0 or the end of
+/// stdin ends the run.
+pub(super) fn emit_test_harness_entrypoint(
+    callable_symbols: &BTreeMap<(String, usize), String>,
+    out: &mut String,
+) {
+    out.push_str(TEST_HARNESS_TERM_CODEC);
+
+    out.push_str(
+        "static int tn_test_call(const char *name, size_t argc, TnVal *args, TnVal *result) {\n",
+    );
+    for ((name, arity), symbol) in callable_symbols {
+        let call = match arity {
+            0 => format!("{symbol}()"),
+            1 => format!("{symbol}(args[0])"),
+            _ => continue,
+        };
+        let escaped = name.replace('\\', "\\\\").replace('"', "\\\"");
+        out.push_str(&format!(
+            "  if (argc == {arity} && strcmp(name, \"{escaped}\") == 0) {{\n    *result = {call};\n    return 1;\n  }}\n"
+        ));
+    }
+    out.push_str("  (void)args;\n  (void)result;\n  return 0;\n}\n\n");
+    out.push_str(TEST_HARNESS_BENCH);
+
+    out.push_str(
+        r###"int main(int argc, char **argv) {
+  tn_global_argc = argc;
+  tn_global_argv = argv;
+  if (argc == 3 && strcmp(argv[1], "--bench") == 0) {
+    return tn_test_bench(argv[2]);
+  }
+  if (argc != 4) {
+    fprintf(stderr, "error: usage: %s <function> <args-path> <result-path>\n", argv[0]);
+    return 2;
+  }
+
+  FILE *args_file = fopen(argv[2], "rb");
+  if (args_file == NULL) {
+    fprintf(stderr, "error: cannot open test arguments %s\n", argv[2]);
+    return 2;
+  }
+  size_t frame = tn_runtime_root_frame_push();
+  size_t call_argc = tn_test_read_size(args_file, ':');
+  TnVal call_args[1] = {0};
+  if (call_argc > 1) {
+    fprintf(stderr, "error: test functions take at most one argument, found %zu\n", call_argc);
+    return 2;
+  }
+  for (size_t i = 0; i < call_argc; i += 1) {
+    call_args[i] = tn_test_read_term(args_file);
+    tn_runtime_root_register(call_args[i]);
+  }
+  fclose(args_file);
+
+  TnVal result = 0;
+  if (!tn_test_call(argv[1], call_argc, call_args, &result)) {
+    fprintf(stderr, "error: unknown test function %s/%zu\n", argv[1], call_argc);
+    return 2;
+  }
+  tn_runtime_root_register(result);
+
+  FILE *result_file = fopen(argv[3], "wb");
+  if (result_file == NULL) {
+    fprintf(stderr, "error: cannot write test result %s\n", argv[3]);
+    return 2;
+  }
+  tn_test_write_term(result_file, result);
+  fclose(result_file);
+  fflush(stdout);
+
+  tn_runtime_root_frame_pop(frame);
+  tn_runtime_gc_finalize();
+  return 0;
+}
+"###,
+    );
+}
+
+const TEST_HARNESS_BENCH: &str = r###"static int64_t tn_test_monotonic_ns(void) {
+  struct timespec now;
+  clock_gettime(CLOCK_MONOTONIC, &now);
+  return (int64_t)now.tv_sec * 1000000000 + (int64_t)now.tv_nsec;
+}
+
+static int tn_test_bench(const char *name) {
+  TnVal result = 0;
+  unsigned long long iterations = 0;
+  while (scanf("%llu", &iterations) == 1 && iterations > 0) {
+    int64_t started = tn_test_monotonic_ns();
+    for (unsigned long long i = 0; i < iterations; i += 1) {
+      size_t frame = tn_runtime_root_frame_push();
+      if (!tn_test_call(name, 0, NULL, &result)) {
+        fprintf(stderr, "error: unknown benchmark function %s/0\n", name);
+        return 2;
+      }
+      tn_runtime_root_frame_pop(frame);
+    }
+    int64_t elapsed = tn_test_monotonic_ns() - started;
+    // Nothing is rooted between batches, so this frees the whole batch.
+    tn_runtime_gc_collect();
+    fflush(stdout);
+    fprintf(stderr, "bench: %" PRId64 "\n", elapsed);
+    fflush(stderr);
+  }
+
+  tn_runtime_gc_finalize();
+  return 0;
+}
+
+"###;
+
+const TEST_HARNESS_TERM_CODEC: &str = r###"static void tn_test_write_term(FILE *out, TnVal value) {
+  if (!tn_is_boxed(value)) {
+    fprintf(out, "i%" PRId64 ";", (int64_t)value);
+    return;
+  }
+
+  TnObj *obj = tn_get_obj(value);
+  if (obj == NULL) {
+    fprintf(out, "i%" PRId64 ";", (int64_t)value);
+    return;
+  }
+
+  switch (obj->kind) {
+    case TN_OBJ_BOOL:
+      fputs(obj->as.bool_value ? "b1" : "b0", out);
+      return;
+    case TN_OBJ_NIL:
+      fputc('n', out);
+      return;
+    case TN_OBJ_ATOM:
+    case TN_OBJ_STRING:
+      fprintf(out, "%c%zu:", obj->kind == TN_OBJ_ATOM ? 'a' : 's', strlen(obj->as.text.text));
+      fputs(obj->as.text.text, out);
+      return;
+    case TN_OBJ_FLOAT:
+      fprintf(out, "f%s;", obj->as.text.text);
+      return;
+    case TN_OBJ_TUPLE:
+      fputc('t', out);
+      tn_test_write_term(out, obj->as.tuple.left);
+      tn_test_write_term(out, obj->as.tuple.right);
+      return;
+    case TN_OBJ_LIST:
+    case TN_OBJ_BINARY:
+      fprintf(out, "%c%zu:", obj->kind == TN_OBJ_LIST ? 'l' : 'y', obj->as.list.len);
+      for (size_t i = 0; i < obj->as.list.len; i += 1) {
+        tn_test_write_term(out, obj->as.list.items[i]);
+      }
+      return;
+    case TN_OBJ_MAP:
+    case TN_OBJ_KEYWORD:
+      fprintf(out, "%c%zu:", obj->kind == TN_OBJ_MAP ? 'm' : 'k', obj->as.map_like.len);
+      for (size_t i = 0; i < obj->as.map_like.len; i += 1) {
+        tn_test_write_term(out, obj->as.map_like.items[i].key);
+        tn_test_write_term(out, obj->as.map_like.items[i].value);
+      }
+      return;
+    case TN_OBJ_RANGE:
+      fputc('r', out);
+      tn_test_write_term(out, obj->as.range.start);
+      tn_test_write_term(out, obj->as.range.end);
+      return;
+    case TN_OBJ_RESULT:
+      fputc(obj->as.result.is_ok ? 'o' : 'e', out);
+      tn_test_write_term(out, obj->as.result.value);
+      return;
+    case TN_OBJ_CLOSURE:
+      fprintf(out, "c%" PRId64 ";", (int64_t)obj->as.closure.param_count);
+      return;
+    default:
+      fputc('n', out);
+      return;
+  }
+}
+
+static void tn_test_read_failure(const char *what) {
+  fprintf(stderr, "error: malformed test arguments: %s\n", what);
+  exit(2);
+}
+
+static char *tn_test_read_until(FILE *in, char end) {
+  size_t len = 0;
+  size_t cap = 32;
+  char *text = (char *)malloc(cap);
+  if (text == NULL) {
+    tn_test_read_failure("allocation failure");
+  }
+  int ch;
+  while ((ch = fgetc(in)) != EOF && ch != end) {
+    if (len + 1 == cap) {
+      cap *= 2;
+      text = (char *)realloc(text, cap);
+    }
+    if (text == NULL) {
+      tn_test_read_failure("allocation failure");
+    }
+    text[len] = (char)ch;
+    len += 1;
+  }
+  if (ch == EOF) {
+    tn_test_read_failure("unexpected end of input");
+  }
+  text[len] = '\0';
+  return text;
+}
+
+static size_t tn_test_read_size(FILE *in, char end) {
+  char *text = tn_test_read_until(in, end);
+  char *rest = NULL;
+  unsigned long long size = strtoull(text, &rest, 10);
+  if (rest == text || *rest != '\0') {
+    tn_test_read_failure("expected a length");
+  }
+  free(text);
+  return (size_t)size;
+}
+
+static char *tn_test_read_bytes(FILE *in) {
+  size_t len = tn_test_read_size(in, ':');
+  char *text = (char *)malloc(len + 1);
+  if (text == NULL || fread(text, 1, len, in) != len) {
+    tn_test_read_failure("truncated text");
+  }
+  text[len] = '\0';
+  return text;
+}
+
+static TnVal tn_test_read_term(FILE *in) {
+  int tag = fgetc(in);
+  switch (tag) {
+    case 'i': {
+      char *text = tn_test_read_until(in, ';');
+      TnVal value = (TnVal)strtoll(text, NULL, 10);
+      free(text);
+      return value;
+    }
+    case 'f': {
+      char *text = tn_test_read_until(in, ';');
+      TnVal value = tn_runtime_const_float((TnVal)(intptr_t)text);
+      free(text);
+      return value;
+    }
+    case 'b':
+      return tn_runtime_const_bool(fgetc(in) == '1' ? 1 : 0);
+    case 'n':
+      return tn_runtime_const_nil();
+    case 's':
+    case 'a': {
+      char *text = tn_test_read_bytes(in);
+      TnVal value = tag == 's' ? tn_runtime_const_string((TnVal)(intptr_t)text)
+                               : tn_runtime_const_atom((TnVal)(intptr_t)text);
+      free(text);
+      return value;
+    }
+    case 'o':
+    case 'e': {
+      TnVal inner = tn_test_read_term(in);
+      return tag == 'o' ? tn_runtime_make_ok(inner) : tn_runtime_make_err(inner);
+    }
+    case 't':
+    case 'r': {
+      TnVal left = tn_test_read_term(in);
+      TnVal right = tn_test_read_term(in);
+      return tag == 't' ? tn_runtime_make_tuple(left, right) : tn_runtime_range(left, right);
+    }
+    case 'l':
+    case 'y': {
+      size_t len = tn_test_read_size(in, ':');
+      TnObj *obj = tn_new_obj(tag == 'l' ? TN_OBJ_LIST : TN_OBJ_BINARY);
+      obj->as.list.len = len;
+      obj->as.list.items = len == 0 ? NULL : (TnVal *)calloc(len, sizeof(TnVal));
+      for (size_t i = 0; i < len; i += 1) {
+        obj->as.list.items[i] = tn_test_read_term(in);
+        tn_runtime_retain(obj->as.list.items[i]);
+      }
+      return tn_heap_store(obj);
+    }
+    case 'm':
+    case 'k': {
+      size_t len = tn_test_read_size(in, ':');
+      TnObj *obj = tn_new_obj(tag == 'm' ? TN_OBJ_MAP : TN_OBJ_KEYWORD);
+      obj->as.map_like.len = len;
+      obj->as.map_like.items = len == 0 ? NULL : (TnPair *)calloc(len, sizeof(TnPair));
+      for (size_t i = 0; i < len; i += 1) {
+        obj->as.map_like.items[i].key = tn_test_read_term(in);
+        obj->as.map_like.items[i].value = tn_test_read_term(in);
+        tn_runtime_retain(obj->as.map_like.items[i].key);
+        tn_runtime_retain(obj->as.map_like.items[i].value);
+      }
+      return tn_heap_store(obj);
+    }
+    default:
+      tn_test_read_failure("unknown value tag");
+      return 0;
+  }
+}
+
+"###;
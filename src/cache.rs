@@ -1,13 +1,18 @@
 use crate::deps::Lockfile;
 use crate::ir::IrProgram;
+use serde::{Deserialize, Serialize};
 #[cfg(test)]
 use std::collections::HashMap;
 use std::io::ErrorKind;
 use std::path::{Path, PathBuf};
+use std::time::SystemTime;
 
 const CACHE_DIRECTORY: &str = ".tonic/cache";
 const CACHE_ARTIFACT_EXTENSION: &str = "ir.json";
 const CACHE_FLAGS: &str = "none";
+const CACHE_STATS_SUFFIX: &str = "-stats.json";
+const CACHE_MAX_SIZE_ENV: &str = "TONIC_CACHE_MAX_MB";
+const DEFAULT_CACHE_MAX_MB: u64 = 512;
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub(crate) struct CacheKey(String);
@@ -39,6 +44,23 @@ impl CacheKey {
     pub(crate) fn as_str(&self) -> &str {
         &self.0
     }
+
+    /// Splits a key back into the parts given to [`CacheKey::from_parts`].
+    fn parts(key: &str) -> Option<Vec<&str>> {
+        let mut parts = Vec::new();
+        let mut rest = key;
+        loop {
+            let (length, tail) = rest.split_once(':')?;
+            let length = length.parse::<usize>().ok()?;
+            let part = tail.get(..length)?;
+            parts.push(part);
+            rest = &tail[length..];
+            if rest.is_empty() {
+                return Some(parts);
+            }
+            rest = rest.strip_prefix('|')?;
+        }
+    }
 }
 
 pub(crate) fn build_run_cache_key(source: &str, project_root: &Path) -> CacheKey {
@@ -56,19 +78,29 @@ pub(crate) fn build_run_cache_key(source: &str, project_root: &Path) -> CacheKey
         }
     };
 
-    let target = format!("{}-{}", std::env::consts::OS, std::env::consts::ARCH);
-
     CacheKey::from_parts(
         &entry_hash,
         &dependency_hash,
         env!("CARGO_PKG_VERSION"),
-        &target,
+        &cache_target(),
         CACHE_FLAGS,
     )
 }
 
-pub(crate) fn load_cached_ir(key: &CacheKey) -> Result<Option<IrProgram>, String> {
-    let artifact_path = cache_artifact_path(key)?;
+/// The project cache, `.tonic/cache` under the current directory.
+pub(crate) fn project_cache_directory() -> Result<PathBuf, String> {
+    let current_directory = std::env::current_dir()
+        .map_err(|error| format!("failed to resolve current directory for cache: {error}"))?;
+
+    Ok(current_directory.join(CACHE_DIRECTORY))
+}
+
+/// Loads a cached IR artifact and marks it as recently used.
+pub(crate) fn load_cached_ir(
+    cache_dir: &Path,
+    key: &CacheKey,
+) -> Result<Option<IrProgram>, String> {
+    let artifact_path = cache_artifact_path(cache_dir, key);
 
     let serialized = match std::fs::read_to_string(&artifact_path) {
         Ok(serialized) => serialized,
@@ -77,7 +109,15 @@ pub(crate) fn load_cached_ir(key: &CacheKey) -> Result<Option<IrProgram>, String
     };
 
     match serde_json::from_str::<IrProgram>(&serialized) {
-        Ok(program) => Ok(Some(program)),
+        Ok(program) => {
+            // Eviction goes by modification time, which atime-less mounts
+            // would not update on reads.
+            let _ = std::fs::File::options()
+                .write(true)
+                .open(&artifact_path)
+                .and_then(|file| file.set_modified(SystemTime::now()));
+            Ok(Some(program))
+        }
         Err(_) => {
             let _ = std::fs::remove_file(&artifact_path);
             Ok(None)
@@ -85,8 +125,14 @@ pub(crate) fn load_cached_ir(key: &CacheKey) -> Result<Option<IrProgram>, String
     }
 }
 
-pub(crate) fn store_cached_ir(key: &CacheKey, program: &IrProgram) -> Result<(), String> {
-    let artifact_path = cache_artifact_path(key)?;
+/// Stores an IR artifact, then evicts the least recently used artifacts
+/// while the cache is over its size limit.
+pub(crate) fn store_cached_ir(
+    cache_dir: &Path,
+    key: &CacheKey,
+    program: &IrProgram,
+) -> Result<(), String> {
+    let artifact_path = cache_artifact_path(cache_dir, key);
 
     let payload = serde_json::to_string(program)
         .map_err(|error| format!("failed to serialize cache artifact: {error}"))?;
@@ -96,7 +142,172 @@ pub(crate) fn store_cached_ir(key: &CacheKey, program: &IrProgram) -> Result<(),
             "failed to write cache artifact {}: {error}",
             artifact_path.display()
         )
-    })
+    })?;
+
+    let evicted = evict_least_recently_used(cache_dir, cache_size_limit())?;
+    if evicted > 0 {
+        update_cache_counters(cache_dir, |counters| counters.evictions += evicted as u64);
+    }
+    Ok(())
+}
+
+/// One IR artifact in a cache directory.
+#[derive(Debug, Clone)]
+pub(crate) struct CacheEntry {
+    pub(crate) path: PathBuf,
+    pub(crate) size: u64,
+    /// When the entry was last stored or loaded.
+    pub(crate) last_used: SystemTime,
+}
+
+/// The IR artifacts in `cache_dir`, least recently used first.
+pub(crate) fn cache_entries(cache_dir: &Path) -> Result<Vec<CacheEntry>, String> {
+    let entries = match std::fs::read_dir(cache_dir) {
+        Ok(entries) => entries,
+        Err(error) if error.kind() == ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(error) => {
+            return Err(format!(
+                "failed to read cache directory {}: {error}",
+                cache_dir.display()
+            ))
+        }
+    };
+
+    let mut artifacts = Vec::new();
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let is_artifact = path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .is_some_and(|name| name.ends_with(&format!(".{CACHE_ARTIFACT_EXTENSION}")));
+        let Ok(metadata) = entry.metadata() else {
+            continue;
+        };
+        if !is_artifact || !metadata.is_file() {
+            continue;
+        }
+        artifacts.push(CacheEntry {
+            path,
+            size: metadata.len(),
+            last_used: metadata.modified().unwrap_or(SystemTime::UNIX_EPOCH),
+        });
+    }
+    artifacts.sort_by(|left, right| {
+        left.last_used
+            .cmp(&right.last_used)
+            .then_with(|| left.path.cmp(&right.path))
+    });
+    Ok(artifacts)
+}
+
+/// Removes the least recently used artifacts until the cache holds at most
+/// `max_bytes`, returning how many were removed.
+pub(crate) fn evict_least_recently_used(cache_dir: &Path, max_bytes: u64) -> Result<usize, String> {
+    let entries = cache_entries(cache_dir)?;
+    let mut total = entries.iter().map(|entry| entry.size).sum::<u64>();
+    let mut evicted = 0;
+    for entry in entries {
+        if total <= max_bytes {
+            break;
+        }
+        std::fs::remove_file(&entry.path).map_err(|error| {
+            format!(
+                "failed to evict cache artifact {}: {error}",
+                entry.path.display()
+            )
+        })?;
+        total -= entry.size;
+        evicted += 1;
+    }
+    Ok(evicted)
+}
+
+/// The cache size limit: `TONIC_CACHE_MAX_MB` megabytes, 512 by default.
+pub(crate) fn cache_size_limit() -> u64 {
+    let megabytes = std::env::var(CACHE_MAX_SIZE_ENV)
+        .ok()
+        .and_then(|value| value.trim().parse::<u64>().ok())
+        .unwrap_or(DEFAULT_CACHE_MAX_MB);
+    megabytes.saturating_mul(1024 * 1024)
+}
+
+/// Why `tonic cache verify` rejects an artifact.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum CacheEntryProblem {
+    /// The artifact is not a readable IR program, or its name is not a key.
+    Corrupt(String),
+    /// The artifact was built by another tonic version or for another target
+    /// and can never be loaded again.
+    Stale(String),
+}
+
+pub(crate) fn verify_cache_entry(path: &Path) -> Result<(), CacheEntryProblem> {
+    let key = path
+        .file_name()
+        .and_then(|name| name.to_str())
+        .and_then(|name| name.strip_suffix(&format!(".{CACHE_ARTIFACT_EXTENSION}")))
+        .unwrap_or_default();
+    let Some(parts) = CacheKey::parts(key).filter(|parts| parts.len() == 5) else {
+        return Err(CacheEntryProblem::Corrupt(
+            "file name is not a cache key".to_string(),
+        ));
+    };
+
+    // Older releases wrote a different IR, so check staleness first.
+    let (version, target) = (parts[2], parts[3]);
+    if version != env!("CARGO_PKG_VERSION") {
+        return Err(CacheEntryProblem::Stale(format!(
+            "built by tonic {version}"
+        )));
+    }
+    if target != cache_target() {
+        return Err(CacheEntryProblem::Stale(format!("built for {target}")));
+    }
+
+    let serialized = std::fs::read_to_string(path)
+        .map_err(|error| CacheEntryProblem::Corrupt(format!("unreadable: {error}")))?;
+    serde_json::from_str::<IrProgram>(&serialized)
+        .map(|_| ())
+        .map_err(|error| CacheEntryProblem::Corrupt(format!("invalid IR: {error}")))
+}
+
+/// Hit, miss and eviction counts, kept next to the cache directory in
+/// `<dir>-stats.json` so the directory holds nothing but artifacts.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub(crate) struct CacheCounters {
+    #[serde(default)]
+    pub(crate) hits: u64,
+    #[serde(default)]
+    pub(crate) misses: u64,
+    #[serde(default)]
+    pub(crate) evictions: u64,
+}
+
+pub(crate) fn load_cache_counters(cache_dir: &Path) -> CacheCounters {
+    std::fs::read_to_string(cache_stats_path(cache_dir))
+        .ok()
+        .and_then(|serialized| serde_json::from_str(&serialized).ok())
+        .unwrap_or_default()
+}
+
+/// Counts a `tonic run` cache lookup, the status [`trace_cache_status`] prints.
+pub(crate) fn record_cache_status(cache_dir: &Path, status: &str) {
+    update_cache_counters(cache_dir, |counters| match status {
+        "hit" => counters.hits += 1,
+        _ => counters.misses += 1,
+    });
+}
+
+/// Counters are best effort: a cache that cannot be written keeps no stats.
+fn update_cache_counters(cache_dir: &Path, update: impl FnOnce(&mut CacheCounters)) {
+    if !cache_dir.is_dir() {
+        return;
+    }
+    let mut counters = load_cache_counters(cache_dir);
+    update(&mut counters);
+    if let Ok(serialized) = serde_json::to_string(&counters) {
+        let _ = write_atomic(&cache_stats_path(cache_dir), &serialized);
+    }
 }
 
 pub(crate) fn write_atomic(target_path: &Path, content: &str) -> std::io::Result<()> {
@@ -145,15 +356,18 @@ pub(crate) fn trace_cache_status(status: &str) {
     eprintln!("cache-status {status}");
 }
 
-fn cache_artifact_path(key: &CacheKey) -> Result<PathBuf, String> {
-    let current_directory = std::env::current_dir()
-        .map_err(|error| format!("failed to resolve current directory for cache: {error}"))?;
+fn cache_artifact_path(cache_dir: &Path, key: &CacheKey) -> PathBuf {
+    cache_dir.join(format!("{}.{}", key.as_str(), CACHE_ARTIFACT_EXTENSION))
+}
+
+fn cache_stats_path(cache_dir: &Path) -> PathBuf {
+    let mut file_name = cache_dir.file_name().unwrap_or_default().to_os_string();
+    file_name.push(CACHE_STATS_SUFFIX);
+    cache_dir.with_file_name(file_name)
+}
 
-    Ok(current_directory.join(CACHE_DIRECTORY).join(format!(
-        "{}.{}",
-        key.as_str(),
-        CACHE_ARTIFACT_EXTENSION
-    )))
+fn cache_target() -> String {
+    format!("{}-{}", std::env::consts::OS, std::env::consts::ARCH)
 }
 
 fn stable_content_hash(content: &str) -> String {
@@ -215,7 +429,8 @@ impl CompileCache {
 
 #[cfg(test)]
 mod tests {
-    use super::{CacheKey, CompileCache};
+    use super::{evict_least_recently_used, CacheKey, CompileCache};
+    use std::time::{Duration, SystemTime};
 
     #[test]
     fn cache_key_is_stable_for_identical_inputs() {
@@ -234,6 +449,39 @@ mod tests {
         assert_ne!(base, changed_target);
     }
 
+    #[test]
+    fn cache_key_parts_round_trip_through_the_encoded_key() {
+        let key = CacheKey::from_parts("a|b", "", "0.1.0", "linux-x86_64", "none");
+
+        assert_eq!(
+            CacheKey::parts(key.as_str()),
+            Some(vec!["a|b", "", "0.1.0", "linux-x86_64", "none"])
+        );
+        assert_eq!(CacheKey::parts("3:abc|x"), None);
+    }
+
+    #[test]
+    fn eviction_removes_least_recently_used_artifacts_first() {
+        let dir = std::env::temp_dir().join(format!("tonic-cache-lru-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        let now = SystemTime::now();
+        for (name, age) in [("old", 30), ("newest", 0), ("middle", 10)] {
+            let path = dir.join(format!("{name}.ir.json"));
+            std::fs::write(&path, "0123456789").unwrap();
+            let file = std::fs::File::options().write(true).open(&path).unwrap();
+            file.set_modified(now - Duration::from_secs(age)).unwrap();
+        }
+        std::fs::write(dir.join("notes.txt"), "kept").unwrap();
+
+        assert_eq!(evict_least_recently_used(&dir, 20).unwrap(), 1);
+        assert!(!dir.join("old.ir.json").exists());
+        assert_eq!(evict_least_recently_used(&dir, 0).unwrap(), 2);
+        assert!(dir.join("notes.txt").exists());
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
     #[test]
     fn compile_cache_reports_miss_then_hit_for_synthetic_key() {
         let mut cache = CompileCache::default();
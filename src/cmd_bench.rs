@@ -0,0 +1,220 @@
+use super::*;
+
+const BENCH_USAGE: &str = "usage: tonic bench <path> [--filter <pattern>] [--warmup <seconds>] [--time <seconds>] [--backend <interpreter|native>] [--save-baseline <path>] [--baseline <path>] [--max-regression <percent>]";
+
+pub(super) fn handle_bench(args: Vec<String>) -> i32 {
+    if matches!(args.first().map(String::as_str), Some("-h" | "--help")) {
+        print_bench_help();
+        return EXIT_OK;
+    }
+
+    if args.is_empty() {
+        return CliDiagnostic::usage_with_hint(
+            "missing required <path>",
+            "run `tonic bench --help` for usage",
+        )
+        .emit();
+    }
+
+    let source_path = args[0].clone();
+    let mut options = BenchOptions::default();
+    let mut save_baseline: Option<String> = None;
+    let mut baseline_path: Option<String> = None;
+    let mut max_regression: Option<f64> = None;
+    let mut index = 1;
+
+    while index < args.len() {
+        let flag = args[index].as_str();
+        if !matches!(
+            flag,
+            "--filter"
+                | "--warmup"
+                | "--time"
+                | "--backend"
+                | "--save-baseline"
+                | "--baseline"
+                | "--max-regression"
+        ) {
+            return CliDiagnostic::usage_with_hint(
+                format!("unexpected argument '{flag}'"),
+                BENCH_USAGE,
+            )
+            .emit();
+        }
+        let Some(value) = args.get(index + 1) else {
+            return CliDiagnostic::usage_with_hint(
+                format!("missing value for {flag}"),
+                BENCH_USAGE,
+            )
+            .emit();
+        };
+
+        match flag {
+            "--filter" => options.filter = Some(value.clone()),
+            "--warmup" | "--time" => {
+                let Some(seconds) = value
+                    .parse::<f64>()
+                    .ok()
+                    .filter(|seconds| seconds.is_finite() && *seconds >= 0.0)
+                else {
+                    return CliDiagnostic::usage_with_hint(
+                        format!(
+                            "invalid {flag} '{value}' (expected a non-negative number of seconds)"
+                        ),
+                        BENCH_USAGE,
+                    )
+                    .emit();
+                };
+                let duration = std::time::Duration::from_secs_f64(seconds);
+                if flag == "--warmup" {
+                    options.warmup = duration;
+                } else {
+                    options.time = duration;
+                }
+            }
+            "--backend" => {
+                options.backend = match TestBackend::parse(value) {
+                    Some(backend) if backend != TestBackend::Both => backend,
+                    _ => {
+                        return CliDiagnostic::usage_with_hint(
+                            format!("unsupported backend '{value}' (expected 'interpreter' or 'native')"),
+                            "valid backends: interpreter, native",
+                        )
+                        .emit();
+                    }
+                };
+            }
+            "--save-baseline" => save_baseline = Some(value.clone()),
+            "--baseline" => baseline_path = Some(value.clone()),
+            _ => {
+                let Some(percent) = value
+                    .parse::<f64>()
+                    .ok()
+                    .filter(|percent| percent.is_finite() && *percent >= 0.0)
+                else {
+                    return CliDiagnostic::usage_with_hint(
+                        format!("invalid --max-regression '{value}' (expected a non-negative percentage)"),
+                        BENCH_USAGE,
+                    )
+                    .emit();
+                };
+                max_regression = Some(percent);
+            }
+        }
+        index += 2;
+    }
+
+    if max_regression.is_some() && baseline_path.is_none() {
+        return CliDiagnostic::usage_with_hint(
+            "--max-regression requires --baseline <path>",
+            BENCH_USAGE,
+        )
+        .emit();
+    }
+
+    // Read the baseline up front so a bad path fails before the benchmarks run.
+    let baseline = match baseline_path.as_deref().map(std::path::Path::new) {
+        Some(path) => match Baseline::load(path) {
+            Ok(baseline) => Some((path, baseline)),
+            Err(error) => return CliDiagnostic::failure(error).emit(),
+        },
+        None => None,
+    };
+
+    println!("backend: {}", options.backend.name());
+    println!("warmup: {}", format_seconds(options.warmup));
+    println!("time: {}", format_seconds(options.time));
+    println!();
+
+    let report = match run_benchmarks(std::path::Path::new(&source_path), &options, |id| {
+        println!("Benchmarking {id} ...");
+    }) {
+        Ok(report) => report,
+        Err(TestRunnerError::Failure(message)) => return CliDiagnostic::failure(message).emit(),
+        Err(TestRunnerError::SourceDiagnostic {
+            message,
+            filename,
+            source,
+            offset,
+        }) => {
+            let source_path = filename.unwrap_or_else(|| source_path.clone());
+            return CliDiagnostic::failure_with_filename_and_source(
+                message,
+                Some(&source_path),
+                &source,
+                offset,
+            )
+            .emit();
+        }
+    };
+
+    if report.results.is_empty() {
+        println!("no benchmarks found in {source_path}");
+        return EXIT_OK;
+    }
+
+    println!();
+    for line in report.render_text() {
+        println!("{line}");
+    }
+
+    let mut regressions = Vec::new();
+    if let Some((path, baseline)) = &baseline {
+        if let Some(recorded) = baseline
+            .backend
+            .as_deref()
+            .filter(|recorded| *recorded != options.backend.name())
+        {
+            eprintln!(
+                "warning: baseline {} was recorded on the {recorded} backend",
+                path.display()
+            );
+        }
+        let comparison = report.compare(baseline);
+        println!();
+        for line in comparison.render_text(path, max_regression) {
+            println!("{line}");
+        }
+        if let Some(max) = max_regression {
+            regressions = comparison
+                .regressions(max)
+                .into_iter()
+                .map(|entry| entry.id.clone())
+                .collect();
+        }
+    }
+
+    if let Some(path) = &save_baseline {
+        let contents = format!(
+            "{}\n",
+            serde_json::to_string_pretty(&report.render_baseline())
+                .expect("baseline json should serialize")
+        );
+        if let Err(error) =
+            write_report_file(std::path::Path::new(path), &contents, "benchmark baseline")
+        {
+            return CliDiagnostic::failure(error).emit();
+        }
+        println!();
+        println!("baseline written to {path}");
+    }
+
+    let errors = report.errors();
+    if errors > 0 {
+        return CliDiagnostic::failure(format!(
+            "{errors} benchmark{} failed",
+            if errors == 1 { "" } else { "s" }
+        ))
+        .emit();
+    }
+    if !regressions.is_empty() {
+        return CliDiagnostic::failure(format!(
+            "{} regressed by more than {}% against the baseline",
+            regressions.join(", "),
+            max_regression.unwrap_or_default()
+        ))
+        .emit();
+    }
+
+    EXIT_OK
+}
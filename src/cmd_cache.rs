@@ -0,0 +1,372 @@
+use super::*;
+use cache::{CacheEntry, CacheEntryProblem};
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::time::{Duration, SystemTime};
+
+/// Set to `0` to keep `tonic run`'s IR cache in the project's `.tonic/cache`
+/// instead of the global cache under `$TONIC_HOME/cache`.
+const SHARED_CACHE_ENV: &str = "TONIC_SHARED_CACHE";
+
+/// Where `tonic run` caches compiled IR: the global cache under
+/// `$TONIC_HOME/cache`, shared across projects, unless `TONIC_SHARED_CACHE=0`
+/// selects the project's `.tonic/cache`. Both are bounded by the same LRU
+/// size limit.
+pub(super) fn run_cache_directory() -> Result<PathBuf, String> {
+    let shared = std::env::var_os(SHARED_CACHE_ENV).is_none_or(|value| value != "0");
+    if shared {
+        Ok(shared_cache_directory())
+    } else {
+        cache::project_cache_directory()
+    }
+}
+
+fn shared_cache_directory() -> PathBuf {
+    tonic_home().join("cache")
+}
+
+pub(super) fn handle_cache(args: Vec<String>) -> i32 {
+    let mut iter = args.into_iter();
+
+    match iter.next().as_deref() {
+        None | Some("-h") | Some("--help") => {
+            print_cache_help();
+            EXIT_OK
+        }
+        Some("stats") => handle_cache_stats(iter.collect()),
+        Some("clean") => handle_cache_clean(iter.collect()),
+        Some("verify") => handle_cache_verify(iter.collect()),
+        Some(other) => CliDiagnostic::usage_with_hint(
+            format!("unknown cache subcommand '{other}'"),
+            "run `tonic cache --help` for usage",
+        )
+        .emit(),
+    }
+}
+
+/// Options shared by the cache subcommands.
+#[derive(Default)]
+struct CacheOptions {
+    shared: bool,
+    project: bool,
+    older_than: Option<Duration>,
+    fix: bool,
+}
+
+/// Parses `args`, accepting `--shared` and `--project` plus the flags in
+/// `allowed`.
+fn parse_cache_options(args: &[String], allowed: &[&str]) -> Result<CacheOptions, i32> {
+    let mut options = CacheOptions::default();
+    let mut iter = args.iter();
+
+    while let Some(arg) = iter.next() {
+        match arg.as_str() {
+            "--shared" => options.shared = true,
+            "--project" => options.project = true,
+            "--fix" if allowed.contains(&"--fix") => options.fix = true,
+            "--older-than" if allowed.contains(&"--older-than") => {
+                let Some(value) = iter.next() else {
+                    return Err(CliDiagnostic::usage_with_hint(
+                        "--older-than requires a value",
+                        "use a number followed by s, m, h or d, e.g. `--older-than 7d`",
+                    )
+                    .emit());
+                };
+                let Some(age) = parse_age(value) else {
+                    return Err(CliDiagnostic::usage_with_hint(
+                        format!("invalid --older-than '{value}'"),
+                        "use a number followed by s, m, h or d, e.g. `--older-than 7d`",
+                    )
+                    .emit());
+                };
+                options.older_than = Some(age);
+            }
+            other => {
+                return Err(CliDiagnostic::usage_with_hint(
+                    format!("unexpected argument '{other}'"),
+                    "run `tonic cache --help` for usage",
+                )
+                .emit());
+            }
+        }
+    }
+
+    if options.shared && options.project {
+        return Err(CliDiagnostic::usage_with_hint(
+            "--shared and --project cannot be combined",
+            "pick the cache to use, or neither for the one `tonic run` uses",
+        )
+        .emit());
+    }
+    Ok(options)
+}
+
+fn selected_cache_directory(options: &CacheOptions) -> Result<PathBuf, i32> {
+    let selected = if options.shared {
+        Ok(shared_cache_directory())
+    } else if options.project {
+        cache::project_cache_directory()
+    } else {
+        run_cache_directory()
+    };
+    selected.map_err(|error| CliDiagnostic::failure(error).emit())
+}
+
+fn load_entries(cache_dir: &std::path::Path) -> Result<Vec<CacheEntry>, i32> {
+    cache::cache_entries(cache_dir).map_err(|error| CliDiagnostic::failure(error).emit())
+}
+
+fn handle_cache_stats(args: Vec<String>) -> i32 {
+    let options = match parse_cache_options(&args, &[]) {
+        Ok(options) => options,
+        Err(exit_code) => return exit_code,
+    };
+    let cache_dir = match selected_cache_directory(&options) {
+        Ok(cache_dir) => cache_dir,
+        Err(exit_code) => return exit_code,
+    };
+    let entries = match load_entries(&cache_dir) {
+        Ok(entries) => entries,
+        Err(exit_code) => return exit_code,
+    };
+    let counters = cache::load_cache_counters(&cache_dir);
+    let size = entries.iter().map(|entry| entry.size).sum::<u64>();
+
+    println!("cache: {}", cache_dir.display());
+    println!("entries: {}", entries.len());
+    println!(
+        "size: {} (limit {})",
+        format_size(size),
+        format_size(cache::cache_size_limit())
+    );
+    match (counters.hits * 100).checked_div(counters.hits + counters.misses) {
+        Some(percent) => println!("hits: {} ({percent}%)", counters.hits),
+        None => println!("hits: 0"),
+    }
+    println!("misses: {}", counters.misses);
+    println!("evictions: {}", counters.evictions);
+    if !options.shared {
+        let artifacts = native_artifacts();
+        let size = artifacts.iter().map(|artifact| artifact.size).sum::<u64>();
+        println!(
+            "native artifacts: {} ({})",
+            artifacts.len(),
+            format_size(size)
+        );
+    }
+    EXIT_OK
+}
+
+fn handle_cache_clean(args: Vec<String>) -> i32 {
+    let options = match parse_cache_options(&args, &["--older-than"]) {
+        Ok(options) => options,
+        Err(exit_code) => return exit_code,
+    };
+    let cache_dir = match selected_cache_directory(&options) {
+        Ok(cache_dir) => cache_dir,
+        Err(exit_code) => return exit_code,
+    };
+    let entries = match load_entries(&cache_dir) {
+        Ok(entries) => entries,
+        Err(exit_code) => return exit_code,
+    };
+
+    let now = SystemTime::now();
+    let expired = |modified: SystemTime| {
+        let age = now.duration_since(modified).unwrap_or_default();
+        options
+            .older_than
+            .is_none_or(|older_than| age >= older_than)
+    };
+    let (mut removed, mut freed) = (0, 0);
+    for entry in entries {
+        if !expired(entry.last_used) {
+            continue;
+        }
+        if let Err(exit_code) = remove_cache_file(&entry.path) {
+            return exit_code;
+        }
+        removed += 1;
+        freed += entry.size;
+    }
+
+    let mut summary = format!(
+        "cache clean: removed {removed} {} ({})",
+        plural(removed, "entry", "entries"),
+        format_size(freed)
+    );
+    if !options.shared {
+        let (mut removed, mut freed) = (0, 0);
+        for artifact in native_artifacts() {
+            if !expired(artifact.modified) {
+                continue;
+            }
+            for file in &artifact.files {
+                if let Err(exit_code) = remove_cache_file(file) {
+                    return exit_code;
+                }
+            }
+            removed += 1;
+            freed += artifact.size;
+        }
+        summary.push_str(&format!(
+            " and {removed} native {} ({})",
+            plural(removed, "artifact", "artifacts"),
+            format_size(freed)
+        ));
+    }
+    println!("{summary}");
+    EXIT_OK
+}
+
+fn remove_cache_file(path: &std::path::Path) -> Result<(), i32> {
+    std::fs::remove_file(path).map_err(|error| {
+        CliDiagnostic::failure(format!("failed to remove {}: {error}", path.display())).emit()
+    })
+}
+
+/// A native build output of the project: a `tonic compile` manifest with the
+/// sidecars beside it, or a `tonic test --backend native` harness with its C
+/// source.
+struct NativeArtifact {
+    files: Vec<PathBuf>,
+    size: u64,
+    modified: SystemTime,
+}
+
+fn native_artifacts() -> Vec<NativeArtifact> {
+    let compiled = native_artifact::native_artifact_manifests(&default_compile_build_dir())
+        .into_iter()
+        .map(|manifest| native_artifact::native_artifact_files(&manifest));
+
+    let cwd = std::env::current_dir().unwrap_or_default();
+    let mut harnesses = BTreeMap::<PathBuf, Vec<PathBuf>>::new();
+    if let Ok(entries) = std::fs::read_dir(test_runner::native_test_build_dir(&cwd)) {
+        for path in entries.flatten().map(|entry| entry.path()) {
+            if path.is_file() {
+                harnesses
+                    .entry(path.with_extension(""))
+                    .or_default()
+                    .push(path);
+            }
+        }
+    }
+
+    compiled
+        .chain(harnesses.into_values())
+        .map(|files| {
+            let metadata = files
+                .iter()
+                .filter_map(|file| std::fs::metadata(file).ok())
+                .collect::<Vec<_>>();
+            NativeArtifact {
+                size: metadata.iter().map(|metadata| metadata.len()).sum(),
+                modified: metadata
+                    .iter()
+                    .filter_map(|metadata| metadata.modified().ok())
+                    .max()
+                    .unwrap_or(SystemTime::UNIX_EPOCH),
+                files,
+            }
+        })
+        .collect()
+}
+
+fn handle_cache_verify(args: Vec<String>) -> i32 {
+    let options = match parse_cache_options(&args, &["--fix"]) {
+        Ok(options) => options,
+        Err(exit_code) => return exit_code,
+    };
+    let cache_dir = match selected_cache_directory(&options) {
+        Ok(cache_dir) => cache_dir,
+        Err(exit_code) => return exit_code,
+    };
+    let entries = match load_entries(&cache_dir) {
+        Ok(entries) => entries,
+        Err(exit_code) => return exit_code,
+    };
+
+    let (mut corrupt, mut stale) = (0, 0);
+    for entry in &entries {
+        let (kind, reason) = match cache::verify_cache_entry(&entry.path) {
+            Ok(()) => continue,
+            Err(CacheEntryProblem::Corrupt(reason)) => {
+                corrupt += 1;
+                ("corrupt", reason)
+            }
+            Err(CacheEntryProblem::Stale(reason)) => {
+                stale += 1;
+                ("stale", reason)
+            }
+        };
+        let file_name = entry
+            .path
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_default();
+        println!("{kind}: {file_name}: {reason}");
+        if options.fix {
+            if let Err(error) = std::fs::remove_file(&entry.path) {
+                return CliDiagnostic::failure(format!(
+                    "failed to remove {}: {error}",
+                    entry.path.display()
+                ))
+                .emit();
+            }
+        }
+    }
+
+    let mut summary = format!(
+        "cache verify: {} {}, {corrupt} corrupt, {stale} stale",
+        entries.len(),
+        plural(entries.len(), "entry", "entries")
+    );
+    let problems = corrupt + stale;
+    if options.fix && problems > 0 {
+        summary.push_str(&format!(", {problems} removed"));
+    }
+    println!("{summary}");
+    if problems == 0 || options.fix {
+        return EXIT_OK;
+    }
+    CliDiagnostic::failure(
+        "cache contains corrupt or stale entries; run `tonic cache verify --fix` to remove them",
+    )
+    .emit()
+}
+
+/// `30s`, `15m`, `12h` or `7d`.
+fn parse_age(value: &str) -> Option<Duration> {
+    let unit = value.chars().last()?;
+    let seconds_per_unit = match unit {
+        's' => 1,
+        'm' => 60,
+        'h' => 60 * 60,
+        'd' => 24 * 60 * 60,
+        _ => return None,
+    };
+    let count = value[..value.len() - 1].parse::<u64>().ok()?;
+    Some(Duration::from_secs(count.checked_mul(seconds_per_unit)?))
+}
+
+fn format_size(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["KiB", "MiB", "GiB", "TiB"];
+    if bytes < 1024 {
+        return format!("{bytes} B");
+    }
+    let mut size = bytes as f64 / 1024.0;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+    format!("{size:.1} {}", UNITS[unit])
+}
+
+fn plural<'a>(count: usize, singular: &'a str, plural: &'a str) -> &'a str {
+    if count == 1 {
+        singular
+    } else {
+        plural
+    }
+}
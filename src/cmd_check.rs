@@ -6,6 +6,10 @@ pub(super) fn handle_check(args: Vec<String>) -> i32 {
         return EXIT_OK;
     }
 
+    if let Some(exit_code) = run_workspace_members("check", &args, &[], handle_check) {
+        return exit_code;
+    }
+
     if args.is_empty() {
         return CliDiagnostic::usage_with_hint(
             "missing required <path>",
@@ -51,7 +55,9 @@ pub(super) fn handle_check(args: Vec<String>) -> i32 {
                     .emit();
                 };
 
-                let Some(parsed) = TestOutputFormat::parse(value) else {
+                let Some(parsed) = TestOutputFormat::parse(value).filter(|format| {
+                    matches!(format, TestOutputFormat::Text | TestOutputFormat::Json)
+                }) else {
                     return CliDiagnostic::usage_with_hint(
                         format!("unsupported format '{value}' (expected 'text' or 'json')"),
                         "valid formats: text, json",
@@ -99,13 +105,7 @@ pub(super) fn handle_check(args: Vec<String>) -> i32 {
     if let Some(observed_run) = observed_run.as_mut() {
         if dump_tokens {
             observed_run.record_metadata("dump_mode", "tokens");
-            observed_run.record_metadata(
-                "format",
-                match token_dump_format {
-                    TestOutputFormat::Text => "text",
-                    TestOutputFormat::Json => "json",
-                },
-            );
+            observed_run.record_metadata("format", token_dump_format.name());
         } else if dump_ast {
             observed_run.record_metadata("dump_mode", "ast");
         } else if dump_ir {
@@ -163,7 +163,7 @@ pub(super) fn handle_check(args: Vec<String>) -> i32 {
                     println!("{}", token.dump_label());
                 }
             }
-            TestOutputFormat::Json => {
+            _ => {
                 let records: Vec<_> = tokens.iter().map(|token| token.dump_record()).collect();
                 let json = match serde_json::to_string(&records) {
                     Ok(value) => value,
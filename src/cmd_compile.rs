@@ -6,6 +6,10 @@ pub(super) fn handle_compile(args: Vec<String>) -> i32 {
         return EXIT_OK;
     }
 
+    if let Some(exit_code) = run_workspace_members("compile", &args, &["--out"], handle_compile) {
+        return exit_code;
+    }
+
     if args.is_empty() {
         return CliDiagnostic::usage_with_hint(
             "missing required <path>",
@@ -17,6 +21,8 @@ pub(super) fn handle_compile(args: Vec<String>) -> i32 {
     let source_path = args[0].clone();
     let mut out_path = None;
     let mut target_triple = None;
+    let mut bin = None;
+    let mut all_bins = false;
     let mut idx = 1;
 
     while idx < args.len() {
@@ -33,6 +39,22 @@ pub(super) fn handle_compile(args: Vec<String>) -> i32 {
                 out_path = Some(args[idx].clone());
                 idx += 1;
             }
+            "--bin" => {
+                idx += 1;
+                if idx >= args.len() {
+                    return CliDiagnostic::usage_with_hint(
+                        "--bin requires a value",
+                        "usage: tonic compile <project-dir> --bin <name>",
+                    )
+                    .emit();
+                }
+                bin = Some(args[idx].clone());
+                idx += 1;
+            }
+            "--all-bins" => {
+                all_bins = true;
+                idx += 1;
+            }
             "--target" => {
                 idx += 1;
                 if idx >= args.len() {
@@ -65,6 +87,10 @@ pub(super) fn handle_compile(args: Vec<String>) -> i32 {
         }
     }
 
+    if all_bins {
+        return compile_all_bins(&source_path, &args[1..], bin.is_some(), out_path.is_some());
+    }
+
     let target = target_triple.unwrap_or_else(target::TargetTriple::host);
     let cwd = std::env::current_dir().unwrap_or_else(|_| std::path::PathBuf::from("."));
     let mut observed_run =
@@ -76,7 +102,7 @@ pub(super) fn handle_compile(args: Vec<String>) -> i32 {
         &mut profiler,
         &mut observed_run,
         "compile.load_source",
-        || load_run_source(&source_path),
+        || load_program_source(&source_path, bin.as_deref()),
     ) {
         Ok(source) => source,
         Err(error) => {
@@ -104,7 +130,10 @@ pub(super) fn handle_compile(args: Vec<String>) -> i32 {
         }
     };
 
-    let artifact_stem = compile_artifact_stem(&source_path, is_project_root_path);
+    let artifact_stem = match &bin {
+        Some(bin) => bin.clone(),
+        None => compile_artifact_stem(&source_path, is_project_root_path),
+    };
     let mir = match observe_phase_result(
         &mut profiler,
         &mut observed_run,
@@ -325,14 +354,65 @@ pub(super) fn handle_compile(args: Vec<String>) -> i32 {
     finalize_observed_run(&mut observed_run, EXIT_OK, None)
 }
 
+/// `tonic compile <project-dir> --all-bins`: one executable per `[[bin]]`,
+/// each compiled as `--bin <name>` with the remaining flags.
+fn compile_all_bins(source_path: &str, flags: &[String], has_bin: bool, has_out: bool) -> i32 {
+    if has_bin || has_out {
+        return CliDiagnostic::usage_with_hint(
+            format!(
+                "--all-bins cannot be combined with {}",
+                if has_bin { "--bin" } else { "--out" }
+            ),
+            "each binary is written to .tonic/build/<name>",
+        )
+        .emit();
+    }
+
+    let manifest = match manifest::load_project_manifest(std::path::Path::new(source_path)) {
+        Ok(manifest) => manifest,
+        Err(error) => return CliDiagnostic::failure(error).emit(),
+    };
+    if manifest.bins.is_empty() {
+        return CliDiagnostic::failure(format!(
+            "--all-bins: tonic.toml at {source_path} declares no [[bin]] targets"
+        ))
+        .emit();
+    }
+
+    let mut exit_code = EXIT_OK;
+    for bin in &manifest.bins {
+        let mut bin_args = vec![
+            source_path.to_string(),
+            "--bin".to_string(),
+            bin.name.clone(),
+        ];
+        bin_args.extend(flags.iter().filter(|flag| *flag != "--all-bins").cloned());
+        let bin_exit_code = handle_compile(bin_args);
+        if exit_code == EXIT_OK {
+            exit_code = bin_exit_code;
+        }
+    }
+    exit_code
+}
+
 pub(super) fn compile_artifact_stem(source_path: &str, is_project_root_path: bool) -> String {
     if is_project_root_path {
-        manifest::load_project_manifest(std::path::Path::new(source_path))
+        // Workspace members often share an entry name like `main.tn`.
+        let project_root = std::path::Path::new(source_path);
+        if let Ok(Some(workspace)) = manifest::Workspace::containing(project_root) {
+            if let Some(member) = workspace.member_at(project_root) {
+                return member.name.clone();
+            }
+        }
+        manifest::load_project_manifest(project_root)
             .ok()
-            .and_then(|m| {
-                m.entry
-                    .file_stem()
-                    .map(|s| s.to_string_lossy().into_owned())
+            .and_then(|m| match m.run_target(None) {
+                Ok(manifest::RunTarget::Bin(bin)) => Some(bin.name.clone()),
+                _ => m
+                    .entry
+                    .as_deref()
+                    .and_then(|entry| entry.file_stem())
+                    .map(|s| s.to_string_lossy().into_owned()),
             })
             .unwrap_or_else(|| "out".to_string())
     } else {
@@ -11,6 +11,10 @@ pub(super) fn handle_deps(args: Vec<String>) -> i32 {
 
     let subcommand = match args.first().map(String::as_str) {
         Some("sync") | Some("fetch") | Some("lock") => args[0].clone(),
+        Some("tree") => return handle_deps_tree(&args[1..]),
+        Some("why") => return handle_deps_why(&args[1..]),
+        Some("outdated") => return handle_deps_outdated(&args[1..]),
+        Some("update") => return handle_deps_update(&args[1..]),
         Some(other) => {
             return CliDiagnostic::usage_with_hint(
                 format!("unknown deps subcommand '{other}'"),
@@ -27,6 +31,25 @@ pub(super) fn handle_deps(args: Vec<String>) -> i32 {
         }
     };
 
+    let mut options = deps::SyncOptions::default();
+    for arg in &args[1..] {
+        match arg.as_str() {
+            "--locked" => options.locked = true,
+            "--offline" => options.offline = true,
+            "--frozen" => {
+                options.locked = true;
+                options.offline = true;
+            }
+            other => {
+                return CliDiagnostic::usage_with_hint(
+                    format!("unexpected argument '{other}'"),
+                    "usage: tonic deps <sync|fetch|lock> [--locked] [--offline] [--frozen]",
+                )
+                .emit();
+            }
+        }
+    }
+
     let cwd = std::env::current_dir().unwrap_or_else(|_| std::path::PathBuf::from("."));
     let mut observed_run = ObservabilityRun::from_env("deps", &command_argv("deps", &args), &cwd);
     if let Some(observed_run) = observed_run.as_mut() {
@@ -59,29 +82,29 @@ pub(super) fn handle_deps(args: Vec<String>) -> i32 {
         observed_run.set_target_path(project_root.display().to_string());
     }
 
-    let manifest =
-        match observe_command_phase_result(&mut observed_run, "deps.load_manifest", || {
-            manifest::load_project_manifest(&project_root)
-        }) {
-            Ok(manifest) => manifest,
-            Err(message) => {
-                let exit_code = CliDiagnostic::failure(message.clone()).emit();
-                return finalize_observed_run(
-                    &mut observed_run,
-                    exit_code,
-                    Some(make_observability_error(
-                        "io_error",
-                        "deps.load_manifest",
-                        message,
-                        None,
-                    )),
-                );
-            }
-        };
+    let scope = match observe_command_phase_result(&mut observed_run, "deps.load_manifest", || {
+        load_dependency_scope(&project_root)
+    }) {
+        Ok(scope) => scope,
+        Err(message) => {
+            let exit_code = CliDiagnostic::failure(message.clone()).emit();
+            return finalize_observed_run(
+                &mut observed_run,
+                exit_code,
+                Some(make_observability_error(
+                    "io_error",
+                    "deps.load_manifest",
+                    message,
+                    None,
+                )),
+            );
+        }
+    };
 
-    if manifest.dependencies.path.is_empty()
-        && manifest.dependencies.git.is_empty()
-        && manifest.dependencies.registry.is_empty()
+    let project_root = scope.root;
+    if scope.dependencies.path.is_empty()
+        && scope.dependencies.git.is_empty()
+        && scope.dependencies.registry.is_empty()
     {
         println!("No dependencies defined in tonic.toml");
         return finalize_observed_run(&mut observed_run, EXIT_OK, None);
@@ -91,7 +114,7 @@ pub(super) fn handle_deps(args: Vec<String>) -> i32 {
         "sync" | "fetch" => {
             println!("Syncing dependencies...");
             match observe_command_phase_result(&mut observed_run, "deps.sync", || {
-                deps::DependencyResolver::sync(&manifest.dependencies, &project_root)
+                deps::DependencyResolver::sync(&scope.dependencies, &project_root, options)
             }) {
                 Ok(lockfile) => {
                     if let Some(observed_run) = observed_run.as_mut() {
@@ -99,11 +122,21 @@ pub(super) fn handle_deps(args: Vec<String>) -> i32 {
                             .record_metadata("path_dependencies", lockfile.path_deps.len() as u64);
                         observed_run
                             .record_metadata("git_dependencies", lockfile.git_deps.len() as u64);
+                        observed_run.record_metadata(
+                            "registry_dependencies",
+                            lockfile.registry_deps.len() as u64,
+                        );
                     }
                     println!("Dependencies synced successfully.");
                     println!("Lockfile saved to tonic.lock");
                     println!("  - path dependencies: {}", lockfile.path_deps.len());
                     println!("  - git dependencies: {}", lockfile.git_deps.len());
+                    if !lockfile.registry_deps.is_empty() {
+                        println!(
+                            "  - registry dependencies: {}",
+                            lockfile.registry_deps.len()
+                        );
+                    }
                     finalize_observed_run(&mut observed_run, EXIT_OK, None)
                 }
                 Err(msg) => {
@@ -125,7 +158,14 @@ pub(super) fn handle_deps(args: Vec<String>) -> i32 {
         "lock" => {
             println!("Generating lockfile...");
             match observe_command_phase_result(&mut observed_run, "deps.lock", || {
-                deps::Lockfile::generate(&manifest.dependencies, &project_root)
+                deps::Lockfile::generate(&scope.dependencies, &project_root, options).and_then(
+                    |lockfile| {
+                        if options.locked {
+                            lockfile.ensure_unchanged(&project_root)?;
+                        }
+                        Ok(lockfile)
+                    },
+                )
             }) {
                 Ok(lockfile) => match lockfile.save(&project_root) {
                     Ok(()) => {
@@ -179,74 +219,105 @@ pub(super) fn find_project_root() -> Option<std::path::PathBuf> {
     }
 }
 
-pub(super) fn handle_publish(args: Vec<String>) -> i32 {
-    if matches!(args.first().map(String::as_str), Some("-h" | "--help")) {
-        print_publish_help();
-        return EXIT_OK;
-    }
-
-    let project_root = find_project_root();
-    if project_root.is_none() {
-        return CliDiagnostic::failure("no tonic.toml found in current directory or parents")
-            .emit();
-    }
-    let project_root = project_root.unwrap();
-
-    let manifest = match manifest::load_project_manifest(&project_root) {
-        Ok(m) => m,
-        Err(msg) => return CliDiagnostic::failure(msg).emit(),
-    };
+/// Where `tonic.lock` and `.tonic/deps` live for the project at
+/// `project_root` and the dependencies they lock. A workspace root and its
+/// members all share the workspace scope, which locks every member's
+/// dependencies together.
+pub(super) struct DependencyScope {
+    pub(super) root: std::path::PathBuf,
+    /// Root of the dependency graph in `deps tree` and `deps why`.
+    pub(super) name: String,
+    pub(super) dependencies: manifest::Dependencies,
+}
 
-    // Validate required package fields before attempting publish.
-    let package = match &manifest.package {
-        Some(p) => p,
-        None => {
-            return CliDiagnostic::failure(
-                "tonic.toml is missing a [package] section; add name, version, and description",
-            )
-            .emit();
-        }
+pub(super) fn load_dependency_scope(
+    project_root: &std::path::Path,
+) -> Result<DependencyScope, String> {
+    let workspace = match manifest::Workspace::load(project_root)? {
+        Some(workspace) => Some(workspace),
+        None => manifest::Workspace::containing(project_root)?,
     };
-
-    let mut missing_fields = Vec::new();
-    if package.name.is_none() {
-        missing_fields.push("name");
-    }
-    if package.version.is_none() {
-        missing_fields.push("version");
-    }
-    if package.description.is_none() {
-        missing_fields.push("description");
+    if let Some(workspace) = workspace {
+        return Ok(DependencyScope {
+            name: workspace.name(),
+            dependencies: workspace.dependencies()?,
+            root: workspace.root,
+        });
     }
 
-    if !missing_fields.is_empty() {
-        return CliDiagnostic::failure(format!(
-            "tonic.toml [package] is missing required fields: {}",
-            missing_fields.join(", ")
-        ))
-        .emit();
-    }
-
-    println!("Publishing to registry is not yet supported");
-    EXIT_OK
+    let manifest = manifest::load_project_manifest(project_root)?;
+    Ok(DependencyScope {
+        name: manifest.display_name(project_root),
+        dependencies: manifest.dependencies,
+        root: project_root.to_path_buf(),
+    })
 }
 
 pub(super) fn print_publish_help() {
     println!(
-        "tonic publish - Publish package to registry (not yet implemented)\n\n\
-         Usage:\n  tonic publish\n\n\
-         Requires tonic.toml [package] section with:\n  \
-         name, version, description\n\n\
-         Publishing to a registry is not yet supported.\n"
+        "Usage:\n  tonic publish [--dry-run] [--registry <url>] [--key <path>] [--token <token>]\n\n\
+         Packages the project and uploads it to a registry. Requires tonic.toml [package]\n\
+         with name, version and description; dependencies must all be registry packages.\n\n\
+         Options:\n\
+         \x20 --dry-run         List the packaged files and write the tarball without uploading\n\
+         \x20 --registry <url>  Registry to publish to (default: [registries] default)\n\
+         \x20 --key <path>      File holding the ed25519 signing key as 64 hex characters\n\
+         \x20                   (default: $TONIC_SIGNING_KEY)\n\
+         \x20 --token <token>   API token for http(s) registries (default: $TONIC_REGISTRY_TOKEN)\n\n\
+         Package contents:\n\
+         \x20 tonic.toml, top-level README/LICENSE/CHANGELOG files and all .tn sources outside\n\
+         \x20 hidden and target/ directories, minus [package] exclude = [\"path\", \"**/*_test.tn\"].\n\
+         \x20 The tarball is reproducible and written to .tonic/package/<name>-<version>.tar.gz;\n\
+         \x20 its sha256 checksum is signed with the key. On a file:// registry the first\n\
+         \x20 publisher's key becomes the package owner\n"
+    );
+}
+
+pub(super) fn print_yank_help() {
+    println!(
+        "Usage:\n  tonic yank <package> <version> [--undo] [--registry <url>] [--key <path>] [--token <token>]\n\n\
+         Marks a published version as yanked: new dependency resolutions skip it, while\n\
+         projects that already lock it keep working. --undo makes it available again.\n\
+         Registry options are as for `tonic publish`\n"
+    );
+}
+
+pub(super) fn print_owner_help() {
+    println!(
+        "Usage:\n  tonic owner list <package> [--registry <url>]\n  tonic owner add <package> <owner> [--registry <url>] [--key <path>] [--token <token>]\n  tonic owner remove <package> <owner> [--registry <url>] [--key <path>] [--token <token>]\n\n\
+         Owners may publish and yank versions of a package. Owners are identified by\n\
+         their ed25519 public key (printed by `tonic publish`); a package always keeps\n\
+         at least one owner. Registry options are as for `tonic publish`\n"
     );
 }
 
 pub(super) fn print_deps_help() {
     println!(
         "tonic deps - Manage project dependencies\n\n\
-         Usage:\n  tonic deps <SUBCOMMAND>\n\n\
-         Subcommands:\n  sync    Fetch all dependencies and generate lockfile\n  fetch   Alias for sync\n  lock    Generate lockfile without fetching\n\n\
-         Examples:\n  tonic deps sync    # Fetch dependencies and create tonic.lock\n  tonic deps lock    # Just create/update tonic.lock\n"
+         Usage:\n  tonic deps <SUBCOMMAND> [--locked] [--offline] [--frozen]\n\n\
+         Subcommands:\n\
+         \x20 sync              Fetch all dependencies and generate lockfile\n\
+         \x20 fetch             Alias for sync\n\
+         \x20 lock              Generate lockfile without fetching\n\
+         \x20 tree [--format <text|json>]\n\
+         \x20                   Show the locked dependency graph; (*) marks packages listed\n\
+         \x20                   earlier and [conflict: ...] requirements the lock misses\n\
+         \x20 why <name>        Show every chain of requirements that pulls in <name>\n\
+         \x20 outdated          List registry packages and git version tags with newer releases\n\
+         \x20 update [name...]  Re-resolve the named dependencies (default: all) and sync\n\n\
+         Options:\n\
+         \x20 --locked   Fail if tonic.lock is missing or would change\n\
+         \x20 --offline  Never touch the network; use tonic.lock and .tonic/deps only\n\
+         \x20 --frozen   Both --locked and --offline (reproducible CI builds)\n\n\
+         Integrity:\n\
+         \x20 Git dependencies are locked with a sha256 hash of their checked-out tree and\n\
+         \x20 every sync verifies it, so a force-pushed tag or an edited checkout is an error.\n\n\
+         Registry dependencies:\n\
+         \x20 `name = \"~> 1.0\"` entries are resolved, with their dependencies, against the\n\
+         \x20 [registries] default registry (http(s):// or file://) or the entry's `registry`.\n\
+         \x20 The highest stable versions that satisfy every requirement are locked with\n\
+         \x20 their sha256 checksums; versions already in tonic.lock are kept while they fit.\n\n\
+         Examples:\n  tonic deps sync    # Fetch dependencies and create tonic.lock\n  tonic deps lock    # Just create/update tonic.lock\n  tonic deps update json\n"
     );
 }
 
@@ -260,36 +331,201 @@ pub(super) fn benchmark_thresholds_report() -> serde_json::Value {
 
 pub(super) fn print_help() {
     println!(
-        "tonic language core v0\n\nUsage:\n  tonic <COMMAND> [OPTIONS]\n\nCommands:\n  run        Execute source\n  repl       Start interactive or remote REPL\n  check      Parse and type-check source\n  test       Run project tests\n  fmt        Format source files\n  compile    Compile source to executable artifact\n  cache      Manage compiled artifacts\n  verify     Run acceptance verification\n  deps       Manage project dependencies\n  install    Install a tonic module globally\n  uninstall  Remove an installed tonic module\n  installed  List installed tonic modules\n  docs       Generate API documentation\n  lsp        Start language server\n  publish    Publish package to registry (not yet implemented)\n"
+        "tonic language core v0\n\nUsage:\n  tonic <COMMAND> [OPTIONS]\n\nCommands:\n  new        Create a project from a template\n  run        Execute source\n  repl       Start interactive or remote REPL\n  check      Parse and type-check source\n  test       Run project tests\n  bench      Run benchmarks\n  task       Run a project task\n  fmt        Format source files\n  lint       Run static analysis rules\n  compile    Compile source to executable artifact\n  cache      Inspect, verify and clean the compile cache\n  verify     Run acceptance verification\n  deps       Manage project dependencies\n  install    Install a tonic module globally\n  uninstall  Remove an installed tonic module\n  installed  List installed tonic modules\n  docs       Generate API documentation\n  lsp        Start language server\n  dap        Start debug adapter (Debug Adapter Protocol)\n  publish    Publish package to registry\n  yank       Yank or restore a published package version\n  owner      Manage package owners on a registry\n"
+    );
+}
+
+pub(super) fn print_new_help() {
+    println!(
+        "Usage:\n  tonic new <name> [--template <template>]\n\n\
+         Creates the project <name> in ./<name> with tonic.toml, sources, a starter test,\n\
+         .gitignore and .tonic-formatter.toml. <name> must be a valid package name:\n\
+         lowercase letters, digits and underscores, starting with a letter.\n\n\
+         Options:\n\
+         \x20 --template <template>  A built-in template, a template directory or a git URL\n\
+         \x20                        (`<url>@<rev>` or `<url>#<branch>`)\n\n\
+         Templates:\n\
+         \x20 cli          A command-line tool: a library plus a [[bin]] target (default)\n\
+         \x20 lib          A library package with no binaries\n\
+         \x20 http-server  An HTTP server built on System.http_listen\n\
+         \x20 workspace    A workspace with an app under apps/ and a library under libs/\n\n\
+         In a template directory or repository, `{{{{name}}}}` and `{{{{module}}}}` in file names and\n\
+         contents become the package name and its module name (`my_app` -> `MyApp`).\n\
+         A project whose dependencies are all path dependencies gets its tonic.lock.\n"
     );
 }
 
 pub(super) fn print_run_help() {
-    println!("Usage:\n  tonic run <path>\n");
+    println!(
+        "Usage:\n  tonic run <path> [--bin <name>] [--profile] [--profile-dir <dir>] [--profile-top <number>]\n\n\
+         Options:\n\
+         \x20 --bin <name>         Run the [[bin]] target <name> of the project at <path>; needed\n\
+         \x20                      when tonic.toml declares several binaries and no project.entry\n\
+         \x20 --profile            Record per-function call counts, inclusive and exclusive time\n\
+         \x20                      and allocations; prints the slowest functions to stderr and\n\
+         \x20                      writes <stem>.folded (collapsed stacks for flamegraph tools)\n\
+         \x20                      and <stem>.trace.json (Chrome trace events)\n\
+         \x20 --profile-dir <dir>  Profile output directory (default: .tonic/profile; implies --profile)\n\
+         \x20 --profile-top <number>\n\
+         \x20                      Functions listed in the table (default: 20; implies --profile)\n\n\
+         Other arguments after <path> are passed to the program (see System.argv)\n"
+    );
 }
 
 pub(super) fn print_check_help() {
     println!(
-        "Usage:\n  tonic check <path> [--dump-tokens [--format <text|json>]|--dump-ast|--dump-ir|--dump-mir]\n"
+        "Usage:\n  tonic check <path> [--dump-tokens [--format <text|json>]|--dump-ast|--dump-ir|--dump-mir]\n\
+         \x20 tonic check [path] -p <name> [...]\n\n\
+         Workspaces:\n\
+         \x20 A workspace root <path> checks every member; -p, --package <name> selects one\n\
+         \x20 member of the workspace at or above <path> (default: current directory)\n"
     );
 }
 
 pub(super) fn print_test_help() {
-    println!("Usage:\n  tonic test <path> [--list] [--filter <pattern>] [--fail-fast] [--seed <number>] [--timeout <ms>] [--format <text|json>]\n\nOptions:\n  --list               List discovered tests without running them\n  --filter <pattern>   Only run tests whose name contains <pattern>\n  --fail-fast          Stop after the first test failure\n  --seed <number>      Randomize test order with a reproducible seed\n  --timeout <ms>       Per-test timeout in milliseconds\n  --format <text|json>  Output format (default: text)\n");
+    println!(
+        "Usage:\n  tonic test <path> [--list] [--filter <pattern>] [--only <tag[:value]>] [--exclude <tag[:value]>] [--failed] [--fail-fast] [--update-snapshots] [--record-cassettes] [--seed <number>] [--timeout <ms>] [--jobs <number>] [--backend <interpreter|native|both>] [--format <text|json|junit|tap>] [--report-file <path>] [--cover]\n\n\
+         Options:\n\
+         \x20 --list               List discovered tests without running them\n\
+         \x20 --filter <pattern>   Only run tests whose name contains <pattern>\n\
+         \x20 --only <tag[:value]>  Only run tests tagged with `@tag`/`@moduletag` (repeatable)\n\
+         \x20 --exclude <tag[:value]>\n\
+         \x20                      Skip tests with a matching tag (repeatable)\n\
+         \x20 --failed             Only run tests that failed in the previous run\n\
+         \x20 --fail-fast          Stop after the first test failure\n\
+         \x20 --update-snapshots   Overwrite `Assert.snapshot` files that no longer match and\n\
+         \x20                      remove obsolete ones\n\
+         \x20 --record-cassettes   Call the real host functions and re-record `Mock.cassette`\n\
+         \x20                      files instead of replaying them\n\
+         \x20 --seed <number>      Randomize test order with a reproducible seed\n\
+         \x20 --timeout <ms>       Per-test timeout in milliseconds\n\
+         \x20 --jobs <number>      Run test files and `@async true` tests on up to <number> threads\n\
+         \x20                      (default: CPU count); output is captured per test\n\
+         \x20 --backend <interpreter|native|both>\n\
+         \x20                      Run test functions in the interpreter (default), in a\n\
+         \x20                      binary compiled through the C backend, or in both,\n\
+         \x20                      failing cases whose status or stdout differs\n\
+         \x20 --format <text|json|junit|tap>\n\
+         \x20                      Output format (default: text); junit is JUnit XML, tap is TAP 13\n\
+         \x20 --report-file <path>  Write the --format report to <path> and print the text\n\
+         \x20                      summary to the console\n\n\
+         Coverage:\n\
+         \x20 --cover              Report line and function coverage per module\n\
+         \x20 --cover-format <text|lcov|cobertura>\n\
+         \x20                      Also write an lcov or Cobertura XML report (implies --cover)\n\
+         \x20 --cover-output <path>  Report path (default: .tonic/coverage/lcov.info or cobertura.xml)\n\
+         \x20 --cover-min <percent>  Fail when line coverage is below <percent> (implies --cover)\n\
+         \x20 --cover-stdlib       Include injected stdlib modules in the report\n\n\
+         Test blocks:\n\
+         \x20 `describe \"group\" do ... end` groups `test \"name\" do ... end` blocks;\n\
+         \x20 `setup_all` runs once per module and `setup` before each test, and both\n\
+         \x20 may return :ok, a map or ok(map) to extend the context (`test \"name\", ctx do`)\n\n\
+         Doctests:\n\
+         \x20 Add `doctest Module` to a test module to run the `tonic>` examples in\n\
+         \x20 Module's @moduledoc and @doc strings as tests; Module may be defined in the\n\
+         \x20 test file or in the project's library\n\n\
+         Property tests:\n\
+         \x20 `check all x <- PropertyTest.integer(), max_runs: 100 do ... end` runs the body\n\
+         \x20 with generated values and shrinks a failure to a minimal counterexample;\n\
+         \x20 pass the reported --seed to reproduce it\n\n\
+         Assertion failures:\n\
+         \x20 `Assert.assert_equal` on maps, lists, tuples and strings prints a diff\n\
+         \x20 (- left, + right; also under \"diff\" in --format json);\n\
+         \x20 `Assert.assert(match?(pattern, value))` reports the sub-pattern that failed\n\n\
+         Snapshots:\n\
+         \x20 `Assert.snapshot(value, \"name\")` writes the value (strings as-is, other values\n\
+         \x20 pretty-printed) to __snapshots__/<test file>/name.snap on first run and compares\n\
+         \x20 against it later; snapshot files no test used are reported as obsolete\n\n\
+         Mocks:\n\
+         \x20 `Mock.stub(:uuid_v4, fn _args -> \"id-1\" end)` replaces a host function until the\n\
+         \x20 test ends; `Mock.cassette(\"api\", [:sys_http_request])` replays those calls from\n\
+         \x20 __cassettes__/<test file>/api.json, recording them on the first run\n\n\
+         Backends:\n\
+         \x20 --backend native builds each test file into .tonic/build/test/ and runs its test,\n\
+         \x20 setup and teardown functions there; setup_all still runs in the interpreter.\n\
+         \x20 Assert.snapshot, Mock and --cover need the interpreter\n\n\
+         Workspaces:\n\
+         \x20 A workspace root <path> tests every member; -p, --package <name> selects one\n\
+         \x20 member of the workspace at or above <path> (default: current directory);\n\
+         \x20 --report-file and --cover-output need a single member\n"
+    );
+}
+
+pub(super) fn print_bench_help() {
+    println!(
+        "Usage:\n  tonic bench <path> [--filter <pattern>] [--warmup <seconds>] [--time <seconds>] [--backend <interpreter|native>] [--save-baseline <path>] [--baseline <path>] [--max-regression <percent>]\n\n\
+         Options:\n\
+         \x20 --filter <pattern>   Only run benchmarks whose name contains <pattern>\n\
+         \x20 --warmup <seconds>   Run each benchmark this long before measuring (default: 2)\n\
+         \x20 --time <seconds>     Measure each benchmark for this long (default: 5)\n\
+         \x20 --backend <interpreter|native>\n\
+         \x20                      Run benchmarks in the interpreter (default) or in a binary\n\
+         \x20                      compiled through the C backend\n\
+         \x20 --save-baseline <path>\n\
+         \x20                      Write the results as JSON to <path>\n\
+         \x20 --baseline <path>    Compare average times against a saved baseline\n\
+         \x20 --max-regression <percent>\n\
+         \x20                      Fail when a benchmark is more than <percent> slower than\n\
+         \x20                      the baseline (requires --baseline)\n\n\
+         Benchmarks:\n\
+         \x20 Public zero-argument `bench_*` functions in `*_bench.tn` files are benchmarks.\n\
+         \x20 Each one runs in batches of at least 1 ms; the table reports iterations per\n\
+         \x20 second, the average, standard deviation, median and 99th percentile of the\n\
+         \x20 per-iteration time. Pass constant inputs through `Bench.black_box(value)` so\n\
+         \x20 the native backend cannot fold the work away at compile time\n"
+    );
+}
+
+pub(super) fn print_task_help() {
+    println!(
+        "Usage:\n  tonic task <name> [args...]\n  tonic task --list\n\n\
+         Options:\n\
+         \x20 --list    List the project's tasks and those of its dependencies\n\n\
+         Tasks:\n\
+         \x20 A module with a public `run(argv)` in a file under tasks/ is a task: `Tasks.Db.Seed`\n\
+         \x20 runs as `tonic task db.seed`. `[tasks]` in tonic.toml names others, e.g.\n\
+         \x20 `release = \"scripts/release.tn\"`. argv is the list of arguments after <name>;\n\
+         \x20 parse it with `CLI.parse(CLI.spec(...), argv)`. `@shortdoc` is the one-line\n\
+         \x20 description --list shows. Tasks run from the project directory with its library\n\
+         \x20 modules and dependencies loaded; returning `err(reason)` or `{{:error, reason}}`\n\
+         \x20 fails the command\n"
+    );
 }
 
 pub(super) fn print_fmt_help() {
-    println!("Usage:\n  tonic fmt <path> [--check]\n");
+    println!(
+        "Usage:\n  tonic fmt <path|-> [--check] [--diff] [--range <start>:<end>]\n\n\
+         Options:\n\
+         \x20 -             Read source from stdin and write the result to stdout\n\
+         \x20 --check       Fail when any file needs formatting\n\
+         \x20 --diff        Print a unified diff instead of writing; fail when anything changes\n\
+         \x20 --range S:E   Only apply changes touching lines S through E (single file or stdin)\n\n\
+         Settings come from the nearest .tonic-formatter.toml, or the [format] section\n\
+         of the nearest tonic.toml: line_length, locals_without_parens (when set, other\n\
+         paren-less local calls gain parentheses), import_deps (dependencies whose\n\
+         locals_without_parens are added), trailing_comma (\"never\"|\"multiline\"),\n\
+         heredocs and sigils (\"preserve\"|\"normalize\").\n\n\
+         Workspaces:\n\
+         \x20 A workspace root <path> formats every member; -p, --package <name> selects one\n\
+         \x20 member of the workspace at or above <path> (default: current directory)\n"
+    );
 }
 
 pub(super) fn print_compile_help() {
     println!(
-        "Usage:\n  tonic compile <path> [--out <artifact-path>] [--target <triple>]\n\n\
+        "Usage:\n  tonic compile <path> [--out <artifact-path>] [--target <triple>]\n\
+         \x20 tonic compile <project-dir> [--bin <name>|--all-bins] [--target <triple>]\n\n\
          Compile contract:\n\
          \x20 Compile always produces a native executable artifact (ELF on Linux, Mach-O on macOS).\n\
          \x20 Default output: .tonic/build/<name>  (runnable as ./.tonic/build/<name>)\n\
          \x20 --out <path>       Write executable to <path> directly\n\
-         \x20 --target <triple>  Cross-compile for the given target triple (default: host)\n\n\
+         \x20 --target <triple>  Cross-compile for the given target triple (default: host)\n\
+         \x20 --bin <name>       Compile the [[bin]] target <name> to .tonic/build/<name>\n\
+         \x20 --all-bins         Compile every [[bin]] target, one executable each\n\n\
+         Workspaces:\n\
+         \x20 A workspace root <path> compiles every member; -p, --package <name> selects one\n\
+         \x20 member of the workspace at or above <path> (default: current directory);\n\
+         \x20 member executables are named after the member and --out needs a single member\n\n\
          Supported targets:\n\
          \x20 x86_64-unknown-linux-gnu    (default on x86_64 Linux)\n\
          \x20 aarch64-unknown-linux-gnu   (ARM64 Linux; requires aarch64-linux-gnu-gcc or clang)\n\
@@ -304,6 +540,31 @@ pub(super) fn print_compile_help() {
     );
 }
 
+pub(super) fn print_cache_help() {
+    println!(
+        "Usage:\n  tonic cache <stats|clean|verify> [--shared|--project]\n\n\
+         Subcommands:\n\
+         \x20 stats                     Entries, size and the hit/miss/eviction counters of `tonic run`,\n\
+         \x20                           plus the project's native artifacts\n\
+         \x20 clean [--older-than <age>]\n\
+         \x20                           Remove every entry and native artifact, or those unused for <age>\n\
+         \x20                           (30s, 15m, 12h, 7d)\n\
+         \x20 verify [--fix]            Report corrupt ir.json entries and stale ones written by another\n\
+         \x20                           tonic version or target; --fix removes them\n\n\
+         Options:\n\
+         \x20 --shared   Use the global cache in $TONIC_HOME/cache\n\
+         \x20 --project  Use the project's .tonic/cache\n\
+         \x20 Without either, the cache `tonic run` uses is selected\n\n\
+         Location:\n\
+         \x20 `tonic run` caches compiled IR in the global $TONIC_HOME/cache (default ~/.tonic/cache),\n\
+         \x20 shared across projects. Set TONIC_SHARED_CACHE=0 to use the project's .tonic/cache\n\
+         \x20 instead. After each store the least recently used entries are evicted while the cache\n\
+         \x20 is larger than TONIC_CACHE_MAX_MB megabytes (default 512). Native artifacts are the\n\
+         \x20 `tonic compile` outputs in .tonic/build and the `tonic test --backend native`\n\
+         \x20 harnesses in .tonic/build/test\n"
+    );
+}
+
 pub(super) fn print_verify_help() {
     println!("Usage:\n  tonic verify run <slice-id> [--mode <auto|mixed|manual>]\n");
 }
@@ -0,0 +1,308 @@
+use super::*;
+use deps::{DependencyGraph, DependencyKind, Lockfile, TreeNode};
+use std::collections::BTreeMap;
+
+const TREE_USAGE: &str = "usage: tonic deps tree [--format <text|json>]";
+const WHY_USAGE: &str = "usage: tonic deps why <name>";
+
+/// The dependency scope and its lockfile; `deps tree`, `why` and `outdated`
+/// only read what `tonic.lock` already records.
+fn load_locked_project() -> Result<(DependencyScope, Lockfile), i32> {
+    let Some(project_root) = find_project_root() else {
+        return Err(
+            CliDiagnostic::failure("no tonic.toml found in current directory or parents").emit(),
+        );
+    };
+    let scope = load_dependency_scope(&project_root)
+        .map_err(|message| CliDiagnostic::failure(message).emit())?;
+    let lockfile = match Lockfile::load(&scope.root) {
+        Ok(Some(lockfile)) => lockfile,
+        Ok(None) => {
+            return Err(CliDiagnostic::usage_with_hint(
+                "tonic.lock not found",
+                "run `tonic deps lock` or `tonic deps sync` first",
+            )
+            .emit());
+        }
+        Err(message) => return Err(CliDiagnostic::failure(message).emit()),
+    };
+    Ok((scope, lockfile))
+}
+
+fn load_graph() -> Result<DependencyGraph, i32> {
+    let (scope, lockfile) = load_locked_project()?;
+    Ok(DependencyGraph::build(
+        &scope.name,
+        &scope.dependencies,
+        &lockfile,
+    ))
+}
+
+pub(super) fn handle_deps_tree(args: &[String]) -> i32 {
+    let mut json = false;
+    let mut index = 0;
+    while index < args.len() {
+        match args[index].as_str() {
+            "--format" => {
+                match args.get(index + 1).map(String::as_str) {
+                    Some("text") => json = false,
+                    Some("json") => json = true,
+                    Some(other) => {
+                        return CliDiagnostic::usage_with_hint(
+                            format!("unsupported format '{other}' (expected 'text' or 'json')"),
+                            TREE_USAGE,
+                        )
+                        .emit();
+                    }
+                    None => {
+                        return CliDiagnostic::usage_with_hint(
+                            "missing value for --format",
+                            TREE_USAGE,
+                        )
+                        .emit();
+                    }
+                }
+                index += 2;
+            }
+            other => {
+                return CliDiagnostic::usage_with_hint(
+                    format!("unexpected argument '{other}'"),
+                    TREE_USAGE,
+                )
+                .emit();
+            }
+        }
+    }
+
+    let graph = match load_graph() {
+        Ok(graph) => graph,
+        Err(exit_code) => return exit_code,
+    };
+    let tree = graph.tree();
+
+    if json {
+        let report = serde_json::json!({
+            "name": graph.root,
+            "dependencies": tree,
+        });
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&report).unwrap_or_else(|_| "{}".to_string())
+        );
+        return EXIT_OK;
+    }
+
+    println!("{}", graph.root);
+    let mut duplicates = false;
+    print_tree_nodes(&tree, "", &mut duplicates);
+    if duplicates {
+        println!();
+        println!("(*) listed above; its dependencies are not repeated");
+    }
+    EXIT_OK
+}
+
+fn print_tree_nodes(nodes: &[TreeNode], prefix: &str, duplicates: &mut bool) {
+    for (index, node) in nodes.iter().enumerate() {
+        let last = index + 1 == nodes.len();
+        let mut line = format!(
+            "{prefix}{}{}",
+            if last { "└── " } else { "├── " },
+            describe_node(node)
+        );
+        if node.duplicate {
+            *duplicates = true;
+            line.push_str(" (*)");
+        }
+        if let Some(conflict) = &node.conflict {
+            line.push_str(&format!(" [conflict: {conflict}]"));
+        }
+        println!("{line}");
+        let child_prefix = format!("{prefix}{}", if last { "    " } else { "│   " });
+        print_tree_nodes(&node.dependencies, &child_prefix, duplicates);
+    }
+}
+
+fn describe_node(node: &TreeNode) -> String {
+    let requirement = node
+        .requirement
+        .as_deref()
+        .map(|requirement| format!(" ({requirement})"))
+        .unwrap_or_default();
+    match (node.kind, node.locked.as_deref()) {
+        (Some(DependencyKind::Registry), Some(version)) => {
+            format!("{} {version}{requirement}", node.name)
+        }
+        (Some(DependencyKind::Git), Some(rev)) => format!(
+            "{} {rev} (git: {})",
+            node.name,
+            node.source.as_deref().unwrap_or("?")
+        ),
+        (Some(DependencyKind::Path), Some(path)) => format!("{} (path: {path})", node.name),
+        _ => format!("{}{requirement}", node.name),
+    }
+}
+
+pub(super) fn handle_deps_why(args: &[String]) -> i32 {
+    let [name] = args else {
+        return CliDiagnostic::usage_with_hint("expected a dependency name", WHY_USAGE).emit();
+    };
+
+    let graph = match load_graph() {
+        Ok(graph) => graph,
+        Err(exit_code) => return exit_code,
+    };
+    let paths = graph.paths_to(name);
+    if paths.is_empty() {
+        return CliDiagnostic::failure(format!("'{name}' is not a dependency of {}", graph.root))
+            .emit();
+    }
+
+    match graph.packages.get(name.as_str()) {
+        Some(package) => println!("{name} {}", package.locked),
+        None => println!("{name} (not in tonic.lock)"),
+    }
+    for path in paths {
+        let steps = path
+            .iter()
+            .map(|edge| match &edge.requirement {
+                Some(requirement) => format!("{} {requirement}", edge.name),
+                None => edge.name.clone(),
+            })
+            .collect::<Vec<_>>();
+        println!("  {} -> {}", graph.root, steps.join(" -> "));
+    }
+    EXIT_OK
+}
+
+pub(super) fn handle_deps_outdated(args: &[String]) -> i32 {
+    if let Some(extra) = args.first() {
+        return CliDiagnostic::usage_with_hint(
+            format!("unexpected argument '{extra}'"),
+            "usage: tonic deps outdated",
+        )
+        .emit();
+    }
+
+    let graph = match load_graph() {
+        Ok(graph) => graph,
+        Err(exit_code) => return exit_code,
+    };
+    let outdated = match graph.outdated() {
+        Ok(outdated) => outdated,
+        Err(message) => {
+            return CliDiagnostic::failure(format!("failed to check for updates: {message}")).emit()
+        }
+    };
+
+    if outdated.is_empty() {
+        println!("All dependencies are up to date.");
+        return EXIT_OK;
+    }
+
+    println!(
+        "{:<20} {:<10} {:<12} {:<12} LATEST",
+        "NAME", "SOURCE", "LOCKED", "COMPATIBLE"
+    );
+    for entry in outdated {
+        println!(
+            "{:<20} {:<10} {:<12} {:<12} {}",
+            entry.name,
+            match entry.kind {
+                DependencyKind::Registry => "registry",
+                DependencyKind::Git => "git",
+                DependencyKind::Path => "path",
+            },
+            entry.locked,
+            entry.compatible.as_deref().unwrap_or("-"),
+            entry.latest
+        );
+    }
+    println!();
+    println!("Run `tonic deps update [name]` to move registry packages to their newest compatible");
+    println!("versions; git dependencies move when their rev in tonic.toml changes.");
+    EXIT_OK
+}
+
+pub(super) fn handle_deps_update(args: &[String]) -> i32 {
+    if let Some(flag) = args.iter().find(|arg| arg.starts_with('-')) {
+        return CliDiagnostic::usage_with_hint(
+            format!("unexpected argument '{flag}'"),
+            "usage: tonic deps update [name...]",
+        )
+        .emit();
+    }
+
+    let Some(project_root) = find_project_root() else {
+        return CliDiagnostic::failure("no tonic.toml found in current directory or parents")
+            .emit();
+    };
+    let scope = match load_dependency_scope(&project_root) {
+        Ok(scope) => scope,
+        Err(message) => return CliDiagnostic::failure(message).emit(),
+    };
+
+    println!("Updating dependencies...");
+    let (previous, lockfile) =
+        match deps::DependencyResolver::update(&scope.dependencies, &scope.root, args) {
+            Ok(result) => result,
+            Err(message) => {
+                return CliDiagnostic::failure(format!("failed to update dependencies: {message}"))
+                    .emit()
+            }
+        };
+
+    let changes = lock_changes(previous.as_ref(), &lockfile);
+    if changes.is_empty() {
+        println!("Dependencies are already up to date.");
+    } else {
+        for change in changes {
+            println!("  {change}");
+        }
+    }
+    println!("Lockfile saved to tonic.lock");
+    EXIT_OK
+}
+
+/// One line per package whose locked version, rev or contents changed.
+fn lock_changes(previous: Option<&Lockfile>, current: &Lockfile) -> Vec<String> {
+    let mut changes = Vec::new();
+    let empty = BTreeMap::new();
+    let previous_registry = previous.map_or(&empty, |lockfile| &lockfile.registry_deps);
+    for (name, lock) in &current.registry_deps {
+        match previous_registry.get(name) {
+            Some(old) if old.version != lock.version => changes.push(format!(
+                "Updating {name} {} -> {}",
+                old.version, lock.version
+            )),
+            Some(_) => {}
+            None => changes.push(format!("Adding {name} {}", lock.version)),
+        }
+    }
+    for (name, old) in previous_registry {
+        if !current.registry_deps.contains_key(name) {
+            changes.push(format!("Removing {name} {}", old.version));
+        }
+    }
+
+    let empty = BTreeMap::new();
+    let previous_git = previous.map_or(&empty, |lockfile| &lockfile.git_deps);
+    for (name, lock) in &current.git_deps {
+        match previous_git.get(name) {
+            Some(old) if old.rev != lock.rev => {
+                changes.push(format!("Updating {name} {} -> {}", old.rev, lock.rev))
+            }
+            Some(old) if old.hash.is_some() && old.hash != lock.hash => {
+                changes.push(format!("Updating {name} {} (new contents)", lock.rev))
+            }
+            Some(_) => {}
+            None => changes.push(format!("Adding {name} {}", lock.rev)),
+        }
+    }
+    for (name, old) in previous_git {
+        if !current.git_deps.contains_key(name) {
+            changes.push(format!("Removing {name} {}", old.rev));
+        }
+    }
+    changes
+}
@@ -27,6 +27,8 @@ struct PackageEntry {
     #[serde(rename = "ref", skip_serializing_if = "Option::is_none")]
     git_ref: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
+    branch: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     commit: Option<String>,
     bins: Vec<String>,
     installed_at: String,
@@ -36,7 +38,7 @@ struct PackageEntry {
 // Paths
 // ---------------------------------------------------------------------------
 
-fn tonic_home() -> PathBuf {
+pub(super) fn tonic_home() -> PathBuf {
     if let Some(val) = std::env::var_os("TONIC_HOME") {
         return PathBuf::from(val);
     }
@@ -57,6 +59,31 @@ fn packages_dir() -> PathBuf {
     tonic_home().join("packages")
 }
 
+/// Where package `name` is installed. The name comes from a project's
+/// `tonic.toml` or a git URL, so it must not escape `packages/`.
+fn package_destination(name: &str) -> Result<PathBuf, String> {
+    validate_install_name("package", name)?;
+    let packages = packages_dir();
+    let destination = packages.join(name);
+    if destination.parent() != Some(packages.as_path()) {
+        return Err(format!(
+            "package '{name}' would be installed outside {}",
+            packages.display()
+        ));
+    }
+    Ok(destination)
+}
+
+/// Rejects package and binary names that are not a single path component.
+fn validate_install_name(kind: &str, name: &str) -> Result<(), String> {
+    if name.is_empty() || name.starts_with('.') || name.contains(['/', '\\', '\0']) {
+        return Err(format!(
+            "invalid {kind} name '{name}': it must not be empty, start with '.' or contain path separators"
+        ));
+    }
+    Ok(())
+}
+
 fn packages_toml_path() -> PathBuf {
     tonic_home().join("packages.toml")
 }
@@ -88,6 +115,7 @@ pub(super) fn handle_install(args: Vec<String>) -> i32 {
     let mut source_arg: Option<String> = None;
     let mut copy = false;
     let mut force = false;
+    let mut upgrade = false;
     let iter = args.iter();
 
     for arg in iter {
@@ -98,6 +126,7 @@ pub(super) fn handle_install(args: Vec<String>) -> i32 {
             }
             "--copy" => copy = true,
             "--force" => force = true,
+            "--upgrade" => upgrade = true,
             other if !other.starts_with('-') => {
                 if source_arg.is_some() {
                     return CliDiagnostic::usage_with_hint(
@@ -118,6 +147,10 @@ pub(super) fn handle_install(args: Vec<String>) -> i32 {
         }
     }
 
+    if upgrade {
+        return upgrade_packages(source_arg.as_deref(), force);
+    }
+
     let source = match source_arg {
         Some(s) => s,
         None => {
@@ -130,19 +163,16 @@ pub(super) fn handle_install(args: Vec<String>) -> i32 {
     };
 
     // Determine source type
-    if source.starts_with("http://")
-        || source.starts_with("https://")
-        || source.starts_with("git://")
-        || source.ends_with(".git")
-    {
-        return install_git(&source, force);
+    if let Some(git_source) = GitSource::parse(&source) {
+        return install_git(&git_source, force);
     }
 
-    // Check if it looks like a bare name (registry)
+    // A bare name is neither a path nor a git URL; packages are only
+    // installed from those two sources.
     let path = Path::new(&source);
     if !path.exists() && !source.contains('/') && !source.starts_with('.') {
         return CliDiagnostic::failure_with_hint(
-            "registry install not yet supported; use a path or git URL".to_string(),
+            format!("'{source}' is not a local path or git URL"),
             format!("try: tonic install ./{source}  (for a local path)"),
         );
     }
@@ -201,31 +231,17 @@ fn install_local_path(source: &str, copy: bool, force: bool) -> i32 {
     // Check binary name conflicts
     let is_reinstall = manifest.packages.contains_key(&pkg_name);
     if !force {
-        for bin_name in &bins {
-            for (other_pkg, entry) in &manifest.packages {
-                if other_pkg == &pkg_name {
-                    continue; // Reinstall of same package is fine
-                }
-                if entry.bins.contains(bin_name) {
-                    return CliDiagnostic::failure_with_hint(
-                        format!("binary '{bin_name}' already installed by package '{other_pkg}'"),
-                        "use --force to overwrite",
-                    );
-                }
-            }
+        if let Err(exit_code) = check_bin_conflicts(&manifest, &pkg_name, &bins) {
+            return exit_code;
         }
     }
 
     // Symlink or copy into ~/.tonic/packages/<name>/
-    let pkg_dest = packages_dir().join(&pkg_name);
-    if pkg_dest.exists() || pkg_dest.read_link().is_ok() {
-        // Remove existing (reinstall)
-        if pkg_dest.read_link().is_ok() {
-            let _ = std::fs::remove_file(&pkg_dest);
-        } else {
-            let _ = std::fs::remove_dir_all(&pkg_dest);
-        }
-    }
+    let pkg_dest = match package_destination(&pkg_name) {
+        Ok(pkg_dest) => pkg_dest,
+        Err(msg) => return CliDiagnostic::failure(msg).emit(),
+    };
+    remove_package_dir(&pkg_dest);
 
     if copy {
         if let Err(e) = copy_dir_recursive(&abs_path, &pkg_dest) {
@@ -264,6 +280,7 @@ fn install_local_path(source: &str, copy: bool, force: bool) -> i32 {
             url: None,
             symlink: Some(!copy),
             git_ref: None,
+            branch: None,
             commit: None,
             bins: bins.clone(),
             installed_at: now,
@@ -295,10 +312,339 @@ fn install_local_path(source: &str, copy: bool, force: bool) -> i32 {
     EXIT_OK
 }
 
-fn install_git(_url: &str, _force: bool) -> i32 {
-    // Git install will be implemented in Slice 3
-    CliDiagnostic::failure("git URL install is not yet implemented; use a local path for now")
-        .emit()
+/// Fails when another installed package already owns one of `bins`.
+fn check_bin_conflicts(
+    manifest: &PackagesManifest,
+    pkg_name: &str,
+    bins: &[String],
+) -> Result<(), i32> {
+    for bin_name in bins {
+        for (other_pkg, entry) in &manifest.packages {
+            if other_pkg == pkg_name {
+                continue; // Reinstall of same package is fine
+            }
+            if entry.bins.contains(bin_name) {
+                return Err(CliDiagnostic::failure_with_hint(
+                    format!("binary '{bin_name}' already installed by package '{other_pkg}'"),
+                    "use --force to overwrite",
+                ));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Removes an installed package, whether it is a symlink or a directory.
+fn remove_package_dir(pkg_path: &Path) {
+    if pkg_path.read_link().is_ok() {
+        let _ = std::fs::remove_file(pkg_path);
+    } else if pkg_path.exists() {
+        let _ = std::fs::remove_dir_all(pkg_path);
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Git install
+// ---------------------------------------------------------------------------
+
+/// A git install source: `<url>`, `<url>@<rev>` or `<url>#<branch>`.
+#[derive(Debug, Clone, PartialEq)]
+pub(super) struct GitSource {
+    pub(super) url: String,
+    rev: Option<String>,
+    branch: Option<String>,
+}
+
+impl GitSource {
+    /// Returns `None` when `source` does not look like a git URL.
+    pub(super) fn parse(source: &str) -> Option<Self> {
+        let (url, rev, branch) = match source.rsplit_once('#') {
+            Some((url, branch)) if !branch.is_empty() => (url, None, Some(branch)),
+            _ => match source.rsplit_once('@') {
+                // `git@host:owner/repo.git` has an '@' but no revision.
+                Some((url, rev))
+                    if !url.is_empty()
+                        && !rev.is_empty()
+                        && !rev.contains('/')
+                        && !rev.contains(':') =>
+                {
+                    (url, Some(rev), None)
+                }
+                _ => (source, None, None),
+            },
+        };
+
+        let is_git = ["http://", "https://", "git://", "ssh://", "file://", "git@"]
+            .iter()
+            .any(|prefix| url.starts_with(prefix))
+            || url.trim_end_matches('/').ends_with(".git");
+        is_git.then(|| GitSource {
+            url: url.to_string(),
+            rev: rev.map(str::to_string),
+            branch: branch.map(str::to_string),
+        })
+    }
+
+    /// The revision to check out after cloning.
+    pub(super) fn checkout_rev(&self) -> String {
+        match (&self.rev, &self.branch) {
+            (Some(rev), _) => rev.clone(),
+            (None, Some(branch)) => format!("origin/{branch}"),
+            (None, None) => "HEAD".to_string(),
+        }
+    }
+
+    pub(super) fn describe(&self) -> String {
+        match (&self.rev, &self.branch) {
+            (Some(rev), _) => format!("rev {rev}"),
+            (None, Some(branch)) => format!("branch {branch}"),
+            (None, None) => "default branch".to_string(),
+        }
+    }
+
+    /// Package name used when `tonic.toml` has no `[package] name`.
+    fn repo_name(&self) -> String {
+        let name = self
+            .url
+            .trim_end_matches('/')
+            .rsplit(['/', ':'])
+            .next()
+            .unwrap_or(&self.url);
+        name.strip_suffix(".git").unwrap_or(name).to_string()
+    }
+}
+
+/// Result of installing one git package.
+struct GitInstall {
+    name: String,
+    bins: Vec<String>,
+    commit: String,
+    previous_commit: Option<String>,
+}
+
+fn install_git(source: &GitSource, force: bool) -> i32 {
+    let first_install = !bin_dir().exists();
+    let installed = match install_git_package(source, force, None) {
+        Ok(installed) => installed,
+        Err(exit_code) => return exit_code,
+    };
+
+    let action = if installed.previous_commit.is_some() {
+        "Updated"
+    } else {
+        "Installed"
+    };
+    println!("{action} package '{}'", installed.name);
+    println!(
+        "  source: {} ({}) at {}",
+        source.url,
+        source.describe(),
+        short_commit(&installed.commit)
+    );
+    if installed.bins.is_empty() {
+        println!("  binaries: (none)");
+    } else {
+        println!("  binaries: {}", installed.bins.join(", "));
+    }
+
+    if first_install {
+        print_path_instructions();
+    }
+
+    EXIT_OK
+}
+
+/// Clones `source` into a staging directory, then replaces the installed
+/// copy, shims and `packages.toml` entry. When `expected_name` is set (an
+/// upgrade), the checkout must still declare that package name.
+fn install_git_package(
+    source: &GitSource,
+    force: bool,
+    expected_name: Option<&str>,
+) -> Result<GitInstall, i32> {
+    for dir in [bin_dir(), packages_dir()] {
+        if let Err(e) = std::fs::create_dir_all(&dir) {
+            return Err(
+                CliDiagnostic::failure(format!("failed to create {}: {e}", dir.display())).emit(),
+            );
+        }
+    }
+
+    let staging = packages_dir().join(format!(".git-install-{}", std::process::id()));
+    remove_package_dir(&staging);
+    let fail = |exit_code: i32| {
+        remove_package_dir(&staging);
+        Err(exit_code)
+    };
+
+    let commit = match deps::checkout_git_rev(&source.url, &source.checkout_rev(), &staging) {
+        Ok(commit) => commit,
+        Err(error) => {
+            return fail(CliDiagnostic::failure_with_hint(
+                format!(
+                    "failed to fetch '{}' at {}: {error}",
+                    source.url,
+                    source.describe()
+                ),
+                "verify the repository URL and revision are reachable",
+            ))
+        }
+    };
+
+    if !staging.join("tonic.toml").exists() {
+        return fail(CliDiagnostic::failure_with_hint(
+            "repository does not appear to be a tonic project (no tonic.toml found)",
+            format!("expected tonic.toml at the root of {}", source.url),
+        ));
+    }
+
+    let (pkg_name, explicit_name) = match read_package_name(&staging) {
+        (name, true) => (name, true),
+        (_, false) => (source.repo_name(), false),
+    };
+    let pkg_dest = match package_destination(&pkg_name) {
+        Ok(pkg_dest) => pkg_dest,
+        Err(msg) => return fail(CliDiagnostic::failure(msg).emit()),
+    };
+    if let Some(expected) = expected_name.filter(|expected| *expected != pkg_name) {
+        return fail(
+            CliDiagnostic::failure(format!(
+                "package '{expected}' is now named '{pkg_name}' upstream; uninstall it and install '{}' again",
+                source.url
+            ))
+            .emit(),
+        );
+    }
+
+    let bins = match discover_binaries(&staging, &pkg_name, explicit_name) {
+        Ok(bins) => bins,
+        Err(msg) => return fail(CliDiagnostic::failure(msg).emit()),
+    };
+
+    let mut manifest = load_packages_manifest();
+    if !force {
+        if let Err(exit_code) = check_bin_conflicts(&manifest, &pkg_name, &bins) {
+            return fail(exit_code);
+        }
+    }
+
+    remove_package_dir(&pkg_dest);
+    if let Err(e) = std::fs::rename(&staging, &pkg_dest) {
+        return fail(
+            CliDiagnostic::failure(format!("failed to move checkout into place: {e}")).emit(),
+        );
+    }
+
+    if let Err(msg) = generate_shims(&pkg_name, &pkg_dest, &bins) {
+        return Err(CliDiagnostic::failure(msg).emit());
+    }
+
+    let now = OffsetDateTime::now_utc()
+        .format(&Rfc3339)
+        .unwrap_or_else(|_| "unknown".to_string());
+    let previous_commit = manifest
+        .packages
+        .get(&pkg_name)
+        .and_then(|entry| entry.commit.clone());
+    manifest.packages.insert(
+        pkg_name.clone(),
+        PackageEntry {
+            source: "git".to_string(),
+            path: None,
+            url: Some(source.url.clone()),
+            symlink: None,
+            git_ref: source.rev.clone(),
+            branch: source.branch.clone(),
+            commit: Some(commit.clone()),
+            bins: bins.clone(),
+            installed_at: now,
+        },
+    );
+    if let Err(msg) = save_packages_manifest(&manifest) {
+        return Err(CliDiagnostic::failure(msg).emit());
+    }
+
+    Ok(GitInstall {
+        name: pkg_name,
+        bins,
+        commit,
+        previous_commit,
+    })
+}
+
+/// `tonic install --upgrade [<name>]`: moves git packages to the latest
+/// commit of their branch. Packages pinned with `@rev` stay where they are.
+fn upgrade_packages(name: Option<&str>, force: bool) -> i32 {
+    let manifest = load_packages_manifest();
+    let targets: Vec<(String, PackageEntry)> = match name {
+        Some(name) => match manifest.packages.get(name) {
+            Some(entry) if entry.source == "git" => vec![(name.to_string(), entry.clone())],
+            Some(_) => {
+                return CliDiagnostic::failure_with_hint(
+                    format!("package '{name}' was not installed from git"),
+                    "path installs follow their source; reinstall with `tonic install <path>`",
+                );
+            }
+            None => {
+                return CliDiagnostic::failure(format!("package '{name}' is not installed")).emit();
+            }
+        },
+        None => manifest
+            .packages
+            .iter()
+            .filter(|(_, entry)| entry.source == "git")
+            .map(|(name, entry)| (name.clone(), entry.clone()))
+            .collect(),
+    };
+
+    if targets.is_empty() {
+        println!("No git packages installed.");
+        return EXIT_OK;
+    }
+
+    let mut status = EXIT_OK;
+    for (name, entry) in targets {
+        if let Some(rev) = &entry.git_ref {
+            println!("Skipped package '{name}': pinned to rev {rev}");
+            continue;
+        }
+        let Some(url) = entry.url.clone() else {
+            status = CliDiagnostic::failure(format!(
+                "package '{name}' has no git url in packages.toml; reinstall it"
+            ))
+            .emit();
+            continue;
+        };
+        let source = GitSource {
+            url,
+            rev: None,
+            branch: entry.branch.clone(),
+        };
+        match install_git_package(&source, force, Some(&name)) {
+            Ok(installed) if installed.previous_commit.as_deref() == Some(&installed.commit) => {
+                println!(
+                    "Package '{name}' is already up to date ({})",
+                    short_commit(&installed.commit)
+                );
+            }
+            Ok(installed) => {
+                println!(
+                    "Upgraded package '{name}' {} -> {}",
+                    installed
+                        .previous_commit
+                        .as_deref()
+                        .map_or("unknown", short_commit),
+                    short_commit(&installed.commit)
+                );
+            }
+            Err(exit_code) => status = exit_code,
+        }
+    }
+    status
+}
+
+fn short_commit(commit: &str) -> &str {
+    &commit[..commit.len().min(12)]
 }
 
 // ---------------------------------------------------------------------------
@@ -310,6 +656,21 @@ fn discover_binaries(
     pkg_name: &str,
     explicit_name: bool,
 ) -> Result<Vec<String>, String> {
+    // Declared `[[bin]]` targets are exactly what gets installed.
+    if let Ok(manifest) = manifest::load_project_manifest(project_path) {
+        if !manifest.bins.is_empty() {
+            for bin in &manifest.bins {
+                validate_install_name("binary", &bin.name)?;
+            }
+            return Ok(manifest.bins.into_iter().map(|bin| bin.name).collect());
+        }
+        if manifest.entry.is_none() && !project_path.join("bin").is_dir() {
+            return Err(format!(
+                "'{pkg_name}' is a library package with no [[bin]] targets; there is nothing to install"
+            ));
+        }
+    }
+
     let bin_dir = project_path.join("bin");
 
     if bin_dir.is_dir() {
@@ -360,11 +721,23 @@ fn generate_shims(pkg_name: &str, pkg_path: &Path, bins: &[String]) -> Result<()
     let bin_base = bin_dir();
     let pkg_bin_dir = pkg_path.join("bin");
     let has_bin_dir = pkg_bin_dir.is_dir();
+    let declared_bins = manifest::load_project_manifest(pkg_path)
+        .map(|manifest| manifest.bins)
+        .unwrap_or_default();
 
     for bin_name in bins {
         let shim_path = bin_base.join(bin_name);
 
-        let shim_content = if has_bin_dir && pkg_bin_dir.join(bin_name).exists() {
+        let shim_content = if declared_bins.iter().any(|bin| bin.name == *bin_name) {
+            format!(
+                "#!/bin/sh\n\
+                 # Generated by tonic install — do not edit\n\
+                 # Package: {pkg_name}, bin: {bin_name}\n\
+                 set -eu\n\
+                 exec tonic run '{}' --bin '{bin_name}' \"$@\"\n",
+                pkg_path.display()
+            )
+        } else if has_bin_dir && pkg_bin_dir.join(bin_name).exists() {
             // Delegate to the bin/ script
             let target = pkg_path.join("bin").join(bin_name);
             format!(
@@ -495,11 +868,8 @@ pub(super) fn handle_uninstall(args: Vec<String>) -> i32 {
     }
 
     // Remove cached package
-    let pkg_path = packages_dir().join(&name);
-    if pkg_path.read_link().is_ok() {
-        let _ = std::fs::remove_file(&pkg_path);
-    } else if pkg_path.exists() {
-        let _ = std::fs::remove_dir_all(&pkg_path);
+    if let Ok(pkg_path) = package_destination(&name) {
+        remove_package_dir(&pkg_path);
     }
 
     // Save updated manifest
@@ -601,18 +971,30 @@ impl CliDiagnosticExt for CliDiagnostic {
 fn print_install_help() {
     println!(
         "tonic install - Install a tonic module globally\n\n\
-         Usage:\n  tonic install <source> [--copy] [--force]\n\n\
+         Usage:\n  tonic install <source> [--copy] [--force]\n  \
+         tonic install --upgrade [<name>] [--force]\n\n\
          Sources:\n  \
-         <path>        Install from a local directory (default: symlink)\n  \
-         <git-url>     Clone from a git repository\n  \
-         <name>        Install from registry (not yet supported)\n\n\
+         <path>              Install from a local directory (default: symlink)\n  \
+         <git-url>           Clone the default branch of a git repository\n  \
+         <git-url>@<rev>     Clone and pin a commit or tag\n  \
+         <git-url>#<branch>  Clone and follow a branch\n\n\
          Options:\n  \
          --copy     Copy source instead of symlinking (local paths only)\n  \
-         --force    Overwrite shims owned by another package\n\n\
+         --force    Overwrite shims owned by another package\n  \
+         --upgrade  Move git packages (all, or <name>) to the latest commit\n             \
+         of their branch; packages pinned with @<rev> are skipped\n\n\
+         Git installs are cloned into ~/.tonic/packages/<name> and the resolved\n\
+         commit is recorded in ~/.tonic/packages.toml.\n\n\
+         Binaries:\n  \
+         A package that declares [[bin]] targets in tonic.toml gets one shim per\n  \
+         binary, running `tonic run <package> --bin <name>`. Otherwise each file\n  \
+         in bin/ is shimmed, or one shim named after [package] name.\n\n\
          Examples:\n  \
          tonic install .                         # Install current project\n  \
          tonic install ../my-tool                # Install from relative path\n  \
-         tonic install ../my-tool --copy         # Snapshot instead of symlink\n"
+         tonic install ../my-tool --copy         # Snapshot instead of symlink\n  \
+         tonic install https://example.com/tool.git#main\n  \
+         tonic install --upgrade                 # Upgrade all git packages\n"
     );
 }
 
@@ -646,10 +1028,6 @@ fn print_path_instructions() {
     println!("  fish_add_path ~/.tonic/bin");
 }
 
-// ---------------------------------------------------------------------------
-// Placeholder for run_placeholder compatibility
-// ---------------------------------------------------------------------------
-
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -713,6 +1091,28 @@ mod tests {
         let _ = std::fs::remove_dir_all(&dir);
     }
 
+    #[test]
+    fn git_source_parses_rev_and_branch_suffixes() {
+        let parse = |source: &str| GitSource::parse(source).expect("should be a git source");
+
+        let pinned = parse("https://example.com/tools/lint.git@v1.2.0");
+        assert_eq!(pinned.url, "https://example.com/tools/lint.git");
+        assert_eq!(pinned.rev.as_deref(), Some("v1.2.0"));
+        assert_eq!(pinned.checkout_rev(), "v1.2.0");
+
+        let branch = parse("file:///srv/git/lint#release");
+        assert_eq!(branch.url, "file:///srv/git/lint");
+        assert_eq!(branch.checkout_rev(), "origin/release");
+
+        let scp = parse("git@github.com:tools/lint.git");
+        assert_eq!(scp.url, "git@github.com:tools/lint.git");
+        assert_eq!(scp.rev, None);
+        assert_eq!(scp.checkout_rev(), "HEAD");
+        assert_eq!(scp.repo_name(), "lint");
+
+        assert_eq!(GitSource::parse("../my-tool"), None);
+    }
+
     #[test]
     fn packages_manifest_round_trip() {
         let mut manifest = PackagesManifest::default();
@@ -724,6 +1124,7 @@ mod tests {
                 url: None,
                 symlink: Some(true),
                 git_ref: None,
+                branch: None,
                 commit: None,
                 bins: vec!["test-bin".to_string()],
                 installed_at: "2026-03-27T12:00:00Z".to_string(),
@@ -0,0 +1,250 @@
+use super::*;
+use std::path::{Path, PathBuf};
+
+#[path = "cmd_new_templates.rs"]
+mod templates;
+use templates::{builtin_template, BUILTIN_TEMPLATES, DEFAULT_TEMPLATE};
+
+pub(super) fn handle_new(args: Vec<String>) -> i32 {
+    let mut name: Option<String> = None;
+    let mut template = DEFAULT_TEMPLATE.to_string();
+    let mut iter = args.iter();
+
+    while let Some(arg) = iter.next() {
+        match arg.as_str() {
+            "-h" | "--help" | "help" => {
+                print_new_help();
+                return EXIT_OK;
+            }
+            "--template" => match iter.next() {
+                Some(value) => template = value.clone(),
+                None => {
+                    return CliDiagnostic::usage_with_hint(
+                        "--template requires a value",
+                        "run `tonic new --help` for usage",
+                    )
+                    .emit();
+                }
+            },
+            other if !other.starts_with('-') => {
+                if name.is_some() {
+                    return CliDiagnostic::usage_with_hint(
+                        format!("unexpected argument '{other}'"),
+                        "run `tonic new --help` for usage",
+                    )
+                    .emit();
+                }
+                name = Some(other.to_string());
+            }
+            other => {
+                return CliDiagnostic::usage_with_hint(
+                    format!("unknown flag '{other}'"),
+                    "run `tonic new --help` for usage",
+                )
+                .emit();
+            }
+        }
+    }
+
+    let Some(name) = name else {
+        return CliDiagnostic::usage_with_hint(
+            "missing required <name>",
+            "run `tonic new --help` for usage",
+        )
+        .emit();
+    };
+    if let Err(error) = deps::validate_package_name(&name) {
+        return CliDiagnostic::usage_with_hint(
+            error,
+            "package names follow the registry naming rules, e.g. `my_app`",
+        )
+        .emit();
+    }
+
+    let destination = PathBuf::from(&name);
+    if destination.exists() {
+        return CliDiagnostic::failure(format!("destination '{name}' already exists")).emit();
+    }
+
+    let next_step = builtin_template(&template).map(|builtin| substitute(builtin.next_step, &name));
+    let generated = match builtin_template(&template) {
+        Some(builtin) => write_builtin_template(builtin, &name, &destination),
+        None => match GitSource::parse(&template) {
+            Some(source) => copy_git_template(&source, &name, &destination),
+            None if Path::new(&template).is_dir() => {
+                copy_template_dir(Path::new(&template), &name, &destination)
+            }
+            None => {
+                let builtins = BUILTIN_TEMPLATES
+                    .iter()
+                    .map(|template| template.name)
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                return CliDiagnostic::usage_with_hint(
+                    format!("unknown template '{template}'"),
+                    format!("use one of {builtins}, a template directory or a git URL"),
+                )
+                .emit();
+            }
+        },
+    };
+    let mut files = match generated.and_then(|mut files| {
+        if lock_path_dependencies(&destination)? {
+            files.push(PathBuf::from("tonic.lock"));
+        }
+        Ok(files)
+    }) {
+        Ok(files) => files,
+        Err(error) => {
+            let _ = std::fs::remove_dir_all(&destination);
+            return CliDiagnostic::failure(error).emit();
+        }
+    };
+
+    files.sort();
+    files.dedup();
+    println!("Created {name} from the {template} template:");
+    for file in &files {
+        println!("  {}", destination.join(file).display());
+    }
+    if let Some(next_step) = next_step {
+        println!("\nNext: {next_step}");
+    }
+    EXIT_OK
+}
+
+/// Writes an embedded template and returns the paths it created.
+fn write_builtin_template(
+    template: &templates::BuiltinTemplate,
+    name: &str,
+    destination: &Path,
+) -> Result<Vec<PathBuf>, String> {
+    let mut files = Vec::new();
+    for (path, contents) in template.files {
+        let relative = PathBuf::from(substitute(path, name));
+        write_new_file(
+            &destination.join(&relative),
+            substitute(contents, name).as_bytes(),
+        )?;
+        files.push(relative);
+    }
+    Ok(files)
+}
+
+/// Clones a user template and copies its checkout without `.git`.
+fn copy_git_template(
+    source: &GitSource,
+    name: &str,
+    destination: &Path,
+) -> Result<Vec<PathBuf>, String> {
+    let checkout = std::env::temp_dir().join(format!("tonic-new-template-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&checkout);
+    let copied = deps::checkout_git_rev(&source.url, &source.checkout_rev(), &checkout)
+        .map_err(|error| {
+            format!(
+                "failed to fetch template '{}' at {}: {error}",
+                source.url,
+                source.describe()
+            )
+        })
+        .and_then(|_| copy_template_dir(&checkout, name, destination));
+    let _ = std::fs::remove_dir_all(&checkout);
+    copied
+}
+
+/// Copies a user template directory, substituting `{{name}}` and
+/// `{{module}}` in paths and in every UTF-8 file.
+fn copy_template_dir(
+    template_dir: &Path,
+    name: &str,
+    destination: &Path,
+) -> Result<Vec<PathBuf>, String> {
+    if !template_dir.join("tonic.toml").is_file() {
+        return Err(format!(
+            "template '{}' has no tonic.toml at its root",
+            template_dir.display()
+        ));
+    }
+
+    let mut files = Vec::new();
+    let mut pending = vec![template_dir.to_path_buf()];
+    while let Some(dir) = pending.pop() {
+        let entries = std::fs::read_dir(&dir)
+            .map_err(|error| format!("failed to read {}: {error}", dir.display()))?;
+        for entry in entries {
+            let path = entry
+                .map_err(|error| format!("failed to read {}: {error}", dir.display()))?
+                .path();
+            if path
+                .file_name()
+                .is_some_and(|file_name| file_name == ".git")
+            {
+                continue;
+            }
+            if path.is_dir() {
+                pending.push(path);
+                continue;
+            }
+
+            let relative = path
+                .strip_prefix(template_dir)
+                .map_err(|error| format!("failed to copy {}: {error}", path.display()))?;
+            let relative = PathBuf::from(substitute(&relative.to_string_lossy(), name));
+            let contents = std::fs::read(&path)
+                .map_err(|error| format!("failed to read {}: {error}", path.display()))?;
+            let contents = match String::from_utf8(contents) {
+                Ok(text) => substitute(&text, name).into_bytes(),
+                Err(binary) => binary.into_bytes(),
+            };
+            write_new_file(&destination.join(&relative), &contents)?;
+            files.push(relative);
+        }
+    }
+    Ok(files)
+}
+
+/// Writes `tonic.lock` when every dependency of the new project is a path
+/// dependency, which needs no network. Returns whether it did.
+fn lock_path_dependencies(project_root: &Path) -> Result<bool, String> {
+    let scope = load_dependency_scope(project_root)?;
+    let dependencies = &scope.dependencies;
+    if dependencies.path.is_empty()
+        || !dependencies.git.is_empty()
+        || !dependencies.registry.is_empty()
+    {
+        return Ok(false);
+    }
+    let lockfile =
+        deps::Lockfile::generate(dependencies, &scope.root, deps::SyncOptions::default())?;
+    lockfile.save(&scope.root)?;
+    Ok(true)
+}
+
+fn write_new_file(path: &Path, contents: &[u8]) -> Result<(), String> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)
+            .map_err(|error| format!("failed to create {}: {error}", parent.display()))?;
+    }
+    std::fs::write(path, contents)
+        .map_err(|error| format!("failed to write {}: {error}", path.display()))
+}
+
+fn substitute(text: &str, name: &str) -> String {
+    text.replace("{{name}}", name)
+        .replace("{{module}}", &module_name(name))
+}
+
+/// The module name for a package: `my_app` becomes `MyApp`.
+fn module_name(package_name: &str) -> String {
+    package_name
+        .split('_')
+        .filter(|segment| !segment.is_empty())
+        .map(|segment| {
+            let mut chars = segment.chars();
+            match chars.next() {
+                Some(first) => first.to_ascii_uppercase().to_string() + chars.as_str(),
+                None => String::new(),
+            }
+        })
+        .collect()
+}
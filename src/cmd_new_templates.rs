@@ -0,0 +1,260 @@
+//! Project templates embedded in the binary for `tonic new`.
+//!
+//! File paths and contents may use `{{name}}` for the package name and
+//! `{{module}}` for its module name (`my_app` becomes `MyApp`).
+
+/// A built-in `--template`.
+pub(super) struct BuiltinTemplate {
+    pub(super) name: &'static str,
+    /// `(path, contents)` pairs relative to the new project's directory.
+    pub(super) files: &'static [(&'static str, &'static str)],
+    /// Printed after the project is created, e.g. how to run it.
+    pub(super) next_step: &'static str,
+}
+
+pub(super) const DEFAULT_TEMPLATE: &str = "cli";
+
+pub(super) const BUILTIN_TEMPLATES: &[BuiltinTemplate] = &[
+    BuiltinTemplate {
+        name: "cli",
+        files: &[
+            (".gitignore", GITIGNORE),
+            (".tonic-formatter.toml", FORMATTER_CONFIG),
+            ("tonic.toml", CLI_MANIFEST),
+            ("src/{{name}}.tn", GREETING_LIBRARY),
+            ("bin/{{name}}.tn", CLI_MAIN),
+            ("test/{{name}}_test.tn", STARTER_TEST),
+        ],
+        next_step: "cd {{name}} && tonic run . --bin {{name}}",
+    },
+    BuiltinTemplate {
+        name: "lib",
+        files: &[
+            (".gitignore", GITIGNORE),
+            (".tonic-formatter.toml", FORMATTER_CONFIG),
+            ("tonic.toml", LIB_MANIFEST),
+            ("src/{{name}}.tn", GREETING_LIBRARY),
+            ("test/{{name}}_test.tn", STARTER_TEST),
+        ],
+        next_step: "cd {{name}} && tonic test .",
+    },
+    BuiltinTemplate {
+        name: "http-server",
+        files: &[
+            (".gitignore", GITIGNORE),
+            (".tonic-formatter.toml", FORMATTER_CONFIG),
+            ("tonic.toml", HTTP_SERVER_MANIFEST),
+            ("src/router.tn", HTTP_ROUTER),
+            ("bin/server.tn", HTTP_SERVER_MAIN),
+            ("test/{{name}}_test.tn", STARTER_TEST),
+        ],
+        next_step: "cd {{name}} && tonic run . --bin {{name}}",
+    },
+    BuiltinTemplate {
+        name: "workspace",
+        files: &[
+            (".gitignore", GITIGNORE),
+            (".tonic-formatter.toml", FORMATTER_CONFIG),
+            ("tonic.toml", WORKSPACE_MANIFEST),
+            ("apps/{{name}}/tonic.toml", WORKSPACE_APP_MANIFEST),
+            ("apps/{{name}}/bin/{{name}}.tn", WORKSPACE_APP_MAIN),
+            ("libs/{{name}}_core/tonic.toml", WORKSPACE_LIB_MANIFEST),
+            ("libs/{{name}}_core/src/{{name}}_core.tn", WORKSPACE_LIBRARY),
+            (
+                "libs/{{name}}_core/test/{{name}}_core_test.tn",
+                WORKSPACE_STARTER_TEST,
+            ),
+        ],
+        next_step: "cd {{name}} && tonic run apps/{{name}} --bin {{name}}",
+    },
+];
+
+pub(super) fn builtin_template(name: &str) -> Option<&'static BuiltinTemplate> {
+    BUILTIN_TEMPLATES
+        .iter()
+        .find(|template| template.name == name)
+}
+
+const GITIGNORE: &str = "# Build artifacts, caches and fetched dependencies
+/.tonic/
+";
+
+const FORMATTER_CONFIG: &str =
+    "# Settings for `tonic fmt`; run `tonic fmt --help` for every option.
+line_length = 98
+trailing_comma = \"never\"
+";
+
+const CLI_MANIFEST: &str = r#"[project]
+name = "{{name}}"
+
+[package]
+name = "{{name}}"
+version = "0.1.0"
+description = "A command-line tool"
+
+[lib]
+path = "src"
+
+[[bin]]
+name = "{{name}}"
+path = "bin/{{name}}.tn"
+main_module = "{{module}}.Main"
+"#;
+
+const LIB_MANIFEST: &str = r#"[project]
+name = "{{name}}"
+
+[package]
+name = "{{name}}"
+version = "0.1.0"
+description = "A tonic library"
+
+[lib]
+path = "src"
+"#;
+
+const HTTP_SERVER_MANIFEST: &str = r#"[project]
+name = "{{name}}"
+
+[package]
+name = "{{name}}"
+version = "0.1.0"
+description = "An HTTP server"
+
+[lib]
+path = "src"
+
+[[bin]]
+name = "{{name}}"
+path = "bin/server.tn"
+main_module = "{{module}}.Server"
+"#;
+
+const WORKSPACE_MANIFEST: &str = r#"[workspace]
+members = ["apps/*", "libs/*"]
+"#;
+
+const WORKSPACE_APP_MANIFEST: &str = r#"[project]
+name = "{{name}}"
+
+[dependencies]
+{{name}}_core = { path = "../../libs/{{name}}_core" }
+
+[[bin]]
+name = "{{name}}"
+path = "bin/{{name}}.tn"
+main_module = "{{module}}.Main"
+"#;
+
+const WORKSPACE_LIB_MANIFEST: &str = r#"[project]
+name = "{{name}}_core"
+
+[lib]
+path = "src"
+"#;
+
+const GREETING_LIBRARY: &str = r#"defmodule {{module}} do
+  @moduledoc "The public API of {{name}}."
+
+  @doc """
+  Returns a greeting for `name`.
+
+      tonic> {{module}}.greeting("world")
+      "Hello, world!"
+  """
+  def greeting(name) do
+    "Hello, " <> name <> "!"
+  end
+end
+"#;
+
+const CLI_MAIN: &str = r#"defmodule {{module}}.Main do
+  @moduledoc "The {{name}} command."
+
+  @doc "Prints a greeting."
+  def run() do
+    IO.puts({{module}}.greeting("world"))
+  end
+end
+"#;
+
+const HTTP_ROUTER: &str = r#"defmodule {{module}}.Router do
+  @moduledoc "Maps requests to responses."
+
+  @doc "Returns the response for a `method` and `path`."
+  def handle("GET", "/") do
+    %{status: 200, body: "Hello from {{name}}!\n"}
+  end
+
+  def handle("GET", "/health") do
+    %{status: 200, body: "ok\n"}
+  end
+
+  def handle(_method, _path) do
+    %{status: 404, body: "not found\n"}
+  end
+end
+"#;
+
+const HTTP_SERVER_MAIN: &str = r#"defmodule {{module}}.Server do
+  @moduledoc "Serves {{module}}.Router over HTTP."
+
+  @doc "Listens on port 4000 and answers requests until stopped."
+  def run() do
+    port = 4000
+    listener = System.http_listen("127.0.0.1", port)
+    IO.puts("listening on http://127.0.0.1:#{port}")
+    serve(Map.get(listener, :listener_id))
+  end
+
+  defp serve(listener_id) do
+    connection_id = Map.get(System.http_accept(listener_id, 0), :connection_id)
+    request = System.http_read_request(connection_id)
+    response = {{module}}.Router.handle(Map.get(request, :method), Map.get(request, :path))
+    headers = [{"content-type", "text/plain"}]
+    System.http_write_response(connection_id, response.status, headers, response.body)
+    serve(listener_id)
+  end
+end
+"#;
+
+const WORKSPACE_APP_MAIN: &str = r#"defmodule {{module}}.Main do
+  @moduledoc "The {{name}} command."
+
+  @doc "Prints a greeting."
+  def run() do
+    IO.puts({{module}}Core.greeting("world"))
+  end
+end
+"#;
+
+const WORKSPACE_LIBRARY: &str = r#"defmodule {{module}}Core do
+  @moduledoc "Code shared by the apps of the {{name}} workspace."
+
+  @doc "Returns a greeting for `name`."
+  def greeting(name) do
+    "Hello, " <> name <> "!"
+  end
+end
+"#;
+
+// `tonic test` compiles each test file on its own, so the starter tests
+// cannot call the project's modules.
+const STARTER_TEST: &str = r#"defmodule {{module}}Test do
+  @moduledoc "Tests for {{module}}."
+
+  test "builds a greeting" do
+    Assert.assert_equal("Hello, " <> "world" <> "!", "Hello, world!")
+  end
+end
+"#;
+
+const WORKSPACE_STARTER_TEST: &str = r#"defmodule {{module}}CoreTest do
+  @moduledoc "Tests for {{module}}Core."
+
+  test "builds a greeting" do
+    Assert.assert_equal("Hello, " <> "world" <> "!", "Hello, world!")
+  end
+end
+"#;
@@ -0,0 +1,292 @@
+use super::*;
+
+const PUBLISH_USAGE: &str =
+    "usage: tonic publish [--dry-run] [--registry <url>] [--key <path>] [--token <token>]";
+const YANK_USAGE: &str =
+    "usage: tonic yank <package> <version> [--undo] [--registry <url>] [--key <path>] [--token <token>]";
+const OWNER_USAGE: &str = "usage: tonic owner <list|add|remove> <package> [<owner>] [--registry <url>] [--key <path>] [--token <token>]";
+
+/// Options shared by the registry write commands.
+#[derive(Default)]
+struct RegistryArgs {
+    positionals: Vec<String>,
+    registry: Option<String>,
+    key: Option<String>,
+    token: Option<String>,
+    switches: Vec<String>,
+}
+
+/// Splits `args` into positionals, `--registry`/`--key`/`--token` values
+/// and the boolean `switches` the command accepts.
+fn parse_registry_args(
+    args: &[String],
+    switches: &[&str],
+    usage: &str,
+) -> Result<RegistryArgs, i32> {
+    let mut parsed = RegistryArgs::default();
+    let mut index = 0;
+    while index < args.len() {
+        let arg = args[index].as_str();
+        match arg {
+            "--registry" | "--key" | "--token" => {
+                let Some(value) = args.get(index + 1) else {
+                    return Err(CliDiagnostic::usage_with_hint(
+                        format!("missing value for {arg}"),
+                        usage,
+                    )
+                    .emit());
+                };
+                let slot = match arg {
+                    "--registry" => &mut parsed.registry,
+                    "--key" => &mut parsed.key,
+                    _ => &mut parsed.token,
+                };
+                *slot = Some(value.clone());
+                index += 2;
+            }
+            _ if switches.contains(&arg) => {
+                parsed.switches.push(arg.to_string());
+                index += 1;
+            }
+            _ if arg.starts_with("--") => {
+                return Err(CliDiagnostic::usage_with_hint(
+                    format!("unexpected argument '{arg}'"),
+                    usage,
+                )
+                .emit());
+            }
+            _ => {
+                parsed.positionals.push(arg.to_string());
+                index += 1;
+            }
+        }
+    }
+    Ok(parsed)
+}
+
+impl RegistryArgs {
+    fn has(&self, switch: &str) -> bool {
+        self.switches.iter().any(|candidate| candidate == switch)
+    }
+
+    /// `--registry`, else the enclosing project's `[registries] default`.
+    fn registry_url(&self) -> String {
+        let manifest_default = find_project_root()
+            .and_then(|root| manifest::load_project_manifest(&root).ok())
+            .and_then(|manifest| manifest.dependencies.default_registry);
+        deps::registry_url(self.registry.as_deref(), manifest_default.as_deref())
+    }
+
+    fn credentials(&self) -> Result<deps::Credentials, String> {
+        deps::Credentials::load(self.key.as_deref(), self.token.clone())
+    }
+}
+
+pub(super) fn handle_publish(args: Vec<String>) -> i32 {
+    if matches!(args.first().map(String::as_str), Some("-h" | "--help")) {
+        print_publish_help();
+        return EXIT_OK;
+    }
+
+    let parsed = match parse_registry_args(&args, &["--dry-run"], PUBLISH_USAGE) {
+        Ok(parsed) => parsed,
+        Err(exit_code) => return exit_code,
+    };
+    if let Some(extra) = parsed.positionals.first() {
+        return CliDiagnostic::usage_with_hint(
+            format!("unexpected argument '{extra}'"),
+            PUBLISH_USAGE,
+        )
+        .emit();
+    }
+
+    let project_root = find_project_root();
+    if project_root.is_none() {
+        return CliDiagnostic::failure("no tonic.toml found in current directory or parents")
+            .emit();
+    }
+    let project_root = project_root.unwrap();
+
+    let manifest = match manifest::load_project_manifest(&project_root) {
+        Ok(m) => m,
+        Err(msg) => return CliDiagnostic::failure(msg).emit(),
+    };
+
+    // Validate required package fields before attempting publish.
+    let package = match &manifest.package {
+        Some(p) => p,
+        None => {
+            return CliDiagnostic::failure(
+                "tonic.toml is missing a [package] section; add name, version, and description",
+            )
+            .emit();
+        }
+    };
+
+    let mut missing_fields = Vec::new();
+    if package.name.is_none() {
+        missing_fields.push("name");
+    }
+    if package.version.is_none() {
+        missing_fields.push("version");
+    }
+    if package.description.is_none() {
+        missing_fields.push("description");
+    }
+
+    if !missing_fields.is_empty() {
+        return CliDiagnostic::failure(format!(
+            "tonic.toml [package] is missing required fields: {}",
+            missing_fields.join(", ")
+        ))
+        .emit();
+    }
+
+    let publication = match deps::prepare_publication(&project_root, &manifest) {
+        Ok(publication) => publication,
+        Err(message) => return CliDiagnostic::failure(message).emit(),
+    };
+    let credentials = match parsed.credentials() {
+        Ok(credentials) => credentials,
+        Err(message) => return CliDiagnostic::failure(message).emit(),
+    };
+    let registry = deps::registry_url(
+        parsed.registry.as_deref(),
+        manifest.dependencies.default_registry.as_deref(),
+    );
+
+    println!(
+        "Packaging {} {} ({} file{})",
+        publication.name,
+        publication.version,
+        publication.tarball.files.len(),
+        if publication.tarball.files.len() == 1 {
+            ""
+        } else {
+            "s"
+        }
+    );
+    for file in &publication.tarball.files {
+        println!("  {file}");
+    }
+    println!("checksum: {}", publication.tarball.checksum);
+
+    let tarball_name = format!(
+        ".tonic/package/{}-{}.tar.gz",
+        publication.name, publication.version
+    );
+    let tarball_path = project_root.join(&tarball_name);
+    if let Err(error) = tarball_path
+        .parent()
+        .map_or(Ok(()), std::fs::create_dir_all)
+        .and_then(|()| std::fs::write(&tarball_path, &publication.tarball.bytes))
+    {
+        return CliDiagnostic::failure(format!("failed to write {tarball_name}: {error}")).emit();
+    }
+    println!("package written to {tarball_name}");
+
+    if let Some(key) = &credentials.key {
+        match key.public_key() {
+            Ok(public_key) => println!("signing key: {public_key}"),
+            Err(message) => return CliDiagnostic::failure(message).emit(),
+        }
+    }
+
+    if parsed.has("--dry-run") {
+        println!("dry run: not publishing to {registry}");
+        return EXIT_OK;
+    }
+
+    match deps::publish(&registry, &publication, &credentials) {
+        Ok(()) => {
+            println!(
+                "Published {} {} to {registry}",
+                publication.name, publication.version
+            );
+            EXIT_OK
+        }
+        Err(message) => CliDiagnostic::failure(format!("failed to publish: {message}")).emit(),
+    }
+}
+
+pub(super) fn handle_yank(args: Vec<String>) -> i32 {
+    if matches!(args.first().map(String::as_str), Some("-h" | "--help")) {
+        print_yank_help();
+        return EXIT_OK;
+    }
+
+    let parsed = match parse_registry_args(&args, &["--undo"], YANK_USAGE) {
+        Ok(parsed) => parsed,
+        Err(exit_code) => return exit_code,
+    };
+    let [name, version] = parsed.positionals.as_slice() else {
+        return CliDiagnostic::usage_with_hint("expected <package> and <version>", YANK_USAGE)
+            .emit();
+    };
+    let credentials = match parsed.credentials() {
+        Ok(credentials) => credentials,
+        Err(message) => return CliDiagnostic::failure(message).emit(),
+    };
+
+    let registry = parsed.registry_url();
+    let yank = !parsed.has("--undo");
+    match deps::set_yanked(&registry, name, version, yank, &credentials) {
+        Ok(()) => {
+            let action = if yank { "Yanked" } else { "Unyanked" };
+            println!("{action} {name} {version} on {registry}");
+            EXIT_OK
+        }
+        Err(message) => CliDiagnostic::failure(message).emit(),
+    }
+}
+
+pub(super) fn handle_owner(args: Vec<String>) -> i32 {
+    if matches!(args.first().map(String::as_str), Some("-h" | "--help")) {
+        print_owner_help();
+        return EXIT_OK;
+    }
+
+    let parsed = match parse_registry_args(&args, &[], OWNER_USAGE) {
+        Ok(parsed) => parsed,
+        Err(exit_code) => return exit_code,
+    };
+    let registry = parsed.registry_url();
+
+    match parsed.positionals.as_slice() {
+        [action, name] if action == "list" => match deps::list_owners(&registry, name) {
+            Ok(owners) => {
+                for owner in owners {
+                    println!("{owner}");
+                }
+                EXIT_OK
+            }
+            Err(message) => CliDiagnostic::failure(message).emit(),
+        },
+        [action, name, owner] if action == "add" || action == "remove" => {
+            let credentials = match parsed.credentials() {
+                Ok(credentials) => credentials,
+                Err(message) => return CliDiagnostic::failure(message).emit(),
+            };
+            let add = action == "add";
+            match deps::change_owner(&registry, name, owner, add, &credentials) {
+                Ok(()) => {
+                    if add {
+                        println!("Added {owner} as an owner of {name}");
+                    } else {
+                        println!("Removed {owner} from the owners of {name}");
+                    }
+                    EXIT_OK
+                }
+                Err(message) => CliDiagnostic::failure(message).emit(),
+            }
+        }
+        [action, ..] if !matches!(action.as_str(), "list" | "add" | "remove") => {
+            CliDiagnostic::usage_with_hint(
+                format!("unknown owner subcommand '{action}'"),
+                OWNER_USAGE,
+            )
+            .emit()
+        }
+        _ => CliDiagnostic::usage_with_hint("missing owner arguments", OWNER_USAGE).emit(),
+    }
+}
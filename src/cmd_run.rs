@@ -1,5 +1,17 @@
 use super::*;
 use crate::interop::{host_stdout_was_observed, reset_host_stdout_observed};
+use crate::runtime::profile::{with_profiler, FunctionProfile};
+
+const RUN_PROFILE_USAGE: &str =
+    "usage: tonic run <path> [--profile] [--profile-dir <dir>] [--profile-top <number>]";
+const RUN_BIN_USAGE: &str = "usage: tonic run <project-dir> --bin <name>";
+
+/// `tonic run --profile`: where the collapsed stacks and Chrome trace go and
+/// how many functions the stderr table lists.
+struct RunProfileOptions {
+    dir: std::path::PathBuf,
+    top: usize,
+}
 
 pub(super) fn handle_run(args: Vec<String>) -> i32 {
     if matches!(args.first().map(String::as_str), Some("-h" | "--help")) {
@@ -7,26 +19,48 @@ pub(super) fn handle_run(args: Vec<String>) -> i32 {
         return EXIT_OK;
     }
 
-    if args.is_empty() {
+    let (profile_options, program_args) = match parse_run_profile_options(&args) {
+        Ok(parsed) => parsed,
+        Err(exit_code) => return exit_code,
+    };
+
+    let (bin, program_args) = match take_bin_flag(program_args) {
+        Ok(parsed) => parsed,
+        Err(exit_code) => return exit_code,
+    };
+
+    let Some(source_path) = program_args.first().cloned() else {
         return CliDiagnostic::usage_with_hint(
             "missing required <path>",
             "run `tonic run --help` for usage",
         )
         .emit();
-    }
-
-    let source_path = args[0].clone();
+    };
     let cwd = std::env::current_dir().unwrap_or_else(|_| std::path::PathBuf::from("."));
     let mut observed_run = ObservabilityRun::from_env("run", &command_argv("run", &args), &cwd);
     let mut profiler = profiling::PhaseProfiler::from_env("run");
 
     if native_artifact::is_native_artifact_path(&source_path) {
+        if bin.is_some() {
+            return CliDiagnostic::usage_with_hint(
+                "--bin requires a project directory; native artifacts hold a single program",
+                RUN_BIN_USAGE,
+            )
+            .emit();
+        }
+        if profile_options.is_some() {
+            return CliDiagnostic::usage_with_hint(
+                "--profile requires a source path; native artifacts cannot be profiled",
+                RUN_PROFILE_USAGE,
+            )
+            .emit();
+        }
         return handle_run_native_artifact(&source_path, &mut profiler, &mut observed_run);
     }
 
     let source =
         match observe_phase_result(&mut profiler, &mut observed_run, "run.load_source", || {
-            load_run_source(&source_path)
+            load_program_source(&source_path, bin.as_deref())
         }) {
             Ok(source) => source,
             Err(error) => {
@@ -56,11 +90,14 @@ pub(super) fn handle_run(args: Vec<String>) -> i32 {
     };
 
     let cache_key = build_run_cache_key(&source, &project_root);
+    let cache_dir = run_cache_directory();
     let mut cache_status = "miss";
 
     let ir =
         match observe_phase_result(&mut profiler, &mut observed_run, "run.cache_lookup", || {
-            load_cached_ir(&cache_key)
+            cache_dir
+                .clone()
+                .and_then(|cache_dir| load_cached_ir(&cache_dir, &cache_key))
         }) {
             Ok(Some(cached_ir)) => {
                 cache_status = "hit";
@@ -86,7 +123,11 @@ pub(super) fn handle_run(args: Vec<String>) -> i32 {
                     &mut profiler,
                     &mut observed_run,
                     "run.cache_store",
-                    || store_cached_ir(&cache_key, &compiled_ir),
+                    || {
+                        cache_dir.clone().and_then(|cache_dir| {
+                            store_cached_ir(&cache_dir, &cache_key, &compiled_ir)
+                        })
+                    },
                 ) {
                     eprintln!("warning: {error}");
                 }
@@ -94,6 +135,9 @@ pub(super) fn handle_run(args: Vec<String>) -> i32 {
             }
         };
 
+    if let Ok(cache_dir) = &cache_dir {
+        record_cache_status(cache_dir, cache_status);
+    }
     if should_trace_cache_status() {
         trace_cache_status(cache_status);
     }
@@ -103,7 +147,14 @@ pub(super) fn handle_run(args: Vec<String>) -> i32 {
         &mut profiler,
         &mut observed_run,
         "run.evaluate_entrypoint",
-        || evaluate_entrypoint(&ir),
+        || match &profile_options {
+            Some(options) => {
+                let (result, function_profile) = with_profiler(|| evaluate_entrypoint(&ir));
+                report_run_profile(options, &source_path, &function_profile);
+                result
+            }
+            None => evaluate_entrypoint(&ir),
+        },
     ) {
         Ok(value) => value,
         Err(error) => {
@@ -153,6 +204,120 @@ pub(super) fn handle_run(args: Vec<String>) -> i32 {
     }
 }
 
+/// Split the `--profile*` flags off the run arguments; everything else is the
+/// path and the program's own arguments.
+fn parse_run_profile_options(
+    args: &[String],
+) -> Result<(Option<RunProfileOptions>, Vec<String>), i32> {
+    let mut enabled = false;
+    let mut dir = std::path::PathBuf::from(".tonic/profile");
+    let mut top = 20;
+    let mut rest = Vec::new();
+    let mut index = 0;
+
+    while index < args.len() {
+        match args[index].as_str() {
+            "--profile" => {
+                enabled = true;
+                index += 1;
+            }
+            flag @ ("--profile-dir" | "--profile-top") => {
+                let Some(value) = args.get(index + 1) else {
+                    return Err(CliDiagnostic::usage_with_hint(
+                        format!("missing value for {flag}"),
+                        RUN_PROFILE_USAGE,
+                    )
+                    .emit());
+                };
+                if flag == "--profile-dir" {
+                    dir = std::path::PathBuf::from(value);
+                } else {
+                    let Some(parsed) = value.parse::<usize>().ok().filter(|top| *top > 0) else {
+                        return Err(CliDiagnostic::usage_with_hint(
+                            format!(
+                                "invalid --profile-top '{value}' (expected a positive integer)"
+                            ),
+                            RUN_PROFILE_USAGE,
+                        )
+                        .emit());
+                    };
+                    top = parsed;
+                }
+                enabled = true;
+                index += 2;
+            }
+            _ => {
+                rest.push(args[index].clone());
+                index += 1;
+            }
+        }
+    }
+
+    Ok((enabled.then_some(RunProfileOptions { dir, top }), rest))
+}
+
+/// Removes `--bin <name>` from the `tonic run` arguments.
+fn take_bin_flag(args: Vec<String>) -> Result<(Option<String>, Vec<String>), i32> {
+    let mut bin = None;
+    let mut rest = Vec::new();
+    let mut args = args.into_iter();
+
+    while let Some(arg) = args.next() {
+        if arg != "--bin" {
+            rest.push(arg);
+            continue;
+        }
+        let Some(name) = args.next() else {
+            return Err(
+                CliDiagnostic::usage_with_hint("missing value for --bin", RUN_BIN_USAGE).emit(),
+            );
+        };
+        bin = Some(name);
+    }
+
+    Ok((bin, rest))
+}
+
+/// Print the top functions to stderr and write `<stem>.folded` and
+/// `<stem>.trace.json` to the profile directory.
+fn report_run_profile(
+    options: &RunProfileOptions,
+    source_path: &str,
+    function_profile: &FunctionProfile,
+) {
+    for line in function_profile.render_table(options.top) {
+        eprintln!("{line}");
+    }
+
+    let path = std::path::Path::new(source_path);
+    let stem = path
+        .canonicalize()
+        .ok()
+        .as_deref()
+        .unwrap_or(path)
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().into_owned())
+        .unwrap_or_else(|| "run".to_string());
+    let collapsed_path = options.dir.join(format!("{stem}.folded"));
+    let trace_path = options.dir.join(format!("{stem}.trace.json"));
+    let trace = format!("{}\n", function_profile.render_chrome_trace());
+
+    let written = std::fs::create_dir_all(&options.dir)
+        .and_then(|()| std::fs::write(&collapsed_path, function_profile.render_collapsed()))
+        .and_then(|()| std::fs::write(&trace_path, trace));
+    match written {
+        Ok(()) => eprintln!(
+            "profile written to {} and {}",
+            collapsed_path.display(),
+            trace_path.display()
+        ),
+        Err(error) => eprintln!(
+            "warning: failed to write profile to {}: {error}",
+            options.dir.display()
+        ),
+    }
+}
+
 pub(super) fn handle_run_native_artifact(
     path: &str,
     profiler: &mut Option<profiling::PhaseProfiler>,
@@ -303,8 +468,3 @@ pub(super) fn maybe_trace_type_summary(signature_count: usize) {
         eprintln!("type-signatures {signature_count}");
     }
 }
-
-pub(super) fn run_placeholder(command: &str) -> i32 {
-    println!("tonic {command} command skeleton");
-    EXIT_OK
-}
@@ -0,0 +1,171 @@
+use super::*;
+use crate::runtime::evaluate_named_function_with_args;
+use manifest::{discover_tasks, load_task_source, ProjectTask};
+
+const TASK_USAGE: &str = "usage: tonic task <name> [args...] | tonic task --list";
+
+pub(super) fn handle_task(args: Vec<String>) -> i32 {
+    if matches!(args.first().map(String::as_str), Some("-h" | "--help")) {
+        print_task_help();
+        return EXIT_OK;
+    }
+
+    let Some(name) = args.first() else {
+        return CliDiagnostic::usage_with_hint(
+            "missing required <name>",
+            "run `tonic task --list` to see the available tasks",
+        )
+        .emit();
+    };
+
+    let project_root = std::path::Path::new(".");
+    let tasks = match discover_tasks(project_root) {
+        Ok(tasks) => tasks,
+        Err(error) => return CliDiagnostic::failure(error).emit(),
+    };
+
+    if name == "--list" {
+        if let Some(extra) = args.get(1) {
+            return CliDiagnostic::usage_with_hint(
+                format!("unexpected argument '{extra}'"),
+                TASK_USAGE,
+            )
+            .emit();
+        }
+        print_task_list(&tasks);
+        return EXIT_OK;
+    }
+
+    let Some(task) = tasks.iter().find(|task| task.name == *name) else {
+        return CliDiagnostic::usage_with_hint(
+            format!("unknown task '{name}'"),
+            "run `tonic task --list` to see the available tasks",
+        )
+        .emit();
+    };
+
+    run_task(project_root, task, &args[1..])
+}
+
+/// Compiles the task with the project's modules and calls its `run/1` with
+/// the remaining arguments as a list of strings.
+fn run_task(project_root: &std::path::Path, task: &ProjectTask, task_args: &[String]) -> i32 {
+    let cwd = std::env::current_dir().unwrap_or_else(|_| std::path::PathBuf::from("."));
+    let mut argv = vec![task.name.clone()];
+    argv.extend(task_args.iter().cloned());
+    let mut observed_run = ObservabilityRun::from_env("task", &command_argv("task", &argv), &cwd);
+    let mut profiler = profiling::PhaseProfiler::from_env("task");
+    let task_path = task.path.display().to_string();
+
+    let source =
+        match observe_phase_result(&mut profiler, &mut observed_run, "task.load_source", || {
+            load_task_source(project_root, task)
+        }) {
+            Ok(source) => source,
+            Err(error) => {
+                let exit_code = CliDiagnostic::failure(error.clone()).emit();
+                return finalize_observed_run(
+                    &mut observed_run,
+                    exit_code,
+                    Some(make_observability_error(
+                        "io_error",
+                        "task.load_source",
+                        error,
+                        None,
+                    )),
+                );
+            }
+        };
+
+    let ir = match compile_source_to_ir(&source, &mut profiler, &mut observed_run) {
+        Ok(ir) => ir,
+        Err(error) => {
+            let obs_error = error.to_observability_error(&task_path, &source);
+            let exit_code = error.into_diagnostic(Some(&task_path), &source).emit();
+            return finalize_observed_run(&mut observed_run, exit_code, Some(obs_error));
+        }
+    };
+
+    let task_argv = RuntimeValue::List(
+        task_args
+            .iter()
+            .map(|arg| RuntimeValue::String(arg.clone()))
+            .collect(),
+    );
+    let value = match observe_phase_result(&mut profiler, &mut observed_run, "task.run", || {
+        evaluate_named_function_with_args(&ir, &format!("{}.run", task.module), &[task_argv])
+    }) {
+        Ok(value) => value,
+        Err(error) => {
+            let message = error.to_string();
+            let source_info = observability_error_source(&task_path, &source, error.offset());
+            let exit_code = CliDiagnostic::failure_with_filename_and_source(
+                message.clone(),
+                Some(&task_path),
+                &source,
+                error.offset(),
+            )
+            .emit();
+            return finalize_observed_run(
+                &mut observed_run,
+                exit_code,
+                Some(make_observability_error(
+                    "runtime_error",
+                    "task.run",
+                    message,
+                    source_info,
+                )),
+            );
+        }
+    };
+
+    // A task reports failure by returning `err(reason)` or `{:error, reason}`.
+    let failure = match &value {
+        RuntimeValue::ResultErr(reason) => Some(reason.render()),
+        RuntimeValue::Tuple(tag, reason) if **tag == RuntimeValue::Atom("error".to_string()) => {
+            Some(reason.render())
+        }
+        _ => None,
+    };
+    if let Some(reason) = failure {
+        let message = format!("task '{}' failed: {reason}", task.name);
+        let exit_code = CliDiagnostic::failure(message.clone()).emit();
+        return finalize_observed_run(
+            &mut observed_run,
+            exit_code,
+            Some(make_observability_error(
+                "runtime_error",
+                "task.run",
+                message,
+                None,
+            )),
+        );
+    }
+
+    finalize_observed_run(&mut observed_run, EXIT_OK, None)
+}
+
+/// `tonic task --list`: one line per task with its `@shortdoc`, and the
+/// dependency it comes from.
+fn print_task_list(tasks: &[ProjectTask]) {
+    if tasks.is_empty() {
+        println!(
+            "no tasks found; add modules with a public run/1 under tasks/ or list them in [tasks]"
+        );
+        return;
+    }
+
+    let mut tasks = tasks.iter().collect::<Vec<_>>();
+    tasks.sort_by(|left, right| left.name.cmp(&right.name));
+    let width = tasks.iter().map(|task| task.name.len()).max().unwrap_or(0);
+    for task in tasks {
+        let mut line = format!("tonic task {:<width$}", task.name);
+        if let Some(shortdoc) = &task.shortdoc {
+            line.push_str(&format!("  # {shortdoc}"));
+        }
+        if let Some(dependency) = &task.dependency {
+            line.push_str(&format!(" (from {dependency})"));
+        }
+        println!("{}", line.trim_end());
+    }
+}
@@ -6,6 +6,15 @@ pub(super) fn handle_test(args: Vec<String>) -> i32 {
         return EXIT_OK;
     }
 
+    if let Some(exit_code) = run_workspace_members(
+        "test",
+        &args,
+        &["--report-file", "--cover-output"],
+        handle_test,
+    ) {
+        return exit_code;
+    }
+
     if args.is_empty() {
         return CliDiagnostic::usage_with_hint(
             "missing required <path>",
@@ -16,11 +25,21 @@ pub(super) fn handle_test(args: Vec<String>) -> i32 {
 
     let source_path = args[0].clone();
     let mut format = TestOutputFormat::Text;
-    let mut filter: Option<String> = None;
+    let mut report_file: Option<String> = None;
+    let mut selection = TestSelection::default();
+    let mut rerun_failed = false;
     let mut list_only = false;
     let mut fail_fast = false;
+    let mut update_snapshots = false;
+    let mut record_cassettes = false;
+    let mut backend = TestBackend::Interpreter;
     let mut seed: Option<u64> = None;
     let mut timeout: Option<u64> = None;
+    let mut jobs: Option<usize> = None;
+    let mut coverage: Option<CoverageOptions> = None;
+    let mut cover_format = CoverageFormat::Text;
+    let mut cover_output: Option<String> = None;
+    let mut cover_min: Option<f64> = None;
     let mut index = 1;
 
     while index < args.len() {
@@ -29,15 +48,15 @@ pub(super) fn handle_test(args: Vec<String>) -> i32 {
                 let Some(value) = args.get(index + 1) else {
                     return CliDiagnostic::usage_with_hint(
                         "missing value for --format",
-                        "usage: tonic test <path> --format <text|json>",
+                        "usage: tonic test <path> --format <text|json|junit|tap>",
                     )
                     .emit();
                 };
 
                 let Some(parsed) = TestOutputFormat::parse(value) else {
                     return CliDiagnostic::usage_with_hint(
-                        format!("unsupported format '{value}' (expected 'text' or 'json')"),
-                        "valid formats: text, json",
+                        format!("unsupported format '{value}' (expected 'text', 'json', 'junit' or 'tap')"),
+                        "valid formats: text, json, junit, tap",
                     )
                     .emit();
                 };
@@ -45,6 +64,18 @@ pub(super) fn handle_test(args: Vec<String>) -> i32 {
                 format = parsed;
                 index += 2;
             }
+            "--report-file" => {
+                let Some(value) = args.get(index + 1) else {
+                    return CliDiagnostic::usage_with_hint(
+                        "missing value for --report-file",
+                        "usage: tonic test <path> --format <json|junit|tap> --report-file <path>",
+                    )
+                    .emit();
+                };
+
+                report_file = Some(value.clone());
+                index += 2;
+            }
             "--list" => {
                 list_only = true;
                 index += 1;
@@ -53,6 +84,34 @@ pub(super) fn handle_test(args: Vec<String>) -> i32 {
                 fail_fast = true;
                 index += 1;
             }
+            "--backend" => {
+                let Some(value) = args.get(index + 1) else {
+                    return CliDiagnostic::usage_with_hint(
+                        "missing value for --backend",
+                        "usage: tonic test <path> --backend <interpreter|native|both>",
+                    )
+                    .emit();
+                };
+
+                let Some(parsed) = TestBackend::parse(value) else {
+                    return CliDiagnostic::usage_with_hint(
+                        format!("unsupported backend '{value}' (expected 'interpreter', 'native' or 'both')"),
+                        "valid backends: interpreter, native, both",
+                    )
+                    .emit();
+                };
+
+                backend = parsed;
+                index += 2;
+            }
+            "--update-snapshots" => {
+                update_snapshots = true;
+                index += 1;
+            }
+            "--record-cassettes" => {
+                record_cassettes = true;
+                index += 1;
+            }
             "--seed" => {
                 let Some(value) = args.get(index + 1) else {
                     return CliDiagnostic::usage_with_hint(
@@ -93,6 +152,26 @@ pub(super) fn handle_test(args: Vec<String>) -> i32 {
                 timeout = Some(parsed);
                 index += 2;
             }
+            "--jobs" => {
+                let Some(value) = args.get(index + 1) else {
+                    return CliDiagnostic::usage_with_hint(
+                        "missing value for --jobs",
+                        "usage: tonic test <path> --jobs <number>",
+                    )
+                    .emit();
+                };
+
+                let Some(parsed) = value.parse::<usize>().ok().filter(|jobs| *jobs > 0) else {
+                    return CliDiagnostic::usage_with_hint(
+                        format!("invalid job count '{value}' (expected a positive integer)"),
+                        "usage: tonic test <path> --jobs <number>",
+                    )
+                    .emit();
+                };
+
+                jobs = Some(parsed);
+                index += 2;
+            }
             "--filter" => {
                 let Some(value) = args.get(index + 1) else {
                     return CliDiagnostic::usage_with_hint(
@@ -102,7 +181,104 @@ pub(super) fn handle_test(args: Vec<String>) -> i32 {
                     .emit();
                 };
 
-                filter = Some(value.clone());
+                selection.filter = Some(value.clone());
+                index += 2;
+            }
+            flag @ ("--only" | "--exclude") => {
+                let Some(value) = args.get(index + 1) else {
+                    return CliDiagnostic::usage_with_hint(
+                        format!("missing value for {flag}"),
+                        format!("usage: tonic test <path> {flag} <tag[:value]>"),
+                    )
+                    .emit();
+                };
+
+                let Some(parsed) = TagFilter::parse(value) else {
+                    return CliDiagnostic::usage_with_hint(
+                        format!("invalid tag filter '{value}' (expected 'tag' or 'tag:value')"),
+                        format!("usage: tonic test <path> {flag} <tag[:value]>"),
+                    )
+                    .emit();
+                };
+
+                if flag == "--only" {
+                    selection.only.push(parsed);
+                } else {
+                    selection.exclude.push(parsed);
+                }
+                index += 2;
+            }
+            "--failed" => {
+                rerun_failed = true;
+                index += 1;
+            }
+            "--cover" => {
+                coverage.get_or_insert_with(CoverageOptions::default);
+                index += 1;
+            }
+            "--cover-stdlib" => {
+                coverage
+                    .get_or_insert_with(CoverageOptions::default)
+                    .include_stdlib = true;
+                index += 1;
+            }
+            "--cover-format" => {
+                let Some(value) = args.get(index + 1) else {
+                    return CliDiagnostic::usage_with_hint(
+                        "missing value for --cover-format",
+                        "usage: tonic test <path> --cover-format <text|lcov|cobertura>",
+                    )
+                    .emit();
+                };
+
+                let Some(parsed) = CoverageFormat::parse(value) else {
+                    return CliDiagnostic::usage_with_hint(
+                        format!("unsupported coverage format '{value}' (expected 'text', 'lcov' or 'cobertura')"),
+                        "valid coverage formats: text, lcov, cobertura",
+                    )
+                    .emit();
+                };
+
+                coverage.get_or_insert_with(CoverageOptions::default);
+                cover_format = parsed;
+                index += 2;
+            }
+            "--cover-output" => {
+                let Some(value) = args.get(index + 1) else {
+                    return CliDiagnostic::usage_with_hint(
+                        "missing value for --cover-output",
+                        "usage: tonic test <path> --cover-format <lcov|cobertura> --cover-output <path>",
+                    )
+                    .emit();
+                };
+
+                coverage.get_or_insert_with(CoverageOptions::default);
+                cover_output = Some(value.clone());
+                index += 2;
+            }
+            "--cover-min" => {
+                let Some(value) = args.get(index + 1) else {
+                    return CliDiagnostic::usage_with_hint(
+                        "missing value for --cover-min",
+                        "usage: tonic test <path> --cover-min <percent>",
+                    )
+                    .emit();
+                };
+
+                let Some(parsed) = value
+                    .parse::<f64>()
+                    .ok()
+                    .filter(|percent| (0.0..=100.0).contains(percent))
+                else {
+                    return CliDiagnostic::usage_with_hint(
+                        format!("invalid coverage threshold '{value}' (expected a percentage between 0 and 100)"),
+                        "usage: tonic test <path> --cover-min <percent>",
+                    )
+                    .emit();
+                };
+
+                coverage.get_or_insert_with(CoverageOptions::default);
+                cover_min = Some(parsed);
                 index += 2;
             }
             other => {
@@ -118,21 +294,48 @@ pub(super) fn handle_test(args: Vec<String>) -> i32 {
     let cwd = std::env::current_dir().unwrap_or_else(|_| std::path::PathBuf::from("."));
     let mut observed_run = ObservabilityRun::from_env("test", &command_argv("test", &args), &cwd);
     if let Some(observed_run) = observed_run.as_mut() {
-        observed_run.record_metadata(
-            "format",
-            match format {
-                TestOutputFormat::Text => "text",
-                TestOutputFormat::Json => "json",
-            },
-        );
-        if let Some(ref f) = filter {
+        observed_run.record_metadata("format", format.name());
+        observed_run.record_metadata("backend", backend.name());
+        if let Some(ref f) = selection.filter {
             observed_run.record_metadata("filter", f.clone());
         }
     }
 
+    if cover_output.is_some() && cover_format == CoverageFormat::Text {
+        return CliDiagnostic::usage_with_hint(
+            "--cover-output requires --cover-format lcov or cobertura",
+            "usage: tonic test <path> --cover-format <lcov|cobertura> --cover-output <path>",
+        )
+        .emit();
+    }
+
+    if coverage.is_some() && backend != TestBackend::Interpreter {
+        return CliDiagnostic::usage_with_hint(
+            "--cover requires the interpreter backend",
+            "drop --backend or use --backend interpreter to collect coverage",
+        )
+        .emit();
+    }
+
+    if report_file.is_some() && format == TestOutputFormat::Text {
+        return CliDiagnostic::usage_with_hint(
+            "--report-file requires --format json, junit or tap",
+            "usage: tonic test <path> --format <json|junit|tap> --report-file <path>",
+        )
+        .emit();
+    }
+
+    let failed_manifest = std::path::Path::new(FAILED_MANIFEST_PATH);
+    if rerun_failed {
+        match load_failed_tests(failed_manifest) {
+            Ok(ids) => selection.ids = Some(ids),
+            Err(error) => return CliDiagnostic::failure(error).emit(),
+        }
+    }
+
     if list_only {
         let tests = match observe_command_phase_result(&mut observed_run, "test.list_tests", || {
-            test_runner::list_tests(&source_path, filter.as_deref())
+            test_runner::list_tests(&source_path, &selection)
         }) {
             Ok(tests) => tests,
             Err(TestRunnerError::Failure(message)) => {
@@ -177,21 +380,34 @@ pub(super) fn handle_test(args: Vec<String>) -> i32 {
         };
 
         match format {
-            TestOutputFormat::Text => {
+            TestOutputFormat::Json => {
+                println!("{}", serde_json::json!({ "tests": tests }));
+            }
+            _ => {
                 for name in &tests {
                     println!("{name}");
                 }
             }
-            TestOutputFormat::Json => {
-                println!("{}", serde_json::json!({ "tests": tests }));
-            }
         }
 
         return finalize_observed_run(&mut observed_run, EXIT_OK, None);
     }
 
     let report = match observe_command_phase_result(&mut observed_run, "test.run_suite", || {
-        test_runner::run(&source_path, filter.as_deref(), fail_fast, seed, timeout)
+        test_runner::run(
+            &source_path,
+            &selection,
+            fail_fast,
+            seed,
+            timeout,
+            coverage,
+            jobs.unwrap_or_else(|| {
+                std::thread::available_parallelism().map_or(1, std::num::NonZeroUsize::get)
+            }),
+            update_snapshots,
+            record_cassettes,
+            backend,
+        )
     }) {
         Ok(report) => report,
         Err(TestRunnerError::Failure(message)) => {
@@ -235,28 +451,122 @@ pub(super) fn handle_test(args: Vec<String>) -> i32 {
         }
     };
 
+    if let Err(error) = record_failed_tests(failed_manifest, &report.results) {
+        eprintln!("warning: {error}");
+    }
+
     if let Some(observed_run) = observed_run.as_mut() {
         observed_run.record_metadata("total", report.total as u64);
         observed_run.record_metadata("passed", report.passed as u64);
         observed_run.record_metadata("failed", report.failed as u64);
     }
 
-    match format {
-        TestOutputFormat::Text => {
+    let machine_report = match format {
+        TestOutputFormat::Text => None,
+        TestOutputFormat::Json => {
+            let mut json = report.render_json();
             if let Some(s) = seed {
-                println!("Randomized with seed {s}");
-                println!();
+                json["seed"] = serde_json::json!(s);
             }
-            for line in report.render_text() {
+            if backend != TestBackend::Interpreter {
+                json["backend"] = serde_json::json!(backend.name());
+            }
+            if let Some(coverage) = &report.coverage {
+                json["coverage"] = coverage.render_json();
+            }
+            Some(format!("{json}\n"))
+        }
+        TestOutputFormat::Junit => Some(report.render_junit()),
+        TestOutputFormat::Tap => Some(report.render_tap()),
+    };
+
+    // With --report-file the machine report goes to the file and the console
+    // keeps the human-readable summary.
+    let console_text = machine_report.is_none() || report_file.is_some();
+    match (&report_file, machine_report) {
+        (Some(path), Some(contents)) => {
+            if let Err(error) = write_report_file(std::path::Path::new(path), &contents, "test") {
+                let exit_code = CliDiagnostic::failure(error.clone()).emit();
+                return finalize_observed_run(
+                    &mut observed_run,
+                    exit_code,
+                    Some(make_observability_error(
+                        "io_error",
+                        "test.report",
+                        error,
+                        None,
+                    )),
+                );
+            }
+        }
+        (None, Some(contents)) => print!("{contents}"),
+        _ => {}
+    }
+
+    if console_text {
+        if let Some(s) = seed {
+            println!("Randomized with seed {s}");
+            println!();
+        }
+        for line in report.render_text() {
+            println!("{line}");
+        }
+        if let Some(coverage) = &report.coverage {
+            println!();
+            for line in coverage.render_text() {
                 println!("{line}");
             }
         }
-        TestOutputFormat::Json => {
-            let mut json = report.render_json();
-            if let Some(s) = seed {
-                json["seed"] = serde_json::json!(s);
+        if let Some(path) = &report_file {
+            println!("test report written to {path}");
+        }
+    }
+
+    if let Some(coverage) = &report.coverage {
+        if let Some(default_output) = cover_format.default_output() {
+            let output_path = cover_output.as_deref().unwrap_or(default_output);
+            let contents = match cover_format {
+                CoverageFormat::Lcov => coverage.render_lcov(),
+                _ => coverage.render_cobertura(),
+            };
+            if let Err(error) =
+                write_report_file(std::path::Path::new(output_path), &contents, "coverage")
+            {
+                let exit_code = CliDiagnostic::failure(error.clone()).emit();
+                return finalize_observed_run(
+                    &mut observed_run,
+                    exit_code,
+                    Some(make_observability_error(
+                        "io_error",
+                        "test.coverage",
+                        error,
+                        None,
+                    )),
+                );
+            }
+            if console_text {
+                println!("coverage report written to {output_path}");
+            }
+        }
+
+        if let Some(minimum) = cover_min {
+            let actual = coverage.line_percent();
+            if actual < minimum {
+                let message = format!(
+                    "line coverage {actual:.1}% is below the --cover-min threshold of {minimum}%"
+                );
+                let exit_code = CliDiagnostic::failure(message.clone()).emit();
+                return finalize_observed_run(
+                    &mut observed_run,
+                    exit_code,
+                    Some(make_observability_error(
+                        "coverage_error",
+                        "test.coverage",
+                        message,
+                        None,
+                    )),
+                );
             }
-            println!("{json}");
         }
     }
 
@@ -276,12 +586,38 @@ pub(super) fn handle_test(args: Vec<String>) -> i32 {
     }
 }
 
+/// Write a `kind` ("test", "coverage" or "benchmark baseline") report, creating
+/// its directory.
+pub(super) fn write_report_file(
+    path: &std::path::Path,
+    contents: &str,
+    kind: &str,
+) -> Result<(), String> {
+    if let Some(parent) = path
+        .parent()
+        .filter(|parent| !parent.as_os_str().is_empty())
+    {
+        std::fs::create_dir_all(parent).map_err(|error| {
+            format!(
+                "failed to create {kind} report directory {}: {error}",
+                parent.display()
+            )
+        })?;
+    }
+    std::fs::write(path, contents)
+        .map_err(|error| format!("failed to write {kind} report {}: {error}", path.display()))
+}
+
 pub(super) fn handle_fmt(args: Vec<String>) -> i32 {
     if matches!(args.first().map(String::as_str), Some("-h" | "--help")) {
         print_fmt_help();
         return EXIT_OK;
     }
 
+    if let Some(exit_code) = run_workspace_members("fmt", &args, &[], handle_fmt) {
+        return exit_code;
+    }
+
     if args.is_empty() {
         return CliDiagnostic::usage_with_hint(
             "missing required <path>",
@@ -292,10 +628,40 @@ pub(super) fn handle_fmt(args: Vec<String>) -> i32 {
 
     let source_path = args[0].clone();
     let mut mode = FormatMode::Write;
+    let mut range: Option<LineRange> = None;
+    let mut index = 1;
+
+    while index < args.len() {
+        match args[index].as_str() {
+            "--check" => {
+                mode = FormatMode::Check;
+                index += 1;
+            }
+            "--diff" => {
+                mode = FormatMode::Diff;
+                index += 1;
+            }
+            "--range" => {
+                let Some(value) = args.get(index + 1) else {
+                    return CliDiagnostic::usage_with_hint(
+                        "missing value for --range",
+                        "usage: tonic fmt <path> --range <start>:<end>",
+                    )
+                    .emit();
+                };
 
-    for argument in args.iter().skip(1) {
-        match argument.as_str() {
-            "--check" => mode = FormatMode::Check,
+                match LineRange::parse(value) {
+                    Ok(parsed) => range = Some(parsed),
+                    Err(message) => {
+                        return CliDiagnostic::usage_with_hint(
+                            message,
+                            "usage: tonic fmt <path> --range <start>:<end>",
+                        )
+                        .emit()
+                    }
+                }
+                index += 2;
+            }
             other => {
                 return CliDiagnostic::usage_with_hint(
                     format!("unexpected argument '{other}'"),
@@ -306,6 +672,10 @@ pub(super) fn handle_fmt(args: Vec<String>) -> i32 {
         }
     }
 
+    if source_path == "-" {
+        return format_stdin(mode, range);
+    }
+
     let cwd = std::env::current_dir().unwrap_or_else(|_| std::path::PathBuf::from("."));
     let mut observed_run = ObservabilityRun::from_env("fmt", &command_argv("fmt", &args), &cwd);
     if let Some(observed_run) = observed_run.as_mut() {
@@ -314,12 +684,13 @@ pub(super) fn handle_fmt(args: Vec<String>) -> i32 {
             match mode {
                 FormatMode::Write => "write",
                 FormatMode::Check => "check",
+                FormatMode::Diff => "diff",
             },
         );
     }
 
     let report = match observe_command_phase_result(&mut observed_run, "fmt.format_path", || {
-        format_path(&source_path, mode)
+        format_path(&source_path, mode, range)
     }) {
         Ok(report) => report,
         Err(error) => {
@@ -342,7 +713,9 @@ pub(super) fn handle_fmt(args: Vec<String>) -> i32 {
         observed_run.record_metadata("changed_files", report.changed_files as u64);
     }
 
-    if mode == FormatMode::Check && report.changed_files > 0 {
+    print!("{}", report.diff);
+
+    if mode != FormatMode::Write && report.changed_files > 0 {
         let suffix = if report.changed_files == 1 { "" } else { "s" };
         let message = format!(
             "formatting required for {} file{} (run `tonic fmt <path>` to apply fixes)",
@@ -364,3 +737,33 @@ pub(super) fn handle_fmt(args: Vec<String>) -> i32 {
     println!("fmt: ok");
     finalize_observed_run(&mut observed_run, EXIT_OK, None)
 }
+
+/// `tonic fmt -`: format stdin to stdout, using the formatter config that
+/// applies to the current directory.
+fn format_stdin(mode: FormatMode, range: Option<LineRange>) -> i32 {
+    let mut source = String::new();
+    if let Err(error) = std::io::Read::read_to_string(&mut std::io::stdin(), &mut source) {
+        return CliDiagnostic::failure(format!("failed to read stdin: {error}")).emit();
+    }
+
+    let cwd = std::env::current_dir().unwrap_or_else(|_| std::path::PathBuf::from("."));
+    let options = match load_format_options(&cwd) {
+        Ok(options) => options,
+        Err(error) => return CliDiagnostic::failure(error).emit(),
+    };
+    let formatted = format_text(&source, &options, range);
+
+    match mode {
+        FormatMode::Write => {
+            print!("{formatted}");
+            EXIT_OK
+        }
+        FormatMode::Check | FormatMode::Diff if source != formatted => {
+            if mode == FormatMode::Diff {
+                print!("{}", unified_diff("<stdin>", &source, &formatted));
+            }
+            CliDiagnostic::failure("formatting required for <stdin>").emit()
+        }
+        FormatMode::Check | FormatMode::Diff => EXIT_OK,
+    }
+}
@@ -0,0 +1,148 @@
+use super::*;
+use manifest::{Workspace, WorkspaceMember};
+
+/// `tonic <command> <workspace-root>` and `tonic <command> [path] -p <name>`:
+/// runs `handler` on every workspace member, or on the one `-p` selects, with
+/// the path argument replaced by the member directory. Returns `None` when
+/// the arguments don't involve a workspace so the caller handles them as
+/// usual. Flags in `single_output_flags` name one output file and are only
+/// accepted when a single member runs.
+pub(super) fn run_workspace_members(
+    command: &str,
+    args: &[String],
+    single_output_flags: &[&str],
+    handler: fn(Vec<String>) -> i32,
+) -> Option<i32> {
+    let mut package = None;
+    let mut rest = Vec::new();
+    let mut index = 0;
+    while index < args.len() {
+        match args[index].as_str() {
+            "-p" | "--package" => {
+                let Some(name) = args.get(index + 1) else {
+                    return Some(
+                        CliDiagnostic::usage_with_hint(
+                            "missing value for --package",
+                            format!("usage: tonic {command} [path] -p <member>"),
+                        )
+                        .emit(),
+                    );
+                };
+                package = Some(name.clone());
+                index += 2;
+            }
+            other => {
+                rest.push(other.to_string());
+                index += 1;
+            }
+        }
+    }
+
+    // With -p the path is optional and defaults to the current directory.
+    let path_given = rest
+        .first()
+        .is_some_and(|arg| arg == "-" || !arg.starts_with('-'));
+    if !path_given {
+        package.as_ref()?;
+        rest.insert(0, ".".to_string());
+    }
+
+    let path = std::path::Path::new(&rest[0]);
+    let workspace = if package.is_some() {
+        Workspace::find(path)
+    } else if path.is_dir() {
+        Workspace::load(path)
+    } else {
+        Ok(None)
+    };
+    let workspace = match workspace {
+        Ok(Some(workspace)) => workspace,
+        Ok(None) => {
+            return package.map(|_| {
+                CliDiagnostic::usage_with_hint(
+                    format!(
+                        "--package needs a workspace, but no tonic.toml with [workspace] was found at or above {}",
+                        path.display()
+                    ),
+                    "list the member directories under [workspace] members in the root tonic.toml",
+                )
+                .emit()
+            });
+        }
+        Err(message) => return Some(CliDiagnostic::failure(message).emit()),
+    };
+
+    let members: Vec<&WorkspaceMember> = match &package {
+        Some(name) => match workspace.member(name) {
+            Some(member) => vec![member],
+            None => {
+                let names = workspace
+                    .members
+                    .iter()
+                    .map(|member| member.name.as_str())
+                    .collect::<Vec<_>>();
+                return Some(
+                    CliDiagnostic::usage_with_hint(
+                        format!("no workspace member named '{name}'"),
+                        format!("workspace members: {}", names.join(", ")),
+                    )
+                    .emit(),
+                );
+            }
+        },
+        None => workspace.members.iter().collect(),
+    };
+
+    if members.len() > 1 {
+        if let Some(flag) = rest[1..]
+            .iter()
+            .find(|arg| single_output_flags.contains(&arg.as_str()))
+        {
+            return Some(
+                CliDiagnostic::usage_with_hint(
+                    format!("{flag} cannot be used when running every workspace member"),
+                    format!("select one member with `tonic {command} -p <name>`"),
+                )
+                .emit(),
+            );
+        }
+    }
+
+    let run_member = |member: &WorkspaceMember| {
+        let mut member_args = rest.clone();
+        member_args[0] = member.root.display().to_string();
+        handler(member_args)
+    };
+    if let [member] = members.as_slice() {
+        return Some(run_member(member));
+    }
+
+    let mut exit_code = EXIT_OK;
+    let mut failed = Vec::new();
+    for (index, member) in members.iter().enumerate() {
+        if index > 0 {
+            println!();
+        }
+        println!("==> {} ({})", member.name, workspace.relative_path(member));
+        let member_exit_code = run_member(member);
+        if member_exit_code != EXIT_OK {
+            if exit_code == EXIT_OK {
+                exit_code = member_exit_code;
+            }
+            failed.push(member.name.as_str());
+        }
+    }
+
+    println!();
+    if failed.is_empty() {
+        println!("workspace: {} members ok", members.len());
+    } else {
+        println!(
+            "workspace: {} of {} members failed: {}",
+            failed.len(),
+            members.len(),
+            failed.join(", ")
+        );
+    }
+    Some(exit_code)
+}
@@ -0,0 +1,384 @@
+//! Debug Adapter Protocol server for the interpreter (`tonic dap`).
+//!
+//! The adapter speaks DAP over stdio. Requests are read on the calling thread;
+//! the debuggee runs on its own interpreter thread with a [`session::DapHook`]
+//! installed, which blocks at breakpoints and answers inspection requests that
+//! this thread forwards while execution is paused.
+
+mod program;
+mod protocol;
+mod session;
+
+use crate::cli_diag::{CliDiagnostic, EXIT_FAILURE, EXIT_OK};
+use crate::interop::{
+    capture_host_output_with_stdin_and_forward, host_stdout_was_observed,
+    reset_host_stdout_observed, HostOutputForwarder,
+};
+use crate::runtime::debug::with_debug_hook;
+use crate::runtime::{evaluate_entrypoint, evaluate_named_function, RuntimeValue};
+use program::{DebugProgram, Entry};
+use protocol::{read_message, DapWriter, Request, SharedWriter};
+use serde_json::{json, Value};
+use session::{DapHook, DebugControl, PausedCommand, THREAD_ID};
+use std::sync::atomic::Ordering;
+use std::sync::mpsc::{channel, Sender};
+use std::sync::Arc;
+
+const INTERPRETER_STACK_SIZE: usize = 64 * 1024 * 1024;
+
+pub(crate) fn handle_dap(args: Vec<String>) -> i32 {
+    match args.first().map(String::as_str) {
+        None => {}
+        Some("-h" | "--help") => {
+            print_dap_help();
+            return EXIT_OK;
+        }
+        Some(other) => {
+            return CliDiagnostic::usage_with_hint(
+                format!("unexpected argument '{other}'"),
+                "run `tonic dap --help` for usage",
+            )
+            .emit();
+        }
+    }
+
+    let writer = DapWriter::shared(Box::new(std::io::stdout()));
+    let mut server = DapServer::new(writer);
+    let stdin = std::io::stdin();
+    let mut reader = stdin.lock();
+
+    loop {
+        match read_message(&mut reader) {
+            Ok(Some(request)) => {
+                if server.dispatch(request) == Flow::Exit {
+                    return EXIT_OK;
+                }
+            }
+            Ok(None) => return EXIT_OK,
+            Err(message) => {
+                eprintln!("error: {message}");
+                return EXIT_FAILURE;
+            }
+        }
+    }
+}
+
+fn print_dap_help() {
+    println!(
+        "Usage:\n  tonic dap\n\n\
+         Speaks the Debug Adapter Protocol over stdin/stdout.\n\n\
+         Launch arguments:\n\
+         \x20 program      .tn source file or project directory to debug (required)\n\
+         \x20 test         Debug a single test case, e.g. \"MathTest.test_add\"\n\
+         \x20 stopOnEntry  Pause before the first executed line\n"
+    );
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Flow {
+    Continue,
+    Exit,
+}
+
+struct Launch {
+    program: Arc<DebugProgram>,
+    stop_on_entry: bool,
+}
+
+struct Running {
+    commands: Sender<PausedCommand>,
+}
+
+struct DapServer {
+    writer: SharedWriter,
+    control: Arc<DebugControl>,
+    launch: Option<Launch>,
+    running: Option<Running>,
+}
+
+impl DapServer {
+    fn new(writer: SharedWriter) -> Self {
+        Self {
+            writer,
+            control: Arc::new(DebugControl::default()),
+            launch: None,
+            running: None,
+        }
+    }
+
+    fn dispatch(&mut self, request: Request) -> Flow {
+        let result = match request.command.as_str() {
+            "initialize" => Ok(json!({
+                "supportsConfigurationDoneRequest": true,
+                "supportsConditionalBreakpoints": true,
+                "supportsEvaluateForHovers": true,
+                "supportsTerminateRequest": true,
+            })),
+            "launch" => self.launch(&request.arguments),
+            "setBreakpoints" => self.set_breakpoints(&request.arguments),
+            "setExceptionBreakpoints" => Ok(json!({ "breakpoints": [] })),
+            "configurationDone" => self.start(),
+            "threads" => Ok(json!({ "threads": [{ "id": THREAD_ID, "name": "main" }] })),
+            "pause" => {
+                self.control.pause_requested.store(true, Ordering::SeqCst);
+                Ok(json!({}))
+            }
+            "stackTrace" | "scopes" | "variables" | "evaluate" | "continue" | "next" | "stepIn"
+            | "stepOut" => return self.forward_to_paused(request),
+            "disconnect" | "terminate" => {
+                self.abort();
+                self.respond(&request, Ok(json!({})));
+                return if request.command == "disconnect" {
+                    Flow::Exit
+                } else {
+                    Flow::Continue
+                };
+            }
+            other => Err(format!("unsupported request: {other}")),
+        };
+
+        let initialized = request.command == "launch" && result.is_ok();
+        self.respond(&request, result);
+        if initialized {
+            self.writer
+                .lock()
+                .expect("dap writer lock")
+                .event("initialized", json!({}));
+        }
+        Flow::Continue
+    }
+
+    fn respond(&self, request: &Request, result: Result<Value, String>) {
+        let mut writer = self.writer.lock().expect("dap writer lock");
+        match result {
+            Ok(body) => writer.response(request, body),
+            Err(message) => writer.error_response(request, message),
+        }
+    }
+
+    fn launch(&mut self, arguments: &Value) -> Result<Value, String> {
+        if self.launch.is_some() {
+            return Err("a program has already been launched".to_string());
+        }
+
+        let program = arguments["program"]
+            .as_str()
+            .ok_or_else(|| "launch requires a 'program' path".to_string())?;
+        let program = DebugProgram::load(program, arguments["test"].as_str())?;
+
+        self.launch = Some(Launch {
+            program: Arc::new(program),
+            stop_on_entry: arguments["stopOnEntry"].as_bool().unwrap_or(false),
+        });
+        Ok(json!({}))
+    }
+
+    fn set_breakpoints(&mut self, arguments: &Value) -> Result<Value, String> {
+        let launch = self
+            .launch
+            .as_ref()
+            .ok_or_else(|| "setBreakpoints requires a launched program".to_string())?;
+        let path = arguments["source"]["path"].as_str().unwrap_or("");
+        let requested = arguments["breakpoints"]
+            .as_array()
+            .cloned()
+            .unwrap_or_default();
+
+        if !launch.program.matches_source(path) {
+            let unverified = requested
+                .iter()
+                .map(|_| json!({ "verified": false, "message": "source is not part of the debuggee" }))
+                .collect::<Vec<_>>();
+            return Ok(json!({ "breakpoints": unverified }));
+        }
+
+        let mut table = self
+            .control
+            .breakpoints
+            .lock()
+            .expect("breakpoint table lock");
+        table.clear();
+
+        let breakpoints = requested
+            .iter()
+            .map(|breakpoint| {
+                let line = breakpoint["line"].as_u64().unwrap_or(0) as usize;
+                match launch.program.resolve_breakpoint_line(line) {
+                    Some(resolved) => {
+                        let condition = breakpoint["condition"]
+                            .as_str()
+                            .map(str::trim)
+                            .filter(|condition| !condition.is_empty())
+                            .map(str::to_string);
+                        table.insert(resolved, condition);
+                        json!({ "verified": true, "line": resolved })
+                    }
+                    None => json!({
+                        "verified": false,
+                        "line": line,
+                        "message": "no executable code at or after this line",
+                    }),
+                }
+            })
+            .collect::<Vec<_>>();
+
+        Ok(json!({ "breakpoints": breakpoints }))
+    }
+
+    fn start(&mut self) -> Result<Value, String> {
+        if self.running.is_some() {
+            return Ok(json!({}));
+        }
+        let launch = self
+            .launch
+            .as_ref()
+            .ok_or_else(|| "configurationDone requires a launched program".to_string())?;
+
+        let (commands, receiver) = channel();
+        let hook = DapHook::new(
+            Arc::clone(&launch.program),
+            Arc::clone(&self.control),
+            Arc::clone(&self.writer),
+            receiver,
+            launch.stop_on_entry,
+        );
+        let program = Arc::clone(&launch.program);
+        let writer = Arc::clone(&self.writer);
+
+        std::thread::Builder::new()
+            .name("tonic-dap-interpreter".to_string())
+            .stack_size(INTERPRETER_STACK_SIZE)
+            .spawn(move || {
+                let exit_code = with_debug_hook(Box::new(hook), || run_debuggee(&program, &writer));
+                let mut writer = writer.lock().expect("dap writer lock");
+                writer.event("exited", json!({ "exitCode": exit_code }));
+                writer.event("terminated", json!({}));
+            })
+            .map_err(|error| format!("failed to start interpreter thread: {error}"))?;
+
+        self.running = Some(Running { commands });
+        Ok(json!({}))
+    }
+
+    fn forward_to_paused(&mut self, request: Request) -> Flow {
+        let paused = self.control.paused.load(Ordering::SeqCst);
+        let Some(running) = self.running.as_ref().filter(|_| paused) else {
+            self.respond(&request, Err("the debuggee is not paused".to_string()));
+            return Flow::Continue;
+        };
+
+        if matches!(
+            request.command.as_str(),
+            "continue" | "next" | "stepIn" | "stepOut"
+        ) {
+            self.control.paused.store(false, Ordering::SeqCst);
+        }
+
+        if running
+            .commands
+            .send(PausedCommand::Request(request.clone()))
+            .is_err()
+        {
+            self.respond(&request, Err("the debuggee has exited".to_string()));
+        }
+        Flow::Continue
+    }
+
+    fn abort(&mut self) {
+        self.control.abort.store(true, Ordering::SeqCst);
+        if let Some(running) = self.running.as_ref() {
+            let _ = running.commands.send(PausedCommand::Abort);
+        }
+    }
+}
+
+/// Evaluate the launch entry, streaming host output as DAP `output` events.
+fn run_debuggee(program: &DebugProgram, writer: &SharedWriter) -> i32 {
+    let forward_writer = Arc::clone(writer);
+    let forward: HostOutputForwarder = Box::new(move |stream, text| {
+        forward_writer
+            .lock()
+            .expect("dap writer lock")
+            .output(stream.as_str(), text);
+        Ok(())
+    });
+
+    reset_host_stdout_observed();
+    let (outcome, _) = capture_host_output_with_stdin_and_forward(Some(""), Some(forward), || {
+        match &program.entry {
+            Entry::Run => evaluate_entrypoint(&program.ir).map(|value| (value, None)),
+            Entry::Test {
+                name,
+                setup,
+                teardown,
+            } => run_test_case(program, name, setup.as_deref(), teardown.as_deref()),
+        }
+    });
+
+    let (message, exit_code) = match outcome {
+        Ok((RuntimeValue::ResultErr(reason), test_name)) => {
+            let message = match test_name {
+                Some(name) => format!(
+                    "test {name} ... FAILED\n  error: err({})\n",
+                    reason.render()
+                ),
+                None => format!("error: runtime returned err({})\n", reason.render()),
+            };
+            (message, EXIT_FAILURE)
+        }
+        Ok((_, Some(name))) => (format!("test {name} ... ok\n"), EXIT_OK),
+        Ok((value, None)) => {
+            let message = if host_stdout_was_observed() {
+                String::new()
+            } else {
+                format!("{}\n", value.render())
+            };
+            (message, EXIT_OK)
+        }
+        Err(error) => {
+            let rendered = program.render_runtime_error(error.to_string(), error.offset());
+            (format!("error: {rendered}\n"), EXIT_FAILURE)
+        }
+    };
+
+    if !message.is_empty() {
+        let category = if exit_code == EXIT_OK {
+            "stdout"
+        } else {
+            "stderr"
+        };
+        writer
+            .lock()
+            .expect("dap writer lock")
+            .output(category, message);
+    }
+    exit_code
+}
+
+type TestOutcome = Result<(RuntimeValue, Option<String>), crate::runtime::RuntimeError>;
+
+fn run_test_case(
+    program: &DebugProgram,
+    name: &str,
+    setup: Option<&str>,
+    teardown: Option<&str>,
+) -> TestOutcome {
+    if let Some(setup) = setup {
+        if let value @ RuntimeValue::ResultErr(_) = evaluate_named_function(&program.ir, setup)? {
+            return Ok((value, Some(name.to_string())));
+        }
+    }
+
+    let outcome = evaluate_named_function(&program.ir, name);
+
+    if let Some(teardown) = teardown {
+        let teardown_outcome = evaluate_named_function(&program.ir, teardown);
+        if outcome.is_ok() {
+            if let value @ RuntimeValue::ResultErr(_) = teardown_outcome? {
+                return Ok((value, Some(name.to_string())));
+            }
+        }
+    }
+
+    outcome.map(|value| (value, Some(name.to_string())))
+}
@@ -0,0 +1,300 @@
+use crate::cli_diag::failure_message_lines_with_filename_and_source;
+use crate::ir::{lower_ast_to_ir, IrProgram};
+use crate::lexer::scan_tokens;
+use crate::manifest::{inject_optional_stdlib, load_project_program};
+use crate::parser::{parse_ast, Ast};
+use crate::resolver::{resolve_ast_with_externals, ExternalModules};
+use crate::runtime::{collect_op_offsets, evaluate_named_function_with_args, RuntimeValue};
+use crate::typing::infer_types;
+use std::collections::{BTreeSet, HashMap};
+use std::path::{Path, PathBuf};
+
+const EVAL_MODULE: &str = "TonicDapEval";
+const EVAL_FN: &str = "__dap_eval__";
+
+/// What the debuggee runs once configuration is done.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(super) enum Entry {
+    /// `Demo.run/0`, exactly like `tonic run`.
+    Run,
+    /// A single `tonic test` case, wrapped in its module's setup/teardown.
+    Test {
+        name: String,
+        setup: Option<String>,
+        teardown: Option<String>,
+    },
+}
+
+/// A compiled program plus the source mapping the debugger needs.
+pub(super) struct DebugProgram {
+    pub(super) path: PathBuf,
+    pub(super) ir: IrProgram,
+    pub(super) entry: Entry,
+    source: String,
+    user_len: usize,
+    line_starts: Vec<usize>,
+    executable_lines: BTreeSet<usize>,
+    externals: ExternalModules,
+}
+
+impl DebugProgram {
+    pub(super) fn load(program: &str, test: Option<&str>) -> Result<Self, String> {
+        let path = Path::new(program);
+        let (path, source, user_len) = if path.is_dir() {
+            let (entry_path, source) = load_project_program(path)?;
+            let user_len = read_source(&entry_path)?.len();
+            (entry_path, source, user_len)
+        } else if path.is_file() {
+            let mut source = read_source(path)?;
+            let user_len = source.len();
+            inject_optional_stdlib(&mut source)?;
+            (path.to_path_buf(), source, user_len)
+        } else {
+            return Err(format!(
+                "dap launch expects a .tn source file or project directory, found '{program}'"
+            ));
+        };
+
+        let ast = compile_frontend(&source).map_err(|(message, offset)| {
+            render_error(&path.display().to_string(), &source, message, offset)
+        })?;
+        let ir = lower_ast_to_ir(&ast).map_err(|error| error.to_string())?;
+
+        let entry = match test {
+            None => Entry::Run,
+            Some(name) => test_entry(&ir, name)?,
+        };
+
+        let line_starts = std::iter::once(0)
+            .chain(source.match_indices('\n').map(|(index, _)| index + 1))
+            .collect::<Vec<_>>();
+
+        let mut program = Self {
+            path: path.canonicalize().unwrap_or(path),
+            ir,
+            entry,
+            source,
+            user_len,
+            line_starts,
+            executable_lines: BTreeSet::new(),
+            externals: module_signatures(&ast),
+        };
+
+        let mut offsets = Vec::new();
+        for function in &program.ir.functions {
+            collect_op_offsets(&function.ops, &mut offsets);
+        }
+        program.executable_lines = offsets
+            .into_iter()
+            .filter(|offset| program.is_user_offset(*offset))
+            .map(|offset| program.position(offset).0)
+            .collect();
+
+        Ok(program)
+    }
+
+    /// Whether `offset` points into the debuggee file rather than injected stdlib.
+    pub(super) fn is_user_offset(&self, offset: usize) -> bool {
+        offset < self.user_len
+    }
+
+    /// 1-based `(line, column)` of a source offset.
+    pub(super) fn position(&self, offset: usize) -> (usize, usize) {
+        let offset = offset.min(self.source.len());
+        let line_index = self
+            .line_starts
+            .partition_point(|start| *start <= offset)
+            .saturating_sub(1);
+        let line_start = self.line_starts[line_index];
+        let column = self
+            .source
+            .get(line_start..offset)
+            .map(|text| text.chars().count())
+            .unwrap_or(0);
+        (line_index + 1, column + 1)
+    }
+
+    /// Snap a requested breakpoint line to the first line at or below it that
+    /// has IR ops attached.
+    pub(super) fn resolve_breakpoint_line(&self, line: usize) -> Option<usize> {
+        self.executable_lines.range(line..).next().copied()
+    }
+
+    pub(super) fn matches_source(&self, path: &str) -> bool {
+        let requested = Path::new(path);
+        requested == self.path
+            || requested
+                .canonicalize()
+                .map(|canonical| canonical == self.path)
+                .unwrap_or(false)
+    }
+
+    pub(super) fn display_path(&self) -> String {
+        self.path.display().to_string()
+    }
+
+    /// Evaluate `expr` with `bindings` in scope, against this program's modules.
+    pub(super) fn evaluate(
+        &self,
+        expr: &str,
+        bindings: &HashMap<String, RuntimeValue>,
+    ) -> Result<RuntimeValue, String> {
+        let mut names = bindings
+            .keys()
+            .filter(|name| is_bindable_name(name))
+            .cloned()
+            .collect::<Vec<_>>();
+        names.sort();
+
+        let wrapped = format!(
+            "defmodule {EVAL_MODULE} do\n  def {EVAL_FN}({}) do\n    {}\n  end\nend\n",
+            names.join(", "),
+            expr.trim()
+        );
+
+        let ast = compile_frontend_with_externals(&wrapped, &self.externals)
+            .map_err(|(message, _)| message)?;
+        let eval_ir = lower_ast_to_ir(&ast).map_err(|error| error.to_string())?;
+
+        let mut functions = self.ir.functions.clone();
+        functions.extend(eval_ir.functions);
+        let program = IrProgram { functions };
+
+        let args = names
+            .iter()
+            .map(|name| bindings[name].clone())
+            .collect::<Vec<_>>();
+        evaluate_named_function_with_args(&program, &format!("{EVAL_MODULE}.{EVAL_FN}"), &args)
+            .map_err(|error| error.to_string())
+    }
+
+    /// Render a runtime error with the same snippet format as `tonic run`.
+    pub(super) fn render_runtime_error(&self, message: String, offset: Option<usize>) -> String {
+        render_error(&self.display_path(), &self.source, message, offset)
+    }
+}
+
+fn compile_frontend(source: &str) -> Result<Ast, (String, Option<usize>)> {
+    compile_frontend_with_externals(source, &ExternalModules::new())
+}
+
+fn compile_frontend_with_externals(
+    source: &str,
+    externals: &ExternalModules,
+) -> Result<Ast, (String, Option<usize>)> {
+    let tokens = scan_tokens(source).map_err(|error| (error.to_string(), Some(error.offset())))?;
+    let ast = parse_ast(&tokens).map_err(|error| (error.to_string(), error.offset()))?;
+    resolve_ast_with_externals(&ast, externals)
+        .map_err(|error| (error.to_string(), error.offset()))?;
+    infer_types(&ast).map_err(|error| (error.to_string(), error.offset()))?;
+    Ok(ast)
+}
+
+fn read_source(path: &Path) -> Result<String, String> {
+    std::fs::read_to_string(path)
+        .map_err(|error| format!("failed to read source file {}: {error}", path.display()))
+}
+
+fn render_error(path: &str, source: &str, message: String, offset: Option<usize>) -> String {
+    failure_message_lines_with_filename_and_source(message, Some(path), source, offset).join("\n")
+}
+
+fn test_entry(ir: &IrProgram, name: &str) -> Result<Entry, String> {
+    let has_zero_arity = |function_name: &str| {
+        ir.functions
+            .iter()
+            .any(|function| function.name == function_name && function.params.is_empty())
+    };
+
+    if !has_zero_arity(name) {
+        return Err(format!("test '{name}' was not found"));
+    }
+
+    let module_name = name
+        .rsplit_once('.')
+        .map(|(module, _)| module)
+        .unwrap_or("");
+    let setup = format!("{module_name}.setup");
+    let teardown = format!("{module_name}.teardown");
+
+    Ok(Entry::Test {
+        name: name.to_string(),
+        setup: has_zero_arity(&setup).then_some(setup),
+        teardown: has_zero_arity(&teardown).then_some(teardown),
+    })
+}
+
+fn module_signatures(ast: &Ast) -> ExternalModules {
+    let mut modules = ExternalModules::new();
+    for module in &ast.modules {
+        let functions = module
+            .functions
+            .iter()
+            .map(|function| (function.name.clone(), !function.is_private()))
+            .collect();
+        modules.insert(module.name.clone(), functions);
+    }
+    modules
+}
+
+/// Interpreter-internal bindings (`__tonic_*`) are not valid parameter names.
+fn is_bindable_name(name: &str) -> bool {
+    let mut chars = name.chars();
+    matches!(chars.next(), Some(first) if first.is_ascii_lowercase() || first == '_')
+        && !name.starts_with("__")
+        && chars.all(|ch| ch.is_ascii_alphanumeric() || ch == '_')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write_fixture(name: &str, source: &str) -> PathBuf {
+        let dir =
+            std::env::temp_dir().join(format!("tonic-dap-program-{name}-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("main.tn");
+        std::fs::write(&path, source).unwrap();
+        path
+    }
+
+    #[test]
+    fn breakpoint_lines_snap_to_next_executable_line() {
+        let path = write_fixture(
+            "snap",
+            "defmodule Demo do\n  def run() do\n\n    x = 1\n    x + 1\n  end\nend\n",
+        );
+        let program = DebugProgram::load(path.to_str().unwrap(), None).unwrap();
+
+        assert_eq!(program.resolve_breakpoint_line(3), Some(4));
+        assert_eq!(program.resolve_breakpoint_line(5), Some(5));
+        assert_eq!(program.resolve_breakpoint_line(7), None);
+    }
+
+    #[test]
+    fn evaluate_sees_frame_bindings_and_program_functions() {
+        let path = write_fixture(
+            "eval",
+            "defmodule Demo do\n  def run() do\n    double(2)\n  end\n\n  def double(x) do\n    x * 2\n  end\nend\n",
+        );
+        let program = DebugProgram::load(path.to_str().unwrap(), None).unwrap();
+        let bindings = HashMap::from([("count".to_string(), RuntimeValue::Int(20))]);
+
+        let value = program
+            .evaluate("Demo.double(count) + 2", &bindings)
+            .unwrap();
+        assert_eq!(value, RuntimeValue::Int(42));
+    }
+
+    #[test]
+    fn load_rejects_unknown_test_case() {
+        let path = write_fixture(
+            "missing-test",
+            "defmodule DemoTest do\n  def test_ok() do\n    :ok\n  end\nend\n",
+        );
+        let error = DebugProgram::load(path.to_str().unwrap(), Some("DemoTest.test_missing"))
+            .err()
+            .unwrap();
+        assert_eq!(error, "test 'DemoTest.test_missing' was not found");
+    }
+}
@@ -0,0 +1,149 @@
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::io::{BufRead, Write};
+use std::sync::{Arc, Mutex};
+
+/// A client request as framed by the Debug Adapter Protocol.
+#[derive(Debug, Clone, Deserialize)]
+pub(super) struct Request {
+    pub(super) seq: i64,
+    pub(super) command: String,
+    #[serde(default)]
+    pub(super) arguments: Value,
+}
+
+/// Read one `Content-Length` framed message. Returns `Ok(None)` on a clean EOF.
+pub(super) fn read_message(reader: &mut impl BufRead) -> Result<Option<Request>, String> {
+    let mut content_length = None;
+
+    loop {
+        let mut header = String::new();
+        let read = reader
+            .read_line(&mut header)
+            .map_err(|error| format!("failed to read dap header: {error}"))?;
+        if read == 0 {
+            return Ok(None);
+        }
+
+        let header = header.trim_end();
+        if header.is_empty() {
+            if content_length.is_some() {
+                break;
+            }
+            continue;
+        }
+
+        if let Some((name, value)) = header.split_once(':') {
+            if name.trim().eq_ignore_ascii_case("content-length") {
+                let length = value
+                    .trim()
+                    .parse::<usize>()
+                    .map_err(|_| format!("invalid dap content length '{}'", value.trim()))?;
+                content_length = Some(length);
+            }
+        }
+    }
+
+    let mut body = vec![0; content_length.unwrap_or(0)];
+    reader
+        .read_exact(&mut body)
+        .map_err(|error| format!("failed to read dap body: {error}"))?;
+
+    serde_json::from_slice(&body)
+        .map(Some)
+        .map_err(|error| format!("invalid dap message: {error}"))
+}
+
+/// Serializes responses and events with a monotonically increasing `seq`.
+pub(super) struct DapWriter {
+    out: Box<dyn Write + Send>,
+    seq: i64,
+}
+
+pub(super) type SharedWriter = Arc<Mutex<DapWriter>>;
+
+impl DapWriter {
+    pub(super) fn shared(out: Box<dyn Write + Send>) -> SharedWriter {
+        Arc::new(Mutex::new(Self { out, seq: 0 }))
+    }
+
+    pub(super) fn response(&mut self, request: &Request, body: Value) {
+        self.send(json!({
+            "type": "response",
+            "request_seq": request.seq,
+            "command": request.command,
+            "success": true,
+            "body": body,
+        }));
+    }
+
+    pub(super) fn error_response(&mut self, request: &Request, message: impl Into<String>) {
+        let message = message.into();
+        self.send(json!({
+            "type": "response",
+            "request_seq": request.seq,
+            "command": request.command,
+            "success": false,
+            "message": message,
+            "body": { "error": { "id": 1, "format": message } },
+        }));
+    }
+
+    pub(super) fn event(&mut self, event: &str, body: Value) {
+        self.send(json!({
+            "type": "event",
+            "event": event,
+            "body": body,
+        }));
+    }
+
+    pub(super) fn output(&mut self, category: &str, text: impl Into<String>) {
+        self.event(
+            "output",
+            json!({ "category": category, "output": text.into() }),
+        );
+    }
+
+    fn send(&mut self, mut message: Value) {
+        self.seq += 1;
+        message["seq"] = json!(self.seq);
+
+        let payload = message.to_string();
+        // A vanished client leaves nothing useful to report to, so write errors
+        // are dropped and the read loop notices the closed stream instead.
+        let _ = write!(
+            self.out,
+            "Content-Length: {}\r\n\r\n{payload}",
+            payload.len()
+        );
+        let _ = self.out.flush();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    #[test]
+    fn read_message_parses_content_length_frames() {
+        let body = r#"{"seq":1,"type":"request","command":"initialize","arguments":{"adapterID":"tonic"}}"#;
+        let framed = format!("Content-Length: {}\r\n\r\n{body}", body.len());
+        let mut reader = Cursor::new(framed.into_bytes());
+
+        let request = read_message(&mut reader)
+            .expect("frame should parse")
+            .expect("frame should be present");
+        assert_eq!(request.seq, 1);
+        assert_eq!(request.command, "initialize");
+        assert_eq!(request.arguments["adapterID"], "tonic");
+        assert!(read_message(&mut reader).expect("eof").is_none());
+    }
+
+    #[test]
+    fn read_message_rejects_invalid_content_length() {
+        let mut reader = Cursor::new(b"Content-Length: nope\r\n\r\n{}".to_vec());
+        let error = read_message(&mut reader).expect_err("length should be rejected");
+        assert!(error.contains("invalid dap content length"));
+    }
+}
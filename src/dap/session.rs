@@ -0,0 +1,377 @@
+use super::program::DebugProgram;
+use super::protocol::{Request, SharedWriter};
+use crate::runtime::debug::{DebugFrame, DebugHook};
+use crate::runtime::RuntimeValue;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::Receiver;
+use std::sync::{Arc, Mutex};
+
+pub(super) const THREAD_ID: i64 = 1;
+
+/// Line breakpoints for the debuggee file, keyed by resolved line.
+pub(super) type BreakpointTable = HashMap<usize, Option<String>>;
+
+/// State shared between the protocol thread and the interpreter thread.
+#[derive(Default)]
+pub(super) struct DebugControl {
+    pub(super) paused: AtomicBool,
+    pub(super) pause_requested: AtomicBool,
+    pub(super) abort: AtomicBool,
+    pub(super) breakpoints: Mutex<BreakpointTable>,
+}
+
+/// Requests the protocol thread hands to a paused interpreter.
+pub(super) enum PausedCommand {
+    Request(Request),
+    Abort,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum StepMode {
+    Run,
+    In,
+    Over { depth: usize },
+    Out { depth: usize },
+}
+
+/// The [`DebugHook`] that pauses the interpreter and services inspection
+/// requests until the client resumes it.
+pub(super) struct DapHook {
+    program: Arc<DebugProgram>,
+    control: Arc<DebugControl>,
+    writer: SharedWriter,
+    commands: Receiver<PausedCommand>,
+    step: StepMode,
+    stop_on_entry: bool,
+    /// Last line reported per frame depth, so each line stops at most once.
+    last_lines: Vec<usize>,
+}
+
+impl DapHook {
+    pub(super) fn new(
+        program: Arc<DebugProgram>,
+        control: Arc<DebugControl>,
+        writer: SharedWriter,
+        commands: Receiver<PausedCommand>,
+        stop_on_entry: bool,
+    ) -> Self {
+        Self {
+            program,
+            control,
+            writer,
+            commands,
+            step: StepMode::Run,
+            stop_on_entry,
+            last_lines: Vec::new(),
+        }
+    }
+
+    fn stop_reason(
+        &mut self,
+        line: usize,
+        depth: usize,
+        line_changed: bool,
+        env: &Env,
+    ) -> Option<&'static str> {
+        if self.control.pause_requested.swap(false, Ordering::SeqCst) {
+            return Some("pause");
+        }
+
+        if std::mem::take(&mut self.stop_on_entry) {
+            return Some("entry");
+        }
+
+        let stepped = match self.step {
+            StepMode::Run => false,
+            StepMode::In => line_changed,
+            StepMode::Over { depth: start } => line_changed && depth <= start,
+            StepMode::Out { depth: start } => depth < start,
+        };
+        if stepped {
+            return Some("step");
+        }
+
+        if !line_changed {
+            return None;
+        }
+
+        let condition = self
+            .control
+            .breakpoints
+            .lock()
+            .expect("breakpoint table lock")
+            .get(&line)
+            .cloned()?;
+
+        match condition {
+            None => Some("breakpoint"),
+            Some(condition) => match self.program.evaluate(&condition, env) {
+                Ok(RuntimeValue::Nil | RuntimeValue::Bool(false)) => None,
+                Ok(_) => Some("breakpoint"),
+                Err(error) => {
+                    self.writer.lock().expect("dap writer lock").output(
+                        "stderr",
+                        format!("breakpoint condition `{condition}` failed: {error}\n"),
+                    );
+                    Some("breakpoint")
+                }
+            },
+        }
+    }
+
+    fn pause(&mut self, reason: &str, frames: &[DebugFrame], env: &Env) -> Result<(), String> {
+        self.step = StepMode::Run;
+        self.control.paused.store(true, Ordering::SeqCst);
+        self.writer.lock().expect("dap writer lock").event(
+            "stopped",
+            json!({ "reason": reason, "threadId": THREAD_ID, "allThreadsStopped": true }),
+        );
+
+        let mut variables = VariableStore::default();
+        let depth = frames.len();
+
+        loop {
+            let request = match self.commands.recv() {
+                Ok(PausedCommand::Request(request)) => request,
+                Ok(PausedCommand::Abort) | Err(_) => {
+                    return Err("debug session terminated".to_string());
+                }
+            };
+
+            let resume = match request.command.as_str() {
+                "continue" => Some(StepMode::Run),
+                "next" => Some(StepMode::Over { depth }),
+                "stepIn" => Some(StepMode::In),
+                "stepOut" => Some(StepMode::Out { depth }),
+                _ => None,
+            };
+
+            if let Some(step) = resume {
+                self.step = step;
+                let body = if request.command == "continue" {
+                    json!({ "allThreadsContinued": true })
+                } else {
+                    json!({})
+                };
+                self.writer
+                    .lock()
+                    .expect("dap writer lock")
+                    .response(&request, body);
+                return Ok(());
+            }
+
+            // Evaluation may print through the host output forwarder, which
+            // takes the writer lock, so the response is built before locking.
+            let result = match request.command.as_str() {
+                "stackTrace" => Ok(self.stack_trace(frames)),
+                "scopes" => frame_bindings(&request, frames, env).map(|bindings| {
+                    let reference = variables.allocate(sorted_bindings(bindings));
+                    json!({ "scopes": [{
+                        "name": "Locals",
+                        "presentationHint": "locals",
+                        "variablesReference": reference,
+                        "expensive": false,
+                    }] })
+                }),
+                "variables" => {
+                    let reference = request.arguments["variablesReference"]
+                        .as_u64()
+                        .unwrap_or(0);
+                    variables
+                        .expand(reference as usize)
+                        .map(|variables| json!({ "variables": variables }))
+                }
+                "evaluate" => frame_bindings(&request, frames, env).and_then(|bindings| {
+                    let expression = request.arguments["expression"].as_str().unwrap_or("");
+                    let value = self.program.evaluate(expression, bindings)?;
+                    let reference = variables.allocate_children(&value);
+                    Ok(json!({
+                        "result": value.render(),
+                        "type": value.kind_label(),
+                        "variablesReference": reference,
+                    }))
+                }),
+                other => Err(format!("unsupported request while paused: {other}")),
+            };
+
+            let mut writer = self.writer.lock().expect("dap writer lock");
+            match result {
+                Ok(body) => writer.response(&request, body),
+                Err(message) => writer.error_response(&request, message),
+            }
+        }
+    }
+
+    fn stack_trace(&self, frames: &[DebugFrame]) -> Value {
+        let source = json!({
+            "name": self.program.path.file_name().map(|name| name.to_string_lossy().into_owned()),
+            "path": self.program.display_path(),
+        });
+
+        let stack_frames = frames
+            .iter()
+            .enumerate()
+            .rev()
+            .map(|(index, frame)| {
+                let (line, column) = self.program.position(frame.offset);
+                let mut stack_frame = json!({
+                    "id": index + 1,
+                    "name": frame.name,
+                    "line": line,
+                    "column": column,
+                });
+                if self.program.is_user_offset(frame.offset) {
+                    stack_frame["source"] = source.clone();
+                }
+                stack_frame
+            })
+            .collect::<Vec<_>>();
+
+        json!({ "stackFrames": stack_frames, "totalFrames": frames.len() })
+    }
+}
+
+type Env = HashMap<String, RuntimeValue>;
+
+impl DebugHook for DapHook {
+    fn on_op(&mut self, offset: usize, frames: &[DebugFrame], env: &Env) -> Result<(), String> {
+        if self.control.abort.load(Ordering::SeqCst) {
+            return Err("debug session terminated".to_string());
+        }
+
+        let depth = frames.len();
+        if depth == 0 || !self.program.is_user_offset(offset) {
+            return Ok(());
+        }
+
+        let (line, _) = self.program.position(offset);
+        self.last_lines.truncate(depth);
+        self.last_lines.resize(depth, 0);
+        let line_changed = self.last_lines[depth - 1] != line;
+        self.last_lines[depth - 1] = line;
+
+        match self.stop_reason(line, depth, line_changed, env) {
+            Some(reason) => self.pause(reason, frames, env),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Bindings for the `frameId` argument, defaulting to the innermost frame.
+fn frame_bindings<'a>(
+    request: &Request,
+    frames: &'a [DebugFrame],
+    env: &'a Env,
+) -> Result<&'a Env, String> {
+    let frame_id = request.arguments["frameId"]
+        .as_u64()
+        .map(|id| id as usize)
+        .unwrap_or(frames.len());
+
+    match frame_id {
+        id if id == frames.len() => Ok(env),
+        id if id >= 1 && id < frames.len() => Ok(&frames[id - 1].env),
+        id => Err(format!("unknown frame id {id}")),
+    }
+}
+
+fn sorted_bindings(bindings: &Env) -> Vec<(String, RuntimeValue)> {
+    let mut entries = bindings
+        .iter()
+        .filter(|(name, _)| !name.starts_with("__"))
+        .map(|(name, value)| (name.clone(), value.clone()))
+        .collect::<Vec<_>>();
+    entries.sort_by(|left, right| left.0.cmp(&right.0));
+    entries
+}
+
+/// `variablesReference` handles, valid until the interpreter resumes.
+#[derive(Default)]
+struct VariableStore {
+    containers: Vec<Vec<(String, RuntimeValue)>>,
+}
+
+impl VariableStore {
+    fn allocate(&mut self, entries: Vec<(String, RuntimeValue)>) -> usize {
+        self.containers.push(entries);
+        self.containers.len()
+    }
+
+    fn allocate_children(&mut self, value: &RuntimeValue) -> usize {
+        match child_entries(value) {
+            Some(entries) => self.allocate(entries),
+            None => 0,
+        }
+    }
+
+    fn expand(&mut self, reference: usize) -> Result<Vec<Value>, String> {
+        let entries = reference
+            .checked_sub(1)
+            .and_then(|index| self.containers.get(index))
+            .cloned()
+            .ok_or_else(|| format!("unknown variables reference {reference}"))?;
+
+        Ok(entries
+            .iter()
+            .map(|(name, value)| {
+                json!({
+                    "name": name,
+                    "value": value.render(),
+                    "type": value.kind_label(),
+                    "variablesReference": self.allocate_children(value),
+                })
+            })
+            .collect())
+    }
+}
+
+fn child_entries(value: &RuntimeValue) -> Option<Vec<(String, RuntimeValue)>> {
+    let entries = match value {
+        RuntimeValue::List(items) | RuntimeValue::Binary(items) => items
+            .iter()
+            .enumerate()
+            .map(|(index, item)| (format!("[{index}]"), item.clone()))
+            .collect(),
+        RuntimeValue::Tuple(left, right) => vec![
+            ("0".to_string(), (**left).clone()),
+            ("1".to_string(), (**right).clone()),
+        ],
+        RuntimeValue::Map(entries) | RuntimeValue::Keyword(entries) => entries
+            .iter()
+            .map(|(key, value)| (key.render(), value.clone()))
+            .collect(),
+        RuntimeValue::ResultOk(inner) | RuntimeValue::ResultErr(inner) => {
+            vec![("value".to_string(), (**inner).clone())]
+        }
+        _ => return None,
+    };
+    Some(entries)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn variable_store_expands_nested_containers() {
+        let mut store = VariableStore::default();
+        let reference = store.allocate(vec![(
+            "pair".to_string(),
+            RuntimeValue::Tuple(
+                Box::new(RuntimeValue::Atom("ok".to_string())),
+                Box::new(RuntimeValue::List(vec![RuntimeValue::Int(1)])),
+            ),
+        )]);
+
+        let top = store.expand(reference).unwrap();
+        assert_eq!(top[0]["value"], "{:ok, [1]}");
+        let tuple_ref = top[0]["variablesReference"].as_u64().unwrap() as usize;
+
+        let tuple = store.expand(tuple_ref).unwrap();
+        assert_eq!(tuple[0]["variablesReference"], 0);
+        assert_eq!(tuple[1]["type"], "list");
+        assert!(store.expand(99).is_err());
+    }
+}
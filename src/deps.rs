@@ -1,42 +1,111 @@
 use crate::manifest::Dependencies;
 use serde::{Deserialize, Serialize};
-use std::collections::BTreeMap;
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, HashMap};
 use std::fs;
 use std::path::{Path, PathBuf};
 
+#[path = "deps_graph.rs"]
+mod graph;
+#[path = "deps_package.rs"]
+mod package;
+#[path = "deps_publish.rs"]
+mod publish;
+#[path = "deps_registry.rs"]
+mod registry;
+#[path = "deps_resolve.rs"]
+mod resolve;
+#[path = "deps_semver.rs"]
+mod semver;
+
+pub(crate) use graph::{DependencyGraph, DependencyKind, TreeNode};
+pub(crate) use publish::{
+    change_owner, list_owners, prepare_publication, publish, registry_url, set_yanked,
+    validate_package_name, Credentials,
+};
+use registry::{RegistryClient, DEFAULT_REGISTRY};
+use resolve::PackageSource;
+use semver::{Requirement, Version};
+
 const LOCKFILE_NAME: &str = "tonic.lock";
 const DEPS_CACHE_DIR: &str = ".tonic/deps";
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// How `deps sync` and `deps lock` may use the network and `tonic.lock`.
+#[derive(Debug, Clone, Copy, Default)]
+pub(crate) struct SyncOptions {
+    /// Fail instead of writing a `tonic.lock` that differs from the current one.
+    pub(crate) locked: bool,
+    /// Never fetch: registry versions come from `tonic.lock` and git
+    /// checkouts from `.tonic/deps`.
+    pub(crate) offline: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub(crate) struct Lockfile {
     pub(crate) version: u32,
     pub(crate) path_deps: BTreeMap<String, PathDepLock>,
     pub(crate) git_deps: BTreeMap<String, GitDepLock>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub(crate) registry_deps: BTreeMap<String, RegistryDepLock>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub(crate) struct PathDepLock {
     pub(crate) path: String,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub(crate) struct GitDepLock {
     pub(crate) url: String,
     pub(crate) rev: String,
+    /// Content hash of the checked-out tree (see [`tree_hash`]), recorded on
+    /// the first sync and verified on every later one.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) hash: Option<String>,
+}
+
+/// A registry package picked by the resolver, direct or transitive.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub(crate) struct RegistryDepLock {
+    pub(crate) version: String,
+    pub(crate) checksum: String,
+    pub(crate) url: String,
+    /// Requirements this version declares on other registry packages.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub(crate) dependencies: BTreeMap<String, String>,
+}
+
+impl RegistryDepLock {
+    /// Directory under `.tonic/deps` holding the unpacked package.
+    pub(crate) fn cache_dir_name(&self, name: &str) -> String {
+        format!("{name}-{}", self.version)
+    }
 }
 
 impl Lockfile {
     pub(crate) fn generate(
         dependencies: &Dependencies,
-        _project_root: &Path,
+        project_root: &Path,
+        options: SyncOptions,
     ) -> Result<Self, String> {
-        // Registry deps cannot be resolved yet; fail early with an actionable message.
-        if let Some(name) = dependencies.registry.keys().next() {
-            return Err(format!(
-                "registry dependency '{}': registry dependencies are not yet supported; use git or path deps",
-                name
-            ));
-        }
+        let previous = Self::load(project_root).ok().flatten();
+        Self::generate_from(dependencies, previous.as_ref(), options)
+    }
+
+    /// Like [`Lockfile::generate`], keeping what `previous` locked instead of
+    /// the lockfile on disk.
+    fn generate_from(
+        dependencies: &Dependencies,
+        previous: Option<&Lockfile>,
+        options: SyncOptions,
+    ) -> Result<Self, String> {
+        let registry_deps = if dependencies.registry.is_empty() {
+            BTreeMap::new()
+        } else if options.offline {
+            offline_registry_deps(dependencies, previous)?
+        } else {
+            resolve_registry_deps(dependencies, previous)?
+        };
 
         let mut path_deps = BTreeMap::new();
         let mut git_deps = BTreeMap::new();
@@ -54,11 +123,18 @@ impl Lockfile {
         }
 
         for (name, git_dep) in &dependencies.git {
+            // Keep the recorded hash while the dependency still points at
+            // the same url and rev.
+            let hash = previous
+                .and_then(|lockfile| lockfile.git_deps.get(name))
+                .filter(|lock| lock.url == git_dep.url && lock.rev == git_dep.rev)
+                .and_then(|lock| lock.hash.clone());
             git_deps.insert(
                 name.clone(),
                 GitDepLock {
                     url: git_dep.url.clone(),
                     rev: git_dep.rev.clone(),
+                    hash,
                 },
             );
         }
@@ -67,6 +143,7 @@ impl Lockfile {
             version: 1,
             path_deps,
             git_deps,
+            registry_deps,
         })
     }
 
@@ -99,6 +176,19 @@ impl Lockfile {
     pub(crate) fn deps_dir(project_root: &Path) -> PathBuf {
         project_root.join(DEPS_CACHE_DIR)
     }
+
+    /// For `--locked`: fails when saving `self` would change `tonic.lock`.
+    pub(crate) fn ensure_unchanged(&self, project_root: &Path) -> Result<(), String> {
+        match Self::load(project_root)? {
+            Some(existing) if existing == *self => Ok(()),
+            Some(_) => Err(format!(
+                "{LOCKFILE_NAME} is out of date and --locked forbids updating it; run `tonic deps sync` and commit the new {LOCKFILE_NAME}"
+            )),
+            None => Err(format!(
+                "{LOCKFILE_NAME} is missing and --locked forbids creating it; run `tonic deps sync` and commit {LOCKFILE_NAME}"
+            )),
+        }
+    }
 }
 
 pub(crate) struct DependencyResolver;
@@ -108,23 +198,132 @@ impl DependencyResolver {
     pub(crate) fn sync(
         dependencies: &Dependencies,
         project_root: &Path,
+        options: SyncOptions,
+    ) -> Result<Lockfile, String> {
+        let previous = Lockfile::load(project_root).ok().flatten();
+        Self::sync_from(dependencies, project_root, previous.as_ref(), options)
+    }
+
+    /// Re-resolve `names` (every dependency when empty) and sync. Registry
+    /// packages lose their locked version and git checkouts are fetched and
+    /// hashed again, accepting new contents for an unchanged rev. Returns
+    /// the lockfile before and after.
+    pub(crate) fn update(
+        dependencies: &Dependencies,
+        project_root: &Path,
+        names: &[String],
+    ) -> Result<(Option<Lockfile>, Lockfile), String> {
+        let previous = Lockfile::load(project_root)?;
+        let mut unlocked = previous.clone();
+        match unlocked.as_mut() {
+            Some(lockfile) => {
+                if let Some(unknown) = names.iter().find(|name| {
+                    !lockfile.registry_deps.contains_key(*name)
+                        && !lockfile.git_deps.contains_key(*name)
+                        && !lockfile.path_deps.contains_key(*name)
+                }) {
+                    return Err(format!(
+                        "no dependency named '{unknown}' in {LOCKFILE_NAME}"
+                    ));
+                }
+                let selected = |name: &str| names.is_empty() || names.iter().any(|n| n == name);
+                lockfile.registry_deps.retain(|name, _| !selected(name));
+                for (name, lock) in &mut lockfile.git_deps {
+                    if selected(name) {
+                        lock.hash = None;
+                    }
+                }
+            }
+            None if !names.is_empty() => {
+                return Err(format!(
+                    "{LOCKFILE_NAME} not found; run `tonic deps sync` first"
+                ));
+            }
+            None => {}
+        }
+
+        let lockfile = Self::sync_from(
+            dependencies,
+            project_root,
+            unlocked.as_ref(),
+            SyncOptions::default(),
+        )?;
+        Ok((previous, lockfile))
+    }
+
+    fn sync_from(
+        dependencies: &Dependencies,
+        project_root: &Path,
+        previous: Option<&Lockfile>,
+        options: SyncOptions,
     ) -> Result<Lockfile, String> {
-        let lockfile = Lockfile::generate(dependencies, project_root)?;
+        let mut lockfile = Lockfile::generate_from(dependencies, previous, options)?;
 
         let deps_dir = Lockfile::deps_dir(project_root);
         fs::create_dir_all(&deps_dir)
             .map_err(|e| format!("failed to create deps directory: {}", e))?;
 
         // Fetch git dependencies
-        for (name, git_lock) in &lockfile.git_deps {
+        for (name, git_lock) in &mut lockfile.git_deps {
             let cache_path = deps_dir.join(name);
+            // A cached checkout is reused only when an earlier sync hashed it
+            // for this url and rev; `deps lock` alone records no hash. Offline,
+            // a checkout from before hashes existed is trusted and hashed now.
+            let cache_current = cache_path.exists()
+                && previous
+                    .and_then(|lockfile| lockfile.git_deps.get(name))
+                    .filter(|lock| lock.url == git_lock.url && lock.rev == git_lock.rev)
+                    .is_some_and(|lock| lock.hash.is_some() || options.offline);
+            if !cache_current {
+                if options.offline {
+                    return Err(format!(
+                        "git dependency '{}' at rev '{}' is not in {} and --offline forbids fetching it; run `tonic deps sync` with network access first",
+                        name, git_lock.rev, DEPS_CACHE_DIR
+                    ));
+                }
+                let _ = fs::remove_dir_all(&cache_path);
+                Self::fetch_git_dep(name, &git_lock.url, &git_lock.rev, &cache_path)?;
+            }
+
+            let hash = tree_hash(&cache_path)?;
+            match &git_lock.hash {
+                Some(expected) if *expected != hash => {
+                    return Err(format!(
+                        "content hash mismatch for git dependency '{}' ({} at rev '{}'):\n  {}: {}\n  {}/{}: {}\nthe revision now has different contents (was it force-pushed?); if the change is expected, run `tonic deps update {}`",
+                        name,
+                        git_lock.url,
+                        git_lock.rev,
+                        LOCKFILE_NAME,
+                        expected,
+                        DEPS_CACHE_DIR,
+                        name,
+                        hash,
+                        name
+                    ));
+                }
+                Some(_) => {}
+                None => git_lock.hash = Some(hash),
+            }
+        }
+
+        for (name, registry_lock) in &lockfile.registry_deps {
+            let cache_path = deps_dir.join(registry_lock.cache_dir_name(name));
             if cache_path.exists() {
-                continue; // Already cached
+                continue;
+            }
+            if options.offline {
+                return Err(format!(
+                    "registry dependency '{}' {} is not in {} and --offline forbids downloading it; run `tonic deps sync` with network access first",
+                    name, registry_lock.version, DEPS_CACHE_DIR
+                ));
             }
 
-            Self::fetch_git_dep(name, &git_lock.url, &git_lock.rev, &cache_path)?;
+            Self::fetch_registry_dep(name, registry_lock, &cache_path)?;
         }
 
+        if options.locked {
+            lockfile.ensure_unchanged(project_root)?;
+        }
         lockfile.save(project_root)?;
         Ok(lockfile)
     }
@@ -137,28 +336,271 @@ impl DependencyResolver {
             )
         };
 
-        // Use git to fetch the specific revision
-        let output = std::process::Command::new("git")
-            .args(["clone", "--no-checkout", url, target_path.to_str().unwrap()])
-            .output()
-            .map_err(|_| diagnostic())?;
+        checkout_git_rev(url, rev, target_path)
+            .map(|_| ())
+            .map_err(|_| diagnostic())
+    }
+
+    /// Download, verify and unpack a registry package tarball. The package
+    /// is unpacked next to its final location and renamed into place so an
+    /// interrupted sync never leaves a partial cache entry.
+    fn fetch_registry_dep(
+        name: &str,
+        lock: &RegistryDepLock,
+        target_path: &Path,
+    ) -> Result<(), String> {
+        let tarball = registry::download(name, &lock.url, &lock.checksum)?;
+
+        let mut staging_name = target_path.as_os_str().to_os_string();
+        staging_name.push(".partial");
+        let staging_path = PathBuf::from(staging_name);
+        let _ = fs::remove_dir_all(&staging_path);
+        let unpack = || -> std::io::Result<()> {
+            let decoder = flate2::read::GzDecoder::new(tarball.as_slice());
+            tar::Archive::new(decoder).unpack(&staging_path)?;
+            fs::rename(&staging_path, target_path)
+        };
+        unpack().map_err(|error| {
+            let _ = fs::remove_dir_all(&staging_path);
+            format!(
+                "failed to unpack registry dependency '{}' {}: {}",
+                name, lock.version, error
+            )
+        })
+    }
+}
+
+/// Clone `url` into `target_path` and detach at `rev` (a commit, tag, or
+/// `origin/<branch>`; `HEAD` is the remote's default branch). Returns the
+/// checked-out commit.
+pub(crate) fn checkout_git_rev(url: &str, rev: &str, target_path: &Path) -> Result<String, String> {
+    let target = target_path.to_string_lossy();
+    run_git(None, &["clone", "--no-checkout", url, &target])?;
+    run_git(Some(target_path), &["checkout", "--detach", rev])?;
+    run_git(Some(target_path), &["rev-parse", "HEAD"])
+}
+
+/// Runs git and returns its trimmed stdout, or its stderr on failure.
+fn run_git(current_dir: Option<&Path>, args: &[&str]) -> Result<String, String> {
+    let mut command = std::process::Command::new("git");
+    if let Some(dir) = current_dir {
+        command.current_dir(dir);
+    }
+    let output = command
+        .args(args)
+        .output()
+        .map_err(|error| format!("failed to run git: {error}"))?;
+    if !output.status.success() {
+        return Err(String::from_utf8_lossy(&output.stderr).trim().to_string());
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
 
-        if !output.status.success() {
-            return Err(diagnostic());
+/// `sha256:<hex>` over every file below `root` except `.git`: each file
+/// contributes its `/`-separated relative path, its length and its bytes,
+/// in path order, so the hash only changes when the tree's contents do.
+pub(crate) fn tree_hash(root: &Path) -> Result<String, String> {
+    fn collect(dir: &Path, prefix: &str, files: &mut Vec<(String, PathBuf)>) -> Result<(), String> {
+        let entries = fs::read_dir(dir)
+            .map_err(|error| format!("failed to read {}: {error}", dir.display()))?;
+        for entry in entries {
+            let entry =
+                entry.map_err(|error| format!("failed to read {}: {error}", dir.display()))?;
+            let name = entry.file_name().to_string_lossy().to_string();
+            if name == ".git" {
+                continue;
+            }
+            let relative = format!("{prefix}{name}");
+            let file_type = entry
+                .file_type()
+                .map_err(|error| format!("failed to read {}: {error}", entry.path().display()))?;
+            if file_type.is_dir() {
+                collect(&entry.path(), &format!("{relative}/"), files)?;
+            } else {
+                files.push((relative, entry.path()));
+            }
         }
+        Ok(())
+    }
+
+    let mut files = Vec::new();
+    collect(root, "", &mut files)?;
+    files.sort();
 
-        // Checkout specific revision
-        let output = std::process::Command::new("git")
-            .current_dir(target_path)
-            .args(["checkout", "--detach", rev])
-            .output()
-            .map_err(|_| diagnostic())?;
+    let mut hasher = Sha256::new();
+    for (relative, path) in files {
+        let contents = match fs::read_link(&path) {
+            Ok(target) => target.to_string_lossy().into_owned().into_bytes(),
+            Err(_) => fs::read(&path)
+                .map_err(|error| format!("failed to read {}: {error}", path.display()))?,
+        };
+        hasher.update(relative.as_bytes());
+        hasher.update([0]);
+        hasher.update((contents.len() as u64).to_le_bytes());
+        hasher.update(&contents);
+    }
+    let hex = hasher
+        .finalize()
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect::<String>();
+    Ok(format!("sha256:{hex}"))
+}
 
-        if !output.status.success() {
-            return Err(diagnostic());
+/// Registry versions for `--offline`: every direct requirement must already
+/// be satisfied by `tonic.lock`, whose entries are kept as they are.
+fn offline_registry_deps(
+    dependencies: &Dependencies,
+    locked: Option<&Lockfile>,
+) -> Result<BTreeMap<String, RegistryDepLock>, String> {
+    let registry_deps = locked
+        .map(|lockfile| lockfile.registry_deps.clone())
+        .unwrap_or_default();
+    for (name, dep) in &dependencies.registry {
+        let requirement = Requirement::parse(&dep.version)
+            .map_err(|error| format!("registry dependency '{name}': {error}"))?;
+        let satisfied = registry_deps
+            .get(name)
+            .and_then(|lock| Version::parse(&lock.version).ok())
+            .is_some_and(|version| requirement.matches(&version));
+        if !satisfied {
+            return Err(format!(
+                "registry dependency '{name}' ({}) is not satisfied by {LOCKFILE_NAME} and --offline forbids resolving it; run `tonic deps sync` with network access first",
+                dep.version
+            ));
         }
+    }
+    Ok(registry_deps)
+}
 
-        Ok(())
+/// Resolves `dependencies.registry` and its transitive registry
+/// dependencies, keeping versions from `locked` that still fit.
+fn resolve_registry_deps(
+    dependencies: &Dependencies,
+    locked: Option<&Lockfile>,
+) -> Result<BTreeMap<String, RegistryDepLock>, String> {
+    let default_registry = dependencies
+        .default_registry
+        .as_deref()
+        .unwrap_or(DEFAULT_REGISTRY);
+
+    let mut root = Vec::new();
+    let mut registries = HashMap::new();
+    let mut names = dependencies.registry.keys().collect::<Vec<_>>();
+    names.sort();
+    for name in names {
+        let dep = &dependencies.registry[name];
+        let requirement = Requirement::parse(&dep.version)
+            .map_err(|error| format!("registry dependency '{name}': {error}"))?;
+        root.push((name.clone(), requirement));
+        registries.insert(
+            name.clone(),
+            dep.registry
+                .clone()
+                .unwrap_or_else(|| default_registry.to_string()),
+        );
+    }
+
+    let preferred: BTreeMap<String, Version> = locked
+        .map(|lockfile| {
+            lockfile
+                .registry_deps
+                .iter()
+                .filter_map(|(name, lock)| {
+                    Version::parse(&lock.version)
+                        .ok()
+                        .map(|version| (name.clone(), version))
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+
+    let mut source = RegistrySource {
+        client: RegistryClient::default(),
+        registries,
+        locked: preferred.clone(),
+    };
+    let resolution = resolve::resolve(&root, &preferred, &mut source)
+        .map_err(|error| format!("failed to resolve registry dependencies: {error}"))?;
+
+    resolution
+        .into_iter()
+        .map(|(name, version)| {
+            let registry = source.registry(&name).to_string();
+            let checksum = source
+                .client
+                .releases(&registry, &name)?
+                .iter()
+                .find(|release| release.version == version)
+                .map(|release| release.checksum.clone())
+                .unwrap_or_default();
+            let metadata = source.client.metadata(&registry, &name, &version)?;
+            if let Some(listed) = &metadata.checksum {
+                if !listed.eq_ignore_ascii_case(&checksum) {
+                    return Err(format!(
+                        "registry {registry} lists checksum {checksum} for '{name}' {version} in its index but {listed} in the version metadata"
+                    ));
+                }
+            }
+            let dependencies = metadata.dependencies.clone();
+            let url = source.client.download_url(&registry, &name, &version)?;
+            Ok((
+                name,
+                RegistryDepLock {
+                    version: version.to_string(),
+                    checksum,
+                    url,
+                    dependencies,
+                },
+            ))
+        })
+        .collect()
+}
+
+/// Serves the resolver from the registries packages were declared in;
+/// transitive dependencies come from the registry of the package that
+/// requires them. Yanked versions are only offered when already locked.
+struct RegistrySource {
+    client: RegistryClient,
+    registries: HashMap<String, String>,
+    locked: BTreeMap<String, Version>,
+}
+
+impl RegistrySource {
+    fn registry(&self, name: &str) -> &str {
+        self.registries
+            .get(name)
+            .map(String::as_str)
+            .unwrap_or(DEFAULT_REGISTRY)
+    }
+}
+
+impl PackageSource for RegistrySource {
+    fn versions(&mut self, name: &str) -> Result<Vec<Version>, String> {
+        let registry = self.registry(name).to_string();
+        let locked = self.locked.get(name);
+        Ok(self
+            .client
+            .releases(&registry, name)?
+            .iter()
+            .filter(|release| !release.yanked || locked == Some(&release.version))
+            .map(|release| release.version.clone())
+            .collect())
+    }
+
+    fn dependencies(
+        &mut self,
+        name: &str,
+        version: &Version,
+    ) -> Result<Vec<(String, Requirement)>, String> {
+        let registry = self.registry(name).to_string();
+        let dependencies = self.client.dependencies(&registry, name, version)?;
+        for (dependency, _) in &dependencies {
+            self.registries
+                .entry(dependency.clone())
+                .or_insert_with(|| registry.clone());
+        }
+        Ok(dependencies)
     }
 }
 
@@ -185,7 +627,7 @@ mod tests {
             },
         );
 
-        let lockfile = Lockfile::generate(&deps, &temp_dir).unwrap();
+        let lockfile = Lockfile::generate(&deps, &temp_dir, SyncOptions::default()).unwrap();
 
         assert_eq!(lockfile.version, 1);
         assert!(lockfile.path_deps.contains_key("local_lib"));
@@ -208,7 +650,7 @@ mod tests {
             },
         );
 
-        let lockfile = Lockfile::generate(&deps, &temp_dir).unwrap();
+        let lockfile = Lockfile::generate(&deps, &temp_dir, SyncOptions::default()).unwrap();
         lockfile.save(&temp_dir).unwrap();
 
         let loaded = Lockfile::load(&temp_dir).unwrap().unwrap();
@@ -217,4 +659,25 @@ mod tests {
 
         std::fs::remove_dir_all(&temp_dir).ok();
     }
+
+    #[test]
+    fn tree_hash_ignores_git_metadata_and_tracks_contents() {
+        let root =
+            std::env::temp_dir().join(format!("tonic-tree-hash-test-{}", std::process::id()));
+        fs::create_dir_all(root.join(".git")).unwrap();
+        fs::create_dir_all(root.join("lib")).unwrap();
+        fs::write(root.join("lib/remote.tn"), "defmodule Remote do\nend\n").unwrap();
+        fs::write(root.join(".git/HEAD"), "ref: refs/heads/main\n").unwrap();
+
+        let original = tree_hash(&root).unwrap();
+        assert!(original.starts_with("sha256:"));
+
+        fs::write(root.join(".git/HEAD"), "0123456789abcdef\n").unwrap();
+        assert_eq!(tree_hash(&root).unwrap(), original);
+
+        fs::write(root.join("lib/remote.tn"), "defmodule Remote2 do\nend\n").unwrap();
+        assert_ne!(tree_hash(&root).unwrap(), original);
+
+        std::fs::remove_dir_all(&root).ok();
+    }
 }
@@ -0,0 +1,477 @@
+//! The locked dependency graph behind `tonic deps tree`, `why` and
+//! `outdated`.
+//!
+//! Direct requirements come from `tonic.toml` and everything they resolved
+//! to from `tonic.lock`. Path and git dependencies are leaves; registry
+//! packages carry the requirements recorded for them in the lock, and
+//! inherit the registry of the package that requires them.
+
+use super::registry::{RegistryClient, DEFAULT_REGISTRY};
+use super::semver::{Requirement, Version};
+use super::{run_git, Lockfile};
+use crate::manifest::Dependencies;
+use serde::Serialize;
+use std::collections::{BTreeMap, BTreeSet};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum DependencyKind {
+    Path,
+    Git,
+    Registry,
+}
+
+/// A package recorded in `tonic.lock`.
+#[derive(Debug, Clone)]
+pub(crate) struct LockedPackage {
+    pub(crate) kind: DependencyKind,
+    /// Registry version, git rev or canonical path.
+    pub(crate) locked: String,
+    /// Git url or registry base URL; `None` for path dependencies.
+    pub(crate) source: Option<String>,
+    /// `(name, requirement)` for each registry package this one requires.
+    pub(crate) dependencies: Vec<(String, String)>,
+}
+
+/// A requirement on a package; `requirement` is `None` for path and git
+/// dependencies, which pin a location rather than a version range.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Edge {
+    pub(crate) name: String,
+    pub(crate) requirement: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct DependencyGraph {
+    pub(crate) root: String,
+    pub(crate) direct: Vec<Edge>,
+    pub(crate) packages: BTreeMap<String, LockedPackage>,
+}
+
+/// One occurrence of a package in `tonic deps tree`.
+#[derive(Debug, Clone, Serialize)]
+pub(crate) struct TreeNode {
+    pub(crate) name: String,
+    /// `None` when the package is missing from `tonic.lock`.
+    pub(crate) kind: Option<DependencyKind>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) requirement: Option<String>,
+    pub(crate) locked: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) source: Option<String>,
+    /// Listed earlier in the tree; its dependencies are not repeated.
+    pub(crate) duplicate: bool,
+    /// Why the locked package does not satisfy `requirement`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) conflict: Option<String>,
+    pub(crate) dependencies: Vec<TreeNode>,
+}
+
+/// A locked package with a newer release available.
+#[derive(Debug, Clone, Serialize)]
+pub(crate) struct Outdated {
+    pub(crate) name: String,
+    pub(crate) kind: DependencyKind,
+    pub(crate) locked: String,
+    /// Newest release every requirement on the package accepts; `None` for
+    /// git dependencies, which pin a rev.
+    pub(crate) compatible: Option<String>,
+    pub(crate) latest: String,
+}
+
+impl DependencyGraph {
+    pub(crate) fn build(root: &str, dependencies: &Dependencies, lockfile: &Lockfile) -> Self {
+        let mut direct = Vec::new();
+        for name in dependencies.path.keys() {
+            direct.push(Edge {
+                name: name.clone(),
+                requirement: None,
+            });
+        }
+        for name in dependencies.git.keys() {
+            direct.push(Edge {
+                name: name.clone(),
+                requirement: None,
+            });
+        }
+        for (name, dep) in &dependencies.registry {
+            direct.push(Edge {
+                name: name.clone(),
+                requirement: Some(dep.version.clone()),
+            });
+        }
+        direct.sort_by(|left, right| left.name.cmp(&right.name));
+
+        let mut packages = BTreeMap::new();
+        for (name, lock) in &lockfile.path_deps {
+            packages.insert(
+                name.clone(),
+                LockedPackage {
+                    kind: DependencyKind::Path,
+                    locked: lock.path.clone(),
+                    source: None,
+                    dependencies: Vec::new(),
+                },
+            );
+        }
+        for (name, lock) in &lockfile.git_deps {
+            packages.insert(
+                name.clone(),
+                LockedPackage {
+                    kind: DependencyKind::Git,
+                    locked: lock.rev.clone(),
+                    source: Some(lock.url.clone()),
+                    dependencies: Vec::new(),
+                },
+            );
+        }
+        for (name, lock) in &lockfile.registry_deps {
+            packages.insert(
+                name.clone(),
+                LockedPackage {
+                    kind: DependencyKind::Registry,
+                    locked: lock.version.clone(),
+                    source: None,
+                    dependencies: lock
+                        .dependencies
+                        .iter()
+                        .map(|(name, requirement)| (name.clone(), requirement.clone()))
+                        .collect(),
+                },
+            );
+        }
+
+        // Registries: declared on direct dependencies, inherited below them.
+        let default_registry = dependencies
+            .default_registry
+            .as_deref()
+            .unwrap_or(DEFAULT_REGISTRY);
+        let mut pending = dependencies
+            .registry
+            .iter()
+            .map(|(name, dep)| {
+                (
+                    name.clone(),
+                    dep.registry
+                        .clone()
+                        .unwrap_or_else(|| default_registry.to_string()),
+                )
+            })
+            .collect::<Vec<_>>();
+        pending.sort();
+        while let Some((name, registry)) = pending.pop() {
+            let Some(package) = packages.get_mut(&name) else {
+                continue;
+            };
+            if package.kind != DependencyKind::Registry || package.source.is_some() {
+                continue;
+            }
+            package.source = Some(registry.clone());
+            for (dependency, _) in &package.dependencies {
+                pending.push((dependency.clone(), registry.clone()));
+            }
+        }
+
+        DependencyGraph {
+            root: root.to_string(),
+            direct,
+            packages,
+        }
+    }
+
+    fn edges(&self, name: &str) -> Vec<Edge> {
+        self.packages
+            .get(name)
+            .map(|package| {
+                package
+                    .dependencies
+                    .iter()
+                    .map(|(name, requirement)| Edge {
+                        name: name.clone(),
+                        requirement: Some(requirement.clone()),
+                    })
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// The dependency tree in depth-first order. A package's dependencies
+    /// are expanded at its first occurrence only.
+    pub(crate) fn tree(&self) -> Vec<TreeNode> {
+        let mut expanded = BTreeSet::new();
+        self.direct
+            .iter()
+            .map(|edge| self.tree_node(edge, &mut expanded))
+            .collect()
+    }
+
+    fn tree_node(&self, edge: &Edge, expanded: &mut BTreeSet<String>) -> TreeNode {
+        let package = self.packages.get(&edge.name);
+        let duplicate = !expanded.insert(edge.name.clone());
+        let dependencies = if duplicate {
+            Vec::new()
+        } else {
+            self.edges(&edge.name)
+                .iter()
+                .map(|child| self.tree_node(child, expanded))
+                .collect()
+        };
+        TreeNode {
+            name: edge.name.clone(),
+            kind: package.map(|package| package.kind),
+            requirement: edge.requirement.clone(),
+            locked: package.map(|package| package.locked.clone()),
+            source: package.and_then(|package| package.source.clone()),
+            duplicate,
+            conflict: self.conflict(edge),
+            dependencies,
+        }
+    }
+
+    /// Why the locked package cannot satisfy `edge`, if it cannot.
+    pub(crate) fn conflict(&self, edge: &Edge) -> Option<String> {
+        let Some(package) = self.packages.get(&edge.name) else {
+            return Some("not in tonic.lock".to_string());
+        };
+        let requirement = edge.requirement.as_deref()?;
+        if package.kind != DependencyKind::Registry {
+            return Some(format!(
+                "requires {requirement} but tonic.lock has a {} dependency",
+                match package.kind {
+                    DependencyKind::Path => "path",
+                    _ => "git",
+                }
+            ));
+        }
+        let parsed = Requirement::parse(requirement).ok()?;
+        let version = Version::parse(&package.locked).ok()?;
+        (!parsed.matches(&version))
+            .then(|| format!("locked {version} does not satisfy {requirement}"))
+    }
+
+    /// Every requirement on `name`, from the project and from the packages
+    /// that depend on it.
+    pub(crate) fn requirements_on(&self, name: &str) -> Vec<String> {
+        let direct = self
+            .direct
+            .iter()
+            .filter(|edge| edge.name == name)
+            .filter_map(|edge| edge.requirement.clone());
+        let transitive = self.packages.values().flat_map(|package| {
+            package
+                .dependencies
+                .iter()
+                .filter(|(dependency, _)| dependency == name)
+                .map(|(_, requirement)| requirement.clone())
+        });
+        direct.chain(transitive).collect()
+    }
+
+    /// Every acyclic chain of edges from the project down to `target`.
+    pub(crate) fn paths_to(&self, target: &str) -> Vec<Vec<Edge>> {
+        let mut paths = Vec::new();
+        let mut current = Vec::new();
+        for edge in &self.direct {
+            self.collect_paths(edge, target, &mut current, &mut paths);
+        }
+        paths
+    }
+
+    fn collect_paths(
+        &self,
+        edge: &Edge,
+        target: &str,
+        current: &mut Vec<Edge>,
+        paths: &mut Vec<Vec<Edge>>,
+    ) {
+        if current.iter().any(|step| step.name == edge.name) {
+            return;
+        }
+        current.push(edge.clone());
+        if edge.name == target {
+            paths.push(current.clone());
+        } else {
+            for child in self.edges(&edge.name) {
+                self.collect_paths(&child, target, current, paths);
+            }
+        }
+        current.pop();
+    }
+
+    /// Registry packages with newer non-yanked releases, and git
+    /// dependencies pinned to a version tag older than the newest one.
+    /// Path dependencies are always current.
+    pub(crate) fn outdated(&self) -> Result<Vec<Outdated>, String> {
+        let mut client = RegistryClient::default();
+        let mut outdated = Vec::new();
+        for (name, package) in &self.packages {
+            let entry = match package.kind {
+                DependencyKind::Registry => self.outdated_release(&mut client, name, package)?,
+                DependencyKind::Git => outdated_tag(name, package)?,
+                DependencyKind::Path => None,
+            };
+            outdated.extend(entry);
+        }
+        Ok(outdated)
+    }
+
+    fn outdated_release(
+        &self,
+        client: &mut RegistryClient,
+        name: &str,
+        package: &LockedPackage,
+    ) -> Result<Option<Outdated>, String> {
+        let Ok(locked) = Version::parse(&package.locked) else {
+            return Ok(None);
+        };
+        let registry = package.source.as_deref().unwrap_or(DEFAULT_REGISTRY);
+        let requirements = self
+            .requirements_on(name)
+            .iter()
+            .filter_map(|requirement| Requirement::parse(requirement).ok())
+            .collect::<Vec<_>>();
+        let releases = client
+            .releases(registry, name)?
+            .iter()
+            .filter(|release| !release.yanked)
+            .map(|release| &release.version)
+            .collect::<Vec<_>>();
+
+        let latest = releases
+            .iter()
+            .filter(|version| !version.is_prerelease() || locked.is_prerelease())
+            .max()
+            .copied();
+        let compatible = releases
+            .iter()
+            .filter(|version| {
+                requirements
+                    .iter()
+                    .all(|requirement| requirement.matches(version))
+            })
+            .max()
+            .copied();
+        let newer = |version: Option<&Version>| version.is_some_and(|version| *version > locked);
+        if !newer(latest) && !newer(compatible) {
+            return Ok(None);
+        }
+        Ok(Some(Outdated {
+            name: name.to_string(),
+            kind: DependencyKind::Registry,
+            locked: package.locked.clone(),
+            compatible: compatible.map(Version::to_string),
+            latest: latest.unwrap_or(&locked).to_string(),
+        }))
+    }
+}
+
+/// Compares a git rev that names a version tag (`v1.2.0` or `1.2.0`)
+/// against the newest version tag on the remote.
+fn outdated_tag(name: &str, package: &LockedPackage) -> Result<Option<Outdated>, String> {
+    let tag_version = |tag: &str| Version::parse(tag.strip_prefix('v').unwrap_or(tag)).ok();
+    let Some(locked) = tag_version(&package.locked) else {
+        return Ok(None);
+    };
+    let url = package.source.as_deref().unwrap_or_default();
+    let refs = run_git(None, &["ls-remote", "--tags", "--refs", url])
+        .map_err(|error| format!("failed to list tags of '{name}' ({url}): {error}"))?;
+    let latest = refs
+        .lines()
+        .filter_map(|line| line.split_once("refs/tags/").map(|(_, tag)| tag.trim()))
+        .filter_map(|tag| tag_version(tag).map(|version| (version, tag.to_string())))
+        .filter(|(version, _)| !version.is_prerelease())
+        .max();
+    Ok(latest
+        .filter(|(version, _)| *version > locked)
+        .map(|(_, tag)| Outdated {
+            name: name.to_string(),
+            kind: DependencyKind::Git,
+            locked: package.locked.clone(),
+            compatible: None,
+            latest: tag,
+        }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::deps::RegistryDepLock;
+    use crate::manifest::RegistryDep;
+
+    fn registry_lock(version: &str, dependencies: &[(&str, &str)]) -> RegistryDepLock {
+        RegistryDepLock {
+            version: version.to_string(),
+            checksum: "sha256:00".to_string(),
+            url: String::new(),
+            dependencies: dependencies
+                .iter()
+                .map(|(name, requirement)| (name.to_string(), requirement.to_string()))
+                .collect(),
+        }
+    }
+
+    fn graph() -> DependencyGraph {
+        let mut dependencies = Dependencies::default();
+        for (name, version) in [("json", "~> 1.0"), ("utf8", "~> 0.2.0")] {
+            dependencies.registry.insert(
+                name.to_string(),
+                RegistryDep {
+                    version: version.to_string(),
+                    registry: None,
+                },
+            );
+        }
+        let mut lockfile = Lockfile {
+            version: 1,
+            path_deps: BTreeMap::new(),
+            git_deps: BTreeMap::new(),
+            registry_deps: BTreeMap::new(),
+        };
+        lockfile.registry_deps.insert(
+            "json".to_string(),
+            registry_lock("1.1.0", &[("utf8", "~> 0.3")]),
+        );
+        lockfile
+            .registry_deps
+            .insert("utf8".to_string(), registry_lock("0.3.1", &[]));
+        DependencyGraph::build("demo", &dependencies, &lockfile)
+    }
+
+    #[test]
+    fn tree_marks_duplicates_and_conflicts() {
+        let tree = graph().tree();
+
+        assert_eq!(tree.len(), 2);
+        assert_eq!(tree[0].name, "json");
+        assert_eq!(tree[0].dependencies[0].name, "utf8");
+        assert!(!tree[0].dependencies[0].duplicate);
+        assert_eq!(tree[0].dependencies[0].conflict, None);
+
+        assert_eq!(tree[1].name, "utf8");
+        assert!(tree[1].duplicate);
+        assert_eq!(
+            tree[1].conflict.as_deref(),
+            Some("locked 0.3.1 does not satisfy ~> 0.2.0")
+        );
+        assert_eq!(
+            tree[1].source.as_deref(),
+            Some(DEFAULT_REGISTRY),
+            "transitive packages inherit the registry of their parent"
+        );
+    }
+
+    #[test]
+    fn paths_to_lists_every_chain_from_the_project() {
+        let paths = graph().paths_to("utf8");
+        let names = paths
+            .iter()
+            .map(|path| {
+                path.iter()
+                    .map(|edge| edge.name.as_str())
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+
+        assert_eq!(names, vec![vec!["json", "utf8"], vec!["utf8"]]);
+    }
+}
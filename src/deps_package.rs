@@ -0,0 +1,185 @@
+//! Package tarballs for `tonic publish`.
+//!
+//! A package holds `tonic.toml`, top-level README/LICENSE files and every
+//! `.tn` source outside hidden and `target/` directories, minus the
+//! `[package] exclude` patterns. Entries are sorted and written with fixed
+//! metadata (mode 0644, mtime 0, no owner) so the same sources always
+//! produce the same bytes and checksum.
+
+use super::registry::checksum;
+use crate::manifest::collect_tonic_source_paths;
+use regex::Regex;
+use std::fs;
+use std::path::Path;
+
+#[derive(Debug, Clone)]
+pub(crate) struct PackageTarball {
+    /// Paths relative to the project root, `/`-separated and sorted.
+    pub(crate) files: Vec<String>,
+    pub(crate) bytes: Vec<u8>,
+    pub(crate) checksum: String,
+}
+
+pub(crate) fn build_package(
+    project_root: &Path,
+    exclude: &[String],
+) -> Result<PackageTarball, String> {
+    let files = package_files(project_root, exclude)?;
+
+    let encoder = flate2::GzBuilder::new()
+        .mtime(0)
+        .write(Vec::new(), flate2::Compression::best());
+    let mut builder = tar::Builder::new(encoder);
+    for file in &files {
+        let contents = fs::read(project_root.join(file))
+            .map_err(|error| format!("failed to read {file}: {error}"))?;
+        let mut header = tar::Header::new_gnu();
+        header.set_size(contents.len() as u64);
+        header.set_mode(0o644);
+        header.set_mtime(0);
+        header.set_uid(0);
+        header.set_gid(0);
+        header.set_entry_type(tar::EntryType::Regular);
+        builder
+            .append_data(&mut header, file, contents.as_slice())
+            .map_err(|error| format!("failed to add {file} to the package: {error}"))?;
+    }
+    let bytes = builder
+        .into_inner()
+        .and_then(|encoder| encoder.finish())
+        .map_err(|error| format!("failed to write the package tarball: {error}"))?;
+
+    Ok(PackageTarball {
+        files,
+        checksum: checksum(&bytes),
+        bytes,
+    })
+}
+
+fn package_files(project_root: &Path, exclude: &[String]) -> Result<Vec<String>, String> {
+    let patterns = exclude
+        .iter()
+        .map(|pattern| {
+            exclude_regex(pattern).map_err(|error| {
+                format!("invalid tonic.toml: package.exclude pattern '{pattern}': {error}")
+            })
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let mut files = vec!["tonic.toml".to_string()];
+    let entries = fs::read_dir(project_root).map_err(|error| {
+        format!(
+            "failed to read project directory {}: {error}",
+            project_root.display()
+        )
+    })?;
+    for entry in entries.flatten() {
+        let name = entry.file_name().to_string_lossy().to_string();
+        let upper = name.to_ascii_uppercase();
+        let is_doc = ["README", "LICENSE", "LICENCE", "CHANGELOG"]
+            .iter()
+            .any(|prefix| upper.starts_with(prefix));
+        if is_doc && entry.path().is_file() {
+            files.push(name);
+        }
+    }
+    for path in collect_tonic_source_paths(project_root)? {
+        let relative = path
+            .strip_prefix(project_root)
+            .unwrap_or(&path)
+            .components()
+            .map(|component| component.as_os_str().to_string_lossy())
+            .collect::<Vec<_>>()
+            .join("/");
+        files.push(relative);
+    }
+
+    files.retain(|file| {
+        file == "tonic.toml" || !patterns.iter().any(|pattern| pattern.is_match(file))
+    });
+    files.sort();
+    files.dedup();
+    Ok(files)
+}
+
+/// `*` and `?` match within one path segment and `**` across segments; a
+/// pattern also excludes everything below a directory it matches.
+fn exclude_regex(pattern: &str) -> Result<Regex, regex::Error> {
+    let pattern = pattern
+        .trim()
+        .trim_start_matches("./")
+        .trim_end_matches('/');
+    let mut regex = String::from("^");
+    let mut chars = pattern.chars().peekable();
+    while let Some(char) = chars.next() {
+        match char {
+            '*' if chars.peek() == Some(&'*') => {
+                chars.next();
+                if chars.peek() == Some(&'/') {
+                    chars.next();
+                    regex.push_str("(?:.*/)?");
+                } else {
+                    regex.push_str(".*");
+                }
+            }
+            '*' => regex.push_str("[^/]*"),
+            '?' => regex.push_str("[^/]"),
+            other => regex.push_str(&regex::escape(&other.to_string())),
+        }
+    }
+    regex.push_str("(?:/.*)?$");
+    Regex::new(&regex)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn exclude_patterns_match_files_and_directories() {
+        let matches = |pattern: &str, path: &str| exclude_regex(pattern).unwrap().is_match(path);
+
+        assert!(matches("test", "test/json_test.tn"));
+        assert!(matches("src/*_test.tn", "src/json_test.tn"));
+        assert!(!matches("src/*_test.tn", "src/nested/json_test.tn"));
+        assert!(matches("**/*_test.tn", "src/nested/json_test.tn"));
+        assert!(matches("**/*_test.tn", "json_test.tn"));
+        assert!(!matches("src", "srcs/json.tn"));
+    }
+
+    #[test]
+    fn package_tarballs_are_reproducible() {
+        let root =
+            std::env::temp_dir().join(format!("tonic-package-reproducible-{}", std::process::id()));
+        fs::create_dir_all(root.join("src/internal")).unwrap();
+        fs::create_dir_all(root.join(".tonic/deps")).unwrap();
+        fs::write(
+            root.join("tonic.toml"),
+            "[project]\nentry = \"src/json.tn\"\n",
+        )
+        .unwrap();
+        fs::write(root.join("README.md"), "# json\n").unwrap();
+        fs::write(root.join("notes.txt"), "scratch\n").unwrap();
+        fs::write(root.join("src/json.tn"), "defmodule Json do\nend\n").unwrap();
+        fs::write(
+            root.join("src/internal/scratch.tn"),
+            "defmodule Scratch do\nend\n",
+        )
+        .unwrap();
+        fs::write(
+            root.join(".tonic/deps/cached.tn"),
+            "defmodule Cached do\nend\n",
+        )
+        .unwrap();
+
+        let exclude = vec!["src/internal".to_string()];
+        let first = build_package(&root, &exclude).unwrap();
+        let second = build_package(&root, &exclude).unwrap();
+
+        assert_eq!(first.files, ["README.md", "src/json.tn", "tonic.toml"]);
+        assert_eq!(first.bytes, second.bytes);
+        assert!(first.checksum.starts_with("sha256:"));
+
+        fs::remove_dir_all(&root).ok();
+    }
+}
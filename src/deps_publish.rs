@@ -0,0 +1,562 @@
+//! Registry write operations: publishing, yanking and package owners.
+//!
+//! Package checksums are signed with an ed25519 key so registries (and
+//! anyone auditing a mirror) can tie every release to its publisher. Owners
+//! are identified by their public keys. An HTTP registry authenticates
+//! requests with an API token; a `file://` registry has no server, so it
+//! checks the signing key against the package's `owners.json` instead.
+
+use super::package::{build_package, PackageTarball};
+use super::registry::{fetch, DEFAULT_REGISTRY};
+use super::semver::{Requirement, Version};
+use crate::manifest::ProjectManifest;
+use serde_json::{json, Value};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Environment variable holding the signing key when `--key` is not given.
+pub(crate) const SIGNING_KEY_ENV: &str = "TONIC_SIGNING_KEY";
+/// Environment variable holding the API token when `--token` is not given.
+pub(crate) const REGISTRY_TOKEN_ENV: &str = "TONIC_REGISTRY_TOKEN";
+
+/// Everything uploaded for one package version.
+pub(crate) struct Publication {
+    pub(crate) name: String,
+    pub(crate) version: String,
+    pub(crate) description: Option<String>,
+    pub(crate) license: Option<String>,
+    /// Registry requirements, as written in `tonic.toml`.
+    pub(crate) dependencies: BTreeMap<String, String>,
+    pub(crate) manifest: String,
+    pub(crate) tarball: PackageTarball,
+}
+
+/// Checks `name` against the registry's package naming rules.
+pub(crate) fn validate_package_name(name: &str) -> Result<(), String> {
+    let valid = name.len() <= 64
+        && name.starts_with(|char: char| char.is_ascii_lowercase())
+        && name
+            .chars()
+            .all(|char| char.is_ascii_lowercase() || char.is_ascii_digit() || char == '_');
+    if valid {
+        Ok(())
+    } else {
+        Err(format!(
+            "invalid package name '{name}': use lowercase letters, digits and underscores, starting with a letter (at most 64 characters)"
+        ))
+    }
+}
+
+/// Validates `[package]` and `[dependencies]` for publishing and builds the
+/// package tarball.
+pub(crate) fn prepare_publication(
+    project_root: &Path,
+    manifest: &ProjectManifest,
+) -> Result<Publication, String> {
+    let package = manifest
+        .package
+        .as_ref()
+        .ok_or("tonic.toml is missing a [package] section")?;
+    let name = package.name.clone().unwrap_or_default();
+    validate_package_name(&name)?;
+    let version = package.version.clone().unwrap_or_default();
+    Version::parse(&version).map_err(|error| format!("invalid package version: {error}"))?;
+
+    let mut local = manifest
+        .dependencies
+        .path
+        .keys()
+        .map(|dep| format!("path dependency '{dep}'"))
+        .chain(
+            manifest
+                .dependencies
+                .git
+                .keys()
+                .map(|dep| format!("git dependency '{dep}'")),
+        )
+        .collect::<Vec<_>>();
+    local.sort();
+    if let Some(dep) = local.first() {
+        return Err(format!(
+            "cannot publish '{name}' with {dep}; published packages may only depend on registry packages"
+        ));
+    }
+    let dependencies = manifest
+        .dependencies
+        .registry
+        .iter()
+        .map(|(dep, registry_dep)| {
+            Requirement::parse(&registry_dep.version)
+                .map(|_| (dep.clone(), registry_dep.version.clone()))
+                .map_err(|error| format!("registry dependency '{dep}': {error}"))
+        })
+        .collect::<Result<BTreeMap<_, _>, _>>()?;
+
+    let manifest_source = fs::read_to_string(project_root.join("tonic.toml"))
+        .map_err(|error| format!("failed to read tonic.toml: {error}"))?;
+    let tarball = build_package(project_root, &package.exclude)?;
+
+    Ok(Publication {
+        name,
+        version,
+        description: package.description.clone(),
+        license: package.license.clone(),
+        dependencies,
+        manifest: manifest_source,
+        tarball,
+    })
+}
+
+/// Credentials for registry writes.
+pub(crate) struct Credentials {
+    pub(crate) key: Option<SigningKey>,
+    pub(crate) token: Option<String>,
+}
+
+impl Credentials {
+    /// Reads `--key <path>` (or `TONIC_SIGNING_KEY`) and `--token` (or
+    /// `TONIC_REGISTRY_TOKEN`).
+    pub(crate) fn load(key_path: Option<&str>, token: Option<String>) -> Result<Self, String> {
+        let key = match key_path {
+            Some(path) => {
+                let contents = fs::read_to_string(path)
+                    .map_err(|error| format!("failed to read signing key {path}: {error}"))?;
+                Some(SigningKey::from_hex(&contents, path)?)
+            }
+            None => match std::env::var(SIGNING_KEY_ENV) {
+                Ok(value) if !value.trim().is_empty() => {
+                    Some(SigningKey::from_hex(&value, SIGNING_KEY_ENV)?)
+                }
+                _ => None,
+            },
+        };
+        let token = token.or_else(|| {
+            std::env::var(REGISTRY_TOKEN_ENV)
+                .ok()
+                .filter(|token| !token.trim().is_empty())
+        });
+        Ok(Self { key, token })
+    }
+
+    pub(crate) fn require_key(&self) -> Result<&SigningKey, String> {
+        self.key.as_ref().ok_or_else(|| {
+            format!("no signing key; pass --key <path> or set {SIGNING_KEY_ENV} to a 64-character hex ed25519 secret key")
+        })
+    }
+
+    fn require_token(&self, registry: &str) -> Result<&str, String> {
+        self.token.as_deref().ok_or_else(|| {
+            format!(
+                "{registry} needs an API token; pass --token <token> or set {REGISTRY_TOKEN_ENV}"
+            )
+        })
+    }
+}
+
+/// An ed25519 secret key, stored as 64 hex characters.
+pub(crate) struct SigningKey {
+    seed: [u8; 32],
+}
+
+impl SigningKey {
+    fn from_hex(text: &str, origin: &str) -> Result<Self, String> {
+        let text = text.trim();
+        let invalid = || format!("invalid signing key in {origin} (expected 64 hex characters)");
+        if text.len() != 64 || !text.is_ascii() {
+            return Err(invalid());
+        }
+        let mut seed = [0u8; 32];
+        for (index, byte) in seed.iter_mut().enumerate() {
+            *byte =
+                u8::from_str_radix(&text[index * 2..index * 2 + 2], 16).map_err(|_| invalid())?;
+        }
+        Ok(Self { seed })
+    }
+
+    pub(crate) fn public_key(&self) -> Result<String, String> {
+        Ok(hex(&signing::public_key(&self.seed)?))
+    }
+
+    /// Signs the package checksum string (`sha256:<hex>`).
+    pub(crate) fn sign(&self, checksum: &str) -> Result<String, String> {
+        Ok(hex(&signing::sign(&self.seed, checksum.as_bytes())?))
+    }
+}
+
+#[cfg(feature = "network")]
+mod signing {
+    use ed25519_dalek::{Signer, SigningKey};
+
+    pub(super) fn public_key(seed: &[u8; 32]) -> Result<Vec<u8>, String> {
+        Ok(SigningKey::from_bytes(seed)
+            .verifying_key()
+            .to_bytes()
+            .to_vec())
+    }
+
+    pub(super) fn sign(seed: &[u8; 32], message: &[u8]) -> Result<Vec<u8>, String> {
+        Ok(SigningKey::from_bytes(seed)
+            .sign(message)
+            .to_bytes()
+            .to_vec())
+    }
+}
+
+#[cfg(not(feature = "network"))]
+mod signing {
+    const UNAVAILABLE: &str = "package signing needs a tonic build with the `network` feature";
+
+    pub(super) fn public_key(_seed: &[u8; 32]) -> Result<Vec<u8>, String> {
+        Err(UNAVAILABLE.to_string())
+    }
+
+    pub(super) fn sign(_seed: &[u8; 32], _message: &[u8]) -> Result<Vec<u8>, String> {
+        Err(UNAVAILABLE.to_string())
+    }
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{byte:02x}")).collect()
+}
+
+enum Target {
+    Directory(PathBuf),
+    Http(String),
+}
+
+impl Target {
+    fn parse(registry: &str) -> Result<Self, String> {
+        if let Some(path) = registry.strip_prefix("file://") {
+            return Ok(Self::Directory(PathBuf::from(path)));
+        }
+        if registry.starts_with("http://") || registry.starts_with("https://") {
+            return Ok(Self::Http(registry.trim_end_matches('/').to_string()));
+        }
+        Err(format!(
+            "unsupported registry URL '{registry}' (expected http://, https:// or file://)"
+        ))
+    }
+}
+
+/// The registry named by `--registry`, else `[registries] default`, else
+/// the public registry.
+pub(crate) fn registry_url(flag: Option<&str>, manifest_default: Option<&str>) -> String {
+    flag.or(manifest_default)
+        .unwrap_or(DEFAULT_REGISTRY)
+        .to_string()
+}
+
+pub(crate) fn publish(
+    registry: &str,
+    publication: &Publication,
+    credentials: &Credentials,
+) -> Result<(), String> {
+    let key = credentials.require_key()?;
+    let public_key = key.public_key()?;
+    let signature = key.sign(&publication.tarball.checksum)?;
+    let name = &publication.name;
+    let version = &publication.version;
+
+    match Target::parse(registry)? {
+        Target::Directory(root) => {
+            let package_dir = root.join("packages").join(name);
+            let mut index = read_json(&package_dir.join("index.json"))?
+                .unwrap_or_else(|| json!({ "name": name, "versions": [] }));
+            let owners_path = package_dir.join("owners.json");
+            let is_new = index["versions"]
+                .as_array()
+                .is_none_or(|versions| versions.is_empty());
+            if is_new {
+                write_json(&owners_path, &json!({ "owners": [public_key] }))?;
+            } else {
+                check_owner(&owners_path, name, &public_key)?;
+            }
+            let versions = index["versions"]
+                .as_array_mut()
+                .ok_or_else(|| format!("invalid registry index for '{name}'"))?;
+            if versions
+                .iter()
+                .any(|entry| entry["version"].as_str() == Some(version.as_str()))
+            {
+                return Err(format!(
+                    "{name} {version} is already published to {registry}"
+                ));
+            }
+
+            let version_dir = package_dir.join(version);
+            fs::create_dir_all(&version_dir)
+                .map_err(|error| format!("failed to create {}: {error}", version_dir.display()))?;
+            fs::write(version_dir.join("download"), &publication.tarball.bytes)
+                .map_err(|error| format!("failed to write the package tarball: {error}"))?;
+            write_json(
+                &version_dir.join("index.json"),
+                &json!({
+                    "name": name,
+                    "version": version,
+                    "description": publication.description,
+                    "license": publication.license,
+                    "checksum": publication.tarball.checksum,
+                    "dependencies": publication.dependencies,
+                    "signature": signature,
+                    "public_key": public_key,
+                }),
+            )?;
+            // The index entry goes last: until it exists the version is invisible.
+            versions.push(json!({
+                "version": version,
+                "checksum": publication.tarball.checksum,
+            }));
+            write_json(&package_dir.join("index.json"), &index)
+        }
+        Target::Http(base) => {
+            let token = credentials.require_token(registry)?;
+            let fields = [
+                ("manifest", "tonic.toml", publication.manifest.as_bytes()),
+                (
+                    "tarball",
+                    "package.tar.gz",
+                    publication.tarball.bytes.as_slice(),
+                ),
+                ("checksum", "", publication.tarball.checksum.as_bytes()),
+                ("signature", "", signature.as_bytes()),
+                ("public_key", "", public_key.as_bytes()),
+            ];
+            let (content_type, body) = multipart_body(&fields);
+            http::send(
+                "POST",
+                &format!("{base}/packages"),
+                token,
+                Some((&content_type, body)),
+            )
+            .map(|_| ())
+        }
+    }
+}
+
+pub(crate) fn set_yanked(
+    registry: &str,
+    name: &str,
+    version: &str,
+    yanked: bool,
+    credentials: &Credentials,
+) -> Result<(), String> {
+    match Target::parse(registry)? {
+        Target::Directory(root) => {
+            let package_dir = root.join("packages").join(name);
+            let public_key = credentials.require_key()?.public_key()?;
+            check_owner(&package_dir.join("owners.json"), name, &public_key)?;
+            let index_path = package_dir.join("index.json");
+            let mut index = read_json(&index_path)?
+                .ok_or_else(|| format!("package '{name}' is not published to {registry}"))?;
+            let entry = index["versions"]
+                .as_array_mut()
+                .and_then(|versions| {
+                    versions
+                        .iter_mut()
+                        .find(|entry| entry["version"].as_str() == Some(version))
+                })
+                .ok_or_else(|| format!("{name} {version} is not published to {registry}"))?;
+            entry["yanked"] = Value::Bool(yanked);
+            write_json(&index_path, &index)
+        }
+        Target::Http(base) => {
+            let token = credentials.require_token(registry)?;
+            let method = if yanked { "PUT" } else { "DELETE" };
+            http::send(
+                method,
+                &format!("{base}/packages/{name}/{version}/yank"),
+                token,
+                None,
+            )
+            .map(|_| ())
+        }
+    }
+}
+
+pub(crate) fn list_owners(registry: &str, name: &str) -> Result<Vec<String>, String> {
+    let url = format!("{}/packages/{name}/owners", registry.trim_end_matches('/'));
+    let body = match Target::parse(registry)? {
+        Target::Directory(root) => {
+            let path = root.join("packages").join(name).join("owners.json");
+            fs::read(&path).map_err(|_| format!("package '{name}' has no owners on {registry}"))?
+        }
+        Target::Http(_) => fetch(&url)?,
+    };
+    parse_owners(&body, name)
+}
+
+pub(crate) fn change_owner(
+    registry: &str,
+    name: &str,
+    owner: &str,
+    add: bool,
+    credentials: &Credentials,
+) -> Result<(), String> {
+    match Target::parse(registry)? {
+        Target::Directory(root) => {
+            let owners_path = root.join("packages").join(name).join("owners.json");
+            let public_key = credentials.require_key()?.public_key()?;
+            check_owner(&owners_path, name, &public_key)?;
+            let mut owners = list_owners(registry, name)?;
+            if add {
+                if !owners.iter().any(|existing| existing == owner) {
+                    owners.push(owner.to_string());
+                }
+            } else {
+                if !owners.iter().any(|existing| existing == owner) {
+                    return Err(format!("'{owner}' is not an owner of '{name}'"));
+                }
+                if owners.len() == 1 {
+                    return Err(format!("cannot remove the last owner of '{name}'"));
+                }
+                owners.retain(|existing| existing != owner);
+            }
+            write_json(&owners_path, &json!({ "owners": owners }))
+        }
+        Target::Http(base) => {
+            let token = credentials.require_token(registry)?;
+            let result = if add {
+                let body = json!({ "owner": owner }).to_string().into_bytes();
+                http::send(
+                    "POST",
+                    &format!("{base}/packages/{name}/owners"),
+                    token,
+                    Some(("application/json", body)),
+                )
+            } else {
+                http::send(
+                    "DELETE",
+                    &format!("{base}/packages/{name}/owners/{owner}"),
+                    token,
+                    None,
+                )
+            };
+            result.map(|_| ())
+        }
+    }
+}
+
+fn check_owner(owners_path: &Path, name: &str, public_key: &str) -> Result<(), String> {
+    let owners = fs::read(owners_path)
+        .map_err(|_| format!("package '{name}' has no owners file"))
+        .and_then(|body| parse_owners(&body, name))?;
+    if owners.iter().any(|owner| owner == public_key) {
+        Ok(())
+    } else {
+        Err(format!(
+            "signing key {public_key} is not an owner of '{name}'"
+        ))
+    }
+}
+
+fn parse_owners(body: &[u8], name: &str) -> Result<Vec<String>, String> {
+    let value: Value = serde_json::from_slice(body)
+        .map_err(|error| format!("invalid owners list for '{name}': {error}"))?;
+    Ok(value["owners"]
+        .as_array()
+        .map(|owners| {
+            owners
+                .iter()
+                .filter_map(|owner| owner.as_str().map(str::to_string))
+                .collect()
+        })
+        .unwrap_or_default())
+}
+
+fn read_json(path: &Path) -> Result<Option<Value>, String> {
+    match fs::read(path) {
+        Ok(body) => serde_json::from_slice(&body)
+            .map(Some)
+            .map_err(|error| format!("invalid registry file {}: {error}", path.display())),
+        Err(_) => Ok(None),
+    }
+}
+
+fn write_json(path: &Path, value: &Value) -> Result<(), String> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|error| format!("failed to create {}: {error}", parent.display()))?;
+    }
+    let contents = serde_json::to_string_pretty(value).expect("registry json should serialize");
+    fs::write(path, format!("{contents}\n"))
+        .map_err(|error| format!("failed to write {}: {error}", path.display()))
+}
+
+/// A `multipart/form-data` body; fields with a file name are sent as files.
+fn multipart_body(fields: &[(&str, &str, &[u8])]) -> (String, Vec<u8>) {
+    let boundary = "tonic-publish-boundary-7f3a9c2e";
+    let mut body = Vec::new();
+    for (name, file_name, contents) in fields {
+        body.extend_from_slice(format!("--{boundary}\r\n").as_bytes());
+        if file_name.is_empty() {
+            body.extend_from_slice(
+                format!("Content-Disposition: form-data; name=\"{name}\"\r\n\r\n").as_bytes(),
+            );
+        } else {
+            body.extend_from_slice(
+                format!(
+                    "Content-Disposition: form-data; name=\"{name}\"; filename=\"{file_name}\"\r\nContent-Type: application/octet-stream\r\n\r\n"
+                )
+                .as_bytes(),
+            );
+        }
+        body.extend_from_slice(contents);
+        body.extend_from_slice(b"\r\n");
+    }
+    body.extend_from_slice(format!("--{boundary}--\r\n").as_bytes());
+    (format!("multipart/form-data; boundary={boundary}"), body)
+}
+
+#[cfg(feature = "network")]
+mod http {
+    pub(super) fn send(
+        method: &str,
+        url: &str,
+        token: &str,
+        body: Option<(&str, Vec<u8>)>,
+    ) -> Result<Vec<u8>, String> {
+        let method =
+            reqwest::Method::from_bytes(method.as_bytes()).map_err(|error| error.to_string())?;
+        let client = reqwest::blocking::Client::builder()
+            .timeout(std::time::Duration::from_secs(120))
+            .build()
+            .map_err(|error| error.to_string())?;
+        let mut request = client
+            .request(method.clone(), url)
+            .header("Authorization", format!("Bearer {token}"));
+        if let Some((content_type, body)) = body {
+            request = request.header("Content-Type", content_type).body(body);
+        }
+        let response = request
+            .send()
+            .map_err(|error| format!("{method} {url} failed: {error}"))?;
+        let status = response.status();
+        let bytes = response
+            .bytes()
+            .map_err(|error| format!("{method} {url} failed: {error}"))?;
+        if !status.is_success() {
+            let detail = String::from_utf8_lossy(&bytes).trim().to_string();
+            return Err(if detail.is_empty() {
+                format!("{method} {url} returned {status}")
+            } else {
+                format!("{method} {url} returned {status}: {detail}")
+            });
+        }
+        Ok(bytes.to_vec())
+    }
+}
+
+#[cfg(not(feature = "network"))]
+mod http {
+    pub(super) fn send(
+        method: &str,
+        url: &str,
+        _token: &str,
+        _body: Option<(&str, Vec<u8>)>,
+    ) -> Result<Vec<u8>, String> {
+        Err(format!(
+            "cannot {method} {url}: this tonic build has no network support; use a file:// registry"
+        ))
+    }
+}
@@ -0,0 +1,213 @@
+//! Registry API client (see `docs/package-registry-protocol.md`).
+//!
+//! Registries are reached over HTTP(S) or, for mirrors and tests, as a
+//! directory tree through a `file://` URL. A file registry mirrors the API
+//! paths; a path that names a directory is served from its `index.json`:
+//!
+//! ```text
+//! packages/json/index.json            GET /packages/json
+//! packages/json/1.1.0/index.json      GET /packages/json/1.1.0
+//! packages/json/1.1.0/download        GET /packages/json/1.1.0/download
+//! ```
+
+use super::semver::{Requirement, Version};
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, HashMap};
+use std::path::Path;
+
+pub(crate) const DEFAULT_REGISTRY: &str = "https://registry.tonic-lang.org/v1";
+
+#[derive(Debug, Clone, Deserialize)]
+pub(crate) struct PackageIndex {
+    pub(crate) versions: Vec<IndexEntry>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub(crate) struct IndexEntry {
+    pub(crate) version: String,
+    pub(crate) checksum: String,
+    /// Set by `tonic yank`; new resolutions skip the version.
+    #[serde(default)]
+    pub(crate) yanked: bool,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub(crate) struct VersionMetadata {
+    #[serde(default)]
+    pub(crate) checksum: Option<String>,
+    #[serde(default)]
+    pub(crate) dependencies: BTreeMap<String, String>,
+    #[serde(default)]
+    pub(crate) download_url: Option<String>,
+}
+
+/// A published version as seen by the resolver.
+#[derive(Debug, Clone)]
+pub(crate) struct Release {
+    pub(crate) version: Version,
+    pub(crate) checksum: String,
+    pub(crate) yanked: bool,
+}
+
+/// Caches index and metadata responses for one resolution.
+#[derive(Default)]
+pub(crate) struct RegistryClient {
+    indexes: HashMap<(String, String), Vec<Release>>,
+    metadata: HashMap<(String, String, Version), VersionMetadata>,
+}
+
+impl RegistryClient {
+    /// Published versions of `name`, skipping entries whose version does not
+    /// parse.
+    pub(crate) fn releases(&mut self, registry: &str, name: &str) -> Result<&[Release], String> {
+        let key = (registry.to_string(), name.to_string());
+        if !self.indexes.contains_key(&key) {
+            let url = format!("{}/packages/{name}", registry.trim_end_matches('/'));
+            let body = fetch(&url).map_err(|error| {
+                format!("failed to fetch the index for '{name}' from {registry}: {error}")
+            })?;
+            let index: PackageIndex = serde_json::from_slice(&body)
+                .map_err(|error| format!("invalid registry index for '{name}': {error}"))?;
+            let releases = index
+                .versions
+                .into_iter()
+                .filter_map(|entry| {
+                    Version::parse(&entry.version).ok().map(|version| Release {
+                        version,
+                        checksum: entry.checksum,
+                        yanked: entry.yanked,
+                    })
+                })
+                .collect();
+            self.indexes.insert(key.clone(), releases);
+        }
+        Ok(&self.indexes[&key])
+    }
+
+    pub(crate) fn metadata(
+        &mut self,
+        registry: &str,
+        name: &str,
+        version: &Version,
+    ) -> Result<&VersionMetadata, String> {
+        let key = (registry.to_string(), name.to_string(), version.clone());
+        if !self.metadata.contains_key(&key) {
+            let url = format!(
+                "{}/packages/{name}/{version}",
+                registry.trim_end_matches('/')
+            );
+            let body = fetch(&url).map_err(|error| {
+                format!("failed to fetch metadata for '{name}' {version} from {registry}: {error}")
+            })?;
+            let metadata: VersionMetadata = serde_json::from_slice(&body).map_err(|error| {
+                format!("invalid registry metadata for '{name}' {version}: {error}")
+            })?;
+            self.metadata.insert(key.clone(), metadata);
+        }
+        Ok(&self.metadata[&key])
+    }
+
+    /// The requirements `name` `version` declares on other packages.
+    pub(crate) fn dependencies(
+        &mut self,
+        registry: &str,
+        name: &str,
+        version: &Version,
+    ) -> Result<Vec<(String, Requirement)>, String> {
+        self.metadata(registry, name, version)?
+            .dependencies
+            .iter()
+            .map(|(dependency, requirement)| {
+                Requirement::parse(requirement)
+                    .map(|requirement| (dependency.clone(), requirement))
+                    .map_err(|error| format!("'{name}' {version} has an {error}"))
+            })
+            .collect()
+    }
+
+    pub(crate) fn download_url(
+        &mut self,
+        registry: &str,
+        name: &str,
+        version: &Version,
+    ) -> Result<String, String> {
+        let metadata = self.metadata(registry, name, version)?;
+        Ok(metadata.download_url.clone().unwrap_or_else(|| {
+            format!(
+                "{}/packages/{name}/{version}/download",
+                registry.trim_end_matches('/')
+            )
+        }))
+    }
+}
+
+/// `sha256:<hex>` digest of a package tarball, as listed in the index.
+pub(crate) fn checksum(bytes: &[u8]) -> String {
+    let digest = Sha256::digest(bytes);
+    let hex = digest
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect::<String>();
+    format!("sha256:{hex}")
+}
+
+/// Download a package tarball and check it against the locked checksum.
+pub(crate) fn download(name: &str, url: &str, expected_checksum: &str) -> Result<Vec<u8>, String> {
+    let bytes = fetch(url)
+        .map_err(|error| format!("failed to download registry dependency '{name}': {error}"))?;
+    let actual = checksum(&bytes);
+    if !actual.eq_ignore_ascii_case(expected_checksum) {
+        return Err(format!(
+            "checksum mismatch for registry dependency '{name}' from {url}: expected {expected_checksum}, got {actual}"
+        ));
+    }
+    Ok(bytes)
+}
+
+pub(crate) fn fetch(url: &str) -> Result<Vec<u8>, String> {
+    if let Some(path) = url.strip_prefix("file://") {
+        let path = Path::new(path);
+        let path = if path.is_dir() {
+            path.join("index.json")
+        } else {
+            path.to_path_buf()
+        };
+        return std::fs::read(&path).map_err(|error| format!("{}: {error}", path.display()));
+    }
+
+    if url.starts_with("http://") || url.starts_with("https://") {
+        return fetch_http(url);
+    }
+
+    Err(format!(
+        "unsupported registry URL '{url}' (expected http://, https:// or file://)"
+    ))
+}
+
+#[cfg(feature = "network")]
+fn fetch_http(url: &str) -> Result<Vec<u8>, String> {
+    let client = reqwest::blocking::Client::builder()
+        .timeout(std::time::Duration::from_secs(60))
+        .build()
+        .map_err(|error| error.to_string())?;
+    let response = client
+        .get(url)
+        .send()
+        .map_err(|error| format!("GET {url} failed: {error}"))?;
+    let status = response.status();
+    if !status.is_success() {
+        return Err(format!("GET {url} returned {status}"));
+    }
+    response
+        .bytes()
+        .map(|bytes| bytes.to_vec())
+        .map_err(|error| format!("GET {url} failed: {error}"))
+}
+
+#[cfg(not(feature = "network"))]
+fn fetch_http(url: &str) -> Result<Vec<u8>, String> {
+    Err(format!(
+        "cannot fetch {url}: this tonic build has no network support; use a file:// registry"
+    ))
+}
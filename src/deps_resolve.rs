@@ -0,0 +1,382 @@
+//! Backtracking version solver for registry dependencies.
+//!
+//! Packages are decided one at a time, most constrained first. Each candidate
+//! (highest stable version first, or the currently locked version when it
+//! still fits) adds its own requirements; a requirement that rules out every
+//! version of a package sends the search back to the last decision. When
+//! nothing fits, the error lists every requirement on the package that could
+//! not be satisfied and who declared it.
+
+use super::semver::{Requirement, Version};
+use std::collections::BTreeMap;
+
+/// Candidates tried before giving up on pathological dependency graphs.
+const MAX_RESOLVE_STEPS: usize = 10_000;
+
+/// Requirements declared in `tonic.toml` are reported as coming from here.
+pub(crate) const ROOT_REQUIRER: &str = "tonic.toml";
+
+pub(crate) trait PackageSource {
+    fn versions(&mut self, name: &str) -> Result<Vec<Version>, String>;
+
+    fn dependencies(
+        &mut self,
+        name: &str,
+        version: &Version,
+    ) -> Result<Vec<(String, Requirement)>, String>;
+}
+
+/// Pick one version of every package reachable from `root`. Versions in
+/// `preferred` (the existing lockfile) are kept when they still satisfy
+/// every requirement.
+pub(crate) fn resolve(
+    root: &[(String, Requirement)],
+    preferred: &BTreeMap<String, Version>,
+    source: &mut dyn PackageSource,
+) -> Result<BTreeMap<String, Version>, String> {
+    let mut solver = Solver {
+        source,
+        preferred,
+        constraints: root
+            .iter()
+            .map(|(package, requirement)| Constraint {
+                package: package.clone(),
+                requirement: requirement.clone(),
+                required_by: ROOT_REQUIRER.to_string(),
+            })
+            .collect(),
+        selected: BTreeMap::new(),
+        conflict: None,
+        steps: 0,
+    };
+
+    match solver.solve() {
+        Ok(()) => Ok(solver.selected),
+        Err(Failure::Fatal(message)) => Err(message),
+        Err(Failure::Conflict) => Err(solver
+            .conflict
+            .map(|(_, explanation)| explanation)
+            .unwrap_or_else(|| "dependency resolution failed".to_string())),
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Constraint {
+    package: String,
+    requirement: Requirement,
+    required_by: String,
+}
+
+enum Failure {
+    /// This branch cannot work; try the next candidate.
+    Conflict,
+    /// Fetching failed or the search gave up; stop resolving.
+    Fatal(String),
+}
+
+struct Solver<'a> {
+    source: &'a mut dyn PackageSource,
+    preferred: &'a BTreeMap<String, Version>,
+    constraints: Vec<Constraint>,
+    selected: BTreeMap<String, Version>,
+    /// The first conflict found, and whether no version at all satisfies the
+    /// requirements involved (those explain a failure better than conflicts
+    /// that depend on the order of decisions).
+    conflict: Option<(bool, String)>,
+    steps: usize,
+}
+
+impl Solver<'_> {
+    fn solve(&mut self) -> Result<(), Failure> {
+        let Some((package, candidates)) = self.next_package()? else {
+            return Ok(());
+        };
+        if candidates.is_empty() {
+            let explanation = self.explain(&package)?;
+            self.record_conflict(true, explanation);
+            return Err(Failure::Conflict);
+        }
+
+        for version in candidates {
+            self.steps += 1;
+            if self.steps > MAX_RESOLVE_STEPS {
+                return Err(Failure::Fatal(format!(
+                    "dependency resolution gave up after trying {MAX_RESOLVE_STEPS} versions; pin versions with `==` to narrow the search"
+                )));
+            }
+
+            let dependencies = self
+                .source
+                .dependencies(&package, &version)
+                .map_err(Failure::Fatal)?;
+            let mark = self.constraints.len();
+            let required_by = format!("{package} {version}");
+            for (dependency, requirement) in dependencies {
+                self.constraints.push(Constraint {
+                    package: dependency,
+                    requirement,
+                    required_by: required_by.clone(),
+                });
+            }
+            self.selected.insert(package.clone(), version);
+
+            match self.violated_constraint(mark)? {
+                Some(conflict) => self.record_conflict(conflict.0, conflict.1),
+                None => match self.solve() {
+                    Ok(()) => return Ok(()),
+                    Err(Failure::Conflict) => {}
+                    Err(fatal) => return Err(fatal),
+                },
+            }
+
+            self.selected.remove(&package);
+            self.constraints.truncate(mark);
+        }
+
+        Err(Failure::Conflict)
+    }
+
+    /// The undecided package with the fewest candidates, ties broken by name.
+    fn next_package(&mut self) -> Result<Option<(String, Vec<Version>)>, Failure> {
+        let mut pending = self
+            .constraints
+            .iter()
+            .map(|constraint| constraint.package.clone())
+            .filter(|package| !self.selected.contains_key(package))
+            .collect::<Vec<_>>();
+        pending.sort();
+        pending.dedup();
+
+        let mut best: Option<(String, Vec<Version>)> = None;
+        for package in pending {
+            let candidates = self.candidates(&package)?;
+            if best
+                .as_ref()
+                .is_none_or(|(_, best)| candidates.len() < best.len())
+            {
+                best = Some((package, candidates));
+            }
+        }
+        Ok(best)
+    }
+
+    /// Versions satisfying every current requirement on `package`, in the
+    /// order they should be tried.
+    fn candidates(&mut self, package: &str) -> Result<Vec<Version>, Failure> {
+        let mut candidates = self
+            .source
+            .versions(package)
+            .map_err(Failure::Fatal)?
+            .into_iter()
+            .filter(|version| {
+                self.constraints_on(package)
+                    .all(|constraint| constraint.requirement.matches(version))
+            })
+            .collect::<Vec<_>>();
+        candidates.sort_by(|left, right| {
+            (!left.is_prerelease(), left)
+                .cmp(&(!right.is_prerelease(), right))
+                .reverse()
+        });
+        candidates.dedup();
+        if let Some(preferred) = self.preferred.get(package) {
+            if let Some(index) = candidates.iter().position(|version| version == preferred) {
+                let preferred = candidates.remove(index);
+                candidates.insert(0, preferred);
+            }
+        }
+        Ok(candidates)
+    }
+
+    fn constraints_on<'c>(&'c self, package: &'c str) -> impl Iterator<Item = &'c Constraint> {
+        self.constraints
+            .iter()
+            .filter(move |constraint| constraint.package == package)
+    }
+
+    /// A requirement added since `mark` that an already selected version
+    /// does not satisfy.
+    fn violated_constraint(&mut self, mark: usize) -> Result<Option<(bool, String)>, Failure> {
+        let violated = self.constraints[mark..].iter().find_map(|constraint| {
+            let selected = self.selected.get(&constraint.package)?;
+            (!constraint.requirement.matches(selected))
+                .then(|| (constraint.clone(), selected.clone()))
+        });
+        let Some((constraint, selected)) = violated else {
+            return Ok(None);
+        };
+
+        if self.candidates(&constraint.package)?.is_empty() {
+            return self
+                .explain(&constraint.package)
+                .map(|text| Some((true, text)));
+        }
+        Ok(Some((
+            false,
+            format!(
+                "{} requires {} {}, but {} {} was already selected",
+                constraint.required_by,
+                constraint.package,
+                constraint.requirement,
+                constraint.package,
+                selected
+            ),
+        )))
+    }
+
+    fn explain(&mut self, package: &str) -> Result<String, Failure> {
+        let mut versions = self.source.versions(package).map_err(Failure::Fatal)?;
+        if versions.is_empty() {
+            return Ok(format!("package '{package}' has no published versions"));
+        }
+        versions.sort();
+        versions.dedup();
+
+        let mut lines = vec![format!(
+            "no version of '{package}' satisfies all requirements:"
+        )];
+        for constraint in self.constraints_on(package) {
+            lines.push(format!(
+                "  {} requires {} {}",
+                constraint.required_by, package, constraint.requirement
+            ));
+        }
+        lines.push(format!(
+            "available versions: {}",
+            versions
+                .iter()
+                .map(Version::to_string)
+                .collect::<Vec<_>>()
+                .join(", ")
+        ));
+        Ok(lines.join("\n"))
+    }
+
+    fn record_conflict(&mut self, definite: bool, explanation: String) {
+        if self
+            .conflict
+            .as_ref()
+            .is_none_or(|(recorded, _)| definite && !recorded)
+        {
+            self.conflict = Some((definite, explanation));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    type Release = (Version, Vec<(String, Requirement)>);
+
+    #[derive(Default)]
+    struct MemorySource(HashMap<String, Vec<Release>>);
+
+    impl MemorySource {
+        fn publish(&mut self, name: &str, version: &str, dependencies: &[(&str, &str)]) {
+            self.0.entry(name.to_string()).or_default().push((
+                Version::parse(version).unwrap(),
+                dependencies
+                    .iter()
+                    .map(|(name, requirement)| {
+                        (name.to_string(), Requirement::parse(requirement).unwrap())
+                    })
+                    .collect(),
+            ));
+        }
+    }
+
+    impl PackageSource for MemorySource {
+        fn versions(&mut self, name: &str) -> Result<Vec<Version>, String> {
+            Ok(self
+                .0
+                .get(name)
+                .map(|releases| {
+                    releases
+                        .iter()
+                        .map(|(version, _)| version.clone())
+                        .collect()
+                })
+                .unwrap_or_default())
+        }
+
+        fn dependencies(
+            &mut self,
+            name: &str,
+            version: &Version,
+        ) -> Result<Vec<(String, Requirement)>, String> {
+            Ok(self.0[name]
+                .iter()
+                .find(|(candidate, _)| candidate == version)
+                .map(|(_, dependencies)| dependencies.clone())
+                .unwrap_or_default())
+        }
+    }
+
+    fn root(requirements: &[(&str, &str)]) -> Vec<(String, Requirement)> {
+        requirements
+            .iter()
+            .map(|(name, requirement)| (name.to_string(), Requirement::parse(requirement).unwrap()))
+            .collect()
+    }
+
+    fn rendered(resolution: &BTreeMap<String, Version>) -> Vec<String> {
+        resolution
+            .iter()
+            .map(|(name, version)| format!("{name} {version}"))
+            .collect()
+    }
+
+    #[test]
+    fn backtracks_to_older_versions_when_transitive_requirements_conflict() {
+        let mut source = MemorySource::default();
+        source.publish("json", "1.0.0", &[("utf8", "~> 0.2")]);
+        source.publish("json", "1.1.0", &[("utf8", "~> 0.3")]);
+        source.publish("http", "0.5.0", &[("utf8", "~> 0.2.1")]);
+        source.publish("utf8", "0.2.0", &[]);
+        source.publish("utf8", "0.2.4", &[]);
+        source.publish("utf8", "0.3.0", &[]);
+        source.publish("utf8", "0.3.1-rc.1", &[]);
+
+        let resolution = resolve(
+            &root(&[("json", "~> 1.0"), ("http", "^0.5")]),
+            &BTreeMap::new(),
+            &mut source,
+        )
+        .expect("resolution should succeed");
+        assert_eq!(
+            rendered(&resolution),
+            ["http 0.5.0", "json 1.0.0", "utf8 0.2.4"]
+        );
+
+        let locked = BTreeMap::from([
+            ("json".to_string(), Version::parse("1.0.0").unwrap()),
+            ("utf8".to_string(), Version::parse("0.2.0").unwrap()),
+        ]);
+        let resolution = resolve(&root(&[("json", "~> 1.0")]), &locked, &mut source)
+            .expect("resolution should succeed");
+        assert_eq!(rendered(&resolution), ["json 1.0.0", "utf8 0.2.0"]);
+    }
+
+    #[test]
+    fn explains_requirements_that_no_version_satisfies() {
+        let mut source = MemorySource::default();
+        source.publish("json", "1.1.0", &[("utf8", "~> 0.3")]);
+        source.publish("utf8", "0.2.0", &[]);
+        source.publish("utf8", "0.3.0", &[]);
+
+        assert_eq!(
+            resolve(
+                &root(&[("json", "~> 1.0"), ("utf8", "~> 0.2.0")]),
+                &BTreeMap::new(),
+                &mut source,
+            ),
+            Err("no version of 'utf8' satisfies all requirements:\n  tonic.toml requires utf8 ~> 0.2.0\n  json 1.1.0 requires utf8 ~> 0.3\navailable versions: 0.2.0, 0.3.0".to_string())
+        );
+        assert_eq!(
+            resolve(&root(&[("missing", "^1.0")]), &BTreeMap::new(), &mut source),
+            Err("package 'missing' has no published versions".to_string())
+        );
+    }
+}
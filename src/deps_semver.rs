@@ -0,0 +1,326 @@
+//! Package versions and version requirements for registry dependencies.
+//!
+//! Versions are `MAJOR.MINOR.PATCH[-PRE][+BUILD]`. Requirements use the
+//! operators from `docs/package-registry-protocol.md` (`~>`, `^`, `>=`, `>`,
+//! `<=`, `<`, `==`) and can be combined with `and` / `or`, e.g.
+//! `>= 1.2 and < 1.5 or == 2.0.0`.
+
+use std::cmp::Ordering;
+use std::fmt;
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub(crate) struct Version {
+    pub(crate) major: u64,
+    pub(crate) minor: u64,
+    pub(crate) patch: u64,
+    pub(crate) pre: Vec<String>,
+}
+
+impl Version {
+    pub(crate) fn new(major: u64, minor: u64, patch: u64) -> Self {
+        Self {
+            major,
+            minor,
+            patch,
+            pre: Vec::new(),
+        }
+    }
+
+    pub(crate) fn parse(text: &str) -> Result<Self, String> {
+        let (version, parts) = parse_partial(text)?;
+        if parts < 3 {
+            return Err(format!(
+                "invalid version '{}' (expected MAJOR.MINOR.PATCH)",
+                text.trim()
+            ));
+        }
+        Ok(version)
+    }
+
+    pub(crate) fn is_prerelease(&self) -> bool {
+        !self.pre.is_empty()
+    }
+}
+
+impl Ord for Version {
+    fn cmp(&self, other: &Self) -> Ordering {
+        (self.major, self.minor, self.patch)
+            .cmp(&(other.major, other.minor, other.patch))
+            .then_with(|| compare_prerelease(&self.pre, &other.pre))
+    }
+}
+
+impl PartialOrd for Version {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl fmt::Display for Version {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{}.{}", self.major, self.minor, self.patch)?;
+        if self.is_prerelease() {
+            write!(f, "-{}", self.pre.join("."))?;
+        }
+        Ok(())
+    }
+}
+
+/// A release sorts after its pre-releases; numeric identifiers sort
+/// numerically and before alphanumeric ones.
+fn compare_prerelease(left: &[String], right: &[String]) -> Ordering {
+    match (left.is_empty(), right.is_empty()) {
+        (true, true) => return Ordering::Equal,
+        (true, false) => return Ordering::Greater,
+        (false, true) => return Ordering::Less,
+        (false, false) => {}
+    }
+
+    for (left, right) in left.iter().zip(right) {
+        let ordering = match (left.parse::<u64>(), right.parse::<u64>()) {
+            (Ok(left), Ok(right)) => left.cmp(&right),
+            (Ok(_), Err(_)) => Ordering::Less,
+            (Err(_), Ok(_)) => Ordering::Greater,
+            (Err(_), Err(_)) => left.cmp(right),
+        };
+        if ordering != Ordering::Equal {
+            return ordering;
+        }
+    }
+    left.len().cmp(&right.len())
+}
+
+/// Parses `1`, `1.2` or `1.2.3-pre`, returning how many numeric parts were
+/// given; missing parts are zero.
+fn parse_partial(text: &str) -> Result<(Version, usize), String> {
+    let text = text.trim();
+    let invalid = || format!("invalid version '{text}'");
+    let without_build = text.split_once('+').map_or(text, |(version, _)| version);
+    let (numbers, pre) = match without_build.split_once('-') {
+        Some((numbers, pre)) => (numbers, Some(pre)),
+        None => (without_build, None),
+    };
+
+    let parts = numbers
+        .split('.')
+        .map(|part| {
+            if part.is_empty() || !part.bytes().all(|byte| byte.is_ascii_digit()) {
+                return Err(invalid());
+            }
+            part.parse::<u64>().map_err(|_| invalid())
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    if parts.is_empty() || parts.len() > 3 {
+        return Err(invalid());
+    }
+
+    let pre = match pre {
+        Some(pre) => {
+            let identifiers = pre.split('.').map(str::to_string).collect::<Vec<_>>();
+            if identifiers.iter().any(|identifier| {
+                identifier.is_empty()
+                    || !identifier
+                        .bytes()
+                        .all(|byte| byte.is_ascii_alphanumeric() || byte == b'-')
+            }) {
+                return Err(invalid());
+            }
+            if parts.len() < 3 {
+                return Err(invalid());
+            }
+            identifiers
+        }
+        None => Vec::new(),
+    };
+
+    Ok((
+        Version {
+            major: parts[0],
+            minor: parts.get(1).copied().unwrap_or(0),
+            patch: parts.get(2).copied().unwrap_or(0),
+            pre,
+        },
+        parts.len(),
+    ))
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Op {
+    Exact,
+    Greater,
+    GreaterEq,
+    Less,
+    LessEq,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Comparator {
+    op: Op,
+    version: Version,
+}
+
+impl Comparator {
+    fn matches(&self, version: &Version) -> bool {
+        match self.op {
+            Op::Exact => version == &self.version,
+            Op::Greater => version > &self.version,
+            Op::GreaterEq => version >= &self.version,
+            Op::Less => version < &self.version,
+            Op::LessEq => version <= &self.version,
+        }
+    }
+}
+
+/// A parsed version requirement such as `~> 1.2` or `>= 1.0 and < 2.0`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Requirement {
+    source: String,
+    /// Alternatives joined by `or`, each a set of comparators joined by `and`.
+    alternatives: Vec<Vec<Comparator>>,
+}
+
+impl Requirement {
+    pub(crate) fn parse(text: &str) -> Result<Self, String> {
+        let source = text.split_whitespace().collect::<Vec<_>>().join(" ");
+        let invalid = |detail: String| format!("invalid version requirement '{source}': {detail}");
+        if source.is_empty() {
+            return Err(invalid("requirement is empty".to_string()));
+        }
+
+        let mut alternatives = Vec::new();
+        for alternative in source.split(" or ") {
+            let mut comparators = Vec::new();
+            for clause in alternative.split(" and ") {
+                comparators.extend(parse_clause(clause).map_err(&invalid)?);
+            }
+            alternatives.push(comparators);
+        }
+
+        Ok(Self {
+            source,
+            alternatives,
+        })
+    }
+
+    /// Pre-releases only match when the requirement itself names one.
+    pub(crate) fn matches(&self, version: &Version) -> bool {
+        if version.is_prerelease() && !self.allows_prerelease() {
+            return false;
+        }
+        self.alternatives.iter().any(|comparators| {
+            comparators
+                .iter()
+                .all(|comparator| comparator.matches(version))
+        })
+    }
+
+    fn allows_prerelease(&self) -> bool {
+        self.alternatives
+            .iter()
+            .flatten()
+            .any(|comparator| comparator.version.is_prerelease())
+    }
+}
+
+impl fmt::Display for Requirement {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.source)
+    }
+}
+
+fn parse_clause(clause: &str) -> Result<Vec<Comparator>, String> {
+    let clause = clause.trim();
+    let (operator, rest) = ["~>", "^", ">=", "<=", "==", ">", "<"]
+        .into_iter()
+        .find_map(|operator| {
+            clause
+                .strip_prefix(operator)
+                .map(|rest| (operator, rest.trim()))
+        })
+        .unwrap_or(("==", clause));
+    if rest.is_empty() {
+        return Err(format!("'{clause}' is missing a version"));
+    }
+    let (version, parts) = parse_partial(rest)?;
+
+    let comparator = |op, version| Comparator { op, version };
+    let comparators = match operator {
+        // `~> X.0` allows the next major, `~> X.Y` and `~> X.Y.Z` the next minor.
+        "~>" => {
+            if parts < 2 {
+                return Err(format!("'{clause}' needs at least MAJOR.MINOR"));
+            }
+            let upper = if parts == 2 && version.minor == 0 {
+                Version::new(version.major + 1, 0, 0)
+            } else {
+                Version::new(version.major, version.minor + 1, 0)
+            };
+            vec![
+                comparator(Op::GreaterEq, version),
+                comparator(Op::Less, upper),
+            ]
+        }
+        "^" => {
+            let upper = Version::new(version.major + 1, 0, 0);
+            vec![
+                comparator(Op::GreaterEq, version),
+                comparator(Op::Less, upper),
+            ]
+        }
+        ">=" => vec![comparator(Op::GreaterEq, version)],
+        "<=" => vec![comparator(Op::LessEq, version)],
+        ">" => vec![comparator(Op::Greater, version)],
+        "<" => vec![comparator(Op::Less, version)],
+        _ => {
+            if parts < 3 {
+                return Err(format!("'{clause}' must pin MAJOR.MINOR.PATCH"));
+            }
+            vec![comparator(Op::Exact, version)]
+        }
+    };
+    Ok(comparators)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn matches(requirement: &str, version: &str) -> bool {
+        Requirement::parse(requirement)
+            .expect("requirement should parse")
+            .matches(&Version::parse(version).expect("version should parse"))
+    }
+
+    #[test]
+    fn requirement_operators_follow_the_registry_protocol() {
+        assert!(matches("~> 1.0", "1.9.3"));
+        assert!(!matches("~> 1.0", "2.0.0"));
+        assert!(matches("~> 1.2", "1.2.7"));
+        assert!(!matches("~> 1.2", "1.3.0"));
+        assert!(matches("~> 1.2.3", "1.2.9"));
+        assert!(!matches("~> 1.2.3", "1.2.2"));
+        assert!(matches("^2.1", "2.9.0"));
+        assert!(!matches("^2.1", "3.0.0"));
+        assert!(matches(">= 0.5", "4.0.0"));
+        assert!(matches("== 1.0.0", "1.0.0"));
+        assert!(!matches("== 1.0.0", "1.0.1"));
+        assert!(matches(">= 1.0 and < 1.5 or == 2.0.0", "2.0.0"));
+        assert!(!matches(">= 1.0 and < 1.5 or == 2.0.0", "1.5.0"));
+    }
+
+    #[test]
+    fn prereleases_sort_first_and_need_an_explicit_requirement() {
+        let rc = Version::parse("2.0.0-rc.2").unwrap();
+        assert!(rc < Version::parse("2.0.0").unwrap());
+        assert!(Version::parse("2.0.0-rc.10").unwrap() > rc);
+        assert!(!matches(">= 1.0", "2.0.0-rc.2"));
+        assert!(matches(">= 2.0.0-rc.1", "2.0.0-rc.2"));
+        assert_eq!(rc.to_string(), "2.0.0-rc.2");
+
+        assert_eq!(
+            Requirement::parse("~> 1").unwrap_err(),
+            "invalid version requirement '~> 1': '~> 1' needs at least MAJOR.MINOR"
+        );
+        assert!(Requirement::parse(">= x.1").is_err());
+    }
+}
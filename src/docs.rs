@@ -1,7 +1,7 @@
 use crate::cli_diag::{CliDiagnostic, EXIT_OK};
 use crate::lexer::scan_tokens;
 use crate::manifest::load_run_source;
-use crate::parser::{parse_ast, Expr, Module};
+use crate::parser::{parse_ast, Expr, Function, Module};
 use crate::stdlib_catalog::STDLIB_SOURCES;
 use std::path::{Path, PathBuf};
 
@@ -34,30 +34,82 @@ fn module_summary(module: &Module) -> Option<String> {
     })
 }
 
-/// Build a list of (function_name, arity, param_names, doc_string_or_None) entries
-/// for **public** functions only.
+/// A `@moduledoc` or `@doc` string literal.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct DocString<'a> {
+    /// The documented function; `None` for the `@moduledoc`.
+    pub(crate) function: Option<&'a Function>,
+    pub(crate) text: &'a str,
+    /// Offset of the string literal in the module's source.
+    pub(crate) offset: usize,
+}
+
+/// Every doc string of `module`: the `@moduledoc` first, then the `@doc` of
+/// each public function, paired as in [`function_docs`].
+pub(crate) fn doc_strings(module: &Module) -> Vec<DocString<'_>> {
+    let moduledoc = module
+        .attributes
+        .iter()
+        .find(|attr| attr.name == "moduledoc")
+        .and_then(|attr| string_literal(&attr.value))
+        .map(|(text, offset)| DocString {
+            function: None,
+            text,
+            offset,
+        });
+
+    moduledoc
+        .into_iter()
+        .chain(
+            public_function_docs(module)
+                .into_iter()
+                .filter_map(|(func, doc)| {
+                    doc.map(|(text, offset)| DocString {
+                        function: Some(func),
+                        text,
+                        offset,
+                    })
+                }),
+        )
+        .collect()
+}
+
+fn string_literal(expr: &Expr) -> Option<(&str, usize)> {
+    match expr {
+        Expr::String { value, offset, .. } => Some((value.as_str(), *offset)),
+        _ => None,
+    }
+}
+
+/// Public functions with their `@doc` literal and its offset.
 ///
 /// Strategy: `@doc` attributes are matched to public functions in declaration
 /// order. The Nth `@doc` attribute is associated with the Nth public function.
-fn function_docs(module: &Module) -> Vec<(String, usize, Vec<String>, Option<String>)> {
-    let doc_attrs: Vec<&str> = module
+fn public_function_docs(module: &Module) -> Vec<(&Function, Option<(&str, usize)>)> {
+    let mut doc_iter = module
         .attributes
         .iter()
         .filter(|attr| attr.name == "doc")
-        .filter_map(|attr| expr_as_str(&attr.value))
-        .collect();
-
-    let mut doc_iter = doc_attrs.into_iter();
+        .filter_map(|attr| string_literal(&attr.value));
 
     module
         .functions
         .iter()
         .filter(|func| !func.is_private())
-        .map(|func| {
+        .map(|func| (func, doc_iter.next()))
+        .collect()
+}
+
+/// Build a list of (function_name, arity, param_names, doc_string_or_None) entries
+/// for **public** functions only.
+fn function_docs(module: &Module) -> Vec<(String, usize, Vec<String>, Option<String>)> {
+    public_function_docs(module)
+        .into_iter()
+        .map(|(func, doc)| {
             let param_names: Vec<String> =
                 func.params.iter().map(|p| p.name().to_string()).collect();
             let arity = param_names.len();
-            let doc = doc_iter.next().map(|s| s.to_string());
+            let doc = doc.map(|(text, _)| text.to_string());
             (func.name.clone(), arity, param_names, doc)
         })
         .collect()
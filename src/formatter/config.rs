@@ -0,0 +1,300 @@
+use crate::manifest;
+use std::path::Path;
+
+pub(crate) const DEFAULT_LINE_LENGTH: usize = 98;
+
+const FORMATTER_CONFIG_FILE: &str = ".tonic-formatter.toml";
+const PROJECT_MANIFEST_FILE: &str = "tonic.toml";
+
+/// Module directives that are always written without parentheses.
+const DIRECTIVES_WITHOUT_PARENS: &[&str] = &[
+    "alias",
+    "import",
+    "require",
+    "doctest",
+    "use",
+    "defstruct",
+    "defprotocol",
+    "defimpl",
+];
+
+/// Whether wrapped argument and collection lists end with a comma.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum TrailingComma {
+    /// Never emit a trailing comma; existing ones are removed.
+    Never,
+    /// Emit a trailing comma after the last item of a group broken across lines.
+    Multiline,
+}
+
+/// Formatter settings read from `[format]` in `tonic.toml` or `.tonic-formatter.toml`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct FormatOptions {
+    pub(crate) line_length: usize,
+    /// When set, paren-less local calls gain parentheses unless listed here
+    /// (or exported by a dependency in `import_deps`). When unset, calls are
+    /// left as written.
+    pub(crate) locals_without_parens: Option<Vec<String>>,
+    /// Dependencies whose own `locals_without_parens` are added to ours.
+    pub(crate) import_deps: Vec<String>,
+    pub(crate) trailing_comma: TrailingComma,
+    /// Keep `"""` heredocs as written instead of rewriting them as plain strings.
+    pub(crate) preserve_heredocs: bool,
+    /// Keep `~s`/`~w` sigils as written instead of rewriting them as literals.
+    pub(crate) preserve_sigils: bool,
+}
+
+impl Default for FormatOptions {
+    fn default() -> Self {
+        Self {
+            line_length: DEFAULT_LINE_LENGTH,
+            locals_without_parens: None,
+            import_deps: Vec::new(),
+            trailing_comma: TrailingComma::Never,
+            preserve_heredocs: true,
+            preserve_sigils: true,
+        }
+    }
+}
+
+impl FormatOptions {
+    /// Whether the formatter should rewrite `name arg` as `name(arg)`.
+    pub(super) fn adds_parens_to_call(&self, name: &str) -> bool {
+        match &self.locals_without_parens {
+            None => false,
+            Some(locals) => {
+                !DIRECTIVES_WITHOUT_PARENS.contains(&name)
+                    && !locals.iter().any(|local| local == name)
+            }
+        }
+    }
+}
+
+/// Locate the formatter config that applies to `path`.
+///
+/// Walks up from `path` and stops at the first directory holding either a
+/// `.tonic-formatter.toml` (preferred) or a `tonic.toml`. Missing config
+/// yields the defaults; a `tonic.toml` without a `[format]` section does too.
+/// Names in `import_deps` are resolved against the locked dependencies of the
+/// directory holding the config.
+pub(crate) fn load_format_options(path: &Path) -> Result<FormatOptions, String> {
+    let found = manifest::find_nearest_config(path, |directory| {
+        Ok(load_config_in(directory)?.map(|options| (options, directory.to_path_buf())))
+    })?;
+    match found {
+        Some((options, directory)) => import_dependency_locals(options, &directory),
+        None => Ok(FormatOptions::default()),
+    }
+}
+
+/// The config held directly in `directory`, if any.
+fn load_config_in(directory: &Path) -> Result<Option<FormatOptions>, String> {
+    let formatter_config = directory.join(FORMATTER_CONFIG_FILE);
+    if formatter_config.is_file() {
+        let source = read_config(&formatter_config)?;
+        return parse_formatter_config(&source).map(Some);
+    }
+
+    let manifest = directory.join(PROJECT_MANIFEST_FILE);
+    if manifest.is_file() {
+        let source = read_config(&manifest)?;
+        return parse_manifest_format_section(&source).map(Some);
+    }
+
+    Ok(None)
+}
+
+/// Add the `locals_without_parens` of every dependency in `import_deps`.
+/// A dependency without formatter config contributes nothing.
+fn import_dependency_locals(
+    mut options: FormatOptions,
+    config_dir: &Path,
+) -> Result<FormatOptions, String> {
+    if options.import_deps.is_empty() {
+        return Ok(options);
+    }
+
+    let dependency_roots = manifest::dependency_roots(config_dir)?;
+    let mut locals = options.locals_without_parens.take().unwrap_or_default();
+    for name in &options.import_deps {
+        let Some((_, root)) = dependency_roots.iter().find(|(dep, _)| dep == name) else {
+            return Err(format!(
+                "import_deps: dependency '{name}' is not locked or not fetched; run `tonic deps sync`"
+            ));
+        };
+        let imported = load_config_in(root)?
+            .and_then(|dependency| dependency.locals_without_parens)
+            .unwrap_or_default();
+        for local in imported {
+            if !locals.contains(&local) {
+                locals.push(local);
+            }
+        }
+    }
+    options.locals_without_parens = Some(locals);
+    Ok(options)
+}
+
+fn read_config(path: &Path) -> Result<String, String> {
+    std::fs::read_to_string(path)
+        .map_err(|error| format!("failed to read {}: {error}", path.display()))
+}
+
+/// Parse a `.tonic-formatter.toml`, whose keys live at the top level.
+pub(super) fn parse_formatter_config(source: &str) -> Result<FormatOptions, String> {
+    let value: toml::Value = toml::from_str(source)
+        .map_err(|error| format!("invalid {FORMATTER_CONFIG_FILE}: {error}"))?;
+    let table = match value {
+        toml::Value::Table(table) => table,
+        _ => return Err(format!("invalid {FORMATTER_CONFIG_FILE}: expected a table")),
+    };
+    parse_format_table(&table, FORMATTER_CONFIG_FILE, "")
+}
+
+/// Parse the `[format]` section of a `tonic.toml`.
+pub(super) fn parse_manifest_format_section(source: &str) -> Result<FormatOptions, String> {
+    let value: toml::Value = toml::from_str(source)
+        .map_err(|error| format!("invalid {PROJECT_MANIFEST_FILE}: {error}"))?;
+    match value.get("format") {
+        None => Ok(FormatOptions::default()),
+        Some(toml::Value::Table(table)) => {
+            parse_format_table(table, PROJECT_MANIFEST_FILE, "format.")
+        }
+        Some(_) => Err(format!(
+            "invalid {PROJECT_MANIFEST_FILE}: [format] must be a table"
+        )),
+    }
+}
+
+fn parse_format_table(
+    table: &toml::value::Table,
+    file: &str,
+    prefix: &str,
+) -> Result<FormatOptions, String> {
+    let mut options = FormatOptions::default();
+
+    for (key, value) in table {
+        let display_path = format!("{prefix}{key}");
+        match key.as_str() {
+            "line_length" => {
+                options.line_length = match value {
+                    toml::Value::Integer(length) if *length > 0 => *length as usize,
+                    _ => {
+                        return Err(format!(
+                            "invalid {file}: {display_path} must be a positive integer"
+                        ))
+                    }
+                }
+            }
+            "locals_without_parens" => {
+                options.locals_without_parens =
+                    Some(parse_string_array(value, file, &display_path)?)
+            }
+            "import_deps" => options.import_deps = parse_string_array(value, file, &display_path)?,
+            "trailing_comma" => {
+                options.trailing_comma = match value.as_str() {
+                    Some("never") => TrailingComma::Never,
+                    Some("multiline") => TrailingComma::Multiline,
+                    _ => {
+                        return Err(format!(
+                            "invalid {file}: {display_path} must be \"never\" or \"multiline\""
+                        ))
+                    }
+                }
+            }
+            "heredocs" => {
+                options.preserve_heredocs = parse_preservation(value, file, &display_path)?
+            }
+            "sigils" => options.preserve_sigils = parse_preservation(value, file, &display_path)?,
+            other => {
+                return Err(format!(
+                    "invalid {file}: unknown formatter option '{prefix}{other}'"
+                ))
+            }
+        }
+    }
+
+    Ok(options)
+}
+
+fn parse_string_array(
+    value: &toml::Value,
+    file: &str,
+    display_path: &str,
+) -> Result<Vec<String>, String> {
+    let toml::Value::Array(items) = value else {
+        return Err(format!("invalid {file}: {display_path} must be an array"));
+    };
+    items
+        .iter()
+        .enumerate()
+        .map(|(index, item)| match item {
+            toml::Value::String(name) => Ok(name.clone()),
+            _ => Err(format!(
+                "invalid {file}: {display_path}[{index}] must be a string"
+            )),
+        })
+        .collect()
+}
+
+fn parse_preservation(value: &toml::Value, file: &str, display_path: &str) -> Result<bool, String> {
+    match value.as_str() {
+        Some("preserve") => Ok(true),
+        Some("normalize") => Ok(false),
+        _ => Err(format!(
+            "invalid {file}: {display_path} must be \"preserve\" or \"normalize\""
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn manifest_without_format_section_uses_defaults() {
+        let options = parse_manifest_format_section("[project]\nentry = \"src/main.tn\"\n")
+            .expect("manifest should parse");
+        assert_eq!(options, FormatOptions::default());
+    }
+
+    #[test]
+    fn manifest_format_section_overrides_defaults() {
+        let options = parse_manifest_format_section(
+            "[project]\nentry = \"src/main.tn\"\n\n[format]\nline_length = 60\nlocals_without_parens = [\"assert\"]\ntrailing_comma = \"multiline\"\nheredocs = \"normalize\"\nsigils = \"preserve\"\n",
+        )
+        .expect("manifest should parse");
+
+        assert_eq!(
+            options,
+            FormatOptions {
+                line_length: 60,
+                locals_without_parens: Some(vec!["assert".to_string()]),
+                import_deps: Vec::new(),
+                trailing_comma: TrailingComma::Multiline,
+                preserve_heredocs: false,
+                preserve_sigils: true,
+            }
+        );
+        assert!(!options.adds_parens_to_call("assert"));
+        assert!(!options.adds_parens_to_call("alias"));
+        assert!(options.adds_parens_to_call("helper"));
+        assert!(!FormatOptions::default().adds_parens_to_call("helper"));
+    }
+
+    #[test]
+    fn formatter_config_rejects_unknown_and_invalid_options() {
+        assert_eq!(
+            parse_formatter_config("line_length = 0\n").unwrap_err(),
+            "invalid .tonic-formatter.toml: line_length must be a positive integer"
+        );
+        assert_eq!(
+            parse_formatter_config("indent = 4\n").unwrap_err(),
+            "invalid .tonic-formatter.toml: unknown formatter option 'indent'"
+        );
+        assert_eq!(
+            parse_manifest_format_section("[format]\ntrailing_comma = \"always\"\n").unwrap_err(),
+            "invalid tonic.toml: format.trailing_comma must be \"never\" or \"multiline\""
+        );
+    }
+}
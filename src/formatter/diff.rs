@@ -0,0 +1,233 @@
+//! Unified line diffs between original and formatted source for `tonic fmt --diff`.
+
+const CONTEXT_LINES: usize = 3;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Edit {
+    Equal,
+    Delete,
+    Insert,
+}
+
+/// A run of changed lines: `old_len` lines at `old_start` in the original are
+/// replaced by `new_len` lines at `new_start` in the formatted text (0-based).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Hunk {
+    old_start: usize,
+    old_len: usize,
+    new_start: usize,
+    new_len: usize,
+}
+
+/// Render a unified diff from `original` to `formatted`, labelled with `path`.
+/// Returns an empty string when the texts are identical.
+pub(crate) fn unified_diff(path: &str, original: &str, formatted: &str) -> String {
+    let old = split_lines(original);
+    let new = split_lines(formatted);
+    let edits = diff_lines(&old, &new);
+    let changes = hunks(&edits);
+    if changes.is_empty() {
+        return String::new();
+    }
+
+    let mut output = format!("--- {path}\n+++ {path}\n");
+
+    let mut index = 0;
+    while index < changes.len() {
+        let mut last = index;
+        while last + 1 < changes.len()
+            && changes[last + 1].old_start
+                <= changes[last].old_start + changes[last].old_len + 2 * CONTEXT_LINES
+        {
+            last += 1;
+        }
+
+        let first_hunk = changes[index];
+        let last_hunk = changes[last];
+        let old_start = first_hunk.old_start.saturating_sub(CONTEXT_LINES);
+        let leading = first_hunk.old_start - old_start;
+        let new_start = first_hunk.new_start - leading;
+        let old_end = (last_hunk.old_start + last_hunk.old_len + CONTEXT_LINES).min(old.len());
+        let trailing = old_end - (last_hunk.old_start + last_hunk.old_len);
+        let new_end = last_hunk.new_start + last_hunk.new_len + trailing;
+
+        output.push_str(&format!(
+            "@@ -{} +{} @@\n",
+            hunk_header_range(old_start, old_end - old_start),
+            hunk_header_range(new_start, new_end - new_start)
+        ));
+
+        let mut old_line = old_start;
+        let mut new_line = new_start;
+        for (edit, old_index, new_index) in &edits {
+            let in_window = match edit {
+                Edit::Insert => (new_start..new_end).contains(new_index),
+                _ => (old_start..old_end).contains(old_index),
+            };
+            if !in_window {
+                continue;
+            }
+            match edit {
+                Edit::Equal => {
+                    push_diff_line(&mut output, ' ', old[*old_index]);
+                    old_line += 1;
+                    new_line += 1;
+                }
+                Edit::Delete => {
+                    push_diff_line(&mut output, '-', old[*old_index]);
+                    old_line += 1;
+                }
+                Edit::Insert => {
+                    push_diff_line(&mut output, '+', new[*new_index]);
+                    new_line += 1;
+                }
+            }
+        }
+        debug_assert_eq!((old_line, new_line), (old_end, new_end));
+
+        index = last + 1;
+    }
+
+    output
+}
+
+fn hunk_header_range(start: usize, len: usize) -> String {
+    match len {
+        0 => format!("{start},0"),
+        1 => format!("{}", start + 1),
+        _ => format!("{},{len}", start + 1),
+    }
+}
+
+fn push_diff_line(output: &mut String, marker: char, line: &str) {
+    output.push(marker);
+    output.push_str(line);
+    if !line.ends_with('\n') {
+        output.push_str("\n\\ No newline at end of file\n");
+    }
+}
+
+fn split_lines(text: &str) -> Vec<&str> {
+    text.split_inclusive('\n').collect()
+}
+
+fn hunks(edits: &[(Edit, usize, usize)]) -> Vec<Hunk> {
+    let mut hunks: Vec<Hunk> = Vec::new();
+    let mut old_index = 0;
+    let mut new_index = 0;
+    let mut open = false;
+
+    for (edit, _, _) in edits {
+        if *edit == Edit::Equal {
+            open = false;
+            old_index += 1;
+            new_index += 1;
+            continue;
+        }
+
+        if !open {
+            hunks.push(Hunk {
+                old_start: old_index,
+                old_len: 0,
+                new_start: new_index,
+                new_len: 0,
+            });
+            open = true;
+        }
+
+        let hunk = hunks.last_mut().expect("open hunk");
+        if *edit == Edit::Delete {
+            hunk.old_len += 1;
+            old_index += 1;
+        } else {
+            hunk.new_len += 1;
+            new_index += 1;
+        }
+    }
+
+    hunks
+}
+
+/// Myers' O(ND) diff over lines. Returns edits in order as
+/// `(edit, old_index, new_index)`; the index not consumed by an edit is the
+/// position it would be applied at.
+fn diff_lines(old: &[&str], new: &[&str]) -> Vec<(Edit, usize, usize)> {
+    let n = old.len() as isize;
+    let m = new.len() as isize;
+    let max = n + m;
+    let offset = max + 1;
+    let mut frontier = vec![0isize; 2 * max as usize + 3];
+    let mut trace = Vec::new();
+
+    'search: for depth in 0..=max {
+        trace.push(frontier.clone());
+        for k in (-depth..=depth).step_by(2) {
+            let index = (k + offset) as usize;
+            let mut x = if k == -depth || (k != depth && frontier[index - 1] < frontier[index + 1])
+            {
+                frontier[index + 1]
+            } else {
+                frontier[index - 1] + 1
+            };
+            let mut y = x - k;
+            while x < n && y < m && old[x as usize] == new[y as usize] {
+                x += 1;
+                y += 1;
+            }
+            frontier[index] = x;
+            if x >= n && y >= m {
+                break 'search;
+            }
+        }
+    }
+
+    let mut edits = Vec::new();
+    let (mut x, mut y) = (n, m);
+    for (depth, frontier) in trace.iter().enumerate().rev() {
+        let depth = depth as isize;
+        let k = x - y;
+        let previous_k = if k == -depth
+            || (k != depth
+                && frontier[(k - 1 + offset) as usize] < frontier[(k + 1 + offset) as usize])
+        {
+            k + 1
+        } else {
+            k - 1
+        };
+        let previous_x = frontier[(previous_k + offset) as usize];
+        let previous_y = previous_x - previous_k;
+
+        while x > previous_x && y > previous_y {
+            x -= 1;
+            y -= 1;
+            edits.push((Edit::Equal, x as usize, y as usize));
+        }
+        if depth > 0 {
+            if x == previous_x {
+                y -= 1;
+                edits.push((Edit::Insert, x as usize, y as usize));
+            } else {
+                x -= 1;
+                edits.push((Edit::Delete, x as usize, y as usize));
+            }
+        }
+    }
+
+    edits.reverse();
+    edits
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unified_diff_renders_changed_lines_with_context() {
+        let diff = unified_diff("demo.tn", "a\nb\nc\n", "a\nB\nc\n");
+        assert_eq!(
+            diff,
+            "--- demo.tn\n+++ demo.tn\n@@ -1,3 +1,3 @@\n a\n-b\n+B\n c\n"
+        );
+        assert_eq!(unified_diff("demo.tn", "same\n", "same\n"), "");
+    }
+}
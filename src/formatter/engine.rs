@@ -1,4 +1,7 @@
+use super::config::{FormatOptions, TrailingComma};
+use super::LineRange;
 use crate::lexer::{scan_tokens_with_comments, Comment, Token, TokenKind};
+use crate::parser::token_can_start_no_paren_arg;
 
 /// Format a Tonic source string using a token-driven approach.
 ///
@@ -8,11 +11,15 @@ use crate::lexer::{scan_tokens_with_comments, Comment, Token, TokenKind};
 ///
 /// If lexing fails (malformed source), the original normalized source is
 /// returned unchanged to avoid corrupting code with syntax errors.
-pub(super) fn format_source_inner(source: &str) -> String {
+pub(super) fn format_source_inner(
+    source: &str,
+    options: &FormatOptions,
+    range: Option<LineRange>,
+) -> String {
     let normalized = normalize_newlines(source);
 
     match scan_tokens_with_comments(&normalized) {
-        Ok((tokens, comments)) => format_tokens(&normalized, &tokens, &comments),
+        Ok((tokens, comments)) => format_tokens(&normalized, &tokens, &comments, options, range),
         Err(_) => normalized,
     }
 }
@@ -33,6 +40,10 @@ pub(super) fn format_source_inner(source: &str) -> String {
 pub(super) struct LogicalLine {
     /// Token kinds for classification
     kinds: Vec<TokenKind>,
+    /// Byte range of each token within `text`
+    ranges: Vec<(usize, usize)>,
+    /// Byte range of each token within the source, used for adjacency checks
+    source_spans: Vec<(usize, usize)>,
     /// The rendered text of the line (without indentation)
     pub(super) text: String,
     /// Whether this line has a blank line gap before it (from source)
@@ -45,6 +56,8 @@ impl LogicalLine {
     fn new() -> Self {
         Self {
             kinds: Vec::new(),
+            ranges: Vec::new(),
+            source_spans: Vec::new(),
             text: String::new(),
             blank_before: false,
             source_line_start: None,
@@ -55,6 +68,8 @@ impl LogicalLine {
     fn comment(comment: &Comment) -> Self {
         Self {
             kinds: Vec::new(),
+            ranges: Vec::new(),
+            source_spans: Vec::new(),
             text: comment.text().to_string(),
             blank_before: comment.blank_lines_before() > 0,
             source_line_start: Some(comment.line()),
@@ -88,7 +103,20 @@ impl LogicalLine {
         )
     }
 
-    fn push(&mut self, kind: TokenKind, text: &str) {
+    fn push(&mut self, kind: TokenKind, text: &str, source_span: (usize, usize)) {
+        // Trailing commas are dropped here and re-added by `wrap_line` when
+        // the group is broken across lines and the policy asks for them.
+        if matches!(
+            kind,
+            TokenKind::RParen | TokenKind::RBracket | TokenKind::RBrace
+        ) && self.last_kind() == Some(TokenKind::Comma)
+        {
+            let (start, _) = self.ranges.pop().unwrap();
+            self.kinds.pop();
+            self.source_spans.pop();
+            self.text.truncate(start);
+        }
+
         let space = if self.text.is_empty() {
             false
         } else {
@@ -98,8 +126,20 @@ impl LogicalLine {
         if space {
             self.text.push(' ');
         }
+        let start = self.text.len();
         self.text.push_str(text);
         self.kinds.push(kind);
+        self.ranges.push((start, self.text.len()));
+        self.source_spans.push(source_span);
+    }
+
+    /// Text of the line up to the end of its last token, excluding any
+    /// trailing comment merged onto it.
+    fn code_text(&self) -> &str {
+        match self.ranges.last() {
+            Some(&(_, end)) => &self.text[..end],
+            None => &self.text,
+        }
     }
 
     fn append_trailing_comment(&mut self, comment: &Comment) {
@@ -158,7 +198,11 @@ fn space_between(prev: TokenKind, curr: TokenKind, prev_text: &str, curr_text: &
 }
 
 #[allow(unused_assignments)]
-pub(super) fn build_logical_lines(source: &str, tokens: &[Token]) -> Vec<LogicalLine> {
+pub(super) fn build_logical_lines(
+    source: &str,
+    tokens: &[Token],
+    options: &FormatOptions,
+) -> Vec<LogicalLine> {
     let line_for_offset = compute_line_map(source);
 
     let mut lines: Vec<LogicalLine> = Vec::new();
@@ -193,23 +237,48 @@ pub(super) fn build_logical_lines(source: &str, tokens: &[Token]) -> Vec<Logical
         }
 
         let text = token_text(token, source);
+        let span = (token.span().start(), token.span().end());
         let tok_line = line_for_offset
             .get(token.span().start())
             .copied()
             .unwrap_or(0);
 
+        if let Some(run) = verbatim_run(tokens, i, source, options) {
+            let inline = paren_depth > 0 || bracket_depth > 0 || brace_depth > 0;
+            if !inline && interp_depth == 0 {
+                if let Some(prev_ln) = prev_line_num {
+                    if tok_line > prev_ln {
+                        flush!();
+                        if blank_before_line.get(tok_line).copied().unwrap_or(false) {
+                            current.blank_before = true;
+                        }
+                    }
+                }
+            }
+            let end_line = line_for_offset
+                .get(run.source_end.saturating_sub(1))
+                .copied()
+                .unwrap_or(tok_line);
+            current.note_source_line(tok_line);
+            current.note_source_line(end_line);
+            current.push(TokenKind::String, &run.text, (span.0, run.source_end));
+            prev_line_num = Some(end_line);
+            i = run.next_index;
+            continue;
+        }
+
         if interp_depth > 0 {
             current.note_source_line(tok_line);
             match kind {
                 TokenKind::InterpolationEnd => {
                     interp_depth -= 1;
-                    current.push(kind, "}");
+                    current.push(kind, "}", span);
                 }
                 TokenKind::StringEnd => {
-                    current.push(kind, "\"");
+                    current.push(kind, "\"", span);
                 }
                 _ => {
-                    current.push(kind, text);
+                    current.push(kind, text, span);
                 }
             }
             i += 1;
@@ -221,74 +290,74 @@ pub(super) fn build_logical_lines(source: &str, tokens: &[Token]) -> Vec<Logical
         match kind {
             TokenKind::StringStart => {
                 current.note_source_line(tok_line);
-                current.push(kind, "\"");
+                current.push(kind, "\"", span);
             }
             TokenKind::StringPart => {
                 current.note_source_line(tok_line);
-                current.push(kind, text);
+                current.push(kind, text, span);
             }
             TokenKind::InterpolationStart => {
                 interp_depth += 1;
                 current.note_source_line(tok_line);
-                current.push(kind, "#{");
+                current.push(kind, "#{", span);
             }
             TokenKind::StringEnd => {
                 current.note_source_line(tok_line);
-                current.push(kind, "\"");
+                current.push(kind, "\"", span);
             }
             TokenKind::String => {
                 current.note_source_line(tok_line);
-                current.push(kind, text);
+                current.push(kind, text, span);
             }
             TokenKind::LParen => {
                 paren_depth += 1;
                 current.note_source_line(tok_line);
-                current.push(kind, "(");
+                current.push(kind, "(", span);
             }
             TokenKind::RParen => {
                 paren_depth = paren_depth.saturating_sub(1);
                 current.note_source_line(tok_line);
-                current.push(kind, ")");
+                current.push(kind, ")", span);
             }
             TokenKind::LBracket => {
                 bracket_depth += 1;
                 current.note_source_line(tok_line);
-                current.push(kind, "[");
+                current.push(kind, "[", span);
             }
             TokenKind::RBracket => {
                 bracket_depth = bracket_depth.saturating_sub(1);
                 current.note_source_line(tok_line);
-                current.push(kind, "]");
+                current.push(kind, "]", span);
             }
             TokenKind::LBrace => {
                 brace_depth += 1;
                 current.note_source_line(tok_line);
-                current.push(kind, "{");
+                current.push(kind, "{", span);
             }
             TokenKind::RBrace => {
                 brace_depth = brace_depth.saturating_sub(1);
                 current.note_source_line(tok_line);
-                current.push(kind, "}");
+                current.push(kind, "}", span);
             }
             TokenKind::Do => {
                 current.note_source_line(tok_line);
-                current.push(kind, "do");
+                current.push(kind, "do", span);
                 flush!();
             }
             TokenKind::End => {
                 flush!();
                 current.note_source_line(tok_line);
-                current.push(kind, "end");
+                current.push(kind, "end", span);
                 flush!();
             }
             TokenKind::Else | TokenKind::Rescue | TokenKind::Catch | TokenKind::After => {
                 flush!();
                 current.note_source_line(tok_line);
-                current.push(kind, text);
+                current.push(kind, text, span);
             }
             TokenKind::Arrow => {
                 current.note_source_line(tok_line);
-                current.push(kind, "->");
+                current.push(kind, "->", span);
                 let next_tok = tokens.get(i + 1);
                 let break_after = match next_tok {
                     None => true,
@@ -321,7 +390,7 @@ pub(super) fn build_logical_lines(source: &str, tokens: &[Token]) -> Vec<Logical
             TokenKind::PipeGt if !inline => {
                 flush!();
                 current.note_source_line(tok_line);
-                current.push(kind, "|>");
+                current.push(kind, "|>", span);
             }
             TokenKind::Semicolon => {
                 flush!();
@@ -338,7 +407,7 @@ pub(super) fn build_logical_lines(source: &str, tokens: &[Token]) -> Vec<Logical
                     }
                 }
                 current.note_source_line(tok_line);
-                current.push(kind, text);
+                current.push(kind, text, span);
             }
         }
 
@@ -350,6 +419,171 @@ pub(super) fn build_logical_lines(source: &str, tokens: &[Token]) -> Vec<Logical
     lines
 }
 
+/// A heredoc or sigil collapsed into a single piece of output text.
+struct VerbatimRun {
+    text: String,
+    source_end: usize,
+    next_index: usize,
+}
+
+/// Heredocs and sigils are rendered from their source text rather than token
+/// by token: `~w(...)` expands to several tokens sharing one span, and heredoc
+/// delimiters would otherwise collapse to plain quotes. With preservation
+/// turned off, `"""` heredocs and `~s`/`~w` sigils are normalized to their
+/// plain literal forms where one exists.
+fn verbatim_run(
+    tokens: &[Token],
+    index: usize,
+    source: &str,
+    options: &FormatOptions,
+) -> Option<VerbatimRun> {
+    let token = &tokens[index];
+    let text = token_text(token, source);
+    let start = token.span().start();
+
+    match token.kind() {
+        TokenKind::StringStart if text != "\"" => {
+            let mut depth = 0usize;
+            let mut end_index = index;
+            for (offset, candidate) in tokens[index..].iter().enumerate() {
+                match candidate.kind() {
+                    TokenKind::StringStart => depth += 1,
+                    TokenKind::StringEnd => {
+                        depth -= 1;
+                        if depth == 0 {
+                            end_index = index + offset;
+                            break;
+                        }
+                    }
+                    TokenKind::Eof => return None,
+                    _ => {}
+                }
+            }
+            if end_index == index {
+                return None;
+            }
+            let end = tokens[end_index].span().end();
+            let text = if text.starts_with('~') || options.preserve_heredocs {
+                source[start..end].to_string()
+            } else {
+                normalize_interpolated_heredoc(&tokens[index..=end_index], source)
+            };
+            Some(VerbatimRun {
+                text,
+                source_end: end,
+                next_index: end_index + 1,
+            })
+        }
+        TokenKind::String if text.starts_with("\"\"\"") => {
+            let end = token.span().end();
+            let text = if options.preserve_heredocs {
+                text.to_string()
+            } else {
+                format!("\"{}\"", escape_quotes(&text[3..text.len() - 3]))
+            };
+            Some(VerbatimRun {
+                text,
+                source_end: end,
+                next_index: index + 1,
+            })
+        }
+        _ if text.starts_with('~') && token.kind() != TokenKind::TildeTildeTilde => {
+            let span = token.span();
+            let run_len = tokens[index..]
+                .iter()
+                .take_while(|candidate| candidate.span() == span)
+                .count();
+            let run = &tokens[index..index + run_len];
+            let text = if options.preserve_sigils {
+                text.to_string()
+            } else {
+                normalize_sigil(text, run).unwrap_or_else(|| text.to_string())
+            };
+            Some(VerbatimRun {
+                text,
+                source_end: span.end(),
+                next_index: index + run_len,
+            })
+        }
+        _ => None,
+    }
+}
+
+fn normalize_interpolated_heredoc(run: &[Token], source: &str) -> String {
+    let open = run[0].span();
+    let close = run[run.len() - 1].span();
+    let mut output = String::from("\"");
+    let mut cursor = open.end();
+    let mut depth = 0usize;
+
+    for token in run {
+        match token.kind() {
+            TokenKind::StringStart => depth += 1,
+            TokenKind::StringEnd => depth -= 1,
+            TokenKind::StringPart if depth == 1 => {
+                let span = token.span();
+                output.push_str(&source[cursor..span.start()]);
+                output.push_str(&escape_quotes(&source[span.start()..span.end()]));
+                cursor = span.end();
+            }
+            _ => {}
+        }
+    }
+
+    output.push_str(&source[cursor..close.start()]);
+    output.push('"');
+    output
+}
+
+fn normalize_sigil(text: &str, run: &[Token]) -> Option<String> {
+    match text.as_bytes().get(1) {
+        Some(b's') if !run[0].lexeme().contains("#{") => Some(format!(
+            "\"{}\"",
+            run[0].lexeme().replace('\\', "\\\\").replace('"', "\\\"")
+        )),
+        Some(b'w') => {
+            let items = run
+                .iter()
+                .filter(|token| matches!(token.kind(), TokenKind::String | TokenKind::Atom))
+                .map(|token| match token.kind() {
+                    TokenKind::Atom => {
+                        let word = token.lexeme();
+                        word.chars()
+                            .all(|ch| ch.is_ascii_alphanumeric() || ch == '_')
+                            .then(|| format!(":{word}"))
+                    }
+                    _ => Some(format!(
+                        "\"{}\"",
+                        token.lexeme().replace('\\', "\\\\").replace('"', "\\\"")
+                    )),
+                })
+                .collect::<Option<Vec<_>>>()?;
+            Some(format!("[{}]", items.join(", ")))
+        }
+        _ => None,
+    }
+}
+
+/// Escape bare double quotes in raw string source, leaving existing escape
+/// sequences and interpolations untouched.
+fn escape_quotes(raw: &str) -> String {
+    let mut output = String::with_capacity(raw.len());
+    let mut chars = raw.chars();
+    while let Some(ch) = chars.next() {
+        match ch {
+            '\\' => {
+                output.push(ch);
+                if let Some(next) = chars.next() {
+                    output.push(next);
+                }
+            }
+            '"' => output.push_str("\\\""),
+            _ => output.push(ch),
+        }
+    }
+    output
+}
+
 fn merge_comments(lines: Vec<LogicalLine>, comments: &[Comment]) -> Vec<LogicalLine> {
     if comments.is_empty() {
         return lines;
@@ -429,12 +663,15 @@ fn compute_blank_before_map(source: &str) -> Vec<bool> {
     result
 }
 
-pub(super) fn apply_indentation(lines: &[LogicalLine]) -> String {
-    let mut output = String::new();
+/// Render each logical line with its indentation. Entries line up with
+/// `lines` and include a leading blank line where one is kept.
+pub(super) fn apply_indentation(lines: &[LogicalLine], options: &FormatOptions) -> Vec<String> {
+    let mut rendered = Vec::with_capacity(lines.len());
     let mut indent: i32 = 0;
     let mut in_branch_body = false;
 
     for line in lines {
+        let mut output = String::new();
         if line.blank_before {
             output.push('\n');
         }
@@ -465,10 +702,12 @@ pub(super) fn apply_indentation(lines: &[LogicalLine]) -> String {
             indent = 0;
         }
 
-        let indent_str = "  ".repeat(indent as usize);
-        output.push_str(&indent_str);
-        output.push_str(&line.text);
-        output.push('\n');
+        for (depth, text) in wrap_line(line, indent as usize, options) {
+            output.push_str(&"  ".repeat(depth));
+            output.push_str(&text);
+            output.push('\n');
+        }
+        rendered.push(output);
 
         if is_block_reopen {
             indent += 1;
@@ -488,14 +727,332 @@ pub(super) fn apply_indentation(lines: &[LogicalLine]) -> String {
         }
     }
 
+    rendered
+}
+
+/// Splice rendered lines into the original source for range formatting.
+///
+/// Logical lines whose source lines overlap are grouped into chunks; chunks
+/// touching `range` are replaced by their formatted rendering and all other
+/// chunks, along with the blank lines before them, are copied verbatim.
+fn splice_range(
+    source: &str,
+    lines: &[LogicalLine],
+    rendered: &[String],
+    range: LineRange,
+) -> String {
+    let source_lines = source.split_inclusive('\n').collect::<Vec<_>>();
+    let first = range.start - 1;
+    let last = range.end - 1;
+    let mut output = String::with_capacity(source.len());
+    let mut next_source_line = 0;
+    let mut last_chunk_in_range = false;
+    let mut index = 0;
+
+    while index < lines.len() {
+        let start = lines[index].source_line_start.unwrap_or(next_source_line);
+        let mut end = lines[index].source_line_end.unwrap_or(start);
+        let mut chunk_end = index + 1;
+        while let Some(next_start) = lines.get(chunk_end).and_then(|line| line.source_line_start) {
+            if next_start > end {
+                break;
+            }
+            end = end.max(lines[chunk_end].source_line_end.unwrap_or(next_start));
+            chunk_end += 1;
+        }
+
+        last_chunk_in_range = start <= last && first <= end;
+        if last_chunk_in_range {
+            rendered[index..chunk_end]
+                .iter()
+                .for_each(|text| output.push_str(text));
+        } else {
+            let from = next_source_line.min(source_lines.len());
+            let to = (end + 1).min(source_lines.len());
+            source_lines[from..to]
+                .iter()
+                .for_each(|text| output.push_str(text));
+        }
+
+        next_source_line = end + 1;
+        index = chunk_end;
+    }
+
+    if !last_chunk_in_range {
+        source_lines[next_source_line.min(source_lines.len())..]
+            .iter()
+            .for_each(|text| output.push_str(text));
+    }
+
+    output
+}
+
+/// Rewrite paren-less local calls at the start of a line (`helper arg`) as
+/// `helper(arg)` when the config sets `locals_without_parens` and the name is
+/// not listed there.
+///
+/// Lines whose call may continue past the logical line (a trailing operator
+/// or comma, a `do`/`->` block, or a following pipe) are left alone.
+fn add_local_call_parens(lines: &mut [LogicalLine], options: &FormatOptions) {
+    use TokenKind::*;
+
+    if options.locals_without_parens.is_none() {
+        return;
+    }
+
+    for index in 0..lines.len() {
+        let followed_by_pipe = lines
+            .get(index + 1)
+            .is_some_and(|next| next.first_kind() == Some(PipeGt));
+        let line = &lines[index];
+
+        if followed_by_pipe
+            || line.kinds.len() < 2
+            || line.kinds[0] != Ident
+            || line.kinds[1] == LParen
+            || !token_can_start_no_paren_arg(line.kinds[1])
+            || line.source_spans[1].0 != line.source_spans[0].1 + 1
+            || line
+                .kinds
+                .iter()
+                .any(|kind| matches!(kind, Do | Arrow | End))
+            || !matches!(
+                line.last_kind(),
+                Some(
+                    Ident
+                        | Atom
+                        | Integer
+                        | Float
+                        | String
+                        | StringEnd
+                        | RParen
+                        | RBracket
+                        | RBrace
+                        | True
+                        | False
+                        | Nil
+                )
+            )
+        {
+            continue;
+        }
+
+        let name = &line.text[line.ranges[0].0..line.ranges[0].1];
+        if name == "_" || !options.adds_parens_to_call(name) {
+            continue;
+        }
+
+        let line = &mut lines[index];
+        let name_end = line.ranges[0].1;
+        let args_start = line.ranges[1].0;
+        let code_end = line.ranges.last().unwrap().1;
+        let shift = name_end + 1 - args_start;
+
+        let mut text = line.text[..name_end].to_string();
+        text.push('(');
+        text.push_str(&line.text[args_start..code_end]);
+        text.push(')');
+        text.push_str(&line.text[code_end..]);
+        line.text = text;
+
+        for range in &mut line.ranges[1..] {
+            *range = (range.0 + shift, range.1 + shift);
+        }
+        line.ranges.insert(1, (name_end, name_end + 1));
+        line.kinds.insert(1, LParen);
+        line.source_spans.insert(1, (0, 0));
+
+        let close = code_end + shift;
+        line.ranges.push((close, close + 1));
+        line.kinds.push(RParen);
+        line.source_spans.push((0, 0));
+    }
+}
+
+/// Break a logical line that exceeds `line_length` at a bracketed group,
+/// putting one item per line and recursing into items that are still too
+/// long. Lines spanning source newlines (heredocs) are emitted as-is.
+fn wrap_line(line: &LogicalLine, indent: usize, options: &FormatOptions) -> Vec<(usize, String)> {
+    let code = line.code_text();
+    if line.kinds.is_empty() || code.contains('\n') {
+        return vec![(indent, line.text.clone())];
+    }
+
+    let mut output = Vec::new();
+    wrap_tokens(
+        line,
+        0,
+        line.kinds.len(),
+        indent,
+        false,
+        options,
+        &mut output,
+    );
+    if let Some((_, last)) = output.last_mut() {
+        last.push_str(&line.text[code.len()..]);
+    }
     output
 }
 
-pub(super) fn format_tokens(source: &str, tokens: &[Token], comments: &[Comment]) -> String {
-    let lines = build_logical_lines(source, tokens);
+/// `in_pattern` is set inside a `def` head or pattern, where the parser
+/// rejects trailing commas.
+fn wrap_tokens(
+    line: &LogicalLine,
+    lo: usize,
+    hi: usize,
+    indent: usize,
+    in_pattern: bool,
+    options: &FormatOptions,
+    output: &mut Vec<(usize, String)>,
+) {
+    let text = &line.text[line.ranges[lo].0..line.ranges[hi - 1].1];
+    let fits = indent * 2 + text.chars().count() <= options.line_length;
+    let Some((open, close)) = (!fits).then(|| find_break_group(line, lo, hi)).flatten() else {
+        output.push((indent, text.to_string()));
+        return;
+    };
+
+    output.push((
+        indent,
+        line.text[line.ranges[lo].0..line.ranges[open].1].to_string(),
+    ));
+
+    let in_pattern = in_pattern || is_pattern_group(line, lo, open, close, hi);
+    let trailing_comma = options.trailing_comma == TrailingComma::Multiline && !in_pattern;
+    let items = split_group_items(line, open + 1, close);
+    let count = items.len();
+    for (position, (item_lo, item_hi)) in items.into_iter().enumerate() {
+        wrap_tokens(
+            line,
+            item_lo,
+            item_hi,
+            indent + 1,
+            in_pattern,
+            options,
+            output,
+        );
+        if position + 1 < count || trailing_comma {
+            if let Some((_, last)) = output.last_mut() {
+                last.push(',');
+            }
+        }
+    }
+
+    wrap_tokens(line, close, hi, indent, in_pattern, options, output);
+}
+
+/// Whether the group `open..=close` within `lo..hi` is a `def` parameter
+/// list or a pattern: followed at the same nesting level by `->`, `<-`, `=`
+/// or `when`.
+fn is_pattern_group(line: &LogicalLine, lo: usize, open: usize, close: usize, hi: usize) -> bool {
+    use TokenKind::*;
+
+    if matches!(line.kinds[lo], Def | Defp) && open == lo + 2 && line.kinds[open] == LParen {
+        return true;
+    }
+
+    let mut depth = 0usize;
+    let mut string_depth = 0usize;
+    for &kind in &line.kinds[close + 1..hi] {
+        match kind {
+            StringStart => string_depth += 1,
+            StringEnd => string_depth = string_depth.saturating_sub(1),
+            _ if string_depth > 0 => {}
+            LParen | LBracket | LBrace => depth += 1,
+            RParen | RBracket | RBrace => depth = depth.saturating_sub(1),
+            Arrow | LeftArrow | MatchEq | When if depth == 0 => return true,
+            _ => {}
+        }
+    }
+    false
+}
+
+/// Pick the bracketed group to break: the first top-level group holding
+/// several comma-separated items, else the first non-empty top-level group.
+fn find_break_group(line: &LogicalLine, lo: usize, hi: usize) -> Option<(usize, usize)> {
+    let mut groups = Vec::new();
+    let mut open_stack: Vec<(usize, bool)> = Vec::new();
+    let mut string_depth = 0usize;
+
+    for index in lo..hi {
+        match line.kinds[index] {
+            TokenKind::StringStart => string_depth += 1,
+            TokenKind::StringEnd => string_depth = string_depth.saturating_sub(1),
+            _ if string_depth > 0 => {}
+            TokenKind::LParen | TokenKind::LBracket | TokenKind::LBrace => {
+                open_stack.push((index, false));
+            }
+            TokenKind::RParen | TokenKind::RBracket | TokenKind::RBrace => {
+                if let Some((open, has_comma)) = open_stack.pop() {
+                    if open_stack.is_empty() && index > open + 1 {
+                        groups.push((open, index, has_comma));
+                    }
+                }
+            }
+            TokenKind::Comma => {
+                if let Some((_, has_comma)) = open_stack.last_mut() {
+                    *has_comma = true;
+                }
+            }
+            _ => {}
+        }
+    }
+
+    groups
+        .iter()
+        .find(|(_, _, has_comma)| *has_comma)
+        .or_else(|| groups.first())
+        .map(|(open, close, _)| (*open, *close))
+}
+
+fn split_group_items(line: &LogicalLine, lo: usize, hi: usize) -> Vec<(usize, usize)> {
+    let mut items = Vec::new();
+    let mut depth = 0usize;
+    let mut string_depth = 0usize;
+    let mut item_start = lo;
+
+    for index in lo..hi {
+        match line.kinds[index] {
+            TokenKind::StringStart => string_depth += 1,
+            TokenKind::StringEnd => string_depth = string_depth.saturating_sub(1),
+            _ if string_depth > 0 => {}
+            TokenKind::LParen | TokenKind::LBracket | TokenKind::LBrace => depth += 1,
+            TokenKind::RParen | TokenKind::RBracket | TokenKind::RBrace => {
+                depth = depth.saturating_sub(1)
+            }
+            TokenKind::Comma if depth == 0 => {
+                if index > item_start {
+                    items.push((item_start, index));
+                }
+                item_start = index + 1;
+            }
+            _ => {}
+        }
+    }
+
+    if hi > item_start {
+        items.push((item_start, hi));
+    }
+    items
+}
+
+pub(super) fn format_tokens(
+    source: &str,
+    tokens: &[Token],
+    comments: &[Comment],
+    options: &FormatOptions,
+    range: Option<LineRange>,
+) -> String {
+    let mut lines = build_logical_lines(source, tokens, options);
+    add_local_call_parens(&mut lines, options);
     let merged = merge_comments(lines, comments);
-    let mut output = apply_indentation(&merged);
+    let rendered = apply_indentation(&merged, options);
+
+    if let Some(range) = range {
+        return splice_range(source, &merged, &rendered, range);
+    }
 
+    let mut output = rendered.concat();
     while output.ends_with("\n\n") {
         output.pop();
     }
@@ -1,7 +1,12 @@
 mod algebra;
+mod config;
+mod diff;
 mod engine;
 mod to_doc;
 
+pub(crate) use config::{load_format_options, FormatOptions};
+pub(crate) use diff::unified_diff;
+
 use std::fs;
 use std::path::{Path, PathBuf};
 
@@ -9,21 +14,60 @@ use std::path::{Path, PathBuf};
 pub(crate) enum FormatMode {
     Write,
     Check,
+    /// Like `Check`, but also collects a unified diff of each change.
+    Diff,
 }
 
+/// A 1-based, inclusive span of source lines for `tonic fmt --range`.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct LineRange {
+    pub(crate) start: usize,
+    pub(crate) end: usize,
+}
+
+impl LineRange {
+    /// Parse `start:end`, e.g. `10:24`.
+    pub(crate) fn parse(raw: &str) -> Result<Self, String> {
+        let invalid = || format!("invalid --range '{raw}'; expected <start>:<end> line numbers");
+        let (start, end) = raw.split_once(':').ok_or_else(invalid)?;
+        let start = start.trim().parse::<usize>().map_err(|_| invalid())?;
+        let end = end.trim().parse::<usize>().map_err(|_| invalid())?;
+
+        if start == 0 || end < start {
+            return Err(format!(
+                "invalid --range '{raw}'; lines start at 1 and end must not precede start"
+            ));
+        }
+
+        Ok(Self { start, end })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub(crate) struct FormatReport {
     pub(crate) checked_files: usize,
     pub(crate) changed_files: usize,
+    pub(crate) diff: String,
 }
 
-pub(crate) fn format_path(path: &str, mode: FormatMode) -> Result<FormatReport, String> {
+pub(crate) fn format_path(
+    path: &str,
+    mode: FormatMode,
+    range: Option<LineRange>,
+) -> Result<FormatReport, String> {
     let requested_path = Path::new(path);
 
     if !requested_path.exists() {
         return Err(format!("path does not exist: {}", requested_path.display()));
     }
 
+    if range.is_some() && !requested_path.is_file() {
+        return Err(format!(
+            "--range requires a single source file, found directory {}",
+            requested_path.display()
+        ));
+    }
+
     let files = collect_tonic_files(requested_path)?;
     if files.is_empty() {
         return Err(format!(
@@ -35,18 +79,28 @@ pub(crate) fn format_path(path: &str, mode: FormatMode) -> Result<FormatReport,
     let mut report = FormatReport {
         checked_files: 0,
         changed_files: 0,
+        diff: String::new(),
     };
 
     for file in files {
         let source = fs::read_to_string(&file)
             .map_err(|error| format!("failed to read source file {}: {error}", file.display()))?;
-        let formatted = format_source(&source);
+        let options = load_format_options(&file)?;
+        let formatted = format_text(&source, &options, range);
 
         report.checked_files += 1;
 
         if source != formatted {
             report.changed_files += 1;
 
+            if mode == FormatMode::Diff {
+                report.diff.push_str(&unified_diff(
+                    &file.display().to_string(),
+                    &source,
+                    &formatted,
+                ));
+            }
+
             if mode == FormatMode::Write {
                 fs::write(&file, formatted).map_err(|error| {
                     format!("failed to write formatted file {}: {error}", file.display())
@@ -62,19 +116,27 @@ pub(crate) fn format_path(path: &str, mode: FormatMode) -> Result<FormatReport,
 ///
 /// Uses a token-driven two-pass approach: lexer tokens are segmented into
 /// logical lines (Pass 1), then indentation is applied (Pass 2), with lexer
-/// comment sidecars merged back into the formatted output.
+/// comment sidecars merged back into the formatted output. Lines longer than
+/// `options.line_length` are broken at their bracketed groups.
+///
+/// With a `range`, only logical lines that touch those source lines are
+/// rewritten; everything else is returned as written.
 ///
 /// If lexing fails (malformed source), the original normalized source is
 /// returned unchanged to avoid corrupting code with syntax errors.
-pub(crate) fn format_source(source: &str) -> String {
-    engine::format_source_inner(source)
+pub(crate) fn format_text(
+    source: &str,
+    options: &FormatOptions,
+    range: Option<LineRange>,
+) -> String {
+    engine::format_source_inner(source, options, range)
 }
 
 // ---------------------------------------------------------------------------
 // File collection
 // ---------------------------------------------------------------------------
 
-fn collect_tonic_files(path: &Path) -> Result<Vec<PathBuf>, String> {
+pub(crate) fn collect_tonic_files(path: &Path) -> Result<Vec<PathBuf>, String> {
     if path.is_file() {
         return Ok(vec![path.to_path_buf()]);
     }
@@ -123,7 +185,12 @@ fn collect_tonic_files_recursive(directory: &Path, files: &mut Vec<PathBuf>) ->
 
 #[cfg(test)]
 mod tests {
-    use super::format_source;
+    use super::config::TrailingComma;
+    use super::*;
+
+    fn format_source(source: &str) -> String {
+        format_text(source, &FormatOptions::default(), None)
+    }
 
     #[test]
     fn format_source_indents_nested_do_blocks() {
@@ -229,4 +296,115 @@ mod tests {
         let second = format_source(&first);
         assert_eq!(first, second, "comment-bearing format must be idempotent");
     }
+
+    fn options_with(configure: impl FnOnce(&mut FormatOptions)) -> FormatOptions {
+        let mut options = FormatOptions::default();
+        configure(&mut options);
+        options
+    }
+
+    #[test]
+    fn format_text_wraps_long_calls_at_line_length() {
+        let source = "defmodule Demo do\n  def run() do\n    combine(first_value, second_value, third_value)\n  end\nend\n";
+        let options = options_with(|options| options.line_length = 30);
+
+        assert_eq!(
+            format_text(source, &options, None),
+            "defmodule Demo do\n  def run() do\n    combine(\n      first_value,\n      second_value,\n      third_value\n    )\n  end\nend\n"
+        );
+    }
+
+    #[test]
+    fn format_text_applies_trailing_comma_policy_to_wrapped_groups() {
+        let source = "defmodule Demo do\n  def run() do\n    combine(first_value, second_value,)\n  end\nend\n";
+
+        assert_eq!(
+            format_source(source),
+            "defmodule Demo do\n  def run() do\n    combine(first_value, second_value)\n  end\nend\n"
+        );
+
+        let options = options_with(|options| {
+            options.line_length = 30;
+            options.trailing_comma = TrailingComma::Multiline;
+        });
+        let formatted = format_text(source, &options, None);
+        assert_eq!(
+            formatted,
+            "defmodule Demo do\n  def run() do\n    combine(\n      first_value,\n      second_value,\n    )\n  end\nend\n"
+        );
+        assert_eq!(format_text(&formatted, &options, None), formatted);
+    }
+
+    #[test]
+    fn format_text_keeps_trailing_commas_out_of_def_heads_and_patterns() {
+        let source = "defmodule Demo do\n  def combine(first_value, second_value, third_value) do\n    case first_value do\n      {left_value, right_value, other_value} -> left_value\n    end\n  end\nend\n";
+        let options = options_with(|options| {
+            options.line_length = 30;
+            options.trailing_comma = TrailingComma::Multiline;
+        });
+
+        let formatted = format_text(source, &options, None);
+
+        assert!(
+            formatted.contains("    third_value\n  ) do\n"),
+            "{formatted}"
+        );
+        assert!(
+            formatted.contains("        other_value\n      } ->"),
+            "{formatted}"
+        );
+        let tokens = crate::lexer::scan_tokens(&formatted).expect("formatted source should lex");
+        crate::parser::parse_ast(&tokens).expect("formatted source should parse");
+    }
+
+    #[test]
+    fn format_text_adds_parens_only_when_locals_without_parens_is_set() {
+        let source =
+            "defmodule Demo do\n  def run() do\n    helper 1, 2\n    assert value\n  end\nend\n";
+        let options = options_with(|options| {
+            options.locals_without_parens = Some(vec!["assert".to_string()]);
+        });
+
+        assert_eq!(format_source(source), source);
+
+        assert_eq!(
+            format_text(source, &options, None),
+            "defmodule Demo do\n  def run() do\n    helper(1, 2)\n    assert value\n  end\nend\n"
+        );
+    }
+
+    #[test]
+    fn format_text_preserves_or_normalizes_heredocs_and_sigils() {
+        let source = "defmodule Demo do\n  def run() do\n    {~w(ok error)a, \"\"\"\nhi\n\"\"\"}\n  end\nend\n";
+
+        assert_eq!(format_source(source), source);
+
+        let options = options_with(|options| {
+            options.preserve_heredocs = false;
+            options.preserve_sigils = false;
+        });
+        assert_eq!(
+            format_text(source, &options, None),
+            "defmodule Demo do\n  def run() do\n    {[:ok, :error], \"\nhi\n\"}\n  end\nend\n"
+        );
+    }
+
+    #[test]
+    fn format_text_with_range_only_rewrites_touched_lines() {
+        let source = "defmodule Demo do\ndef one() do\n1\nend\ndef two() do\n2\nend\nend\n";
+        let range = LineRange::parse("5:7").expect("range should parse");
+
+        assert_eq!(
+            format_text(source, &FormatOptions::default(), Some(range)),
+            "defmodule Demo do\ndef one() do\n1\nend\n  def two() do\n    2\n  end\nend\n"
+        );
+    }
+
+    #[test]
+    fn line_range_parse_rejects_malformed_and_inverted_ranges() {
+        assert_eq!(LineRange::parse("3:9"), Ok(LineRange { start: 3, end: 9 }));
+        assert!(LineRange::parse("9").is_err());
+        assert!(LineRange::parse("0:4").is_err());
+        assert!(LineRange::parse("9:3").is_err());
+    }
 }
@@ -29,10 +29,13 @@ mod json_mod;
 mod logger_mod;
 mod map_mod;
 mod math_mod;
+mod mock_mod;
 mod path_mod;
+mod property_mod;
 mod random_mod;
 mod regex_mod;
 mod shell_mod;
+mod snapshot_mod;
 mod store_mod;
 mod string_mod;
 mod system;
@@ -42,6 +45,13 @@ mod url_mod;
 mod uuid_mod;
 mod yaml_mod;
 
+pub(crate) use mock_mod::{host_stub, mock_scope, set_mock_scope, MockScope};
+pub(crate) use property_mod::{property_seed, set_property_seed, PropertySeed};
+pub(crate) use snapshot_mod::{
+    set_snapshot_scope, snapshot_scope, SnapshotOutcome, SnapshotRecord, SnapshotScope,
+    SNAPSHOT_EXTENSION,
+};
+
 /// Host function signature: takes runtime values, returns result
 pub type HostFn = fn(&[RuntimeValue]) -> Result<RuntimeValue, HostError>;
 
@@ -336,7 +346,7 @@ impl HostRegistry {
         let function = functions
             .get(key)
             .ok_or_else(|| HostError::new(format!("unknown host function: {key}")))?;
-        function(args)
+        mock_mod::call_through_cassette(key, args, *function)
     }
 
     /// Register sample host functions for testing and tooling interop.
@@ -450,6 +460,15 @@ impl HostRegistry {
 
         // Assert stdlib interop primitives for test assertion functions.
         assert_mod::register_assert_host_functions(self);
+
+        // PropertyTest choice stream and leaf generators for `check all`.
+        property_mod::register_property_host_functions(self);
+
+        // Assert.snapshot files under `__snapshots__/` next to each test file.
+        snapshot_mod::register_snapshot_host_functions(self);
+
+        // Mock.stub/Mock.cassette overrides scoped to one `tonic test` case.
+        mock_mod::register_mock_host_functions(self);
     }
 }
 
@@ -413,6 +413,199 @@ fn host_assert_raises_check(args: &[RuntimeValue]) -> Result<RuntimeValue, HostE
     }
 }
 
+/// Explain an `Assert.assert(match?(pattern, value))` failure. The closure
+/// built by the parser already decided that `value` does not match; this
+/// walks the encoded pattern to find the first sub-pattern that fails.
+///
+/// Pattern nodes are `{kind, {source, payload}}` where kind is :bind, :pin,
+/// :literal, :tuple, :list, :map or :opaque (bitstrings and structs, which
+/// are not explained further).
+fn host_assert_pattern(args: &[RuntimeValue]) -> Result<RuntimeValue, HostError> {
+    if args.len() < 3 || args.len() > 4 {
+        return Err(HostError::new(
+            "Assert.assert_pattern expects 3-4 arguments (value, pattern, tree, optional message)",
+        ));
+    }
+    let value = &args[0];
+    let pattern = &args[1];
+    let message = extract_message(args, 3, "value does not match pattern");
+
+    let mut details = vec![
+        detail_entry("type", RuntimeValue::Atom("assert_pattern".to_string())),
+        detail_entry("pattern", pattern.clone()),
+        detail_entry("value", value.clone()),
+        detail_entry("message", RuntimeValue::String(message)),
+    ];
+    let mut bindings = Vec::new();
+    if let Some(mismatch) = find_pattern_mismatch(&args[2], value, "value", &mut bindings)? {
+        details.push(detail_entry(
+            "failed",
+            RuntimeValue::String(mismatch.source),
+        ));
+        details.push(detail_entry("path", RuntimeValue::String(mismatch.path)));
+        details.push(detail_entry(
+            "reason",
+            RuntimeValue::String(mismatch.reason),
+        ));
+    }
+
+    Ok(RuntimeValue::ResultErr(Box::new(RuntimeValue::Tuple(
+        Box::new(RuntimeValue::Atom("assertion_failed".to_string())),
+        Box::new(RuntimeValue::List(details)),
+    ))))
+}
+
+fn detail_entry(key: &str, value: RuntimeValue) -> RuntimeValue {
+    RuntimeValue::Tuple(
+        Box::new(RuntimeValue::Atom(key.to_string())),
+        Box::new(value),
+    )
+}
+
+struct PatternMismatch {
+    /// Source text of the sub-pattern that failed.
+    source: String,
+    /// Where in the value it failed: `value[:user][0]`, with `{1}` for the
+    /// second element of a tuple.
+    path: String,
+    reason: String,
+}
+
+fn find_pattern_mismatch(
+    tree: &RuntimeValue,
+    value: &RuntimeValue,
+    path: &str,
+    bindings: &mut Vec<(String, RuntimeValue)>,
+) -> Result<Option<PatternMismatch>, HostError> {
+    let invalid = || HostError::new("Assert.assert_pattern expects a pattern built by match?");
+    let RuntimeValue::Tuple(kind, node) = tree else {
+        return Err(invalid());
+    };
+    let (RuntimeValue::Atom(kind), RuntimeValue::Tuple(source, payload)) = (&**kind, &**node)
+    else {
+        return Err(invalid());
+    };
+    let RuntimeValue::String(source) = &**source else {
+        return Err(invalid());
+    };
+    let mismatch = |reason: String| {
+        Ok(Some(PatternMismatch {
+            source: source.clone(),
+            path: path.to_string(),
+            reason,
+        }))
+    };
+
+    match (kind.as_str(), &**payload) {
+        ("bind", RuntimeValue::String(name)) if name.starts_with('_') => Ok(None),
+        ("bind", RuntimeValue::String(name)) => {
+            match bindings.iter().find(|(bound, _)| bound == name) {
+                Some((_, bound)) if bound != value => mismatch(format!(
+                    "{name} is already bound to {}, got {}",
+                    bound.render(),
+                    value.render()
+                )),
+                Some(_) => Ok(None),
+                None => {
+                    bindings.push((name.clone(), value.clone()));
+                    Ok(None)
+                }
+            }
+        }
+        ("pin" | "literal", expected) if expected == value => Ok(None),
+        ("pin" | "literal", expected) => mismatch(format!(
+            "expected {}, got {}",
+            expected.render(),
+            value.render()
+        )),
+        ("tuple", RuntimeValue::List(items)) => match (items.as_slice(), value) {
+            ([left, right], RuntimeValue::Tuple(left_value, right_value)) => {
+                for (index, (item, item_value)) in [(left, left_value), (right, right_value)]
+                    .into_iter()
+                    .enumerate()
+                {
+                    let item_path = format!("{path}{{{index}}}");
+                    if let Some(found) =
+                        find_pattern_mismatch(item, item_value, &item_path, bindings)?
+                    {
+                        return Ok(Some(found));
+                    }
+                }
+                Ok(None)
+            }
+            _ => mismatch(format!(
+                "expected a {}-element tuple, got {}",
+                items.len(),
+                value.render()
+            )),
+        },
+        ("list", RuntimeValue::Tuple(items, tail)) => {
+            let (RuntimeValue::List(items), RuntimeValue::List(values)) = (&**items, value) else {
+                return mismatch(format!("expected a list, got {}", value.render()));
+            };
+            let has_tail = !matches!(**tail, RuntimeValue::Nil);
+            if values.len() < items.len() || (!has_tail && values.len() > items.len()) {
+                let at_least = if has_tail { "at least " } else { "" };
+                return mismatch(format!(
+                    "expected a list of {at_least}{} element{}, got {}",
+                    items.len(),
+                    if items.len() == 1 { "" } else { "s" },
+                    values.len()
+                ));
+            }
+            for (index, (item, item_value)) in items.iter().zip(values).enumerate() {
+                let item_path = format!("{path}[{index}]");
+                if let Some(found) = find_pattern_mismatch(item, item_value, &item_path, bindings)?
+                {
+                    return Ok(Some(found));
+                }
+            }
+            if has_tail {
+                let rest = RuntimeValue::List(values[items.len()..].to_vec());
+                let tail_path = format!("{path}[{}..]", items.len());
+                return find_pattern_mismatch(tail, &rest, &tail_path, bindings);
+            }
+            Ok(None)
+        }
+        ("map", RuntimeValue::List(entries)) => {
+            let RuntimeValue::Map(values) = value else {
+                return mismatch(format!("expected a map, got {}", value.render()));
+            };
+            for entry in entries {
+                let RuntimeValue::Tuple(key_tree, value_tree) = entry else {
+                    return Err(invalid());
+                };
+                // Only literal and pinned keys can be looked up.
+                let key = match &**key_tree {
+                    RuntimeValue::Tuple(kind, node) => match (&**kind, &**node) {
+                        (RuntimeValue::Atom(kind), RuntimeValue::Tuple(_, key))
+                            if kind == "literal" || kind == "pin" =>
+                        {
+                            key
+                        }
+                        _ => continue,
+                    },
+                    _ => return Err(invalid()),
+                };
+                match values.iter().find(|(candidate, _)| candidate == &**key) {
+                    Some((_, entry_value)) => {
+                        let entry_path = format!("{path}[{}]", key.render());
+                        if let Some(found) =
+                            find_pattern_mismatch(value_tree, entry_value, &entry_path, bindings)?
+                        {
+                            return Ok(Some(found));
+                        }
+                    }
+                    None => return mismatch(format!("key {} is missing", key.render())),
+                }
+            }
+            Ok(None)
+        }
+        ("opaque", _) => Ok(None),
+        _ => Err(invalid()),
+    }
+}
+
 pub fn register_assert_host_functions(registry: &HostRegistry) {
     registry.register("assert", host_assert);
     registry.register("refute", host_refute);
@@ -422,5 +615,6 @@ pub fn register_assert_host_functions(registry: &HostRegistry) {
     registry.register("assert_in_delta", host_assert_in_delta);
     registry.register("skip", host_skip);
     registry.register("assert_match", host_assert_match);
+    registry.register("assert_pattern", host_assert_pattern);
     registry.register("assert_raises_check", host_assert_raises_check);
 }
@@ -0,0 +1,538 @@
+//! Host side of the `Mock` module: test-scoped host function stubs and
+//! cassettes that record host calls once and replay them on later runs.
+//!
+//! `tonic test` sets a fresh [`MockScope`] before each case and drops it
+//! afterwards, so stubs and cassettes never leak into the next test.
+//! Stubs are closures and are invoked by the interpreter (see
+//! [`host_stub`]); cassettes wrap [`HostRegistry::call`](super::HostRegistry::call).
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+use serde_json::{json, Value};
+
+use super::{host_value_kind, HostError, HostFn, HostRegistry};
+use crate::runtime::RuntimeValue;
+
+pub(crate) const CASSETTE_EXTENSION: &str = "json";
+
+/// Mocks of the current test case, set by `tonic test` before each case.
+#[derive(Debug, Clone)]
+pub(crate) struct MockScope {
+    /// `__cassettes__/<test file>/` of the case's test file.
+    pub dir: PathBuf,
+    /// `--record-cassettes`: call the real host functions and overwrite cassettes.
+    pub record: bool,
+    /// Shared by the setup, test and teardown calls of one case.
+    state: Arc<Mutex<MockState>>,
+}
+
+impl MockScope {
+    pub(crate) fn new(dir: PathBuf, record: bool) -> Self {
+        Self {
+            dir,
+            record,
+            state: Arc::default(),
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct MockState {
+    stubs: HashMap<String, RuntimeValue>,
+    cassettes: Vec<Cassette>,
+}
+
+#[derive(Debug)]
+struct Cassette {
+    name: String,
+    path: PathBuf,
+    functions: Vec<String>,
+    mode: CassetteMode,
+}
+
+#[derive(Debug)]
+enum CassetteMode {
+    /// Recorded calls and whether this run already replayed them.
+    Replay(Vec<(CassetteCall, bool)>),
+    Record(Vec<CassetteCall>),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct CassetteCall {
+    function: String,
+    args: Value,
+    result: Result<Value, String>,
+}
+
+thread_local! {
+    static MOCK_SCOPE: RefCell<Option<MockScope>> = const { RefCell::new(None) };
+}
+
+pub(crate) fn set_mock_scope(scope: Option<MockScope>) {
+    MOCK_SCOPE.with(|current| *current.borrow_mut() = scope);
+}
+
+pub(crate) fn mock_scope() -> Option<MockScope> {
+    MOCK_SCOPE.with(|current| current.borrow().clone())
+}
+
+fn with_state<T>(
+    name: &str,
+    f: impl FnOnce(&MockScope, &mut MockState) -> Result<T, HostError>,
+) -> Result<T, HostError> {
+    let Some(scope) = mock_scope() else {
+        return Err(HostError::new(format!(
+            "{name} is only available when running `tonic test`"
+        )));
+    };
+    let mut state = scope
+        .state
+        .lock()
+        .map_err(|_| HostError::new("mock state lock poisoned"))?;
+    f(&scope, &mut state)
+}
+
+/// The closure installed by `Mock.stub` for `key` in the current test, if any.
+pub(crate) fn host_stub(key: &str) -> Option<RuntimeValue> {
+    let scope = mock_scope()?;
+    let state = scope.state.lock().ok()?;
+    state.stubs.get(key).cloned()
+}
+
+/// Call `function` for `key`, or replay/record the call when a cassette of
+/// the current test covers `key`.
+pub(super) fn call_through_cassette(
+    key: &str,
+    args: &[RuntimeValue],
+    function: HostFn,
+) -> Result<RuntimeValue, HostError> {
+    let Some(scope) = mock_scope() else {
+        return function(args);
+    };
+    let mut state = scope
+        .state
+        .lock()
+        .map_err(|_| HostError::new("mock state lock poisoned"))?;
+    let Some(cassette) = state
+        .cassettes
+        .iter_mut()
+        .find(|cassette| cassette.functions.iter().any(|function| function == key))
+    else {
+        drop(state);
+        return function(args);
+    };
+
+    let encoded_args = value_to_json(&RuntimeValue::List(args.to_vec()))?;
+    match &mut cassette.mode {
+        CassetteMode::Replay(calls) => {
+            let Some((call, used)) = calls
+                .iter_mut()
+                .find(|(call, used)| !used && call.function == key && call.args == encoded_args)
+            else {
+                return Err(HostError::new(format!(
+                    "cassette \"{}\" has no unused {key} call with arguments {}; run with --record-cassettes to record it",
+                    cassette.name,
+                    RuntimeValue::List(args.to_vec()).render()
+                )));
+            };
+            *used = true;
+            match &call.result {
+                Ok(value) => value_from_json(value),
+                Err(message) => Err(HostError::new(message.clone())),
+            }
+        }
+        CassetteMode::Record(calls) => {
+            let result = function(args);
+            calls.push(CassetteCall {
+                function: key.to_string(),
+                args: encoded_args,
+                result: match &result {
+                    Ok(value) => Ok(value_to_json(value)?),
+                    Err(error) => Err(error.message.clone()),
+                },
+            });
+            write_cassette(&cassette.path, calls)?;
+            result
+        }
+    }
+}
+
+/// `Mock.stub(key, fun)`: calls to the host function `key` run `fun` with
+/// the call's argument list until the test ends.
+fn host_mock_stub(args: &[RuntimeValue]) -> Result<RuntimeValue, HostError> {
+    if args.len() != 2 {
+        return Err(HostError::new(
+            "Mock.stub expects exactly 2 arguments (host function, fun)",
+        ));
+    }
+    let key = expect_host_key("Mock.stub", &args[0])?;
+    if !matches!(args[1], RuntimeValue::Closure(_)) {
+        return Err(HostError::new(format!(
+            "Mock.stub expects a function as argument 2, found {}",
+            host_value_kind(&args[1])
+        )));
+    }
+
+    with_state("Mock.stub", |_, state| {
+        state.stubs.insert(key, args[1].clone());
+        Ok(RuntimeValue::Atom("ok".to_string()))
+    })
+}
+
+fn host_mock_unstub(args: &[RuntimeValue]) -> Result<RuntimeValue, HostError> {
+    if args.len() != 1 {
+        return Err(HostError::new(
+            "Mock.unstub expects exactly 1 argument (host function)",
+        ));
+    }
+    let key = expect_host_key("Mock.unstub", &args[0])?;
+
+    with_state("Mock.unstub", |_, state| {
+        state.stubs.remove(&key);
+        Ok(RuntimeValue::Atom("ok".to_string()))
+    })
+}
+
+/// `Mock.cassette(name, functions)`: replay calls to `functions` from the
+/// named cassette, or record them when it does not exist yet.
+fn host_mock_cassette(args: &[RuntimeValue]) -> Result<RuntimeValue, HostError> {
+    if args.len() != 2 {
+        return Err(HostError::new(
+            "Mock.cassette expects exactly 2 arguments (name, host functions)",
+        ));
+    }
+    let RuntimeValue::String(name) = &args[0] else {
+        return Err(HostError::new(format!(
+            "Mock.cassette expects a string name, found {}",
+            host_value_kind(&args[0])
+        )));
+    };
+    if name.trim().is_empty() {
+        return Err(HostError::new("Mock.cassette expects a non-empty name"));
+    }
+    let RuntimeValue::List(keys) = &args[1] else {
+        return Err(HostError::new(format!(
+            "Mock.cassette expects a list of host functions, found {}",
+            host_value_kind(&args[1])
+        )));
+    };
+    let functions = keys
+        .iter()
+        .map(|key| expect_host_key("Mock.cassette", key))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    with_state("Mock.cassette", |scope, state| {
+        let path = cassette_path(&scope.dir, name);
+        if state.cassettes.iter().any(|cassette| cassette.path == path) {
+            return Err(HostError::new(format!(
+                "cassette \"{name}\" is already in use by this test"
+            )));
+        }
+
+        let mode = if scope.record || !path.exists() {
+            CassetteMode::Record(Vec::new())
+        } else {
+            let calls = read_cassette(&path)?;
+            CassetteMode::Replay(calls.into_iter().map(|call| (call, false)).collect())
+        };
+        state.cassettes.push(Cassette {
+            name: name.clone(),
+            path,
+            functions,
+            mode,
+        });
+        Ok(RuntimeValue::Atom("ok".to_string()))
+    })
+}
+
+fn expect_host_key(name: &str, value: &RuntimeValue) -> Result<String, HostError> {
+    match value {
+        RuntimeValue::Atom(key) if !key.starts_with("mock_") => Ok(key.clone()),
+        RuntimeValue::Atom(key) => Err(HostError::new(format!(
+            "{name} cannot mock the Mock host function {key}"
+        ))),
+        other => Err(HostError::new(format!(
+            "{name} expects a host function atom, found {}",
+            host_value_kind(other)
+        ))),
+    }
+}
+
+/// The cassette file for `name`; characters other than letters, digits, `_`
+/// and `-` become `_`.
+fn cassette_path(dir: &Path, name: &str) -> PathBuf {
+    let file_name = name
+        .chars()
+        .map(|ch| {
+            if ch.is_ascii_alphanumeric() || ch == '_' || ch == '-' {
+                ch
+            } else {
+                '_'
+            }
+        })
+        .collect::<String>();
+    dir.join(format!("{file_name}.{CASSETTE_EXTENSION}"))
+}
+
+fn read_cassette(path: &Path) -> Result<Vec<CassetteCall>, HostError> {
+    let invalid =
+        |detail: String| HostError::new(format!("invalid cassette {}: {detail}", path.display()));
+    let text = std::fs::read_to_string(path).map_err(|error| invalid(error.to_string()))?;
+    let document: Value =
+        serde_json::from_str(&text).map_err(|error| invalid(error.to_string()))?;
+    let Some(calls) = document.get("calls").and_then(Value::as_array) else {
+        return Err(invalid("expected a \"calls\" list".to_string()));
+    };
+
+    calls
+        .iter()
+        .map(|call| {
+            let function = call
+                .get("function")
+                .and_then(Value::as_str)
+                .ok_or_else(|| invalid("call without \"function\"".to_string()))?;
+            let args = call
+                .get("args")
+                .cloned()
+                .ok_or_else(|| invalid(format!("{function} call without \"args\"")))?;
+            let result = match (call.get("result"), call.get("error")) {
+                (Some(result), None) => Ok(result.clone()),
+                (None, Some(Value::String(error))) => Err(error.clone()),
+                _ => {
+                    return Err(invalid(format!(
+                        "{function} call needs either \"result\" or \"error\""
+                    )))
+                }
+            };
+            Ok(CassetteCall {
+                function: function.to_string(),
+                args,
+                result,
+            })
+        })
+        .collect()
+}
+
+fn write_cassette(path: &Path, calls: &[CassetteCall]) -> Result<(), HostError> {
+    let calls = calls
+        .iter()
+        .map(|call| match &call.result {
+            Ok(result) => json!({"function": call.function, "args": call.args, "result": result}),
+            Err(error) => json!({"function": call.function, "args": call.args, "error": error}),
+        })
+        .collect::<Vec<_>>();
+    let mut text = serde_json::to_string_pretty(&json!({ "calls": calls }))
+        .map_err(|error| HostError::new(format!("failed to encode cassette: {error}")))?;
+    text.push('\n');
+
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir).map_err(|error| {
+            HostError::new(format!(
+                "failed to create cassette directory {}: {error}",
+                dir.display()
+            ))
+        })?;
+    }
+    std::fs::write(path, text).map_err(|error| {
+        HostError::new(format!(
+            "failed to write cassette {}: {error}",
+            path.display()
+        ))
+    })
+}
+
+/// Cassette encoding: integers, strings, booleans, nil and lists map to the
+/// matching JSON values; everything else is an object with a single tag.
+fn value_to_json(value: &RuntimeValue) -> Result<Value, HostError> {
+    let pairs = |entries: &[(RuntimeValue, RuntimeValue)]| {
+        entries
+            .iter()
+            .map(|(key, value)| Ok(json!([value_to_json(key)?, value_to_json(value)?])))
+            .collect::<Result<Vec<_>, HostError>>()
+    };
+    let items = |items: &[RuntimeValue]| {
+        items
+            .iter()
+            .map(value_to_json)
+            .collect::<Result<Vec<_>, HostError>>()
+    };
+
+    Ok(match value {
+        RuntimeValue::Int(number) => json!(number),
+        RuntimeValue::Float(text) => json!({ "float": text }),
+        RuntimeValue::Bool(flag) => json!(flag),
+        RuntimeValue::Nil => Value::Null,
+        RuntimeValue::String(text) => json!(text),
+        RuntimeValue::Atom(atom) => json!({ "atom": atom }),
+        RuntimeValue::ResultOk(inner) => json!({ "ok": value_to_json(inner)? }),
+        RuntimeValue::ResultErr(inner) => json!({ "err": value_to_json(inner)? }),
+        RuntimeValue::Tuple(first, second) => {
+            json!({ "tuple": [value_to_json(first)?, value_to_json(second)?] })
+        }
+        RuntimeValue::Map(entries) => json!({ "map": pairs(entries)? }),
+        RuntimeValue::Keyword(entries) => json!({ "keyword": pairs(entries)? }),
+        RuntimeValue::List(values) => Value::Array(items(values)?),
+        RuntimeValue::Binary(bytes) => json!({ "binary": items(bytes)? }),
+        RuntimeValue::Range(start, end) => json!({ "range": [start, end] }),
+        RuntimeValue::SteppedRange(start, end, step) => json!({ "range": [start, end, step] }),
+        RuntimeValue::Closure(_) => {
+            return Err(HostError::new(
+                "cassettes cannot record functions passed to or returned from host calls",
+            ))
+        }
+    })
+}
+
+fn value_from_json(value: &Value) -> Result<RuntimeValue, HostError> {
+    let invalid = || HostError::new(format!("invalid cassette value: {value}"));
+    let pairs = |entries: &Value| {
+        entries
+            .as_array()
+            .ok_or_else(invalid)?
+            .iter()
+            .map(|entry| match entry.as_array().map(Vec::as_slice) {
+                Some([key, value]) => Ok((value_from_json(key)?, value_from_json(value)?)),
+                _ => Err(invalid()),
+            })
+            .collect::<Result<Vec<_>, HostError>>()
+    };
+    let ints = |values: &Value| {
+        values
+            .as_array()
+            .ok_or_else(invalid)?
+            .iter()
+            .map(|value| value.as_i64().ok_or_else(invalid))
+            .collect::<Result<Vec<_>, HostError>>()
+    };
+
+    match value {
+        Value::Null => Ok(RuntimeValue::Nil),
+        Value::Bool(flag) => Ok(RuntimeValue::Bool(*flag)),
+        Value::Number(number) => number.as_i64().map(RuntimeValue::Int).ok_or_else(invalid),
+        Value::String(text) => Ok(RuntimeValue::String(text.clone())),
+        Value::Array(items) => Ok(RuntimeValue::List(
+            items
+                .iter()
+                .map(value_from_json)
+                .collect::<Result<_, _>>()?,
+        )),
+        Value::Object(object) => {
+            let Some((tag, inner)) = object.iter().next().filter(|_| object.len() == 1) else {
+                return Err(invalid());
+            };
+            match tag.as_str() {
+                "float" => Ok(RuntimeValue::Float(
+                    inner.as_str().ok_or_else(invalid)?.to_string(),
+                )),
+                "atom" => Ok(RuntimeValue::Atom(
+                    inner.as_str().ok_or_else(invalid)?.to_string(),
+                )),
+                "ok" => Ok(RuntimeValue::ResultOk(Box::new(value_from_json(inner)?))),
+                "err" => Ok(RuntimeValue::ResultErr(Box::new(value_from_json(inner)?))),
+                "tuple" => match inner.as_array().map(Vec::as_slice) {
+                    Some([first, second]) => Ok(RuntimeValue::Tuple(
+                        Box::new(value_from_json(first)?),
+                        Box::new(value_from_json(second)?),
+                    )),
+                    _ => Err(invalid()),
+                },
+                "map" => Ok(RuntimeValue::Map(pairs(inner)?)),
+                "keyword" => Ok(RuntimeValue::Keyword(pairs(inner)?)),
+                "binary" => Ok(RuntimeValue::Binary(
+                    ints(inner)?.into_iter().map(RuntimeValue::Int).collect(),
+                )),
+                "range" => match ints(inner)?.as_slice() {
+                    [start, end] => Ok(RuntimeValue::Range(*start, *end)),
+                    [start, end, step] => Ok(RuntimeValue::SteppedRange(*start, *end, *step)),
+                    _ => Err(invalid()),
+                },
+                _ => Err(invalid()),
+            }
+        }
+    }
+}
+
+pub fn register_mock_host_functions(registry: &HostRegistry) {
+    registry.register("mock_stub", host_mock_stub);
+    registry.register("mock_unstub", host_mock_unstub);
+    registry.register("mock_cassette", host_mock_cassette);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cassette_values_round_trip_through_json() {
+        let value = RuntimeValue::ResultOk(Box::new(RuntimeValue::Map(vec![
+            (
+                RuntimeValue::Atom("status".to_string()),
+                RuntimeValue::Int(200),
+            ),
+            (
+                RuntimeValue::Atom("body".to_string()),
+                RuntimeValue::String("{\"ok\":true}".to_string()),
+            ),
+            (
+                RuntimeValue::Atom("headers".to_string()),
+                RuntimeValue::List(vec![RuntimeValue::Tuple(
+                    Box::new(RuntimeValue::String("content-type".to_string())),
+                    Box::new(RuntimeValue::String("application/json".to_string())),
+                )]),
+            ),
+            (
+                RuntimeValue::Atom("elapsed".to_string()),
+                RuntimeValue::Float("0.25".to_string()),
+            ),
+        ])));
+
+        let encoded = value_to_json(&value).expect("value should encode");
+        assert_eq!(encoded["ok"]["map"][0], json!([{"atom": "status"}, 200]));
+        assert_eq!(value_from_json(&encoded), Ok(value));
+    }
+
+    #[test]
+    fn cassettes_replay_recorded_calls_in_order() {
+        let dir = std::env::temp_dir().join(format!(
+            "tonic-mock-cassette-{}-{}",
+            std::process::id(),
+            std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .map_or(0, |elapsed| elapsed.as_nanos())
+        ));
+        let counter = |args: &[RuntimeValue]| -> Result<RuntimeValue, HostError> {
+            static CALLS: std::sync::atomic::AtomicI64 = std::sync::atomic::AtomicI64::new(0);
+            let calls = CALLS.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+            Ok(RuntimeValue::List(vec![
+                args[0].clone(),
+                RuntimeValue::Int(calls),
+            ]))
+        };
+        let cassette = [
+            RuntimeValue::String("clock".to_string()),
+            RuntimeValue::List(vec![RuntimeValue::Atom("counter".to_string())]),
+        ];
+        let arg = [RuntimeValue::Atom("tick".to_string())];
+
+        set_mock_scope(Some(MockScope::new(dir.clone(), false)));
+        host_mock_cassette(&cassette).expect("cassette should start recording");
+        let first = call_through_cassette("counter", &arg, counter).expect("first call");
+        let second = call_through_cassette("counter", &arg, counter).expect("second call");
+
+        set_mock_scope(Some(MockScope::new(dir.clone(), false)));
+        host_mock_cassette(&cassette).expect("cassette should start replaying");
+        assert_eq!(call_through_cassette("counter", &arg, counter), Ok(first));
+        assert_eq!(call_through_cassette("counter", &arg, counter), Ok(second));
+        let exhausted = call_through_cassette("counter", &arg, counter)
+            .expect_err("cassette should be exhausted");
+        assert!(exhausted
+            .to_string()
+            .contains("cassette \"clock\" has no unused counter call"));
+
+        set_mock_scope(None);
+        let _ = std::fs::remove_dir_all(dir);
+    }
+}
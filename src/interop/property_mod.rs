@@ -0,0 +1,421 @@
+//! Host side of `PropertyTest`: the stream of random choices generators draw
+//! from, replaying and shrinking it, and the leaf generators.
+//!
+//! Every generated value is a function of the integers drawn while producing
+//! it. A failing case is shrunk by replaying smaller choice sequences through
+//! the same generators, so `bind`, `map` and `filter` shrink for free.
+
+use std::cell::{Cell, RefCell};
+
+use super::system::expect_exact_args;
+use super::{host_value_kind, HostError, HostRegistry};
+use crate::runtime::RuntimeValue;
+
+const ALPHANUMERIC: &[u8] = b"abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ0123456789";
+const ATOM_CHARS: &[u8] = b"abcdefghijklmnopqrstuvwxyz0123456789_";
+
+/// Seed of the property runs in the current test, set by `tonic test` before
+/// each case. `salt` is derived from the test id so that every test draws its
+/// own values for the same `--seed`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct PropertySeed {
+    pub seed: u64,
+    pub salt: u64,
+}
+
+#[derive(Debug, Default)]
+struct ChoiceStream {
+    /// SplitMix64 state while generating fresh cases; `None` while replaying.
+    random: Option<u64>,
+    replay: Vec<u64>,
+    position: usize,
+    drawn: Vec<u64>,
+}
+
+impl ChoiceStream {
+    /// A choice in `0..=bound`. Replayed choices are clamped to `bound`, and
+    /// a replay that runs out draws zeros.
+    fn draw(&mut self, bound: u64) -> u64 {
+        let choice = match self.random.as_mut() {
+            Some(state) => match bound.checked_add(1) {
+                Some(range) => splitmix64(state) % range,
+                None => splitmix64(state),
+            },
+            None => {
+                let choice = self.replay.get(self.position).copied().unwrap_or(0);
+                self.position += 1;
+                choice.min(bound)
+            }
+        };
+        self.drawn.push(choice);
+        choice
+    }
+}
+
+thread_local! {
+    static PROPERTY_SEED: Cell<Option<PropertySeed>> = const { Cell::new(None) };
+    static CHOICE_STREAM: RefCell<ChoiceStream> = RefCell::new(ChoiceStream::default());
+}
+
+pub(crate) fn set_property_seed(seed: Option<PropertySeed>) {
+    PROPERTY_SEED.with(|current| current.set(seed));
+}
+
+pub(crate) fn property_seed() -> Option<PropertySeed> {
+    PROPERTY_SEED.with(Cell::get)
+}
+
+/// The seed for this thread, picking a random one outside `tonic test`.
+fn current_seed() -> PropertySeed {
+    property_seed().unwrap_or_else(|| {
+        let seed = PropertySeed {
+            seed: rand::random_range(0..1_000_000),
+            salt: 0,
+        };
+        set_property_seed(Some(seed));
+        seed
+    })
+}
+
+fn splitmix64(state: &mut u64) -> u64 {
+    *state = state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+    let mut z = *state;
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
+
+fn draw(bound: u64) -> u64 {
+    CHOICE_STREAM.with(|stream| stream.borrow_mut().draw(bound))
+}
+
+fn expect_int(function: &str, value: &RuntimeValue) -> Result<i64, HostError> {
+    match value {
+        RuntimeValue::Int(value) => Ok(*value),
+        other => Err(HostError::new(format!(
+            "{function} expects an integer, found {}",
+            host_value_kind(other)
+        ))),
+    }
+}
+
+fn expect_size(function: &str, value: &RuntimeValue) -> Result<u64, HostError> {
+    let size = expect_int(function, value)?;
+    u64::try_from(size).map_err(|_| {
+        HostError::new(format!(
+            "{function} expects a non-negative size, found {size}"
+        ))
+    })
+}
+
+fn expect_choices(function: &str, value: &RuntimeValue) -> Result<Vec<u64>, HostError> {
+    let RuntimeValue::List(items) = value else {
+        return Err(HostError::new(format!(
+            "{function} expects a list of choices, found {}",
+            host_value_kind(value)
+        )));
+    };
+    items
+        .iter()
+        .map(|item| expect_size(function, item))
+        .collect()
+}
+
+fn choices_value(choices: &[u64]) -> RuntimeValue {
+    RuntimeValue::List(
+        choices
+            .iter()
+            .map(|choice| RuntimeValue::Int(*choice as i64))
+            .collect(),
+    )
+}
+
+/// The run's seed, shown in failure reports so the run can be repeated.
+fn host_property_seed(args: &[RuntimeValue]) -> Result<RuntimeValue, HostError> {
+    expect_exact_args("PropertyTest.seed", args, 0)?;
+    Ok(RuntimeValue::Int(current_seed().seed as i64))
+}
+
+/// Start generating run number `run` of the current property.
+fn host_property_start(args: &[RuntimeValue]) -> Result<RuntimeValue, HostError> {
+    expect_exact_args("PropertyTest.start", args, 1)?;
+    let run = expect_size("PropertyTest.start", &args[0])?;
+    let PropertySeed { seed, salt } = current_seed();
+    let mut state = seed ^ salt.rotate_left(17) ^ run.wrapping_mul(0xA24B_AED4_963E_E407);
+    splitmix64(&mut state);
+
+    CHOICE_STREAM.with(|stream| {
+        *stream.borrow_mut() = ChoiceStream {
+            random: Some(state),
+            ..ChoiceStream::default()
+        }
+    });
+    Ok(RuntimeValue::Atom("ok".to_string()))
+}
+
+/// Generate the next case from a recorded choice sequence.
+fn host_property_replay(args: &[RuntimeValue]) -> Result<RuntimeValue, HostError> {
+    expect_exact_args("PropertyTest.replay", args, 1)?;
+    let replay = expect_choices("PropertyTest.replay", &args[0])?;
+
+    CHOICE_STREAM.with(|stream| {
+        *stream.borrow_mut() = ChoiceStream {
+            replay,
+            ..ChoiceStream::default()
+        }
+    });
+    Ok(RuntimeValue::Atom("ok".to_string()))
+}
+
+/// The choices drawn since the last start or replay.
+fn host_property_choices(args: &[RuntimeValue]) -> Result<RuntimeValue, HostError> {
+    expect_exact_args("PropertyTest.choices", args, 0)?;
+    Ok(CHOICE_STREAM.with(|stream| choices_value(&stream.borrow().drawn)))
+}
+
+fn host_property_draw(args: &[RuntimeValue]) -> Result<RuntimeValue, HostError> {
+    expect_exact_args("PropertyTest.draw", args, 1)?;
+    let bound = expect_size("PropertyTest.draw", &args[0])?;
+    Ok(RuntimeValue::Int(draw(bound) as i64))
+}
+
+/// An integer in `min..=max` that shrinks towards the value closest to zero.
+fn host_property_integer(args: &[RuntimeValue]) -> Result<RuntimeValue, HostError> {
+    expect_exact_args("PropertyTest.integer", args, 2)?;
+    let min = expect_int("PropertyTest.integer", &args[0])?;
+    let max = expect_int("PropertyTest.integer", &args[1])?;
+    if min > max {
+        return Err(HostError::new(format!(
+            "PropertyTest.integer: min ({min}) must be <= max ({max})"
+        )));
+    }
+
+    let span = |from: i64, to: i64| (i128::from(to) - i128::from(from)) as u64;
+    let value = if min >= 0 {
+        i128::from(min) + i128::from(draw(span(min, max)))
+    } else if max <= 0 {
+        i128::from(max) - i128::from(draw(span(min, max)))
+    } else if draw(1) == 1 {
+        -i128::from(draw(span(min, 0)))
+    } else {
+        i128::from(draw(span(0, max)))
+    };
+    Ok(RuntimeValue::Int(value as i64))
+}
+
+/// A float with a whole part up to `size` and three decimals.
+fn host_property_float(args: &[RuntimeValue]) -> Result<RuntimeValue, HostError> {
+    expect_exact_args("PropertyTest.float", args, 1)?;
+    let size = expect_size("PropertyTest.float", &args[0])?;
+    let negative = draw(1) == 1;
+    let whole = draw(size) as f64;
+    let fraction = draw(999) as f64 / 1000.0;
+    let value = if negative {
+        -(whole + fraction)
+    } else {
+        whole + fraction
+    };
+
+    Ok(RuntimeValue::Float(if value.fract() == 0.0 {
+        format!("{value:.1}")
+    } else {
+        value.to_string()
+    }))
+}
+
+/// One character of the given kind; every kind shrinks towards `"a"`.
+fn host_property_char(args: &[RuntimeValue]) -> Result<RuntimeValue, HostError> {
+    expect_exact_args("PropertyTest.string", args, 1)?;
+    let character = match &args[0] {
+        RuntimeValue::Atom(kind) if kind == "alphanumeric" => {
+            ALPHANUMERIC[draw(ALPHANUMERIC.len() as u64 - 1) as usize] as char
+        }
+        RuntimeValue::Atom(kind) if kind == "ascii" => {
+            // Printable ASCII, starting the rotation at 'a'.
+            char::from(32 + ((draw(94) + 65) % 95) as u8)
+        }
+        RuntimeValue::Atom(kind) if kind == "utf8" => {
+            // Every scalar value once: surrogates are skipped.
+            let mut code = (0x61 + draw(0x10_F7FF)) % 0x10_F800;
+            if code >= 0xD800 {
+                code += 0x800;
+            }
+            char::from_u32(code as u32).unwrap_or('a')
+        }
+        other => {
+            return Err(HostError::new(format!(
+                "PropertyTest.string expects :alphanumeric, :ascii or :utf8, found {}",
+                other.render()
+            )))
+        }
+    };
+    Ok(RuntimeValue::String(character.to_string()))
+}
+
+/// An atom of up to `size + 1` lowercase characters, starting with a letter.
+fn host_property_atom(args: &[RuntimeValue]) -> Result<RuntimeValue, HostError> {
+    expect_exact_args("PropertyTest.atom", args, 1)?;
+    let size = expect_size("PropertyTest.atom", &args[0])?;
+    let length = draw(size.min(15));
+    let mut atom = String::from(ATOM_CHARS[draw(25) as usize] as char);
+    for _ in 0..length {
+        atom.push(ATOM_CHARS[draw(ATOM_CHARS.len() as u64 - 1) as usize] as char);
+    }
+    Ok(RuntimeValue::Atom(atom))
+}
+
+/// `:passed`, or `{:failed, reason}` for a property body that returned
+/// `err(reason)`.
+fn host_property_outcome(args: &[RuntimeValue]) -> Result<RuntimeValue, HostError> {
+    expect_exact_args("PropertyTest.outcome", args, 1)?;
+    Ok(match &args[0] {
+        RuntimeValue::ResultErr(reason) => RuntimeValue::Tuple(
+            Box::new(RuntimeValue::Atom("failed".to_string())),
+            reason.clone(),
+        ),
+        _ => RuntimeValue::Atom("passed".to_string()),
+    })
+}
+
+/// Smaller variants of a failing choice sequence, most promising first:
+/// deleted chunks, zeroed chunks, then each choice lowered.
+fn host_property_shrink(args: &[RuntimeValue]) -> Result<RuntimeValue, HostError> {
+    expect_exact_args("PropertyTest.shrink", args, 1)?;
+    let choices = expect_choices("PropertyTest.shrink", &args[0])?;
+    Ok(RuntimeValue::List(
+        shrink_candidates(&choices)
+            .iter()
+            .map(|candidate| choices_value(candidate))
+            .collect(),
+    ))
+}
+
+fn shrink_candidates(choices: &[u64]) -> Vec<Vec<u64>> {
+    let mut candidates: Vec<Vec<u64>> = Vec::new();
+    let mut push = |candidate: Vec<u64>| {
+        if candidate != choices && !candidates.contains(&candidate) {
+            candidates.push(candidate);
+        }
+    };
+
+    for chunk in [8, 4, 2, 1] {
+        for start in 0..choices.len().saturating_sub(chunk - 1) {
+            let mut candidate = choices[..start].to_vec();
+            candidate.extend_from_slice(&choices[start + chunk..]);
+            push(candidate);
+        }
+    }
+
+    for chunk in [8, 4, 2] {
+        for start in 0..choices.len().saturating_sub(chunk - 1) {
+            let mut candidate = choices.to_vec();
+            candidate[start..start + chunk].fill(0);
+            push(candidate);
+        }
+    }
+
+    for (index, choice) in choices.iter().enumerate() {
+        for lower in [0, choice / 2, choice.saturating_sub(1)] {
+            if lower < *choice {
+                let mut candidate = choices.to_vec();
+                candidate[index] = lower;
+                push(candidate);
+            }
+        }
+    }
+
+    candidates
+}
+
+/// Whether the first choice sequence is shorter, or as long and smaller.
+fn host_property_simpler(args: &[RuntimeValue]) -> Result<RuntimeValue, HostError> {
+    expect_exact_args("PropertyTest.simpler", args, 2)?;
+    let candidate = expect_choices("PropertyTest.simpler", &args[0])?;
+    let current = expect_choices("PropertyTest.simpler", &args[1])?;
+    Ok(RuntimeValue::Bool(
+        (candidate.len(), &candidate) < (current.len(), &current),
+    ))
+}
+
+pub fn register_property_host_functions(registry: &HostRegistry) {
+    registry.register("property_seed", host_property_seed);
+    registry.register("property_start", host_property_start);
+    registry.register("property_replay", host_property_replay);
+    registry.register("property_choices", host_property_choices);
+    registry.register("property_draw", host_property_draw);
+    registry.register("property_integer", host_property_integer);
+    registry.register("property_float", host_property_float);
+    registry.register("property_char", host_property_char);
+    registry.register("property_atom", host_property_atom);
+    registry.register("property_outcome", host_property_outcome);
+    registry.register("property_shrink", host_property_shrink);
+    registry.register("property_simpler", host_property_simpler);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn replay(choices: &[u64]) {
+        host_property_replay(&[choices_value(choices)]).unwrap();
+    }
+
+    fn integer(min: i64, max: i64) -> RuntimeValue {
+        host_property_integer(&[RuntimeValue::Int(min), RuntimeValue::Int(max)]).unwrap()
+    }
+
+    #[test]
+    fn same_seed_and_run_draw_the_same_choices() {
+        set_property_seed(Some(PropertySeed { seed: 7, salt: 3 }));
+        let sample = || {
+            host_property_start(&[RuntimeValue::Int(4)]).unwrap();
+            (0..5).map(|_| integer(-1000, 1000)).collect::<Vec<_>>()
+        };
+
+        assert_eq!(sample(), sample());
+        set_property_seed(None);
+    }
+
+    #[test]
+    fn replayed_integers_shrink_towards_zero_within_range() {
+        replay(&[]);
+        assert_eq!(integer(-5, 5), RuntimeValue::Int(0));
+        assert_eq!(integer(3, 9), RuntimeValue::Int(3));
+        assert_eq!(integer(-9, -3), RuntimeValue::Int(-3));
+
+        replay(&[1, 4, 100]);
+        assert_eq!(integer(-5, 5), RuntimeValue::Int(-4));
+        assert_eq!(integer(3, 9), RuntimeValue::Int(9));
+    }
+
+    #[test]
+    fn replay_records_the_choices_it_used() {
+        replay(&[0, 7]);
+        integer(0, 3);
+        integer(0, 3);
+        integer(0, 3);
+
+        assert_eq!(
+            host_property_choices(&[]).unwrap(),
+            choices_value(&[0, 3, 0])
+        );
+    }
+
+    #[test]
+    fn shrink_candidates_delete_before_lowering() {
+        assert_eq!(
+            shrink_candidates(&[2, 5]),
+            vec![
+                vec![],
+                vec![5],
+                vec![2],
+                vec![0, 0],
+                vec![0, 5],
+                vec![1, 5],
+                vec![2, 0],
+                vec![2, 2],
+                vec![2, 4]
+            ]
+        );
+    }
+}
@@ -0,0 +1,325 @@
+//! Host side of `Assert.snapshot`: rendering values into snapshot files and
+//! comparing them against the stored ones.
+//!
+//! `tonic test` sets a [`SnapshotScope`] before each case, naming the
+//! `__snapshots__/<test file>/` directory of the case's file and collecting
+//! every snapshot the run touches so obsolete ones can be reported.
+
+use sha2::{Digest, Sha256};
+use std::cell::RefCell;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+use super::{HostError, HostRegistry};
+use crate::runtime::RuntimeValue;
+
+/// Values whose one-line rendering is longer than this are split across lines.
+const SNAPSHOT_WIDTH: usize = 80;
+
+pub(crate) const SNAPSHOT_EXTENSION: &str = "snap";
+
+/// Where the current test's snapshots live, set by `tonic test` before each case.
+#[derive(Debug, Clone)]
+pub(crate) struct SnapshotScope {
+    pub dir: PathBuf,
+    pub test: String,
+    /// `--update-snapshots`: overwrite snapshots that no longer match.
+    pub update: bool,
+    /// Shared by every case of the run.
+    pub records: Arc<Mutex<Vec<SnapshotRecord>>>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct SnapshotRecord {
+    pub path: PathBuf,
+    pub test: String,
+    pub outcome: SnapshotOutcome,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum SnapshotOutcome {
+    Matched,
+    Written,
+    Updated,
+    Mismatched,
+}
+
+thread_local! {
+    static SNAPSHOT_SCOPE: RefCell<Option<SnapshotScope>> = const { RefCell::new(None) };
+}
+
+pub(crate) fn set_snapshot_scope(scope: Option<SnapshotScope>) {
+    SNAPSHOT_SCOPE.with(|current| *current.borrow_mut() = scope);
+}
+
+pub(crate) fn snapshot_scope() -> Option<SnapshotScope> {
+    SNAPSHOT_SCOPE.with(|current| current.borrow().clone())
+}
+
+/// The snapshot file for `name`; characters other than letters, digits, `_`
+/// and `-` become `_`, and a name changed that way gets a short hash of the
+/// original appended so that, say, "a b" and "a_b" keep separate files.
+pub(crate) fn snapshot_path(dir: &Path, name: &str) -> PathBuf {
+    let mut file_name = name
+        .chars()
+        .map(|ch| {
+            if ch.is_ascii_alphanumeric() || ch == '_' || ch == '-' {
+                ch
+            } else {
+                '_'
+            }
+        })
+        .collect::<String>();
+    if file_name != name {
+        let digest = Sha256::digest(name.as_bytes());
+        file_name.push('-');
+        file_name.extend(digest[..4].iter().map(|byte| format!("{byte:02x}")));
+    }
+    dir.join(format!("{file_name}.{SNAPSHOT_EXTENSION}"))
+}
+
+/// Snapshot contents: strings as they are, so CLI output reads naturally,
+/// and other values pretty-printed.
+pub(crate) fn render_snapshot(value: &RuntimeValue) -> String {
+    let mut text = match value {
+        RuntimeValue::String(text) => text.clone(),
+        other => pretty(other, 0),
+    };
+    text.push('\n');
+    text
+}
+
+fn pretty(value: &RuntimeValue, depth: usize) -> String {
+    let flat = value.render();
+    if depth * 2 + flat.len() <= SNAPSHOT_WIDTH {
+        return flat;
+    }
+
+    let indent = "  ".repeat(depth + 1);
+    let block = |open: &str, items: Vec<String>, close: &str| {
+        let closing_indent = "  ".repeat(depth);
+        let items = items
+            .into_iter()
+            .map(|item| format!("{indent}{item},\n"))
+            .collect::<String>();
+        format!("{open}\n{items}{closing_indent}{close}")
+    };
+
+    match value {
+        RuntimeValue::Map(entries) => block(
+            "%{",
+            entries
+                .iter()
+                .map(|(key, value)| format!("{} => {}", key.render(), pretty(value, depth + 1)))
+                .collect(),
+            "}",
+        ),
+        RuntimeValue::Keyword(entries) => block(
+            "[",
+            entries
+                .iter()
+                .map(|(key, value)| {
+                    let key = match key {
+                        RuntimeValue::Atom(atom) => atom.clone(),
+                        other => other.render(),
+                    };
+                    format!("{key}: {}", pretty(value, depth + 1))
+                })
+                .collect(),
+            "]",
+        ),
+        RuntimeValue::List(items) => block(
+            "[",
+            items.iter().map(|item| pretty(item, depth + 1)).collect(),
+            "]",
+        ),
+        RuntimeValue::Tuple(first, second) => block(
+            "{",
+            vec![pretty(first, depth + 1), pretty(second, depth + 1)],
+            "}",
+        ),
+        RuntimeValue::ResultOk(inner) => format!("ok({})", pretty(inner, depth)),
+        RuntimeValue::ResultErr(inner) => format!("err({})", pretty(inner, depth)),
+        _ => flat,
+    }
+}
+
+/// `Assert.snapshot(value, name)`: write the snapshot on first use, then
+/// compare against it, or overwrite it with `--update-snapshots`.
+fn host_assert_snapshot(args: &[RuntimeValue]) -> Result<RuntimeValue, HostError> {
+    if args.len() != 2 {
+        return Err(HostError::new(
+            "Assert.snapshot expects exactly 2 arguments (value, name)",
+        ));
+    }
+    let RuntimeValue::String(name) = &args[1] else {
+        return Err(HostError::new(format!(
+            "Assert.snapshot expects a string name, found {}",
+            args[1].kind_label()
+        )));
+    };
+    if name.trim().is_empty() {
+        return Err(HostError::new("Assert.snapshot expects a non-empty name"));
+    }
+    let Some(scope) = snapshot_scope() else {
+        return Err(HostError::new(
+            "Assert.snapshot is only available when running `tonic test`",
+        ));
+    };
+
+    let path = snapshot_path(&scope.dir, name);
+    let mut records = scope
+        .records
+        .lock()
+        .map_err(|_| HostError::new("snapshot records lock poisoned"))?;
+    if let Some(other) = records
+        .iter()
+        .find(|record| record.path == path && record.test != scope.test)
+    {
+        return Err(HostError::new(format!(
+            "snapshot \"{name}\" is already used by test {}; give each snapshot a unique name",
+            other.test
+        )));
+    }
+
+    let actual = render_snapshot(&args[0]);
+    let stored = match std::fs::read_to_string(&path) {
+        Ok(stored) => Some(stored),
+        Err(error) if error.kind() == std::io::ErrorKind::NotFound => None,
+        Err(error) => {
+            return Err(HostError::new(format!(
+                "failed to read snapshot {}: {error}",
+                path.display()
+            )))
+        }
+    };
+
+    let outcome = match stored {
+        Some(stored) if stored == actual => SnapshotOutcome::Matched,
+        Some(stored) if !scope.update => {
+            records.push(SnapshotRecord {
+                path: path.clone(),
+                test: scope.test.clone(),
+                outcome: SnapshotOutcome::Mismatched,
+            });
+            return Ok(snapshot_mismatch(name, &path, stored, actual));
+        }
+        stored => {
+            write_snapshot(&path, &actual)?;
+            if stored.is_some() {
+                SnapshotOutcome::Updated
+            } else {
+                SnapshotOutcome::Written
+            }
+        }
+    };
+
+    records.push(SnapshotRecord {
+        path,
+        test: scope.test.clone(),
+        outcome,
+    });
+    Ok(RuntimeValue::Atom("ok".to_string()))
+}
+
+fn write_snapshot(path: &Path, contents: &str) -> Result<(), HostError> {
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir).map_err(|error| {
+            HostError::new(format!(
+                "failed to create snapshot directory {}: {error}",
+                dir.display()
+            ))
+        })?;
+    }
+    std::fs::write(path, contents).map_err(|error| {
+        HostError::new(format!(
+            "failed to write snapshot {}: {error}",
+            path.display()
+        ))
+    })
+}
+
+/// `{:assertion_failed, [type: :snapshot, left: stored, right: actual, message: ...]}`
+fn snapshot_mismatch(name: &str, path: &Path, stored: String, actual: String) -> RuntimeValue {
+    let entry = |key: &str, value: RuntimeValue| {
+        RuntimeValue::Tuple(
+            Box::new(RuntimeValue::Atom(key.to_string())),
+            Box::new(value),
+        )
+    };
+    let message = format!(
+        "snapshot \"{name}\" does not match {}; run with --update-snapshots to accept the new value",
+        path.display()
+    );
+
+    RuntimeValue::ResultErr(Box::new(RuntimeValue::Tuple(
+        Box::new(RuntimeValue::Atom("assertion_failed".to_string())),
+        Box::new(RuntimeValue::List(vec![
+            entry("type", RuntimeValue::Atom("snapshot".to_string())),
+            entry("left", RuntimeValue::String(stored)),
+            entry("right", RuntimeValue::String(actual)),
+            entry("message", RuntimeValue::String(message)),
+        ])),
+    )))
+}
+
+pub fn register_snapshot_host_functions(registry: &HostRegistry) {
+    registry.register("assert_snapshot", host_assert_snapshot);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn atom(name: &str) -> RuntimeValue {
+        RuntimeValue::Atom(name.to_string())
+    }
+
+    #[test]
+    fn short_values_stay_on_one_line_and_long_ones_are_split() {
+        let short = RuntimeValue::Map(vec![(atom("id"), RuntimeValue::Int(1))]);
+        assert_eq!(render_snapshot(&short), "%{:id => 1}\n");
+
+        let long = RuntimeValue::Map(vec![
+            (
+                atom("name"),
+                RuntimeValue::String("a fairly long name for a snapshot".to_string()),
+            ),
+            (
+                atom("tags"),
+                RuntimeValue::List((0..20).map(RuntimeValue::Int).collect()),
+            ),
+        ]);
+        assert_eq!(
+            render_snapshot(&long),
+            "%{\n  :name => \"a fairly long name for a snapshot\",\n  :tags => [0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16, 17, 18, 19],\n}\n"
+        );
+
+        let text = RuntimeValue::String("usage: tool <path>\n".to_string());
+        assert_eq!(render_snapshot(&text), "usage: tool <path>\n\n");
+    }
+
+    #[test]
+    fn snapshot_names_become_safe_file_names() {
+        let dir = Path::new("__snapshots__/cli_test");
+        assert_eq!(
+            snapshot_path(dir, "help_output-v2"),
+            Path::new("__snapshots__/cli_test/help_output-v2.snap")
+        );
+
+        let sanitized = snapshot_path(dir, "help output/v2");
+        let file_name = sanitized.file_name().unwrap().to_str().unwrap();
+        assert!(file_name.starts_with("help_output_v2-"), "{file_name}");
+        assert_eq!(file_name.len(), "help_output_v2-01234567.snap".len());
+    }
+
+    #[test]
+    fn sanitized_snapshot_names_do_not_collide() {
+        let dir = Path::new("__snapshots__/cli_test");
+        let paths = ["a b", "a_b", "a/b", "a.b"].map(|name| snapshot_path(dir, name));
+
+        for (index, path) in paths.iter().enumerate() {
+            assert!(!paths[index + 1..].contains(path), "{path:?} collides");
+        }
+    }
+}
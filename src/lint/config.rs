@@ -0,0 +1,173 @@
+use super::rules::LintRule;
+use crate::manifest;
+use std::collections::HashMap;
+use std::path::Path;
+
+const PROJECT_MANIFEST_FILE: &str = "tonic.toml";
+
+/// Settings for one rule from a `[lint.<rule>]` table.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct RuleConfig {
+    pub(crate) enabled: bool,
+    pub(crate) max: Option<usize>,
+}
+
+/// Lint settings read from the `[lint]` section of `tonic.toml`.
+///
+/// Every rule is enabled with its default threshold unless configured:
+///
+/// ```toml
+/// [lint.missing_docs]
+/// enabled = false
+///
+/// [lint.cyclomatic_complexity]
+/// max = 12
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub(crate) struct LintConfig {
+    rules: HashMap<String, RuleConfig>,
+}
+
+impl LintConfig {
+    pub(crate) fn is_enabled(&self, rule: &str) -> bool {
+        self.rules.get(rule).is_none_or(|config| config.enabled)
+    }
+
+    pub(crate) fn max_for(&self, rule: &dyn LintRule) -> usize {
+        self.rules
+            .get(rule.id())
+            .and_then(|config| config.max)
+            .or_else(|| rule.default_max())
+            .unwrap_or(usize::MAX)
+    }
+}
+
+/// Locate the `tonic.toml` that applies to `path` and read its `[lint]`
+/// section. Missing manifests and sections yield the defaults.
+pub(crate) fn load_lint_config(
+    path: &Path,
+    rules: &[Box<dyn LintRule>],
+) -> Result<LintConfig, String> {
+    let config = manifest::find_nearest_config(path, |directory| {
+        let manifest = directory.join(PROJECT_MANIFEST_FILE);
+        if !manifest.is_file() {
+            return Ok(None);
+        }
+        let source = std::fs::read_to_string(&manifest)
+            .map_err(|error| format!("failed to read {}: {error}", manifest.display()))?;
+        parse_lint_section(&source, rules).map(Some)
+    })?;
+    Ok(config.unwrap_or_default())
+}
+
+pub(super) fn parse_lint_section(
+    source: &str,
+    rules: &[Box<dyn LintRule>],
+) -> Result<LintConfig, String> {
+    let value: toml::Value = toml::from_str(source)
+        .map_err(|error| format!("invalid {PROJECT_MANIFEST_FILE}: {error}"))?;
+    let table = match value.get("lint") {
+        None => return Ok(LintConfig::default()),
+        Some(toml::Value::Table(table)) => table,
+        Some(_) => {
+            return Err(format!(
+                "invalid {PROJECT_MANIFEST_FILE}: [lint] must be a table"
+            ))
+        }
+    };
+
+    let mut config = LintConfig::default();
+    for (rule_id, settings) in table {
+        let Some(rule) = rules.iter().find(|rule| rule.id() == rule_id) else {
+            return Err(format!(
+                "invalid {PROJECT_MANIFEST_FILE}: unknown lint rule '{rule_id}'"
+            ));
+        };
+        let toml::Value::Table(settings) = settings else {
+            return Err(format!(
+                "invalid {PROJECT_MANIFEST_FILE}: [lint.{rule_id}] must be a table"
+            ));
+        };
+
+        let mut rule_config = RuleConfig {
+            enabled: true,
+            max: None,
+        };
+        for (key, value) in settings {
+            match (key.as_str(), value) {
+                ("enabled", toml::Value::Boolean(enabled)) => rule_config.enabled = *enabled,
+                ("enabled", _) => {
+                    return Err(format!(
+                        "invalid {PROJECT_MANIFEST_FILE}: lint.{rule_id}.enabled must be a boolean"
+                    ))
+                }
+                ("max", _) if rule.default_max().is_none() => {
+                    return Err(format!(
+                        "invalid {PROJECT_MANIFEST_FILE}: lint rule '{rule_id}' does not take a max"
+                    ))
+                }
+                ("max", toml::Value::Integer(max)) if *max >= 0 => {
+                    rule_config.max = Some(*max as usize)
+                }
+                ("max", _) => {
+                    return Err(format!(
+                        "invalid {PROJECT_MANIFEST_FILE}: lint.{rule_id}.max must be a non-negative integer"
+                    ))
+                }
+                (other, _) => {
+                    return Err(format!(
+                        "invalid {PROJECT_MANIFEST_FILE}: unknown lint option 'lint.{rule_id}.{other}'"
+                    ))
+                }
+            }
+        }
+
+        config.rules.insert(rule_id.clone(), rule_config);
+    }
+
+    Ok(config)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lint::rules::builtin_rules;
+
+    #[test]
+    fn lint_section_disables_rules_and_overrides_thresholds() {
+        let rules = builtin_rules();
+        let config = parse_lint_section(
+            "[project]\nentry = \"src/main.tn\"\n\n[lint.missing_docs]\nenabled = false\n\n[lint.cyclomatic_complexity]\nmax = 12\n",
+            &rules,
+        )
+        .expect("lint section should parse");
+
+        assert!(!config.is_enabled("missing_docs"));
+        assert!(config.is_enabled("nesting_depth"));
+
+        let complexity = rules
+            .iter()
+            .find(|rule| rule.id() == "cyclomatic_complexity")
+            .unwrap();
+        assert_eq!(config.max_for(complexity.as_ref()), 12);
+        let nesting = rules
+            .iter()
+            .find(|rule| rule.id() == "nesting_depth")
+            .unwrap();
+        assert_eq!(config.max_for(nesting.as_ref()), 3);
+    }
+
+    #[test]
+    fn lint_section_rejects_unknown_rules_and_misplaced_thresholds() {
+        let rules = builtin_rules();
+
+        assert_eq!(
+            parse_lint_section("[lint.no_such_rule]\nenabled = false\n", &rules).unwrap_err(),
+            "invalid tonic.toml: unknown lint rule 'no_such_rule'"
+        );
+        assert_eq!(
+            parse_lint_section("[lint.predicate_naming]\nmax = 2\n", &rules).unwrap_err(),
+            "invalid tonic.toml: lint rule 'predicate_naming' does not take a max"
+        );
+    }
+}
@@ -0,0 +1,309 @@
+//! Static analysis for Tonic sources (`tonic lint`).
+//!
+//! Each `.tn` file is parsed on its own and every enabled [`rules::LintRule`]
+//! runs over its modules. Rules are configured through `[lint.<rule>]` tables
+//! in `tonic.toml` and silenced inline with `# tonic-lint: disable` comments.
+
+mod config;
+mod report;
+mod rules;
+
+use crate::cli_diag::{CliDiagnostic, EXIT_FAILURE, EXIT_OK};
+use crate::formatter::collect_tonic_files;
+use crate::lexer::{scan_tokens_with_comments, Comment};
+use crate::parser::parse_ast;
+use config::{load_lint_config, LintConfig};
+use report::{FileFindings, LintOutputFormat, LintReport};
+use rules::{builtin_rules, LintRule, RuleContext};
+use std::path::Path;
+
+const DIRECTIVE_PREFIX: &str = "tonic-lint:";
+
+/// One rule violation at a byte offset in the linted source.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct LintFinding {
+    pub(crate) rule: &'static str,
+    pub(crate) message: String,
+    pub(crate) offset: usize,
+}
+
+impl LintFinding {
+    pub(crate) fn new(rule: &'static str, message: impl Into<String>, offset: usize) -> Self {
+        Self {
+            rule,
+            message: message.into(),
+            offset,
+        }
+    }
+}
+
+/// Entry point for `tonic lint <path> [--format <text|json|sarif>]`.
+pub(crate) fn handle_lint(args: Vec<String>) -> i32 {
+    if matches!(args.first().map(String::as_str), Some("-h" | "--help")) {
+        print_lint_help();
+        return EXIT_OK;
+    }
+
+    if args.is_empty() {
+        return CliDiagnostic::usage_with_hint(
+            "missing required <path>",
+            "run `tonic lint --help` for usage",
+        )
+        .emit();
+    }
+
+    let source_path = &args[0];
+    let mut format = LintOutputFormat::Text;
+    let mut index = 1;
+
+    while index < args.len() {
+        match args[index].as_str() {
+            "--format" => {
+                let Some(value) = args.get(index + 1) else {
+                    return CliDiagnostic::usage_with_hint(
+                        "missing value for --format",
+                        "usage: tonic lint <path> --format <text|json|sarif>",
+                    )
+                    .emit();
+                };
+
+                let Some(parsed) = LintOutputFormat::parse(value) else {
+                    return CliDiagnostic::usage_with_hint(
+                        format!(
+                            "unsupported format '{value}' (expected 'text', 'json' or 'sarif')"
+                        ),
+                        "valid formats: text, json, sarif",
+                    )
+                    .emit();
+                };
+
+                format = parsed;
+                index += 2;
+            }
+            other => {
+                return CliDiagnostic::usage_with_hint(
+                    format!("unexpected argument '{other}'"),
+                    "run `tonic lint --help` for usage",
+                )
+                .emit();
+            }
+        }
+    }
+
+    let requested_path = Path::new(source_path);
+    if !requested_path.exists() {
+        return CliDiagnostic::failure(format!(
+            "path does not exist: {}",
+            requested_path.display()
+        ))
+        .emit();
+    }
+
+    let rules = builtin_rules();
+    let config = match load_lint_config(requested_path, &rules) {
+        Ok(config) => config,
+        Err(error) => return CliDiagnostic::failure(error).emit(),
+    };
+    let files = match collect_tonic_files(requested_path) {
+        Ok(files) => files,
+        Err(error) => return CliDiagnostic::failure(error).emit(),
+    };
+
+    let mut report = LintReport {
+        checked_files: 0,
+        files: Vec::new(),
+    };
+
+    for file in files {
+        let path = file.display().to_string();
+        let source = match std::fs::read_to_string(&file) {
+            Ok(source) => source,
+            Err(error) => {
+                return CliDiagnostic::failure(format!(
+                    "failed to read source file {path}: {error}"
+                ))
+                .emit()
+            }
+        };
+
+        let findings = match lint_source(&source, &rules, &config) {
+            Ok(findings) => findings,
+            Err((message, offset)) => {
+                return CliDiagnostic::failure_with_filename_and_source(
+                    message,
+                    Some(&path),
+                    &source,
+                    offset,
+                )
+                .emit()
+            }
+        };
+
+        report.checked_files += 1;
+        report.files.push(FileFindings {
+            path,
+            source,
+            findings,
+        });
+    }
+
+    match format {
+        LintOutputFormat::Text => {
+            for line in report.render_text() {
+                println!("{line}");
+            }
+        }
+        LintOutputFormat::Json => println!("{}", report.render_json()),
+        LintOutputFormat::Sarif => println!("{}", report.render_sarif(&rules)),
+    }
+
+    if report.finding_count() == 0 {
+        EXIT_OK
+    } else {
+        EXIT_FAILURE
+    }
+}
+
+/// Run every enabled rule over `source`, dropping suppressed findings.
+/// Lexer and parser failures are returned with their source offset.
+fn lint_source(
+    source: &str,
+    rules: &[Box<dyn LintRule>],
+    config: &LintConfig,
+) -> Result<Vec<LintFinding>, (String, Option<usize>)> {
+    let (tokens, comments) = scan_tokens_with_comments(source)
+        .map_err(|error| (error.to_string(), Some(error.offset())))?;
+    let ast = parse_ast(&tokens).map_err(|error| (error.to_string(), error.offset()))?;
+    let suppressions = Suppressions::from_comments(source, &comments);
+
+    let mut findings = Vec::new();
+    for rule in rules.iter().filter(|rule| config.is_enabled(rule.id())) {
+        let context = RuleContext {
+            source,
+            max: config.max_for(rule.as_ref()),
+        };
+        for module in &ast.modules {
+            findings.extend(
+                rule.check_module(module, &context)
+                    .into_iter()
+                    .filter(|finding| {
+                        !suppressions.covers(finding.rule, line_of(source, finding.offset))
+                    }),
+            );
+        }
+    }
+
+    findings.sort_by_key(|finding| finding.offset);
+    Ok(findings)
+}
+
+/// `# tonic-lint: disable [rule, ...]` silences its own line when it trails
+/// code and the following line when it stands alone;
+/// `# tonic-lint: disable-file [rule, ...]` silences the whole file. Without
+/// a rule list every rule is silenced.
+#[derive(Debug, Default)]
+struct Suppressions {
+    /// `(line, rules)`; `None` for the line means the whole file.
+    entries: Vec<(Option<usize>, Vec<String>)>,
+}
+
+impl Suppressions {
+    fn from_comments(source: &str, comments: &[Comment]) -> Self {
+        let mut suppressions = Self::default();
+
+        for comment in comments {
+            let Some(directive) = comment
+                .text()
+                .trim_start_matches('#')
+                .trim()
+                .strip_prefix(DIRECTIVE_PREFIX)
+            else {
+                continue;
+            };
+            let directive = directive.trim();
+            let (action, rule_list) = directive
+                .split_once(char::is_whitespace)
+                .unwrap_or((directive, ""));
+            let rules = rule_list
+                .split(|ch: char| ch == ',' || ch.is_whitespace())
+                .filter(|rule| !rule.is_empty())
+                .map(str::to_string)
+                .collect();
+            let line = line_of(source, comment.span().start());
+
+            match action {
+                "disable" if comment.has_code_before() => {
+                    suppressions.entries.push((Some(line), rules));
+                }
+                "disable" => suppressions.entries.push((Some(line + 1), rules)),
+                "disable-file" => suppressions.entries.push((None, rules)),
+                _ => {}
+            }
+        }
+
+        suppressions
+    }
+
+    fn covers(&self, rule: &str, line: usize) -> bool {
+        self.entries.iter().any(|(target, rules)| {
+            target.is_none_or(|target| target == line)
+                && (rules.is_empty() || rules.iter().any(|listed| listed == rule))
+        })
+    }
+}
+
+fn line_of(source: &str, offset: usize) -> usize {
+    source.as_bytes()[..offset.min(source.len())]
+        .iter()
+        .filter(|byte| **byte == b'\n')
+        .count()
+        + 1
+}
+
+fn print_lint_help() {
+    let mut rule_lines = String::new();
+    for rule in builtin_rules() {
+        let threshold = rule
+            .default_max()
+            .map(|max| format!(" (max {max})"))
+            .unwrap_or_default();
+        rule_lines.push_str(&format!(
+            "  {:<25} {}{threshold}\n",
+            rule.id(),
+            rule.description()
+        ));
+    }
+
+    println!(
+        "Usage:\n  tonic lint <path> [--format <text|json|sarif>]\n\n\
+         Options:\n  --format <text|json|sarif>   Output format (default: text)\n\n\
+         Rules:\n{rule_lines}\n\
+         Configure rules in tonic.toml with [lint.<rule>] tables (`enabled`, `max`).\n\
+         Silence findings with `# tonic-lint: disable [rule, ...]` (this or the next line)\n\
+         or `# tonic-lint: disable-file [rule, ...]`.\n"
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lint_source_honours_line_and_file_suppressions() {
+        let source = "defmodule Demo do\n  @moduledoc \"Demo.\"\n\n  # tonic-lint: disable missing_docs\n  def is_ready(x) do\n    x\n  end\n\n  def is_done(x) do # tonic-lint: disable\n    x\n  end\nend\n";
+        let rules = builtin_rules();
+
+        let findings = lint_source(source, &rules, &LintConfig::default()).unwrap();
+        assert_eq!(
+            findings
+                .iter()
+                .map(|finding| (finding.rule, line_of(source, finding.offset)))
+                .collect::<Vec<_>>(),
+            vec![("predicate_naming", 5)]
+        );
+
+        let whole_file = format!("# tonic-lint: disable-file predicate_naming\n{source}");
+        let findings = lint_source(&whole_file, &rules, &LintConfig::default()).unwrap();
+        assert!(findings.is_empty(), "unexpected findings: {findings:?}");
+    }
+}
@@ -0,0 +1,146 @@
+use super::rules::LintRule;
+use super::LintFinding;
+use crate::cli_diag::failure_message_lines_with_filename_and_source;
+use serde_json::json;
+
+const SARIF_SCHEMA: &str = "https://json.schemastore.org/sarif-2.1.0.json";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum LintOutputFormat {
+    Text,
+    Json,
+    Sarif,
+}
+
+impl LintOutputFormat {
+    pub(crate) fn parse(value: &str) -> Option<Self> {
+        match value {
+            "text" => Some(Self::Text),
+            "json" => Some(Self::Json),
+            "sarif" => Some(Self::Sarif),
+            _ => None,
+        }
+    }
+}
+
+/// Findings for one linted file, kept with its source for snippet rendering.
+pub(crate) struct FileFindings {
+    pub(crate) path: String,
+    pub(crate) source: String,
+    pub(crate) findings: Vec<LintFinding>,
+}
+
+impl FileFindings {
+    fn line_and_column(&self, offset: usize) -> (usize, usize) {
+        let before = &self.source[..offset.min(self.source.len())];
+        let line_start = before.rfind('\n').map(|index| index + 1).unwrap_or(0);
+        let line = before.bytes().filter(|byte| *byte == b'\n').count() + 1;
+        (line, before[line_start..].chars().count() + 1)
+    }
+}
+
+pub(crate) struct LintReport {
+    pub(crate) checked_files: usize,
+    pub(crate) files: Vec<FileFindings>,
+}
+
+impl LintReport {
+    pub(crate) fn finding_count(&self) -> usize {
+        self.files.iter().map(|file| file.findings.len()).sum()
+    }
+
+    pub(crate) fn render_text(&self) -> Vec<String> {
+        let mut lines = Vec::new();
+
+        for file in &self.files {
+            for finding in &file.findings {
+                lines.extend(failure_message_lines_with_filename_and_source(
+                    format!("warning: [{}] {}", finding.rule, finding.message),
+                    Some(&file.path),
+                    &file.source,
+                    Some(finding.offset),
+                ));
+                lines.push(String::new());
+            }
+        }
+
+        let count = self.finding_count();
+        if count == 0 {
+            lines.push("lint: ok".to_string());
+        } else {
+            let flagged_files = self
+                .files
+                .iter()
+                .filter(|file| !file.findings.is_empty())
+                .count();
+            lines.push(format!(
+                "lint: {count} warning{} in {flagged_files} file{}",
+                if count == 1 { "" } else { "s" },
+                if flagged_files == 1 { "" } else { "s" }
+            ));
+        }
+
+        lines
+    }
+
+    pub(crate) fn render_json(&self) -> serde_json::Value {
+        let mut diagnostics = Vec::new();
+        for file in &self.files {
+            for finding in &file.findings {
+                let (line, column) = file.line_and_column(finding.offset);
+                diagnostics.push(json!({
+                    "rule": finding.rule,
+                    "message": finding.message,
+                    "path": file.path,
+                    "line": line,
+                    "column": column,
+                }));
+            }
+        }
+
+        json!({
+            "status": if diagnostics.is_empty() { "ok" } else { "warnings" },
+            "checked_files": self.checked_files,
+            "diagnostics": diagnostics,
+        })
+    }
+
+    /// SARIF 2.1.0 log with a single run, for CI code-scanning uploads.
+    pub(crate) fn render_sarif(&self, rules: &[Box<dyn LintRule>]) -> serde_json::Value {
+        let mut results = Vec::new();
+        for file in &self.files {
+            for finding in &file.findings {
+                let (line, column) = file.line_and_column(finding.offset);
+                results.push(json!({
+                    "ruleId": finding.rule,
+                    "level": "warning",
+                    "message": { "text": finding.message },
+                    "locations": [{
+                        "physicalLocation": {
+                            "artifactLocation": { "uri": file.path.replace('\\', "/") },
+                            "region": { "startLine": line, "startColumn": column },
+                        }
+                    }],
+                }));
+            }
+        }
+
+        json!({
+            "$schema": SARIF_SCHEMA,
+            "version": "2.1.0",
+            "runs": [{
+                "tool": {
+                    "driver": {
+                        "name": "tonic-lint",
+                        "version": env!("CARGO_PKG_VERSION"),
+                        "rules": rules.iter().map(|rule| json!({
+                            "id": rule.id(),
+                            "shortDescription": { "text": rule.description() },
+                        })).collect::<Vec<_>>(),
+                    }
+                },
+                "results": results,
+            }],
+        })
+    }
+}
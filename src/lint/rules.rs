@@ -0,0 +1,844 @@
+use super::LintFinding;
+use crate::parser::{
+    BinaryOp, CaseBranch, Expr, Function, InterpolationSegment, Module, ModuleForm, Pattern,
+};
+use std::collections::HashSet;
+
+/// Binding introduced by the parser when lowering `with` into nested cases.
+const WITH_FAILURE_BINDING: &str = "__tonic_with_failure";
+
+/// Attributes with compiler or tooling meaning that are never "read" by code.
+const RESERVED_ATTRIBUTES: &[&str] = &[
+    "moduledoc",
+    "doc",
+    "typedoc",
+    "spec",
+    "type",
+    "typep",
+    "opaque",
+    "callback",
+    "behaviour",
+    "impl",
+    "derive",
+    "deprecated",
+    "since",
+    "compile",
+    "vsn",
+    "async",
+    "tag",
+    "moduletag",
+];
+
+/// Per-run inputs handed to each rule.
+pub(crate) struct RuleContext<'a> {
+    pub(crate) source: &'a str,
+    /// Configured threshold, for rules with a `default_max`.
+    pub(crate) max: usize,
+}
+
+/// A lint rule run over every parsed module of a file.
+///
+/// Rules are registered in [`builtin_rules`]; the `id` doubles as the
+/// `[lint.<id>]` config table name and the name used in suppression comments.
+pub(crate) trait LintRule {
+    fn id(&self) -> &'static str;
+
+    fn description(&self) -> &'static str;
+
+    /// Threshold used when `tonic.toml` does not set `max` for this rule.
+    /// Rules without a threshold return `None` and reject a configured `max`.
+    fn default_max(&self) -> Option<usize> {
+        None
+    }
+
+    fn check_module(&self, module: &Module, context: &RuleContext<'_>) -> Vec<LintFinding>;
+}
+
+pub(crate) fn builtin_rules() -> Vec<Box<dyn LintRule>> {
+    vec![
+        Box::new(CyclomaticComplexity),
+        Box::new(NestingDepth),
+        Box::new(PipeChainStart),
+        Box::new(LengthComparison),
+        Box::new(UnusedModuleAttribute),
+        Box::new(MissingDocs),
+        Box::new(PredicateNaming),
+        Box::new(LargeCond),
+    ]
+}
+
+// ---------------------------------------------------------------------------
+// Rules
+// ---------------------------------------------------------------------------
+
+struct CyclomaticComplexity;
+
+impl LintRule for CyclomaticComplexity {
+    fn id(&self) -> &'static str {
+        "cyclomatic_complexity"
+    }
+
+    fn description(&self) -> &'static str {
+        "Function clauses with too many independent paths"
+    }
+
+    fn default_max(&self) -> Option<usize> {
+        Some(9)
+    }
+
+    fn check_module(&self, module: &Module, context: &RuleContext<'_>) -> Vec<LintFinding> {
+        module
+            .functions
+            .iter()
+            .filter_map(|function| {
+                let complexity = 1 + function_roots(function)
+                    .into_iter()
+                    .map(decision_points)
+                    .sum::<usize>();
+                (complexity > context.max).then(|| {
+                    LintFinding::new(
+                        self.id(),
+                        format!(
+                            "function `{}` has cyclomatic complexity {complexity} (max {})",
+                            function_label(function),
+                            context.max
+                        ),
+                        function.offset,
+                    )
+                })
+            })
+            .collect()
+    }
+}
+
+fn decision_points(expr: &Expr) -> usize {
+    match expr {
+        Expr::Case {
+            subject, branches, ..
+        } if is_lowered_with(branches) => {
+            // Every `with` clause lowers to its own case, each carrying a copy
+            // of the `else` branches; count a clause once and `else` once.
+            let continuation = branches[0].body();
+            let failure = if is_lowered_with_expr(continuation) {
+                0
+            } else {
+                decision_points(branches[1].body())
+            };
+            1 + decision_points(subject) + decision_points(continuation) + failure
+        }
+        Expr::Case { branches, .. } => {
+            branches.len().saturating_sub(1)
+                + children(expr)
+                    .into_iter()
+                    .map(decision_points)
+                    .sum::<usize>()
+        }
+        Expr::Try { rescue, catch, .. } => {
+            rescue.len()
+                + catch.len()
+                + children(expr)
+                    .into_iter()
+                    .map(decision_points)
+                    .sum::<usize>()
+        }
+        Expr::For { generators, .. } => {
+            generators
+                .iter()
+                .filter(|generator| generator.guard().is_some())
+                .count()
+                + children(expr)
+                    .into_iter()
+                    .map(decision_points)
+                    .sum::<usize>()
+        }
+        Expr::Binary {
+            op: BinaryOp::And | BinaryOp::Or | BinaryOp::AndAnd | BinaryOp::OrOr,
+            ..
+        } => {
+            1 + children(expr)
+                .into_iter()
+                .map(decision_points)
+                .sum::<usize>()
+        }
+        _ => children(expr).into_iter().map(decision_points).sum(),
+    }
+}
+
+struct NestingDepth;
+
+impl LintRule for NestingDepth {
+    fn id(&self) -> &'static str {
+        "nesting_depth"
+    }
+
+    fn description(&self) -> &'static str {
+        "Control flow nested too deeply inside a function"
+    }
+
+    fn default_max(&self) -> Option<usize> {
+        Some(3)
+    }
+
+    fn check_module(&self, module: &Module, context: &RuleContext<'_>) -> Vec<LintFinding> {
+        let mut findings = Vec::new();
+
+        for function in &module.functions {
+            let mut deepest = NestingVisit::default();
+            for root in function_roots(function) {
+                deepest.visit(root, 0, false);
+            }
+
+            if let Some(offset) = deepest.first_violation(context.max) {
+                findings.push(LintFinding::new(
+                    self.id(),
+                    format!(
+                        "function `{}` nests control flow {} levels deep (max {})",
+                        function_label(function),
+                        deepest.max_depth,
+                        context.max
+                    ),
+                    offset,
+                ));
+            }
+        }
+
+        findings
+    }
+}
+
+#[derive(Default)]
+struct NestingVisit {
+    max_depth: usize,
+    /// Offset of each construct, in visit order, paired with its depth.
+    levels: Vec<(usize, usize)>,
+}
+
+impl NestingVisit {
+    /// `same_level` marks the nested cases a single `with` lowers into, which
+    /// read as one construct in the source.
+    fn visit(&mut self, expr: &Expr, depth: usize, same_level: bool) {
+        let nests = matches!(
+            expr,
+            Expr::Case { .. } | Expr::Try { .. } | Expr::For { .. } | Expr::Fn { .. }
+        );
+        let depth = if nests && !same_level {
+            depth + 1
+        } else {
+            depth
+        };
+        if nests {
+            self.max_depth = self.max_depth.max(depth);
+            self.levels.push((expr.offset(), depth));
+        }
+
+        if let Expr::Case {
+            subject, branches, ..
+        } = expr
+        {
+            if is_lowered_with(branches) {
+                self.visit(subject, depth, false);
+                let continuation = branches[0].body();
+                self.visit(continuation, depth, is_lowered_with_expr(continuation));
+                self.visit(branches[1].body(), depth, true);
+                return;
+            }
+        }
+
+        for child in children(expr) {
+            self.visit(child, depth, false);
+        }
+    }
+
+    fn first_violation(&self, max: usize) -> Option<usize> {
+        self.levels
+            .iter()
+            .find(|(_, depth)| *depth > max)
+            .map(|(offset, _)| *offset)
+    }
+}
+
+struct PipeChainStart;
+
+impl LintRule for PipeChainStart {
+    fn id(&self) -> &'static str {
+        "pipe_chain_start"
+    }
+
+    fn description(&self) -> &'static str {
+        "Pipe chains should start with a value, not a function call"
+    }
+
+    fn check_module(&self, module: &Module, _context: &RuleContext<'_>) -> Vec<LintFinding> {
+        let mut findings = Vec::new();
+        for root in module_roots(module) {
+            self.visit(root, &mut findings);
+        }
+        findings
+    }
+}
+
+impl PipeChainStart {
+    fn visit(&self, expr: &Expr, findings: &mut Vec<LintFinding>) {
+        let Expr::Pipe { .. } = expr else {
+            for child in children(expr) {
+                self.visit(child, findings);
+            }
+            return;
+        };
+
+        // `a |> b() |> c()` parses as `(a |> b()) |> c()`; walk to the start.
+        let mut steps = Vec::new();
+        let mut start = expr;
+        while let Expr::Pipe { left, right, .. } = start {
+            steps.push(right.as_ref());
+            start = left;
+        }
+
+        if let Expr::Call { callee, args, .. } = start {
+            if !args.is_empty() {
+                findings.push(LintFinding::new(
+                    self.id(),
+                    format!(
+                        "pipe chain starts with a call to `{callee}`; start with its first argument and pipe it into `{callee}` instead"
+                    ),
+                    start.offset(),
+                ));
+            }
+        }
+
+        self.visit(start, findings);
+        for step in steps {
+            self.visit(step, findings);
+        }
+    }
+}
+
+struct LengthComparison;
+
+impl LintRule for LengthComparison {
+    fn id(&self) -> &'static str {
+        "length_comparison"
+    }
+
+    fn description(&self) -> &'static str {
+        "Emptiness checks through `length/1`, which walks the whole list"
+    }
+
+    fn check_module(&self, module: &Module, _context: &RuleContext<'_>) -> Vec<LintFinding> {
+        let mut findings = Vec::new();
+        for root in module_roots(module) {
+            visit_all(root, &mut |expr| {
+                if let Expr::Binary {
+                    op, left, right, ..
+                } = expr
+                {
+                    let zero_on_right = matches!(
+                        op,
+                        BinaryOp::Eq
+                            | BinaryOp::NotEq
+                            | BinaryOp::StrictEq
+                            | BinaryOp::StrictBangEq
+                            | BinaryOp::Gt
+                    ) && is_length_call(left)
+                        && is_zero(right);
+                    let zero_on_left = matches!(
+                        op,
+                        BinaryOp::Eq
+                            | BinaryOp::NotEq
+                            | BinaryOp::StrictEq
+                            | BinaryOp::StrictBangEq
+                            | BinaryOp::Lt
+                    ) && is_zero(left)
+                        && is_length_call(right);
+
+                    if zero_on_right || zero_on_left {
+                        findings.push(LintFinding::new(
+                            self.id(),
+                            "comparing `length/1` with 0 walks the whole list; match on `[]` instead",
+                            expr.offset(),
+                        ));
+                    }
+                }
+            });
+        }
+        findings
+    }
+}
+
+fn is_length_call(expr: &Expr) -> bool {
+    matches!(expr, Expr::Call { callee, args, .. } if callee == "length" && args.len() == 1)
+}
+
+fn is_zero(expr: &Expr) -> bool {
+    matches!(expr, Expr::Int { value: 0, .. })
+}
+
+struct UnusedModuleAttribute;
+
+impl LintRule for UnusedModuleAttribute {
+    fn id(&self) -> &'static str {
+        "unused_module_attribute"
+    }
+
+    fn description(&self) -> &'static str {
+        "Module attributes that are set but never read"
+    }
+
+    fn check_module(&self, module: &Module, _context: &RuleContext<'_>) -> Vec<LintFinding> {
+        let mut used = HashSet::new();
+        for root in module_roots(module) {
+            visit_all(root, &mut |expr| {
+                if let Expr::Variable { name, .. } = expr {
+                    if let Some(attribute) = name.strip_prefix('@') {
+                        used.insert(attribute.to_string());
+                    }
+                }
+            });
+        }
+
+        let mut reported = HashSet::new();
+        module
+            .attributes
+            .iter()
+            .filter(|attribute| {
+                !RESERVED_ATTRIBUTES.contains(&attribute.name.as_str())
+                    && !used.contains(&attribute.name)
+                    && reported.insert(attribute.name.clone())
+            })
+            .map(|attribute| {
+                LintFinding::new(
+                    self.id(),
+                    format!(
+                        "module attribute `@{}` is set but never used",
+                        attribute.name
+                    ),
+                    attribute.offset,
+                )
+            })
+            .collect()
+    }
+}
+
+struct MissingDocs;
+
+impl LintRule for MissingDocs {
+    fn id(&self) -> &'static str {
+        "missing_docs"
+    }
+
+    fn description(&self) -> &'static str {
+        "Modules without `@moduledoc` and public functions without `@doc`"
+    }
+
+    fn check_module(&self, module: &Module, _context: &RuleContext<'_>) -> Vec<LintFinding> {
+        let mut findings = Vec::new();
+
+        if !module
+            .attributes
+            .iter()
+            .any(|attribute| attribute.name == "moduledoc")
+        {
+            findings.push(LintFinding::new(
+                self.id(),
+                format!("module `{}` is missing `@moduledoc`", module.name),
+                module.offset,
+            ));
+        }
+
+        let mut previous_offset = module.offset;
+        let mut previous_function: Option<&Function> = None;
+        for function in &module.functions {
+            let continues_clauses = previous_function.is_some_and(|previous| {
+                previous.name == function.name && previous.params.len() == function.params.len()
+            });
+            let documented = module.attributes.iter().any(|attribute| {
+                attribute.name == "doc"
+                    && attribute.offset > previous_offset
+                    && attribute.offset < function.offset
+            });
+
+            // Bodies of `test`/`setup` blocks are not part of the module's API.
+            let is_test_block = module
+                .test_blocks
+                .iter()
+                .any(|block| block.function == function.name);
+
+            if !function.is_private() && !continues_clauses && !documented && !is_test_block {
+                findings.push(LintFinding::new(
+                    self.id(),
+                    format!(
+                        "public function `{}` is missing `@doc`",
+                        function_label(function)
+                    ),
+                    function.offset,
+                ));
+            }
+
+            previous_offset = function.offset;
+            previous_function = Some(function);
+        }
+
+        findings
+    }
+}
+
+struct PredicateNaming;
+
+impl LintRule for PredicateNaming {
+    fn id(&self) -> &'static str {
+        "predicate_naming"
+    }
+
+    fn description(&self) -> &'static str {
+        "Predicate functions should end in `?` instead of starting with `is_`"
+    }
+
+    fn check_module(&self, module: &Module, _context: &RuleContext<'_>) -> Vec<LintFinding> {
+        let mut seen = HashSet::new();
+        module
+            .functions
+            .iter()
+            .filter(|function| seen.insert((function.name.as_str(), function.params.len())))
+            .filter_map(|function| {
+                let stem = function
+                    .name
+                    .strip_prefix("is_")
+                    .filter(|stem| !stem.is_empty())?;
+                let suggested = if stem.ends_with('?') {
+                    stem.to_string()
+                } else {
+                    format!("{stem}?")
+                };
+                Some(LintFinding::new(
+                    self.id(),
+                    format!(
+                        "predicate `{}` should be named `{suggested}`",
+                        function.name
+                    ),
+                    function.offset,
+                ))
+            })
+            .collect()
+    }
+}
+
+struct LargeCond;
+
+impl LintRule for LargeCond {
+    fn id(&self) -> &'static str {
+        "large_cond"
+    }
+
+    fn description(&self) -> &'static str {
+        "`cond` blocks with too many branches"
+    }
+
+    fn default_max(&self) -> Option<usize> {
+        Some(8)
+    }
+
+    fn check_module(&self, module: &Module, context: &RuleContext<'_>) -> Vec<LintFinding> {
+        let mut findings = Vec::new();
+        for root in module_roots(module) {
+            visit_all(root, &mut |expr| {
+                let Expr::Case {
+                    offset, branches, ..
+                } = expr
+                else {
+                    return;
+                };
+                // `cond` lowers to a case; only the source tells them apart.
+                let is_cond = context
+                    .source
+                    .get(*offset..)
+                    .is_some_and(|rest| rest.starts_with("cond"));
+                if is_cond && branches.len() > context.max {
+                    findings.push(LintFinding::new(
+                        self.id(),
+                        format!(
+                            "`cond` has {} branches (max {}); consider pattern matching or splitting it up",
+                            branches.len(),
+                            context.max
+                        ),
+                        *offset,
+                    ));
+                }
+            });
+        }
+        findings
+    }
+}
+
+// ---------------------------------------------------------------------------
+// AST traversal
+// ---------------------------------------------------------------------------
+
+fn function_label(function: &Function) -> String {
+    format!("{}/{}", function.name, function.params.len())
+}
+
+fn function_roots(function: &Function) -> Vec<&Expr> {
+    let mut roots: Vec<&Expr> = function
+        .params
+        .iter()
+        .filter_map(|param| param.default())
+        .collect();
+    roots.extend(function.guard());
+    roots.push(&function.body);
+    roots
+}
+
+/// Every top-level expression in a module: function parts, attribute values,
+/// struct defaults and protocol implementation bodies.
+fn module_roots(module: &Module) -> Vec<&Expr> {
+    let mut roots: Vec<&Expr> = module.functions.iter().flat_map(function_roots).collect();
+    roots.extend(module.attributes.iter().map(|attribute| &attribute.value));
+
+    for form in &module.forms {
+        match form {
+            ModuleForm::Defstruct { fields } => {
+                roots.extend(fields.iter().map(|field| &field.default));
+            }
+            ModuleForm::Defimpl { functions, .. } => {
+                for function in functions {
+                    roots.extend(function.params.iter().filter_map(|param| param.default()));
+                    roots.extend(function.guard.as_ref());
+                    roots.push(&function.body);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    roots
+}
+
+fn visit_all<'a>(expr: &'a Expr, visit: &mut impl FnMut(&'a Expr)) {
+    visit(expr);
+    for child in children(expr) {
+        visit_all(child, visit);
+    }
+}
+
+fn is_lowered_with(branches: &[CaseBranch]) -> bool {
+    branches.len() == 2
+        && matches!(branches[1].head(), Pattern::Bind { name } if name == WITH_FAILURE_BINDING)
+}
+
+fn is_lowered_with_expr(expr: &Expr) -> bool {
+    matches!(expr, Expr::Case { branches, .. } if is_lowered_with(branches))
+}
+
+fn push_branches<'a>(branches: &'a [CaseBranch], children: &mut Vec<&'a Expr>) {
+    for branch in branches {
+        children.extend(branch.guard());
+        children.push(branch.body());
+    }
+}
+
+/// Direct sub-expressions of `expr`, in source order.
+fn children(expr: &Expr) -> Vec<&Expr> {
+    let mut children = Vec::new();
+
+    match expr {
+        Expr::Int { .. }
+        | Expr::Float { .. }
+        | Expr::Bool { .. }
+        | Expr::Nil { .. }
+        | Expr::String { .. }
+        | Expr::Variable { .. }
+        | Expr::Atom { .. } => {}
+        Expr::InterpolatedString { segments, .. } => {
+            for segment in segments {
+                if let InterpolationSegment::Expr { expr } = segment {
+                    children.push(expr);
+                }
+            }
+        }
+        Expr::Tuple { items, .. } | Expr::List { items, .. } | Expr::Bitstring { items, .. } => {
+            children.extend(items);
+        }
+        Expr::Map { entries, .. } => {
+            for entry in entries {
+                children.push(entry.key());
+                children.push(entry.value());
+            }
+        }
+        Expr::Struct { entries, .. } | Expr::Keyword { entries, .. } => {
+            children.extend(entries.iter().map(|entry| &entry.value));
+        }
+        Expr::MapUpdate { base, updates, .. } | Expr::StructUpdate { base, updates, .. } => {
+            children.push(base);
+            children.extend(updates.iter().map(|entry| &entry.value));
+        }
+        Expr::Call { args, .. } => children.extend(args),
+        Expr::FieldAccess { base, .. } => children.push(base),
+        Expr::IndexAccess { base, index, .. } => {
+            children.push(base);
+            children.push(index);
+        }
+        Expr::Fn { body, .. } => children.push(body),
+        Expr::Invoke { callee, args, .. } => {
+            children.push(callee);
+            children.extend(args);
+        }
+        Expr::Question { value, .. } | Expr::Unary { value, .. } => children.push(value),
+        Expr::Group { inner, .. } => children.push(inner),
+        Expr::Binary { left, right, .. } | Expr::Pipe { left, right, .. } => {
+            children.push(left);
+            children.push(right);
+        }
+        Expr::Case {
+            subject, branches, ..
+        } => {
+            children.push(subject);
+            push_branches(branches, &mut children);
+        }
+        Expr::Try {
+            body,
+            rescue,
+            catch,
+            after,
+            ..
+        } => {
+            children.push(body);
+            push_branches(rescue, &mut children);
+            push_branches(catch, &mut children);
+            children.extend(after.as_deref());
+        }
+        Expr::Raise { error, .. } => children.push(error),
+        Expr::For {
+            generators,
+            into,
+            reduce,
+            body,
+            ..
+        } => {
+            for generator in generators {
+                children.push(generator.source());
+                children.extend(generator.guard());
+            }
+            children.extend(into.as_deref());
+            children.extend(reduce.as_deref());
+            children.push(body);
+        }
+        Expr::Block { exprs, .. } => children.extend(exprs),
+    }
+
+    children
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lexer::scan_tokens;
+    use crate::parser::parse_ast;
+
+    fn check(rule: &dyn LintRule, max: usize, source: &str) -> Vec<String> {
+        let tokens = scan_tokens(source).expect("lint fixture should tokenize");
+        let ast = parse_ast(&tokens).expect("lint fixture should parse");
+        let context = RuleContext { source, max };
+        ast.modules
+            .iter()
+            .flat_map(|module| rule.check_module(module, &context))
+            .map(|finding| finding.message)
+            .collect()
+    }
+
+    #[test]
+    fn cyclomatic_complexity_counts_branches_and_boolean_operators() {
+        let source = "defmodule Demo do\n  def run(x) do\n    case x do\n      1 -> :one\n      2 -> :two\n      _ ->\n        if x > 3 and x < 9 do\n          :mid\n        else\n          :other\n        end\n    end\n  end\nend\n";
+
+        assert_eq!(
+            check(&CyclomaticComplexity, 4, source),
+            vec!["function `run/1` has cyclomatic complexity 5 (max 4)".to_string()]
+        );
+        assert!(check(&CyclomaticComplexity, 5, source).is_empty());
+    }
+
+    #[test]
+    fn nesting_depth_treats_with_clauses_as_one_level() {
+        let with_source = "defmodule Demo do\n  def run(a) do\n    with {:ok, b} <- a,\n         {:ok, c} <- b,\n         {:ok, d} <- c do\n      d\n    end\n  end\nend\n";
+        assert!(check(&NestingDepth, 1, with_source).is_empty());
+
+        let nested_source = "defmodule Demo do\n  def run(a) do\n    if a do\n      case a do\n        _ -> fn x -> x end\n      end\n    end\n  end\nend\n";
+        assert_eq!(
+            check(&NestingDepth, 2, nested_source),
+            vec!["function `run/1` nests control flow 3 levels deep (max 2)".to_string()]
+        );
+    }
+
+    #[test]
+    fn pipe_chain_start_flags_calls_with_arguments_only() {
+        let source = "defmodule Demo do\n  def run(xs) do\n    a = Enum.map(xs, fn x -> x end) |> Enum.sum()\n    b = xs |> Enum.sum()\n    c = values() |> Enum.sum()\n    {a, b, c}\n  end\nend\n";
+
+        assert_eq!(
+            check(&PipeChainStart, 0, source),
+            vec!["pipe chain starts with a call to `Enum.map`; start with its first argument and pipe it into `Enum.map` instead".to_string()]
+        );
+    }
+
+    #[test]
+    fn length_comparison_flags_zero_checks_in_either_order() {
+        let source = "defmodule Demo do\n  def run(xs) do\n    {length(xs) == 0, 0 < length(xs), length(xs) == 2}\n  end\nend\n";
+
+        assert_eq!(check(&LengthComparison, 0, source).len(), 2);
+    }
+
+    #[test]
+    fn unused_module_attribute_ignores_read_and_reserved_attributes() {
+        let source = "defmodule Demo do\n  @moduledoc \"Demo.\"\n  @limit 3\n  @unused 4\n  def run() do\n    @limit\n  end\nend\n";
+
+        assert_eq!(
+            check(&UnusedModuleAttribute, 0, source),
+            vec!["module attribute `@unused` is set but never used".to_string()]
+        );
+    }
+
+    #[test]
+    fn missing_docs_requires_docs_on_first_public_clause_only() {
+        let source = "defmodule Demo do\n  @doc \"Documented.\"\n  def one(0) do\n    0\n  end\n\n  def one(n) do\n    n\n  end\n\n  def two() do\n    2\n  end\n\n  defp three() do\n    3\n  end\nend\n";
+
+        assert_eq!(
+            check(&MissingDocs, 0, source),
+            vec![
+                "module `Demo` is missing `@moduledoc`".to_string(),
+                "public function `two/0` is missing `@doc`".to_string(),
+            ]
+        );
+    }
+
+    #[test]
+    fn missing_docs_skips_test_and_setup_blocks() {
+        let source = "defmodule DemoTest do\n  @moduledoc \"Tests.\"\n  @tag :slow\n  setup do\n    :ok\n  end\n\n  test \"runs\" do\n    :ok\n  end\nend\n";
+
+        assert!(check(&MissingDocs, 0, source).is_empty());
+        assert!(check(&UnusedModuleAttribute, 0, source).is_empty());
+    }
+
+    #[test]
+    fn predicate_naming_suggests_question_mark_names() {
+        let source = "defmodule Demo do\n  def is_even(n) do\n    rem(n, 2) == 0\n  end\n\n  def is_odd?(n) do\n    !is_even(n)\n  end\nend\n";
+
+        assert_eq!(
+            check(&PredicateNaming, 0, source),
+            vec![
+                "predicate `is_even` should be named `even?`".to_string(),
+                "predicate `is_odd?` should be named `odd?`".to_string(),
+            ]
+        );
+    }
+
+    #[test]
+    fn large_cond_ignores_case_blocks_of_the_same_size() {
+        let source = "defmodule Demo do\n  def run(x) do\n    a = cond do\n      x == 1 -> 1\n      x == 2 -> 2\n      true -> 3\n    end\n    b = case x do\n      1 -> 1\n      2 -> 2\n      _ -> 3\n    end\n    a + b\n  end\nend\n";
+
+        assert_eq!(
+            check(&LargeCond, 2, source),
+            vec![
+                "`cond` has 3 branches (max 2); consider pattern matching or splitting it up"
+                    .to_string()
+            ]
+        );
+    }
+}
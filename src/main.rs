@@ -1,8 +1,10 @@
 mod acceptance;
 mod backend_names;
+mod bench_runner;
 mod c_backend;
 mod cache;
 mod cli_diag;
+mod dap;
 mod deps;
 mod docs;
 mod formatter;
@@ -11,6 +13,7 @@ mod interop;
 mod ir;
 mod lexer;
 mod linker;
+mod lint;
 #[cfg(feature = "lsp")]
 mod lsp;
 mod manifest;
@@ -31,9 +34,10 @@ mod test_runner;
 mod typing;
 
 use acceptance::{load_acceptance_yaml, load_feature_scenarios, BenchmarkMetrics};
+use bench_runner::{format_seconds, run_benchmarks, Baseline, BenchOptions};
 use cache::{
-    build_run_cache_key, load_cached_ir, should_trace_cache_status, store_cached_ir,
-    trace_cache_status,
+    build_run_cache_key, load_cached_ir, record_cache_status, should_trace_cache_status,
+    store_cached_ir, trace_cache_status,
 };
 use cli_diag::{CliDiagnostic, EXIT_FAILURE, EXIT_OK};
 use observability::{ErrorSource, ObservabilityError, ObservabilityRun};
@@ -124,15 +128,20 @@ impl CompileError {
         }
     }
 }
-use formatter::{format_path, FormatMode};
+use formatter::{
+    format_path, format_text, load_format_options, unified_diff, FormatMode, LineRange,
+};
 use ir::{lower_ast_to_ir, IrProgram};
 use lexer::scan_tokens;
-use manifest::load_run_source;
+use manifest::{load_program_source, load_run_source};
 use mir::{lower_ir_to_mir, optimize_for_native_backend};
 use parser::parse_ast;
 use resolver::resolve_ast;
 use runtime::{evaluate_entrypoint, RuntimeValue};
-use test_runner::{TestOutputFormat, TestRunnerError};
+use test_runner::{
+    load_failed_tests, record_failed_tests, CoverageFormat, CoverageOptions, TagFilter,
+    TestBackend, TestOutputFormat, TestRunnerError, TestSelection, FAILED_MANIFEST_PATH,
+};
 use typing::infer_types;
 
 fn command_argv(command: &str, args: &[String]) -> Vec<String> {
@@ -296,10 +305,12 @@ const BENCHMARK_THRESHOLD_COLD_START_P50_MS: u64 = 50;
 const BENCHMARK_THRESHOLD_WARM_START_P50_MS: u64 = 10;
 const BENCHMARK_THRESHOLD_IDLE_RSS_MB: u64 = 30;
 
+/// Stack size for every thread that evaluates Tonic code. Rust's default
+/// stack overflows on idiomatic recursive code (e.g. brainfuck_interpreter).
+const STACK_SIZE: usize = 64 * 1024 * 1024;
+
 fn main() {
     // Spawn on a thread with 64MB stack to support deeply recursive Tonic programs.
-    // Rust's default 8MB stack overflows on idiomatic recursive code (e.g. brainfuck_interpreter).
-    const STACK_SIZE: usize = 64 * 1024 * 1024;
     let builder = std::thread::Builder::new().stack_size(STACK_SIZE);
     let handler = builder
         .spawn(|| run(std::env::args().skip(1).collect()))
@@ -315,20 +326,27 @@ fn run(args: Vec<String>) -> i32 {
             print_help();
             EXIT_OK
         }
+        Some("new") => handle_new(iter.collect()),
         Some("run") => handle_run(iter.collect()),
         Some("check") => handle_check(iter.collect()),
         Some("test") => handle_test(iter.collect()),
+        Some("bench") => handle_bench(iter.collect()),
+        Some("task") => handle_task(iter.collect()),
         Some("fmt") => handle_fmt(iter.collect()),
+        Some("lint") => lint::handle_lint(iter.collect()),
         Some("compile") => handle_compile(iter.collect()),
-        Some("cache") => run_placeholder("cache"),
+        Some("cache") => handle_cache(iter.collect()),
         Some("repl") => repl::handle_repl(iter.collect()),
         Some("verify") => handle_verify(iter.collect()),
         Some("deps") => handle_deps(iter.collect()),
         Some("publish") => handle_publish(iter.collect()),
+        Some("yank") => handle_yank(iter.collect()),
+        Some("owner") => handle_owner(iter.collect()),
         Some("install") => handle_install(iter.collect()),
         Some("uninstall") => handle_uninstall(iter.collect()),
         Some("installed") => handle_installed(iter.collect()),
         Some("docs") => docs::handle_docs(iter.collect()),
+        Some("dap") => dap::handle_dap(iter.collect()),
         #[cfg(feature = "lsp")]
         Some("lsp") => {
             lsp::run_lsp_server();
@@ -342,6 +360,10 @@ fn run(args: Vec<String>) -> i32 {
     }
 }
 
+#[path = "cmd_new.rs"]
+mod cmd_new;
+use cmd_new::*;
+
 #[path = "cmd_run.rs"]
 mod cmd_run;
 use cmd_run::*;
@@ -354,10 +376,22 @@ use cmd_check::*;
 mod cmd_test;
 use cmd_test::*;
 
+#[path = "cmd_bench.rs"]
+mod cmd_bench;
+use cmd_bench::*;
+
+#[path = "cmd_task.rs"]
+mod cmd_task;
+use cmd_task::*;
+
 #[path = "cmd_compile.rs"]
 mod cmd_compile;
 use cmd_compile::*;
 
+#[path = "cmd_cache.rs"]
+mod cmd_cache;
+use cmd_cache::*;
+
 #[path = "cmd_verify.rs"]
 mod cmd_verify;
 use cmd_verify::*;
@@ -366,6 +400,18 @@ use cmd_verify::*;
 mod cmd_deps;
 use cmd_deps::*;
 
+#[path = "cmd_deps_graph.rs"]
+mod cmd_deps_graph;
+use cmd_deps_graph::*;
+
+#[path = "cmd_workspace.rs"]
+mod cmd_workspace;
+use cmd_workspace::*;
+
+#[path = "cmd_publish.rs"]
+mod cmd_publish;
+use cmd_publish::*;
+
 #[path = "cmd_install.rs"]
 mod cmd_install;
 use cmd_install::*;
@@ -2,20 +2,72 @@ use crate::deps::Lockfile;
 use crate::lexer::scan_tokens;
 use crate::parser::{parse_ast, Ast, Expr};
 use crate::stdlib_catalog::{stdlib_module_names, STDLIB_SOURCES};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
 use std::path::{Path, PathBuf};
 
 #[path = "manifest_parse.rs"]
 mod parse;
-pub(crate) use parse::load_project_manifest;
 use parse::*;
+pub(crate) use parse::{collect_tonic_source_paths, load_project_manifest};
+
+#[path = "manifest_workspace.rs"]
+mod workspace;
+pub(crate) use workspace::{Workspace, WorkspaceMember};
+
+#[path = "manifest_tasks.rs"]
+mod tasks;
+pub(crate) use tasks::{dependency_roots, discover_tasks, load_task_source, ProjectTask};
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub(crate) struct ProjectManifest {
-    pub(crate) entry: PathBuf,
+    /// `project.name`, when given.
+    pub(crate) name: Option<String>,
+    /// `project.entry`; optional when the package declares `[[bin]]`
+    /// targets or a `[lib]`.
+    pub(crate) entry: Option<PathBuf>,
     pub(crate) dependencies: Dependencies,
     /// Optional package metadata for registry publishing.
     pub(crate) package: Option<PackageMetadata>,
+    pub(crate) bins: Vec<BinTarget>,
+    pub(crate) lib: Option<LibTarget>,
+    /// `[tasks]`: task name to the source file defining it, relative to the
+    /// project root.
+    pub(crate) tasks: BTreeMap<String, PathBuf>,
+}
+
+/// A `[[bin]]` table: one program the package ships.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct BinTarget {
+    pub(crate) name: String,
+    /// Source file of the program, relative to the project root.
+    pub(crate) path: PathBuf,
+    /// Module whose `run/0` starts the program; `Demo` when unset.
+    pub(crate) main_module: Option<String>,
+}
+
+/// `[lib]`: the modules dependents load and the package's own entry and
+/// binaries build on.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct LibTarget {
+    /// Directory of library modules, relative to the project root.
+    pub(crate) path: PathBuf,
+}
+
+impl ProjectManifest {
+    /// `[package] name`, then `project.name`, then the directory name.
+    pub(crate) fn display_name(&self, project_root: &Path) -> String {
+        self.package
+            .as_ref()
+            .and_then(|package| package.name.clone())
+            .or_else(|| self.name.clone())
+            .or_else(|| {
+                project_root.canonicalize().ok().and_then(|root| {
+                    root.file_name()
+                        .map(|name| name.to_string_lossy().to_string())
+                })
+            })
+            .unwrap_or_else(|| "project".to_string())
+    }
 }
 
 /// Package metadata for registry publishing.
@@ -30,6 +82,8 @@ pub(crate) struct PackageMetadata {
     pub(crate) authors: Vec<String>,
     pub(crate) repository: Option<String>,
     pub(crate) keywords: Vec<String>,
+    /// Paths or globs left out of the published package tarball.
+    pub(crate) exclude: Vec<String>,
 }
 
 #[derive(Debug, Clone, Default, PartialEq, Eq)]
@@ -37,6 +91,9 @@ pub(crate) struct Dependencies {
     pub(crate) path: HashMap<String, PathBuf>,
     pub(crate) git: HashMap<String, GitDep>,
     pub(crate) registry: HashMap<String, RegistryDep>,
+    /// `[registries] default`; registry deps without their own `registry`
+    /// resolve against it.
+    pub(crate) default_registry: Option<String>,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -50,18 +107,51 @@ pub(crate) struct GitDep {
 pub(crate) struct RegistryDep {
     /// Semver version requirement, e.g. "~> 1.0" or "^2.1".
     pub(crate) version: String,
-    /// Override registry URL; uses `[registries.default]` when `None`.
+    /// Override registry URL; uses `[registries] default` when `None`.
     pub(crate) registry: Option<String>,
 }
 
-// Registry URL will be configurable via [registries.default] in tonic.toml
-// when registry support is implemented.
+/// Walk up from `path` (its directory when `path` is a file) and return the
+/// first config `load` finds. Used for `[format]` and `[lint]` settings.
+pub(crate) fn find_nearest_config<T>(
+    path: &Path,
+    mut load: impl FnMut(&Path) -> Result<Option<T>, String>,
+) -> Result<Option<T>, String> {
+    let start = if path.is_dir() {
+        path.to_path_buf()
+    } else {
+        path.parent()
+            .filter(|parent| !parent.as_os_str().is_empty())
+            .map(Path::to_path_buf)
+            .unwrap_or_else(|| PathBuf::from("."))
+    };
+    let start = start.canonicalize().unwrap_or(start);
+
+    for directory in start.ancestors() {
+        if let Some(config) = load(directory)? {
+            return Ok(Some(config));
+        }
+    }
+    Ok(None)
+}
+
+/// The nearest ancestor of `path` containing a `tonic.toml`.
+pub(crate) fn find_project_root(path: &Path) -> Option<PathBuf> {
+    find_nearest_config(path, |directory| {
+        Ok(directory
+            .join("tonic.toml")
+            .is_file()
+            .then(|| directory.to_path_buf()))
+    })
+    .ok()
+    .flatten()
+}
 
 pub(crate) fn load_run_source(requested_path: &str) -> Result<String, String> {
     let path = Path::new(requested_path);
 
     if path.is_dir() {
-        return load_run_source_from_project_root(path);
+        return load_run_source_from_project_root(path, None, false);
     }
 
     let mut source = std::fs::read_to_string(path)
@@ -72,32 +162,109 @@ pub(crate) fn load_run_source(requested_path: &str) -> Result<String, String> {
     Ok(source)
 }
 
-fn load_run_source_from_project_root(project_root: &Path) -> Result<String, String> {
+/// Like [`load_run_source`] for `tonic run` and `tonic compile`: `bin`
+/// selects a `[[bin]]` target, and a library-only project is an error
+/// rather than its modules without an entry.
+pub(crate) fn load_program_source(
+    requested_path: &str,
+    bin: Option<&str>,
+) -> Result<String, String> {
+    let path = Path::new(requested_path);
+
+    match bin {
+        Some(_) if !path.is_dir() => Err(format!(
+            "--bin needs a project directory, but '{requested_path}' is not one"
+        )),
+        _ if path.is_dir() => load_run_source_from_project_root(path, bin, true),
+        _ => load_run_source(requested_path),
+    }
+}
+
+/// The source `tonic run <project_root>` loads, with the path of the file it
+/// starts from. That file's contents open the source.
+pub(crate) fn load_project_program(project_root: &Path) -> Result<(PathBuf, String), String> {
+    let source = load_run_source_from_project_root(project_root, None, true)?;
     let manifest = load_project_manifest(project_root)?;
-    let entry_path = project_root.join(&manifest.entry);
+    let entry_path = match manifest.run_target(None)? {
+        RunTarget::Entry(entry) => entry,
+        RunTarget::Bin(bin) => bin.path.as_path(),
+        RunTarget::Library => unreachable!("library packages are rejected above"),
+    };
+    Ok((project_root.join(entry_path), source))
+}
 
-    if !entry_path.exists() {
-        return Err(format!(
-            "project entry path '{}' does not exist",
-            manifest.entry.display()
-        ));
+/// What a project runs: its `project.entry`, one of its binaries, or
+/// nothing for a library-only package.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum RunTarget<'a> {
+    Entry(&'a Path),
+    Bin(&'a BinTarget),
+    Library,
+}
+
+impl ProjectManifest {
+    /// The binary named `bin`, or the default target: `project.entry`, else
+    /// the only `[[bin]]`.
+    pub(crate) fn run_target(&self, bin: Option<&str>) -> Result<RunTarget<'_>, String> {
+        let names = || {
+            self.bins
+                .iter()
+                .map(|bin| bin.name.as_str())
+                .collect::<Vec<_>>()
+                .join(", ")
+        };
+
+        if let Some(name) = bin {
+            return match self.bins.iter().find(|bin| bin.name == name) {
+                Some(bin) => Ok(RunTarget::Bin(bin)),
+                None if self.bins.is_empty() => Err(format!(
+                    "no binary named '{name}': tonic.toml declares no [[bin]] targets"
+                )),
+                None => Err(format!(
+                    "no binary named '{name}' in tonic.toml (binaries: {})",
+                    names()
+                )),
+            };
+        }
+
+        match (&self.entry, self.bins.as_slice()) {
+            (Some(entry), _) => Ok(RunTarget::Entry(entry)),
+            (None, [bin]) => Ok(RunTarget::Bin(bin)),
+            (None, []) => Ok(RunTarget::Library),
+            (None, _) => Err(format!(
+                "tonic.toml declares several binaries ({}); select one with --bin <name>",
+                names()
+            )),
+        }
     }
-    if !entry_path.is_file() {
+}
+
+fn load_run_source_from_project_root(
+    project_root: &Path,
+    bin: Option<&str>,
+    require_program: bool,
+) -> Result<String, String> {
+    let manifest = load_project_manifest(project_root)?;
+    let target = manifest.run_target(bin)?;
+    if require_program && target == RunTarget::Library {
         return Err(format!(
-            "project entry path '{}' is not a file",
-            manifest.entry.display()
+            "'{}' is a library package: tonic.toml has no project.entry or [[bin]] to run",
+            manifest.display_name(project_root)
         ));
     }
 
-    let mut project_sources = vec![read_source_file(&entry_path)?];
-
-    for module_path in collect_project_module_paths(project_root, &entry_path)? {
-        project_sources.push(read_source_file(&module_path)?);
+    let mut project_sources = Vec::new();
+    for source_path in target_source_paths(project_root, &manifest, target)? {
+        project_sources.push(read_source_file(&source_path)?);
+    }
+    if let RunTarget::Bin(bin) = target {
+        if let Some(main_module) = bin_main_module_shim(bin, &project_sources.join("\n\n"))? {
+            project_sources.push(main_module);
+        }
     }
 
     // FIX: Load dependency sources into the runtime
-    let dependency_sources = load_dependency_sources(project_root, &manifest.dependencies)?;
-    project_sources.extend(dependency_sources);
+    project_sources.extend(project_dependency_sources(project_root, &manifest)?);
 
     let mut source = project_sources.join("\n\n");
 
@@ -113,24 +280,185 @@ fn load_run_source_from_project_root(project_root: &Path) -> Result<String, Stri
     Ok(source)
 }
 
-/// Load source files from all dependencies (path and git)
-fn load_dependency_sources(
+/// The target's own file first, then the modules it builds on: the `[lib]`
+/// directory when there is one, otherwise the modules next to the file.
+/// Other binaries and the project entry are never loaded alongside.
+fn target_source_paths(
     project_root: &Path,
-    manifest_dependencies: &Dependencies,
-) -> Result<Vec<String>, String> {
-    // Registry deps are not yet resolvable — surface an actionable error.
-    if let Some(name) = manifest_dependencies.registry.keys().next() {
+    manifest: &ProjectManifest,
+    target: RunTarget<'_>,
+) -> Result<Vec<PathBuf>, String> {
+    let (label, relative_path) = match target {
+        RunTarget::Entry(entry) => ("project entry", entry),
+        RunTarget::Bin(bin) => ("binary", bin.path.as_path()),
+        RunTarget::Library => return library_source_paths(project_root, manifest),
+    };
+    let entry_path = project_root.join(relative_path);
+
+    if !entry_path.exists() {
+        return Err(format!(
+            "{label} path '{}' does not exist",
+            relative_path.display()
+        ));
+    }
+    if !entry_path.is_file() {
+        return Err(format!(
+            "{label} path '{}' is not a file",
+            relative_path.display()
+        ));
+    }
+
+    let mut source_paths = match &manifest.lib {
+        Some(_) => library_source_paths(project_root, manifest)?,
+        None => collect_project_module_paths(project_root, &entry_path)?,
+    };
+    let other_targets = target_paths(project_root, manifest);
+    source_paths.retain(|path| *path != entry_path && !other_targets.contains(path));
+    source_paths.insert(0, entry_path);
+    Ok(source_paths)
+}
+
+/// Sources a test file's doctests may target: the project's library
+/// modules with their paths, and the dependency sources those build on.
+pub(crate) struct LibrarySources {
+    pub(crate) files: Vec<(PathBuf, String)>,
+    pub(crate) dependencies: Vec<String>,
+}
+
+/// Load the modules the project at `project_root` provides to its
+/// dependents. `*_test.tn` files are left out.
+pub(crate) fn load_library_sources(project_root: &Path) -> Result<LibrarySources, String> {
+    let manifest = load_project_manifest(project_root)?;
+    let mut files = Vec::new();
+    for source_path in library_source_paths(project_root, &manifest)? {
+        let is_test = source_path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .is_some_and(|name| name.ends_with("_test.tn"));
+        if !is_test {
+            let source = read_source_file(&source_path)?;
+            files.push((source_path, source));
+        }
+    }
+
+    Ok(LibrarySources {
+        files,
+        dependencies: project_dependency_sources(project_root, &manifest)?,
+    })
+}
+
+/// Modules a project provides to its dependents: the `[lib]` directory,
+/// else the entry and the modules next to it, else every source file that
+/// is not a binary.
+fn library_source_paths(
+    project_root: &Path,
+    manifest: &ProjectManifest,
+) -> Result<Vec<PathBuf>, String> {
+    if let Some(lib) = &manifest.lib {
+        let lib_root = project_root.join(&lib.path);
+        if !lib_root.is_dir() {
+            return Err(format!(
+                "library path '{}' is not a directory",
+                lib.path.display()
+            ));
+        }
+        let other_targets = target_paths(project_root, manifest);
+        let mut source_paths = collect_tonic_source_paths(&lib_root)?;
+        source_paths.retain(|path| !other_targets.contains(path));
+        return Ok(source_paths);
+    }
+
+    if let Some(entry) = &manifest.entry {
+        return target_source_paths(project_root, manifest, RunTarget::Entry(entry));
+    }
+
+    let bin_paths = target_paths(project_root, manifest);
+    let mut source_paths = collect_tonic_source_paths(project_root)?;
+    source_paths.retain(|path| !bin_paths.contains(path));
+    Ok(source_paths)
+}
+
+/// Source files of `project.entry` and every `[[bin]]`.
+fn target_paths(project_root: &Path, manifest: &ProjectManifest) -> Vec<PathBuf> {
+    manifest
+        .entry
+        .iter()
+        .chain(manifest.bins.iter().map(|bin| &bin.path))
+        .map(|path| project_root.join(path))
+        .collect()
+}
+
+/// Files a dependency contributes: its `[lib]` modules when its tonic.toml
+/// declares one, otherwise every source file under it.
+fn dependency_source_paths(dep_root: &Path) -> Result<Vec<PathBuf>, String> {
+    match load_project_manifest(dep_root) {
+        Ok(manifest) if manifest.lib.is_some() => library_source_paths(dep_root, &manifest),
+        _ => collect_tonic_source_paths(dep_root),
+    }
+}
+
+/// `tonic run` always starts `Demo.run/0`; a binary with another
+/// `main_module` gets a generated `Demo` that delegates to it.
+fn bin_main_module_shim(bin: &BinTarget, project_source: &str) -> Result<Option<String>, String> {
+    let Some(main_module) = bin
+        .main_module
+        .as_deref()
+        .filter(|module| *module != "Demo")
+    else {
+        return Ok(None);
+    };
+    let shim = format!("defmodule Demo do\n  def run() do\n    {main_module}.run()\n  end\nend\n");
+
+    // Parse errors are reported when the program itself is compiled.
+    if parse_project_ast(project_source).is_none() {
+        return Ok(Some(shim));
+    }
+    let analysis = analyze_project_source(project_source)?;
+    if !analysis.module_names.iter().any(|name| name == main_module) {
         return Err(format!(
-            "registry dependency '{}': registry dependencies are not yet supported; use git or path deps",
-            name
+            "binary '{}' sets main_module = \"{main_module}\" but no module {main_module} is defined in its sources",
+            bin.name
         ));
     }
+    if analysis.module_names.iter().any(|name| name == "Demo") {
+        return Err(format!(
+            "binary '{}' sets main_module = \"{main_module}\" but its sources also define Demo; rename Demo or drop main_module",
+            bin.name
+        ));
+    }
+
+    Ok(Some(shim))
+}
+
+/// Dependency sources for the project at `project_root`, through the
+/// workspace lockfile when it is a workspace member.
+fn project_dependency_sources(
+    project_root: &Path,
+    manifest: &ProjectManifest,
+) -> Result<Vec<String>, String> {
+    let workspace = Workspace::containing(project_root)?;
+    match workspace
+        .as_ref()
+        .and_then(|workspace| Some((workspace, workspace.member_at(project_root)?)))
+    {
+        Some((workspace, member)) => load_member_dependency_sources(workspace, member),
+        None => load_dependency_sources(project_root, &manifest.dependencies),
+    }
+}
 
+/// Load source files from all dependencies (path and git)
+fn load_dependency_sources(
+    project_root: &Path,
+    manifest_dependencies: &Dependencies,
+) -> Result<Vec<String>, String> {
     let mut dependency_sources = Vec::new();
 
     let lockfile = match Lockfile::load(project_root)? {
         Some(lockfile) => lockfile,
-        None if !manifest_dependencies.path.is_empty() || !manifest_dependencies.git.is_empty() => {
+        None if !manifest_dependencies.path.is_empty()
+            || !manifest_dependencies.git.is_empty()
+            || !manifest_dependencies.registry.is_empty() =>
+        {
             return Err(
                 "dependencies declared in tonic.toml but tonic.lock is missing; run `tonic deps lock` or `tonic deps sync`"
                     .to_string(),
@@ -151,7 +479,7 @@ fn load_dependency_sources(
             ));
         }
 
-        for source_path in collect_tonic_source_paths(dep_path)? {
+        for source_path in dependency_source_paths(dep_path)? {
             if should_trace_module_loads() {
                 trace_module_load("dep:path", &source_path.to_string_lossy());
             }
@@ -170,7 +498,7 @@ fn load_dependency_sources(
             ));
         }
 
-        for source_path in collect_tonic_source_paths(&dep_path)? {
+        for source_path in dependency_source_paths(&dep_path)? {
             if should_trace_module_loads() {
                 trace_module_load("dep:git", &source_path.to_string_lossy());
             }
@@ -178,6 +506,186 @@ fn load_dependency_sources(
         }
     }
 
+    let mut unlocked_registry_deps = manifest_dependencies
+        .registry
+        .keys()
+        .filter(|name| !lockfile.registry_deps.contains_key(*name))
+        .collect::<Vec<_>>();
+    unlocked_registry_deps.sort();
+    if let Some(name) = unlocked_registry_deps.first() {
+        return Err(format!(
+            "registry dependency '{name}' is missing from tonic.lock; run `tonic deps sync`"
+        ));
+    }
+
+    // Load registry dependencies (direct and transitive) from cache
+    for (name, registry_dep) in &lockfile.registry_deps {
+        let dep_path = deps_dir.join(registry_dep.cache_dir_name(name));
+        if !dep_path.exists() {
+            return Err(format!(
+                "cached registry dependency '{}' {} not found at {}; run `tonic deps sync`",
+                name,
+                registry_dep.version,
+                dep_path.display()
+            ));
+        }
+
+        for source_path in dependency_source_paths(&dep_path)? {
+            if should_trace_module_loads() {
+                trace_module_load("dep:registry", &source_path.to_string_lossy());
+            }
+            dependency_sources.push(read_source_file(&source_path)?);
+        }
+    }
+
+    Ok(dependency_sources)
+}
+
+/// Dependency sources for a workspace member. The shared `tonic.lock` at the
+/// workspace root locks every member's dependencies, so only those `member`
+/// reaches are loaded. A path dependency on another member loads that
+/// member's project modules and follows its own dependencies; anything
+/// reached more than once is loaded once.
+fn load_member_dependency_sources(
+    workspace: &Workspace,
+    member: &WorkspaceMember,
+) -> Result<Vec<String>, String> {
+    let mut dependency_sources = Vec::new();
+    let lockfile = Lockfile::load(&workspace.root)?;
+    let deps_dir = Lockfile::deps_dir(&workspace.root);
+
+    let mut visited_members = vec![member.root.clone()];
+    let mut pending_members = vec![member];
+    let mut loaded_paths = HashSet::new();
+    let mut loaded_git = HashSet::new();
+    let mut pending_registry = Vec::new();
+
+    while let Some(current) = pending_members.pop() {
+        let dependencies = &current.manifest.dependencies;
+        let Some(lockfile) = lockfile.as_ref() else {
+            if dependencies.path.is_empty()
+                && dependencies.git.is_empty()
+                && dependencies.registry.is_empty()
+            {
+                continue;
+            }
+            return Err(format!(
+                "dependencies declared in {}/tonic.toml but the workspace tonic.lock is missing; run `tonic deps lock` or `tonic deps sync`",
+                workspace.relative_path(current)
+            ));
+        };
+
+        let mut path_names = dependencies.path.keys().collect::<Vec<_>>();
+        path_names.sort();
+        for name in path_names {
+            let Some(path_dep) = lockfile.path_deps.get(name) else {
+                return Err(format!(
+                    "path dependency '{name}' is missing from tonic.lock; run `tonic deps lock`"
+                ));
+            };
+            let dep_path = PathBuf::from(&path_dep.path);
+            if !dep_path.exists() {
+                return Err(format!(
+                    "locked path dependency '{}' not found at {}; run `tonic deps lock`",
+                    name, path_dep.path
+                ));
+            }
+
+            if let Some(dep_member) = workspace.member_at(&dep_path) {
+                if !visited_members.contains(&dep_member.root) {
+                    visited_members.push(dep_member.root.clone());
+                    for source_path in library_source_paths(&dep_member.root, &dep_member.manifest)?
+                    {
+                        if should_trace_module_loads() {
+                            trace_module_load("dep:member", &source_path.to_string_lossy());
+                        }
+                        dependency_sources.push(read_source_file(&source_path)?);
+                    }
+                    pending_members.push(dep_member);
+                }
+                continue;
+            }
+
+            if loaded_paths.insert(dep_path.clone()) {
+                for source_path in dependency_source_paths(&dep_path)? {
+                    if should_trace_module_loads() {
+                        trace_module_load("dep:path", &source_path.to_string_lossy());
+                    }
+                    dependency_sources.push(read_source_file(&source_path)?);
+                }
+            }
+        }
+
+        let mut git_names = dependencies.git.keys().collect::<Vec<_>>();
+        git_names.sort();
+        for name in git_names {
+            if !lockfile.git_deps.contains_key(name) {
+                return Err(format!(
+                    "git dependency '{name}' is missing from tonic.lock; run `tonic deps sync`"
+                ));
+            }
+            if !loaded_git.insert(name.clone()) {
+                continue;
+            }
+            let dep_path = deps_dir.join(name);
+            if !dep_path.exists() {
+                return Err(format!(
+                    "cached git dependency '{}' not found at {}; run `tonic deps sync`",
+                    name,
+                    dep_path.display()
+                ));
+            }
+            for source_path in dependency_source_paths(&dep_path)? {
+                if should_trace_module_loads() {
+                    trace_module_load("dep:git", &source_path.to_string_lossy());
+                }
+                dependency_sources.push(read_source_file(&source_path)?);
+            }
+        }
+
+        pending_registry.extend(dependencies.registry.keys().cloned());
+    }
+
+    // Registry packages last, direct and transitive, as for single projects.
+    let mut registry_names = BTreeSet::new();
+    while let Some(name) = pending_registry.pop() {
+        if !registry_names.insert(name.clone()) {
+            continue;
+        }
+        let Some(registry_dep) = lockfile
+            .as_ref()
+            .and_then(|lockfile| lockfile.registry_deps.get(&name))
+        else {
+            return Err(format!(
+                "registry dependency '{name}' is missing from tonic.lock; run `tonic deps sync`"
+            ));
+        };
+        pending_registry.extend(registry_dep.dependencies.keys().cloned());
+    }
+    for name in registry_names {
+        let Some(registry_dep) = lockfile
+            .as_ref()
+            .and_then(|lockfile| lockfile.registry_deps.get(&name))
+        else {
+            continue;
+        };
+        let dep_path = deps_dir.join(registry_dep.cache_dir_name(&name));
+        if !dep_path.exists() {
+            return Err(format!(
+                "cached registry dependency '{}' {} not found at {}; run `tonic deps sync`",
+                name,
+                registry_dep.version,
+                dep_path.display()
+            ));
+        }
+        for source_path in dependency_source_paths(&dep_path)? {
+            if should_trace_module_loads() {
+                trace_module_load("dep:registry", &source_path.to_string_lossy());
+            }
+            dependency_sources.push(read_source_file(&source_path)?);
+        }
+    }
+
     Ok(dependency_sources)
 }
 
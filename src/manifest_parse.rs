@@ -1,4 +1,7 @@
-use super::{Dependencies, GitDep, PackageMetadata, ProjectManifest, RegistryDep};
+use super::{
+    BinTarget, Dependencies, GitDep, LibTarget, PackageMetadata, ProjectManifest, RegistryDep,
+};
+use std::collections::BTreeMap;
 use std::path::{Path, PathBuf};
 
 pub(crate) fn load_project_manifest(project_root: &Path) -> Result<ProjectManifest, String> {
@@ -18,23 +21,52 @@ pub(super) fn parse_manifest(source: &str, project_root: &Path) -> Result<Projec
     let value: toml::Value =
         toml::from_str(source).map_err(|error| format!("invalid tonic.toml: {}", error))?;
 
+    if value.get("project").is_none() && value.get("workspace").is_some() {
+        return Err(format!(
+            "tonic.toml at {} is a workspace root without a [project]; pass a member directory or select one with -p <name>",
+            project_root.display()
+        ));
+    }
+
+    let bins = value
+        .get("bin")
+        .map(parse_bin_targets)
+        .transpose()?
+        .unwrap_or_default();
+    let lib = value.get("lib").map(parse_lib_target).transpose()?;
+    let tasks = value
+        .get("tasks")
+        .map(parse_task_paths)
+        .transpose()?
+        .unwrap_or_default();
+
     let entry = value.get("project").and_then(|p| p.get("entry"));
 
-    let entry_str = match entry {
+    let entry = match entry {
+        // Packages with binaries or a library need no default entry.
+        None if !bins.is_empty() || lib.is_some() => None,
         None => return Err("invalid tonic.toml: missing required key project.entry".to_string()),
         Some(toml::Value::String(s)) => {
             if s.trim().is_empty() {
                 return Err("invalid tonic.toml: project.entry cannot be empty".to_string());
             }
-            s.trim().to_string()
+            Some(PathBuf::from(s.trim()))
         }
         Some(_) => return Err("invalid tonic.toml: project.entry must be a string".to_string()),
     };
 
-    let dependencies = value
+    let name = match value.get("project") {
+        Some(toml::Value::Table(project)) => {
+            extract_optional_string(project, "name", "project.name")?
+        }
+        _ => None,
+    };
+
+    let mut dependencies = value
         .get("dependencies")
         .map(|deps| parse_dependencies_from_value(deps, project_root))
         .unwrap_or_else(|| Ok(Dependencies::default()))?;
+    dependencies.default_registry = parse_default_registry(value.get("registries"))?;
 
     let package = value
         .get("package")
@@ -42,12 +74,125 @@ pub(super) fn parse_manifest(source: &str, project_root: &Path) -> Result<Projec
         .transpose()?;
 
     Ok(ProjectManifest {
-        entry: PathBuf::from(entry_str),
+        name,
+        entry,
         dependencies,
         package,
+        bins,
+        lib,
+        tasks,
+    })
+}
+
+pub(super) fn parse_bin_targets(value: &toml::Value) -> Result<Vec<BinTarget>, String> {
+    let toml::Value::Array(tables) = value else {
+        return Err("invalid tonic.toml: [[bin]] must be an array of tables".to_string());
+    };
+
+    let mut bins: Vec<BinTarget> = Vec::new();
+    for (index, table) in tables.iter().enumerate() {
+        let toml::Value::Table(table) = table else {
+            return Err(format!("invalid tonic.toml: bin[{index}] must be a table"));
+        };
+        let required = |key: &str| match extract_optional_string(
+            table,
+            key,
+            &format!("bin[{index}].{key}"),
+        )? {
+            Some(value) if !value.trim().is_empty() => Ok(value.trim().to_string()),
+            Some(_) => Err(format!(
+                "invalid tonic.toml: bin[{index}].{key} cannot be empty"
+            )),
+            None => Err(format!(
+                "invalid tonic.toml: missing required key bin[{index}].{key}"
+            )),
+        };
+        let name = required("name")?;
+        let path = required("path")?;
+        let main_module =
+            extract_optional_string(table, "main_module", &format!("bin[{index}].main_module"))?;
+
+        if name.contains(['/', '\\']) || name.starts_with('.') {
+            return Err(format!(
+                "invalid tonic.toml: bin[{index}].name '{name}' must be a plain file name"
+            ));
+        }
+        if bins.iter().any(|bin| bin.name == name) {
+            return Err(format!(
+                "invalid tonic.toml: binary '{name}' is declared more than once"
+            ));
+        }
+        if let Some(module) = &main_module {
+            if !module.starts_with(|c: char| c.is_ascii_uppercase()) {
+                return Err(format!(
+                    "invalid tonic.toml: bin[{index}].main_module '{module}' must be a module name like `Cli`"
+                ));
+            }
+        }
+
+        bins.push(BinTarget {
+            name,
+            path: PathBuf::from(path),
+            main_module,
+        });
+    }
+
+    Ok(bins)
+}
+
+pub(super) fn parse_task_paths(value: &toml::Value) -> Result<BTreeMap<String, PathBuf>, String> {
+    let toml::Value::Table(table) = value else {
+        return Err("invalid tonic.toml: [tasks] must be a table".to_string());
+    };
+
+    let mut tasks = BTreeMap::new();
+    for (name, path) in table {
+        let valid_name = name.starts_with(|c: char| c.is_ascii_lowercase())
+            && name.chars().all(|c| {
+                c.is_ascii_lowercase() || c.is_ascii_digit() || matches!(c, '_' | '.' | '-')
+            });
+        if !valid_name {
+            return Err(format!(
+                "invalid tonic.toml: task name '{name}' must be lowercase, like `db.seed`"
+            ));
+        }
+        match path {
+            toml::Value::String(path) if !path.trim().is_empty() => {
+                tasks.insert(name.clone(), PathBuf::from(path.trim()));
+            }
+            _ => {
+                return Err(format!(
+                    "invalid tonic.toml: tasks.{name} must be the path of a source file"
+                ))
+            }
+        }
+    }
+
+    Ok(tasks)
+}
+
+pub(super) fn parse_lib_target(value: &toml::Value) -> Result<LibTarget, String> {
+    let toml::Value::Table(table) = value else {
+        return Err("invalid tonic.toml: [lib] must be a table".to_string());
+    };
+    let path = extract_optional_string(table, "path", "lib.path")?.unwrap_or_else(|| "src".into());
+    Ok(LibTarget {
+        path: PathBuf::from(path.trim()),
     })
 }
 
+pub(super) fn parse_default_registry(
+    value: Option<&toml::Value>,
+) -> Result<Option<String>, String> {
+    match value {
+        None => Ok(None),
+        Some(toml::Value::Table(table)) => {
+            extract_optional_string(table, "default", "registries.default")
+        }
+        Some(_) => Err("invalid tonic.toml: [registries] must be a table".to_string()),
+    }
+}
+
 pub(super) fn parse_package_metadata(value: &toml::Value) -> Result<PackageMetadata, String> {
     let table = match value {
         toml::Value::Table(t) => t,
@@ -62,6 +207,7 @@ pub(super) fn parse_package_metadata(value: &toml::Value) -> Result<PackageMetad
 
     let authors = extract_string_array(table, "authors", "package.authors")?;
     let keywords = extract_string_array(table, "keywords", "package.keywords")?;
+    let exclude = extract_string_array(table, "exclude", "package.exclude")?;
 
     Ok(PackageMetadata {
         name,
@@ -71,6 +217,7 @@ pub(super) fn parse_package_metadata(value: &toml::Value) -> Result<PackageMetad
         authors,
         repository,
         keywords,
+        exclude,
     })
 }
 
@@ -262,7 +409,7 @@ pub(super) fn collect_project_module_paths(
     Ok(module_paths)
 }
 
-pub(super) fn collect_tonic_source_paths(root: &Path) -> Result<Vec<PathBuf>, String> {
+pub(crate) fn collect_tonic_source_paths(root: &Path) -> Result<Vec<PathBuf>, String> {
     let mut source_paths = Vec::new();
     let mut pending_directories = vec![root.to_path_buf()];
 
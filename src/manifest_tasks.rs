@@ -0,0 +1,210 @@
+use super::{
+    inject_optional_stdlib, library_source_paths, load_project_manifest, parse_project_ast,
+    project_dependency_sources, read_source_file, Workspace,
+};
+use crate::deps::Lockfile;
+use crate::lexer::scan_tokens;
+use crate::parser::{parse_ast, Expr, Module};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+/// A `tonic task` command: a module with a public `run/1` in a file under
+/// `tasks/` or named in `[tasks]`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct ProjectTask {
+    pub(crate) name: String,
+    pub(crate) module: String,
+    /// The module's `@shortdoc`, shown by `tonic task --list`.
+    pub(crate) shortdoc: Option<String>,
+    pub(crate) path: PathBuf,
+    /// The dependency that provides the task, `None` for the project's own.
+    pub(crate) dependency: Option<String>,
+}
+
+/// Every task the project at `project_root` can run: its own first, then
+/// those of its locked dependencies. A dependency task whose name is
+/// already taken is shadowed.
+pub(crate) fn discover_tasks(project_root: &Path) -> Result<Vec<ProjectTask>, String> {
+    let manifest = load_project_manifest(project_root)?;
+    let mut tasks = package_tasks(project_root, &manifest.tasks, None)?;
+
+    for (name, dep_root) in dependency_roots(project_root)? {
+        let declared = load_project_manifest(&dep_root)
+            .map(|manifest| manifest.tasks)
+            .unwrap_or_default();
+        for task in package_tasks(&dep_root, &declared, Some(&name))? {
+            if !tasks.iter().any(|existing| existing.name == task.name) {
+                tasks.push(task);
+            }
+        }
+    }
+
+    Ok(tasks)
+}
+
+/// The project's library modules and dependencies plus the file defining
+/// `task`, ready to compile.
+pub(crate) fn load_task_source(project_root: &Path, task: &ProjectTask) -> Result<String, String> {
+    let manifest = load_project_manifest(project_root)?;
+    let mut sources = Vec::new();
+    for source_path in library_source_paths(project_root, &manifest)? {
+        sources.push(read_source_file(&source_path)?);
+    }
+    sources.extend(project_dependency_sources(project_root, &manifest)?);
+
+    // A dependency without a [lib], or a project whose entry sits at its
+    // root, already loads the task file. Otherwise it goes first so
+    // diagnostics point at the right lines.
+    let already_loaded = parse_project_ast(&sources.join("\n\n"))
+        .is_some_and(|ast| ast.modules.iter().any(|module| module.name == task.module));
+    if !already_loaded {
+        sources.insert(0, read_source_file(&task.path)?);
+    }
+
+    let mut source = sources.join("\n\n");
+    inject_optional_stdlib(&mut source)?;
+    Ok(source)
+}
+
+/// `Tasks.Db.Seed` runs as `db.seed`, `GenSchema` as `gen_schema`.
+fn task_name(module_name: &str) -> String {
+    let module_name = module_name.strip_prefix("Tasks.").unwrap_or(module_name);
+    module_name
+        .split('.')
+        .map(|segment| {
+            let mut name = String::new();
+            for (index, c) in segment.chars().enumerate() {
+                if c.is_ascii_uppercase() && index > 0 {
+                    name.push('_');
+                }
+                name.push(c.to_ascii_lowercase());
+            }
+            name
+        })
+        .collect::<Vec<_>>()
+        .join(".")
+}
+
+/// Tasks declared in `[tasks]`, then those found under `tasks/`.
+fn package_tasks(
+    root: &Path,
+    declared: &BTreeMap<String, PathBuf>,
+    dependency: Option<&str>,
+) -> Result<Vec<ProjectTask>, String> {
+    let mut tasks: Vec<ProjectTask> = Vec::new();
+
+    for (name, relative_path) in declared {
+        let path = root.join(relative_path);
+        if !path.is_file() {
+            return Err(format!(
+                "task '{name}' path '{}' does not exist",
+                relative_path.display()
+            ));
+        }
+        let Some(module) = task_modules(&path)?.into_iter().next() else {
+            return Err(format!(
+                "task '{name}': {} defines no module with a public run/1",
+                relative_path.display()
+            ));
+        };
+        tasks.push(ProjectTask {
+            name: name.clone(),
+            module: module.name.clone(),
+            shortdoc: shortdoc(&module),
+            path,
+            dependency: dependency.map(str::to_string),
+        });
+    }
+
+    let tasks_dir = root.join("tasks");
+    if !tasks_dir.is_dir() {
+        return Ok(tasks);
+    }
+    let mut paths = super::collect_tonic_source_paths(&tasks_dir)?;
+    paths.sort();
+    for path in paths {
+        for module in task_modules(&path)? {
+            if tasks.iter().any(|task| task.module == module.name) {
+                continue;
+            }
+            let name = task_name(&module.name);
+            if let Some(existing) = tasks.iter().find(|task| task.name == name) {
+                return Err(format!(
+                    "task '{name}' is defined by both {} and {}",
+                    existing.path.display(),
+                    path.display()
+                ));
+            }
+            tasks.push(ProjectTask {
+                name,
+                module: module.name.clone(),
+                shortdoc: shortdoc(&module),
+                path: path.clone(),
+                dependency: dependency.map(str::to_string),
+            });
+        }
+    }
+
+    Ok(tasks)
+}
+
+/// Modules in `path` with a public `run/1`.
+fn task_modules(path: &Path) -> Result<Vec<Module>, String> {
+    let source = read_source_file(path)?;
+    let tokens = scan_tokens(&source)
+        .map_err(|error| format!("failed to parse task file {}: {error}", path.display()))?;
+    let ast = parse_ast(&tokens)
+        .map_err(|error| format!("failed to parse task file {}: {error}", path.display()))?;
+
+    Ok(ast
+        .modules
+        .into_iter()
+        .filter(|module| {
+            module.functions.iter().any(|function| {
+                function.name == "run" && function.params.len() == 1 && !function.is_private()
+            })
+        })
+        .collect())
+}
+
+fn shortdoc(module: &Module) -> Option<String> {
+    module
+        .attributes
+        .iter()
+        .find(|attribute| attribute.name == "shortdoc")
+        .and_then(|attribute| match &attribute.value {
+            Expr::String { value, .. } => Some(value.trim().to_string()),
+            _ => None,
+        })
+}
+
+/// Locked dependency directories, from the workspace lockfile for members.
+pub(crate) fn dependency_roots(project_root: &Path) -> Result<Vec<(String, PathBuf)>, String> {
+    let lock_root = match Workspace::containing(project_root)? {
+        Some(workspace) => workspace.root,
+        None => project_root.to_path_buf(),
+    };
+    let Some(lockfile) = Lockfile::load(&lock_root)? else {
+        return Ok(Vec::new());
+    };
+    let deps_dir = Lockfile::deps_dir(&lock_root);
+
+    let path_deps = lockfile
+        .path_deps
+        .iter()
+        .map(|(name, dep)| (name.clone(), PathBuf::from(&dep.path)));
+    let git_deps = lockfile
+        .git_deps
+        .keys()
+        .map(|name| (name.clone(), deps_dir.join(name)));
+    let registry_deps = lockfile
+        .registry_deps
+        .iter()
+        .map(|(name, dep)| (name.clone(), deps_dir.join(dep.cache_dir_name(name))));
+
+    Ok(path_deps
+        .chain(git_deps)
+        .chain(registry_deps)
+        .filter(|(_, root)| root.is_dir())
+        .collect())
+}
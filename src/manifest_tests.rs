@@ -1,6 +1,10 @@
-use super::{load_run_source, parse_manifest, Dependencies, ProjectManifest};
+use super::{
+    load_run_source, parse_manifest, BinTarget, Dependencies, LibTarget, ProjectManifest,
+    RunTarget, Workspace,
+};
 use crate::lexer::scan_tokens;
 use crate::parser::parse_ast;
+use std::collections::BTreeMap;
 use std::path::{Path, PathBuf};
 
 #[test]
@@ -30,13 +34,95 @@ fn parse_manifest_reads_project_entry() {
             Path::new("."),
         ),
         Ok(ProjectManifest {
-            entry: PathBuf::from("main.tn"),
+            name: Some("demo".to_string()),
+            entry: Some(PathBuf::from("main.tn")),
+            bins: Vec::new(),
+            lib: None,
+            tasks: BTreeMap::new(),
             dependencies: Dependencies::default(),
             package: None,
         })
     );
 }
 
+#[test]
+fn parse_manifest_reads_bin_targets_and_lib_without_entry() {
+    let manifest = parse_manifest(
+        "[project]\nname = \"tools\"\n\n[lib]\n\n[[bin]]\nname = \"serve\"\npath = \"bin/serve.tn\"\nmain_module = \"Serve\"\n\n[[bin]]\nname = \"migrate\"\npath = \"bin/migrate.tn\"\n",
+        Path::new("."),
+    )
+    .expect("manifest with [[bin]] and [lib] should parse without project.entry");
+
+    assert_eq!(manifest.entry, None);
+    assert_eq!(
+        manifest.lib,
+        Some(LibTarget {
+            path: PathBuf::from("src")
+        })
+    );
+    assert_eq!(
+        manifest.bins,
+        vec![
+            BinTarget {
+                name: "serve".to_string(),
+                path: PathBuf::from("bin/serve.tn"),
+                main_module: Some("Serve".to_string()),
+            },
+            BinTarget {
+                name: "migrate".to_string(),
+                path: PathBuf::from("bin/migrate.tn"),
+                main_module: None,
+            },
+        ]
+    );
+    assert_eq!(
+        manifest.run_target(Some("migrate")),
+        Ok(RunTarget::Bin(&manifest.bins[1]))
+    );
+    assert_eq!(
+        manifest.run_target(None),
+        Err(
+            "tonic.toml declares several binaries (serve, migrate); select one with --bin <name>"
+                .to_string()
+        )
+    );
+    assert_eq!(
+        manifest.run_target(Some("deploy")),
+        Err("no binary named 'deploy' in tonic.toml (binaries: serve, migrate)".to_string())
+    );
+    assert_eq!(
+        parse_manifest(
+            "[project]\nname = \"tools\"\n\n[[bin]]\nname = \"serve\"\npath = \"serve.tn\"\n\n[[bin]]\nname = \"serve\"\npath = \"other.tn\"\n",
+            Path::new("."),
+        ),
+        Err("invalid tonic.toml: binary 'serve' is declared more than once".to_string())
+    );
+}
+
+#[test]
+fn parse_manifest_reads_task_paths() {
+    let manifest = parse_manifest(
+        "[project]\nname = \"demo\"\nentry = \"main.tn\"\n\n[tasks]\nrelease = \"scripts/release.tn\"\n\"db.seed\" = \"scripts/seed.tn\"\n",
+        Path::new("."),
+    )
+    .expect("manifest with [tasks] should parse");
+    assert_eq!(
+        manifest.tasks,
+        BTreeMap::from([
+            ("db.seed".to_string(), PathBuf::from("scripts/seed.tn")),
+            ("release".to_string(), PathBuf::from("scripts/release.tn")),
+        ])
+    );
+
+    assert_eq!(
+        parse_manifest(
+            "[project]\nname = \"demo\"\nentry = \"main.tn\"\n\n[tasks]\nRelease = \"scripts/release.tn\"\n",
+            Path::new("."),
+        ),
+        Err("invalid tonic.toml: task name 'Release' must be lowercase, like `db.seed`".to_string())
+    );
+}
+
 #[test]
 fn parse_manifest_resolves_relative_path_dependencies_from_project_root() {
     let fixture_root = unique_fixture_root("manifest-relative-path-dependency");
@@ -182,7 +268,8 @@ fn parse_manifest_reads_full_package_metadata() {
         license = \"MIT\"\n\
         repository = \"https://github.com/example/my_lib\"\n\
         authors = [\"Alice\", \"Bob\"]\n\
-        keywords = [\"tonic\", \"library\"]\n";
+        keywords = [\"tonic\", \"library\"]\n\
+        exclude = [\"test\"]\n";
 
     let manifest = parse_manifest(source, Path::new("."))
         .expect("manifest with full package section should parse");
@@ -197,6 +284,7 @@ fn parse_manifest_reads_full_package_metadata() {
             authors: vec!["Alice".to_string(), "Bob".to_string()],
             repository: Some("https://github.com/example/my_lib".to_string()),
             keywords: vec!["tonic".to_string(), "library".to_string()],
+            exclude: vec!["test".to_string()],
         })
     );
 }
@@ -279,6 +367,25 @@ fn parse_manifest_reads_registry_dep_table_form_without_registry_override() {
     );
 }
 
+#[test]
+fn parse_manifest_reads_default_registry() {
+    let source = "[project]\nentry = \"main.tn\"\n\n[registries]\ndefault = \"file:///srv/registry\"\n\n[dependencies]\njson = \"~> 1.0\"\n";
+    let manifest =
+        parse_manifest(source, Path::new(".")).expect("manifest with registries should parse");
+
+    assert_eq!(
+        manifest.dependencies.default_registry.as_deref(),
+        Some("file:///srv/registry")
+    );
+    assert_eq!(
+        parse_manifest(
+            "[project]\nentry = \"main.tn\"\n\n[registries]\ndefault = 1\n",
+            Path::new(".")
+        ),
+        Err("invalid tonic.toml: registries.default must be a string".to_string())
+    );
+}
+
 #[test]
 fn parse_manifest_rejects_package_name_non_string() {
     let source = "[project]\nentry = \"main.tn\"\n\n[package]\nname = 42\n";
@@ -288,6 +395,55 @@ fn parse_manifest_rejects_package_name_non_string() {
     );
 }
 
+#[test]
+fn workspace_merges_member_dependencies_into_one_lock_scope() {
+    let fixture_root = unique_fixture_root("manifest-workspace-merge");
+    let write = |relative: &str, contents: &str| {
+        let path = fixture_root.join(relative);
+        std::fs::create_dir_all(path.parent().unwrap()).expect("fixture setup should create dirs");
+        std::fs::write(path, contents).expect("fixture setup should write file");
+    };
+    write(
+        "tonic.toml",
+        "[workspace]\nmembers = [\"apps/*\"]\n\n[registries]\ndefault = \"https://registry.test\"\n",
+    );
+    write(
+        "apps/api/tonic.toml",
+        "[project]\nentry = \"main.tn\"\n\n[dependencies]\njson = \"~> 1.0\"\n",
+    );
+    write(
+        "apps/web/tonic.toml",
+        "[project]\nname = \"site\"\nentry = \"main.tn\"\n\n[dependencies]\njson = \"^1.2\"\n",
+    );
+
+    let workspace = Workspace::load(&fixture_root)
+        .expect("workspace should load")
+        .expect("tonic.toml declares a workspace");
+    let names = workspace
+        .members
+        .iter()
+        .map(|member| member.name.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(names, ["api", "site"]);
+
+    let dependencies = workspace.dependencies().expect("members should merge");
+    let json = &dependencies.registry["json"];
+    assert_eq!(json.version, "~> 1.0 and ^1.2");
+    assert_eq!(json.registry.as_deref(), Some("https://registry.test"));
+
+    write(
+        "apps/web/tonic.toml",
+        "[project]\nname = \"site\"\nentry = \"main.tn\"\n\n[dependencies]\njson = { version = \"^1.2\", registry = \"https://mirror.test\" }\n",
+    );
+    let workspace = Workspace::load(&fixture_root).unwrap().unwrap();
+    assert_eq!(
+        workspace.dependencies().unwrap_err(),
+        "workspace members 'api' and 'site' declare dependency 'json' from different sources"
+    );
+
+    std::fs::remove_dir_all(&fixture_root).ok();
+}
+
 fn unique_fixture_root(test_name: &str) -> PathBuf {
     let timestamp = std::time::SystemTime::now()
         .duration_since(std::time::UNIX_EPOCH)
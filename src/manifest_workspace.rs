@@ -0,0 +1,294 @@
+use super::parse::{extract_string_array, parse_default_registry};
+use super::{load_project_manifest, Dependencies, ProjectManifest};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+/// A `tonic.toml` with a `[workspace]` table. Its members share one
+/// `tonic.lock` and `.tonic/deps` at the workspace root.
+#[derive(Debug, Clone)]
+pub(crate) struct Workspace {
+    /// Canonical workspace root directory.
+    pub(crate) root: PathBuf,
+    pub(crate) members: Vec<WorkspaceMember>,
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct WorkspaceMember {
+    /// `[package] name`, `project.name` or the directory name.
+    pub(crate) name: String,
+    /// Canonical member directory.
+    pub(crate) root: PathBuf,
+    /// The member manifest, with the workspace `[registries] default`
+    /// applied when it names none of its own.
+    pub(crate) manifest: ProjectManifest,
+}
+
+impl Workspace {
+    /// Loads the workspace declared at `root`, or `None` when `root` has no
+    /// `tonic.toml` or it has no `[workspace]` table.
+    pub(crate) fn load(root: &Path) -> Result<Option<Self>, String> {
+        let Ok(source) = std::fs::read_to_string(root.join("tonic.toml")) else {
+            return Ok(None);
+        };
+        let value: toml::Value =
+            toml::from_str(&source).map_err(|error| format!("invalid tonic.toml: {error}"))?;
+        let Some(workspace) = value.get("workspace") else {
+            return Ok(None);
+        };
+        if value.get("project").is_some() {
+            return Err(
+                "invalid tonic.toml: a [workspace] root cannot also declare [project]; move the project into a member directory"
+                    .to_string(),
+            );
+        }
+        let toml::Value::Table(table) = workspace else {
+            return Err("invalid tonic.toml: [workspace] must be a table".to_string());
+        };
+        let patterns = extract_string_array(table, "members", "workspace.members")?;
+        if patterns.is_empty() {
+            return Err(
+                "invalid tonic.toml: workspace.members must list at least one member directory"
+                    .to_string(),
+            );
+        }
+        let default_registry = parse_default_registry(value.get("registries"))?;
+        let root = root.canonicalize().map_err(|error| {
+            format!(
+                "failed to resolve workspace root {}: {error}",
+                root.display()
+            )
+        })?;
+
+        let mut members: Vec<WorkspaceMember> = Vec::new();
+        for pattern in &patterns {
+            for member_root in expand_member_pattern(&root, pattern)? {
+                if members.iter().any(|member| member.root == member_root) {
+                    continue;
+                }
+                let mut manifest = load_project_manifest(&member_root)
+                    .map_err(|error| format!("workspace member '{pattern}': {error}"))?;
+                if manifest.dependencies.default_registry.is_none() {
+                    manifest.dependencies.default_registry = default_registry.clone();
+                }
+                let name = manifest.display_name(&member_root);
+                if let Some(other) = members.iter().find(|member| member.name == name) {
+                    return Err(format!(
+                        "invalid tonic.toml: workspace members {} and {} are both named '{name}'",
+                        other.root.display(),
+                        member_root.display()
+                    ));
+                }
+                members.push(WorkspaceMember {
+                    name,
+                    root: member_root,
+                    manifest,
+                });
+            }
+        }
+
+        Ok(Some(Self { root, members }))
+    }
+
+    /// The nearest workspace declared at or above `start`.
+    pub(crate) fn find(start: &Path) -> Result<Option<Self>, String> {
+        let Ok(start) = start.canonicalize() else {
+            return Ok(None);
+        };
+        for directory in start.ancestors() {
+            if declares_workspace(directory) {
+                return Self::load(directory);
+            }
+        }
+        Ok(None)
+    }
+
+    /// The workspace that lists the project at `project_root` as a member.
+    /// A project inside a workspace directory but missing from its
+    /// `members` stays standalone.
+    pub(crate) fn containing(project_root: &Path) -> Result<Option<Self>, String> {
+        let Some(parent) = project_root
+            .canonicalize()
+            .ok()
+            .and_then(|root| root.parent().map(Path::to_path_buf))
+        else {
+            return Ok(None);
+        };
+        Ok(Self::find(&parent)?.filter(|workspace| workspace.member_at(project_root).is_some()))
+    }
+
+    /// The workspace directory name, used as the root of `tonic deps tree`.
+    pub(crate) fn name(&self) -> String {
+        self.root
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_else(|| "workspace".to_string())
+    }
+
+    pub(crate) fn member(&self, name: &str) -> Option<&WorkspaceMember> {
+        self.members.iter().find(|member| member.name == name)
+    }
+
+    pub(crate) fn member_at(&self, path: &Path) -> Option<&WorkspaceMember> {
+        let path = path.canonicalize().ok()?;
+        self.members.iter().find(|member| member.root == path)
+    }
+
+    /// `member`'s directory relative to the workspace root.
+    pub(crate) fn relative_path(&self, member: &WorkspaceMember) -> String {
+        member
+            .root
+            .strip_prefix(&self.root)
+            .unwrap_or(&member.root)
+            .display()
+            .to_string()
+    }
+
+    /// Every member's dependencies as one set for the shared `tonic.lock`.
+    /// Members may share a dependency only from the same source; registry
+    /// requirements on the same package are combined with `and` so the
+    /// workspace locks one version that satisfies all of them.
+    pub(crate) fn dependencies(&self) -> Result<Dependencies, String> {
+        let mut merged = Dependencies::default();
+        let mut declared_by: HashMap<String, &str> = HashMap::new();
+        let conflict = |name: &str, first: &str, second: &str| {
+            format!(
+                "workspace members '{first}' and '{second}' declare dependency '{name}' from different sources"
+            )
+        };
+
+        for member in &self.members {
+            let dependencies = &member.manifest.dependencies;
+
+            let mut path_names = dependencies.path.keys().collect::<Vec<_>>();
+            path_names.sort();
+            for name in path_names {
+                let path = &dependencies.path[name];
+                let path = path.canonicalize().unwrap_or_else(|_| path.clone());
+                if merged.git.contains_key(name) || merged.registry.contains_key(name) {
+                    return Err(conflict(name, declared_by[name], &member.name));
+                }
+                match merged.path.get(name) {
+                    Some(existing) if *existing != path => {
+                        return Err(conflict(name, declared_by[name], &member.name));
+                    }
+                    Some(_) => {}
+                    None => {
+                        declared_by.insert(name.clone(), &member.name);
+                        merged.path.insert(name.clone(), path);
+                    }
+                }
+            }
+
+            let mut git_names = dependencies.git.keys().collect::<Vec<_>>();
+            git_names.sort();
+            for name in git_names {
+                let git = &dependencies.git[name];
+                if merged.path.contains_key(name) || merged.registry.contains_key(name) {
+                    return Err(conflict(name, declared_by[name], &member.name));
+                }
+                match merged.git.get(name) {
+                    Some(existing) if existing != git => {
+                        return Err(conflict(name, declared_by[name], &member.name));
+                    }
+                    Some(_) => {}
+                    None => {
+                        declared_by.insert(name.clone(), &member.name);
+                        merged.git.insert(name.clone(), git.clone());
+                    }
+                }
+            }
+
+            let mut registry_names = dependencies.registry.keys().collect::<Vec<_>>();
+            registry_names.sort();
+            for name in registry_names {
+                let mut registry = dependencies.registry[name].clone();
+                registry.registry = registry
+                    .registry
+                    .or_else(|| dependencies.default_registry.clone());
+                if merged.path.contains_key(name) || merged.git.contains_key(name) {
+                    return Err(conflict(name, declared_by[name], &member.name));
+                }
+                let Some(existing) = merged.registry.get_mut(name) else {
+                    declared_by.insert(name.clone(), &member.name);
+                    merged.registry.insert(name.clone(), registry);
+                    continue;
+                };
+                if existing.registry != registry.registry {
+                    return Err(conflict(name, declared_by[name], &member.name));
+                }
+                if existing.version == registry.version {
+                    continue;
+                }
+                if existing.version.contains(" or ") || registry.version.contains(" or ") {
+                    return Err(format!(
+                        "workspace members '{}' and '{}' require dependency '{name}' as '{}' and '{}'; requirements using `or` cannot be combined",
+                        declared_by[name], member.name, existing.version, registry.version
+                    ));
+                }
+                existing.version = format!("{} and {}", existing.version, registry.version);
+            }
+        }
+
+        Ok(merged)
+    }
+}
+
+fn declares_workspace(directory: &Path) -> bool {
+    std::fs::read_to_string(directory.join("tonic.toml"))
+        .ok()
+        .and_then(|source| toml::from_str::<toml::Value>(&source).ok())
+        .is_some_and(|value| value.get("workspace").is_some())
+}
+
+/// A member directory, or with a trailing `/*` every subdirectory holding a
+/// `tonic.toml`.
+fn expand_member_pattern(root: &Path, pattern: &str) -> Result<Vec<PathBuf>, String> {
+    let (parent, wildcard) = match pattern.strip_suffix('*') {
+        Some(prefix) if prefix.is_empty() || prefix.ends_with('/') => (prefix, true),
+        _ => (pattern, false),
+    };
+    if parent.contains(['*', '?', '[']) {
+        return Err(format!(
+            "invalid tonic.toml: workspace member '{pattern}' is not supported; only a trailing '/*' wildcard is"
+        ));
+    }
+
+    let directory = root.join(parent);
+    if !wildcard {
+        if !directory.join("tonic.toml").is_file() {
+            return Err(format!(
+                "invalid tonic.toml: workspace member '{pattern}' has no tonic.toml at {}",
+                directory.display()
+            ));
+        }
+        return directory
+            .canonicalize()
+            .map(|path| vec![path])
+            .map_err(|error| format!("failed to resolve workspace member '{pattern}': {error}"));
+    }
+
+    let entries = std::fs::read_dir(&directory).map_err(|error| {
+        format!(
+            "failed to read workspace member directory {}: {error}",
+            directory.display()
+        )
+    })?;
+    let mut member_roots = Vec::new();
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let hidden = path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .is_some_and(|name| name.starts_with('.'));
+        if !hidden && path.join("tonic.toml").is_file() {
+            member_roots.push(path.canonicalize().map_err(|error| {
+                format!(
+                    "failed to resolve workspace member {}: {error}",
+                    path.display()
+                )
+            })?);
+        }
+    }
+    member_roots.sort();
+    Ok(member_roots)
+}
@@ -29,6 +29,37 @@ pub(crate) fn is_native_artifact_path(path: &str) -> bool {
     path.ends_with(".tnx.json")
 }
 
+/// The native artifact manifests `tonic compile` wrote into `build_dir`.
+pub(crate) fn native_artifact_manifests(build_dir: &Path) -> Vec<PathBuf> {
+    let Ok(entries) = std::fs::read_dir(build_dir) else {
+        return Vec::new();
+    };
+    let mut manifests = entries
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.is_file() && is_native_artifact_path(&path.to_string_lossy()))
+        .collect::<Vec<_>>();
+    manifests.sort();
+    manifests
+}
+
+/// The manifest at `manifest_path` plus the artifacts it lists that sit next
+/// to it; an executable written elsewhere with `--out` is left out.
+pub(crate) fn native_artifact_files(manifest_path: &Path) -> Vec<PathBuf> {
+    let mut files = vec![manifest_path.to_path_buf()];
+    let Ok(manifest) = load_manifest(manifest_path) else {
+        return files;
+    };
+    let artifacts = &manifest.artifacts;
+    for artifact in [&artifacts.ir, &artifacts.c_source, &artifacts.executable] {
+        let path = resolve_artifact_path(manifest_path, artifact);
+        if path.parent() == manifest_path.parent() && path.is_file() {
+            files.push(path);
+        }
+    }
+    files
+}
+
 pub(crate) fn host_target_triple() -> String {
     format!("{}-{}", std::env::consts::OS, std::env::consts::ARCH)
 }
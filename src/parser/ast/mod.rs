@@ -61,6 +61,11 @@ pub enum ModuleForm {
     Use {
         module: String,
     },
+    /// `doctest Module` in a test module: run the `iex>`/`tonic>` examples
+    /// from `Module`'s docs as test cases.
+    Doctest {
+        module: String,
+    },
     Defstruct {
         fields: Vec<StructFieldEntry>,
     },
@@ -105,36 +110,72 @@ pub struct ProtocolImplFunction {
 
 #[derive(Debug, Clone, Serialize, PartialEq, Eq)]
 pub struct ModuleAttribute {
+    #[serde(skip_serializing)]
+    pub offset: usize,
     pub name: String,
     pub value: Expr,
 }
 
+/// What an ExUnit-style block inside a test module declares.
+#[derive(Debug, Clone, Copy, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum TestBlockKind {
+    Test,
+    Setup,
+    SetupAll,
+}
+
+/// A `test "name" do`, `setup do` or `setup_all do` block. The parser lowers
+/// its body to the public function `function` of the enclosing module, taking
+/// the test context as its only parameter when one is written.
+#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
+pub struct TestBlock {
+    #[serde(skip_serializing)]
+    pub offset: usize,
+    pub kind: TestBlockKind,
+    /// The test's name; `None` for setup blocks.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    /// The enclosing `describe "group" do` block, if any.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub describe: Option<String>,
+    pub function: String,
+}
+
 #[derive(Debug, Serialize, PartialEq, Eq)]
 pub struct Module {
     #[serde(skip_serializing)]
     pub id: NodeId,
+    #[serde(skip_serializing)]
+    pub offset: usize,
     pub name: String,
     #[serde(skip_serializing_if = "Vec::is_empty")]
     pub forms: Vec<ModuleForm>,
     #[serde(skip_serializing_if = "Vec::is_empty")]
     pub attributes: Vec<ModuleAttribute>,
     pub functions: Vec<Function>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub test_blocks: Vec<TestBlock>,
 }
 
 impl Module {
     pub(crate) fn with_id(
         id: NodeId,
+        offset: usize,
         name: String,
         forms: Vec<ModuleForm>,
         attributes: Vec<ModuleAttribute>,
         functions: Vec<Function>,
+        test_blocks: Vec<TestBlock>,
     ) -> Self {
         Self {
             id,
+            offset,
             name,
             forms,
             attributes,
             functions,
+            test_blocks,
         }
     }
 }
@@ -156,6 +197,8 @@ impl FunctionVisibility {
 pub struct Function {
     #[serde(skip_serializing)]
     pub id: NodeId,
+    #[serde(skip_serializing)]
+    pub offset: usize,
     pub name: String,
     #[serde(skip_serializing_if = "FunctionVisibility::is_public")]
     pub visibility: FunctionVisibility,
@@ -168,6 +211,7 @@ pub struct Function {
 impl Function {
     pub(crate) fn with_id(
         id: NodeId,
+        offset: usize,
         name: String,
         visibility: FunctionVisibility,
         params: Vec<Parameter>,
@@ -176,6 +220,7 @@ impl Function {
     ) -> Self {
         Self {
             id,
+            offset,
             name,
             visibility,
             params,
@@ -0,0 +1,171 @@
+use super::pattern::pattern_label;
+use super::*;
+use crate::lexer::TokenKind;
+
+impl<'a> Parser<'a> {
+    pub(super) fn current_starts_check_all(&self) -> bool {
+        let is_ident = |offset: usize, lexeme: &str| {
+            self.peek(offset)
+                .is_some_and(|token| token.kind() == TokenKind::Ident && token.lexeme() == lexeme)
+        };
+        is_ident(0, "check") && is_ident(1, "all")
+    }
+
+    /// Parse `check all x <- gen, ..., max_runs: n do ... end` and lower it to
+    /// `PropertyTest.check_all(property, n)`, where `property` binds each
+    /// generator in turn and ends in the counterexample and a body closure:
+    ///
+    /// `PropertyTest.bind(gen, fn x -> PropertyTest.constant({[{"x", x}], fn -> ... end}) end)`
+    pub(super) fn parse_check_all_expression(&mut self) -> Result<Expr, ParserError> {
+        let check_span = self
+            .advance()
+            .expect("check token should be available")
+            .span();
+        self.advance();
+        let offset = check_span.start();
+
+        let mut clauses = Vec::new();
+        let mut max_runs = None;
+
+        loop {
+            if self.check(TokenKind::Ident)
+                && self
+                    .peek(1)
+                    .is_some_and(|token| token.kind() == TokenKind::Colon)
+            {
+                let option_token = self.expect_token(TokenKind::Ident, "check all option")?;
+                self.expect(TokenKind::Colon, ":")?;
+
+                match option_token.lexeme() {
+                    "max_runs" if max_runs.is_some() => {
+                        return Err(ParserError::at_current(
+                            "duplicate check all option 'max_runs'",
+                            Some(option_token),
+                        ));
+                    }
+                    "max_runs" => max_runs = Some(self.parse_expression()?),
+                    other => {
+                        return Err(ParserError::at_current(
+                            format!(
+                                "unsupported check all option '{other}'; supported option is max_runs"
+                            ),
+                            Some(option_token),
+                        ));
+                    }
+                }
+            } else {
+                let pattern = self.parse_pattern()?;
+                self.expect(TokenKind::LeftArrow, "<-")?;
+                let generator = self.parse_expression()?;
+                clauses.push((pattern, generator));
+            }
+
+            if !self.match_kind(TokenKind::Comma) {
+                break;
+            }
+        }
+
+        if clauses.is_empty() {
+            return Err(ParserError::at_span(
+                "check all expects at least one generator, for example `check all x <- PropertyTest.integer() do ... end`",
+                check_span,
+            ));
+        }
+
+        self.expect_block_do(
+            "check all",
+            check_span,
+            "add 'do' after the check all clauses to begin the property body",
+        )?;
+        let body = self.parse_block_body()?;
+        self.expect_block_end("check all", check_span)?;
+
+        // Non-variable patterns are matched inside the closure, so the
+        // counterexample can show the whole generated value.
+        let params = clauses
+            .iter()
+            .enumerate()
+            .map(|(index, (pattern, _))| match pattern {
+                Pattern::Bind { name } if name != "_" => name.clone(),
+                _ => format!("__check{index}"),
+            })
+            .collect::<Vec<_>>();
+
+        let counterexample = clauses
+            .iter()
+            .zip(&params)
+            .map(|((pattern, _), param)| {
+                let label = Expr::string(self.node_ids.next_expr(), offset, pattern_label(pattern));
+                let value = Expr::variable(self.node_ids.next_expr(), offset, param.clone());
+                Expr::tuple(self.node_ids.next_expr(), offset, vec![label, value])
+            })
+            .collect::<Vec<_>>();
+        let counterexample = Expr::list(self.node_ids.next_expr(), offset, counterexample);
+        let body = Expr::anonymous_fn(self.node_ids.next_expr(), offset, Vec::new(), body);
+        let case = Expr::tuple(
+            self.node_ids.next_expr(),
+            offset,
+            vec![counterexample, body],
+        );
+        let mut property = Expr::call(
+            self.node_ids.next_expr(),
+            offset,
+            "PropertyTest.constant".to_string(),
+            vec![case],
+        );
+
+        for ((pattern, generator), param) in clauses.into_iter().zip(params).rev() {
+            let (generator, inner) = match pattern {
+                Pattern::Bind { ref name } if *name == param => (generator, property),
+                pattern => {
+                    // Values that don't match are discarded like `filter`, so
+                    // the wildcard branch below is only a safety net.
+                    let predicate = self.match_predicate(offset, param.clone(), pattern.clone());
+                    let generator = Expr::call(
+                        self.node_ids.next_expr(),
+                        offset,
+                        "PropertyTest.filter".to_string(),
+                        vec![generator, predicate],
+                    );
+
+                    let message = Expr::string(
+                        self.node_ids.next_expr(),
+                        offset,
+                        format!(
+                            "check all pattern {} did not match the generated value",
+                            pattern_label(&pattern)
+                        ),
+                    );
+                    let mismatch = Expr::raise(self.node_ids.next_expr(), offset, message);
+                    let subject = Expr::variable(self.node_ids.next_expr(), offset, param.clone());
+                    let inner = Expr::case(
+                        self.node_ids.next_expr(),
+                        offset,
+                        subject,
+                        vec![
+                            CaseBranch::new(pattern, None, property),
+                            CaseBranch::new(Pattern::Wildcard, None, mismatch),
+                        ],
+                    );
+                    (generator, inner)
+                }
+            };
+            let bind = Expr::anonymous_fn(self.node_ids.next_expr(), offset, vec![param], inner);
+            property = Expr::call(
+                self.node_ids.next_expr(),
+                offset,
+                "PropertyTest.bind".to_string(),
+                vec![generator, bind],
+            );
+        }
+
+        let mut args = vec![property];
+        args.extend(max_runs);
+        Ok(Expr::call(
+            self.node_ids.next_expr(),
+            offset,
+            "PropertyTest.check_all".to_string(),
+            args,
+        ))
+    }
+}
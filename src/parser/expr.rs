@@ -255,6 +255,18 @@ impl<'a> Parser<'a> {
             return self.parse_for_expression();
         }
 
+        if self.current_starts_check_all() {
+            return self.parse_check_all_expression();
+        }
+
+        if self.current_starts_assert_match_question() {
+            return self.parse_assert_match_question_expression();
+        }
+
+        if self.current_starts_match_question() {
+            return self.parse_match_question_expression();
+        }
+
         if self.check(TokenKind::Case) {
             return self.parse_case_expression();
         }
@@ -456,6 +468,10 @@ impl<'a> Parser<'a> {
             args.push(self.parse_expression()?);
 
             if self.match_kind(TokenKind::Comma) {
+                // Allow trailing comma before `)`
+                if self.check(TokenKind::RParen) {
+                    break;
+                }
                 continue;
             }
 
@@ -234,6 +234,7 @@ impl<'a> Parser<'a> {
                         | "import"
                         | "require"
                         | "use"
+                        | "doctest"
                         | "defstruct"
                         | "defprotocol"
                         | "defimpl"
@@ -191,6 +191,10 @@ impl<'a> Parser<'a> {
         let mut entries = vec![self.parse_map_entry_from_key(first_key)?];
 
         while self.match_kind(TokenKind::Comma) {
+            // Allow trailing comma before `}`
+            if self.check(TokenKind::RBrace) {
+                break;
+            }
             entries.push(self.parse_map_entry()?);
         }
 
@@ -282,6 +286,10 @@ impl<'a> Parser<'a> {
         let mut entries = vec![self.parse_map_entry_from_label()?];
 
         while self.match_kind(TokenKind::Comma) {
+            // Allow trailing comma before `}`
+            if self.check(TokenKind::RBrace) {
+                break;
+            }
             entries.push(self.parse_map_entry()?);
         }
 
@@ -359,6 +367,10 @@ impl<'a> Parser<'a> {
             entries.push(LabelExprEntry { key, value });
 
             if self.match_kind(TokenKind::Comma) {
+                // Allow trailing comma before the closing delimiter
+                if self.check(closing) {
+                    break;
+                }
                 continue;
             }
 
@@ -412,6 +424,10 @@ impl<'a> Parser<'a> {
             items.push(self.parse_expression()?);
 
             if self.match_kind(TokenKind::Comma) {
+                // Allow trailing comma before the closing delimiter
+                if self.check(closing) {
+                    break;
+                }
                 continue;
             }
 
@@ -0,0 +1,212 @@
+use super::pattern::pattern_label;
+use super::*;
+use crate::lexer::TokenKind;
+
+impl<'a> Parser<'a> {
+    pub(super) fn current_starts_match_question(&self) -> bool {
+        self.peek(0)
+            .is_some_and(|token| token.kind() == TokenKind::Ident && token.lexeme() == "match?")
+            && self
+                .peek(1)
+                .is_some_and(|token| token.kind() == TokenKind::LParen)
+    }
+
+    /// `Assert.assert(match?(...)`, which gets a failure naming the
+    /// sub-pattern that did not match instead of "expected truthy value".
+    pub(super) fn current_starts_assert_match_question(&self) -> bool {
+        let is = |offset: usize, kind: TokenKind, lexeme: Option<&str>| {
+            self.peek(offset).is_some_and(|token| {
+                token.kind() == kind && lexeme.is_none_or(|lexeme| token.lexeme() == lexeme)
+            })
+        };
+        is(0, TokenKind::Ident, Some("Assert"))
+            && is(1, TokenKind::Dot, None)
+            && is(2, TokenKind::Ident, Some("assert"))
+            && is(3, TokenKind::LParen, None)
+            && is(4, TokenKind::Ident, Some("match?"))
+            && is(5, TokenKind::LParen, None)
+    }
+
+    /// Parse `match?(pattern, expr)` and lower it to
+    /// `case expr do pattern -> true; _ -> false end`.
+    pub(super) fn parse_match_question_expression(&mut self) -> Result<Expr, ParserError> {
+        let (offset, pattern, value) = self.parse_match_question_args()?;
+        Ok(self.match_case(offset, value, pattern))
+    }
+
+    /// Parse `Assert.assert(match?(pattern, expr), message)` and lower it to
+    /// `Assert.assert_pattern(expr, fn v -> match?(pattern, v) end, source, tree, message)`,
+    /// where `tree` describes the pattern for explaining a failure.
+    pub(super) fn parse_assert_match_question_expression(&mut self) -> Result<Expr, ParserError> {
+        let assert_span = self
+            .advance()
+            .expect("Assert token should be available")
+            .span();
+        self.advance();
+        self.advance();
+        let opening_span = self
+            .advance()
+            .expect("assert lparen should be available")
+            .span();
+        let offset = assert_span.start();
+
+        let (match_offset, pattern, value) = self.parse_match_question_args()?;
+        let source = Expr::string(self.node_ids.next_expr(), offset, pattern_label(&pattern));
+        let tree = self.pattern_tree(offset, &pattern);
+        let matches = self.match_predicate(match_offset, "__match".to_string(), pattern);
+        let mut args = vec![value, matches, source, tree];
+
+        if self.match_kind(TokenKind::Comma) && !self.check(TokenKind::RParen) {
+            args.push(self.parse_expression()?);
+            self.match_kind(TokenKind::Comma);
+        }
+        self.expect_closing_delimiter(
+            TokenKind::RParen,
+            ")",
+            "Assert.assert arguments",
+            opening_span,
+            "add ')' to close the assert call, for example `Assert.assert(match?({:ok, _}, result))`",
+        )?;
+
+        Ok(Expr::call(
+            self.node_ids.next_expr(),
+            offset,
+            "Assert.assert_pattern".to_string(),
+            args,
+        ))
+    }
+
+    /// `fn param -> case param do pattern -> true; _ -> false end end`
+    pub(super) fn match_predicate(
+        &mut self,
+        offset: usize,
+        param: String,
+        pattern: Pattern,
+    ) -> Expr {
+        let subject = Expr::variable(self.node_ids.next_expr(), offset, param.clone());
+        let matches = self.match_case(offset, subject, pattern);
+        Expr::anonymous_fn(self.node_ids.next_expr(), offset, vec![param], matches)
+    }
+
+    fn parse_match_question_args(&mut self) -> Result<(usize, Pattern, Expr), ParserError> {
+        let offset = self
+            .advance()
+            .expect("match? token should be available")
+            .span()
+            .start();
+        let opening_span = self
+            .advance()
+            .expect("match? lparen should be available")
+            .span();
+
+        let pattern = self.parse_pattern()?;
+        self.expect(TokenKind::Comma, ",")?;
+        let value = self.parse_expression()?;
+        self.expect_closing_delimiter(
+            TokenKind::RParen,
+            ")",
+            "match? arguments",
+            opening_span,
+            "add ')' to close match?, for example `match?({:ok, _}, result)`",
+        )?;
+
+        Ok((offset, pattern, value))
+    }
+
+    fn match_case(&mut self, offset: usize, subject: Expr, pattern: Pattern) -> Expr {
+        let matched = Expr::bool(self.node_ids.next_expr(), offset, true);
+        let unmatched = Expr::bool(self.node_ids.next_expr(), offset, false);
+        Expr::case(
+            self.node_ids.next_expr(),
+            offset,
+            subject,
+            vec![
+                CaseBranch::new(pattern, None, matched),
+                CaseBranch::new(Pattern::Wildcard, None, unmatched),
+            ],
+        )
+    }
+
+    /// Encode a pattern as `{kind, {source, payload}}` values for
+    /// `Assert.assert_pattern`; pinned variables are captured by value.
+    fn pattern_tree(&mut self, offset: usize, pattern: &Pattern) -> Expr {
+        let (kind, payload) = match pattern {
+            Pattern::Wildcard => (
+                "bind",
+                Expr::string(self.node_ids.next_expr(), offset, "_".to_string()),
+            ),
+            Pattern::Bind { name } => (
+                "bind",
+                Expr::string(self.node_ids.next_expr(), offset, name.clone()),
+            ),
+            Pattern::Pin { name } => (
+                "pin",
+                Expr::variable(self.node_ids.next_expr(), offset, name.clone()),
+            ),
+            Pattern::Atom { value } => (
+                "literal",
+                Expr::atom(self.node_ids.next_expr(), offset, value.clone()),
+            ),
+            Pattern::Integer { value } => (
+                "literal",
+                Expr::int(self.node_ids.next_expr(), offset, *value),
+            ),
+            Pattern::Bool { value } => (
+                "literal",
+                Expr::bool(self.node_ids.next_expr(), offset, *value),
+            ),
+            Pattern::Nil => ("literal", Expr::nil(self.node_ids.next_expr(), offset)),
+            Pattern::String { value } => (
+                "literal",
+                Expr::string(self.node_ids.next_expr(), offset, value.clone()),
+            ),
+            Pattern::Tuple { items } => {
+                let items = items
+                    .iter()
+                    .map(|item| self.pattern_tree(offset, item))
+                    .collect();
+                (
+                    "tuple",
+                    Expr::list(self.node_ids.next_expr(), offset, items),
+                )
+            }
+            Pattern::List { items, tail } => {
+                let items = items
+                    .iter()
+                    .map(|item| self.pattern_tree(offset, item))
+                    .collect();
+                let items = Expr::list(self.node_ids.next_expr(), offset, items);
+                let tail = match tail {
+                    Some(tail) => self.pattern_tree(offset, tail),
+                    None => Expr::nil(self.node_ids.next_expr(), offset),
+                };
+                (
+                    "list",
+                    Expr::tuple(self.node_ids.next_expr(), offset, vec![items, tail]),
+                )
+            }
+            Pattern::Map { entries } => {
+                let entries = entries
+                    .iter()
+                    .map(|entry| {
+                        let key = self.pattern_tree(offset, entry.key());
+                        let value = self.pattern_tree(offset, entry.value());
+                        Expr::tuple(self.node_ids.next_expr(), offset, vec![key, value])
+                    })
+                    .collect();
+                (
+                    "map",
+                    Expr::list(self.node_ids.next_expr(), offset, entries),
+                )
+            }
+            Pattern::Bitstring { .. } | Pattern::Struct { .. } => {
+                ("opaque", Expr::nil(self.node_ids.next_expr(), offset))
+            }
+        };
+
+        let kind = Expr::atom(self.node_ids.next_expr(), offset, kind.to_string());
+        let source = Expr::string(self.node_ids.next_expr(), offset, pattern_label(pattern));
+        let node = Expr::tuple(self.node_ids.next_expr(), offset, vec![source, payload]);
+        Expr::tuple(self.node_ids.next_expr(), offset, vec![kind, node])
+    }
+}
@@ -2,13 +2,16 @@ use crate::lexer::{Span, Token, TokenKind};
 
 mod ast;
 mod canonicalize;
+mod check_all;
 mod control;
 mod expr;
 mod fn_expr;
 mod imports;
 mod literal;
+mod match_pattern;
 mod module;
 mod pattern;
+mod test_block;
 mod try_expr;
 
 pub use ast::*;
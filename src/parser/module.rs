@@ -23,6 +23,7 @@ impl<'a> Parser<'a> {
         let mut forms = Vec::new();
         let mut attributes = Vec::new();
         let mut functions = Vec::new();
+        let mut test_blocks = Vec::new();
         let mut nested_modules: Vec<Module> = Vec::new();
 
         while !self.check(TokenKind::End) {
@@ -46,6 +47,11 @@ impl<'a> Parser<'a> {
                 continue;
             }
 
+            if self.current_starts_test_block() {
+                self.parse_test_block(None, &mut functions, &mut attributes, &mut test_blocks)?;
+                continue;
+            }
+
             // Nested defmodule: flatten into sibling modules with dotted name.
             if self.check(TokenKind::Defmodule) {
                 let mut nested = self.parse_module_group(Some(&name))?;
@@ -58,7 +64,15 @@ impl<'a> Parser<'a> {
 
         self.expect_block_end(&construct, module_span)?;
 
-        let mut result = vec![Module::with_id(id, name, forms, attributes, functions)];
+        let mut result = vec![Module::with_id(
+            id,
+            module_span.start(),
+            name,
+            forms,
+            attributes,
+            functions,
+            test_blocks,
+        )];
         result.append(&mut nested_modules);
         Ok(result)
     }
@@ -118,7 +132,15 @@ impl<'a> Parser<'a> {
         let body = self.parse_block_body()?;
         self.expect_block_end(&construct, function_span)?;
 
-        Ok(Function::with_id(id, name, visibility, params, guard, body))
+        Ok(Function::with_id(
+            id,
+            function_span.start(),
+            name,
+            visibility,
+            params,
+            guard,
+            body,
+        ))
     }
 
     fn current_starts_module_form(&self) -> bool {
@@ -130,6 +152,7 @@ impl<'a> Parser<'a> {
                         | "import"
                         | "require"
                         | "use"
+                        | "doctest"
                         | "defstruct"
                         | "defprotocol"
                         | "defimpl"
@@ -147,6 +170,7 @@ impl<'a> Parser<'a> {
             "import" => self.parse_import_form().map(|f| vec![f]),
             "require" => self.parse_named_module_form("require").map(|f| vec![f]),
             "use" => self.parse_named_module_form("use").map(|f| vec![f]),
+            "doctest" => self.parse_named_module_form("doctest").map(|f| vec![f]),
             "defstruct" => self.parse_defstruct_form().map(|f| vec![f]),
             "defprotocol" => self.parse_defprotocol_form().map(|f| vec![f]),
             "defimpl" => self.parse_defimpl_form().map(|f| vec![f]),
@@ -174,6 +198,7 @@ impl<'a> Parser<'a> {
         let form = match form_name {
             "require" => ModuleForm::Require { module },
             "use" => ModuleForm::Use { module },
+            "doctest" => ModuleForm::Doctest { module },
             _ => {
                 return Err(ParserError::at_current(
                     format!("unsupported module form '{form_name}'"),
@@ -321,11 +346,32 @@ impl<'a> Parser<'a> {
     }
 
     pub(super) fn parse_module_attribute(&mut self) -> Result<ModuleAttribute, ParserError> {
-        self.expect(TokenKind::At, "@")?;
+        let offset = self.expect_token(TokenKind::At, "@")?.span().start();
         let name = self.expect_ident("attribute name")?;
-        let value = self.parse_expression()?;
 
-        Ok(ModuleAttribute { name, value })
+        // `@tag slow: true, timeout: 10` is a keyword list without brackets.
+        let value = if self.starts_keyword_literal_entry() {
+            let value_offset = self.current().map_or(offset, |token| token.span().start());
+            let mut entries = Vec::new();
+            loop {
+                let key = self.expect_ident("keyword key")?;
+                self.expect(TokenKind::Colon, ":")?;
+                let value = self.parse_expression()?;
+                entries.push(LabelExprEntry { key, value });
+                if !self.match_kind(TokenKind::Comma) {
+                    break;
+                }
+            }
+            Expr::keyword(self.node_ids.next_expr(), value_offset, entries)
+        } else {
+            self.parse_expression()?
+        };
+
+        Ok(ModuleAttribute {
+            offset,
+            name,
+            value,
+        })
     }
 
     pub(super) fn parse_module_reference(&mut self, expected: &str) -> Result<String, ParserError> {
@@ -387,7 +433,7 @@ impl<'a> Parser<'a> {
         Ok(params)
     }
 
-    fn parse_param(&mut self, index: usize) -> Result<Parameter, ParserError> {
+    pub(super) fn parse_param(&mut self, index: usize) -> Result<Parameter, ParserError> {
         let (name, annotation, pattern, supports_default) =
             if self.current_starts_dynamic_param_annotation() {
                 self.advance();
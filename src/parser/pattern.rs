@@ -302,3 +302,52 @@ impl<'a> Parser<'a> {
         Ok(Pattern::Struct { module, entries })
     }
 }
+
+/// Source-like text of a pattern, labelling generated values in `check all`
+/// counterexamples and sub-patterns in `match?` assertion failures.
+pub(super) fn pattern_label(pattern: &Pattern) -> String {
+    let join = |items: &[Pattern]| {
+        items
+            .iter()
+            .map(pattern_label)
+            .collect::<Vec<_>>()
+            .join(", ")
+    };
+
+    match pattern {
+        Pattern::Atom { value } => format!(":{value}"),
+        Pattern::Bind { name } => name.clone(),
+        Pattern::Pin { name } => format!("^{name}"),
+        Pattern::Wildcard => "_".to_string(),
+        Pattern::Integer { value } => value.to_string(),
+        Pattern::Bool { value } => value.to_string(),
+        Pattern::Nil => "nil".to_string(),
+        Pattern::String { value } => format!("\"{value}\""),
+        Pattern::Tuple { items } => format!("{{{}}}", join(items)),
+        Pattern::List { items, tail: None } => format!("[{}]", join(items)),
+        Pattern::List {
+            items,
+            tail: Some(tail),
+        } => format!("[{} | {}]", join(items), pattern_label(tail)),
+        Pattern::Bitstring { items } => format!("<<{}>>", join(items)),
+        Pattern::Map { entries } => {
+            let entries = entries
+                .iter()
+                .map(|entry| match entry.key() {
+                    Pattern::Atom { value } => {
+                        format!("{value}: {}", pattern_label(entry.value()))
+                    }
+                    key => format!("{} => {}", pattern_label(key), pattern_label(entry.value())),
+                })
+                .collect::<Vec<_>>();
+            format!("%{{{}}}", entries.join(", "))
+        }
+        Pattern::Struct { module, entries } => {
+            let entries = entries
+                .iter()
+                .map(|entry| format!("{}: {}", entry.key(), pattern_label(entry.value())))
+                .collect::<Vec<_>>();
+            format!("%{module}{{{}}}", entries.join(", "))
+        }
+    }
+}
@@ -0,0 +1,229 @@
+use super::*;
+use crate::lexer::TokenKind;
+
+impl<'a> Parser<'a> {
+    /// Whether the module body continues with `describe "..."`, `test "..."`,
+    /// `setup` or `setup_all`.
+    pub(super) fn current_starts_test_block(&self) -> bool {
+        let Some(token) = self.current() else {
+            return false;
+        };
+        if token.kind() != TokenKind::Ident {
+            return false;
+        }
+
+        let next = self.peek(1).map(|token| token.kind());
+        match token.lexeme() {
+            "describe" | "test" => next == Some(TokenKind::String),
+            "setup" | "setup_all" => next.is_some_and(|kind| kind != TokenKind::LParen),
+            _ => false,
+        }
+    }
+
+    /// Parse a `describe`, `test`, `setup` or `setup_all` block, lowering each
+    /// test and setup body to a function of the enclosing module.
+    pub(super) fn parse_test_block(
+        &mut self,
+        describe: Option<&str>,
+        functions: &mut Vec<Function>,
+        attributes: &mut Vec<ModuleAttribute>,
+        test_blocks: &mut Vec<TestBlock>,
+    ) -> Result<(), ParserError> {
+        let kind = match self.current().map(|token| token.lexeme()) {
+            Some("describe") => {
+                return self.parse_describe_block(describe, functions, attributes, test_blocks)
+            }
+            Some("test") => TestBlockKind::Test,
+            Some("setup") => TestBlockKind::Setup,
+            Some("setup_all") => TestBlockKind::SetupAll,
+            _ => return Err(self.expected("test block")),
+        };
+
+        let id = self.node_ids.next_function();
+        let span = self
+            .advance()
+            .expect("test block token should be available")
+            .span();
+
+        if kind == TestBlockKind::SetupAll && describe.is_some() {
+            return Err(ParserError::at_span(
+                "setup_all is not allowed inside describe; move it to the module body",
+                span,
+            ));
+        }
+
+        let (name, construct) = match kind {
+            TestBlockKind::Test => {
+                let name = self.expect_test_name("test name")?;
+                let construct = format!("test \"{name}\"");
+                (Some(name), construct)
+            }
+            TestBlockKind::Setup => (None, "setup".to_string()),
+            TestBlockKind::SetupAll => (None, "setup_all".to_string()),
+        };
+
+        if let Some(name) = &name {
+            let duplicate = test_blocks.iter().any(|block| {
+                block.kind == TestBlockKind::Test
+                    && block.describe.as_deref() == describe
+                    && block.name.as_ref() == Some(name)
+            });
+            if duplicate {
+                let scope = describe
+                    .map(|describe| format!(" in describe \"{describe}\""))
+                    .unwrap_or_default();
+                return Err(ParserError::at_span(
+                    format!("{construct} is already defined{scope}"),
+                    span,
+                ));
+            }
+        }
+
+        // `test "name", ctx do` and `setup ctx do` receive the test context.
+        let takes_context = match kind {
+            TestBlockKind::Test => self.match_kind(TokenKind::Comma),
+            TestBlockKind::Setup | TestBlockKind::SetupAll => !self.check(TokenKind::Do),
+        };
+        let params = if takes_context {
+            vec![self.parse_param(0)?]
+        } else {
+            Vec::new()
+        };
+
+        self.expect_block_do(
+            &construct,
+            span,
+            format!("add 'do' after '{construct}' to begin its body"),
+        )?;
+        let body = self.parse_block_body()?;
+        self.expect_block_end(&construct, span)?;
+
+        let function = test_block_function_name(kind, describe, name.as_deref(), test_blocks);
+        functions.push(Function::with_id(
+            id,
+            span.start(),
+            function.clone(),
+            FunctionVisibility::Public,
+            params,
+            None,
+            body,
+        ));
+        test_blocks.push(TestBlock {
+            offset: span.start(),
+            kind,
+            name,
+            describe: describe.map(str::to_string),
+            function,
+        });
+
+        Ok(())
+    }
+
+    fn parse_describe_block(
+        &mut self,
+        outer: Option<&str>,
+        functions: &mut Vec<Function>,
+        attributes: &mut Vec<ModuleAttribute>,
+        test_blocks: &mut Vec<TestBlock>,
+    ) -> Result<(), ParserError> {
+        let span = self
+            .advance()
+            .expect("describe token should be available")
+            .span();
+
+        if outer.is_some() {
+            return Err(ParserError::at_span(
+                "describe blocks cannot be nested; use a longer describe name instead",
+                span,
+            ));
+        }
+
+        let name = self.expect_test_name("describe name")?;
+        if test_blocks
+            .iter()
+            .any(|block| block.describe.as_deref() == Some(name.as_str()))
+        {
+            return Err(ParserError::at_span(
+                format!("describe \"{name}\" is already defined"),
+                span,
+            ));
+        }
+
+        let construct = format!("describe \"{name}\"");
+        self.expect_block_do(
+            &construct,
+            span,
+            format!("add 'do' after '{construct}' to begin the group"),
+        )?;
+
+        while !self.check(TokenKind::End) {
+            if self.is_at_end() {
+                return Err(self.missing_end_error(&construct, span));
+            }
+
+            if self.check(TokenKind::At) {
+                attributes.push(self.parse_module_attribute()?);
+                continue;
+            }
+
+            if self.current_starts_test_block() {
+                self.parse_test_block(Some(&name), functions, attributes, test_blocks)?;
+                continue;
+            }
+
+            return Err(self.expected("test, setup or @tag inside describe"));
+        }
+
+        self.expect_block_end(&construct, span)
+    }
+
+    fn expect_test_name(&mut self, expected: &str) -> Result<String, ParserError> {
+        Ok(self
+            .expect_token(TokenKind::String, expected)?
+            .lexeme()
+            .to_string())
+    }
+}
+
+/// Name of the function a test block is lowered to. Test names become
+/// identifiers (`describe "math"` + `test "adds 1 + 1"` gives
+/// `__test_math_adds_1_1`); setup blocks are numbered per module.
+fn test_block_function_name(
+    kind: TestBlockKind,
+    describe: Option<&str>,
+    name: Option<&str>,
+    test_blocks: &[TestBlock],
+) -> String {
+    let prefix = match kind {
+        TestBlockKind::Test => "__test",
+        TestBlockKind::Setup => "__setup",
+        TestBlockKind::SetupAll => "__setup_all",
+    };
+    if kind != TestBlockKind::Test {
+        let index = test_blocks
+            .iter()
+            .filter(|block| block.kind == kind)
+            .count()
+            + 1;
+        return format!("{prefix}_{index}");
+    }
+
+    let mut base = prefix.to_string();
+    let words = [describe, name]
+        .into_iter()
+        .flatten()
+        .flat_map(|text| text.split(|ch: char| !ch.is_ascii_alphanumeric()))
+        .filter(|word| !word.is_empty());
+    for word in words {
+        base.push('_');
+        base.push_str(&word.to_ascii_lowercase());
+    }
+
+    let mut function = base.clone();
+    let mut suffix = 1;
+    while test_blocks.iter().any(|block| block.function == function) {
+        suffix += 1;
+        function = format!("{base}_{suffix}");
+    }
+    function
+}
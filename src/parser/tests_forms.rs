@@ -1119,6 +1119,79 @@ fn parse_ast_rejects_unsupported_for_options() {
     );
 }
 
+#[test]
+fn parse_ast_lowers_check_all_to_property_test_calls() {
+    let tokens = scan_tokens(
+        "defmodule Demo do\n  def run() do\n    check all x <- PropertyTest.integer(), {a, _} <- gen(), max_runs: 10 do\n      x + a\n    end\n  end\nend\n",
+    )
+    .expect("scanner should tokenize parser fixture");
+
+    let ast = parse_ast(&tokens).expect("parser should lower check all forms");
+    let body_json = serde_json::to_value(&ast.modules[0].functions[0].body)
+        .expect("expression should serialize");
+
+    assert_eq!(body_json["kind"], "call");
+    assert_eq!(body_json["callee"], "PropertyTest.check_all");
+    assert_eq!(body_json["args"][1]["value"], 10);
+
+    let first = &body_json["args"][0];
+    assert_eq!(first["callee"], "PropertyTest.bind");
+    assert_eq!(first["args"][1]["params"][0], "x");
+
+    let second = &first["args"][1]["body"];
+    assert_eq!(second["callee"], "PropertyTest.bind");
+    assert_eq!(second["args"][0]["callee"], "PropertyTest.filter");
+    assert_eq!(second["args"][1]["params"][0], "__check1");
+    assert_eq!(second["args"][1]["body"]["kind"], "case");
+
+    let case = &second["args"][1]["body"]["branches"][0]["body"];
+    assert_eq!(case["callee"], "PropertyTest.constant");
+    assert_eq!(
+        case["args"][0]["items"][0]["items"][1]["items"][0]["value"],
+        "{a, _}"
+    );
+}
+
+#[test]
+fn parse_ast_lowers_match_question_and_assert_match_question() {
+    let tokens = scan_tokens(
+        "defmodule Demo do\n  def run(v) do\n    ok = match?({:ok, _}, v)\n    Assert.assert(match?(%{id: ^ok}, v), \"bad\")\n  end\nend\n",
+    )
+    .expect("scanner should tokenize parser fixture");
+
+    let ast = parse_ast(&tokens).expect("parser should lower match? forms");
+    let body_json = serde_json::to_value(&ast.modules[0].functions[0].body)
+        .expect("expression should serialize");
+
+    let question = &body_json["exprs"][0]["right"];
+    assert_eq!(question["kind"], "case");
+    assert_eq!(question["branches"][0]["body"]["value"], true);
+    assert_eq!(question["branches"][1]["pattern"]["kind"], "wildcard");
+
+    let assertion = &body_json["exprs"][1];
+    assert_eq!(assertion["callee"], "Assert.assert_pattern");
+    assert_eq!(assertion["args"][0]["name"], "v");
+    assert_eq!(assertion["args"][1]["params"][0], "__match");
+    assert_eq!(assertion["args"][2]["value"], "%{id: ^ok}");
+    assert_eq!(assertion["args"][3]["items"][0]["value"], "map");
+    assert_eq!(assertion["args"][4]["value"], "bad");
+}
+
+#[test]
+fn parse_ast_rejects_unsupported_check_all_options() {
+    let tokens = scan_tokens(
+        "defmodule Demo do\n  def run() do\n    check all x <- gen(), runs: 10 do\n      x\n    end\n  end\nend\n",
+    )
+    .expect("scanner should tokenize parser fixture");
+
+    let error = parse_ast(&tokens).expect_err("parser should reject unsupported check all options");
+
+    assert_eq!(
+        error.to_string(),
+        "unsupported check all option 'runs'; supported option is max_runs at offset 59"
+    );
+}
+
 #[test]
 fn parse_ast_rejects_non_trailing_default_params() {
     let tokens = scan_tokens(
@@ -1425,3 +1498,20 @@ fn parse_ast_reports_stray_block_boundary_keywords() {
         );
     }
 }
+
+#[test]
+fn parse_ast_accepts_trailing_commas_in_multiline_collections_and_calls() {
+    let tokens = scan_tokens(
+        "defmodule Demo do\n  def run() do\n    add(\n      [1, 2,],\n      {3,},\n      %{a: 1, \"b\" => 2,},\n      [c: 3,],\n    )\n  end\nend\n",
+    )
+    .expect("scanner should tokenize parser fixture");
+
+    let ast = parse_ast(&tokens).expect("parser should accept trailing commas");
+    let Expr::Call { args, .. } = &ast.modules[0].functions[0].body else {
+        panic!(
+            "expected call body, got {:?}",
+            ast.modules[0].functions[0].body
+        );
+    };
+    assert_eq!(args.len(), 4);
+}
@@ -73,6 +73,70 @@ fn parse_ast_supports_module_forms_and_attributes() {
     );
 }
 
+#[test]
+fn parse_ast_supports_doctest_module_form() {
+    let tokens = scan_tokens(
+        "defmodule MathTest do\n  doctest Math\n\n  def test_ok() do\n    :ok\n  end\nend\n",
+    )
+    .expect("scanner should tokenize parser fixture");
+
+    let ast = parse_ast(&tokens).expect("parser should produce ast");
+
+    assert_eq!(
+        serde_json::to_value(&ast.modules[0].forms).expect("module forms should serialize"),
+        serde_json::json!([{"kind":"doctest","module":"Math"}])
+    );
+}
+
+#[test]
+fn parse_ast_lowers_describe_and_test_blocks_to_functions() {
+    let tokens = scan_tokens(
+        "defmodule MathTest do\n  setup_all do\n    :ok\n  end\n\n  describe \"add\" do\n    setup ctx do\n      ctx\n    end\n\n    test \"adds 1 + 1\", ctx do\n      ctx\n    end\n  end\n\n  test \"plain\" do\n    :ok\n  end\nend\n",
+    )
+    .expect("scanner should tokenize parser fixture");
+
+    let ast = parse_ast(&tokens).expect("parser should produce ast");
+    let module = &ast.modules[0];
+
+    assert_eq!(
+        serde_json::to_value(&module.test_blocks).expect("test blocks should serialize"),
+        serde_json::json!([
+            {"kind":"setup_all","function":"__setup_all_1"},
+            {"kind":"setup","describe":"add","function":"__setup_1"},
+            {"kind":"test","name":"adds 1 + 1","describe":"add","function":"__test_add_adds_1_1"},
+            {"kind":"test","name":"plain","function":"__test_plain"}
+        ])
+    );
+    assert_eq!(
+        module
+            .functions
+            .iter()
+            .map(|function| (function.name.as_str(), function.params.len()))
+            .collect::<Vec<_>>(),
+        vec![
+            ("__setup_all_1", 0),
+            ("__setup_1", 1),
+            ("__test_add_adds_1_1", 1),
+            ("__test_plain", 0)
+        ]
+    );
+}
+
+#[test]
+fn parse_ast_rejects_nested_describe_blocks() {
+    let tokens = scan_tokens(
+        "defmodule MathTest do\n  describe \"outer\" do\n    describe \"inner\" do\n    end\n  end\nend\n",
+    )
+    .expect("scanner should tokenize parser fixture");
+
+    let error = parse_ast(&tokens).expect_err("parser should reject nested describe");
+
+    assert_eq!(
+        error.to_string(),
+        "describe blocks cannot be nested; use a longer describe name instead at offset 48"
+    );
+}
+
 #[test]
 fn parse_ast_canonicalizes_use_calls_when_no_explicit_imports() {
     let tokens = scan_tokens(
@@ -18,6 +18,7 @@ pub enum ResolverDiagnosticCode {
     AmbiguousImportCall,
     #[allow(dead_code)]
     GuardBuiltinOutsideGuard,
+    UndefinedDoctestModule,
 }
 
 impl ResolverDiagnosticCode {
@@ -38,6 +39,7 @@ impl ResolverDiagnosticCode {
             Self::ImportFilterExcludesCall => "E1013",
             Self::AmbiguousImportCall => "E1014",
             Self::GuardBuiltinOutsideGuard => "E1015",
+            Self::UndefinedDoctestModule => "E1016",
         }
     }
 }
@@ -180,6 +182,11 @@ impl ResolverError {
             format!("used module '{used_module}' is not defined for {module}; add the module or remove use"))
     }
 
+    pub fn undefined_doctest_module(doctest_module: &str, module: &str) -> Self {
+        Self::new(ResolverDiagnosticCode::UndefinedDoctestModule,
+            format!("doctest module '{doctest_module}' is not defined for {module}; add the module or remove doctest"))
+    }
+
     pub fn import_filter_excludes_call(
         function: &str,
         arity: usize,
@@ -456,6 +463,17 @@ mod tests {
         );
     }
 
+    #[test]
+    fn undefined_doctest_module_constructor_uses_stable_code_and_message() {
+        let error = ResolverError::undefined_doctest_module("Math", "MathTest");
+
+        assert_eq!(error.code(), ResolverDiagnosticCode::UndefinedDoctestModule);
+        assert_eq!(
+            error.to_string(),
+            "[E1016] doctest module 'Math' is not defined for MathTest; add the module or remove doctest"
+        );
+    }
+
     #[test]
     fn import_filter_excludes_call_constructor_uses_stable_code_and_message() {
         let error = ResolverError::import_filter_excludes_call(
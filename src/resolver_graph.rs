@@ -248,6 +248,14 @@ impl ModuleGraph {
                             ));
                         }
                     }
+                    ModuleForm::Doctest {
+                        module: doctest_module,
+                    } if !modules.contains_key(doctest_module) => {
+                        return Err(ResolverError::undefined_doctest_module(
+                            doctest_module,
+                            &module.name,
+                        ));
+                    }
                     _ => {}
                 }
             }
@@ -81,7 +81,7 @@ impl RuntimeValue {
         }
     }
 
-    fn kind_label(&self) -> &'static str {
+    pub(crate) fn kind_label(&self) -> &'static str {
         match self {
             Self::Int(_) => "int",
             Self::Float(_) => "float",
@@ -192,6 +192,14 @@ pub fn evaluate_named_function(
     evaluate_function(program, function_name, &[], 0)
 }
 
+pub(crate) fn evaluate_named_function_with_args(
+    program: &IrProgram,
+    function_name: &str,
+    args: &[RuntimeValue],
+) -> Result<RuntimeValue, RuntimeError> {
+    evaluate_function(program, function_name, args, 0)
+}
+
 fn evaluate_function(
     program: &IrProgram,
     function_name: &str,
@@ -262,6 +270,8 @@ fn evaluate_function(
             }
         }
 
+        let _frame = debug::enter_frame(function_name, call_offset);
+        let _profiled = profile::enter_function(function_name);
         let mut stack: Vec<RuntimeValue> = Vec::new();
 
         if let Some(ret) = evaluate_ops(program, &function.ops, &mut env, &mut stack)? {
@@ -282,6 +292,12 @@ fn evaluate_function(
 #[path = "runtime_try.rs"]
 mod try_helper;
 
+#[path = "runtime_debug.rs"]
+pub(crate) mod debug;
+
+#[path = "runtime_profile.rs"]
+pub(crate) mod profile;
+
 #[path = "runtime_eval.rs"]
 mod eval;
 pub(crate) use eval::evaluate_ops;
@@ -289,6 +305,7 @@ pub(crate) use eval::evaluate_ops;
 #[path = "runtime_for.rs"]
 mod for_comp;
 use for_comp::*;
+pub(crate) use for_comp::{collect_op_offsets, ir_op_offset};
 
 #[cfg(test)]
 #[path = "runtime_tests.rs"]
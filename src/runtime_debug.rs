@@ -0,0 +1,127 @@
+//! Interpreter debug hooks.
+//!
+//! A debugger (see `crate::dap`) installs a [`DebugHook`] for the duration of an
+//! evaluation. The evaluator reports every IR op it is about to execute together
+//! with the current call-frame stack, which lets the hook pause execution for
+//! breakpoints and stepping. When no hook is installed the per-op cost is a
+//! single thread-local flag check.
+
+use super::{RuntimeError, RuntimeValue};
+use crate::ir::IrOp;
+use std::cell::{Cell, RefCell};
+use std::collections::HashMap;
+
+/// A call frame as seen by the debugger.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct DebugFrame {
+    /// Qualified function name (`Module.fun`) or `fn` for anonymous closures.
+    pub(crate) name: String,
+    /// Source offset of the op most recently executed in this frame.
+    pub(crate) offset: usize,
+    /// Bindings captured when this frame last made a call. The innermost frame's
+    /// live bindings are passed to [`DebugHook::on_op`] directly instead.
+    pub(crate) env: HashMap<String, RuntimeValue>,
+}
+
+/// Receives interpreter progress while a debug session is active.
+///
+/// The hook is detached while its callback runs, so any evaluation the hook
+/// performs itself (conditional breakpoints, watch expressions) runs undebugged.
+pub(crate) trait DebugHook {
+    /// Called before `op` executes. `frames` is ordered outermost first and the
+    /// last frame owns `env`. Returning an error aborts the evaluation with
+    /// that message.
+    fn on_op(
+        &mut self,
+        offset: usize,
+        frames: &[DebugFrame],
+        env: &HashMap<String, RuntimeValue>,
+    ) -> Result<(), String>;
+}
+
+#[derive(Default)]
+struct DebugState {
+    hook: Option<Box<dyn DebugHook>>,
+    frames: Vec<DebugFrame>,
+}
+
+thread_local! {
+    static DEBUG_ACTIVE: Cell<bool> = const { Cell::new(false) };
+    static DEBUG_STATE: RefCell<DebugState> = RefCell::new(DebugState::default());
+}
+
+/// Run `f` with `hook` observing every op evaluated on this thread.
+pub(crate) fn with_debug_hook<T>(hook: Box<dyn DebugHook>, f: impl FnOnce() -> T) -> T {
+    DEBUG_STATE.with(|state| {
+        *state.borrow_mut() = DebugState {
+            hook: Some(hook),
+            frames: Vec::new(),
+        }
+    });
+    DEBUG_ACTIVE.with(|active| active.set(true));
+
+    let result = f();
+
+    DEBUG_ACTIVE.with(|active| active.set(false));
+    DEBUG_STATE.with(|state| *state.borrow_mut() = DebugState::default());
+    result
+}
+
+/// Pops the frame pushed by [`enter_frame`] when the callee finishes.
+pub(super) struct FrameGuard;
+
+impl Drop for FrameGuard {
+    fn drop(&mut self) {
+        DEBUG_STATE.with(|state| {
+            state.borrow_mut().frames.pop();
+        });
+    }
+}
+
+pub(super) fn enter_frame(name: &str, offset: usize) -> Option<FrameGuard> {
+    if !DEBUG_ACTIVE.with(Cell::get) {
+        return None;
+    }
+
+    DEBUG_STATE.with(|state| {
+        state.borrow_mut().frames.push(DebugFrame {
+            name: name.to_string(),
+            offset,
+            env: HashMap::new(),
+        })
+    });
+    Some(FrameGuard)
+}
+
+pub(super) fn on_op(op: &IrOp, env: &HashMap<String, RuntimeValue>) -> Result<(), RuntimeError> {
+    // `Drop` has no offset and `Return` reuses the body's start offset, so
+    // neither marks a new source position worth reporting.
+    if matches!(op, IrOp::Drop | IrOp::Return { .. }) || !DEBUG_ACTIVE.with(Cell::get) {
+        return Ok(());
+    }
+
+    let offset = super::ir_op_offset(op);
+    DEBUG_ACTIVE.with(|active| active.set(false));
+
+    let result = DEBUG_STATE.with(|state| {
+        let mut state = state.borrow_mut();
+        let DebugState { hook, frames } = &mut *state;
+
+        if let Some(frame) = frames.last_mut() {
+            frame.offset = offset;
+            if matches!(op, IrOp::Call { .. } | IrOp::CallValue { .. }) {
+                frame.env = env.clone();
+            }
+        }
+
+        match hook.as_mut() {
+            Some(hook) => hook
+                .on_op(offset, frames, env)
+                .map_err(|message| RuntimeError::at_offset(message, offset)),
+            None => Ok(()),
+        }
+    });
+
+    DEBUG_ACTIVE.with(|active| active.set(true));
+    result
+}
@@ -8,6 +8,9 @@ pub(crate) fn evaluate_ops(
     stack: &mut Vec<RuntimeValue>,
 ) -> Result<Option<RuntimeValue>, RuntimeError> {
     for op in ops {
+        debug::on_op(op, env)?;
+        profile::on_op(op);
+
         match op {
             IrOp::ConstInt { value, .. } => stack.push(RuntimeValue::Int(*value)),
             IrOp::ConstFloat { value, .. } => stack.push(RuntimeValue::Float(value.clone())),
@@ -266,8 +266,17 @@ pub(super) fn evaluate_call(
 
     match callee {
         IrCallTarget::Function { name } => evaluate_function(program, name, &args, offset),
-        IrCallTarget::Builtin { name } => native_runtime::evaluate_builtin_call(name, args, offset)
-            .map_err(map_native_runtime_error),
+        IrCallTarget::Builtin { name } => {
+            // `Mock.stub` closures replace host functions for the current test.
+            if let Some(RuntimeValue::Atom(key)) = args.first().filter(|_| name == "host_call") {
+                if let Some(stub) = crate::interop::host_stub(key) {
+                    let host_args = RuntimeValue::List(args[1..].to_vec());
+                    return call_value(program, stub, vec![host_args], offset);
+                }
+            }
+            native_runtime::evaluate_builtin_call(name, args, offset)
+                .map_err(map_native_runtime_error)
+        }
     }
 }
 
@@ -281,7 +290,15 @@ pub(super) fn evaluate_call_value(
     let callee = stack
         .pop()
         .ok_or_else(|| RuntimeError::at_offset("empty stack", offset))?;
+    call_value(program, callee, args, offset)
+}
 
+fn call_value(
+    program: &IrProgram,
+    callee: RuntimeValue,
+    args: Vec<RuntimeValue>,
+    offset: usize,
+) -> Result<RuntimeValue, RuntimeError> {
     match callee {
         RuntimeValue::Closure(closure) => {
             if closure.params.len() != args.len() {
@@ -300,6 +317,8 @@ pub(super) fn evaluate_call_value(
                 closure_env.insert(param.clone(), arg.clone());
             }
 
+            let _frame = debug::enter_frame("fn", offset);
+            let _profiled = profile::enter_closure();
             let mut closure_stack = Vec::new();
             if let Some(ret) =
                 evaluate_ops(program, &closure.ops, &mut closure_env, &mut closure_stack)?
@@ -348,7 +367,7 @@ pub(super) fn match_pattern(
     native_match(value, pattern, env, bindings)
 }
 
-pub(super) fn ir_op_offset(op: &IrOp) -> usize {
+pub(crate) fn ir_op_offset(op: &IrOp) -> usize {
     match op {
         IrOp::ConstInt { offset, .. } => *offset,
         IrOp::ConstFloat { offset, .. } => *offset,
@@ -399,6 +418,54 @@ pub(super) fn ir_op_offset(op: &IrOp) -> usize {
     }
 }
 
+/// Append the source offset of every op in `ops` that debug hooks observe,
+/// descending into nested op lists (closures, branches, comprehensions).
+/// `Drop` and `Return` are never reported to hooks, so they are skipped.
+pub(crate) fn collect_op_offsets(ops: &[IrOp], offsets: &mut Vec<usize>) {
+    for op in ops {
+        match op {
+            IrOp::Drop | IrOp::Return { .. } => continue,
+            IrOp::MakeClosure { ops, .. } => collect_op_offsets(ops, offsets),
+            IrOp::AndAnd { right_ops, .. }
+            | IrOp::OrOr { right_ops, .. }
+            | IrOp::And { right_ops, .. }
+            | IrOp::Or { right_ops, .. } => collect_op_offsets(right_ops, offsets),
+            IrOp::Case { branches, .. } => {
+                for branch in branches {
+                    collect_op_offsets(&branch.ops, offsets);
+                }
+            }
+            IrOp::Try {
+                body_ops,
+                rescue_branches,
+                catch_branches,
+                after_ops,
+                ..
+            } => {
+                collect_op_offsets(body_ops, offsets);
+                for branch in rescue_branches.iter().chain(catch_branches) {
+                    collect_op_offsets(&branch.ops, offsets);
+                }
+                if let Some(after_ops) = after_ops {
+                    collect_op_offsets(after_ops, offsets);
+                }
+            }
+            IrOp::For {
+                generators,
+                body_ops,
+                ..
+            } => {
+                for generator in generators {
+                    collect_op_offsets(&generator.source_ops, offsets);
+                }
+                collect_op_offsets(body_ops, offsets);
+            }
+            _ => {}
+        }
+        offsets.push(ir_op_offset(op));
+    }
+}
+
 pub(super) fn map_native_runtime_error(err: native_runtime::NativeRuntimeError) -> RuntimeError {
     RuntimeError {
         message: err.message().to_string(),
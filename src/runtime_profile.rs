@@ -0,0 +1,393 @@
+//! Interpreter function profiler for `tonic run --profile`.
+//!
+//! While a profile is active, every named function and closure call records
+//! its call count, inclusive and exclusive wall time, and the heap values its
+//! own ops construct (strings, lists, maps, keywords, tuples, results and
+//! closures; values built inside host functions are not counted). When no
+//! profile is active the per-call and per-op cost is a single thread-local
+//! flag check, as for [`super::debug`].
+
+use crate::ir::{IrCallTarget, IrOp};
+use serde_json::{json, Value};
+use std::cell::{Cell, RefCell};
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+/// Trace events kept for `--profile`'s Chrome trace; later calls still count
+/// towards the table and collapsed stacks.
+const MAX_TRACE_EVENTS: usize = 100_000;
+
+/// Closures have no name of their own.
+const CLOSURE_NAME: &str = "fn";
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub(crate) struct FunctionStats {
+    pub calls: u64,
+    /// Time from entry to exit; recursive calls are only counted at the
+    /// outermost level so the total never exceeds the run time.
+    pub inclusive: Duration,
+    /// Inclusive time minus the time spent in callees.
+    pub exclusive: Duration,
+    pub allocations: u64,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct TraceEvent {
+    name: String,
+    start: Duration,
+    duration: Duration,
+}
+
+/// The result of a profiled evaluation.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub(crate) struct FunctionProfile {
+    pub total: Duration,
+    pub functions: HashMap<String, FunctionStats>,
+    /// Exclusive time per call stack, outermost function first.
+    stacks: HashMap<Vec<String>, Duration>,
+    events: Vec<TraceEvent>,
+    dropped_events: usize,
+}
+
+struct OpenCall {
+    name: String,
+    started: Instant,
+    child_time: Duration,
+    allocations: u64,
+}
+
+#[derive(Default)]
+struct ProfileState {
+    profile: FunctionProfile,
+    stack: Vec<OpenCall>,
+    started: Option<Instant>,
+}
+
+thread_local! {
+    static PROFILE_ACTIVE: Cell<bool> = const { Cell::new(false) };
+    static PROFILE_STATE: RefCell<ProfileState> = RefCell::new(ProfileState::default());
+}
+
+/// Run `f` with every function call on this thread profiled.
+pub(crate) fn with_profiler<T>(f: impl FnOnce() -> T) -> (T, FunctionProfile) {
+    let started = Instant::now();
+    PROFILE_STATE.with(|state| {
+        *state.borrow_mut() = ProfileState {
+            started: Some(started),
+            ..ProfileState::default()
+        }
+    });
+    PROFILE_ACTIVE.with(|active| active.set(true));
+
+    let result = f();
+
+    PROFILE_ACTIVE.with(|active| active.set(false));
+    let mut profile = PROFILE_STATE
+        .with(|state| std::mem::take(&mut *state.borrow_mut()))
+        .profile;
+    profile.total = started.elapsed();
+    (result, profile)
+}
+
+/// Records the call opened by [`enter_function`] when the callee finishes.
+pub(super) struct ProfileGuard;
+
+impl Drop for ProfileGuard {
+    fn drop(&mut self) {
+        PROFILE_STATE.with(|state| state.borrow_mut().exit());
+    }
+}
+
+pub(super) fn enter_function(name: &str) -> Option<ProfileGuard> {
+    if !PROFILE_ACTIVE.with(Cell::get) {
+        return None;
+    }
+
+    PROFILE_STATE.with(|state| {
+        state.borrow_mut().stack.push(OpenCall {
+            name: name.to_string(),
+            started: Instant::now(),
+            child_time: Duration::ZERO,
+            allocations: 0,
+        })
+    });
+    Some(ProfileGuard)
+}
+
+pub(super) fn enter_closure() -> Option<ProfileGuard> {
+    enter_function(CLOSURE_NAME)
+}
+
+pub(super) fn on_op(op: &IrOp) {
+    if !PROFILE_ACTIVE.with(Cell::get) || !allocates(op) {
+        return;
+    }
+
+    PROFILE_STATE.with(|state| {
+        if let Some(call) = state.borrow_mut().stack.last_mut() {
+            call.allocations += 1;
+        }
+    });
+}
+
+/// Ops that construct a new heap value.
+fn allocates(op: &IrOp) -> bool {
+    match op {
+        IrOp::ConstString { .. }
+        | IrOp::ToString { .. }
+        | IrOp::Concat { .. }
+        | IrOp::PlusPlus { .. }
+        | IrOp::MinusMinus { .. }
+        | IrOp::MakeClosure { .. } => true,
+        IrOp::Call {
+            callee: IrCallTarget::Builtin { name },
+            ..
+        } => matches!(
+            name.as_str(),
+            "ok" | "err"
+                | "tuple"
+                | "list"
+                | "map_empty"
+                | "map"
+                | "map_put"
+                | "map_update"
+                | "keyword"
+                | "keyword_append"
+        ),
+        _ => false,
+    }
+}
+
+impl ProfileState {
+    fn exit(&mut self) {
+        let Some(call) = self.stack.pop() else {
+            return;
+        };
+        let elapsed = call.started.elapsed();
+        let exclusive = elapsed.saturating_sub(call.child_time);
+        let recursive = self.stack.iter().any(|open| open.name == call.name);
+
+        let stats = self.profile.functions.entry(call.name.clone()).or_default();
+        stats.calls += 1;
+        stats.exclusive += exclusive;
+        stats.allocations += call.allocations;
+        if !recursive {
+            stats.inclusive += elapsed;
+        }
+
+        let mut path = self
+            .stack
+            .iter()
+            .map(|open| open.name.clone())
+            .collect::<Vec<_>>();
+        path.push(call.name.clone());
+        *self.profile.stacks.entry(path).or_default() += exclusive;
+
+        if self.profile.events.len() < MAX_TRACE_EVENTS {
+            let start = self
+                .started
+                .map(|started| call.started.saturating_duration_since(started))
+                .unwrap_or_default();
+            self.profile.events.push(TraceEvent {
+                name: call.name,
+                start,
+                duration: elapsed,
+            });
+        } else {
+            self.profile.dropped_events += 1;
+        }
+
+        if let Some(parent) = self.stack.last_mut() {
+            parent.child_time += elapsed;
+        }
+    }
+}
+
+impl FunctionProfile {
+    /// Functions by exclusive time, slowest first.
+    pub(crate) fn ranked(&self) -> Vec<(&str, &FunctionStats)> {
+        let mut ranked = self
+            .functions
+            .iter()
+            .map(|(name, stats)| (name.as_str(), stats))
+            .collect::<Vec<_>>();
+        ranked.sort_by(|left, right| {
+            right
+                .1
+                .exclusive
+                .cmp(&left.1.exclusive)
+                .then_with(|| left.0.cmp(right.0))
+        });
+        ranked
+    }
+
+    /// The `top` functions by exclusive time as a text table.
+    pub(crate) fn render_table(&self, top: usize) -> Vec<String> {
+        let calls = self
+            .functions
+            .values()
+            .map(|stats| stats.calls)
+            .sum::<u64>();
+        let mut lines = vec![format!(
+            "profile: {calls} call{} in {} function{}, {}",
+            if calls == 1 { "" } else { "s" },
+            self.functions.len(),
+            if self.functions.len() == 1 { "" } else { "s" },
+            format_ms(self.total)
+        )];
+
+        let ranked = self.ranked().into_iter().take(top).collect::<Vec<_>>();
+        if ranked.is_empty() {
+            return lines;
+        }
+        let width = ranked
+            .iter()
+            .map(|(name, _)| name.chars().count())
+            .max()
+            .unwrap_or(0)
+            .max("Function".len())
+            + 2;
+
+        lines.push(format!(
+            "{:<width$}{:>10}{:>14}{:>14}{:>8}{:>10}",
+            "Function", "calls", "inclusive", "exclusive", "excl%", "allocs"
+        ));
+        let total = self.total.as_secs_f64().max(f64::MIN_POSITIVE);
+        for (name, stats) in &ranked {
+            lines.push(format!(
+                "{name:<width$}{:>10}{:>14}{:>14}{:>8}{:>10}",
+                stats.calls,
+                format_ms(stats.inclusive),
+                format_ms(stats.exclusive),
+                format!("{:.1}", stats.exclusive.as_secs_f64() / total * 100.0),
+                stats.allocations
+            ));
+        }
+        if self.functions.len() > ranked.len() {
+            lines.push(format!(
+                "... {} more (see --profile-top)",
+                self.functions.len() - ranked.len()
+            ));
+        }
+        lines
+    }
+
+    /// Collapsed stacks (`outer;inner <weight>`) for flamegraph tools, one
+    /// line per call stack weighted by exclusive time in microseconds.
+    pub(crate) fn render_collapsed(&self) -> String {
+        let mut lines = self
+            .stacks
+            .iter()
+            .map(|(path, time)| format!("{} {}", path.join(";"), time.as_micros()))
+            .collect::<Vec<_>>();
+        lines.sort();
+        let mut out = lines.join("\n");
+        out.push('\n');
+        out
+    }
+
+    /// Chrome trace-event JSON (complete events), for `chrome://tracing`,
+    /// Perfetto or speedscope.
+    pub(crate) fn render_chrome_trace(&self) -> Value {
+        let mut events = self.events.clone();
+        events.sort_by_key(|event| event.start);
+        let events = events
+            .iter()
+            .map(|event| {
+                json!({
+                    "name": event.name,
+                    "cat": "function",
+                    "ph": "X",
+                    "ts": micros(event.start),
+                    "dur": micros(event.duration),
+                    "pid": 1,
+                    "tid": 1,
+                })
+            })
+            .collect::<Vec<_>>();
+        json!({
+            "traceEvents": events,
+            "displayTimeUnit": "ms",
+            "otherData": {
+                "dropped_events": self.dropped_events,
+            },
+        })
+    }
+}
+
+fn micros(duration: Duration) -> f64 {
+    duration.as_nanos() as f64 / 1000.0
+}
+
+fn format_ms(duration: Duration) -> String {
+    format!("{:.3} ms", duration.as_secs_f64() * 1000.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stats(calls: u64, inclusive_us: u64, exclusive_us: u64, allocations: u64) -> FunctionStats {
+        FunctionStats {
+            calls,
+            inclusive: Duration::from_micros(inclusive_us),
+            exclusive: Duration::from_micros(exclusive_us),
+            allocations,
+        }
+    }
+
+    #[test]
+    fn nested_and_recursive_calls_split_inclusive_and_exclusive_time() {
+        let ((), profile) = with_profiler(|| {
+            let _outer = enter_function("Demo.run");
+            for _ in 0..2 {
+                let _fib = enter_function("Demo.fib");
+                let _inner = enter_function("Demo.fib");
+                on_op(&IrOp::ConstString {
+                    value: "x".to_string(),
+                    offset: 0,
+                });
+                let _closure = enter_closure();
+            }
+        });
+
+        let fib = &profile.functions["Demo.fib"];
+        assert_eq!(fib.calls, 4);
+        assert_eq!(fib.allocations, 2);
+        assert!(fib.inclusive <= profile.functions["Demo.run"].inclusive);
+        assert_eq!(profile.functions[CLOSURE_NAME].calls, 2);
+        assert!(profile
+            .render_collapsed()
+            .lines()
+            .any(|line| line.starts_with("Demo.run;Demo.fib;Demo.fib;fn ")));
+        assert_eq!(
+            profile.render_chrome_trace()["traceEvents"][0]["name"],
+            "Demo.run"
+        );
+        assert!(enter_function("Demo.run").is_none());
+    }
+
+    #[test]
+    fn table_ranks_functions_by_exclusive_time() {
+        let profile = FunctionProfile {
+            total: Duration::from_millis(10),
+            functions: HashMap::from([
+                ("Demo.run".to_string(), stats(1, 10_000, 1_000, 3)),
+                ("Demo.slow".to_string(), stats(5, 9_000, 9_000, 40)),
+                ("fn".to_string(), stats(2, 10, 10, 0)),
+            ]),
+            ..FunctionProfile::default()
+        };
+
+        assert_eq!(
+            profile.render_table(2),
+            vec![
+                "profile: 8 calls in 3 functions, 10.000 ms",
+                "Function        calls     inclusive     exclusive   excl%    allocs",
+                "Demo.slow           5      9.000 ms      9.000 ms    90.0        40",
+                "Demo.run            1     10.000 ms      1.000 ms    10.0         3",
+                "... 1 more (see --profile-top)",
+            ]
+        );
+    }
+}
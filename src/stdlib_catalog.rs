@@ -13,6 +13,10 @@ pub(crate) const STDLIB_SOURCES: &[(&str, &str)] = &[
     ("Tuple", OPTIONAL_STDLIB_TUPLE_SOURCE),
     ("Assert", OPTIONAL_STDLIB_ASSERT_SOURCE),
     ("Json", OPTIONAL_STDLIB_JSON_SOURCE),
+    ("PropertyTest", OPTIONAL_STDLIB_PROPERTY_TEST_SOURCE),
+    ("Mock", OPTIONAL_STDLIB_MOCK_SOURCE),
+    ("Bench", OPTIONAL_STDLIB_BENCH_SOURCE),
+    ("CLI", OPTIONAL_STDLIB_CLI_SOURCE),
 ];
 
 pub(crate) fn stdlib_module_names() -> impl Iterator<Item = &'static str> {
@@ -1348,7 +1352,7 @@ end
 "#;
 
 pub(super) const OPTIONAL_STDLIB_ASSERT_SOURCE: &str =
-    "defmodule Assert do\n  def assert(value, message \\\\ nil) do\n    host_call(:assert, value, message)\n  end\n\n  def refute(value, message \\\\ nil) do\n    host_call(:refute, value, message)\n  end\n\n  def assert_equal(left, right, message \\\\ nil) do\n    host_call(:assert_equal, left, right, message)\n  end\n\n  def assert_not_equal(left, right, message \\\\ nil) do\n    host_call(:assert_not_equal, left, right, message)\n  end\n\n  def assert_contains(container, element, message \\\\ nil) do\n    host_call(:assert_contains, container, element, message)\n  end\n\n  def assert_in_delta(left, right, delta, message \\\\ nil) do\n    host_call(:assert_in_delta, left, right, delta, message)\n  end\n\n  def skip(reason \\\\ nil) do\n    host_call(:skip, reason)\n  end\n\n  def assert_match(expected, actual, message \\\\ nil) do\n    host_call(:assert_match, expected, actual, message)\n  end\n\n  def assert_raises(fun, expected \\\\ nil) do\n    check_raises(do_try_raises(fun), expected)\n  end\n\n  defp do_try_raises(fun) do\n    try do\n      fun.()\n      {:no_raise, :ok}\n    rescue\n      e -> {:raised, to_string(e)}\n    end\n  end\n\n  defp check_raises({:raised, _msg}, nil) do\n    :ok\n  end\n\n  defp check_raises({:raised, msg}, expected) do\n    host_call(:assert_raises_check, msg, expected)\n  end\n\n  defp check_raises(_, _expected) do\n    err({:assertion_failed, {:assert_raises, \"expected function to raise, but it returned normally\"}})\n  end\nend\n";
+    "defmodule Assert do\n  def assert(value, message \\\\ nil) do\n    host_call(:assert, value, message)\n  end\n\n  def refute(value, message \\\\ nil) do\n    host_call(:refute, value, message)\n  end\n\n  def assert_equal(left, right, message \\\\ nil) do\n    host_call(:assert_equal, left, right, message)\n  end\n\n  def assert_not_equal(left, right, message \\\\ nil) do\n    host_call(:assert_not_equal, left, right, message)\n  end\n\n  def assert_contains(container, element, message \\\\ nil) do\n    host_call(:assert_contains, container, element, message)\n  end\n\n  def assert_in_delta(left, right, delta, message \\\\ nil) do\n    host_call(:assert_in_delta, left, right, delta, message)\n  end\n\n  def skip(reason \\\\ nil) do\n    host_call(:skip, reason)\n  end\n\n  def assert_match(expected, actual, message \\\\ nil) do\n    host_call(:assert_match, expected, actual, message)\n  end\n\n  def assert_pattern(value, matches, pattern, tree, message \\\\ nil) do\n    case matches.(value) do\n      true -> :ok\n      _ -> host_call(:assert_pattern, value, pattern, tree, message)\n    end\n  end\n\n  def snapshot(value, name) do\n    host_call(:assert_snapshot, value, name)\n  end\n\n  def assert_raises(fun, expected \\\\ nil) do\n    check_raises(do_try_raises(fun), expected)\n  end\n\n  defp do_try_raises(fun) do\n    try do\n      fun.()\n      {:no_raise, :ok}\n    rescue\n      e -> {:raised, to_string(e)}\n    end\n  end\n\n  defp check_raises({:raised, _msg}, nil) do\n    :ok\n  end\n\n  defp check_raises({:raised, msg}, expected) do\n    host_call(:assert_raises_check, msg, expected)\n  end\n\n  defp check_raises(_, _expected) do\n    err({:assertion_failed, {:assert_raises, \"expected function to raise, but it returned normally\"}})\n  end\nend\n";
 
 pub(super) const OPTIONAL_STDLIB_JSON_SOURCE: &str = r#"defmodule Json do
   ## Decodes a JSON string into a Tonic value.
@@ -1470,3 +1474,470 @@ pub(super) const OPTIONAL_STDLIB_JSON_SOURCE: &str = r#"defmodule Json do
   end
 end
 "#;
+
+pub(super) const OPTIONAL_STDLIB_PROPERTY_TEST_SOURCE: &str = r#"defmodule PropertyTest do
+  ## Integers in min..max, shrinking towards the value closest to zero.
+  ## Without bounds, integers between -size and size; size grows with
+  ## each run.
+  ##
+  ## Parameters:
+  ##   min: integer — smallest value (default: -size)
+  ##   max: integer — largest value (default: size)
+  ##
+  ## Returns: generator
+  def integer(min \\ nil, max \\ nil) do
+    make(:integer, {min, max})
+  end
+
+  ## Floats with a whole part up to size and three decimals.
+  ##
+  ## Returns: generator
+  def float() do
+    make(:float, nil)
+  end
+
+  ## true or false, shrinking towards false.
+  ##
+  ## Returns: generator
+  def boolean() do
+    make(:boolean, nil)
+  end
+
+  ## Strings of up to size characters.
+  ##
+  ## Parameters:
+  ##   kind: atom — :alphanumeric, :ascii (printable) or :utf8
+  ##
+  ## Returns: generator
+  def string(kind \\ :alphanumeric) do
+    make(:string, kind)
+  end
+
+  ## Lowercase atoms such as :a or :key_1.
+  ##
+  ## Returns: generator
+  def atom() do
+    make(:atom, nil)
+  end
+
+  ## Lists of values from element, shrinking towards the empty list.
+  ##
+  ## Parameters:
+  ##   element: generator — generator for each element
+  ##   max_length: integer — longest list (default: size)
+  ##
+  ## Returns: generator
+  def list_of(element, max_length \\ nil) do
+    make(:list_of, {element, max_length})
+  end
+
+  ## Maps with keys from key and values from value.
+  ##
+  ## Returns: generator
+  def map_of(key, value) do
+    make(:map_of, {key, value})
+  end
+
+  ## Tuples whose elements come from a tuple of generators, e.g.
+  ## tuple({integer(), {boolean(), atom()}}).
+  ##
+  ## Returns: generator
+  def tuple(generators) do
+    make(:tuple, generators)
+  end
+
+  ## A value from one of the generators, shrinking towards the first.
+  ##
+  ## Returns: generator
+  def one_of(generators) do
+    make(:one_of, generators)
+  end
+
+  ## Like one_of, picking each generator in proportion to its weight:
+  ## frequency([{3, integer()}, {1, constant(nil)}]).
+  ##
+  ## Returns: generator
+  def frequency(weighted) do
+    make(:frequency, weighted)
+  end
+
+  ## One of the given values, shrinking towards the first.
+  ##
+  ## Returns: generator
+  def member_of(values) do
+    make(:member_of, values)
+  end
+
+  ## Always value.
+  ##
+  ## Returns: generator
+  def constant(value) do
+    make(:constant, value)
+  end
+
+  ## Generates a value, then generates from the generator fun returns for it.
+  ##
+  ## Returns: generator
+  def bind(gen, fun) do
+    make(:bind, {gen, fun})
+  end
+
+  ## Applies fun to each generated value.
+  ##
+  ## Returns: generator
+  def map(gen, fun) do
+    make(:map, {gen, fun})
+  end
+
+  ## Keeps the values fun accepts, retrying with a larger size after each
+  ## rejection and giving up after max_tries rejections in a row.
+  ##
+  ## Returns: generator
+  def filter(gen, fun, max_tries \\ 25) do
+    make(:filter, {gen, {fun, max_tries}})
+  end
+
+  ## Runs a property built by `check all`: a generator of
+  ## {counterexample, body} pairs. Returns :ok, or an err with the shrunk
+  ## counterexample of the first failing run.
+  ##
+  ## Parameters:
+  ##   property: generator
+  ##   max_runs: integer — number of cases to try (default: 100)
+  ##
+  ## Returns: :ok or err
+  def check_all(property, max_runs \\ 100) do
+    run_property(property, 0, max_runs)
+  end
+
+  defp make(kind, args) do
+    {:property_generator, {kind, args}}
+  end
+
+  defp run_property(_property, run, max_runs) when run >= max_runs do
+    :ok
+  end
+
+  defp run_property(property, run, max_runs) do
+    host_call(:property_start, run)
+    size = min(run, 100)
+
+    case run_case(property, size) do
+      :passed -> run_property(property, run + 1, max_runs)
+      failure -> shrink(property, size, host_call(:property_choices), failure, run + 1, 0)
+    end
+  end
+
+  defp run_case(property, size) do
+    {values, body} = generate(property, size)
+
+    case evaluate(body) do
+      :passed -> :passed
+      reason -> {values, reason}
+    end
+  end
+
+  defp evaluate(body) do
+    try do
+      case host_call(:property_outcome, body.()) do
+        {:failed, reason} -> reason
+        _ -> :passed
+      end
+    rescue
+      error -> {:property_raised, to_string(error)}
+    end
+  end
+
+  defp shrink(_property, _size, _choices, failure, runs, shrinks) when shrinks >= 500 do
+    report(failure, runs, shrinks)
+  end
+
+  defp shrink(property, size, choices, failure, runs, shrinks) do
+    case shrink_step(property, size, choices, host_call(:property_shrink, choices)) do
+      {smaller, smaller_failure} -> shrink(property, size, smaller, smaller_failure, runs, shrinks + 1)
+      _ -> report(failure, runs, shrinks)
+    end
+  end
+
+  defp shrink_step(_property, _size, _choices, []) do
+    nil
+  end
+
+  defp shrink_step(property, size, choices, [candidate | rest]) do
+    host_call(:property_replay, candidate)
+
+    case replay_case(property, size) do
+      :passed -> shrink_step(property, size, choices, rest)
+      failure -> accept_shrink(property, size, choices, rest, failure)
+    end
+  end
+
+  defp accept_shrink(property, size, choices, rest, failure) do
+    smaller = host_call(:property_choices)
+
+    if host_call(:property_simpler, smaller, choices) do
+      {smaller, failure}
+    else
+      shrink_step(property, size, choices, rest)
+    end
+  end
+
+  defp replay_case(property, size) do
+    try do
+      run_case(property, size)
+    rescue
+      _ -> :passed
+    end
+  end
+
+  defp report({values, reason}, runs, shrinks) do
+    err({:property_failed, %{runs: runs, seed: host_call(:property_seed), shrinks: shrinks, counterexample: values, reason: reason}})
+  end
+
+  defp generate({:property_generator, {:constant, value}}, _size) do
+    value
+  end
+
+  defp generate({:property_generator, {:integer, {nil, nil}}}, size) do
+    host_call(:property_integer, 0 - size, size)
+  end
+
+  defp generate({:property_generator, {:integer, {nil, max}}}, size) do
+    host_call(:property_integer, min(0 - size, max), max)
+  end
+
+  defp generate({:property_generator, {:integer, {min, nil}}}, size) do
+    host_call(:property_integer, min, max(min, size))
+  end
+
+  defp generate({:property_generator, {:integer, {min, max}}}, _size) do
+    host_call(:property_integer, min, max)
+  end
+
+  defp generate({:property_generator, {:float, _}}, size) do
+    host_call(:property_float, size)
+  end
+
+  defp generate({:property_generator, {:boolean, _}}, _size) do
+    host_call(:property_draw, 1) == 1
+  end
+
+  defp generate({:property_generator, {:string, kind}}, size) do
+    host_call(:enum_join, generate_list(make(:char, kind), size, size, []), "")
+  end
+
+  defp generate({:property_generator, {:char, kind}}, _size) do
+    host_call(:property_char, kind)
+  end
+
+  defp generate({:property_generator, {:atom, _}}, size) do
+    host_call(:property_atom, size)
+  end
+
+  defp generate({:property_generator, {:list_of, {element, nil}}}, size) do
+    generate_list(element, size, size, [])
+  end
+
+  defp generate({:property_generator, {:list_of, {element, max_length}}}, size) do
+    generate_list(element, size, max_length, [])
+  end
+
+  defp generate({:property_generator, {:map_of, {key, value}}}, size) do
+    for {k, v} <- generate_list({key, value}, size, size, []), into: %{} do
+      {k, v}
+    end
+  end
+
+  defp generate({:property_generator, {:tuple, generators}}, size) do
+    generate(generators, size)
+  end
+
+  defp generate({:property_generator, {:one_of, []}}, _size) do
+    raise "PropertyTest.one_of expects at least one generator"
+  end
+
+  defp generate({:property_generator, {:one_of, generators}}, size) do
+    generate(nth(generators, host_call(:property_draw, length(generators) - 1)), size)
+  end
+
+  defp generate({:property_generator, {:member_of, []}}, _size) do
+    raise "PropertyTest.member_of expects at least one value"
+  end
+
+  defp generate({:property_generator, {:member_of, values}}, _size) do
+    nth(values, host_call(:property_draw, length(values) - 1))
+  end
+
+  defp generate({:property_generator, {:frequency, weighted}}, size) do
+    pick = host_call(:property_draw, total_weight(weighted, 0) - 1)
+    generate(pick_weighted(weighted, pick), size)
+  end
+
+  defp generate({:property_generator, {:bind, {gen, fun}}}, size) do
+    generate(fun.(generate(gen, size)), size)
+  end
+
+  defp generate({:property_generator, {:map, {gen, fun}}}, size) do
+    fun.(generate(gen, size))
+  end
+
+  defp generate({:property_generator, {:filter, {gen, {fun, max_tries}}}}, size) do
+    generate_filtered(gen, fun, max_tries, size)
+  end
+
+  defp generate({left, right}, size) do
+    {generate(left, size), generate(right, size)}
+  end
+
+  defp generate(other, _size) do
+    raise "PropertyTest expected a generator, found #{inspect(other)}"
+  end
+
+  defp generate_list(_element, _size, 0, acc) do
+    reverse(acc, [])
+  end
+
+  defp generate_list(element, size, remaining, acc) do
+    case host_call(:property_draw, 4) do
+      0 -> reverse(acc, [])
+      _ -> generate_list(element, size, remaining - 1, [generate(element, size)] ++ acc)
+    end
+  end
+
+  defp generate_filtered(_gen, _fun, 0, _size) do
+    raise "PropertyTest.filter rejected too many values in a row; use a generator that produces matching values more often"
+  end
+
+  defp generate_filtered(gen, fun, tries, size) do
+    value = generate(gen, size)
+
+    if fun.(value) do
+      value
+    else
+      generate_filtered(gen, fun, tries - 1, size + 1)
+    end
+  end
+
+  defp nth([head | _], 0) do
+    head
+  end
+
+  defp nth([_ | tail], index) do
+    nth(tail, index - 1)
+  end
+
+  defp total_weight([], total) do
+    total
+  end
+
+  defp total_weight([{weight, _} | rest], total) do
+    total_weight(rest, total + weight)
+  end
+
+  defp pick_weighted([{weight, gen} | rest], pick) do
+    if pick < weight do
+      gen
+    else
+      pick_weighted(rest, pick - weight)
+    end
+  end
+
+  defp reverse([], acc) do
+    acc
+  end
+
+  defp reverse([head | tail], acc) do
+    reverse(tail, [head] ++ acc)
+  end
+end
+"#;
+
+pub(super) const OPTIONAL_STDLIB_MOCK_SOURCE: &str = r#"defmodule Mock do
+  ## Replaces a host function for the rest of the current test. The
+  ## function receives the host call's arguments as a list and its
+  ## return value becomes the call's result.
+  ##
+  ## Parameters:
+  ##   function: atom — host function key, e.g. :sys_http_request
+  ##   fun: function — fn args -> ... end
+  ##
+  ## Returns: :ok
+  def stub(function, fun) do
+    host_call(:mock_stub, function, fun)
+  end
+
+  ## Removes the stub for a host function before the test ends.
+  ##
+  ## Parameters:
+  ##   function: atom — host function key
+  ##
+  ## Returns: :ok
+  def unstub(function) do
+    host_call(:mock_unstub, function)
+  end
+
+  ## Replays calls to the given host functions from the named cassette
+  ## under __cassettes__/ next to the test file. When the cassette does
+  ## not exist yet, or with --record-cassettes, the real functions run
+  ## and their arguments and results are recorded.
+  ##
+  ## Parameters:
+  ##   name: string — cassette name
+  ##   functions: list — host function keys, e.g. [:sys_http_request]
+  ##
+  ## Returns: :ok
+  def cassette(name, functions) do
+    host_call(:mock_cassette, name, functions)
+  end
+end
+"#;
+
+pub(super) const OPTIONAL_STDLIB_BENCH_SOURCE: &str = r#"defmodule Bench do
+  ## Returns its argument unchanged. Routing constant inputs through
+  ## black_box keeps the native backend from folding a benchmark's
+  ## work away at compile time.
+  ##
+  ## Parameters:
+  ##   value: any — the value to pass through
+  ##
+  ## Returns: value
+  def black_box(value) do
+    value
+  end
+end
+"#;
+
+pub(super) const OPTIONAL_STDLIB_CLI_SOURCE: &str = r#"defmodule CLI do
+  ## Builds a command-line spec from a keyword list with name, version,
+  ## description, flags, args and commands.
+  ##
+  ## Parameters:
+  ##   options: keyword list — the spec, e.g. [name: "seed", flags: [count: [type: :integer]]]
+  ##
+  ## Returns: the validated spec
+  def spec(options) do
+    host_call(:cli_build_spec, options)
+  end
+
+  ## Parses an argument list against a spec.
+  ##
+  ## Parameters:
+  ##   spec: keyword list — a spec from CLI.spec
+  ##   argv: list — the arguments to parse, e.g. the argv a task receives
+  ##
+  ## Returns: {:ok, %{flags: map, args: map, rest: list}}, {:help, text}, {:version, text} or {:error, message}
+  def parse(spec, argv) do
+    host_call(:cli_parse, spec, argv)
+  end
+
+  ## Renders the usage text for a spec.
+  ##
+  ## Parameters:
+  ##   spec: keyword list — a spec from CLI.spec
+  ##
+  ## Returns: string
+  def help_text(spec) do
+    host_call(:cli_format_help, spec)
+  end
+end
+"#;
@@ -0,0 +1,631 @@
+//! Line and function coverage for `tonic test --cover`.
+//!
+//! Every compiled suite gets a [`SuiteCoverage`] that maps IR op offsets back
+//! to source lines and function clauses. While a test case runs, a
+//! [`DebugHook`] counts how often each op offset executes and which offsets
+//! open a new call frame (clause entries); the counts are folded into a
+//! [`CoverageReport`] keyed by file and module once the run finishes. Modules
+//! from the embedded stdlib are left out unless requested.
+
+use crate::ir::IrProgram;
+use crate::parser::Ast;
+use crate::runtime::debug::{with_debug_hook, DebugFrame, DebugHook};
+use crate::runtime::{collect_op_offsets, ir_op_offset, RuntimeValue};
+use serde_json::json;
+use std::collections::{BTreeMap, HashMap};
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+
+const STDLIB_PATH_PREFIX: &str = "<stdlib>";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CoverageFormat {
+    Text,
+    Lcov,
+    Cobertura,
+}
+
+impl CoverageFormat {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "text" => Some(Self::Text),
+            "lcov" => Some(Self::Lcov),
+            "cobertura" => Some(Self::Cobertura),
+            _ => None,
+        }
+    }
+
+    /// Where the report is written when `--cover-output` is not given.
+    pub fn default_output(self) -> Option<&'static str> {
+        match self {
+            Self::Text => None,
+            Self::Lcov => Some(".tonic/coverage/lcov.info"),
+            Self::Cobertura => Some(".tonic/coverage/cobertura.xml"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CoverageOptions {
+    /// Also report the stdlib modules injected into each suite.
+    pub include_stdlib: bool,
+}
+
+#[derive(Debug, Clone, Default)]
+struct Counts {
+    /// Offset -> times an op at that offset executed.
+    ops: HashMap<usize, u64>,
+    /// Offset -> times it was the first op of a new call frame.
+    entries: HashMap<usize, u64>,
+}
+
+/// Op execution counts for one suite, shared with the threads that run it.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct CoverageRecorder {
+    counts: Arc<Mutex<Counts>>,
+}
+
+impl CoverageRecorder {
+    /// Evaluate `f` with every executed op counted towards this recorder.
+    pub(crate) fn observe<T>(&self, f: impl FnOnce() -> T) -> T {
+        let hook = CoverageHook {
+            local: Counts::default(),
+            depth: 0,
+            shared: Arc::clone(&self.counts),
+        };
+        with_debug_hook(Box::new(hook), f)
+    }
+
+    fn counts(&self) -> Counts {
+        self.counts
+            .lock()
+            .map(|counts| counts.clone())
+            .unwrap_or_default()
+    }
+}
+
+/// Counts ops locally and merges them into the shared counts when the debug
+/// session ends, so the lock is taken once per evaluation instead of per op.
+struct CoverageHook {
+    local: Counts,
+    /// Frame depth at the previous op; a deeper frame means a call was entered.
+    depth: usize,
+    shared: Arc<Mutex<Counts>>,
+}
+
+impl DebugHook for CoverageHook {
+    fn on_op(
+        &mut self,
+        offset: usize,
+        frames: &[DebugFrame],
+        _env: &HashMap<String, RuntimeValue>,
+    ) -> Result<(), String> {
+        *self.local.ops.entry(offset).or_default() += 1;
+        if frames.len() > self.depth {
+            *self.local.entries.entry(offset).or_default() += 1;
+        }
+        self.depth = frames.len();
+        Ok(())
+    }
+}
+
+impl Drop for CoverageHook {
+    fn drop(&mut self) {
+        if let Ok(mut shared) = self.shared.lock() {
+            for (offset, count) in self.local.ops.drain() {
+                *shared.ops.entry(offset).or_default() += count;
+            }
+            for (offset, count) in self.local.entries.drain() {
+                *shared.entries.entry(offset).or_default() += count;
+            }
+        }
+    }
+}
+
+/// Source layout of one compiled suite plus the recorder its cases report to.
+pub(crate) struct SuiteCoverage {
+    pub(crate) recorder: CoverageRecorder,
+    modules: Vec<ModuleLayout>,
+}
+
+struct ModuleLayout {
+    path: String,
+    name: String,
+    /// Executable line -> offset -> number of ops sharing that offset.
+    lines: BTreeMap<usize, BTreeMap<usize, u64>>,
+    clauses: Vec<ClauseLayout>,
+}
+
+struct ClauseLayout {
+    /// `name/arity`.
+    function: String,
+    head_offset: usize,
+    head_line: usize,
+    /// First op of the clause body; its count is the number of clause entries.
+    entry_offset: Option<usize>,
+}
+
+impl SuiteCoverage {
+    /// `source` is the compiled text (test file, generated doctests and
+    /// injected stdlib) and `user_len` the length of the test file's own part.
+    pub(crate) fn new(
+        path: &Path,
+        source: &str,
+        user_len: usize,
+        ast: &Ast,
+        ir: &IrProgram,
+        options: CoverageOptions,
+    ) -> Self {
+        let line_starts = std::iter::once(0)
+            .chain(source.match_indices('\n').map(|(index, _)| index + 1))
+            .collect::<Vec<_>>();
+        let line_of = |offset: usize| line_starts.partition_point(|start| *start <= offset);
+
+        let mut modules = Vec::new();
+        for module in &ast.modules {
+            if super::doctest::is_generated_module(&module.name) {
+                continue;
+            }
+            let is_stdlib = module.offset >= user_len;
+            if is_stdlib && !options.include_stdlib {
+                continue;
+            }
+
+            // Stdlib modules get a virtual file each, numbered from `defmodule`.
+            let (path, base_line) = if is_stdlib {
+                (
+                    format!("{STDLIB_PATH_PREFIX}/{}.tn", module.name),
+                    line_of(module.offset) - 1,
+                )
+            } else {
+                (path.display().to_string(), 0)
+            };
+
+            let mut clauses = module
+                .functions
+                .iter()
+                .map(|function| ClauseLayout {
+                    function: format!("{}/{}", function.name, function.params.len()),
+                    head_offset: function.offset,
+                    head_line: line_of(function.offset) - base_line,
+                    entry_offset: None,
+                })
+                .collect::<Vec<_>>();
+            clauses.sort_by_key(|clause| clause.head_offset);
+
+            modules.push((
+                base_line,
+                is_stdlib,
+                ModuleLayout {
+                    path,
+                    name: module.name.clone(),
+                    lines: BTreeMap::new(),
+                    clauses,
+                },
+            ));
+        }
+
+        for function in &ir.functions {
+            let Some((module_name, _)) = function.name.rsplit_once('.') else {
+                continue;
+            };
+            let Some((base_line, is_stdlib, layout)) = modules
+                .iter_mut()
+                .find(|(_, _, layout)| layout.name == module_name)
+            else {
+                continue;
+            };
+
+            let mut offsets = Vec::new();
+            if let Some(guard_ops) = &function.guard_ops {
+                collect_op_offsets(guard_ops, &mut offsets);
+            }
+            collect_op_offsets(&function.ops, &mut offsets);
+
+            // Synthesized ops (protocol dispatch, default-argument wrappers)
+            // may point outside the module's clauses; those are not counted.
+            let first_head = layout.clauses.first().map(|clause| clause.head_offset);
+            let belongs = |offset: usize| {
+                first_head.is_some_and(|head| offset >= head) && (offset >= user_len) == *is_stdlib
+            };
+
+            if let Some(entry) = function.ops.first().map(ir_op_offset) {
+                if belongs(entry) {
+                    let clause_index = layout
+                        .clauses
+                        .partition_point(|clause| clause.head_offset <= entry)
+                        - 1;
+                    let clause = &mut layout.clauses[clause_index];
+                    clause.entry_offset.get_or_insert(entry);
+                }
+            }
+
+            for offset in offsets.into_iter().filter(|offset| belongs(*offset)) {
+                layout
+                    .lines
+                    .entry(line_of(offset) - *base_line)
+                    .or_default()
+                    .entry(offset)
+                    .and_modify(|ops| *ops += 1)
+                    .or_insert(1);
+            }
+        }
+
+        Self {
+            recorder: CoverageRecorder::default(),
+            modules: modules.into_iter().map(|(_, _, layout)| layout).collect(),
+        }
+    }
+
+    pub(crate) fn report(&self) -> CoverageReport {
+        let counts = self.recorder.counts();
+        let mut report = CoverageReport::default();
+
+        for layout in &self.modules {
+            // Ops sharing an offset run together, so a line's count is the
+            // busiest offset on it divided by the ops that share it.
+            let mut lines = layout
+                .lines
+                .iter()
+                .map(|(line, offsets)| {
+                    let hits = offsets
+                        .iter()
+                        .map(|(offset, ops)| counts.ops.get(offset).copied().unwrap_or(0) / ops)
+                        .max()
+                        .unwrap_or(0);
+                    (*line, hits)
+                })
+                .collect::<BTreeMap<_, _>>();
+
+            let mut functions: Vec<FunctionCoverage> = Vec::new();
+            for clause in &layout.clauses {
+                let entries = clause
+                    .entry_offset
+                    .and_then(|offset| counts.entries.get(&offset).copied())
+                    .unwrap_or(0);
+                let head = lines.entry(clause.head_line).or_insert(0);
+                *head = (*head).max(entries);
+
+                match functions
+                    .iter_mut()
+                    .find(|function| function.name == clause.function)
+                {
+                    Some(function) => function.hits += entries,
+                    None => functions.push(FunctionCoverage {
+                        name: clause.function.clone(),
+                        line: clause.head_line,
+                        hits: entries,
+                    }),
+                }
+            }
+
+            report.add_module(
+                &layout.path,
+                ModuleCoverage {
+                    name: layout.name.clone(),
+                    lines,
+                    functions,
+                },
+            );
+        }
+
+        report
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CoverageReport {
+    pub files: Vec<FileCoverage>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileCoverage {
+    pub path: String,
+    pub modules: Vec<ModuleCoverage>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ModuleCoverage {
+    pub name: String,
+    /// Executable line -> execution count.
+    pub lines: BTreeMap<usize, u64>,
+    pub functions: Vec<FunctionCoverage>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FunctionCoverage {
+    /// `name/arity`.
+    pub name: String,
+    pub line: usize,
+    pub hits: u64,
+}
+
+impl ModuleCoverage {
+    fn line_totals(&self) -> (usize, usize) {
+        let covered = self.lines.values().filter(|hits| **hits > 0).count();
+        (covered, self.lines.len())
+    }
+
+    fn function_totals(&self) -> (usize, usize) {
+        let covered = self
+            .functions
+            .iter()
+            .filter(|function| function.hits > 0)
+            .count();
+        (covered, self.functions.len())
+    }
+
+    fn merge(&mut self, other: ModuleCoverage) {
+        for (line, hits) in other.lines {
+            *self.lines.entry(line).or_insert(0) += hits;
+        }
+        for function in other.functions {
+            match self
+                .functions
+                .iter_mut()
+                .find(|existing| existing.name == function.name)
+            {
+                Some(existing) => existing.hits += function.hits,
+                None => self.functions.push(function),
+            }
+        }
+    }
+}
+
+impl FileCoverage {
+    fn line_totals(&self) -> (usize, usize) {
+        sum_totals(self.modules.iter().map(ModuleCoverage::line_totals))
+    }
+
+    fn function_totals(&self) -> (usize, usize) {
+        sum_totals(self.modules.iter().map(ModuleCoverage::function_totals))
+    }
+}
+
+impl CoverageReport {
+    /// Fold a module into the report. The same stdlib module compiled into
+    /// several suites is merged by summing its counts.
+    fn add_module(&mut self, path: &str, module: ModuleCoverage) {
+        let file = match self.files.iter().position(|file| file.path == path) {
+            Some(index) => &mut self.files[index],
+            None => {
+                self.files.push(FileCoverage {
+                    path: path.to_string(),
+                    modules: Vec::new(),
+                });
+                self.files.last_mut().expect("file was just pushed")
+            }
+        };
+
+        match file
+            .modules
+            .iter_mut()
+            .find(|existing| existing.name == module.name)
+        {
+            Some(existing) => existing.merge(module),
+            None => file.modules.push(module),
+        }
+    }
+
+    pub fn merge(&mut self, other: CoverageReport) {
+        for file in other.files {
+            for module in file.modules {
+                self.add_module(&file.path, module);
+            }
+        }
+        self.files.sort_by(|left, right| left.path.cmp(&right.path));
+    }
+
+    pub fn line_totals(&self) -> (usize, usize) {
+        sum_totals(self.files.iter().map(FileCoverage::line_totals))
+    }
+
+    pub fn function_totals(&self) -> (usize, usize) {
+        sum_totals(self.files.iter().map(FileCoverage::function_totals))
+    }
+
+    /// Line coverage of the whole run as a percentage.
+    pub fn line_percent(&self) -> f64 {
+        percent(self.line_totals())
+    }
+
+    pub fn render_text(&self) -> Vec<String> {
+        let mut lines = vec!["Coverage:".to_string()];
+
+        for file in &self.files {
+            lines.push(format!("  {}", file.path));
+            for module in &file.modules {
+                lines.push(format!(
+                    "    {}  lines {}  functions {}",
+                    module.name,
+                    format_totals(module.line_totals()),
+                    format_totals(module.function_totals())
+                ));
+            }
+        }
+
+        lines.push(format!(
+            "coverage: lines {}; functions {}",
+            format_totals(self.line_totals()),
+            format_totals(self.function_totals())
+        ));
+        lines
+    }
+
+    pub fn render_json(&self) -> serde_json::Value {
+        let totals_json = |(covered, total): (usize, usize)| {
+            json!({
+                "covered": covered,
+                "total": total,
+                "percent": round_percent(percent((covered, total))),
+            })
+        };
+
+        json!({
+            "lines": totals_json(self.line_totals()),
+            "functions": totals_json(self.function_totals()),
+            "files": self.files.iter().map(|file| json!({
+                "path": file.path,
+                "modules": file.modules.iter().map(|module| json!({
+                    "name": module.name,
+                    "lines": totals_json(module.line_totals()),
+                    "functions": totals_json(module.function_totals()),
+                    "uncovered_lines": module.lines.iter()
+                        .filter(|(_, hits)| **hits == 0)
+                        .map(|(line, _)| *line)
+                        .collect::<Vec<_>>(),
+                })).collect::<Vec<_>>(),
+            })).collect::<Vec<_>>(),
+        })
+    }
+
+    /// LCOV tracefile, one record per source file.
+    pub fn render_lcov(&self) -> String {
+        let mut out = String::from("TN:\n");
+
+        for file in &self.files {
+            out.push_str(&format!("SF:{}\n", file.path));
+
+            for module in &file.modules {
+                for function in &module.functions {
+                    out.push_str(&format!(
+                        "FN:{},{}.{}\n",
+                        function.line, module.name, function.name
+                    ));
+                }
+            }
+            for module in &file.modules {
+                for function in &module.functions {
+                    out.push_str(&format!(
+                        "FNDA:{},{}.{}\n",
+                        function.hits, module.name, function.name
+                    ));
+                }
+            }
+            let (functions_hit, functions_found) = file.function_totals();
+            out.push_str(&format!("FNF:{functions_found}\nFNH:{functions_hit}\n"));
+
+            let mut file_lines = BTreeMap::new();
+            for module in &file.modules {
+                for (line, hits) in &module.lines {
+                    *file_lines.entry(*line).or_insert(0) += hits;
+                }
+            }
+            for (line, hits) in &file_lines {
+                out.push_str(&format!("DA:{line},{hits}\n"));
+            }
+            let lines_hit = file_lines.values().filter(|hits| **hits > 0).count();
+            out.push_str(&format!(
+                "LF:{}\nLH:{lines_hit}\nend_of_record\n",
+                file_lines.len()
+            ));
+        }
+
+        out
+    }
+
+    /// Cobertura XML with one package per file and one class per module.
+    pub fn render_cobertura(&self) -> String {
+        let timestamp = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|elapsed| elapsed.as_millis())
+            .unwrap_or(0);
+        let (lines_covered, lines_valid) = self.line_totals();
+
+        let mut out = String::from("<?xml version=\"1.0\" ?>\n");
+        out.push_str(
+            "<!DOCTYPE coverage SYSTEM \"http://cobertura.sourceforge.net/xml/coverage-04.dtd\">\n",
+        );
+        out.push_str(&format!(
+            "<coverage line-rate=\"{}\" branch-rate=\"0\" lines-covered=\"{lines_covered}\" lines-valid=\"{lines_valid}\" branches-covered=\"0\" branches-valid=\"0\" complexity=\"0\" version=\"tonic {}\" timestamp=\"{timestamp}\">\n",
+            rate((lines_covered, lines_valid)),
+            env!("CARGO_PKG_VERSION")
+        ));
+        out.push_str("  <sources>\n    <source>.</source>\n  </sources>\n  <packages>\n");
+
+        for file in &self.files {
+            let path = xml_escape(&file.path);
+            out.push_str(&format!(
+                "    <package name=\"{path}\" line-rate=\"{}\" branch-rate=\"0\" complexity=\"0\">\n      <classes>\n",
+                rate(file.line_totals())
+            ));
+
+            for module in &file.modules {
+                out.push_str(&format!(
+                    "        <class name=\"{}\" filename=\"{path}\" line-rate=\"{}\" branch-rate=\"0\" complexity=\"0\">\n          <methods>\n",
+                    xml_escape(&module.name),
+                    rate(module.line_totals())
+                ));
+                for function in &module.functions {
+                    let (name, arity) = function
+                        .name
+                        .rsplit_once('/')
+                        .unwrap_or((&function.name, "0"));
+                    out.push_str(&format!(
+                        "            <method name=\"{}\" signature=\"/{arity}\" line-rate=\"{}\" branch-rate=\"0\" complexity=\"0\">\n              <lines>\n                <line number=\"{}\" hits=\"{}\"/>\n              </lines>\n            </method>\n",
+                        xml_escape(name),
+                        if function.hits > 0 { "1" } else { "0" },
+                        function.line,
+                        function.hits
+                    ));
+                }
+                out.push_str("          </methods>\n          <lines>\n");
+                for (line, hits) in &module.lines {
+                    out.push_str(&format!(
+                        "            <line number=\"{line}\" hits=\"{hits}\"/>\n"
+                    ));
+                }
+                out.push_str("          </lines>\n        </class>\n");
+            }
+
+            out.push_str("      </classes>\n    </package>\n");
+        }
+
+        out.push_str("  </packages>\n</coverage>\n");
+        out
+    }
+}
+
+fn sum_totals(totals: impl Iterator<Item = (usize, usize)>) -> (usize, usize) {
+    totals.fold(
+        (0, 0),
+        |(covered, total), (module_covered, module_total)| {
+            (covered + module_covered, total + module_total)
+        },
+    )
+}
+
+/// Nothing to cover counts as fully covered.
+fn percent((covered, total): (usize, usize)) -> f64 {
+    if total == 0 {
+        100.0
+    } else {
+        covered as f64 * 100.0 / total as f64
+    }
+}
+
+fn round_percent(value: f64) -> f64 {
+    (value * 100.0).round() / 100.0
+}
+
+fn rate(totals: (usize, usize)) -> String {
+    format!("{:.4}", percent(totals) / 100.0)
+}
+
+fn format_totals(totals: (usize, usize)) -> String {
+    format!("{}/{} ({:.1}%)", totals.0, totals.1, percent(totals))
+}
+
+/// Escapes markup and drops the control characters XML 1.0 does not allow,
+/// such as the ANSI escapes in captured output.
+pub(super) fn xml_escape(value: &str) -> String {
+    value
+        .replace(
+            |ch: char| ch.is_control() && !matches!(ch, '\t' | '\n' | '\r'),
+            "",
+        )
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
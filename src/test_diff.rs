@@ -0,0 +1,672 @@
+//! Structural diffs of `assert_equal` operands: map entries added, removed
+//! or changed, list edits from a longest common subsequence, and line or
+//! character diffs of strings. Rendered with `-`/`+` markers in text output
+//! and as a tree in `--format json`.
+
+use super::AnsiColor;
+use crate::runtime::RuntimeValue;
+use serde_json::{json, Value};
+
+/// Past this many LCS cells (left length times right length) a list or
+/// string is reported as a whole replacement instead.
+const MAX_LCS_CELLS: usize = 1_000_000;
+
+/// Unchanged items shown in a row before the rest are folded into a count.
+const MAX_UNCHANGED_RUN: usize = 3;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ValueDiff {
+    Equal(RuntimeValue),
+    Changed(RuntimeValue, RuntimeValue),
+    Map {
+        keyword: bool,
+        entries: Vec<EntryDiff>,
+    },
+    List(Vec<ItemDiff>),
+    Tuple(Box<ValueDiff>, Box<ValueDiff>),
+    /// Multi-line strings, edited line by line.
+    Lines(Vec<Edit<String>>),
+    /// Single-line strings, edited in runs of characters.
+    Chars(Vec<Edit<String>>),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EntryDiff {
+    Equal(RuntimeValue, RuntimeValue),
+    Removed(RuntimeValue, RuntimeValue),
+    Added(RuntimeValue, RuntimeValue),
+    Changed(RuntimeValue, ValueDiff),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ItemDiff {
+    Equal(RuntimeValue),
+    Removed(RuntimeValue),
+    Added(RuntimeValue),
+    Changed(ValueDiff),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Edit<T> {
+    Equal(T),
+    Delete(T),
+    Insert(T),
+}
+
+impl ValueDiff {
+    /// The diff shown for a failed `assert_equal`, or `None` when the plain
+    /// `left:`/`right:` lines say it all (numbers, atoms and the like).
+    pub fn between(left: &RuntimeValue, right: &RuntimeValue) -> Option<Self> {
+        let structured = |value: &RuntimeValue| {
+            matches!(
+                value,
+                RuntimeValue::Map(_)
+                    | RuntimeValue::Keyword(_)
+                    | RuntimeValue::List(_)
+                    | RuntimeValue::Tuple(..)
+            )
+        };
+        let both_strings = matches!(
+            (left, right),
+            (RuntimeValue::String(_), RuntimeValue::String(_))
+        );
+
+        if left == right || !(both_strings || structured(left) || structured(right)) {
+            return None;
+        }
+        Some(diff(left, right))
+    }
+
+    /// Text lines for an assertion failure. Single-line strings keep the
+    /// `left:`/`right:` layout with the differing characters highlighted;
+    /// everything else is a `-`/`+` diff.
+    pub(super) fn render(&self, color: &AnsiColor) -> Vec<String> {
+        if let Self::Chars(edits) = self {
+            return vec![
+                format!("left:  {}", highlight_chars(edits, Side::Left, color, "")),
+                format!("right: {}", highlight_chars(edits, Side::Right, color, "")),
+            ];
+        }
+
+        let mut lines = Vec::new();
+        match self {
+            Self::Lines(edits) => {
+                for edit in edits {
+                    let (sign, text) = match edit {
+                        Edit::Equal(text) => (' ', text),
+                        Edit::Delete(text) => ('-', text),
+                        Edit::Insert(text) => ('+', text),
+                    };
+                    lines.push(Line::new(sign, 0, text.clone()));
+                }
+            }
+            _ => push_lines(self, "", "", 0, color, &mut lines),
+        }
+
+        let mut out = vec!["diff (- left, + right):".to_string()];
+        out.extend(lines.iter().map(|line| format!("  {}", line.render(color))));
+        out
+    }
+
+    pub fn to_json(&self) -> Value {
+        match self {
+            Self::Equal(value) => json!({ "op": "equal", "value": value.render() }),
+            Self::Changed(left, right) => json!({
+                "op": "changed",
+                "left": left.render(),
+                "right": right.render(),
+            }),
+            Self::Map { keyword, entries } => json!({
+                "op": if *keyword { "keyword" } else { "map" },
+                "entries": entries.iter().map(EntryDiff::to_json).collect::<Vec<_>>(),
+            }),
+            Self::List(items) => json!({
+                "op": "list",
+                "items": items.iter().map(ItemDiff::to_json).collect::<Vec<_>>(),
+            }),
+            Self::Tuple(left, right) => json!({
+                "op": "tuple",
+                "items": [left.to_json(), right.to_json()],
+            }),
+            Self::Lines(edits) => json!({ "op": "lines", "edits": edits_json(edits) }),
+            Self::Chars(edits) => json!({ "op": "chars", "edits": edits_json(edits) }),
+        }
+    }
+}
+
+impl EntryDiff {
+    fn to_json(&self) -> Value {
+        match self {
+            Self::Equal(key, value) => {
+                json!({ "op": "equal", "key": key.render(), "value": value.render() })
+            }
+            Self::Removed(key, value) => {
+                json!({ "op": "removed", "key": key.render(), "value": value.render() })
+            }
+            Self::Added(key, value) => {
+                json!({ "op": "added", "key": key.render(), "value": value.render() })
+            }
+            Self::Changed(key, diff) => {
+                json!({ "op": "changed", "key": key.render(), "diff": diff.to_json() })
+            }
+        }
+    }
+}
+
+impl ItemDiff {
+    fn to_json(&self) -> Value {
+        match self {
+            Self::Equal(value) => json!({ "op": "equal", "value": value.render() }),
+            Self::Removed(value) => json!({ "op": "removed", "value": value.render() }),
+            Self::Added(value) => json!({ "op": "added", "value": value.render() }),
+            Self::Changed(diff) => json!({ "op": "changed", "diff": diff.to_json() }),
+        }
+    }
+}
+
+fn edits_json(edits: &[Edit<String>]) -> Vec<Value> {
+    edits
+        .iter()
+        .map(|edit| match edit {
+            Edit::Equal(text) => json!({ "op": "equal", "text": text }),
+            Edit::Delete(text) => json!({ "op": "removed", "text": text }),
+            Edit::Insert(text) => json!({ "op": "added", "text": text }),
+        })
+        .collect()
+}
+
+fn diff(left: &RuntimeValue, right: &RuntimeValue) -> ValueDiff {
+    if left == right {
+        return ValueDiff::Equal(left.clone());
+    }
+
+    match (left, right) {
+        (RuntimeValue::Map(left), RuntimeValue::Map(right)) => ValueDiff::Map {
+            keyword: false,
+            entries: diff_entries(left, right),
+        },
+        (RuntimeValue::Keyword(left), RuntimeValue::Keyword(right)) => ValueDiff::Map {
+            keyword: true,
+            entries: diff_entries(left, right),
+        },
+        (RuntimeValue::List(left), RuntimeValue::List(right)) => match lcs_edits(left, right) {
+            Some(edits) => ValueDiff::List(pair_changed_items(edits)),
+            None => changed(left, right),
+        },
+        (
+            RuntimeValue::Tuple(left_first, left_second),
+            RuntimeValue::Tuple(right_first, right_second),
+        ) => ValueDiff::Tuple(
+            Box::new(diff(left_first, right_first)),
+            Box::new(diff(left_second, right_second)),
+        ),
+        (RuntimeValue::String(left_text), RuntimeValue::String(right_text))
+            if left_text.contains('\n') || right_text.contains('\n') =>
+        {
+            let left_lines = left_text.lines().map(str::to_string).collect::<Vec<_>>();
+            let right_lines = right_text.lines().map(str::to_string).collect::<Vec<_>>();
+            match lcs_edits(&left_lines, &right_lines) {
+                Some(edits) => ValueDiff::Lines(edits),
+                None => ValueDiff::Changed(left.clone(), right.clone()),
+            }
+        }
+        (RuntimeValue::String(left_text), RuntimeValue::String(right_text)) => {
+            let left_chars = left_text.chars().collect::<Vec<_>>();
+            let right_chars = right_text.chars().collect::<Vec<_>>();
+            match lcs_edits(&left_chars, &right_chars) {
+                Some(edits) => ValueDiff::Chars(group_chars(edits)),
+                None => ValueDiff::Changed(left.clone(), right.clone()),
+            }
+        }
+        _ => ValueDiff::Changed(left.clone(), right.clone()),
+    }
+}
+
+fn changed(left: &[RuntimeValue], right: &[RuntimeValue]) -> ValueDiff {
+    ValueDiff::Changed(
+        RuntimeValue::List(left.to_vec()),
+        RuntimeValue::List(right.to_vec()),
+    )
+}
+
+/// Entries in left's order, then the keys only right has.
+fn diff_entries(
+    left: &[(RuntimeValue, RuntimeValue)],
+    right: &[(RuntimeValue, RuntimeValue)],
+) -> Vec<EntryDiff> {
+    let mut entries = left
+        .iter()
+        .map(
+            |(key, left_value)| match right.iter().find(|(candidate, _)| candidate == key) {
+                Some((_, right_value)) if right_value == left_value => {
+                    EntryDiff::Equal(key.clone(), left_value.clone())
+                }
+                Some((_, right_value)) => {
+                    EntryDiff::Changed(key.clone(), diff(left_value, right_value))
+                }
+                None => EntryDiff::Removed(key.clone(), left_value.clone()),
+            },
+        )
+        .collect::<Vec<_>>();
+
+    entries.extend(
+        right
+            .iter()
+            .filter(|(key, _)| !left.iter().any(|(candidate, _)| candidate == key))
+            .map(|(key, value)| EntryDiff::Added(key.clone(), value.clone())),
+    );
+    entries
+}
+
+/// Within each run of removals and additions, pair up values of the same
+/// shape so an edited element shows as a nested diff instead of a whole
+/// removed and added element.
+fn pair_changed_items(edits: Vec<Edit<RuntimeValue>>) -> Vec<ItemDiff> {
+    let mut items = Vec::new();
+    let mut removed = Vec::new();
+    let mut added = Vec::new();
+
+    let flush = |items: &mut Vec<ItemDiff>,
+                 removed: &mut Vec<RuntimeValue>,
+                 added: &mut Vec<RuntimeValue>| {
+        let mut added_iter = std::mem::take(added).into_iter().peekable();
+        let mut leftover = Vec::new();
+        for left in std::mem::take(removed) {
+            match added_iter.peek() {
+                Some(right) if same_shape(&left, right) => {
+                    let right = added_iter.next().expect("peeked value");
+                    items.push(ItemDiff::Changed(diff(&left, &right)));
+                }
+                _ => leftover.push(ItemDiff::Removed(left)),
+            }
+        }
+        items.extend(leftover);
+        items.extend(added_iter.map(ItemDiff::Added));
+    };
+
+    for edit in edits {
+        match edit {
+            Edit::Equal(value) => {
+                flush(&mut items, &mut removed, &mut added);
+                items.push(ItemDiff::Equal(value));
+            }
+            Edit::Delete(value) => removed.push(value),
+            Edit::Insert(value) => added.push(value),
+        }
+    }
+    flush(&mut items, &mut removed, &mut added);
+    items
+}
+
+fn same_shape(left: &RuntimeValue, right: &RuntimeValue) -> bool {
+    matches!(
+        (left, right),
+        (RuntimeValue::Map(_), RuntimeValue::Map(_))
+            | (RuntimeValue::Keyword(_), RuntimeValue::Keyword(_))
+            | (RuntimeValue::List(_), RuntimeValue::List(_))
+            | (RuntimeValue::Tuple(..), RuntimeValue::Tuple(..))
+            | (RuntimeValue::String(_), RuntimeValue::String(_))
+    )
+}
+
+/// Shortest edit script from a longest common subsequence, or `None` when
+/// the inputs are too large to compare element by element.
+fn lcs_edits<T: PartialEq + Clone>(left: &[T], right: &[T]) -> Option<Vec<Edit<T>>> {
+    let (rows, cols) = (left.len() + 1, right.len() + 1);
+    if rows.saturating_mul(cols) > MAX_LCS_CELLS {
+        return None;
+    }
+
+    // lengths[i * cols + j]: LCS length of left[i..] and right[j..].
+    let mut lengths = vec![0u32; rows * cols];
+    for i in (0..left.len()).rev() {
+        for j in (0..right.len()).rev() {
+            lengths[i * cols + j] = if left[i] == right[j] {
+                lengths[(i + 1) * cols + j + 1] + 1
+            } else {
+                lengths[(i + 1) * cols + j].max(lengths[i * cols + j + 1])
+            };
+        }
+    }
+
+    let (mut i, mut j) = (0, 0);
+    let mut edits = Vec::new();
+    while i < left.len() && j < right.len() {
+        if left[i] == right[j] {
+            edits.push(Edit::Equal(left[i].clone()));
+            i += 1;
+            j += 1;
+        } else if lengths[(i + 1) * cols + j] >= lengths[i * cols + j + 1] {
+            edits.push(Edit::Delete(left[i].clone()));
+            i += 1;
+        } else {
+            edits.push(Edit::Insert(right[j].clone()));
+            j += 1;
+        }
+    }
+    edits.extend(left[i..].iter().cloned().map(Edit::Delete));
+    edits.extend(right[j..].iter().cloned().map(Edit::Insert));
+    Some(edits)
+}
+
+fn group_chars(edits: Vec<Edit<char>>) -> Vec<Edit<String>> {
+    let mut grouped: Vec<Edit<String>> = Vec::new();
+    for edit in edits {
+        match (grouped.last_mut(), edit) {
+            (Some(Edit::Equal(text)), Edit::Equal(ch))
+            | (Some(Edit::Delete(text)), Edit::Delete(ch))
+            | (Some(Edit::Insert(text)), Edit::Insert(ch)) => text.push(ch),
+            (_, Edit::Equal(ch)) => grouped.push(Edit::Equal(ch.to_string())),
+            (_, Edit::Delete(ch)) => grouped.push(Edit::Delete(ch.to_string())),
+            (_, Edit::Insert(ch)) => grouped.push(Edit::Insert(ch.to_string())),
+        }
+    }
+    grouped
+}
+
+#[derive(Clone, Copy)]
+enum Side {
+    Left,
+    Right,
+}
+
+/// One side of a character diff as a quoted string, with the characters
+/// only that side has in bold. `base` is the color of the surrounding line.
+fn highlight_chars(edits: &[Edit<String>], side: Side, color: &AnsiColor, base: &str) -> String {
+    let mut out = String::from("\"");
+    for edit in edits {
+        match (edit, side) {
+            (Edit::Equal(text), _) => out.push_str(text),
+            (Edit::Delete(text), Side::Left) | (Edit::Insert(text), Side::Right) => {
+                out.push_str(&format!("{}{text}{}{base}", color.bold, color.reset));
+            }
+            _ => {}
+        }
+    }
+    out.push('"');
+    out
+}
+
+struct Line {
+    sign: char,
+    depth: usize,
+    text: String,
+}
+
+impl Line {
+    fn new(sign: char, depth: usize, text: String) -> Self {
+        Self { sign, depth, text }
+    }
+
+    fn render(&self, color: &AnsiColor) -> String {
+        let (start, end) = match self.sign {
+            '-' => (color.red, color.reset),
+            '+' => (color.green, color.reset),
+            _ => ("", ""),
+        };
+        let indent = "  ".repeat(self.depth);
+        format!("{start}{} {indent}{}{end}", self.sign, self.text)
+    }
+}
+
+/// Lines for `diff` written after `prefix` (a map key or nothing) and
+/// before `suffix` (a separating comma or nothing).
+fn push_lines(
+    diff: &ValueDiff,
+    prefix: &str,
+    suffix: &str,
+    depth: usize,
+    color: &AnsiColor,
+    lines: &mut Vec<Line>,
+) {
+    let changed = |left: String, right: String, lines: &mut Vec<Line>| {
+        lines.push(Line::new('-', depth, format!("{prefix}{left}{suffix}")));
+        lines.push(Line::new('+', depth, format!("{prefix}{right}{suffix}")));
+    };
+
+    match diff {
+        ValueDiff::Equal(value) => lines.push(Line::new(
+            ' ',
+            depth,
+            format!("{prefix}{}{suffix}", value.render()),
+        )),
+        ValueDiff::Changed(left, right) => changed(left.render(), right.render(), lines),
+        ValueDiff::Chars(edits) => changed(
+            highlight_chars(edits, Side::Left, color, color.red),
+            highlight_chars(edits, Side::Right, color, color.green),
+            lines,
+        ),
+        ValueDiff::Lines(edits) => {
+            let side = |keep_insert: bool| {
+                let text = edits
+                    .iter()
+                    .filter_map(|edit| match edit {
+                        Edit::Equal(text) => Some(text.as_str()),
+                        Edit::Delete(text) if !keep_insert => Some(text.as_str()),
+                        Edit::Insert(text) if keep_insert => Some(text.as_str()),
+                        _ => None,
+                    })
+                    .collect::<Vec<_>>()
+                    .join("\n");
+                RuntimeValue::String(text).render()
+            };
+            changed(side(false), side(true), lines);
+        }
+        ValueDiff::Map { keyword, entries } => {
+            let (open, close) = if *keyword { ("[", "]") } else { ("%{", "}") };
+            lines.push(Line::new(' ', depth, format!("{prefix}{open}")));
+            let key_prefix = |key: &RuntimeValue| match (keyword, key) {
+                (true, RuntimeValue::Atom(name)) => format!("{name}: "),
+                _ => format!("{} => ", key.render()),
+            };
+            push_children(
+                entries,
+                |entry| matches!(entry, EntryDiff::Equal(..)),
+                |entry, lines| match entry {
+                    EntryDiff::Equal(key, value) => lines.push(Line::new(
+                        ' ',
+                        depth + 1,
+                        format!("{}{},", key_prefix(key), value.render()),
+                    )),
+                    EntryDiff::Removed(key, value) => lines.push(Line::new(
+                        '-',
+                        depth + 1,
+                        format!("{}{},", key_prefix(key), value.render()),
+                    )),
+                    EntryDiff::Added(key, value) => lines.push(Line::new(
+                        '+',
+                        depth + 1,
+                        format!("{}{},", key_prefix(key), value.render()),
+                    )),
+                    EntryDiff::Changed(key, diff) => {
+                        push_lines(diff, &key_prefix(key), ",", depth + 1, color, lines)
+                    }
+                },
+                depth + 1,
+                lines,
+            );
+            lines.push(Line::new(' ', depth, format!("{close}{suffix}")));
+        }
+        ValueDiff::List(items) => {
+            lines.push(Line::new(' ', depth, format!("{prefix}[")));
+            push_children(
+                items,
+                |item| matches!(item, ItemDiff::Equal(_)),
+                |item, lines| match item {
+                    ItemDiff::Equal(value) => {
+                        lines.push(Line::new(' ', depth + 1, format!("{},", value.render())))
+                    }
+                    ItemDiff::Removed(value) => {
+                        lines.push(Line::new('-', depth + 1, format!("{},", value.render())))
+                    }
+                    ItemDiff::Added(value) => {
+                        lines.push(Line::new('+', depth + 1, format!("{},", value.render())))
+                    }
+                    ItemDiff::Changed(diff) => push_lines(diff, "", ",", depth + 1, color, lines),
+                },
+                depth + 1,
+                lines,
+            );
+            lines.push(Line::new(' ', depth, format!("]{suffix}")));
+        }
+        ValueDiff::Tuple(first, second) => {
+            lines.push(Line::new(' ', depth, format!("{prefix}{{")));
+            push_lines(first, "", ",", depth + 1, color, lines);
+            push_lines(second, "", "", depth + 1, color, lines);
+            lines.push(Line::new(' ', depth, format!("}}{suffix}")));
+        }
+    }
+}
+
+/// Push a container's children, folding long runs of unchanged ones into a
+/// single `...` line.
+fn push_children<T>(
+    children: &[T],
+    is_equal: impl Fn(&T) -> bool,
+    mut push: impl FnMut(&T, &mut Vec<Line>),
+    depth: usize,
+    lines: &mut Vec<Line>,
+) {
+    let mut index = 0;
+    while index < children.len() {
+        let run = children[index..]
+            .iter()
+            .take_while(|child| is_equal(child))
+            .count();
+        if run > MAX_UNCHANGED_RUN {
+            lines.push(Line::new(' ', depth, format!("... {run} unchanged")));
+            index += run;
+            continue;
+        }
+        for child in &children[index..index + run.max(1)] {
+            push(child, lines);
+        }
+        index += run.max(1);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn atom(name: &str) -> RuntimeValue {
+        RuntimeValue::Atom(name.to_string())
+    }
+
+    fn string(text: &str) -> RuntimeValue {
+        RuntimeValue::String(text.to_string())
+    }
+
+    fn render(diff: &ValueDiff) -> String {
+        diff.render(&AnsiColor::plain()).join("\n")
+    }
+
+    #[test]
+    fn scalars_and_equal_values_have_no_diff() {
+        assert_eq!(
+            ValueDiff::between(&RuntimeValue::Int(1), &RuntimeValue::Int(2)),
+            None
+        );
+        assert_eq!(ValueDiff::between(&string("a"), &string("a")), None);
+    }
+
+    #[test]
+    fn maps_show_removed_added_and_nested_changes() {
+        let left = RuntimeValue::Map(vec![
+            (atom("id"), RuntimeValue::Int(1)),
+            (atom("name"), string("ada")),
+            (
+                atom("tags"),
+                RuntimeValue::List(vec![atom("a"), atom("b"), atom("c")]),
+            ),
+        ]);
+        let right = RuntimeValue::Map(vec![
+            (atom("id"), RuntimeValue::Int(1)),
+            (
+                atom("tags"),
+                RuntimeValue::List(vec![atom("a"), atom("x"), atom("c")]),
+            ),
+            (atom("admin"), RuntimeValue::Bool(true)),
+        ]);
+
+        let diff = ValueDiff::between(&left, &right).expect("maps should diff");
+        assert_eq!(
+            render(&diff),
+            "diff (- left, + right):\n\
+             \x20   %{\n\
+             \x20     :id => 1,\n\
+             \x20 -   :name => \"ada\",\n\
+             \x20     :tags => [\n\
+             \x20       :a,\n\
+             \x20 -     :b,\n\
+             \x20 +     :x,\n\
+             \x20       :c,\n\
+             \x20     ],\n\
+             \x20 +   :admin => true,\n\
+             \x20   }"
+        );
+        assert_eq!(diff.to_json()["entries"][1]["op"], "removed");
+        assert_eq!(
+            diff.to_json()["entries"][2]["diff"]["items"][2],
+            json!({ "op": "added", "value": ":x" })
+        );
+    }
+
+    #[test]
+    fn list_edits_pair_changed_elements_and_fold_unchanged_runs() {
+        let row = |name: &str| RuntimeValue::Map(vec![(atom("name"), string(name))]);
+        let left = RuntimeValue::List(vec![
+            RuntimeValue::Int(1),
+            RuntimeValue::Int(2),
+            RuntimeValue::Int(3),
+            RuntimeValue::Int(4),
+            row("ada"),
+        ]);
+        let right = RuntimeValue::List(vec![
+            RuntimeValue::Int(1),
+            RuntimeValue::Int(2),
+            RuntimeValue::Int(3),
+            RuntimeValue::Int(4),
+            row("bob"),
+        ]);
+
+        let diff = ValueDiff::between(&left, &right).expect("lists should diff");
+        assert_eq!(
+            render(&diff),
+            "diff (- left, + right):\n\
+             \x20   [\n\
+             \x20     ... 4 unchanged\n\
+             \x20     %{\n\
+             \x20 -     :name => \"ada\",\n\
+             \x20 +     :name => \"bob\",\n\
+             \x20     },\n\
+             \x20   ]"
+        );
+    }
+
+    #[test]
+    fn strings_diff_by_line_or_by_character() {
+        let lines = ValueDiff::between(&string("one\ntwo\nthree"), &string("one\n2\nthree"))
+            .expect("multi-line strings should diff");
+        assert_eq!(
+            render(&lines),
+            "diff (- left, + right):\n    one\n  - two\n  + 2\n    three"
+        );
+
+        let chars = ValueDiff::between(&string("hello world"), &string("hello wurld"))
+            .expect("strings should diff");
+        assert_eq!(
+            render(&chars),
+            "left:  \"hello world\"\nright: \"hello wurld\""
+        );
+        assert_eq!(
+            chars.to_json()["edits"],
+            json!([
+                { "op": "equal", "text": "hello w" },
+                { "op": "removed", "text": "o" },
+                { "op": "added", "text": "u" },
+                { "op": "equal", "text": "rld" },
+            ])
+        );
+    }
+}
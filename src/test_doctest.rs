@@ -0,0 +1,529 @@
+//! Doctests for `tonic test`.
+//!
+//! A test module that declares `doctest Module` gets one test case per
+//! example block in `Module`'s `@moduledoc` and `@doc` strings:
+//!
+//! ```text
+//! @doc """
+//! Adds two numbers.
+//!
+//!     tonic> Math.add(1, 2)
+//!     3
+//! """
+//! ```
+//!
+//! `Module` may be defined in the test file itself or among the project's
+//! library modules (see `manifest::load_library_sources`); the examples come from the
+//! same doc strings `tonic docs` renders.
+//!
+//! Prompts may be written `tonic>` or `iex>`; `...>` continues the previous
+//! prompt and a blank line ends the block. Every block becomes a generated
+//! function that evaluates its prompts in order, so bindings carry over, and
+//! returns the values of the prompts that have an expected result. Those are
+//! compared with [`RuntimeValue::render`].
+
+use super::TestRunnerError;
+use crate::docs::doc_strings;
+use crate::lexer::scan_tokens;
+use crate::parser::{parse_ast, Ast, Module, ModuleForm};
+use crate::runtime::RuntimeValue;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+
+const PROMPTS: [&str; 2] = ["tonic>", "iex>"];
+const CONTINUATION: &str = "...>";
+const GENERATED_MODULE_PREFIX: &str = "TonicDoctest";
+
+/// Whether `module` holds generated doctest functions rather than user code.
+pub(crate) fn is_generated_module(module: &str) -> bool {
+    module
+        .strip_prefix(GENERATED_MODULE_PREFIX)
+        .is_some_and(|rest| rest.starts_with('.'))
+}
+
+/// One example block, run as a test case.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Doctest {
+    /// `TestModule.doctest Module.fun/1 (1)`.
+    pub(crate) id: String,
+    /// The test module that declared `doctest`.
+    pub(crate) module: String,
+    /// Qualified name of the generated function that evaluates the block.
+    pub(crate) function: String,
+    /// `path:line` of the block's first prompt.
+    location: String,
+    /// Offset of the block's first prompt in the test file; `None` when the
+    /// docs live in a project source file.
+    offset: Option<usize>,
+    steps: Vec<Step>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Step {
+    code: String,
+    expected: Option<String>,
+}
+
+impl Doctest {
+    /// Compare the values returned by the generated function with the
+    /// expected results written in the docs.
+    pub(crate) fn check(&self, value: &RuntimeValue) -> Result<(), String> {
+        let RuntimeValue::List(values) = value else {
+            return Err(format!(
+                "doctest at {} returned {} instead of its results",
+                self.location,
+                value.render()
+            ));
+        };
+
+        let checked = self
+            .steps
+            .iter()
+            .filter(|step| step.expected.is_some())
+            .collect::<Vec<_>>();
+        if checked.len() != values.len() {
+            return Err(format!(
+                "doctest at {} returned {} result(s) for {} expected value(s)",
+                self.location,
+                values.len(),
+                checked.len()
+            ));
+        }
+
+        for (step, actual) in checked.into_iter().zip(values) {
+            let expected = step.expected.as_deref().unwrap_or_default();
+            let actual = actual.render();
+            if normalize(&actual) != normalize(expected) {
+                return Err(format!(
+                    "doctest failed at {}\n  tonic> {}\n  expected: {expected}\n  actual:   {actual}",
+                    self.location,
+                    step.code.replace('\n', "\n  ...> ")
+                ));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Prefix a runtime error with the doc location it came from.
+    pub(crate) fn locate_error(&self, error: &str) -> String {
+        format!(
+            "doctest raised at {}: {}",
+            self.location,
+            strip_generated_offset(error)
+        )
+    }
+}
+
+/// A project source file whose modules a test file may doctest.
+pub(crate) struct LibraryFile {
+    /// Path shown in doctest locations.
+    path: PathBuf,
+    source: String,
+    /// `None` when the file does not parse; compiling it reports why.
+    ast: Option<Ast>,
+}
+
+impl LibraryFile {
+    pub(crate) fn new(path: PathBuf, source: String) -> Self {
+        let ast = scan_tokens(&source)
+            .ok()
+            .and_then(|tokens| parse_ast(&tokens).ok());
+        Self { path, source, ast }
+    }
+
+    pub(crate) fn source(&self) -> &str {
+        &self.source
+    }
+}
+
+/// Whether `ast` doctests a module it does not define itself.
+pub(crate) fn doctests_outside_file(ast: &Ast) -> bool {
+    ast.modules
+        .iter()
+        .flat_map(|module| &module.forms)
+        .any(|form| match form {
+            ModuleForm::Doctest { module } => {
+                !ast.modules.iter().any(|defined| &defined.name == module)
+            }
+            _ => false,
+        })
+}
+
+/// Doctests requested by the modules of one test file, plus the source of
+/// the modules that evaluate them.
+#[derive(Debug, Default)]
+pub(crate) struct DoctestPlan {
+    pub(crate) doctests: Vec<Doctest>,
+    /// Generated modules, appended to the test file after a blank line.
+    pub(crate) source: String,
+    /// Span of each doctest's function within `source`.
+    spans: Vec<Range<usize>>,
+}
+
+impl DoctestPlan {
+    /// Collect the examples for every `doctest Module` form in `ast`, which
+    /// must be parsed from `source` alone. Targets are looked up in the test
+    /// file first, then in `library`; those that do not exist are left for the
+    /// resolver to report.
+    pub(crate) fn new(path: &Path, source: &str, ast: &Ast, library: &[LibraryFile]) -> Self {
+        let mut plan = Self::default();
+
+        for test_module in &ast.modules {
+            let targets = test_module.forms.iter().filter_map(|form| match form {
+                ModuleForm::Doctest { module } => Some(module),
+                _ => None,
+            });
+
+            let mut functions = Vec::new();
+            let mut imports = Vec::new();
+            for target in targets {
+                let in_file = ast
+                    .modules
+                    .iter()
+                    .find(|module| &module.name == target)
+                    .map(|module| (path, source, module, true));
+                let in_library = || {
+                    library.iter().find_map(|file| {
+                        let module = file
+                            .ast
+                            .as_ref()?
+                            .modules
+                            .iter()
+                            .find(|module| &module.name == target)?;
+                        Some((file.path.as_path(), file.source.as_str(), module, false))
+                    })
+                };
+                let Some((doc_path, doc_source, module, in_test_file)) =
+                    in_file.or_else(in_library)
+                else {
+                    continue;
+                };
+                if module
+                    .functions
+                    .iter()
+                    .any(|function| !function.is_private())
+                {
+                    imports.push(format!("  import {target}\n"));
+                }
+
+                for block in doc_blocks(doc_path, doc_source, module, in_test_file) {
+                    let index = plan.doctests.len() + 1;
+                    functions.push(block.render_function(index));
+                    plan.doctests.push(Doctest {
+                        id: format!(
+                            "{}.doctest {} ({})",
+                            test_module.name, block.label, block.number
+                        ),
+                        module: test_module.name.clone(),
+                        function: format!(
+                            "{GENERATED_MODULE_PREFIX}.{}.doctest_{index}",
+                            test_module.name
+                        ),
+                        location: block.location,
+                        offset: block.offset,
+                        steps: block.steps,
+                    });
+                }
+            }
+
+            if functions.is_empty() {
+                continue;
+            }
+
+            plan.source.push_str(&format!(
+                "defmodule {GENERATED_MODULE_PREFIX}.{} do\n",
+                test_module.name
+            ));
+            for import in imports {
+                plan.source.push_str(&import);
+            }
+            for function in functions {
+                let start = plan.source.len();
+                plan.source.push_str(&function);
+                plan.spans.push(start..plan.source.len());
+            }
+            plan.source.push_str("end\n");
+        }
+
+        plan
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.doctests.is_empty()
+    }
+
+    /// Point a compile error inside the generated source (which starts at
+    /// `base` in the compiled text) back at the example it came from.
+    pub(crate) fn locate_compile_error(
+        &self,
+        error: TestRunnerError,
+        base: usize,
+    ) -> TestRunnerError {
+        let TestRunnerError::SourceDiagnostic {
+            message,
+            filename,
+            source,
+            offset: Some(offset),
+        } = error
+        else {
+            return error;
+        };
+
+        let doctest = offset.checked_sub(base).and_then(|relative| {
+            self.spans
+                .iter()
+                .position(|span| span.contains(&relative))
+                .map(|index| &self.doctests[index])
+        });
+
+        match doctest {
+            Some(doctest) if doctest.offset.is_some() => TestRunnerError::SourceDiagnostic {
+                message: format!(
+                    "{} does not compile: {}",
+                    doctest.id,
+                    strip_generated_offset(&message)
+                ),
+                filename,
+                source,
+                offset: doctest.offset,
+            },
+            Some(doctest) => TestRunnerError::Failure(format!(
+                "{} does not compile at {}: {}",
+                doctest.id,
+                doctest.location,
+                strip_generated_offset(&message)
+            )),
+            None => TestRunnerError::SourceDiagnostic {
+                message,
+                filename,
+                source,
+                offset: Some(offset),
+            },
+        }
+    }
+}
+
+struct DocBlock {
+    /// `Module.fun/arity` or `module Module`.
+    label: String,
+    /// 1-based position of the block among those with the same label.
+    number: usize,
+    location: String,
+    offset: Option<usize>,
+    steps: Vec<Step>,
+}
+
+impl DocBlock {
+    fn render_function(&self, index: usize) -> String {
+        let mut body = String::new();
+        let mut results = Vec::new();
+
+        for step in &self.steps {
+            let code = step.code.replace('\n', "\n    ");
+            if step.expected.is_some() {
+                let binding = format!("doctest_value_{}", results.len() + 1);
+                body.push_str(&format!("    {binding} = {code}\n"));
+                results.push(binding);
+            } else {
+                body.push_str(&format!("    {code}\n"));
+            }
+        }
+
+        format!(
+            "  def doctest_{index}() do\n{body}    [{}]\n  end\n",
+            results.join(", ")
+        )
+    }
+}
+
+/// Example blocks from `module`'s docs, in source order. `in_test_file`
+/// says whether `source` is the test file, whose offsets diagnostics use.
+fn doc_blocks(path: &Path, source: &str, module: &Module, in_test_file: bool) -> Vec<DocBlock> {
+    let mut blocks: Vec<DocBlock> = Vec::new();
+
+    for doc in doc_strings(module) {
+        let label = match doc.function {
+            None => format!("module {}", module.name),
+            Some(function) => format!(
+                "{}.{}/{}",
+                module.name,
+                function.name,
+                function.params.len()
+            ),
+        };
+
+        // Docs are string literals, so line `n` of the value sits `n` lines
+        // below the opening quote.
+        let first_line = line_index(source, doc.offset);
+        for (line, steps) in parse_examples(doc.text) {
+            let line_offset = line_start(source, first_line + line);
+            let indent = source[line_offset..]
+                .chars()
+                .take_while(|ch| ch.is_whitespace() && *ch != '\n')
+                .count();
+            let number = blocks.iter().filter(|block| block.label == label).count() + 1;
+
+            blocks.push(DocBlock {
+                label: label.clone(),
+                number,
+                location: format!("{}:{}", path.display(), first_line + line + 1),
+                offset: in_test_file.then_some(line_offset + indent),
+                steps,
+            });
+        }
+    }
+
+    blocks
+}
+
+/// Split a doc string into example blocks, each tagged with the index of its
+/// first line within `doc`.
+fn parse_examples(doc: &str) -> Vec<(usize, Vec<Step>)> {
+    let mut blocks = Vec::new();
+    let mut current: Option<(usize, Vec<Step>)> = None;
+
+    for (index, line) in doc.lines().enumerate() {
+        let trimmed = line.trim();
+
+        if let Some(code) = PROMPTS
+            .iter()
+            .find_map(|prompt| trimmed.strip_prefix(prompt))
+        {
+            let (_, steps) = current.get_or_insert_with(|| (index, Vec::new()));
+            // A prompt after an expected result starts a new step in the
+            // same block; bindings still carry over.
+            steps.push(Step {
+                code: code.trim().to_string(),
+                expected: None,
+            });
+            continue;
+        }
+
+        let Some((_, steps)) = current.as_mut() else {
+            continue;
+        };
+
+        if trimmed.is_empty() {
+            blocks.extend(current.take());
+            continue;
+        }
+
+        let step = steps.last_mut().expect("blocks start with a prompt");
+        match (trimmed.strip_prefix(CONTINUATION), &mut step.expected) {
+            (Some(code), None) => {
+                step.code.push('\n');
+                step.code.push_str(code.trim());
+            }
+            (_, Some(expected)) => {
+                expected.push('\n');
+                expected.push_str(trimmed);
+            }
+            (None, expected @ None) => *expected = Some(trimmed.to_string()),
+        }
+    }
+
+    blocks.extend(current);
+    blocks
+}
+
+/// Diagnostics end with `at offset N`, which for doctests points into the
+/// generated source rather than the file the user wrote.
+fn strip_generated_offset(message: &str) -> &str {
+    match message.rsplit_once(" at offset ") {
+        Some((message, offset)) if offset.bytes().all(|byte| byte.is_ascii_digit()) => message,
+        _ => message,
+    }
+}
+
+/// Compare rendered values without caring how the docs wrapped them.
+fn normalize(value: &str) -> String {
+    value.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+fn line_index(source: &str, offset: usize) -> usize {
+    source.as_bytes()[..offset.min(source.len())]
+        .iter()
+        .filter(|byte| **byte == b'\n')
+        .count()
+}
+
+fn line_start(source: &str, line: usize) -> usize {
+    if line == 0 {
+        return 0;
+    }
+    source
+        .match_indices('\n')
+        .nth(line - 1)
+        .map(|(index, _)| index + 1)
+        .unwrap_or(source.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_examples_groups_prompts_until_blank_line() {
+        let doc = "Adds numbers.\n\n    iex> x = 1\n    tonic> add(x,\n    ...>   2)\n    3\n    tonic> add(1, 1)\n    2\n\n    tonic> add(0, 0)\n    0\n";
+
+        assert_eq!(
+            parse_examples(doc),
+            vec![
+                (
+                    2,
+                    vec![
+                        Step {
+                            code: "x = 1".to_string(),
+                            expected: None,
+                        },
+                        Step {
+                            code: "add(x,\n2)".to_string(),
+                            expected: Some("3".to_string()),
+                        },
+                        Step {
+                            code: "add(1, 1)".to_string(),
+                            expected: Some("2".to_string()),
+                        },
+                    ],
+                ),
+                (
+                    9,
+                    vec![Step {
+                        code: "add(0, 0)".to_string(),
+                        expected: Some("0".to_string()),
+                    }],
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn check_fails_when_result_count_differs_from_expected_values() {
+        let step = |code: &str, expected: &str| Step {
+            code: code.to_string(),
+            expected: Some(expected.to_string()),
+        };
+        let doctest = Doctest {
+            id: "MathTest.doctest Math.add/2 (1)".to_string(),
+            module: "MathTest".to_string(),
+            function: "TonicDoctest.MathTest.doctest_1".to_string(),
+            location: "math.tn:3".to_string(),
+            offset: None,
+            steps: vec![step("add(1, 2)", "3"), step("add(2, 2)", "4")],
+        };
+
+        assert_eq!(
+            doctest.check(&RuntimeValue::List(vec![
+                RuntimeValue::Int(3),
+                RuntimeValue::Int(4)
+            ])),
+            Ok(())
+        );
+        assert_eq!(
+            doctest.check(&RuntimeValue::List(vec![RuntimeValue::Int(3)])),
+            Err("doctest at math.tn:3 returned 1 result(s) for 2 expected value(s)".to_string())
+        );
+        assert!(doctest.check(&RuntimeValue::List(Vec::new())).is_err());
+    }
+}
@@ -0,0 +1,551 @@
+//! `tonic test --backend native|both`: each suite is compiled through MIR and
+//! the C backend into a harness binary that runs one test function per
+//! process. Arguments and return values cross the process boundary in the
+//! term encoding documented in `c_backend/test_harness.rs`.
+
+use super::{TestCaseResult, TestCaseStatus, TestExecResult};
+use crate::c_backend::lower_mir_to_c_test_harness;
+use crate::interop::CapturedHostOutput;
+use crate::ir::IrProgram;
+use crate::linker::compile_c_to_executable;
+use crate::mir::{lower_ir_to_mir, optimize_for_native_backend};
+use crate::runtime::RuntimeValue;
+use crate::target::TargetTriple;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Child, ChildStderr, ChildStdin, Command, Stdio};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{Duration, Instant};
+
+/// Harness binaries are cached here, relative to the project root, by a hash
+/// of their C source.
+const NATIVE_TEST_BUILD_DIR: &str = ".tonic/build/test";
+
+/// The harness build directory of the project containing `path`, or of the
+/// current directory outside a project.
+pub(crate) fn native_test_build_dir(path: &Path) -> PathBuf {
+    let project_root = crate::manifest::find_project_root(path)
+        .unwrap_or_else(|| std::env::current_dir().unwrap_or_default());
+    project_root.join(NATIVE_TEST_BUILD_DIR)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TestBackend {
+    Interpreter,
+    Native,
+    /// Run on both and fail cases whose outcome differs.
+    Both,
+}
+
+impl TestBackend {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "interpreter" => Some(Self::Interpreter),
+            "native" => Some(Self::Native),
+            "both" => Some(Self::Both),
+            _ => None,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Interpreter => "interpreter",
+            Self::Native => "native",
+            Self::Both => "both",
+        }
+    }
+
+    pub(crate) fn needs_native(self) -> bool {
+        self != Self::Interpreter
+    }
+}
+
+/// A test file compiled to a native harness binary.
+#[derive(Debug)]
+pub(crate) struct NativeSuite {
+    executable: PathBuf,
+}
+
+impl NativeSuite {
+    pub(crate) fn build(file: &Path, source: &str, ir: &IrProgram) -> Result<Self, String> {
+        let failure = |error: String| {
+            format!(
+                "native backend: failed to compile {}: {error}",
+                file.display()
+            )
+        };
+
+        let mir = lower_ir_to_mir(ir).map_err(|error| failure(error.to_string()))?;
+        let mir = optimize_for_native_backend(mir);
+        let c_source = lower_mir_to_c_test_harness(&mir, &file.display().to_string(), source)
+            .map_err(|error| failure(error.to_string()))?;
+
+        let stem = file
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().replace('.', "_"))
+            .unwrap_or_else(|| "suite".to_string());
+        let build_dir = native_test_build_dir(file);
+        let executable = build_dir.join(format!("{stem}-{:016x}", super::fnv1a(&c_source)));
+        if executable.is_file() {
+            return Ok(Self { executable });
+        }
+
+        std::fs::create_dir_all(&build_dir)
+            .map_err(|error| failure(format!("cannot create {}: {error}", build_dir.display())))?;
+        let c_path = executable.with_extension("c");
+        std::fs::write(&c_path, &c_source)
+            .map_err(|error| failure(format!("cannot write {}: {error}", c_path.display())))?;
+        compile_c_to_executable(&c_path, &executable, &TargetTriple::host())
+            .map_err(|error| failure(error.to_string()))?;
+
+        Ok(Self { executable })
+    }
+
+    /// Run `function` in a fresh process, capturing its output.
+    pub(super) fn call(
+        &self,
+        function: &str,
+        args: &[RuntimeValue],
+        timeout: Option<Duration>,
+        output: &mut CapturedHostOutput,
+    ) -> TestExecResult {
+        static CALLS: AtomicUsize = AtomicUsize::new(0);
+        let call = CALLS.fetch_add(1, Ordering::Relaxed);
+        let scratch = format!("{}-{call}", std::process::id());
+        let args_path = self.executable.with_extension(format!("{scratch}.args"));
+        let result_path = self.executable.with_extension(format!("{scratch}.result"));
+
+        let mut encoded = format!("{}:", args.len()).into_bytes();
+        for arg in args {
+            if let Err(error) = encode_term(arg, &mut encoded) {
+                return TestExecResult::Err(format!("native backend: {error}"));
+            }
+        }
+        if let Err(error) = std::fs::write(&args_path, encoded) {
+            return TestExecResult::Err(format!(
+                "native backend: cannot write {}: {error}",
+                args_path.display()
+            ));
+        }
+
+        let outcome = self.run_process(function, &args_path, &result_path, timeout, output);
+        let _ = std::fs::remove_file(&args_path);
+        let result = outcome.map(|()| std::fs::read(&result_path));
+        let _ = std::fs::remove_file(&result_path);
+
+        match result {
+            Ok(Ok(bytes)) => match decode_term(&mut bytes.as_slice()) {
+                Ok(value) => TestExecResult::Ok(value),
+                Err(error) => TestExecResult::Err(format!("native backend: {error}")),
+            },
+            Ok(Err(_)) => TestExecResult::Err(take_native_error(output)),
+            Err(result) => result,
+        }
+    }
+
+    /// Start `function` in `--bench` mode for `tonic bench`.
+    pub(crate) fn bench(&self, function: &str) -> Result<NativeBench, String> {
+        let mut child = Command::new(&self.executable)
+            .arg("--bench")
+            .arg(function)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(|error| {
+                format!(
+                    "native backend: cannot run {}: {error}",
+                    self.executable.display()
+                )
+            })?;
+        let stdin = child.stdin.take();
+        let stderr = child.stderr.take().map(BufReader::new);
+        match (stdin, stderr) {
+            (Some(stdin), Some(stderr)) => Ok(NativeBench {
+                child,
+                stdin: Some(stdin),
+                stderr,
+            }),
+            _ => Err("native backend: cannot attach to the benchmark process".to_string()),
+        }
+    }
+
+    fn run_process(
+        &self,
+        function: &str,
+        args_path: &Path,
+        result_path: &Path,
+        timeout: Option<Duration>,
+        output: &mut CapturedHostOutput,
+    ) -> Result<(), TestExecResult> {
+        let mut child = Command::new(&self.executable)
+            .arg(function)
+            .arg(args_path)
+            .arg(result_path)
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(|error| {
+                TestExecResult::Err(format!(
+                    "native backend: cannot run {}: {error}",
+                    self.executable.display()
+                ))
+            })?;
+
+        let read = |stream: Option<Box<dyn Read + Send>>| {
+            std::thread::spawn(move || {
+                let mut text = Vec::new();
+                if let Some(mut stream) = stream {
+                    let _ = stream.read_to_end(&mut text);
+                }
+                String::from_utf8_lossy(&text).into_owned()
+            })
+        };
+        let stdout = read(
+            child
+                .stdout
+                .take()
+                .map(|s| Box::new(s) as Box<dyn Read + Send>),
+        );
+        let stderr = read(
+            child
+                .stderr
+                .take()
+                .map(|s| Box::new(s) as Box<dyn Read + Send>),
+        );
+
+        let deadline = timeout.map(|limit| (Instant::now() + limit, limit));
+        let timed_out = loop {
+            match child.try_wait() {
+                Ok(Some(_)) => break None,
+                Ok(None) => {}
+                Err(error) => {
+                    return Err(TestExecResult::Err(format!(
+                        "native backend: cannot wait for test process: {error}"
+                    )))
+                }
+            }
+            if let Some((deadline, limit)) = deadline {
+                if Instant::now() >= deadline {
+                    let _ = child.kill();
+                    let _ = child.wait();
+                    break Some(limit);
+                }
+            }
+            std::thread::sleep(Duration::from_millis(1));
+        };
+
+        output.stdout.push_str(&stdout.join().unwrap_or_default());
+        output.stderr.push_str(&stderr.join().unwrap_or_default());
+        match timed_out {
+            Some(limit) => Err(TestExecResult::TimedOut(limit.as_millis() as u64)),
+            None => Ok(()),
+        }
+    }
+}
+
+/// A harness process running one benchmark function; each
+/// [`measure`](Self::measure) call times a batch of iterations.
+pub(crate) struct NativeBench {
+    child: Child,
+    stdin: Option<ChildStdin>,
+    stderr: BufReader<ChildStderr>,
+}
+
+impl NativeBench {
+    pub(crate) fn measure(&mut self, iterations: u64) -> Result<Duration, String> {
+        if let Some(stdin) = self.stdin.as_mut() {
+            // A failed write means the process exited; its stderr explains why.
+            let _ = writeln!(stdin, "{iterations}").and_then(|()| stdin.flush());
+        }
+
+        let mut output = CapturedHostOutput::default();
+        let mut line = String::new();
+        loop {
+            line.clear();
+            match self.stderr.read_line(&mut line) {
+                Ok(0) | Err(_) => {
+                    self.stdin = None;
+                    let _ = self.child.wait();
+                    return Err(take_native_error(&mut output));
+                }
+                Ok(_) => {}
+            }
+            match line.trim_end().strip_prefix("bench: ") {
+                Some(nanos) => {
+                    let nanos = nanos.parse::<u64>().map_err(|_| {
+                        format!("native backend: malformed benchmark timing {nanos:?}")
+                    })?;
+                    return Ok(Duration::from_nanos(nanos));
+                }
+                None => output.stderr.push_str(&line),
+            }
+        }
+    }
+}
+
+impl Drop for NativeBench {
+    fn drop(&mut self) {
+        // Closing stdin ends the benchmark loop.
+        self.stdin = None;
+        let _ = self.child.wait();
+    }
+}
+
+/// The native runtime reports errors as `error: ...` on stderr before
+/// exiting; move that report out of the captured stderr into the error.
+fn take_native_error(output: &mut CapturedHostOutput) -> String {
+    let start = if output.stderr.starts_with("error: ") {
+        Some(0)
+    } else {
+        output.stderr.rfind("\nerror: ").map(|index| index + 1)
+    };
+    match start {
+        Some(start) => {
+            let report = output.stderr.split_off(start);
+            report["error: ".len()..].trim_end().to_string()
+        }
+        None => "native test process exited without a result".to_string(),
+    }
+}
+
+/// Merge a case's interpreter and native results: the interpreter's result
+/// when both backends agree on the status and stdout, otherwise a failure
+/// describing the divergence.
+pub(super) fn compare_backends(
+    interpreted: TestCaseResult,
+    native: TestCaseResult,
+) -> TestCaseResult {
+    let status_name = |status| match status {
+        TestCaseStatus::Passed => "passed",
+        TestCaseStatus::Failed => "failed",
+        TestCaseStatus::Skipped => "skipped",
+    };
+
+    let mut lines = Vec::new();
+    if interpreted.status != native.status {
+        lines.push(format!(
+            "backend divergence: interpreter {}, native {}",
+            status_name(interpreted.status),
+            status_name(native.status)
+        ));
+        for (backend, result) in [("interpreter", &interpreted), ("native", &native)] {
+            if let Some(error) = result.error.as_deref().filter(|error| !error.is_empty()) {
+                lines.push(format!("  {backend} error: {error}"));
+            }
+        }
+    } else if interpreted.stdout != native.stdout {
+        lines.push("backend divergence: stdout differs".to_string());
+        for (backend, result) in [("interpreter", &interpreted), ("native", &native)] {
+            lines.push(format!("  {backend} stdout: {:?}", result.stdout));
+        }
+    }
+
+    if lines.is_empty() {
+        return interpreted;
+    }
+    TestCaseResult {
+        status: TestCaseStatus::Failed,
+        error: Some(lines.join("\n")),
+        diff: None,
+        ..interpreted
+    }
+}
+
+fn encode_term(value: &RuntimeValue, out: &mut Vec<u8>) -> Result<(), String> {
+    let mut text = |tag: char, text: &str| {
+        out.extend(format!("{tag}{}:", text.len()).bytes());
+        out.extend(text.bytes());
+    };
+    match value {
+        RuntimeValue::Int(value) => out.extend(format!("i{value};").bytes()),
+        RuntimeValue::Float(value) => out.extend(format!("f{value};").bytes()),
+        RuntimeValue::Bool(value) => out.extend(if *value { b"b1" } else { b"b0" }),
+        RuntimeValue::Nil => out.push(b'n'),
+        RuntimeValue::String(value) => text('s', value),
+        RuntimeValue::Atom(value) => text('a', value),
+        RuntimeValue::ResultOk(inner) | RuntimeValue::ResultErr(inner) => {
+            out.push(if matches!(value, RuntimeValue::ResultOk(_)) {
+                b'o'
+            } else {
+                b'e'
+            });
+            encode_term(inner, out)?;
+        }
+        RuntimeValue::Tuple(left, right) => {
+            out.push(b't');
+            encode_term(left, out)?;
+            encode_term(right, out)?;
+        }
+        RuntimeValue::Range(start, end) => {
+            out.extend(format!("ri{start};i{end};").bytes());
+        }
+        RuntimeValue::List(items) | RuntimeValue::Binary(items) => {
+            let tag = if matches!(value, RuntimeValue::List(_)) {
+                'l'
+            } else {
+                'y'
+            };
+            out.extend(format!("{tag}{}:", items.len()).bytes());
+            for item in items {
+                encode_term(item, out)?;
+            }
+        }
+        RuntimeValue::Map(entries) | RuntimeValue::Keyword(entries) => {
+            let tag = if matches!(value, RuntimeValue::Map(_)) {
+                'm'
+            } else {
+                'k'
+            };
+            out.extend(format!("{tag}{}:", entries.len()).bytes());
+            for (key, value) in entries {
+                encode_term(key, out)?;
+                encode_term(value, out)?;
+            }
+        }
+        RuntimeValue::SteppedRange(..) | RuntimeValue::Closure(_) => {
+            return Err(format!(
+                "cannot pass a {} to native test code",
+                value.kind_label()
+            ))
+        }
+    }
+    Ok(())
+}
+
+fn decode_term(input: &mut &[u8]) -> Result<RuntimeValue, String> {
+    let (&tag, rest) = input
+        .split_first()
+        .ok_or("truncated test result".to_string())?;
+    *input = rest;
+
+    let value = match tag {
+        b'i' => {
+            let text = read_until(input, b';')?;
+            RuntimeValue::Int(
+                text.parse()
+                    .map_err(|_| format!("invalid integer '{text}'"))?,
+            )
+        }
+        b'f' => RuntimeValue::Float(read_until(input, b';')?),
+        b'b' => {
+            let (&flag, rest) = input
+                .split_first()
+                .ok_or("truncated test result".to_string())?;
+            *input = rest;
+            RuntimeValue::Bool(flag == b'1')
+        }
+        b'n' => RuntimeValue::Nil,
+        b's' => RuntimeValue::String(read_text(input)?),
+        b'a' => RuntimeValue::Atom(read_text(input)?),
+        b'o' => RuntimeValue::ResultOk(Box::new(decode_term(input)?)),
+        b'e' => RuntimeValue::ResultErr(Box::new(decode_term(input)?)),
+        b't' => {
+            let left = decode_term(input)?;
+            let right = decode_term(input)?;
+            RuntimeValue::Tuple(Box::new(left), Box::new(right))
+        }
+        b'r' => match (decode_term(input)?, decode_term(input)?) {
+            (RuntimeValue::Int(start), RuntimeValue::Int(end)) => RuntimeValue::Range(start, end),
+            _ => return Err("range bounds should be integers".to_string()),
+        },
+        // Closures cannot leave the native process; keep their rendering.
+        b'c' => RuntimeValue::String(format!("#Function<{}>", read_until(input, b';')?)),
+        b'l' | b'y' => {
+            let len = read_len(input)?;
+            let items = (0..len)
+                .map(|_| decode_term(input))
+                .collect::<Result<Vec<_>, _>>()?;
+            if tag == b'l' {
+                RuntimeValue::List(items)
+            } else {
+                RuntimeValue::Binary(items)
+            }
+        }
+        b'm' | b'k' => {
+            let len = read_len(input)?;
+            let entries = (0..len)
+                .map(|_| Ok((decode_term(input)?, decode_term(input)?)))
+                .collect::<Result<Vec<_>, String>>()?;
+            if tag == b'm' {
+                RuntimeValue::Map(entries)
+            } else {
+                RuntimeValue::Keyword(entries)
+            }
+        }
+        other => return Err(format!("unknown value tag '{}'", other as char)),
+    };
+    Ok(value)
+}
+
+fn read_until(input: &mut &[u8], end: u8) -> Result<String, String> {
+    let position = input
+        .iter()
+        .position(|byte| *byte == end)
+        .ok_or("truncated test result".to_string())?;
+    let text = String::from_utf8_lossy(&input[..position]).into_owned();
+    *input = &input[position + 1..];
+    Ok(text)
+}
+
+fn read_len(input: &mut &[u8]) -> Result<usize, String> {
+    let text = read_until(input, b':')?;
+    text.parse().map_err(|_| format!("invalid length '{text}'"))
+}
+
+fn read_text(input: &mut &[u8]) -> Result<String, String> {
+    let len = read_len(input)?;
+    if input.len() < len {
+        return Err("truncated test result".to_string());
+    }
+    let text = String::from_utf8_lossy(&input[..len]).into_owned();
+    *input = &input[len..];
+    Ok(text)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn terms_round_trip_through_the_harness_encoding() {
+        let value = RuntimeValue::ResultErr(Box::new(RuntimeValue::Tuple(
+            Box::new(RuntimeValue::Atom("assertion_failed".to_string())),
+            Box::new(RuntimeValue::List(vec![
+                RuntimeValue::Keyword(vec![(
+                    RuntimeValue::Atom("left".to_string()),
+                    RuntimeValue::String("a:b;\"c\"\n".to_string()),
+                )]),
+                RuntimeValue::Map(vec![(
+                    RuntimeValue::Int(-3),
+                    RuntimeValue::Float("1.5".to_string()),
+                )]),
+                RuntimeValue::Range(1, 4),
+                RuntimeValue::Bool(true),
+                RuntimeValue::Nil,
+            ])),
+        )));
+
+        let mut encoded = Vec::new();
+        encode_term(&value, &mut encoded).expect("value should encode");
+        assert_eq!(
+            decode_term(&mut encoded.as_slice()).expect("value should decode"),
+            value
+        );
+    }
+
+    #[test]
+    fn native_errors_move_from_stderr_into_the_error() {
+        let mut output = CapturedHostOutput {
+            stdout: String::new(),
+            stderr: "warning: slow\nerror: no matching clause at offset 12\n".to_string(),
+        };
+
+        assert_eq!(
+            take_native_error(&mut output),
+            "no matching clause at offset 12"
+        );
+        assert_eq!(output.stderr, "warning: slow\n");
+    }
+}
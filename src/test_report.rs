@@ -0,0 +1,266 @@
+//! Machine-readable `tonic test` reports for CI: JUnit XML (`--format junit`)
+//! and TAP version 13 (`--format tap`).
+
+use super::coverage::xml_escape;
+use super::{TestCaseResult, TestCaseStatus, TestRunReport};
+use std::time::Duration;
+
+impl TestRunReport {
+    /// JUnit XML with one `<testsuite>` per test module, in run order.
+    pub fn render_junit(&self) -> String {
+        let mut suites: Vec<(&str, Vec<&TestCaseResult>)> = Vec::new();
+        for result in &self.results {
+            match suites
+                .iter_mut()
+                .find(|(module, _)| *module == result.module)
+            {
+                Some((_, results)) => results.push(result),
+                None => suites.push((&result.module, vec![result])),
+            }
+        }
+
+        let mut out = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+        out.push_str(&format!(
+            "<testsuites name=\"tonic test\" tests=\"{}\" failures=\"{}\" errors=\"0\" skipped=\"{}\" time=\"{}\">\n",
+            self.total,
+            self.failed,
+            self.skipped,
+            seconds(self.duration)
+        ));
+
+        for (module, results) in suites {
+            let count = |status| {
+                results
+                    .iter()
+                    .filter(|result| result.status == status)
+                    .count()
+            };
+            out.push_str(&format!(
+                "  <testsuite name=\"{}\" tests=\"{}\" failures=\"{}\" errors=\"0\" skipped=\"{}\" time=\"{}\">\n",
+                xml_escape(module),
+                results.len(),
+                count(TestCaseStatus::Failed),
+                count(TestCaseStatus::Skipped),
+                seconds(results.iter().map(|result| result.duration).sum())
+            ));
+
+            for result in results {
+                out.push_str(&format!(
+                    "    <testcase name=\"{}\" classname=\"{}\" time=\"{}\"",
+                    xml_escape(case_name(result)),
+                    xml_escape(module),
+                    seconds(result.duration)
+                ));
+                let error = result.error.as_deref().unwrap_or_default();
+                let detail = match result.status {
+                    TestCaseStatus::Passed => None,
+                    TestCaseStatus::Failed => Some(format!(
+                        "      <failure message=\"{}\" type=\"failure\">{}</failure>\n",
+                        xml_escape(error.lines().next().unwrap_or_default()),
+                        xml_escape(error)
+                    )),
+                    TestCaseStatus::Skipped if error.is_empty() => {
+                        Some("      <skipped/>\n".to_string())
+                    }
+                    TestCaseStatus::Skipped => Some(format!(
+                        "      <skipped message=\"{}\"/>\n",
+                        xml_escape(error)
+                    )),
+                };
+                if detail.is_none() && result.stdout.is_empty() && result.stderr.is_empty() {
+                    out.push_str("/>\n");
+                    continue;
+                }
+
+                out.push_str(">\n");
+                out.push_str(&detail.unwrap_or_default());
+                for (tag, output) in [
+                    ("system-out", &result.stdout),
+                    ("system-err", &result.stderr),
+                ] {
+                    if !output.is_empty() {
+                        out.push_str(&format!("      <{tag}>{}</{tag}>\n", xml_escape(output)));
+                    }
+                }
+                out.push_str("    </testcase>\n");
+            }
+
+            out.push_str("  </testsuite>\n");
+        }
+
+        out.push_str("</testsuites>\n");
+        out
+    }
+
+    /// TAP version 13; each test point carries a YAML block with its
+    /// duration, failure message and captured output.
+    pub fn render_tap(&self) -> String {
+        let mut out = format!("TAP version 13\n1..{}\n", self.results.len());
+
+        for (index, result) in self.results.iter().enumerate() {
+            let number = index + 1;
+            let description = result.id.replace('\\', "\\\\").replace('#', "\\#");
+            let error = result.error.as_deref().unwrap_or_default();
+            out.push_str(&match result.status {
+                TestCaseStatus::Passed => format!("ok {number} - {description}\n"),
+                TestCaseStatus::Failed => format!("not ok {number} - {description}\n"),
+                TestCaseStatus::Skipped if error.is_empty() => {
+                    format!("ok {number} - {description} # SKIP\n")
+                }
+                TestCaseStatus::Skipped => format!(
+                    "ok {number} - {description} # SKIP {}\n",
+                    error.lines().next().unwrap_or_default()
+                ),
+            });
+
+            out.push_str("  ---\n");
+            out.push_str(&format!(
+                "  duration_ms: {}\n",
+                super::duration_ms(result.duration)
+            ));
+            if result.status == TestCaseStatus::Failed {
+                push_yaml_block(&mut out, "message", error);
+            }
+            push_yaml_block(&mut out, "stdout", &result.stdout);
+            push_yaml_block(&mut out, "stderr", &result.stderr);
+            out.push_str("  ...\n");
+        }
+
+        out.push_str(&format!(
+            "# tests {}\n# pass {}\n# fail {}\n# skip {}\n# duration_ms {}\n",
+            self.total,
+            self.passed,
+            self.failed,
+            self.skipped,
+            super::duration_ms(self.duration)
+        ));
+        out
+    }
+}
+
+/// The test's name within its module: `MathTest.test_add` gives `test_add`
+/// and `MathTest: add adds` gives `add adds`.
+fn case_name(result: &TestCaseResult) -> &str {
+    result
+        .id
+        .strip_prefix(result.module.as_str())
+        .and_then(|rest| rest.strip_prefix('.').or_else(|| rest.strip_prefix(": ")))
+        .unwrap_or(&result.id)
+}
+
+fn seconds(duration: Duration) -> String {
+    format!("{:.3}", duration.as_secs_f64())
+}
+
+fn push_yaml_block(out: &mut String, key: &str, text: &str) {
+    if text.is_empty() {
+        return;
+    }
+
+    out.push_str(&format!("  {key}: |-\n"));
+    for line in text.lines() {
+        if line.is_empty() {
+            out.push('\n');
+        } else {
+            out.push_str(&format!("    {line}\n"));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn result(id: &str, status: TestCaseStatus, error: Option<&str>) -> TestCaseResult {
+        TestCaseResult {
+            id: id.to_string(),
+            module: "MathTest".to_string(),
+            status,
+            error: error.map(str::to_string),
+            duration: Duration::from_millis(5),
+            stdout: String::new(),
+            stderr: String::new(),
+            diff: None,
+        }
+    }
+
+    fn report() -> TestRunReport {
+        let mut failed = result(
+            "MathTest: add <fails>",
+            TestCaseStatus::Failed,
+            Some("assert_equal failed: values are not equal\n  left:  3\n  right: 4"),
+        );
+        failed.stdout = "printed\n".to_string();
+
+        TestRunReport {
+            total: 3,
+            passed: 1,
+            failed: 1,
+            skipped: 1,
+            duration: Duration::from_millis(15),
+            results: vec![
+                result("MathTest.test_ok", TestCaseStatus::Passed, None),
+                failed,
+                result(
+                    "MathTest: todo #1",
+                    TestCaseStatus::Skipped,
+                    Some("not ready"),
+                ),
+            ],
+            coverage: None,
+            snapshots: Default::default(),
+        }
+    }
+
+    #[test]
+    fn junit_groups_cases_by_module_with_failures_skips_and_output() {
+        assert_eq!(
+            report().render_junit(),
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+             <testsuites name=\"tonic test\" tests=\"3\" failures=\"1\" errors=\"0\" skipped=\"1\" time=\"0.015\">\n\
+             \x20 <testsuite name=\"MathTest\" tests=\"3\" failures=\"1\" errors=\"0\" skipped=\"1\" time=\"0.015\">\n\
+             \x20   <testcase name=\"test_ok\" classname=\"MathTest\" time=\"0.005\"/>\n\
+             \x20   <testcase name=\"add &lt;fails&gt;\" classname=\"MathTest\" time=\"0.005\">\n\
+             \x20     <failure message=\"assert_equal failed: values are not equal\" type=\"failure\">assert_equal failed: values are not equal\n  left:  3\n  right: 4</failure>\n\
+             \x20     <system-out>printed\n</system-out>\n\
+             \x20   </testcase>\n\
+             \x20   <testcase name=\"todo #1\" classname=\"MathTest\" time=\"0.005\">\n\
+             \x20     <skipped message=\"not ready\"/>\n\
+             \x20   </testcase>\n\
+             \x20 </testsuite>\n\
+             </testsuites>\n"
+        );
+    }
+
+    #[test]
+    fn tap_reports_each_case_with_a_yaml_block() {
+        assert_eq!(
+            report().render_tap(),
+            "TAP version 13\n\
+             1..3\n\
+             ok 1 - MathTest.test_ok\n\
+             \x20 ---\n\
+             \x20 duration_ms: 5\n\
+             \x20 ...\n\
+             not ok 2 - MathTest: add <fails>\n\
+             \x20 ---\n\
+             \x20 duration_ms: 5\n\
+             \x20 message: |-\n\
+             \x20   assert_equal failed: values are not equal\n\
+             \x20     left:  3\n\
+             \x20     right: 4\n\
+             \x20 stdout: |-\n\
+             \x20   printed\n\
+             \x20 ...\n\
+             ok 3 - MathTest: todo \\#1 # SKIP not ready\n\
+             \x20 ---\n\
+             \x20 duration_ms: 5\n\
+             \x20 ...\n\
+             # tests 3\n\
+             # pass 1\n\
+             # fail 1\n\
+             # skip 1\n\
+             # duration_ms 15\n"
+        );
+    }
+}
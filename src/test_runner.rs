@@ -1,19 +1,60 @@
+use crate::interop::{
+    capture_host_output_with_stdin, mock_scope, property_seed, set_mock_scope, set_property_seed,
+    set_snapshot_scope, snapshot_scope, CapturedHostOutput, MockScope, PropertySeed,
+    SnapshotRecord, SnapshotScope,
+};
 use crate::ir::{lower_ast_to_ir, IrProgram};
 use crate::lexer::scan_tokens;
-use crate::manifest::inject_optional_stdlib;
-use crate::parser::parse_ast;
+use crate::manifest::{self, inject_optional_stdlib};
+use crate::parser::{parse_ast, Ast, Expr, Function, TestBlockKind};
 use crate::resolver::resolve_ast;
-use crate::runtime::{evaluate_named_function, RuntimeValue};
+use crate::runtime::{evaluate_named_function_with_args, RuntimeError, RuntimeValue};
 use crate::typing::infer_types;
 use serde_json::json;
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet, VecDeque};
 use std::path::{Path, PathBuf};
+use std::sync::{Arc, Condvar, Mutex};
 use std::time::{Duration, Instant};
 
+#[path = "test_coverage.rs"]
+mod coverage;
+pub use coverage::{CoverageFormat, CoverageOptions, CoverageReport};
+use coverage::{CoverageRecorder, SuiteCoverage};
+
+#[path = "test_diff.rs"]
+mod diff;
+pub use diff::ValueDiff;
+
+#[path = "test_doctest.rs"]
+mod doctest;
+use doctest::{doctests_outside_file, Doctest, DoctestPlan, LibraryFile};
+
+#[path = "test_native.rs"]
+mod native;
+pub use native::TestBackend;
+pub(crate) use native::{native_test_build_dir, NativeSuite};
+
+#[path = "test_report.rs"]
+mod report;
+
+#[path = "test_selection.rs"]
+mod selection;
+pub use selection::{
+    load_failed_tests, record_failed_tests, TagFilter, TestSelection, FAILED_MANIFEST_PATH,
+};
+use selection::{module_test_tags, Tags};
+
+#[path = "test_snapshot.rs"]
+mod snapshot;
+pub use snapshot::SnapshotReport;
+use snapshot::{cassette_dir, snapshot_dir, SnapshotFile};
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum TestOutputFormat {
     Text,
     Json,
+    Junit,
+    Tap,
 }
 
 impl TestOutputFormat {
@@ -21,17 +62,51 @@ impl TestOutputFormat {
         match value {
             "text" => Some(Self::Text),
             "json" => Some(Self::Json),
+            "junit" => Some(Self::Junit),
+            "tap" => Some(Self::Tap),
             _ => None,
         }
     }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Text => "text",
+            Self::Json => "json",
+            Self::Junit => "junit",
+            Self::Tap => "tap",
+        }
+    }
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct TestCaseResult {
     pub id: String,
+    /// Test module the case belongs to.
+    pub module: String,
     pub status: TestCaseStatus,
     pub error: Option<String>,
     pub duration: Duration,
+    /// Output the case wrote to stdout and stderr, captured per case.
+    pub stdout: String,
+    pub stderr: String,
+    /// Structural diff of a failed `assert_equal`'s operands; its plain
+    /// rendering is already part of `error`.
+    pub diff: Option<ValueDiff>,
+}
+
+impl TestCaseResult {
+    /// `error` with the diff in it drawn in `color`.
+    fn error_text(&self, color: &AnsiColor) -> Option<String> {
+        let error = self.error.as_ref()?;
+        Some(match &self.diff {
+            Some(diff) => error.replacen(
+                &diff.render(&AnsiColor::plain()).join("\n  "),
+                &diff.render(color).join("\n  "),
+                1,
+            ),
+            None => error.clone(),
+        })
+    }
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -49,6 +124,9 @@ pub struct TestRunReport {
     pub skipped: usize,
     pub duration: Duration,
     pub results: Vec<TestCaseResult>,
+    /// Present when the run was started with coverage enabled.
+    pub coverage: Option<CoverageReport>,
+    pub snapshots: SnapshotReport,
 }
 
 impl TestRunReport {
@@ -74,7 +152,7 @@ impl TestRunReport {
                         "test {} ... {}FAILED{} ({duration})",
                         result.id, color.red, color.reset
                     ));
-                    if let Some(error) = &result.error {
+                    if let Some(error) = result.error_text(&color) {
                         lines.push(format!("  error: {error}"));
                     }
                 }
@@ -109,15 +187,31 @@ impl TestRunReport {
             lines.push(String::new());
             for (i, result) in failures.iter().enumerate() {
                 lines.push(format!("  {}. {}", i + 1, result.id));
-                if let Some(error) = &result.error {
+                if let Some(error) = result.error_text(&color) {
                     for error_line in error.lines() {
                         lines.push(format!("     {error_line}"));
                     }
                 }
+                for (stream, output) in [("stdout", &result.stdout), ("stderr", &result.stderr)] {
+                    if output.is_empty() {
+                        continue;
+                    }
+                    lines.push(format!("     captured {stream}:"));
+                    for output_line in output.lines() {
+                        lines.push(format!("       {output_line}"));
+                    }
+                }
                 lines.push(String::new());
             }
         }
 
+        let snapshot_lines = self.snapshots.render_text(&color);
+        if !snapshot_lines.is_empty() {
+            lines.push(String::new());
+            lines.extend(snapshot_lines);
+            lines.push(String::new());
+        }
+
         let (status, status_color) = if self.succeeded() {
             ("ok", color.green)
         } else {
@@ -140,7 +234,7 @@ impl TestRunReport {
 
     pub fn render_json(&self) -> serde_json::Value {
         let result_to_json = |result: &TestCaseResult| {
-            json!({
+            let mut value = json!({
                 "id": result.id,
                 "status": match result.status {
                     TestCaseStatus::Passed => "passed",
@@ -149,10 +243,16 @@ impl TestRunReport {
                 },
                 "error": result.error,
                 "duration_ms": duration_ms(result.duration),
-            })
+                "stdout": result.stdout,
+                "stderr": result.stderr,
+            });
+            if let Some(diff) = &result.diff {
+                value["diff"] = diff.to_json();
+            }
+            value
         };
 
-        json!({
+        let mut report = json!({
             "status": if self.succeeded() { "ok" } else { "failed" },
             "total": self.total,
             "passed": self.passed,
@@ -164,7 +264,11 @@ impl TestRunReport {
                 .filter(|r| r.status == TestCaseStatus::Failed)
                 .map(result_to_json)
                 .collect::<Vec<_>>(),
-        })
+        });
+        if !self.snapshots.is_empty() {
+            report["snapshots"] = self.snapshots.render_json();
+        }
+        report
     }
 }
 
@@ -179,14 +283,72 @@ pub enum TestRunnerError {
     },
 }
 
+/// Entries of the context map handed to setup blocks and tests.
+type Context = Vec<(RuntimeValue, RuntimeValue)>;
+
+/// One case queued by [`run`], with its own copy of its suite's program.
+struct TestCase {
+    id: String,
+    module: String,
+    /// Qualified name of the function that runs the test.
+    function: String,
+    takes_context: bool,
+    ir: IrProgram,
+    recorder: Option<CoverageRecorder>,
+    doctest: Option<Doctest>,
+    /// Cases with the same group run one at a time; `None` marks an
+    /// `@async true` case that may run alongside any other.
+    group: Option<usize>,
+    /// The context built by `setup_all`, or why `setup_all` failed.
+    context: Result<Context, String>,
+    /// `setup` blocks of the test's module and describe, in source order.
+    setups: Vec<SetupBlock>,
+    /// `__snapshots__/<file>/` of the case's test file.
+    snapshot_dir: PathBuf,
+    /// `__cassettes__/<file>/` of the case's test file.
+    cassette_dir: PathBuf,
+    /// The suite's harness binary when running on the native backend.
+    native: Option<Arc<NativeSuite>>,
+}
+
+/// A public zero-arity `test_*` function, or a `test "name"` block.
+struct SuiteTest {
+    /// `Module.test_name` for functions, `Module: describe name` for blocks.
+    id: String,
+    module: String,
+    /// The block's name, or the function name; `:test` in the context.
+    name: String,
+    describe: Option<String>,
+    function: String,
+    takes_context: bool,
+    /// Marked `@async true`.
+    is_async: bool,
+    tags: Tags,
+}
+
+/// A `setup` or `setup_all` block.
+#[derive(Debug, Clone)]
+struct SetupBlock {
+    module: String,
+    describe: Option<String>,
+    function: String,
+    takes_context: bool,
+}
+
 struct TestSuite {
-    tests: Vec<String>,
+    tests: Vec<SuiteTest>,
+    doctests: Vec<Doctest>,
     ir: IrProgram,
     setup_modules: HashSet<String>,
     teardown_modules: HashSet<String>,
+    setups: Vec<SetupBlock>,
+    setup_alls: Vec<SetupBlock>,
+    coverage: Option<SuiteCoverage>,
+    /// The test file with its doctests and injected stdlib modules.
+    source: String,
 }
 
-pub fn list_tests(path: &str, filter: Option<&str>) -> Result<Vec<String>, TestRunnerError> {
+pub fn list_tests(path: &str, selection: &TestSelection) -> Result<Vec<String>, TestRunnerError> {
     let target = Path::new(path);
     let test_files = discover_test_files(target)?;
     let mut all_tests = Vec::new();
@@ -199,37 +361,48 @@ pub fn list_tests(path: &str, filter: Option<&str>) -> Result<Vec<String>, TestR
             ))
         })?;
 
-        let suite = compile_suite(&file, &source)?;
+        let suite = compile_suite(&file, &source, None)?;
+        let tests = suite.tests.into_iter().map(|test| (test.id, test.tags));
+        let doctests = suite
+            .doctests
+            .into_iter()
+            .map(|doctest| (doctest.id, Tags::new()));
 
-        for test_name in suite.tests {
-            if let Some(pattern) = filter {
-                if !test_name.contains(pattern) {
-                    continue;
-                }
+        for (id, tags) in tests.chain(doctests) {
+            if selection.includes(&id, &tags) {
+                all_tests.push(id);
             }
-            all_tests.push(test_name);
         }
     }
 
     Ok(all_tests)
 }
 
+#[allow(clippy::too_many_arguments)]
 pub fn run(
     path: &str,
-    filter: Option<&str>,
+    selection: &TestSelection,
     fail_fast: bool,
     seed: Option<u64>,
     timeout: Option<u64>,
+    coverage: Option<CoverageOptions>,
+    jobs: usize,
+    update_snapshots: bool,
+    record_cassettes: bool,
+    backend: TestBackend,
 ) -> Result<TestRunReport, TestRunnerError> {
     let target = Path::new(path);
     let test_files = discover_test_files(target)?;
 
-    // Collect all (test_name, ir) pairs, then optionally shuffle.
-    let mut test_cases: Vec<(String, IrProgram)> = Vec::new();
+    // Collect all cases, then optionally shuffle.
+    let mut test_cases: Vec<TestCase> = Vec::new();
     let mut setup_modules: HashSet<String> = HashSet::new();
     let mut teardown_modules: HashSet<String> = HashSet::new();
+    let mut suite_coverage: Vec<SuiteCoverage> = Vec::new();
+    let mut snapshot_files: Vec<SnapshotFile> = Vec::new();
+    let timeout_duration = timeout.map(Duration::from_millis);
 
-    for file in test_files {
+    for (group, file) in test_files.into_iter().enumerate() {
         let source = std::fs::read_to_string(&file).map_err(|error| {
             TestRunnerError::Failure(format!(
                 "failed to read source file {}: {error}",
@@ -237,159 +410,448 @@ pub fn run(
             ))
         })?;
 
-        let suite = compile_suite(&file, &source)?;
-        setup_modules.extend(suite.setup_modules.into_iter());
-        teardown_modules.extend(suite.teardown_modules.into_iter());
+        let suite = compile_suite(&file, &source, coverage)?;
+        let native = if backend.needs_native() {
+            let native = NativeSuite::build(&file, &suite.source, &suite.ir)
+                .map_err(TestRunnerError::Failure)?;
+            Some(Arc::new(native))
+        } else {
+            None
+        };
+        setup_modules.extend(suite.setup_modules);
+        teardown_modules.extend(suite.teardown_modules);
+        let recorder = suite
+            .coverage
+            .as_ref()
+            .map(|coverage| coverage.recorder.clone());
+
+        let mut snapshot_file = SnapshotFile {
+            dir: snapshot_dir(&file),
+            case_ids: Vec::new(),
+            fully_selected: true,
+        };
+
+        // `setup_all` runs once per module, before any of its selected tests.
+        let mut module_contexts: HashMap<String, Result<Context, String>> = HashMap::new();
+
+        for test in &suite.tests {
+            snapshot_file.case_ids.push(test.id.clone());
+            if !selection.includes(&test.id, &test.tags) {
+                snapshot_file.fully_selected = false;
+                continue;
+            }
 
-        for test_name in suite.tests {
-            if let Some(pattern) = filter {
-                if !test_name.contains(pattern) {
-                    continue;
+            let module_context = module_contexts
+                .entry(test.module.clone())
+                .or_insert_with(|| {
+                    run_setup_all(
+                        &suite.ir,
+                        &test.module,
+                        &suite.setup_alls,
+                        timeout_duration,
+                        recorder.as_ref(),
+                    )
+                })
+                .clone();
+            let context = module_context.map(|module_context| {
+                let mut context = vec![
+                    atom_entry("module", RuntimeValue::String(test.module.clone())),
+                    atom_entry(
+                        "describe",
+                        test.describe
+                            .clone()
+                            .map_or(RuntimeValue::Nil, RuntimeValue::String),
+                    ),
+                    atom_entry("test", RuntimeValue::String(test.name.clone())),
+                ];
+                for (key, value) in &test.tags {
+                    put_entry(&mut context, RuntimeValue::Atom(key.clone()), value.clone());
                 }
+                for (key, value) in module_context {
+                    put_entry(&mut context, key, value);
+                }
+                context
+            });
+            let setups = suite
+                .setups
+                .iter()
+                .filter(|setup| {
+                    setup.module == test.module
+                        && (setup.describe.is_none() || setup.describe == test.describe)
+                })
+                .cloned()
+                .collect();
+
+            test_cases.push(TestCase {
+                id: test.id.clone(),
+                module: test.module.clone(),
+                function: test.function.clone(),
+                takes_context: test.takes_context,
+                ir: suite.ir.clone(),
+                recorder: recorder.clone(),
+                doctest: None,
+                group: (!test.is_async).then_some(group),
+                context,
+                setups,
+                snapshot_dir: snapshot_file.dir.clone(),
+                cassette_dir: cassette_dir(&file),
+                native: native.clone(),
+            });
+        }
+
+        for doctest in suite.doctests {
+            snapshot_file.case_ids.push(doctest.id.clone());
+            if !selection.includes(&doctest.id, &Tags::new()) {
+                snapshot_file.fully_selected = false;
+                continue;
             }
-            test_cases.push((test_name, suite.ir.clone()));
+            test_cases.push(TestCase {
+                id: doctest.id.clone(),
+                module: doctest.module.clone(),
+                function: doctest.function.clone(),
+                takes_context: false,
+                ir: suite.ir.clone(),
+                recorder: recorder.clone(),
+                doctest: Some(doctest),
+                group: Some(group),
+                context: Ok(Context::new()),
+                setups: Vec::new(),
+                snapshot_dir: snapshot_file.dir.clone(),
+                cassette_dir: cassette_dir(&file),
+                native: native.clone(),
+            });
         }
+        suite_coverage.extend(suite.coverage);
+        snapshot_files.push(snapshot_file);
     }
 
     if let Some(seed) = seed {
         shuffle(&mut test_cases, seed);
     }
 
-    let mut results = Vec::new();
     let run_start = Instant::now();
+    let context = CaseContext {
+        setup_modules,
+        teardown_modules,
+        timeout: timeout_duration,
+        property_seed: seed.unwrap_or_else(|| rand::random_range(0..1_000_000)),
+        update_snapshots,
+        snapshot_records: Arc::default(),
+        record_cassettes,
+        backend,
+    };
+    let results = run_cases(&test_cases, jobs, fail_fast, &context);
+    let snapshots = SnapshotReport::collect(
+        &context
+            .snapshot_records
+            .lock()
+            .expect("snapshot records lock poisoned"),
+        &snapshot_files,
+        &results,
+        update_snapshots,
+    );
 
-    let timeout_duration = timeout.map(Duration::from_millis);
+    let passed = results
+        .iter()
+        .filter(|result| result.status == TestCaseStatus::Passed)
+        .count();
+    let skipped = results
+        .iter()
+        .filter(|result| result.status == TestCaseStatus::Skipped)
+        .count();
+    let failed = results.len().saturating_sub(passed + skipped);
 
-    for (test_name, ir) in &test_cases {
-        let test_start = Instant::now();
-
-        // Run setup/0 if the test's module has one.
-        let module_name = test_name.split('.').next().unwrap_or("");
-        if setup_modules.contains(module_name) {
-            let setup_fn = format!("{module_name}.setup");
-            match run_with_timeout(ir, &setup_fn, timeout_duration) {
-                TestExecResult::Ok(RuntimeValue::ResultErr(reason)) => {
-                    let error = format!("setup failed: {}", format_assertion_failure(&reason));
-                    results.push(TestCaseResult {
-                        id: test_name.clone(),
-                        status: TestCaseStatus::Failed,
-                        error: Some(error),
-                        duration: test_start.elapsed(),
-                    });
-                    if fail_fast {
-                        break;
-                    }
-                    continue;
-                }
-                TestExecResult::Err(error) => {
-                    let error = format!("setup failed: {error}");
-                    results.push(TestCaseResult {
-                        id: test_name.clone(),
-                        status: TestCaseStatus::Failed,
-                        error: Some(error),
-                        duration: test_start.elapsed(),
-                    });
-                    if fail_fast {
-                        break;
-                    }
-                    continue;
+    let coverage = coverage.map(|_| {
+        let mut report = CoverageReport::default();
+        for suite in &suite_coverage {
+            report.merge(suite.report());
+        }
+        report
+    });
+
+    Ok(TestRunReport {
+        total: results.len(),
+        passed,
+        failed,
+        skipped,
+        duration: run_start.elapsed(),
+        results,
+        coverage,
+        snapshots,
+    })
+}
+
+/// Setup/teardown hooks and limits shared by every case of a run.
+struct CaseContext {
+    setup_modules: HashSet<String>,
+    teardown_modules: HashSet<String>,
+    timeout: Option<Duration>,
+    /// Seed for `check all` properties: `--seed`, or a random one per run.
+    property_seed: u64,
+    update_snapshots: bool,
+    /// Every `Assert.snapshot` call of the run.
+    snapshot_records: Arc<Mutex<Vec<SnapshotRecord>>>,
+    /// `--record-cassettes`: re-record `Mock.cassette` calls.
+    record_cassettes: bool,
+    backend: TestBackend,
+}
+
+/// Cases waiting to run, and the serial groups that currently have one running.
+struct Schedule {
+    pending: VecDeque<usize>,
+    busy_groups: HashSet<usize>,
+    results: Vec<Option<TestCaseResult>>,
+    stopped: bool,
+}
+
+/// Run `cases` on up to `jobs` threads and return their results in case order.
+///
+/// Cases of the same serial group never overlap. Each thread takes the
+/// earliest pending case it is allowed to start, so with one job the cases run
+/// exactly in the given (possibly seed-shuffled) order. With `fail_fast`, no
+/// new case starts after a failure.
+fn run_cases(
+    cases: &[TestCase],
+    jobs: usize,
+    fail_fast: bool,
+    context: &CaseContext,
+) -> Vec<TestCaseResult> {
+    let schedule = Mutex::new(Schedule {
+        pending: (0..cases.len()).collect(),
+        busy_groups: HashSet::new(),
+        results: vec![None; cases.len()],
+        stopped: false,
+    });
+    let ready = Condvar::new();
+
+    let worker = || loop {
+        let index = {
+            let mut schedule = schedule.lock().expect("test schedule lock poisoned");
+            loop {
+                if schedule.stopped || schedule.pending.is_empty() {
+                    return;
                 }
-                TestExecResult::TimedOut(ms) => {
-                    let error = format!("setup timed out after {ms}ms");
-                    results.push(TestCaseResult {
-                        id: test_name.clone(),
-                        status: TestCaseStatus::Failed,
-                        error: Some(error),
-                        duration: test_start.elapsed(),
-                    });
-                    if fail_fast {
-                        break;
-                    }
-                    continue;
+                let startable = schedule.pending.iter().position(|index| {
+                    cases[*index]
+                        .group
+                        .is_none_or(|group| !schedule.busy_groups.contains(&group))
+                });
+                if let Some(position) = startable {
+                    let index = schedule.pending.remove(position).unwrap_or_default();
+                    schedule.busy_groups.extend(cases[index].group);
+                    break index;
                 }
-                TestExecResult::Ok(_) => {} // setup succeeded, proceed to test
+                schedule = ready.wait(schedule).expect("test schedule lock poisoned");
             }
+        };
+
+        let result = run_case(&cases[index], context);
+
+        let mut schedule = schedule.lock().expect("test schedule lock poisoned");
+        if let Some(group) = cases[index].group {
+            schedule.busy_groups.remove(&group);
+        }
+        if fail_fast && result.status == TestCaseStatus::Failed {
+            schedule.stopped = true;
         }
+        schedule.results[index] = Some(result);
+        ready.notify_all();
+    };
 
-        let (mut status, mut error) = match run_with_timeout(ir, test_name, timeout_duration) {
-            TestExecResult::Ok(RuntimeValue::ResultErr(reason)) => {
-                if is_test_skipped(&reason) {
-                    let skip_reason = extract_skip_reason(&reason);
-                    (TestCaseStatus::Skipped, Some(skip_reason))
-                } else {
-                    let err = format_assertion_failure(&reason);
-                    (TestCaseStatus::Failed, Some(err))
-                }
+    std::thread::scope(|scope| {
+        // Workers need the main thread's large stack for recursive tests.
+        for _ in 1..jobs.min(cases.len()) {
+            std::thread::Builder::new()
+                .stack_size(crate::STACK_SIZE)
+                .spawn_scoped(scope, worker)
+                .expect("failed to spawn test worker thread");
+        }
+        worker();
+    });
+
+    schedule
+        .into_inner()
+        .expect("test schedule lock poisoned")
+        .results
+        .into_iter()
+        .flatten()
+        .collect()
+}
+
+/// Run one case on the interpreter, the native harness, or both.
+fn run_case(case: &TestCase, context: &CaseContext) -> TestCaseResult {
+    let native = case.native.as_deref();
+    match context.backend {
+        TestBackend::Interpreter => run_case_on(case, context, None),
+        TestBackend::Native => run_case_on(case, context, native),
+        TestBackend::Both => native::compare_backends(
+            run_case_on(case, context, None),
+            run_case_on(case, context, native),
+        ),
+    }
+}
+
+/// Run one case, including its module's setup/0 and teardown/0, with its
+/// output captured so concurrent cases do not interleave. With `native`,
+/// its functions run in the suite's harness binary instead of the
+/// interpreter; `setup_all` contexts always come from the interpreter.
+fn run_case_on(
+    case: &TestCase,
+    context: &CaseContext,
+    native: Option<&NativeSuite>,
+) -> TestCaseResult {
+    let ir = &case.ir;
+    let recorder = case.recorder.as_ref();
+    let timeout = context.timeout;
+    let call = |function: &str, args: &[RuntimeValue], output: &mut CapturedHostOutput| match native
+    {
+        Some(native) => native.call(function, args, timeout, output),
+        None => run_with_timeout(ir, function, args, timeout, recorder, output),
+    };
+    let mut output = CapturedHostOutput::default();
+    let test_start = Instant::now();
+    set_property_seed(Some(PropertySeed {
+        seed: context.property_seed,
+        salt: fnv1a(&case.id),
+    }));
+    set_snapshot_scope(Some(SnapshotScope {
+        dir: case.snapshot_dir.clone(),
+        test: case.id.clone(),
+        update: context.update_snapshots,
+        records: Arc::clone(&context.snapshot_records),
+    }));
+    // A fresh scope per case drops the previous test's stubs and cassettes.
+    set_mock_scope(Some(MockScope::new(
+        case.cassette_dir.clone(),
+        context.record_cassettes,
+    )));
+
+    let finish = |status, error, output: CapturedHostOutput| TestCaseResult {
+        id: case.id.clone(),
+        module: case.module.clone(),
+        status,
+        error,
+        duration: test_start.elapsed(),
+        stdout: output.stdout,
+        stderr: output.stderr,
+        diff: None,
+    };
+
+    // Doctests only evaluate their examples; setup/teardown do not apply.
+    if let Some(doctest) = &case.doctest {
+        let (status, error) = match call(&doctest.function, &[], &mut output) {
+            TestExecResult::Ok(value) => match doctest.check(&value) {
+                Ok(()) => (TestCaseStatus::Passed, None),
+                Err(error) => (TestCaseStatus::Failed, Some(error)),
+            },
+            TestExecResult::Err(error) => {
+                (TestCaseStatus::Failed, Some(doctest.locate_error(&error)))
             }
-            TestExecResult::Ok(_) => (TestCaseStatus::Passed, None),
-            TestExecResult::Err(e) => (TestCaseStatus::Failed, Some(e.to_string())),
             TestExecResult::TimedOut(ms) => (
                 TestCaseStatus::Failed,
                 Some(format!("test timed out after {ms}ms")),
             ),
         };
+        return finish(status, error, output);
+    }
 
-        // Run teardown/0 if the test's module has one (always, regardless of test outcome).
-        if teardown_modules.contains(module_name) {
-            let teardown_fn = format!("{module_name}.teardown");
-            let teardown_err = match run_with_timeout(ir, &teardown_fn, timeout_duration) {
-                TestExecResult::Ok(RuntimeValue::ResultErr(reason)) => Some(format!(
-                    "teardown failed: {}",
-                    format_assertion_failure(&reason)
-                )),
-                TestExecResult::Err(e) => Some(format!("teardown failed: {e}")),
-                TestExecResult::TimedOut(ms) => Some(format!("teardown timed out after {ms}ms")),
-                TestExecResult::Ok(_) => None,
-            };
-            if let Some(td_err) = teardown_err {
-                match status {
-                    TestCaseStatus::Passed => {
-                        status = TestCaseStatus::Failed;
-                        error = Some(td_err);
-                    }
-                    TestCaseStatus::Failed => {
-                        let existing = error.unwrap_or_default();
-                        error = Some(format!("{existing}\n({td_err})"));
-                    }
-                    TestCaseStatus::Skipped => {
-                        // Teardown failure overrides skip — the test has a real problem
-                        status = TestCaseStatus::Failed;
-                        error = Some(td_err);
-                    }
-                }
-            }
+    let mut test_context = match &case.context {
+        Ok(test_context) => test_context.clone(),
+        Err(error) => {
+            let error = format!("setup_all failed: {error}");
+            return finish(TestCaseStatus::Failed, Some(error), output);
         }
+    };
 
-        let is_failure = status == TestCaseStatus::Failed;
-        results.push(TestCaseResult {
-            id: test_name.clone(),
-            status,
-            error,
-            duration: test_start.elapsed(),
-        });
-        if is_failure && fail_fast {
-            break;
+    // Run setup/0 if the test's module has one.
+    let module_name = case.module.as_str();
+    if context.setup_modules.contains(module_name) {
+        let setup_fn = format!("{module_name}.setup");
+        let setup_error = match call(&setup_fn, &[], &mut output) {
+            TestExecResult::Ok(RuntimeValue::ResultErr(reason)) => Some(format!(
+                "setup failed: {}",
+                format_assertion_failure(&reason)
+            )),
+            TestExecResult::Err(error) => Some(format!("setup failed: {error}")),
+            TestExecResult::TimedOut(ms) => Some(format!("setup timed out after {ms}ms")),
+            TestExecResult::Ok(_) => None, // setup succeeded, proceed to test
+        };
+        if let Some(error) = setup_error {
+            return finish(TestCaseStatus::Failed, Some(error), output);
         }
     }
 
-    let passed = results
-        .iter()
-        .filter(|result| result.status == TestCaseStatus::Passed)
-        .count();
-    let skipped = results
-        .iter()
-        .filter(|result| result.status == TestCaseStatus::Skipped)
-        .count();
-    let failed = results.len().saturating_sub(passed + skipped);
+    // `setup` blocks extend the context in order.
+    for setup in &case.setups {
+        let args = context_args(setup.takes_context, &test_context);
+        let setup_error = match call(&setup.function, &args, &mut output) {
+            TestExecResult::Ok(value) => merge_context(&mut test_context, value)
+                .err()
+                .map(|error| format!("setup failed: {error}")),
+            TestExecResult::Err(error) => Some(format!("setup failed: {error}")),
+            TestExecResult::TimedOut(ms) => Some(format!("setup timed out after {ms}ms")),
+        };
+        if let Some(error) = setup_error {
+            return finish(TestCaseStatus::Failed, Some(error), output);
+        }
+    }
 
-    Ok(TestRunReport {
-        total: results.len(),
-        passed,
-        failed,
-        skipped,
-        duration: run_start.elapsed(),
-        results,
-    })
+    let args = context_args(case.takes_context, &test_context);
+    let mut diff = None;
+    let (mut status, mut error) = match call(&case.function, &args, &mut output) {
+        TestExecResult::Ok(RuntimeValue::ResultErr(reason)) => {
+            if is_test_skipped(&reason) {
+                let skip_reason = extract_skip_reason(&reason);
+                (TestCaseStatus::Skipped, Some(skip_reason))
+            } else {
+                diff = assertion_diff(&reason);
+                let err = format_assertion_failure(&reason);
+                (TestCaseStatus::Failed, Some(err))
+            }
+        }
+        TestExecResult::Ok(_) => (TestCaseStatus::Passed, None),
+        TestExecResult::Err(e) => (TestCaseStatus::Failed, Some(e.to_string())),
+        TestExecResult::TimedOut(ms) => (
+            TestCaseStatus::Failed,
+            Some(format!("test timed out after {ms}ms")),
+        ),
+    };
+
+    // Run teardown/0 if the test's module has one (always, regardless of test outcome).
+    if context.teardown_modules.contains(module_name) {
+        let teardown_fn = format!("{module_name}.teardown");
+        let teardown_err = match call(&teardown_fn, &[], &mut output) {
+            TestExecResult::Ok(RuntimeValue::ResultErr(reason)) => Some(format!(
+                "teardown failed: {}",
+                format_assertion_failure(&reason)
+            )),
+            TestExecResult::Err(e) => Some(format!("teardown failed: {e}")),
+            TestExecResult::TimedOut(ms) => Some(format!("teardown timed out after {ms}ms")),
+            TestExecResult::Ok(_) => None,
+        };
+        if let Some(td_err) = teardown_err {
+            match status {
+                TestCaseStatus::Passed => {
+                    status = TestCaseStatus::Failed;
+                    error = Some(td_err);
+                }
+                TestCaseStatus::Failed => {
+                    let existing = error.unwrap_or_default();
+                    error = Some(format!("{existing}\n({td_err})"));
+                }
+                TestCaseStatus::Skipped => {
+                    // Teardown failure overrides skip — the test has a real problem
+                    status = TestCaseStatus::Failed;
+                    error = Some(td_err);
+                }
+            }
+        }
+    }
+
+    TestCaseResult {
+        diff,
+        ..finish(status, error, output)
+    }
 }
 
 /// Result of running a test function, possibly with a timeout.
@@ -400,23 +862,55 @@ enum TestExecResult {
 }
 
 /// Run a named function with an optional timeout. When timeout is None, runs directly.
-fn run_with_timeout(ir: &IrProgram, fn_name: &str, timeout: Option<Duration>) -> TestExecResult {
+/// Executed ops are counted towards `recorder` when coverage is enabled, and
+/// host output is appended to `output` instead of the process streams.
+fn run_with_timeout(
+    ir: &IrProgram,
+    fn_name: &str,
+    args: &[RuntimeValue],
+    timeout: Option<Duration>,
+    recorder: Option<&CoverageRecorder>,
+    output: &mut CapturedHostOutput,
+) -> TestExecResult {
+    let mut keep_output = |captured: CapturedHostOutput| {
+        output.stdout.push_str(&captured.stdout);
+        output.stderr.push_str(&captured.stderr);
+    };
+
     match timeout {
-        None => match evaluate_named_function(ir, fn_name) {
-            Ok(val) => TestExecResult::Ok(val),
-            Err(e) => TestExecResult::Err(e.to_string()),
-        },
+        None => {
+            let (result, captured) = evaluate_observed(ir, fn_name, args, recorder);
+            keep_output(captured);
+            match result {
+                Ok(val) => TestExecResult::Ok(val),
+                Err(e) => TestExecResult::Err(e.to_string()),
+            }
+        }
         Some(limit) => {
             let ir = ir.clone();
             let fn_name = fn_name.to_string();
+            let args = args.to_vec();
+            let recorder = recorder.cloned();
+            let seed = property_seed();
+            let snapshots = snapshot_scope();
+            let mocks = mock_scope();
             let (tx, rx) = std::sync::mpsc::channel();
             std::thread::spawn(move || {
-                let result = evaluate_named_function(&ir, &fn_name);
+                set_property_seed(seed);
+                set_snapshot_scope(snapshots);
+                set_mock_scope(mocks);
+                let result = evaluate_observed(&ir, &fn_name, &args, recorder.as_ref());
                 let _ = tx.send(result);
             });
             match rx.recv_timeout(limit) {
-                Ok(Ok(val)) => TestExecResult::Ok(val),
-                Ok(Err(e)) => TestExecResult::Err(e.to_string()),
+                Ok((Ok(val), captured)) => {
+                    keep_output(captured);
+                    TestExecResult::Ok(val)
+                }
+                Ok((Err(e), captured)) => {
+                    keep_output(captured);
+                    TestExecResult::Err(e.to_string())
+                }
                 Err(std::sync::mpsc::RecvTimeoutError::Timeout) => {
                     TestExecResult::TimedOut(limit.as_millis() as u64)
                 }
@@ -428,6 +922,107 @@ fn run_with_timeout(ir: &IrProgram, fn_name: &str, timeout: Option<Duration>) ->
     }
 }
 
+fn evaluate_observed(
+    ir: &IrProgram,
+    fn_name: &str,
+    args: &[RuntimeValue],
+    recorder: Option<&CoverageRecorder>,
+) -> (Result<RuntimeValue, RuntimeError>, CapturedHostOutput) {
+    capture_host_output_with_stdin(None, || match recorder {
+        Some(recorder) => recorder.observe(|| evaluate_named_function_with_args(ir, fn_name, args)),
+        None => evaluate_named_function_with_args(ir, fn_name, args),
+    })
+}
+
+/// Run the `setup_all` blocks of `module` in order, building the context its
+/// tests start from.
+fn run_setup_all(
+    ir: &IrProgram,
+    module: &str,
+    setup_alls: &[SetupBlock],
+    timeout: Option<Duration>,
+    recorder: Option<&CoverageRecorder>,
+) -> Result<Context, String> {
+    let mut context = vec![atom_entry(
+        "module",
+        RuntimeValue::String(module.to_string()),
+    )];
+    let mut output = CapturedHostOutput::default();
+
+    for setup in setup_alls.iter().filter(|setup| setup.module == module) {
+        let args = context_args(setup.takes_context, &context);
+        match run_with_timeout(ir, &setup.function, &args, timeout, recorder, &mut output) {
+            TestExecResult::Ok(value) => merge_context(&mut context, value)?,
+            TestExecResult::Err(error) => return Err(error),
+            TestExecResult::TimedOut(ms) => return Err(format!("timed out after {ms}ms")),
+        }
+    }
+
+    Ok(context)
+}
+
+/// Arguments for a test or setup block: the context map, when it takes one.
+fn context_args(takes_context: bool, context: &Context) -> Vec<RuntimeValue> {
+    if takes_context {
+        vec![RuntimeValue::Map(context.clone())]
+    } else {
+        Vec::new()
+    }
+}
+
+fn atom_entry(key: &str, value: RuntimeValue) -> (RuntimeValue, RuntimeValue) {
+    (RuntimeValue::Atom(key.to_string()), value)
+}
+
+fn put_entry(context: &mut Context, key: RuntimeValue, value: RuntimeValue) {
+    match context.iter_mut().find(|(existing, _)| *existing == key) {
+        Some((_, existing)) => *existing = value,
+        None => context.push((key, value)),
+    }
+}
+
+/// Merge what a setup block returned into the context. Setup blocks return
+/// `:ok`, a map or keyword list, or one wrapped in `ok(...)` or `{:ok, ...}`.
+fn merge_context(context: &mut Context, value: RuntimeValue) -> Result<(), String> {
+    let entries = match value {
+        RuntimeValue::Atom(atom) if atom == "ok" => return Ok(()),
+        RuntimeValue::Map(entries) | RuntimeValue::Keyword(entries) => entries,
+        RuntimeValue::ResultOk(inner) => match *inner {
+            RuntimeValue::Map(entries) | RuntimeValue::Keyword(entries) => entries,
+            other => {
+                return Err(unexpected_setup_value(&RuntimeValue::ResultOk(Box::new(
+                    other,
+                ))))
+            }
+        },
+        RuntimeValue::Tuple(tag, inner) if *tag == RuntimeValue::Atom("ok".to_string()) => {
+            match *inner {
+                RuntimeValue::Map(entries) | RuntimeValue::Keyword(entries) => entries,
+                other => {
+                    return Err(unexpected_setup_value(&RuntimeValue::Tuple(
+                        tag,
+                        Box::new(other),
+                    )))
+                }
+            }
+        }
+        RuntimeValue::ResultErr(reason) => return Err(format_assertion_failure(&reason)),
+        other => return Err(unexpected_setup_value(&other)),
+    };
+
+    for (key, value) in entries {
+        put_entry(context, key, value);
+    }
+    Ok(())
+}
+
+fn unexpected_setup_value(value: &RuntimeValue) -> String {
+    format!(
+        "setup must return :ok, a map or ok(map), got {}",
+        value.render()
+    )
+}
+
 /// Splitmix64 PRNG — simple, fast, dependency-free.
 fn splitmix64(state: &mut u64) -> u64 {
     *state = state.wrapping_add(0x9e3779b97f4a7c15);
@@ -437,6 +1032,14 @@ fn splitmix64(state: &mut u64) -> u64 {
     z ^ (z >> 31)
 }
 
+/// Stable 64-bit FNV-1a hash, so a test's property seed does not depend on
+/// the Rust version.
+fn fnv1a(text: &str) -> u64 {
+    text.bytes().fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3)
+    })
+}
+
 /// Fisher-Yates shuffle using splitmix64 PRNG.
 fn shuffle<T>(slice: &mut [T], seed: u64) {
     let mut state = seed;
@@ -455,15 +1058,19 @@ struct AnsiColor {
 }
 
 impl AnsiColor {
+    fn plain() -> Self {
+        Self {
+            green: "",
+            red: "",
+            yellow: "",
+            bold: "",
+            reset: "",
+        }
+    }
+
     fn from_env() -> Self {
         if std::env::var("NO_COLOR").is_ok() {
-            Self {
-                green: "",
-                red: "",
-                yellow: "",
-                bold: "",
-                reset: "",
-            }
+            Self::plain()
         } else {
             Self {
                 green: "\x1b[32m",
@@ -492,6 +1099,15 @@ fn duration_ms(d: Duration) -> f64 {
 }
 
 fn discover_test_files(path: &Path) -> Result<Vec<PathBuf>, TestRunnerError> {
+    discover_source_files(path, is_test_file_name)
+}
+
+/// `path` itself when it is a file, otherwise every file under it accepted by
+/// `matches`, skipping hidden and `target` directories.
+pub(crate) fn discover_source_files(
+    path: &Path,
+    matches: fn(&Path) -> bool,
+) -> Result<Vec<PathBuf>, TestRunnerError> {
     if path.is_file() {
         return Ok(vec![path.to_path_buf()]);
     }
@@ -539,7 +1155,7 @@ fn discover_test_files(path: &Path) -> Result<Vec<PathBuf>, TestRunnerError> {
                 continue;
             }
 
-            if is_test_file_name(&entry_path) {
+            if matches(&entry_path) {
                 files.push(entry_path);
             }
         }
@@ -582,6 +1198,10 @@ fn extract_skip_reason(reason: &RuntimeValue) -> String {
 /// Recognizes the `{:assertion_failed, details}` tuple convention used by Assert host functions.
 fn format_assertion_failure(reason: &RuntimeValue) -> String {
     match reason {
+        // PropertyTest.check_all: {:property_failed, %{runs:, seed:, ...}}
+        RuntimeValue::Tuple(tag, details) if matches!(**tag, RuntimeValue::Atom(ref a) if a == "property_failed") => {
+            format_property_failure(details)
+        }
         // assert/refute: {:assertion_failed, {:assert|:refute, message}}
         RuntimeValue::Tuple(tag, details) if matches!(**tag, RuntimeValue::Atom(ref a) if a == "assertion_failed") =>
         {
@@ -611,6 +1231,12 @@ fn format_assertion_failure(reason: &RuntimeValue) -> String {
                     let mut missing_keys = None;
                     let mut mismatched_keys = None;
                     let mut message = None;
+                    let mut operands = (None, None);
+                    let mut pattern = None;
+                    let mut value = None;
+                    let mut failed = None;
+                    let mut path = None;
+                    let mut reason = None;
 
                     for entry in entries {
                         if let RuntimeValue::Tuple(key, val) = entry {
@@ -622,15 +1248,19 @@ fn format_assertion_failure(reason: &RuntimeValue) -> String {
                                             "assert_contains" => "assert_contains",
                                             "assert_in_delta" => "assert_in_delta",
                                             "assert_match" => "assert_match",
+                                            "assert_pattern" => "assert_pattern",
+                                            "snapshot" => "snapshot",
                                             _ => "assert_equal",
                                         };
                                     }
                                 }
                                 RuntimeValue::Atom(k) if k == "left" => {
                                     left = Some(val.render());
+                                    operands.0 = Some(val.as_ref());
                                 }
                                 RuntimeValue::Atom(k) if k == "right" => {
                                     right = Some(val.render());
+                                    operands.1 = Some(val.as_ref());
                                 }
                                 RuntimeValue::Atom(k) if k == "container" => {
                                     container = Some(val.render());
@@ -653,6 +1283,21 @@ fn format_assertion_failure(reason: &RuntimeValue) -> String {
                                 RuntimeValue::Atom(k) if k == "mismatched_keys" => {
                                     mismatched_keys = Some(val.render());
                                 }
+                                RuntimeValue::Atom(k) if k == "pattern" => {
+                                    pattern = Some(plain_text(val));
+                                }
+                                RuntimeValue::Atom(k) if k == "value" => {
+                                    value = Some(val.render());
+                                }
+                                RuntimeValue::Atom(k) if k == "failed" => {
+                                    failed = Some(plain_text(val));
+                                }
+                                RuntimeValue::Atom(k) if k == "path" => {
+                                    path = Some(plain_text(val));
+                                }
+                                RuntimeValue::Atom(k) if k == "reason" => {
+                                    reason = Some(plain_text(val));
+                                }
                                 RuntimeValue::Atom(k) if k == "message" => {
                                     if let RuntimeValue::String(s) = val.as_ref() {
                                         message = Some(s.clone());
@@ -698,12 +1343,42 @@ fn format_assertion_failure(reason: &RuntimeValue) -> String {
                                 lines.push(format!("  mismatched keys: {m}"));
                             }
                         }
-                        _ => {
-                            if let Some(l) = left {
-                                lines.push(format!("  left:  {l}"));
+                        "assert_pattern" => {
+                            if let Some(p) = pattern {
+                                lines.push(format!("  pattern: {p}"));
                             }
-                            if let Some(r) = right {
-                                lines.push(format!("  right: {r}"));
+                            if let Some(v) = value {
+                                lines.push(format!("  value:   {v}"));
+                            }
+                            if let (Some(f), Some(p)) = (failed, path) {
+                                lines.push(format!("  failed:  {f} at {p}"));
+                            }
+                            if let Some(r) = reason {
+                                lines.push(format!("  reason:  {r}"));
+                            }
+                        }
+                        _ => {
+                            let diff = match operands {
+                                (Some(l), Some(r))
+                                    if matches!(kind, "assert_equal" | "snapshot") =>
+                                {
+                                    ValueDiff::between(l, r)
+                                }
+                                _ => None,
+                            };
+                            if let Some(diff) = diff {
+                                lines.extend(
+                                    diff.render(&AnsiColor::plain())
+                                        .into_iter()
+                                        .map(|line| format!("  {line}")),
+                                );
+                            } else {
+                                if let Some(l) = left {
+                                    lines.push(format!("  left:  {l}"));
+                                }
+                                if let Some(r) = right {
+                                    lines.push(format!("  right: {r}"));
+                                }
                             }
                         }
                     }
@@ -717,30 +1392,149 @@ fn format_assertion_failure(reason: &RuntimeValue) -> String {
     }
 }
 
-fn compile_suite(path: &Path, source: &str) -> Result<TestSuite, TestRunnerError> {
+fn plain_text(value: &RuntimeValue) -> String {
+    match value {
+        RuntimeValue::String(text) => text.clone(),
+        other => other.render(),
+    }
+}
+
+/// The structural diff of a failed `assert_equal` or snapshot, shown next to its error.
+fn assertion_diff(reason: &RuntimeValue) -> Option<ValueDiff> {
+    let RuntimeValue::Tuple(tag, details) = reason else {
+        return None;
+    };
+    let (RuntimeValue::Atom(tag), RuntimeValue::List(entries)) = (&**tag, &**details) else {
+        return None;
+    };
+    if tag != "assertion_failed" {
+        return None;
+    }
+
+    let field = |name: &str| {
+        entries.iter().find_map(|entry| match entry {
+            RuntimeValue::Tuple(key, value)
+                if matches!(&**key, RuntimeValue::Atom(key) if key == name) =>
+            {
+                Some(&**value)
+            }
+            _ => None,
+        })
+    };
+    match field("type") {
+        Some(RuntimeValue::Atom(kind)) if kind == "assert_equal" || kind == "snapshot" => {
+            ValueDiff::between(field("left")?, field("right")?)
+        }
+        _ => None,
+    }
+}
+
+/// Report a shrunk `check all` counterexample and how to reproduce it.
+fn format_property_failure(details: &RuntimeValue) -> String {
+    let field = |name: &str| match details {
+        RuntimeValue::Map(entries) => entries
+            .iter()
+            .find(|(key, _)| matches!(key, RuntimeValue::Atom(key) if key == name))
+            .map(|(_, value)| value.clone()),
+        _ => None,
+    };
+    let count = |name: &str| match field(name) {
+        Some(RuntimeValue::Int(value)) => value,
+        _ => 0,
+    };
+    let plural = |count: i64| if count == 1 { "" } else { "s" };
+
+    let runs = count("runs");
+    let shrinks = count("shrinks");
+    let seed = count("seed") as u64;
+    let mut lines = vec![format!(
+        "property failed after {runs} run{} (seed {seed}); shrunk {shrinks} time{}",
+        plural(runs),
+        plural(shrinks)
+    )];
+
+    lines.push("  counterexample:".to_string());
+    if let Some(RuntimeValue::List(values)) = field("counterexample") {
+        for value in values {
+            match value {
+                RuntimeValue::Tuple(label, value) => {
+                    let label = match label.as_ref() {
+                        RuntimeValue::String(label) => label.clone(),
+                        other => other.render(),
+                    };
+                    lines.push(format!("    {label} <- {}", value.render()));
+                }
+                other => lines.push(format!("    {}", other.render())),
+            }
+        }
+    }
+
+    let reason = match field("reason") {
+        Some(RuntimeValue::Tuple(tag, message)) if matches!(*tag, RuntimeValue::Atom(ref a) if a == "property_raised") => {
+            match *message {
+                RuntimeValue::String(message) => format!("raised: {message}"),
+                other => format!("raised: {}", other.render()),
+            }
+        }
+        Some(reason) => format_assertion_failure(&reason),
+        None => String::new(),
+    };
+    lines.extend(reason.lines().map(|line| format!("  {line}")));
+    lines.push(format!("  rerun with --seed {seed} to reproduce"));
+    lines.join("\n")
+}
+
+fn compile_suite(
+    path: &Path,
+    source: &str,
+    coverage: Option<CoverageOptions>,
+) -> Result<TestSuite, TestRunnerError> {
     let filename = Some(path.display().to_string());
+    let user_len = source.len();
+
+    // Doctest examples are compiled as generated modules placed after the
+    // test file, followed by the project's library when a target lives
+    // there. A file that does not parse on its own has no doctests; the full
+    // compile below reports its errors.
+    let file_ast = scan_tokens(source)
+        .ok()
+        .and_then(|tokens| parse_ast(&tokens).ok());
+    let library = match &file_ast {
+        Some(ast) if doctests_outside_file(ast) => load_doctest_library(path)?,
+        _ => None,
+    };
+    let (library_files, library_dependencies) = library
+        .map(|library| (library.files, library.dependencies))
+        .unwrap_or_default();
+    let doctests = file_ast
+        .map(|ast| DoctestPlan::new(path, source, &ast, &library_files))
+        .unwrap_or_default();
 
     // Inject stdlib modules (e.g. Assert) referenced by the test source.
     let mut enriched_source = source.to_string();
+    if !doctests.is_empty() {
+        enriched_source.push_str("\n\n");
+        enriched_source.push_str(&doctests.source);
+    }
+    for library_source in library_files
+        .iter()
+        .map(LibraryFile::source)
+        .chain(library_dependencies.iter().map(String::as_str))
+    {
+        enriched_source.push_str("\n\n");
+        enriched_source.push_str(library_source);
+    }
     inject_optional_stdlib(&mut enriched_source)
         .map_err(|e| TestRunnerError::Failure(format!("stdlib injection failed: {e}")))?;
     let source = &enriched_source;
 
-    let tokens = scan_tokens(source).map_err(|error| TestRunnerError::SourceDiagnostic {
-        message: error.to_string(),
-        filename: filename.clone(),
-        source: source.to_string(),
-        offset: None,
-    })?;
-
-    let ast = parse_ast(&tokens).map_err(|error| TestRunnerError::SourceDiagnostic {
-        message: error.to_string(),
-        filename: filename.clone(),
-        source: source.to_string(),
-        offset: error.offset(),
-    })?;
+    let ast = analyze_suite_source(&filename, source)
+        .map_err(|error| doctests.locate_compile_error(error, user_len + 2))?;
 
     let mut tests = Vec::new();
+    let mut block_tests = Vec::new();
+    let mut setups = Vec::new();
+    let mut setup_alls = Vec::new();
     let mut setup_modules = HashSet::new();
     let mut teardown_modules = HashSet::new();
 
@@ -759,16 +1553,172 @@ fn compile_suite(path: &Path, source: &str) -> Result<TestSuite, TestRunnerError
             teardown_modules.insert(module.name.clone());
         }
 
-        for function in &module.functions {
-            if !function.is_private()
+        let is_legacy_test = |function: &&Function| {
+            !function.is_private()
                 && function.params.is_empty()
                 && function.name.starts_with("test_")
-            {
-                tests.push(format!("{}.{}", module.name, function.name));
+        };
+        let test_offsets = module
+            .functions
+            .iter()
+            .filter(is_legacy_test)
+            .map(|function| function.offset)
+            .chain(
+                module
+                    .test_blocks
+                    .iter()
+                    .filter(|block| block.kind == TestBlockKind::Test)
+                    .map(|block| block.offset),
+            )
+            .collect::<Vec<_>>();
+        let mut tags = module_test_tags(module, &test_offsets, &filename, source)?;
+
+        // `@async true` applies to the function that follows it.
+        let async_functions = module
+            .attributes
+            .iter()
+            .filter(|attribute| {
+                attribute.name == "async"
+                    && matches!(attribute.value, Expr::Bool { value: true, .. })
+            })
+            .filter_map(|attribute| {
+                module
+                    .functions
+                    .iter()
+                    .find(|function| function.offset > attribute.offset)
+            })
+            .map(|function| function.name.as_str())
+            .collect::<HashSet<_>>();
+
+        for function in module.functions.iter().filter(is_legacy_test) {
+            tests.push(SuiteTest {
+                id: format!("{}.{}", module.name, function.name),
+                module: module.name.clone(),
+                name: function.name.clone(),
+                describe: None,
+                function: format!("{}.{}", module.name, function.name),
+                takes_context: false,
+                is_async: async_functions.contains(function.name.as_str()),
+                tags: tags.remove(&function.offset).unwrap_or_default(),
+            });
+        }
+
+        for block in &module.test_blocks {
+            let takes_context = module
+                .functions
+                .iter()
+                .any(|function| function.name == block.function && !function.params.is_empty());
+            let qualified = format!("{}.{}", module.name, block.function);
+
+            match block.kind {
+                TestBlockKind::Test => {
+                    let name = block.name.clone().unwrap_or_default();
+                    let label = match &block.describe {
+                        Some(describe) => format!("{describe} {name}"),
+                        None => name.clone(),
+                    };
+                    block_tests.push(SuiteTest {
+                        id: format!("{}: {label}", module.name),
+                        module: module.name.clone(),
+                        name,
+                        describe: block.describe.clone(),
+                        function: qualified,
+                        takes_context,
+                        is_async: async_functions.contains(block.function.as_str()),
+                        tags: tags.remove(&block.offset).unwrap_or_default(),
+                    });
+                }
+                TestBlockKind::Setup | TestBlockKind::SetupAll => {
+                    let setup = SetupBlock {
+                        module: module.name.clone(),
+                        describe: block.describe.clone(),
+                        function: qualified,
+                        takes_context,
+                    };
+                    if block.kind == TestBlockKind::Setup {
+                        setups.push(setup);
+                    } else {
+                        setup_alls.push(setup);
+                    }
+                }
             }
         }
     }
-    tests.sort();
+    // Test functions run in name order, test blocks in source order.
+    tests.sort_by(|left, right| left.id.cmp(&right.id));
+    tests.extend(block_tests);
+
+    let ir = lower_ast_to_ir(&ast).map_err(|error| TestRunnerError::Failure(error.to_string()))?;
+    let coverage =
+        coverage.map(|options| SuiteCoverage::new(path, source, user_len, &ast, &ir, options));
+
+    Ok(TestSuite {
+        tests,
+        doctests: doctests.doctests,
+        ir,
+        setup_modules,
+        teardown_modules,
+        setups,
+        setup_alls,
+        coverage,
+        source: enriched_source,
+    })
+}
+
+/// The library of the project containing `test_path`, for doctests of
+/// modules outside the test file. `None` outside a project.
+fn load_doctest_library(test_path: &Path) -> Result<Option<DoctestLibrary>, TestRunnerError> {
+    let Some(project_root) = manifest::find_project_root(test_path) else {
+        return Ok(None);
+    };
+
+    let library = manifest::load_library_sources(&project_root).map_err(|error| {
+        TestRunnerError::Failure(format!(
+            "failed to load project modules for doctests: {error}"
+        ))
+    })?;
+    let cwd = std::env::current_dir().unwrap_or_default();
+    let files = library
+        .files
+        .into_iter()
+        .map(|(path, source)| {
+            let path = path
+                .strip_prefix(&cwd)
+                .map(Path::to_path_buf)
+                .unwrap_or(path);
+            LibraryFile::new(path, source)
+        })
+        .collect();
+
+    Ok(Some(DoctestLibrary {
+        files,
+        dependencies: library.dependencies,
+    }))
+}
+
+struct DoctestLibrary {
+    files: Vec<LibraryFile>,
+    dependencies: Vec<String>,
+}
+
+/// Lex, parse, resolve and type-check a suite's full source.
+pub(crate) fn analyze_suite_source(
+    filename: &Option<String>,
+    source: &str,
+) -> Result<Ast, TestRunnerError> {
+    let tokens = scan_tokens(source).map_err(|error| TestRunnerError::SourceDiagnostic {
+        message: error.to_string(),
+        filename: filename.clone(),
+        source: source.to_string(),
+        offset: None,
+    })?;
+
+    let ast = parse_ast(&tokens).map_err(|error| TestRunnerError::SourceDiagnostic {
+        message: error.to_string(),
+        filename: filename.clone(),
+        source: source.to_string(),
+        offset: error.offset(),
+    })?;
 
     resolve_ast(&ast).map_err(|error| TestRunnerError::SourceDiagnostic {
         message: error.to_string(),
@@ -784,12 +1734,5 @@ fn compile_suite(path: &Path, source: &str) -> Result<TestSuite, TestRunnerError
         offset: error.offset(),
     })?;
 
-    let ir = lower_ast_to_ir(&ast).map_err(|error| TestRunnerError::Failure(error.to_string()))?;
-
-    Ok(TestSuite {
-        tests,
-        ir,
-        setup_modules,
-        teardown_modules,
-    })
+    Ok(ast)
 }
@@ -0,0 +1,226 @@
+//! Choosing which tests a `tonic test` run includes: `--filter`, the tags set
+//! with `@tag`/`@moduletag` (matched by `--only` and `--exclude`), and the
+//! failure manifest behind `--failed`.
+
+use super::{TestCaseResult, TestCaseStatus, TestRunnerError};
+use crate::parser::{Expr, Module};
+use crate::runtime::RuntimeValue;
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::path::Path;
+
+/// Failure manifest location, relative to the working directory.
+pub const FAILED_MANIFEST_PATH: &str = ".tonic/test/failures.json";
+
+/// Tags of one test, in declaration order.
+pub(crate) type Tags = Vec<(String, RuntimeValue)>;
+
+/// A `--only`/`--exclude` argument: `tag` or `tag:value`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TagFilter {
+    key: String,
+    value: Option<String>,
+}
+
+impl TagFilter {
+    pub fn parse(text: &str) -> Option<Self> {
+        let (key, value) = match text.split_once(':') {
+            Some((key, value)) => (key, Some(value.to_string())),
+            None => (text, None),
+        };
+        if key.is_empty() || value.as_deref() == Some("") {
+            return None;
+        }
+
+        Some(Self {
+            key: key.to_string(),
+            value,
+        })
+    }
+
+    /// A bare `tag` matches any value but `false` and `nil`; `tag:value`
+    /// compares with the value written without its `:` or quotes.
+    fn matches(&self, tags: &Tags) -> bool {
+        tags.iter().any(|(key, value)| {
+            key == &self.key
+                && match &self.value {
+                    None => !matches!(value, RuntimeValue::Bool(false) | RuntimeValue::Nil),
+                    Some(expected) => &tag_text(value) == expected,
+                }
+        })
+    }
+}
+
+/// Which discovered tests a run or listing includes.
+#[derive(Debug, Clone, Default)]
+pub struct TestSelection {
+    /// Substring of the test id (`--filter`).
+    pub filter: Option<String>,
+    /// A test must match one of these, when any are given (`--only`).
+    pub only: Vec<TagFilter>,
+    /// A test matching any of these is left out (`--exclude`).
+    pub exclude: Vec<TagFilter>,
+    /// Only these ids (`--failed`).
+    pub ids: Option<HashSet<String>>,
+}
+
+impl TestSelection {
+    pub(crate) fn includes(&self, id: &str, tags: &Tags) -> bool {
+        self.filter
+            .as_deref()
+            .is_none_or(|pattern| id.contains(pattern))
+            && self.ids.as_ref().is_none_or(|ids| ids.contains(id))
+            && (self.only.is_empty() || self.only.iter().any(|filter| filter.matches(tags)))
+            && !self.exclude.iter().any(|filter| filter.matches(tags))
+    }
+}
+
+/// Tags of the tests in `module`, keyed by the offset of each test's
+/// function: `@moduletag` applies to every test and `@tag` to the first test
+/// after it.
+pub(crate) fn module_test_tags(
+    module: &Module,
+    test_offsets: &[usize],
+    filename: &Option<String>,
+    source: &str,
+) -> Result<HashMap<usize, Tags>, TestRunnerError> {
+    let mut module_tags = Tags::new();
+    let mut test_tags: HashMap<usize, Tags> = HashMap::new();
+
+    for attribute in &module.attributes {
+        if attribute.name != "tag" && attribute.name != "moduletag" {
+            continue;
+        }
+        let Some(tags) = tag_entries(&attribute.value) else {
+            return Err(TestRunnerError::SourceDiagnostic {
+                message: format!(
+                    "@{} expects an atom or a keyword list of literal values",
+                    attribute.name
+                ),
+                filename: filename.clone(),
+                source: source.to_string(),
+                offset: Some(attribute.offset),
+            });
+        };
+
+        if attribute.name == "moduletag" {
+            module_tags.extend(tags);
+        } else if let Some(test) = test_offsets
+            .iter()
+            .filter(|offset| **offset > attribute.offset)
+            .min()
+        {
+            test_tags.entry(*test).or_default().extend(tags);
+        }
+    }
+
+    Ok(test_offsets
+        .iter()
+        .map(|offset| {
+            let mut tags = module_tags.clone();
+            tags.extend(test_tags.remove(offset).unwrap_or_default());
+            (*offset, tags)
+        })
+        .collect())
+}
+
+/// `@tag :slow` is shorthand for `@tag slow: true`.
+fn tag_entries(value: &Expr) -> Option<Tags> {
+    match value {
+        Expr::Atom { value, .. } => Some(vec![(value.clone(), RuntimeValue::Bool(true))]),
+        Expr::Keyword { entries, .. } => entries
+            .iter()
+            .map(|entry| Some((entry.key.clone(), tag_value(&entry.value)?)))
+            .collect(),
+        _ => None,
+    }
+}
+
+fn tag_value(value: &Expr) -> Option<RuntimeValue> {
+    match value {
+        Expr::Atom { value, .. } => Some(RuntimeValue::Atom(value.clone())),
+        Expr::String { value, .. } => Some(RuntimeValue::String(value.clone())),
+        Expr::Int { value, .. } => Some(RuntimeValue::Int(*value)),
+        Expr::Bool { value, .. } => Some(RuntimeValue::Bool(*value)),
+        Expr::Nil { .. } => Some(RuntimeValue::Nil),
+        _ => None,
+    }
+}
+
+fn tag_text(value: &RuntimeValue) -> String {
+    match value {
+        RuntimeValue::Atom(text) | RuntimeValue::String(text) => text.clone(),
+        other => other.render(),
+    }
+}
+
+/// Ids of the tests recorded as failed; empty when there is no manifest.
+pub fn load_failed_tests(path: &Path) -> Result<HashSet<String>, String> {
+    let contents = match std::fs::read_to_string(path) {
+        Ok(contents) => contents,
+        Err(error) if error.kind() == std::io::ErrorKind::NotFound => return Ok(HashSet::new()),
+        Err(error) => return Err(format!("failed to read {}: {error}", path.display())),
+    };
+
+    let manifest: serde_json::Value = serde_json::from_str(&contents)
+        .map_err(|error| format!("invalid failure manifest {}: {error}", path.display()))?;
+    let failed = manifest["failed"].as_array().ok_or_else(|| {
+        format!(
+            "invalid failure manifest {}: missing 'failed'",
+            path.display()
+        )
+    })?;
+
+    Ok(failed
+        .iter()
+        .filter_map(|id| id.as_str().map(str::to_string))
+        .collect())
+}
+
+/// Update the manifest with the outcome of `results`: tests that failed are
+/// added, and tests that ran otherwise are removed. Tests that did not run
+/// keep their entry.
+pub fn record_failed_tests(path: &Path, results: &[TestCaseResult]) -> Result<(), String> {
+    let mut failed: BTreeSet<String> = load_failed_tests(path)?.into_iter().collect();
+    for result in results {
+        if result.status == TestCaseStatus::Failed {
+            failed.insert(result.id.clone());
+        } else {
+            failed.remove(&result.id);
+        }
+    }
+
+    if let Some(parent) = path
+        .parent()
+        .filter(|parent| !parent.as_os_str().is_empty())
+    {
+        std::fs::create_dir_all(parent)
+            .map_err(|error| format!("failed to create {}: {error}", parent.display()))?;
+    }
+    let manifest = serde_json::json!({ "failed": failed });
+    std::fs::write(path, format!("{manifest}\n"))
+        .map_err(|error| format!("failed to write {}: {error}", path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tag_filters_match_bare_tags_and_rendered_values() {
+        let tags = vec![
+            ("slow".to_string(), RuntimeValue::Bool(true)),
+            ("area".to_string(), RuntimeValue::Atom("math".to_string())),
+            ("flaky".to_string(), RuntimeValue::Bool(false)),
+        ];
+
+        let matches = |filter: &str| TagFilter::parse(filter).unwrap().matches(&tags);
+        assert!(matches("slow"));
+        assert!(matches("slow:true"));
+        assert!(matches("area:math"));
+        assert!(!matches("area:io"));
+        assert!(!matches("flaky"));
+        assert!(!matches("missing"));
+        assert_eq!(TagFilter::parse("area:"), None);
+        assert_eq!(TagFilter::parse(":math"), None);
+    }
+}
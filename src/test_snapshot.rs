@@ -0,0 +1,182 @@
+//! Snapshot bookkeeping for a `tonic test` run: which `Assert.snapshot`
+//! files were written or updated, and which ones no test uses anymore.
+//! `Mock.cassette` recordings live in a sibling `__cassettes__/` directory.
+
+use super::{AnsiColor, TestCaseResult, TestCaseStatus};
+use crate::interop::{SnapshotOutcome, SnapshotRecord, SNAPSHOT_EXTENSION};
+use serde_json::{json, Value};
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+
+/// Snapshot directory of a test file: `__snapshots__/<file stem>/` next to it.
+pub(super) fn snapshot_dir(test_file: &Path) -> PathBuf {
+    fixture_dir(test_file, "__snapshots__")
+}
+
+/// Cassette directory of a test file: `__cassettes__/<file stem>/` next to it.
+pub(super) fn cassette_dir(test_file: &Path) -> PathBuf {
+    fixture_dir(test_file, "__cassettes__")
+}
+
+fn fixture_dir(test_file: &Path, kind: &str) -> PathBuf {
+    let stem = test_file
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    test_file
+        .parent()
+        .unwrap_or(Path::new(""))
+        .join(kind)
+        .join(stem)
+}
+
+/// A test file's snapshot directory and whether every one of its cases was
+/// selected, which is required before any of its snapshots count as obsolete.
+pub(super) struct SnapshotFile {
+    pub dir: PathBuf,
+    pub case_ids: Vec<String>,
+    pub fully_selected: bool,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SnapshotReport {
+    pub written: Vec<PathBuf>,
+    pub updated: Vec<PathBuf>,
+    /// Snapshot files of fully run, passing test files that no test used.
+    pub obsolete: Vec<PathBuf>,
+    /// Obsolete snapshots deleted by `--update-snapshots`.
+    pub removed: Vec<PathBuf>,
+}
+
+impl SnapshotReport {
+    /// Collect the run's snapshot records and look for obsolete files. Only
+    /// files whose cases were all selected and all passed are checked, since
+    /// a failed or skipped test may not have reached its snapshots.
+    pub(super) fn collect(
+        records: &[SnapshotRecord],
+        files: &[SnapshotFile],
+        results: &[TestCaseResult],
+        update: bool,
+    ) -> Self {
+        let mut report = Self::default();
+        for record in records {
+            let list = match record.outcome {
+                SnapshotOutcome::Written => &mut report.written,
+                SnapshotOutcome::Updated => &mut report.updated,
+                SnapshotOutcome::Matched | SnapshotOutcome::Mismatched => continue,
+            };
+            if !list.contains(&record.path) {
+                list.push(record.path.clone());
+            }
+        }
+
+        let used = records
+            .iter()
+            .map(|record| record.path.as_path())
+            .collect::<HashSet<_>>();
+        let statuses = results
+            .iter()
+            .map(|result| (result.id.as_str(), result.status))
+            .collect::<HashMap<_, _>>();
+
+        for file in files {
+            let all_passed = file
+                .case_ids
+                .iter()
+                .all(|id| statuses.get(id.as_str()) == Some(&TestCaseStatus::Passed));
+            if !file.fully_selected || !all_passed {
+                continue;
+            }
+            let Ok(entries) = std::fs::read_dir(&file.dir) else {
+                continue;
+            };
+            let mut obsolete = entries
+                .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+                .filter(|path| {
+                    path.extension()
+                        .is_some_and(|extension| extension == SNAPSHOT_EXTENSION)
+                        && !used.contains(path.as_path())
+                })
+                .collect::<Vec<_>>();
+            obsolete.sort();
+
+            if update {
+                for path in obsolete {
+                    if std::fs::remove_file(&path).is_ok() {
+                        report.removed.push(path);
+                    } else {
+                        report.obsolete.push(path);
+                    }
+                }
+                // Only succeeds once the directory is empty.
+                let _ = std::fs::remove_dir(&file.dir);
+            } else {
+                report.obsolete.extend(obsolete);
+            }
+        }
+
+        report
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.written.is_empty()
+            && self.updated.is_empty()
+            && self.obsolete.is_empty()
+            && self.removed.is_empty()
+    }
+
+    pub(super) fn render_text(&self, color: &AnsiColor) -> Vec<String> {
+        if self.is_empty() {
+            return Vec::new();
+        }
+
+        let counts = [
+            (self.written.len(), "written"),
+            (self.updated.len(), "updated"),
+            (self.obsolete.len(), "obsolete"),
+            (self.removed.len(), "removed"),
+        ]
+        .into_iter()
+        .filter(|(count, _)| *count > 0)
+        .map(|(count, label)| format!("{count} {label}"))
+        .collect::<Vec<_>>();
+
+        let mut lines = vec![format!("snapshots: {}", counts.join(", "))];
+        for (label, paths) in [
+            ("written", &self.written),
+            ("updated", &self.updated),
+            ("removed", &self.removed),
+        ] {
+            for path in paths {
+                lines.push(format!("  {label}: {}", path.display()));
+            }
+        }
+        for path in &self.obsolete {
+            lines.push(format!(
+                "  {}obsolete{}: {}",
+                color.yellow,
+                color.reset,
+                path.display()
+            ));
+        }
+        if !self.obsolete.is_empty() {
+            lines.push("  run with --update-snapshots to remove obsolete snapshots".to_string());
+        }
+        lines
+    }
+
+    pub fn render_json(&self) -> Value {
+        let paths = |paths: &[PathBuf]| {
+            paths
+                .iter()
+                .map(|path| path.display().to_string())
+                .collect::<Vec<_>>()
+        };
+        json!({
+            "written": paths(&self.written),
+            "updated": paths(&self.updated),
+            "obsolete": paths(&self.obsolete),
+            "removed": paths(&self.removed),
+        })
+    }
+}
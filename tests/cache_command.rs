@@ -0,0 +1,161 @@
+mod common;
+
+use common::{tonic_in, tonic_in_with_env};
+use std::ffi::OsStr;
+use std::fs;
+use std::path::Path;
+
+fn write_project(root: &Path, value: &str) {
+    fs::create_dir_all(root.join("src")).unwrap();
+    fs::write(
+        root.join("tonic.toml"),
+        "[project]\nname = \"demo\"\nentry = \"src/main.tn\"\n",
+    )
+    .unwrap();
+    fs::write(
+        root.join("src/main.tn"),
+        format!("defmodule Demo do\n  def run() do\n    {value}\n  end\nend\n"),
+    )
+    .unwrap();
+}
+
+#[test]
+fn cache_stats_verify_and_clean_manage_the_project_cache() {
+    let root = common::unique_fixture_root("cache-command-project");
+    write_project(&root, "1");
+    let project = [
+        ("TONIC_DEBUG_CACHE", OsStr::new("1")),
+        ("TONIC_SHARED_CACHE", OsStr::new("0")),
+    ];
+
+    for _ in 0..2 {
+        let (code, _, stderr) = tonic_in_with_env(&root, &["run", "."], &project);
+        assert_eq!(code, 0, "stderr: {stderr}");
+    }
+    let (code, stdout, _) = tonic_in_with_env(&root, &["cache", "stats"], &project);
+    assert_eq!(code, 0);
+    assert!(stdout.contains("entries: 1\n"), "{stdout}");
+    assert!(stdout.contains("hits: 1 (50%)\nmisses: 1\n"), "{stdout}");
+
+    // A copy written by another tonic version is stale; a valid key with
+    // unreadable IR is corrupt.
+    let cache_dir = root.join(".tonic/cache");
+    let entry = fs::read_dir(&cache_dir)
+        .unwrap()
+        .map(|entry| entry.unwrap().file_name().to_string_lossy().to_string())
+        .find(|name| name.ends_with(".ir.json"))
+        .expect("run should store an IR artifact");
+    let version = env!("CARGO_PKG_VERSION");
+    let stale = entry.replace(&format!("|{}:{version}|", version.len()), "|5:0.0.1|");
+    fs::copy(cache_dir.join(&entry), cache_dir.join(&stale)).unwrap();
+    let corrupt = entry.replace("|4:none", "|5:other");
+    fs::write(cache_dir.join(&corrupt), "{\"functions\":").unwrap();
+
+    let (code, stdout, stderr) = tonic_in_with_env(&root, &["cache", "verify"], &project);
+    assert_eq!(code, 1);
+    assert!(
+        stdout.contains(&format!("stale: {stale}: built by tonic 0.0.1\n")),
+        "{stdout}"
+    );
+    assert!(
+        stdout.contains(&format!("corrupt: {corrupt}: invalid IR:")),
+        "{stdout}"
+    );
+    assert!(
+        stdout.ends_with("cache verify: 3 entries, 1 corrupt, 1 stale\n"),
+        "{stdout}"
+    );
+    assert!(stderr.contains("tonic cache verify --fix"), "{stderr}");
+
+    let (code, stdout, _) = tonic_in_with_env(&root, &["cache", "verify", "--fix"], &project);
+    assert_eq!(code, 0);
+    assert!(stdout.ends_with(", 2 removed\n"), "{stdout}");
+    assert!(!cache_dir.join(&stale).exists());
+
+    let (code, stdout, _) =
+        tonic_in_with_env(&root, &["cache", "clean", "--older-than", "1d"], &project);
+    assert_eq!(code, 0);
+    assert!(stdout.contains("removed 0 entries"), "{stdout}");
+    let (code, stdout, _) = tonic_in_with_env(&root, &["cache", "clean"], &project);
+    assert_eq!(code, 0);
+    assert!(stdout.contains("removed 1 entry"), "{stdout}");
+    let (_, _, stderr) = tonic_in_with_env(&root, &["run", "."], &project);
+    assert!(stderr.contains("cache-status miss"), "{stderr}");
+
+    let (code, _, stderr) =
+        tonic_in_with_env(&root, &["cache", "clean", "--older-than", "soon"], &project);
+    assert_eq!(code, 64);
+    assert!(stderr.contains("invalid --older-than 'soon'"), "{stderr}");
+}
+
+#[test]
+fn cache_stats_and_clean_count_native_artifacts() {
+    let root = common::unique_fixture_root("cache-command-native");
+    write_project(&root, "1");
+
+    let (code, _, stderr) = tonic_in(&root, &["compile", "."]);
+    assert_eq!(code, 0, "stderr: {stderr}");
+    let manifest = fs::read_dir(root.join(".tonic/build"))
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .find(|path| path.to_string_lossy().ends_with(".tnx.json"))
+        .expect("compile should write a native artifact manifest");
+
+    let (code, stdout, _) = tonic_in(&root, &["cache", "stats", "--project"]);
+    assert_eq!(code, 0);
+    assert!(stdout.contains("\nnative artifacts: 1 ("), "{stdout}");
+
+    let (code, stdout, _) = tonic_in(&root, &["cache", "clean", "--project"]);
+    assert_eq!(code, 0);
+    assert!(
+        stdout.starts_with("cache clean: removed 0 entries (0 B) and 1 native artifact ("),
+        "{stdout}"
+    );
+    assert!(!manifest.exists());
+    assert!(!manifest.with_extension("").with_extension("c").exists());
+    let (_, stdout, _) = tonic_in(&root, &["cache", "stats", "--project"]);
+    assert!(stdout.contains("\nnative artifacts: 0 (0 B)\n"), "{stdout}");
+}
+
+#[test]
+fn run_cache_is_shared_across_projects_by_default_and_evicts_least_recently_used_entries() {
+    let root = common::unique_fixture_root("cache-command-shared");
+    let home = root.join("home");
+    let (first, second) = (root.join("first"), root.join("second"));
+    write_project(&first, "1");
+    write_project(&second, "1");
+    let shared = [
+        ("TONIC_DEBUG_CACHE", OsStr::new("1")),
+        ("TONIC_HOME", home.as_os_str()),
+    ];
+
+    let (_, _, stderr) = tonic_in_with_env(&first, &["run", "."], &shared);
+    assert!(stderr.contains("cache-status miss"), "{stderr}");
+    let (_, _, stderr) = tonic_in_with_env(&second, &["run", "."], &shared);
+    assert!(stderr.contains("cache-status hit"), "{stderr}");
+    assert!(!first.join(".tonic/cache").exists());
+    assert!(home.join("cache").is_dir());
+
+    let (code, stdout, _) = tonic_in_with_env(&root, &["cache", "stats"], &shared);
+    assert_eq!(code, 0);
+    assert!(
+        stdout.starts_with(&format!(
+            "cache: {}\nentries: 1\n",
+            home.join("cache").display()
+        )),
+        "{stdout}"
+    );
+
+    // With a zero limit, storing a new artifact evicts it and the older one.
+    write_project(&second, "2");
+    let limited = [
+        shared[0],
+        shared[1],
+        ("TONIC_CACHE_MAX_MB", OsStr::new("0")),
+    ];
+    let (code, _, stderr) = tonic_in_with_env(&second, &["run", "."], &limited);
+    assert_eq!(code, 0, "stderr: {stderr}");
+    let (_, stdout, _) = tonic_in_with_env(&root, &["cache", "stats", "--shared"], &shared);
+    assert!(stdout.contains("entries: 0\n"), "{stdout}");
+    assert!(stdout.contains("evictions: 2\n"), "{stdout}");
+}
@@ -79,6 +79,143 @@ fn fmt_accepts_project_root_path_and_emits_ok_contract() {
     assert_eq!(stderr, "");
 }
 
+#[test]
+fn fmt_reads_stdin_and_applies_project_format_config() {
+    use std::io::Write;
+    use std::process::Stdio;
+
+    let fixture_root = write_project_fixture("fmt-stdin-config");
+    fs::write(
+        fixture_root.join("tonic.toml"),
+        "[project]\nname = \"demo\"\nentry = \"src/main.tn\"\n\n[format]\nline_length = 30\ntrailing_comma = \"multiline\"\n",
+    )
+    .expect("fixture setup should write tonic.toml");
+
+    let mut child = std::process::Command::new(env!("CARGO_BIN_EXE_tonic"))
+        .current_dir(&fixture_root)
+        .args(["fmt", "-"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .expect("fmt command should spawn");
+    child
+        .stdin
+        .take()
+        .expect("stdin should be piped")
+        .write_all(
+            b"defmodule Demo do\ndef run() do\ncombine(first_value, second_value)\nend\nend\n",
+        )
+        .expect("stdin should accept source");
+    let output = child.wait_with_output().expect("fmt command should finish");
+
+    assert!(
+        output.status.success(),
+        "expected fmt - to succeed, got status {:?} with stderr: {}",
+        output.status.code(),
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    let stdout = String::from_utf8(output.stdout).expect("stdout should be utf8");
+    assert_eq!(
+        stdout,
+        "defmodule Demo do\n  def run() do\n    combine(\n      first_value,\n      second_value,\n    )\n  end\nend\n"
+    );
+}
+
+#[test]
+fn fmt_diff_prints_unified_diff_and_leaves_file_untouched() {
+    let fixture_root = write_project_fixture("fmt-diff");
+    let entry = fixture_root.join("src/main.tn");
+    let unformatted = "defmodule Demo do\ndef run() do\n1\nend\nend\n";
+    fs::write(&entry, unformatted).expect("fixture setup should write entry source file");
+
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_tonic"))
+        .current_dir(&fixture_root)
+        .args(["fmt", "src/main.tn", "--diff"])
+        .output()
+        .expect("fmt command should execute");
+
+    assert_eq!(output.status.code(), Some(1));
+
+    let stdout = String::from_utf8(output.stdout).expect("stdout should be utf8");
+    assert_eq!(
+        stdout,
+        "--- src/main.tn\n+++ src/main.tn\n@@ -1,5 +1,5 @@\n defmodule Demo do\n-def run() do\n-1\n+  def run() do\n+    1\n+  end\n end\n-end\n"
+    );
+    assert_eq!(
+        fs::read_to_string(&entry).expect("entry should still be readable"),
+        unformatted
+    );
+}
+
+#[test]
+fn fmt_adds_local_call_parens_only_when_configured_and_imports_dependency_locals() {
+    let fixture_root = write_project_fixture("fmt-locals-without-parens");
+    let entry = fixture_root.join("src/main.tn");
+    let source =
+        "defmodule Demo do\n  def run() do\n    helper 1, 2\n    route \"/\"\n  end\nend\n";
+    fs::write(&entry, source).expect("fixture setup should write entry source file");
+
+    let fmt = |args: &[&str]| {
+        std::process::Command::new(env!("CARGO_BIN_EXE_tonic"))
+            .current_dir(&fixture_root)
+            .args(args)
+            .output()
+            .expect("command should execute")
+    };
+
+    // Without `locals_without_parens`, paren-less calls are left alone.
+    let output = fmt(&["fmt", "src/main.tn", "--check"]);
+    assert_eq!(
+        output.status.code(),
+        Some(0),
+        "stderr: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    let dsl_root = fixture_root.join("dsl");
+    fs::create_dir_all(dsl_root.join("src")).expect("fixture setup should create dsl");
+    fs::write(
+        dsl_root.join("tonic.toml"),
+        "[project]\nname = \"dsl\"\n\n[lib]\npath = \"src\"\n\n[format]\nlocals_without_parens = [\"route\"]\n",
+    )
+    .expect("fixture setup should write dsl tonic.toml");
+    fs::write(
+        dsl_root.join("src/dsl.tn"),
+        "defmodule Dsl do\n  def route(path) do\n    path\n  end\nend\n",
+    )
+    .expect("fixture setup should write dsl source");
+    fs::write(
+        fixture_root.join("tonic.toml"),
+        "[project]\nname = \"demo\"\nentry = \"src/main.tn\"\n\n[dependencies]\ndsl = { path = \"dsl\" }\n\n[format]\nimport_deps = [\"dsl\"]\n",
+    )
+    .expect("fixture setup should write tonic.toml");
+
+    let output = fmt(&["fmt", "src/main.tn"]);
+    assert_eq!(
+        output.status.code(),
+        Some(1),
+        "import_deps needs a lockfile: {}",
+        String::from_utf8_lossy(&output.stdout)
+    );
+    assert!(String::from_utf8_lossy(&output.stderr)
+        .contains("import_deps: dependency 'dsl' is not locked or not fetched"));
+
+    let output = fmt(&["deps", "lock"]);
+    assert!(output.status.success());
+    let output = fmt(&["fmt", "src/main.tn"]);
+    assert!(
+        output.status.success(),
+        "stderr: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert_eq!(
+        fs::read_to_string(&entry).expect("entry should be readable"),
+        "defmodule Demo do\n  def run() do\n    helper(1, 2)\n    route \"/\"\n  end\nend\n"
+    );
+}
+
 fn strip_ansi(s: &str) -> String {
     let mut out = String::with_capacity(s.len());
     let mut chars = s.chars();
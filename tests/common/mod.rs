@@ -1,9 +1,11 @@
 #![allow(dead_code)]
 
 pub mod differential;
+pub mod registry;
 pub mod self_hosted_lexer_parity;
 
-use std::path::PathBuf;
+use std::ffi::OsStr;
+use std::path::{Path, PathBuf};
 
 pub fn unique_fixture_root(test_name: &str) -> PathBuf {
     let timestamp = std::time::SystemTime::now()
@@ -19,6 +21,95 @@ pub fn unique_fixture_root(test_name: &str) -> PathBuf {
     path
 }
 
+/// Write `contents` to `root/relative`, creating parent directories.
+pub fn write_file(root: &Path, relative: &str, contents: &str) {
+    let path = root.join(relative);
+    std::fs::create_dir_all(path.parent().unwrap())
+        .expect("fixture setup should create directories");
+    std::fs::write(path, contents).expect("fixture setup should write file");
+}
+
+/// Write `source` to `file_name` in a fresh fixture root and return the root.
+pub fn write_test_file(test_name: &str, file_name: &str, source: &str) -> PathBuf {
+    let fixture_root = unique_fixture_root(test_name);
+    std::fs::write(fixture_root.join(file_name), source)
+        .expect("fixture setup should write test file");
+    fixture_root
+}
+
+/// Run `tonic test <args>` in `fixture_root` with colors disabled.
+pub fn run_tonic_test(fixture_root: &Path, args: &[&str]) -> std::process::Output {
+    std::process::Command::new(env!("CARGO_BIN_EXE_tonic"))
+        .current_dir(fixture_root)
+        .env("NO_COLOR", "1")
+        .arg("test")
+        .args(args)
+        .output()
+        .expect("test command should execute")
+}
+
+/// A `tonic <args>` command run from `current_dir`.
+pub fn tonic_command(current_dir: &Path, args: &[&str]) -> std::process::Command {
+    let mut command = std::process::Command::new(env!("CARGO_BIN_EXE_tonic"));
+    command.current_dir(current_dir).args(args);
+    command
+}
+
+/// Run `tonic <args>` from `current_dir`.
+pub fn run_tonic(current_dir: &Path, args: &[&str]) -> std::process::Output {
+    tonic_command(current_dir, args)
+        .output()
+        .expect("tonic should execute")
+}
+
+/// Run `tonic <args>` from `current_dir` and return its exit code, stdout
+/// and stderr.
+pub fn tonic_in(current_dir: &Path, args: &[&str]) -> (i32, String, String) {
+    output_parts(run_tonic(current_dir, args))
+}
+
+/// Run `tonic <args>` from `current_dir` with extra environment variables
+/// and return its exit code, stdout and stderr.
+pub fn tonic_in_with_env(
+    current_dir: &Path,
+    args: &[&str],
+    env: &[(&str, &OsStr)],
+) -> (i32, String, String) {
+    let mut command = tonic_command(current_dir, args);
+    command.envs(env.iter().copied());
+    output_parts(command.output().expect("tonic should execute"))
+}
+
+fn output_parts(output: std::process::Output) -> (i32, String, String) {
+    (
+        output.status.code().unwrap_or(-1),
+        String::from_utf8_lossy(&output.stdout).to_string(),
+        String::from_utf8_lossy(&output.stderr).to_string(),
+    )
+}
+
+/// Run `git <args>` in `dir` as a test identity, panicking on failure, and
+/// return its trimmed stdout.
+pub fn git(dir: &Path, args: &[&str]) -> String {
+    let output = std::process::Command::new("git")
+        .current_dir(dir)
+        .args([
+            "-c",
+            "user.email=tonic-tests@test.invalid",
+            "-c",
+            "user.name=tonic-tests",
+        ])
+        .args(args)
+        .output()
+        .expect("git should run");
+    assert!(
+        output.status.success(),
+        "git {args:?} failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    String::from_utf8(output.stdout).unwrap().trim().to_string()
+}
+
 pub fn unique_temp_dir(test_name: &str) -> PathBuf {
     unique_fixture_root(test_name)
 }
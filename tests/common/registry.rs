@@ -0,0 +1,120 @@
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// A package registry laid out on disk, served to tonic via `file://`.
+pub struct FileRegistry {
+    pub root: PathBuf,
+}
+
+impl FileRegistry {
+    pub fn new(root: PathBuf) -> Self {
+        fs::create_dir_all(root.join("packages")).expect("registry root should be created");
+        Self { root }
+    }
+
+    pub fn url(&self) -> String {
+        format!("file://{}", self.root.display())
+    }
+
+    /// Publish `name` `version` with one source file and the given
+    /// dependency requirements; returns the tarball checksum.
+    pub fn publish(
+        &self,
+        name: &str,
+        version: &str,
+        dependencies: &[(&str, &str)],
+        source: &str,
+    ) -> String {
+        let tarball = package_tarball(&format!("{name}.tn"), source);
+        let checksum = format!("sha256:{:x}", Sha256::digest(&tarball));
+
+        let package_dir = self.root.join("packages").join(name);
+        let version_dir = package_dir.join(version);
+        fs::create_dir_all(&version_dir).expect("version directory should be created");
+        fs::write(version_dir.join("download"), &tarball).expect("tarball should be written");
+
+        let dependencies = dependencies
+            .iter()
+            .map(|(name, requirement)| {
+                (
+                    name.to_string(),
+                    serde_json::Value::String(requirement.to_string()),
+                )
+            })
+            .collect::<serde_json::Map<_, _>>();
+        write_json(
+            &version_dir.join("index.json"),
+            &serde_json::json!({
+                "name": name,
+                "version": version,
+                "checksum": checksum,
+                "dependencies": dependencies,
+            }),
+        );
+
+        let index_path = package_dir.join("index.json");
+        let mut index = fs::read_to_string(&index_path)
+            .ok()
+            .and_then(|contents| serde_json::from_str::<serde_json::Value>(&contents).ok())
+            .unwrap_or_else(|| serde_json::json!({ "name": name, "versions": [] }));
+        index["versions"]
+            .as_array_mut()
+            .expect("index versions should be a list")
+            .push(serde_json::json!({ "version": version, "checksum": checksum }));
+        write_json(&index_path, &index);
+
+        checksum
+    }
+
+    /// Mark a published version as yanked in the package index.
+    pub fn yank(&self, name: &str, version: &str) {
+        let index_path = self.root.join("packages").join(name).join("index.json");
+        let mut index: serde_json::Value = serde_json::from_str(
+            &fs::read_to_string(&index_path).expect("package index should exist"),
+        )
+        .expect("package index should be json");
+        for entry in index["versions"]
+            .as_array_mut()
+            .expect("index versions should be a list")
+        {
+            if entry["version"] == version {
+                entry["yanked"] = serde_json::Value::Bool(true);
+            }
+        }
+        write_json(&index_path, &index);
+    }
+
+    pub fn tarball_path(&self, name: &str, version: &str) -> PathBuf {
+        self.root
+            .join("packages")
+            .join(name)
+            .join(version)
+            .join("download")
+    }
+}
+
+/// A `.tar.gz` holding `src/<file_name>`.
+pub fn package_tarball(file_name: &str, source: &str) -> Vec<u8> {
+    let encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+    let mut builder = tar::Builder::new(encoder);
+    let mut header = tar::Header::new_gnu();
+    header.set_size(source.len() as u64);
+    header.set_mode(0o644);
+    header.set_cksum();
+    builder
+        .append_data(&mut header, format!("src/{file_name}"), source.as_bytes())
+        .expect("tarball entry should be written");
+    builder
+        .into_inner()
+        .and_then(|encoder| encoder.finish())
+        .expect("tarball should be finished")
+}
+
+fn write_json(path: &Path, value: &serde_json::Value) {
+    fs::write(
+        path,
+        serde_json::to_string_pretty(value).expect("json should serialize"),
+    )
+    .expect("json file should be written");
+}
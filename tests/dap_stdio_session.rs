@@ -0,0 +1,336 @@
+use serde_json::{json, Value};
+use std::io::{BufRead, BufReader, Read, Write};
+use std::process::{Child, ChildStdin, ChildStdout, Command, Stdio};
+
+mod common;
+
+struct DapClient {
+    child: Child,
+    stdin: ChildStdin,
+    stdout: BufReader<ChildStdout>,
+    seq: i64,
+}
+
+impl Drop for DapClient {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
+
+impl DapClient {
+    fn spawn() -> Self {
+        let mut child = Command::new(env!("CARGO_BIN_EXE_tonic"))
+            .arg("dap")
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()
+            .expect("dap server should start");
+        let stdin = child.stdin.take().unwrap();
+        let stdout = BufReader::new(child.stdout.take().unwrap());
+        Self {
+            child,
+            stdin,
+            stdout,
+            seq: 0,
+        }
+    }
+
+    fn send(&mut self, command: &str, arguments: Value) -> i64 {
+        self.seq += 1;
+        let payload = json!({
+            "seq": self.seq,
+            "type": "request",
+            "command": command,
+            "arguments": arguments,
+        })
+        .to_string();
+        write!(
+            self.stdin,
+            "Content-Length: {}\r\n\r\n{payload}",
+            payload.len()
+        )
+        .unwrap();
+        self.stdin.flush().unwrap();
+        self.seq
+    }
+
+    fn read(&mut self) -> Value {
+        let mut length = 0;
+        loop {
+            let mut header = String::new();
+            self.stdout.read_line(&mut header).unwrap();
+            let header = header.trim_end();
+            if header.is_empty() {
+                break;
+            }
+            if let Some(value) = header.strip_prefix("Content-Length:") {
+                length = value.trim().parse().unwrap();
+            }
+        }
+        let mut body = vec![0; length];
+        self.stdout.read_exact(&mut body).unwrap();
+        serde_json::from_slice(&body).unwrap()
+    }
+
+    /// Send a request and return its response, collecting events seen on the way.
+    fn request(&mut self, command: &str, arguments: Value, events: &mut Vec<Value>) -> Value {
+        let seq = self.send(command, arguments);
+        loop {
+            let message = self.read();
+            if message["type"] == "response" && message["request_seq"] == seq {
+                return message;
+            }
+            events.push(message);
+        }
+    }
+
+    fn wait_for_event(&mut self, event: &str, events: &mut Vec<Value>) -> Value {
+        if let Some(index) = events.iter().position(|message| message["event"] == event) {
+            return events.remove(index);
+        }
+        loop {
+            let message = self.read();
+            if message["event"] == event {
+                return message;
+            }
+            events.push(message);
+        }
+    }
+}
+
+fn write_program(test_name: &str, file_name: &str, source: &str) -> String {
+    let dir = common::unique_fixture_root(test_name);
+    let path = dir.join(file_name);
+    std::fs::write(&path, source).unwrap();
+    path.to_string_lossy().into_owned()
+}
+
+const PROGRAM: &str = "defmodule Demo do
+  def run() do
+    total = sum([1, 2, 3])
+    IO.puts(\"total #{total}\")
+    total
+  end
+
+  def sum(items) do
+    Enum.reduce(items, 0, fn item, acc -> acc + item end)
+  end
+end
+";
+
+#[test]
+fn dap_stops_at_breakpoint_and_inspects_frame() {
+    let path = write_program("dap-breakpoint", "main.tn", PROGRAM);
+    let mut client = DapClient::spawn();
+    let mut events = Vec::new();
+
+    let init = client.request("initialize", json!({ "adapterID": "tonic" }), &mut events);
+    assert_eq!(init["body"]["supportsConditionalBreakpoints"], true);
+
+    let launch = client.request("launch", json!({ "program": path }), &mut events);
+    assert_eq!(launch["success"], true, "launch failed: {launch}");
+    client.wait_for_event("initialized", &mut events);
+
+    let snapped = client.request(
+        "setBreakpoints",
+        json!({ "source": { "path": path }, "breakpoints": [{ "line": 7 }] }),
+        &mut events,
+    );
+    assert_eq!(snapped["body"]["breakpoints"][0]["line"], 9);
+
+    let breakpoints = client.request(
+        "setBreakpoints",
+        json!({ "source": { "path": path }, "breakpoints": [{ "line": 4 }] }),
+        &mut events,
+    );
+    assert_eq!(breakpoints["body"]["breakpoints"][0]["verified"], true);
+    assert_eq!(breakpoints["body"]["breakpoints"][0]["line"], 4);
+
+    client.request("configurationDone", json!({}), &mut events);
+    let stopped = client.wait_for_event("stopped", &mut events);
+    assert_eq!(stopped["body"]["reason"], "breakpoint");
+
+    let trace = client.request("stackTrace", json!({ "threadId": 1 }), &mut events);
+    let frames = trace["body"]["stackFrames"].as_array().unwrap();
+    assert_eq!(frames[0]["name"], "Demo.run");
+    assert_eq!(frames[0]["line"], 4);
+    let frame_id = frames[0]["id"].clone();
+
+    let scopes = client.request("scopes", json!({ "frameId": frame_id }), &mut events);
+    let reference = scopes["body"]["scopes"][0]["variablesReference"].clone();
+    let variables = client.request(
+        "variables",
+        json!({ "variablesReference": reference }),
+        &mut events,
+    );
+    assert_eq!(
+        variables["body"]["variables"],
+        json!([{ "name": "total", "value": "6", "type": "int", "variablesReference": 0 }])
+    );
+
+    let evaluated = client.request(
+        "evaluate",
+        json!({ "expression": "total * 7", "frameId": frame_id }),
+        &mut events,
+    );
+    assert_eq!(evaluated["body"]["result"], "42");
+
+    client.request("continue", json!({ "threadId": 1 }), &mut events);
+    let output = client.wait_for_event("output", &mut events);
+    assert_eq!(output["body"]["output"], "total 6\n");
+
+    let exited = client.wait_for_event("exited", &mut events);
+    assert_eq!(exited["body"]["exitCode"], 0);
+    client.wait_for_event("terminated", &mut events);
+    client.request("disconnect", json!({}), &mut events);
+}
+
+#[test]
+fn dap_steps_into_and_out_of_calls() {
+    let path = write_program("dap-step", "main.tn", PROGRAM);
+    let mut client = DapClient::spawn();
+    let mut events = Vec::new();
+
+    client.request("initialize", json!({ "adapterID": "tonic" }), &mut events);
+    client.request(
+        "launch",
+        json!({ "program": path, "stopOnEntry": true }),
+        &mut events,
+    );
+    client.request("configurationDone", json!({}), &mut events);
+
+    let stopped = client.wait_for_event("stopped", &mut events);
+    assert_eq!(stopped["body"]["reason"], "entry");
+
+    client.request("stepIn", json!({ "threadId": 1 }), &mut events);
+    client.wait_for_event("stopped", &mut events);
+    let trace = client.request("stackTrace", json!({ "threadId": 1 }), &mut events);
+    assert_eq!(trace["body"]["stackFrames"][0]["name"], "Demo.sum");
+    assert_eq!(trace["body"]["stackFrames"][0]["line"], 9);
+    assert_eq!(trace["body"]["stackFrames"][1]["name"], "Demo.run");
+
+    client.request("stepOut", json!({ "threadId": 1 }), &mut events);
+    client.wait_for_event("stopped", &mut events);
+    let trace = client.request("stackTrace", json!({ "threadId": 1 }), &mut events);
+    assert_eq!(trace["body"]["stackFrames"][0]["name"], "Demo.run");
+    assert_eq!(trace["body"]["totalFrames"], 1);
+
+    client.request("next", json!({ "threadId": 1 }), &mut events);
+    client.wait_for_event("stopped", &mut events);
+    let trace = client.request("stackTrace", json!({ "threadId": 1 }), &mut events);
+    assert_eq!(trace["body"]["stackFrames"][0]["line"], 4);
+
+    client.request("disconnect", json!({}), &mut events);
+}
+
+#[test]
+fn dap_conditional_breakpoint_in_test_case() {
+    let path = write_program(
+        "dap-test-case",
+        "math_test.tn",
+        "defmodule MathTest do
+  def test_loop() do
+    check(3)
+  end
+
+  def check(n) do
+    if n == 0 do
+      :ok
+    else
+      check(n - 1)
+    end
+  end
+end
+",
+    );
+    let mut client = DapClient::spawn();
+    let mut events = Vec::new();
+
+    client.request("initialize", json!({ "adapterID": "tonic" }), &mut events);
+    let launch = client.request(
+        "launch",
+        json!({ "program": path, "test": "MathTest.test_loop" }),
+        &mut events,
+    );
+    assert_eq!(launch["success"], true, "launch failed: {launch}");
+    client.request(
+        "setBreakpoints",
+        json!({ "source": { "path": path }, "breakpoints": [{ "line": 7, "condition": "n == 1" }] }),
+        &mut events,
+    );
+    client.request("configurationDone", json!({}), &mut events);
+
+    client.wait_for_event("stopped", &mut events);
+    let evaluated = client.request("evaluate", json!({ "expression": "n" }), &mut events);
+    assert_eq!(evaluated["body"]["result"], "1");
+    let trace = client.request("stackTrace", json!({ "threadId": 1 }), &mut events);
+    assert_eq!(trace["body"]["totalFrames"], 4);
+
+    client.request("continue", json!({ "threadId": 1 }), &mut events);
+    let output = client.wait_for_event("output", &mut events);
+    assert_eq!(output["body"]["output"], "test MathTest.test_loop ... ok\n");
+    client.wait_for_event("terminated", &mut events);
+}
+
+#[test]
+fn dap_launches_a_project_directory_through_its_manifest() {
+    let root = common::unique_fixture_root("dap-project");
+    common::write_file(
+        &root,
+        "tonic.toml",
+        "[project]\nname = \"demo\"\nentry = \"src/main.tn\"\n",
+    );
+    common::write_file(
+        &root,
+        "src/main.tn",
+        "defmodule Demo do
+  def run() do
+    total = Math.sum([1, 2, 3])
+    IO.puts(\"total #{total}\")
+    total
+  end
+end
+",
+    );
+    common::write_file(
+        &root,
+        "src/math.tn",
+        "defmodule Math do
+  def sum(items) do
+    Enum.reduce(items, 0, fn item, acc -> acc + item end)
+  end
+end
+",
+    );
+    let entry = root.join("src/main.tn").to_string_lossy().into_owned();
+    let mut client = DapClient::spawn();
+    let mut events = Vec::new();
+
+    client.request("initialize", json!({ "adapterID": "tonic" }), &mut events);
+    let launch = client.request("launch", json!({ "program": root }), &mut events);
+    assert_eq!(launch["success"], true, "launch failed: {launch}");
+    let breakpoints = client.request(
+        "setBreakpoints",
+        json!({ "source": { "path": entry }, "breakpoints": [{ "line": 4 }] }),
+        &mut events,
+    );
+    assert_eq!(breakpoints["body"]["breakpoints"][0]["verified"], true);
+    client.request("configurationDone", json!({}), &mut events);
+
+    client.wait_for_event("stopped", &mut events);
+    let trace = client.request("stackTrace", json!({ "threadId": 1 }), &mut events);
+    let frame = &trace["body"]["stackFrames"][0];
+    assert_eq!(frame["name"], "Demo.run");
+    assert_eq!(frame["line"], 4);
+    assert_eq!(frame["source"]["name"], "main.tn");
+    let evaluated = client.request("evaluate", json!({ "expression": "total" }), &mut events);
+    assert_eq!(evaluated["body"]["result"], "6");
+
+    client.request("continue", json!({ "threadId": 1 }), &mut events);
+    let output = client.wait_for_event("output", &mut events);
+    assert_eq!(output["body"]["output"], "total 6\n");
+    let exited = client.wait_for_event("exited", &mut events);
+    assert_eq!(exited["body"]["exitCode"], 0);
+}
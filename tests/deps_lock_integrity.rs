@@ -0,0 +1,170 @@
+use std::fs;
+use std::path::Path;
+mod common;
+use common::{git, run_tonic};
+
+/// A project depending on tag `v1` of a local git repository.
+fn write_fixture(fixture_root: &Path) -> (std::path::PathBuf, std::path::PathBuf) {
+    let remote_repo = fixture_root.join("remote_dep");
+    let project_root = fixture_root.join("app");
+    fs::create_dir_all(&remote_repo).unwrap();
+    fs::create_dir_all(project_root.join("src")).unwrap();
+
+    fs::write(
+        remote_repo.join("remote.tn"),
+        "defmodule Remote do\n  def answer() do\n    9\n  end\nend\n",
+    )
+    .unwrap();
+    git(&remote_repo, &["init"]);
+    git(&remote_repo, &["add", "."]);
+    git(&remote_repo, &["commit", "-m", "initial"]);
+    git(&remote_repo, &["tag", "v1"]);
+
+    fs::write(
+        project_root.join("tonic.toml"),
+        format!(
+            "[project]\nname = \"demo\"\nentry = \"src/main.tn\"\n\n[dependencies]\nremote_dep = {{ git = \"{}\", rev = \"v1\" }}\n",
+            remote_repo.display()
+        ),
+    )
+    .unwrap();
+    fs::write(
+        project_root.join("src/main.tn"),
+        "defmodule Demo do\n  def run() do\n    Remote.answer()\n  end\nend\n",
+    )
+    .unwrap();
+
+    (remote_repo, project_root)
+}
+
+#[test]
+fn deps_sync_records_git_tree_hashes_and_rejects_force_pushed_tags() {
+    let fixture_root = common::unique_fixture_root("deps-lock-integrity-hash");
+    let (remote_repo, project_root) = write_fixture(&fixture_root);
+
+    let sync = run_tonic(&project_root, &["deps", "sync"]);
+    assert_eq!(
+        sync.status.code(),
+        Some(0),
+        "stderr: {}",
+        String::from_utf8_lossy(&sync.stderr)
+    );
+    let lockfile = fs::read_to_string(project_root.join("tonic.lock")).unwrap();
+    assert!(
+        lockfile.contains("hash = \"sha256:"),
+        "expected a git tree hash in tonic.lock:\n{lockfile}"
+    );
+
+    // Re-tag v1 at different contents, as a force-push would, and sync from
+    // a clean cache the way CI does.
+    fs::write(
+        remote_repo.join("remote.tn"),
+        "defmodule Remote do\n  def answer() do\n    10\n  end\nend\n",
+    )
+    .unwrap();
+    git(&remote_repo, &["commit", "-am", "rewritten"]);
+    git(&remote_repo, &["tag", "-f", "v1"]);
+    fs::remove_dir_all(project_root.join(".tonic/deps")).unwrap();
+
+    let sync = run_tonic(&project_root, &["deps", "sync"]);
+    let stderr = String::from_utf8_lossy(&sync.stderr);
+    assert_eq!(sync.status.code(), Some(1), "stderr: {stderr}");
+    assert!(
+        stderr.contains("content hash mismatch for git dependency 'remote_dep'"),
+        "stderr: {stderr}"
+    );
+    assert!(stderr.contains("force-pushed"), "stderr: {stderr}");
+    assert_eq!(
+        fs::read_to_string(project_root.join("tonic.lock")).unwrap(),
+        lockfile,
+        "a failed sync must not rewrite tonic.lock"
+    );
+
+    // `deps update` accepts the new contents and records their hash.
+    let update = run_tonic(&project_root, &["deps", "update", "remote_dep"]);
+    let stdout = String::from_utf8_lossy(&update.stdout);
+    assert_eq!(
+        update.status.code(),
+        Some(0),
+        "stderr: {}",
+        String::from_utf8_lossy(&update.stderr)
+    );
+    assert!(
+        stdout.contains("Updating remote_dep v1 (new contents)"),
+        "stdout: {stdout}"
+    );
+    assert_ne!(
+        fs::read_to_string(project_root.join("tonic.lock")).unwrap(),
+        lockfile
+    );
+    assert_eq!(
+        run_tonic(&project_root, &["deps", "sync", "--frozen"])
+            .status
+            .code(),
+        Some(0)
+    );
+}
+
+#[test]
+fn deps_sync_locked_and_offline_modes_never_change_the_lock_or_fetch() {
+    let fixture_root = common::unique_fixture_root("deps-lock-integrity-modes");
+    let (_remote_repo, project_root) = write_fixture(&fixture_root);
+
+    let missing = run_tonic(&project_root, &["deps", "sync", "--locked"]);
+    let stderr = String::from_utf8_lossy(&missing.stderr);
+    assert_eq!(missing.status.code(), Some(1), "stderr: {stderr}");
+    assert!(stderr.contains("tonic.lock is missing"), "stderr: {stderr}");
+
+    let sync = run_tonic(&project_root, &["deps", "sync"]);
+    assert_eq!(sync.status.code(), Some(0));
+    let lockfile = fs::read_to_string(project_root.join("tonic.lock")).unwrap();
+
+    let frozen = run_tonic(&project_root, &["deps", "sync", "--frozen"]);
+    assert_eq!(
+        frozen.status.code(),
+        Some(0),
+        "stderr: {}",
+        String::from_utf8_lossy(&frozen.stderr)
+    );
+
+    // Adding a dependency changes the lock, which --locked refuses.
+    let local_lib = fixture_root.join("local_lib");
+    fs::create_dir_all(&local_lib).unwrap();
+    let manifest = fs::read_to_string(project_root.join("tonic.toml")).unwrap();
+    fs::write(
+        project_root.join("tonic.toml"),
+        format!(
+            "{manifest}local_lib = {{ path = \"{}\" }}\n",
+            local_lib.display()
+        ),
+    )
+    .unwrap();
+    let locked = run_tonic(&project_root, &["deps", "lock", "--locked"]);
+    let stderr = String::from_utf8_lossy(&locked.stderr);
+    assert_eq!(locked.status.code(), Some(1), "stderr: {stderr}");
+    assert!(
+        stderr.contains("tonic.lock is out of date"),
+        "stderr: {stderr}"
+    );
+    assert_eq!(
+        fs::read_to_string(project_root.join("tonic.lock")).unwrap(),
+        lockfile
+    );
+
+    // Offline sync may update the lock but only from .tonic/deps.
+    let offline = run_tonic(&project_root, &["deps", "sync", "--offline"]);
+    assert_eq!(
+        offline.status.code(),
+        Some(0),
+        "stderr: {}",
+        String::from_utf8_lossy(&offline.stderr)
+    );
+    fs::remove_dir_all(project_root.join(".tonic/deps")).unwrap();
+    let offline = run_tonic(&project_root, &["deps", "sync", "--offline"]);
+    let stderr = String::from_utf8_lossy(&offline.stderr);
+    assert_eq!(offline.status.code(), Some(1), "stderr: {stderr}");
+    assert!(
+        stderr.contains("--offline forbids fetching it"),
+        "stderr: {stderr}"
+    );
+}
@@ -0,0 +1,188 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+mod common;
+use common::registry::FileRegistry;
+use common::run_tonic;
+
+const JSON_SOURCE: &str =
+    "defmodule Json do\n  def encode(value) do\n    Utf8.wrap(value)\n  end\nend\n";
+const UTF8_SOURCE: &str =
+    "defmodule Utf8 do\n  def wrap(value) do\n    \"<\" <> value <> \">\"\n  end\nend\n";
+
+#[test]
+fn deps_sync_resolves_registry_packages_and_run_loads_them() {
+    let fixture_root = common::unique_fixture_root("deps-registry-resolve");
+    let registry = FileRegistry::new(fixture_root.join("registry"));
+    registry.publish("json", "1.0.0", &[("utf8", "~> 0.2")], JSON_SOURCE);
+    registry.publish("json", "1.1.0", &[("utf8", "~> 0.3")], JSON_SOURCE);
+    registry.publish("json", "2.0.0", &[], JSON_SOURCE);
+    registry.publish("utf8", "0.2.0", &[], UTF8_SOURCE);
+    let utf8_checksum = registry.publish("utf8", "0.3.1", &[], UTF8_SOURCE);
+    registry.publish("utf8", "0.4.0-rc.1", &[], UTF8_SOURCE);
+
+    let project_root = write_project(
+        &fixture_root,
+        &registry,
+        "json = \"~> 1.0\"\n",
+        "defmodule Demo do\n  def run() do\n    IO.puts(Json.encode(\"hi\"))\n  end\nend\n",
+    );
+
+    let sync = run_tonic(&project_root, &["deps", "sync"]);
+    assert_eq!(
+        sync.status.code(),
+        Some(0),
+        "stderr: {}",
+        String::from_utf8_lossy(&sync.stderr)
+    );
+    assert!(String::from_utf8_lossy(&sync.stdout).contains("  - registry dependencies: 2\n"));
+
+    let lockfile: toml::Value = toml::from_str(
+        &fs::read_to_string(project_root.join("tonic.lock")).expect("lockfile should exist"),
+    )
+    .expect("lockfile should be toml");
+    assert_eq!(
+        lockfile["registry_deps"]["json"]["version"].as_str(),
+        Some("1.1.0")
+    );
+    assert_eq!(
+        lockfile["registry_deps"]["utf8"]["version"].as_str(),
+        Some("0.3.1")
+    );
+    assert_eq!(
+        lockfile["registry_deps"]["utf8"]["checksum"].as_str(),
+        Some(utf8_checksum.as_str())
+    );
+    assert_eq!(
+        lockfile["registry_deps"]["utf8"]["url"].as_str(),
+        Some(format!("{}/packages/utf8/0.3.1/download", registry.url()).as_str())
+    );
+
+    let run = run_tonic(&project_root, &["run", "."]);
+    assert_eq!(
+        run.status.code(),
+        Some(0),
+        "stderr: {}",
+        String::from_utf8_lossy(&run.stderr)
+    );
+    assert_eq!(String::from_utf8_lossy(&run.stdout), "<hi>\n");
+
+    // A newer release does not move locked versions that still fit.
+    registry.publish("json", "1.2.0", &[("utf8", "~> 0.3")], JSON_SOURCE);
+    let relock = run_tonic(&project_root, &["deps", "lock"]);
+    assert_eq!(relock.status.code(), Some(0));
+    assert!(fs::read_to_string(project_root.join("tonic.lock"))
+        .expect("lockfile should exist")
+        .contains("[registry_deps.json]\nversion = \"1.1.0\""));
+}
+
+#[test]
+fn deps_lock_explains_conflicting_registry_requirements() {
+    let fixture_root = common::unique_fixture_root("deps-registry-conflict");
+    let registry = FileRegistry::new(fixture_root.join("registry"));
+    registry.publish("json", "1.1.0", &[("utf8", "~> 0.3")], JSON_SOURCE);
+    registry.publish("utf8", "0.2.0", &[], UTF8_SOURCE);
+    registry.publish("utf8", "0.3.0", &[], UTF8_SOURCE);
+
+    let project_root = write_project(
+        &fixture_root,
+        &registry,
+        "json = \"~> 1.0\"\nutf8 = \"~> 0.2.0\"\n",
+        "defmodule Demo do\n  def run() do\n    1\n  end\nend\n",
+    );
+
+    let output = run_tonic(&project_root, &["deps", "lock"]);
+
+    assert_eq!(output.status.code(), Some(1));
+    assert_eq!(
+        String::from_utf8_lossy(&output.stderr),
+        "error: failed to generate lockfile: failed to resolve registry dependencies: no version of 'utf8' satisfies all requirements:\n  tonic.toml requires utf8 ~> 0.2.0\n  json 1.1.0 requires utf8 ~> 0.3\navailable versions: 0.2.0, 0.3.0\n"
+    );
+    assert!(!project_root.join("tonic.lock").exists());
+}
+
+#[test]
+fn deps_lock_skips_yanked_versions_unless_already_locked() {
+    let fixture_root = common::unique_fixture_root("deps-registry-yanked");
+    let registry = FileRegistry::new(fixture_root.join("registry"));
+    registry.publish("utf8", "0.3.0", &[], UTF8_SOURCE);
+    registry.publish("utf8", "0.3.1", &[], UTF8_SOURCE);
+    let project_root = write_project(
+        &fixture_root,
+        &registry,
+        "utf8 = \"^0.3\"\n",
+        "defmodule Demo do\n  def run() do\n    1\n  end\nend\n",
+    );
+    let locked_version = |project_root: &Path| {
+        let lockfile: toml::Value = toml::from_str(
+            &fs::read_to_string(project_root.join("tonic.lock")).expect("lockfile should exist"),
+        )
+        .expect("lockfile should be toml");
+        lockfile["registry_deps"]["utf8"]["version"]
+            .as_str()
+            .map(str::to_string)
+    };
+
+    assert_eq!(
+        run_tonic(&project_root, &["deps", "lock"]).status.code(),
+        Some(0)
+    );
+    assert_eq!(locked_version(&project_root).as_deref(), Some("0.3.1"));
+
+    registry.yank("utf8", "0.3.1");
+    assert_eq!(
+        run_tonic(&project_root, &["deps", "lock"]).status.code(),
+        Some(0)
+    );
+    assert_eq!(locked_version(&project_root).as_deref(), Some("0.3.1"));
+
+    fs::remove_file(project_root.join("tonic.lock")).expect("lockfile should be removable");
+    assert_eq!(
+        run_tonic(&project_root, &["deps", "lock"]).status.code(),
+        Some(0)
+    );
+    assert_eq!(locked_version(&project_root).as_deref(), Some("0.3.0"));
+}
+
+#[test]
+fn deps_sync_rejects_registry_tarballs_with_a_wrong_checksum() {
+    let fixture_root = common::unique_fixture_root("deps-registry-checksum");
+    let registry = FileRegistry::new(fixture_root.join("registry"));
+    registry.publish("utf8", "0.3.0", &[], UTF8_SOURCE);
+    fs::write(registry.tarball_path("utf8", "0.3.0"), b"tampered")
+        .expect("fixture setup should overwrite tarball");
+
+    let project_root = write_project(
+        &fixture_root,
+        &registry,
+        "utf8 = \"^0.3\"\n",
+        "defmodule Demo do\n  def run() do\n    1\n  end\nend\n",
+    );
+
+    let output = run_tonic(&project_root, &["deps", "sync"]);
+
+    assert_eq!(output.status.code(), Some(1));
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("checksum mismatch for registry dependency 'utf8'"));
+    assert!(!project_root.join(".tonic/deps/utf8-0.3.0").exists());
+}
+
+fn write_project(
+    fixture_root: &Path,
+    registry: &FileRegistry,
+    dependencies: &str,
+    main_source: &str,
+) -> PathBuf {
+    let project_root = fixture_root.join("app");
+    fs::create_dir_all(project_root.join("src")).expect("fixture setup should create src");
+    fs::write(
+        project_root.join("tonic.toml"),
+        format!(
+            "[project]\nname = \"demo\"\nentry = \"src/main.tn\"\n\n[registries]\ndefault = \"{}\"\n\n[dependencies]\n{dependencies}",
+            registry.url()
+        ),
+    )
+    .expect("fixture setup should write tonic.toml");
+    fs::write(project_root.join("src/main.tn"), main_source)
+        .expect("fixture setup should write entry source");
+    project_root
+}
@@ -0,0 +1,166 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+mod common;
+use common::registry::FileRegistry;
+use common::tonic_in;
+
+const JSON_SOURCE: &str = "defmodule Json do\n  def encode(value) do\n    value\n  end\nend\n";
+const UTF8_SOURCE: &str = "defmodule Utf8 do\n  def wrap(value) do\n    value\n  end\nend\n";
+
+fn write_project(fixture_root: &Path, registry: &FileRegistry, dependencies: &str) -> PathBuf {
+    let project_root = fixture_root.join("app");
+    fs::create_dir_all(project_root.join("src")).expect("fixture setup should create src");
+    fs::write(
+        project_root.join("tonic.toml"),
+        format!(
+            "[project]\nentry = \"src/main.tn\"\n\n[registries]\ndefault = \"{}\"\n\n[dependencies]\n{dependencies}",
+            registry.url()
+        ),
+    )
+    .expect("fixture setup should write tonic.toml");
+    fs::write(
+        project_root.join("src/main.tn"),
+        "defmodule Demo do\n  def run() do\n    1\n  end\nend\n",
+    )
+    .expect("fixture setup should write entry source");
+    project_root
+}
+
+fn locked_version(project_root: &Path, name: &str) -> String {
+    let lockfile: toml::Value = toml::from_str(
+        &fs::read_to_string(project_root.join("tonic.lock")).expect("lockfile should exist"),
+    )
+    .expect("lockfile should be toml");
+    lockfile["registry_deps"][name]["version"]
+        .as_str()
+        .expect("package should be locked")
+        .to_string()
+}
+
+#[test]
+fn deps_tree_and_why_show_the_locked_graph() {
+    let fixture_root = common::unique_fixture_root("deps-tree-why");
+    let registry = FileRegistry::new(fixture_root.join("registry"));
+    registry.publish("json", "1.0.0", &[("utf8", "~> 0.3")], JSON_SOURCE);
+    registry.publish("utf8", "0.3.0", &[], UTF8_SOURCE);
+    let project_root = write_project(
+        &fixture_root,
+        &registry,
+        "json = \"~> 1.0\"\nutf8 = \"^0.3\"\n",
+    );
+
+    let (code, _, stderr) = tonic_in(&project_root, &["deps", "tree"]);
+    assert_eq!(code, 64, "tree needs a lockfile");
+    assert!(stderr.contains("tonic.lock not found"), "stderr: {stderr}");
+
+    assert_eq!(tonic_in(&project_root, &["deps", "sync"]).0, 0);
+
+    let (code, stdout, stderr) = tonic_in(&project_root, &["deps", "tree"]);
+    assert_eq!(code, 0, "stderr: {stderr}");
+    assert_eq!(
+        stdout,
+        "app\n\
+         ├── json 1.0.0 (~> 1.0)\n\
+         │   └── utf8 0.3.0 (~> 0.3)\n\
+         └── utf8 0.3.0 (^0.3) (*)\n\
+         \n\
+         (*) listed above; its dependencies are not repeated\n"
+    );
+
+    let (code, stdout, _) = tonic_in(&project_root, &["deps", "tree", "--format", "json"]);
+    assert_eq!(code, 0);
+    let report: serde_json::Value = serde_json::from_str(&stdout).expect("tree json");
+    assert_eq!(report["name"], "app");
+    assert_eq!(report["dependencies"][0]["name"], "json");
+    assert_eq!(report["dependencies"][0]["kind"], "registry");
+    assert_eq!(
+        report["dependencies"][0]["dependencies"][0]["locked"],
+        "0.3.0"
+    );
+    assert_eq!(report["dependencies"][1]["duplicate"], true);
+
+    let (code, stdout, _) = tonic_in(&project_root, &["deps", "why", "utf8"]);
+    assert_eq!(code, 0);
+    assert_eq!(
+        stdout,
+        "utf8 0.3.0\n  app -> json ~> 1.0 -> utf8 ~> 0.3\n  app -> utf8 ^0.3\n"
+    );
+    let (code, _, stderr) = tonic_in(&project_root, &["deps", "why", "yaml"]);
+    assert_eq!(code, 1);
+    assert!(stderr.contains("'yaml' is not a dependency of app"));
+
+    // Tightening a requirement without relocking shows up as a conflict.
+    let manifest = fs::read_to_string(project_root.join("tonic.toml")).unwrap();
+    fs::write(
+        project_root.join("tonic.toml"),
+        manifest.replace("utf8 = \"^0.3\"", "utf8 = \"~> 0.2.0\""),
+    )
+    .unwrap();
+    let (code, stdout, _) = tonic_in(&project_root, &["deps", "tree"]);
+    assert_eq!(code, 0);
+    assert!(
+        stdout.contains(
+            "└── utf8 0.3.0 (~> 0.2.0) (*) [conflict: locked 0.3.0 does not satisfy ~> 0.2.0]"
+        ),
+        "stdout: {stdout}"
+    );
+}
+
+#[test]
+fn deps_outdated_and_update_move_locked_versions() {
+    let fixture_root = common::unique_fixture_root("deps-outdated-update");
+    let registry = FileRegistry::new(fixture_root.join("registry"));
+    registry.publish("json", "1.0.0", &[("utf8", "~> 0.3")], JSON_SOURCE);
+    registry.publish("utf8", "0.3.0", &[], UTF8_SOURCE);
+    let project_root = write_project(&fixture_root, &registry, "json = \"~> 1.0\"\n");
+    assert_eq!(tonic_in(&project_root, &["deps", "sync"]).0, 0);
+
+    let (code, stdout, _) = tonic_in(&project_root, &["deps", "outdated"]);
+    assert_eq!(code, 0);
+    assert_eq!(stdout, "All dependencies are up to date.\n");
+
+    registry.publish("json", "1.1.0", &[("utf8", "~> 0.3")], JSON_SOURCE);
+    registry.publish("json", "2.0.0", &[("utf8", "~> 0.3")], JSON_SOURCE);
+    registry.publish("utf8", "0.3.1", &[], UTF8_SOURCE);
+
+    let (code, stdout, stderr) = tonic_in(&project_root, &["deps", "outdated"]);
+    assert_eq!(code, 0, "stderr: {stderr}");
+    let rows = stdout
+        .lines()
+        .map(|line| line.split_whitespace().collect::<Vec<_>>())
+        .collect::<Vec<_>>();
+    assert_eq!(
+        rows[..3],
+        [
+            vec!["NAME", "SOURCE", "LOCKED", "COMPATIBLE", "LATEST"],
+            vec!["json", "registry", "1.0.0", "1.1.0", "2.0.0"],
+            vec!["utf8", "registry", "0.3.0", "0.3.1", "0.3.1"],
+        ]
+    );
+
+    // Updating one package leaves the others locked.
+    let (code, stdout, stderr) = tonic_in(&project_root, &["deps", "update", "json"]);
+    assert_eq!(code, 0, "stderr: {stderr}");
+    assert!(
+        stdout.contains("  Updating json 1.0.0 -> 1.1.0\n"),
+        "stdout: {stdout}"
+    );
+    assert_eq!(locked_version(&project_root, "json"), "1.1.0");
+    assert_eq!(locked_version(&project_root, "utf8"), "0.3.0");
+
+    let (code, stdout, _) = tonic_in(&project_root, &["deps", "update"]);
+    assert_eq!(code, 0);
+    assert!(
+        stdout.contains("  Updating utf8 0.3.0 -> 0.3.1\n"),
+        "stdout: {stdout}"
+    );
+    assert_eq!(locked_version(&project_root, "utf8"), "0.3.1");
+
+    let (code, stdout, _) = tonic_in(&project_root, &["deps", "update"]);
+    assert_eq!(code, 0);
+    assert!(stdout.contains("Dependencies are already up to date."));
+
+    let (code, _, stderr) = tonic_in(&project_root, &["deps", "update", "yaml"]);
+    assert_eq!(code, 1);
+    assert!(stderr.contains("no dependency named 'yaml' in tonic.lock"));
+}
@@ -0,0 +1,59 @@
+mod common;
+
+use std::fs;
+
+/// A package with `[[bin]]` targets gets exactly one shim per declared binary.
+#[test]
+fn install_shims_exactly_the_declared_binaries() {
+    let (dir, home) = common::isolated_tonic_home("install-declared-bins");
+
+    let project = dir.join("tools");
+    fs::create_dir_all(project.join("bin")).unwrap();
+    fs::write(
+        project.join("tonic.toml"),
+        "[package]\nname = \"tools\"\n\n[project]\n\n[[bin]]\nname = \"serve\"\npath = \"bin/serve.tn\"\n\n[[bin]]\nname = \"migrate\"\npath = \"bin/migrate.tn\"\n",
+    )
+    .unwrap();
+    for name in ["serve", "migrate"] {
+        fs::write(
+            project.join(format!("bin/{name}.tn")),
+            "defmodule Demo do\n  def run() do\n    1\n  end\nend\n",
+        )
+        .unwrap();
+    }
+    fs::write(project.join("bin/helper.sh"), "#!/bin/sh\n").unwrap();
+
+    common::tonic_cmd(&["install", project.to_str().unwrap()])
+        .env("TONIC_HOME", &home)
+        .assert()
+        .success();
+
+    let mut shims = fs::read_dir(home.join("bin"))
+        .unwrap()
+        .map(|entry| entry.unwrap().file_name().to_string_lossy().to_string())
+        .collect::<Vec<_>>();
+    shims.sort();
+    assert_eq!(shims, vec!["migrate", "serve"]);
+
+    let shim = fs::read_to_string(home.join("bin/serve")).unwrap();
+    assert!(shim.contains("exec tonic run '"), "{shim}");
+    assert!(shim.contains("' --bin 'serve' \"$@\""), "{shim}");
+
+    // A library without binaries has nothing to install.
+    let library = dir.join("textlib");
+    fs::create_dir_all(library.join("src")).unwrap();
+    fs::write(
+        library.join("tonic.toml"),
+        "[package]\nname = \"textlib\"\n\n[project]\n\n[lib]\n",
+    )
+    .unwrap();
+    common::tonic_cmd(&["install", library.to_str().unwrap()])
+        .env("TONIC_HOME", &home)
+        .assert()
+        .failure()
+        .stderr(predicates::prelude::predicate::str::contains(
+            "'textlib' is a library package with no [[bin]] targets",
+        ));
+
+    let _ = fs::remove_dir_all(&dir);
+}
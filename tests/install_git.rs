@@ -0,0 +1,173 @@
+mod common;
+
+use common::git;
+use predicates::prelude::*;
+use std::fs;
+use std::path::Path;
+
+/// Commits a `greeter` package whose `bin/greet` prints `message`.
+fn commit_greeter(work: &Path, message: &str) -> String {
+    fs::create_dir_all(work.join("bin")).unwrap();
+    fs::write(
+        work.join("tonic.toml"),
+        "[package]\nname = \"greeter\"\nversion = \"0.1.0\"\n",
+    )
+    .unwrap();
+    fs::write(
+        work.join("bin").join("greet"),
+        format!("#!/bin/sh\necho {message}\n"),
+    )
+    .unwrap();
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        fs::set_permissions(
+            work.join("bin").join("greet"),
+            fs::Permissions::from_mode(0o755),
+        )
+        .unwrap();
+    }
+    git(work, &["add", "."]);
+    git(work, &["commit", "-m", message]);
+    git(work, &["rev-parse", "HEAD"])
+}
+
+fn packages_toml(home: &Path) -> toml::Value {
+    toml::from_str(&fs::read_to_string(home.join("packages.toml")).unwrap()).unwrap()
+}
+
+#[test]
+fn install_git_pins_commits_and_upgrade_follows_the_branch() {
+    let (dir, home) = common::isolated_tonic_home("install-git");
+    let work = dir.join("work");
+    fs::create_dir_all(&work).unwrap();
+    git(&work, &["init", "-b", "main"]);
+    let first = commit_greeter(&work, "hello");
+    git(&work, &["tag", "v1"]);
+    let remote = dir.join("greeter.git");
+    git(&dir, &["clone", "--bare", "work", remote.to_str().unwrap()]);
+    let remote_url = remote.to_str().unwrap();
+
+    common::tonic_cmd(&["install", &format!("{remote_url}#main")])
+        .env("TONIC_HOME", &home)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Installed package 'greeter'"))
+        .stdout(predicate::str::contains(&first[..12]));
+
+    let shim = home.join("bin").join("greet");
+    let greeting = std::process::Command::new(&shim).output().unwrap();
+    assert_eq!(String::from_utf8_lossy(&greeting.stdout).trim(), "hello");
+    let entry = packages_toml(&home)["packages"]["greeter"].clone();
+    assert_eq!(entry["source"].as_str(), Some("git"));
+    assert_eq!(entry["branch"].as_str(), Some("main"));
+    assert_eq!(entry["commit"].as_str(), Some(first.as_str()));
+
+    // A new upstream commit is only picked up by --upgrade.
+    let second = commit_greeter(&work, "howdy");
+    git(&work, &["push", remote_url, "main"]);
+    common::tonic_cmd(&["install", "--upgrade", "greeter"])
+        .env("TONIC_HOME", &home)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(format!(
+            "Upgraded package 'greeter' {} -> {}",
+            &first[..12],
+            &second[..12]
+        )));
+    let greeting = std::process::Command::new(&shim).output().unwrap();
+    assert_eq!(String::from_utf8_lossy(&greeting.stdout).trim(), "howdy");
+    assert_eq!(
+        packages_toml(&home)["packages"]["greeter"]["commit"].as_str(),
+        Some(second.as_str())
+    );
+
+    common::tonic_cmd(&["install", "--upgrade"])
+        .env("TONIC_HOME", &home)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "Package 'greeter' is already up to date",
+        ));
+
+    // A pinned tag stays put on upgrade.
+    common::tonic_cmd(&["install", &format!("{remote_url}@v1")])
+        .env("TONIC_HOME", &home)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Updated package 'greeter'"));
+    let entry = packages_toml(&home)["packages"]["greeter"].clone();
+    assert_eq!(entry["ref"].as_str(), Some("v1"));
+    assert_eq!(entry["commit"].as_str(), Some(first.as_str()));
+    common::tonic_cmd(&["install", "--upgrade"])
+        .env("TONIC_HOME", &home)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "Skipped package 'greeter': pinned to rev v1",
+        ));
+    let greeting = std::process::Command::new(&shim).output().unwrap();
+    assert_eq!(String::from_utf8_lossy(&greeting.stdout).trim(), "hello");
+
+    let _ = fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn install_git_reports_unreachable_revisions() {
+    let (dir, home) = common::isolated_tonic_home("install-git-bad-rev");
+    let work = dir.join("work");
+    fs::create_dir_all(&work).unwrap();
+    git(&work, &["init", "-b", "main"]);
+    commit_greeter(&work, "hello");
+    let remote = dir.join("greeter.git");
+    git(&dir, &["clone", "--bare", "work", remote.to_str().unwrap()]);
+
+    common::tonic_cmd(&["install", &format!("{}@no-such-tag", remote.display())])
+        .env("TONIC_HOME", &home)
+        .assert()
+        .code(1)
+        .stderr(predicate::str::contains("failed to fetch"))
+        .stderr(predicate::str::contains("rev no-such-tag"));
+
+    assert!(!home.join("packages.toml").exists());
+    let leftovers: Vec<_> = fs::read_dir(home.join("packages"))
+        .unwrap()
+        .flatten()
+        .collect();
+    assert!(leftovers.is_empty(), "staging checkout should be removed");
+
+    let _ = fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn install_git_rejects_package_names_that_escape_the_packages_directory() {
+    let (dir, home) = common::isolated_tonic_home("install-git-traversal");
+    let victim = dir.join("victim");
+    fs::create_dir_all(&victim).unwrap();
+    fs::write(victim.join("keep.txt"), "keep").unwrap();
+    let work = dir.join("work");
+    fs::create_dir_all(work.join("bin")).unwrap();
+    git(&work, &["init", "-b", "main"]);
+    fs::write(
+        work.join("tonic.toml"),
+        "[package]\nname = \"../../victim\"\nversion = \"0.1.0\"\n",
+    )
+    .unwrap();
+    fs::write(work.join("bin").join("greet"), "#!/bin/sh\necho hi\n").unwrap();
+    git(&work, &["add", "."]);
+    git(&work, &["commit", "-m", "escape"]);
+
+    common::tonic_cmd(&["install", &format!("file://{}", work.display())])
+        .env("TONIC_HOME", &home)
+        .assert()
+        .code(1)
+        .stderr(predicate::str::contains(
+            "invalid package name '../../victim'",
+        ));
+
+    assert_eq!(fs::read_to_string(victim.join("keep.txt")).unwrap(), "keep");
+    assert!(!victim.join("tonic.toml").exists());
+    assert_eq!(fs::read_dir(home.join("packages")).unwrap().count(), 0);
+
+    let _ = fs::remove_dir_all(&dir);
+}
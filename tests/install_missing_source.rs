@@ -15,3 +15,20 @@ fn install_missing_source_path_errors() {
 
     let _ = std::fs::remove_dir_all(&dir);
 }
+
+/// A bare name is neither a path nor a git URL.
+#[test]
+fn install_bare_name_errors() {
+    let (dir, home) = common::isolated_tonic_home("install-bare-name");
+
+    common::tonic_cmd(&["install", "tonic-nonexistent-package-12345"])
+        .current_dir(&dir)
+        .env("TONIC_HOME", &home)
+        .assert()
+        .code(1)
+        .stderr(predicates::prelude::predicate::str::contains(
+            "'tonic-nonexistent-package-12345' is not a local path or git URL",
+        ));
+
+    let _ = std::fs::remove_dir_all(&dir);
+}
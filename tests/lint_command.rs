@@ -0,0 +1,121 @@
+use std::fs;
+use std::path::PathBuf;
+mod common;
+
+#[test]
+fn lint_reports_findings_with_snippets_and_fails() {
+    let fixture_root = write_lint_fixture(
+        "lint-text-findings",
+        "",
+        "defmodule Demo do\n  @moduledoc \"Demo.\"\n\n  @doc \"Checks.\"\n  def is_empty(xs) do\n    length(xs) == 0\n  end\nend\n",
+    );
+
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_tonic"))
+        .current_dir(&fixture_root)
+        .args(["lint", "."])
+        .output()
+        .expect("lint command should execute");
+
+    assert_eq!(output.status.code(), Some(1));
+
+    let stdout = String::from_utf8(output.stdout).expect("stdout should be utf8");
+    assert_eq!(
+        stdout,
+        "warning: [predicate_naming] predicate `is_empty` should be named `empty?`\n \
+         --> ./src/main.tn:5:3\n   \
+         5 |   def is_empty(xs) do\n     \
+         |   ^\n\n\
+         warning: [length_comparison] comparing `length/1` with 0 walks the whole list; match on `[]` instead\n \
+         --> ./src/main.tn:6:5\n   \
+         6 |     length(xs) == 0\n     \
+         |     ^\n\n\
+         lint: 2 warnings in 1 file\n"
+    );
+}
+
+#[test]
+fn lint_applies_manifest_config_and_renders_json_and_sarif() {
+    let fixture_root = write_lint_fixture(
+        "lint-config-formats",
+        "\n[lint.missing_docs]\nenabled = false\n",
+        "defmodule Demo do\n  def is_ready(x) do\n    x\n  end\nend\n",
+    );
+
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_tonic"))
+        .current_dir(&fixture_root)
+        .args(["lint", "src/main.tn", "--format", "json"])
+        .output()
+        .expect("lint command should execute");
+
+    assert_eq!(output.status.code(), Some(1));
+    let report: serde_json::Value =
+        serde_json::from_slice(&output.stdout).expect("lint json should parse");
+    assert_eq!(
+        report,
+        serde_json::json!({
+            "status": "warnings",
+            "checked_files": 1,
+            "diagnostics": [{
+                "rule": "predicate_naming",
+                "message": "predicate `is_ready` should be named `ready?`",
+                "path": "src/main.tn",
+                "line": 2,
+                "column": 3,
+            }],
+        })
+    );
+
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_tonic"))
+        .current_dir(&fixture_root)
+        .args(["lint", "src/main.tn", "--format", "sarif"])
+        .output()
+        .expect("lint command should execute");
+
+    let sarif: serde_json::Value =
+        serde_json::from_slice(&output.stdout).expect("sarif output should parse");
+    assert_eq!(sarif["version"], "2.1.0");
+    let result = &sarif["runs"][0]["results"][0];
+    assert_eq!(result["ruleId"], "predicate_naming");
+    assert_eq!(
+        result["locations"][0]["physicalLocation"]["region"],
+        serde_json::json!({ "startLine": 2, "startColumn": 3 })
+    );
+}
+
+#[test]
+fn lint_passes_clean_project() {
+    let fixture_root = write_lint_fixture(
+        "lint-clean",
+        "",
+        "defmodule Demo do\n  @moduledoc \"Demo.\"\n\n  @doc \"Runs.\"\n  def run() do\n    [1, 2] |> Enum.sum()\n  end\nend\n",
+    );
+
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_tonic"))
+        .current_dir(&fixture_root)
+        .args(["lint", "."])
+        .output()
+        .expect("lint command should execute");
+
+    assert!(
+        output.status.success(),
+        "expected lint to succeed, got status {:?} with stdout: {}",
+        output.status.code(),
+        String::from_utf8_lossy(&output.stdout)
+    );
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "lint: ok\n");
+}
+
+fn write_lint_fixture(test_name: &str, manifest_extra: &str, source: &str) -> PathBuf {
+    let fixture_root = common::unique_fixture_root(test_name);
+    let src_dir = fixture_root.join("src");
+
+    fs::create_dir_all(&src_dir).expect("fixture setup should create src directory");
+    fs::write(
+        fixture_root.join("tonic.toml"),
+        format!("[project]\nname = \"demo\"\nentry = \"src/main.tn\"\n{manifest_extra}"),
+    )
+    .expect("fixture setup should write tonic.toml");
+    fs::write(src_dir.join("main.tn"), source).expect("fixture setup should write source file");
+
+    fixture_root
+}
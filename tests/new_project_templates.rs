@@ -0,0 +1,113 @@
+mod common;
+
+use common::{git, tonic_in};
+use std::fs;
+
+#[test]
+fn builtin_templates_generate_projects_that_check_test_format_and_lint_cleanly() {
+    let root = common::unique_fixture_root("new-builtin-templates");
+
+    for (template, name) in [
+        ("cli", "hello_cli"),
+        ("lib", "hello_lib"),
+        ("http-server", "hello_web"),
+        ("workspace", "hello_ws"),
+    ] {
+        let (code, stdout, stderr) = tonic_in(&root, &["new", name, "--template", template]);
+        assert_eq!(code, 0, "{template} stderr: {stderr}");
+        assert!(
+            stdout.starts_with(&format!("Created {name} from the {template} template:\n")),
+            "{stdout}"
+        );
+        let project = root.join(name);
+        for file in [".gitignore", ".tonic-formatter.toml", "tonic.toml"] {
+            assert!(project.join(file).is_file(), "{template} is missing {file}");
+        }
+
+        for args in [
+            &["check", "."][..],
+            &["test", "."],
+            &["fmt", ".", "--check"],
+            &["lint", "."],
+        ] {
+            let (code, stdout, stderr) = tonic_in(&project, args);
+            assert_eq!(
+                code, 0,
+                "{template} {args:?}\nstdout: {stdout}\nstderr: {stderr}"
+            );
+        }
+    }
+
+    assert_eq!(
+        fs::read_to_string(root.join("hello_cli/src/hello_cli.tn"))
+            .unwrap()
+            .lines()
+            .next(),
+        Some("defmodule HelloCli do")
+    );
+    let (code, stdout, stderr) =
+        tonic_in(&root.join("hello_cli"), &["run", ".", "--bin", "hello_cli"]);
+    assert_eq!(code, 0, "stderr: {stderr}");
+    assert_eq!(stdout, "Hello, world!\n");
+
+    // The workspace's app depends on its library by path, so it is locked
+    // and runs straight away.
+    assert!(root.join("hello_ws/tonic.lock").is_file());
+    let (code, stdout, stderr) = tonic_in(&root.join("hello_ws"), &["run", "apps/hello_ws"]);
+    assert_eq!(code, 0, "stderr: {stderr}");
+    assert_eq!(stdout, "Hello, world!\n");
+}
+
+#[test]
+fn user_templates_load_from_directories_and_git_repositories() {
+    let root = common::unique_fixture_root("new-user-templates");
+    let template = root.join("template");
+    fs::create_dir_all(template.join("src")).unwrap();
+    fs::write(
+        template.join("tonic.toml"),
+        "[project]\nname = \"{{name}}\"\nentry = \"src/{{name}}.tn\"\n",
+    )
+    .unwrap();
+    fs::write(
+        template.join("src/{{name}}.tn"),
+        "defmodule Demo do\n  def run() do\n    \"{{module}}\"\n  end\nend\n",
+    )
+    .unwrap();
+
+    let (code, _, stderr) = tonic_in(&root, &["new", "from_dir", "--template", "template"]);
+    assert_eq!(code, 0, "stderr: {stderr}");
+    let (code, stdout, stderr) = tonic_in(&root.join("from_dir"), &["run", "."]);
+    assert_eq!(code, 0, "stderr: {stderr}");
+    assert_eq!(stdout.trim(), "\"FromDir\"");
+
+    git(&template, &["init", "-q"]);
+    git(&template, &["add", "."]);
+    git(&template, &["commit", "-q", "-m", "template"]);
+    let url = format!("file://{}", template.display());
+    let (code, _, stderr) = tonic_in(&root, &["new", "from_git", "--template", &url]);
+    assert_eq!(code, 0, "stderr: {stderr}");
+    assert!(root.join("from_git/src/from_git.tn").is_file());
+    assert!(!root.join("from_git/.git").exists());
+
+    let (code, _, stderr) = tonic_in(&root, &["new", "MyApp"]);
+    assert_eq!(code, 64);
+    assert!(
+        stderr.contains("invalid package name 'MyApp': use lowercase letters"),
+        "stderr: {stderr}"
+    );
+
+    let (code, _, stderr) = tonic_in(&root, &["new", "from_dir"]);
+    assert_eq!(code, 1);
+    assert!(
+        stderr.contains("destination 'from_dir' already exists"),
+        "stderr: {stderr}"
+    );
+
+    let (code, _, stderr) = tonic_in(&root, &["new", "empty", "--template", "from_dir/src"]);
+    assert_eq!(code, 1);
+    assert!(
+        stderr.contains("template 'from_dir/src' has no tonic.toml at its root"),
+        "stderr: {stderr}"
+    );
+    assert!(!root.join("empty").exists());
+}
@@ -70,6 +70,7 @@ fn run_with_observability_emits_bundle_with_phase_timings() {
         .current_dir(&fixture_root)
         .env("TONIC_OBS_ENABLE", "1")
         .env("TONIC_OBS_DIR", &obs_dir)
+        .env("TONIC_HOME", fixture_root.join("home"))
         .args(["run", "demo.tn"])
         .output()
         .expect("run command should execute");
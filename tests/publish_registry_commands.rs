@@ -0,0 +1,166 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+mod common;
+
+const OWNER_KEY: &str = "9d61b19deffd5a60ba844af492ec2cc44449c5697b326919703bac031cae7f60";
+const OTHER_KEY: &str = "4ccd089b28ff96da9db6c346ec114e0f5b8a319f35aba624da8cf6ed4fb8a6fb";
+const OWNER_PUBLIC_KEY: &str = "d75a980182b10ab7d54bfed3c964073a0ee172f3daa62325af021a68f707511a";
+
+#[test]
+fn publish_dry_run_lists_files_and_writes_a_reproducible_tarball() {
+    let fixture_root = common::unique_fixture_root("publish-dry-run");
+    let project_root = write_package(&fixture_root, "1.0.0");
+
+    let first = run_with_signing_key(&project_root, &["publish", "--dry-run"], None);
+    assert_eq!(
+        first.status.code(),
+        Some(0),
+        "stderr: {}",
+        String::from_utf8_lossy(&first.stderr)
+    );
+    let stdout = String::from_utf8_lossy(&first.stdout);
+    assert!(stdout.starts_with(
+        "Packaging json 1.0.0 (3 files)\n  README.md\n  src/json.tn\n  tonic.toml\nchecksum: sha256:"
+    ));
+    assert!(stdout.contains("package written to .tonic/package/json-1.0.0.tar.gz\n"));
+    assert!(stdout.ends_with(&format!(
+        "dry run: not publishing to file://{}\n",
+        fixture_root.join("registry").display()
+    )));
+    assert!(!fixture_root.join("registry/packages/json").exists());
+
+    let tarball = fs::read(project_root.join(".tonic/package/json-1.0.0.tar.gz"))
+        .expect("dry run should write the tarball");
+    let second = run_with_signing_key(&project_root, &["publish", "--dry-run"], None);
+    assert_eq!(second.status.code(), Some(0));
+    assert_eq!(
+        fs::read(project_root.join(".tonic/package/json-1.0.0.tar.gz")).expect("tarball"),
+        tarball
+    );
+}
+
+#[test]
+fn publish_yank_and_owner_manage_a_file_registry() {
+    let fixture_root = common::unique_fixture_root("publish-file-registry");
+    let project_root = write_package(&fixture_root, "1.0.0");
+    let key_path = fixture_root.join("owner.key");
+    fs::write(&key_path, format!("{OWNER_KEY}\n")).expect("fixture setup should write key");
+    let key_arg = key_path.to_string_lossy().to_string();
+
+    let publish = run_with_signing_key(&project_root, &["publish", "--key", &key_arg], None);
+    assert_eq!(
+        publish.status.code(),
+        Some(0),
+        "stderr: {}",
+        String::from_utf8_lossy(&publish.stderr)
+    );
+    let stdout = String::from_utf8_lossy(&publish.stdout);
+    assert!(stdout.contains(&format!("signing key: {OWNER_PUBLIC_KEY}\n")));
+    assert!(stdout.contains("Published json 1.0.0 to file://"));
+
+    let metadata: serde_json::Value = serde_json::from_str(
+        &fs::read_to_string(fixture_root.join("registry/packages/json/1.0.0/index.json"))
+            .expect("version metadata should exist"),
+    )
+    .expect("metadata should be json");
+    assert_eq!(metadata["public_key"], OWNER_PUBLIC_KEY);
+    assert_eq!(metadata["signature"].as_str().map(str::len), Some(128));
+    assert_eq!(metadata["dependencies"]["utf8"], "~> 0.3");
+
+    let again = run_with_signing_key(&project_root, &["publish"], Some(OWNER_KEY));
+    assert_eq!(again.status.code(), Some(1));
+    assert!(String::from_utf8_lossy(&again.stderr)
+        .contains("error: failed to publish: json 1.0.0 is already published to file://"));
+
+    let intruder = write_package(&fixture_root.join("intruder"), "1.0.1");
+    let registry_url = format!("file://{}", fixture_root.join("registry").display());
+    let rejected = run_with_signing_key(
+        &intruder,
+        &["publish", "--registry", &registry_url],
+        Some(OTHER_KEY),
+    );
+    assert_eq!(rejected.status.code(), Some(1));
+    assert!(String::from_utf8_lossy(&rejected.stderr).contains("is not an owner of 'json'"));
+
+    let yank = run_with_signing_key(&project_root, &["yank", "json", "1.0.0"], Some(OWNER_KEY));
+    assert_eq!(yank.status.code(), Some(0));
+    assert!(String::from_utf8_lossy(&yank.stdout).starts_with("Yanked json 1.0.0 on file://"));
+    let index = fs::read_to_string(fixture_root.join("registry/packages/json/index.json"))
+        .expect("index should exist");
+    assert!(index.contains("\"yanked\": true"));
+
+    let add = run_with_signing_key(
+        &project_root,
+        &["owner", "add", "json", "alice-public-key"],
+        Some(OWNER_KEY),
+    );
+    assert_eq!(add.status.code(), Some(0));
+    let list = run_with_signing_key(&project_root, &["owner", "list", "json"], None);
+    assert_eq!(
+        String::from_utf8_lossy(&list.stdout),
+        format!("{OWNER_PUBLIC_KEY}\nalice-public-key\n")
+    );
+}
+
+#[test]
+fn publish_rejects_path_dependencies() {
+    let fixture_root = common::unique_fixture_root("publish-path-dependency");
+    let project_root = write_package(&fixture_root, "1.0.0");
+    fs::create_dir_all(project_root.join("vendor/local")).expect("fixture setup should mkdir");
+    fs::write(
+        project_root.join("tonic.toml"),
+        "[project]\nentry = \"src/json.tn\"\n\n[package]\nname = \"json\"\nversion = \"1.0.0\"\ndescription = \"JSON\"\n\n[dependencies]\nlocal = { path = \"vendor/local\" }\n",
+    )
+    .expect("fixture setup should write tonic.toml");
+
+    let output = run_with_signing_key(&project_root, &["publish", "--dry-run"], None);
+
+    assert_eq!(output.status.code(), Some(1));
+    assert_eq!(
+        String::from_utf8_lossy(&output.stderr),
+        "error: cannot publish 'json' with path dependency 'local'; published packages may only depend on registry packages\n"
+    );
+}
+
+fn write_package(fixture_root: &Path, version: &str) -> PathBuf {
+    let project_root = fixture_root.join("json");
+    fs::create_dir_all(project_root.join("src")).expect("fixture setup should create src");
+    fs::create_dir_all(project_root.join("test")).expect("fixture setup should create test");
+    fs::write(
+        project_root.join("tonic.toml"),
+        format!(
+            "[project]\nentry = \"src/json.tn\"\n\n[package]\nname = \"json\"\nversion = \"{version}\"\ndescription = \"JSON encoding\"\nexclude = [\"test\"]\n\n[registries]\ndefault = \"file://{}\"\n\n[dependencies]\nutf8 = \"~> 0.3\"\n",
+            fixture_root.join("registry").display()
+        ),
+    )
+    .expect("fixture setup should write tonic.toml");
+    fs::write(project_root.join("README.md"), "# json\n")
+        .expect("fixture setup should write readme");
+    fs::write(
+        project_root.join("src/json.tn"),
+        "defmodule Json do\n  def encode(value) do\n    value\n  end\nend\n",
+    )
+    .expect("fixture setup should write source");
+    fs::write(
+        project_root.join("test/json_test.tn"),
+        "defmodule JsonTest do\nend\n",
+    )
+    .expect("fixture setup should write test");
+    project_root
+}
+
+/// Run tonic with only `signing_key` (if any) as the publishing credential.
+fn run_with_signing_key(
+    project_root: &Path,
+    args: &[&str],
+    signing_key: Option<&str>,
+) -> std::process::Output {
+    let mut command = common::tonic_command(project_root, args);
+    command
+        .env_remove("TONIC_SIGNING_KEY")
+        .env_remove("TONIC_REGISTRY_TOKEN");
+    if let Some(key) = signing_key {
+        command.env("TONIC_SIGNING_KEY", key);
+    }
+    command.output().expect("tonic should execute")
+}
@@ -0,0 +1,114 @@
+use std::path::Path;
+mod common;
+use common::{tonic_in, write_file};
+
+/// A `[lib]` in `src/` shared by two binaries, one with its own main module.
+fn write_tools_project(root: &Path) {
+    write_file(
+        root,
+        "tonic.toml",
+        "[project]\nname = \"tools\"\n\n[lib]\npath = \"src\"\n\n[[bin]]\nname = \"serve\"\npath = \"bin/serve.tn\"\nmain_module = \"Serve\"\n\n[[bin]]\nname = \"migrate\"\npath = \"bin/migrate.tn\"\n",
+    );
+    write_file(
+        root,
+        "src/greeting.tn",
+        "defmodule Greeting do\n  def hello(name) do\n    \"hello \" <> name\n  end\nend\n",
+    );
+    write_file(
+        root,
+        "bin/serve.tn",
+        "defmodule Serve do\n  def run() do\n    Greeting.hello(\"serve\")\n  end\nend\n",
+    );
+    write_file(
+        root,
+        "bin/migrate.tn",
+        "defmodule Demo do\n  def run() do\n    Greeting.hello(\"migrate\")\n  end\nend\n",
+    );
+}
+
+#[test]
+fn run_selects_a_declared_binary_with_bin() {
+    let root = common::unique_fixture_root("run-bin-targets");
+    write_tools_project(&root);
+    let project = root.to_str().unwrap();
+
+    let (code, stdout, stderr) = tonic_in(&root, &["run", project, "--bin", "serve"]);
+    assert_eq!(code, 0, "stderr: {stderr}");
+    assert_eq!(stdout.trim(), "\"hello serve\"");
+
+    let (code, stdout, stderr) = tonic_in(&root, &["run", "--bin", "migrate", project]);
+    assert_eq!(code, 0, "stderr: {stderr}");
+    assert_eq!(stdout.trim(), "\"hello migrate\"");
+
+    let (code, _, stderr) = tonic_in(&root, &["run", project]);
+    assert_eq!(code, 1);
+    assert!(
+        stderr.contains("declares several binaries (serve, migrate); select one with --bin <name>"),
+        "stderr: {stderr}"
+    );
+
+    let (code, _, stderr) = tonic_in(&root, &["run", project, "--bin", "deploy"]);
+    assert_eq!(code, 1);
+    assert!(
+        stderr.contains("no binary named 'deploy' in tonic.toml (binaries: serve, migrate)"),
+        "stderr: {stderr}"
+    );
+
+    // The library alone still checks; it just has nothing to run.
+    write_file(
+        &root,
+        "tonic.toml",
+        "[project]\nname = \"tools\"\n\n[lib]\n",
+    );
+    let (code, stdout, stderr) = tonic_in(&root, &["check", project]);
+    assert_eq!(code, 0, "stderr: {stderr}");
+    assert_eq!(stdout, "check: ok\n");
+    let (code, _, stderr) = tonic_in(&root, &["run", project]);
+    assert_eq!(code, 1);
+    assert!(
+        stderr.contains("'tools' is a library package"),
+        "stderr: {stderr}"
+    );
+}
+
+#[test]
+fn compile_all_bins_writes_one_executable_per_binary() {
+    let root = common::unique_fixture_root("compile-all-bins");
+    write_tools_project(&root);
+
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_tonic"))
+        .current_dir(&root)
+        .args(["compile", ".", "--all-bins"])
+        .output()
+        .expect("tonic should execute");
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert_eq!(output.status.code(), Some(0), "stderr: {stderr}");
+
+    for (bin, expected) in [("serve", "hello serve"), ("migrate", "hello migrate")] {
+        let exe = root.join(".tonic/build").join(bin);
+        assert!(exe.exists(), "missing {}", exe.display());
+        let output = std::process::Command::new(&exe)
+            .output()
+            .expect("compiled binary should execute");
+        assert_eq!(
+            String::from_utf8_lossy(&output.stdout).trim(),
+            format!("\"{expected}\"")
+        );
+    }
+
+    let (code, _, stderr) = tonic_in(
+        &root,
+        &[
+            "compile",
+            root.to_str().unwrap(),
+            "--all-bins",
+            "--out",
+            "app",
+        ],
+    );
+    assert_eq!(code, 64);
+    assert!(
+        stderr.contains("--all-bins cannot be combined with --out"),
+        "stderr: {stderr}"
+    );
+}
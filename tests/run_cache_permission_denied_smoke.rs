@@ -65,6 +65,7 @@ fn run_with_cache_trace(fixture_root: &Path) -> std::process::Output {
     std::process::Command::new(env!("CARGO_BIN_EXE_tonic"))
         .current_dir(fixture_root)
         .env("TONIC_DEBUG_CACHE", "1")
+        .env("TONIC_SHARED_CACHE", "0")
         .args(["run", "."])
         .output()
         .expect("run command should execute")
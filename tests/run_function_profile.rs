@@ -0,0 +1,75 @@
+use serde_json::Value;
+use std::fs;
+mod common;
+
+const PROFILED_SOURCE: &str = "defmodule Demo do\n  def fib(n) do\n    if n < 2 do\n      n\n    else\n      fib(n - 1) + fib(n - 2)\n    end\n  end\n\n  def labels(items) do\n    Enum.map(items, fn x -> \"item \" <> Integer.to_string(x) end)\n  end\n\n  def run() do\n    IO.puts(fib(10))\n    IO.puts(List.first(labels([1, 2, 3])))\n  end\nend\n";
+
+#[test]
+fn run_profile_writes_table_collapsed_stacks_and_chrome_trace() {
+    let fixture_root = common::unique_fixture_root("run-function-profile");
+    fs::write(fixture_root.join("slow.tn"), PROFILED_SOURCE)
+        .expect("fixture setup should write source");
+
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_tonic"))
+        .current_dir(&fixture_root)
+        .args(["run", "slow.tn", "--profile", "--profile-dir", "out"])
+        .output()
+        .expect("run command should execute");
+
+    assert_eq!(output.status.code(), Some(0));
+    assert_eq!(
+        String::from_utf8(output.stdout).expect("stdout should be utf8"),
+        "55\nitem 1\n"
+    );
+    let stderr = String::from_utf8(output.stderr).expect("stderr should be utf8");
+    assert!(stderr.starts_with("profile: "));
+    assert!(stderr.contains("Function "));
+    assert!(stderr.contains("calls     inclusive     exclusive   excl%    allocs\n"));
+    let fib_row = stderr
+        .lines()
+        .find(|line| line.starts_with("Demo.fib "))
+        .expect("table should list Demo.fib");
+    assert_eq!(fib_row.split_whitespace().nth(1), Some("177"));
+    let closure_row = stderr
+        .lines()
+        .find(|line| line.starts_with("fn "))
+        .expect("table should list closures");
+    assert_eq!(closure_row.split_whitespace().last(), Some("6"));
+    assert!(stderr.contains("profile written to out/slow.folded and out/slow.trace.json"));
+
+    let collapsed =
+        fs::read_to_string(fixture_root.join("out/slow.folded")).expect("collapsed stacks");
+    assert!(collapsed
+        .lines()
+        .any(|line| line.starts_with("Demo.run;Demo.labels;Enum.map;")));
+    assert!(collapsed.lines().all(|line| line
+        .rsplit_once(' ')
+        .is_some_and(|(_, weight)| weight.parse::<u64>().is_ok())));
+
+    let trace: Value = serde_json::from_str(
+        &fs::read_to_string(fixture_root.join("out/slow.trace.json")).expect("chrome trace"),
+    )
+    .expect("trace should be json");
+    let events = trace["traceEvents"].as_array().expect("trace events");
+    assert_eq!(events[0]["name"], "Demo.run");
+    assert_eq!(events[0]["ph"], "X");
+    assert_eq!(
+        events
+            .iter()
+            .filter(|event| event["name"] == "Demo.fib")
+            .count(),
+        177
+    );
+}
+
+#[test]
+fn run_profile_top_rejects_non_positive_values() {
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_tonic"))
+        .args(["run", "slow.tn", "--profile-top", "0"])
+        .output()
+        .expect("run command should execute");
+
+    assert_eq!(output.status.code(), Some(64));
+    let stderr = String::from_utf8(output.stderr).expect("stderr should be utf8");
+    assert!(stderr.contains("error: invalid --profile-top '0' (expected a positive integer)"));
+}
@@ -0,0 +1,99 @@
+use std::path::Path;
+mod common;
+use common::{tonic_in, write_file};
+
+/// An app with a `tasks/` task, a `[tasks]` entry and a path dependency
+/// that ships a task of its own.
+fn write_project(root: &Path) {
+    write_file(
+        root,
+        "app/tonic.toml",
+        "[project]\nname = \"app\"\nentry = \"src/main.tn\"\n\n[tasks]\nrelease = \"scripts/release.tn\"\n\n[dependencies]\ngen = { path = \"../gen\" }\n",
+    );
+    write_file(
+        root,
+        "app/src/main.tn",
+        "defmodule Demo do\n  def run() do\n    Greeting.hello(\"app\")\n  end\nend\n",
+    );
+    write_file(
+        root,
+        "app/src/greeting.tn",
+        "defmodule Greeting do\n  def hello(name) do\n    \"hello \" <> name\n  end\nend\n",
+    );
+    write_file(
+        root,
+        "app/tasks/db/seed.tn",
+        "defmodule Tasks.Db.Seed do\n  @shortdoc \"Seeds the database\"\n\n  def run(argv) do\n    spec = CLI.spec([name: \"db.seed\", flags: [count: [type: :integer, default: 1]]])\n\n    case CLI.parse(spec, argv) do\n      {:ok, parsed} -> IO.puts(Greeting.hello(\"seed x\" <> Integer.to_string(parsed[:flags][:count])))\n      {:error, message} -> {:error, message}\n      _ -> :ok\n    end\n  end\nend\n",
+    );
+    write_file(
+        root,
+        "app/scripts/release.tn",
+        "defmodule Release do\n  def run(_argv) do\n    {:error, \"no version tag\"}\n  end\nend\n",
+    );
+    write_file(
+        root,
+        "gen/tonic.toml",
+        "[project]\nname = \"gen\"\n\n[lib]\n",
+    );
+    write_file(
+        root,
+        "gen/src/gen.tn",
+        "defmodule Gen do\n  def banner() do\n    \"generated\"\n  end\nend\n",
+    );
+    write_file(
+        root,
+        "gen/tasks/gen_schema.tn",
+        "defmodule Tasks.GenSchema do\n  @shortdoc \"Generates schema modules\"\n\n  def run(argv) do\n    IO.puts(Gen.banner() <> \" \" <> Enum.join(argv, \",\"))\n  end\nend\n",
+    );
+}
+
+#[test]
+fn task_list_shows_project_and_dependency_tasks() {
+    let root = common::unique_fixture_root("task-list");
+    write_project(&root);
+    let app = root.join("app");
+    assert_eq!(tonic_in(&app, &["deps", "lock"]).0, 0);
+
+    let (code, stdout, stderr) = tonic_in(&app, &["task", "--list"]);
+    assert_eq!(code, 0, "stderr: {stderr}");
+    assert_eq!(
+        stdout,
+        "tonic task db.seed     # Seeds the database\n\
+         tonic task gen_schema  # Generates schema modules (from gen)\n\
+         tonic task release\n"
+    );
+}
+
+#[test]
+fn task_runs_with_its_arguments() {
+    let root = common::unique_fixture_root("task-run");
+    write_project(&root);
+    let app = root.join("app");
+    assert_eq!(tonic_in(&app, &["deps", "lock"]).0, 0);
+
+    let (code, stdout, stderr) = tonic_in(&app, &["task", "db.seed", "--count", "3"]);
+    assert_eq!(code, 0, "stderr: {stderr}");
+    assert_eq!(stdout, "hello seed x3\n");
+
+    let (code, stdout, stderr) = tonic_in(&app, &["task", "gen_schema", "users", "posts"]);
+    assert_eq!(code, 0, "stderr: {stderr}");
+    assert_eq!(stdout, "generated users,posts\n");
+
+    let (code, _, stderr) = tonic_in(&app, &["task", "db.seed", "--count", "many"]);
+    assert_eq!(code, 1);
+    assert!(
+        stderr.contains("task 'db.seed' failed: \"flag --count expects an integer"),
+        "stderr: {stderr}"
+    );
+
+    let (code, _, stderr) = tonic_in(&app, &["task", "release"]);
+    assert_eq!(code, 1);
+    assert!(
+        stderr.contains("task 'release' failed: \"no version tag\""),
+        "stderr: {stderr}"
+    );
+
+    let (code, _, stderr) = tonic_in(&app, &["task", "deploy"]);
+    assert_eq!(code, 64);
+    assert!(stderr.contains("unknown task 'deploy'"), "stderr: {stderr}");
+}
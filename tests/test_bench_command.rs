@@ -0,0 +1,111 @@
+use serde_json::Value;
+use std::fs;
+use std::path::{Path, PathBuf};
+mod common;
+
+const LIST_BENCH: &str = "defmodule ListBench do\n  def bench_append() do\n    [1, 2, 3] ++ Bench.black_box([4, 5, 6])\n  end\n\n  def bench_sum() do\n    Enum.reduce([1, 2, 3, 4, 5], 0, fn x, acc -> x + acc end)\n  end\n\n  def helper() do\n    IO.puts(\"not a benchmark\")\n  end\nend\n";
+
+#[test]
+fn bench_discovers_bench_files_and_saves_a_baseline() {
+    let fixture_root = write_bench_file("bench-baseline", LIST_BENCH);
+    fs::write(
+        fixture_root.join("notes.tn"),
+        "defmodule Notes do\n  def bench_ignored() do\n    1\n  end\nend\n",
+    )
+    .expect("fixture setup should write non-bench file");
+
+    let output = run_bench(&fixture_root, &[".", "--save-baseline", "out/base.json"]);
+
+    assert_eq!(output.status.code(), Some(0));
+    let stdout = String::from_utf8(output.stdout).expect("stdout should be utf8");
+    assert!(stdout.contains("backend: interpreter\nwarmup: 0 s\ntime: 0.05 s\n"));
+    assert!(stdout.contains("Benchmarking ListBench.bench_append ...\n"));
+    assert!(!stdout.contains("bench_ignored"));
+    assert!(stdout.contains("ips        average    deviation         median         99th %"));
+    assert!(stdout.contains("Comparison:\n"));
+    assert!(stdout.contains("x slower +"));
+    assert!(stdout.contains("baseline written to out/base.json"));
+
+    let baseline: Value = serde_json::from_str(
+        &fs::read_to_string(fixture_root.join("out/base.json")).expect("baseline should exist"),
+    )
+    .expect("baseline should be json");
+    assert_eq!(baseline["backend"], "interpreter");
+    let ids = baseline["benchmarks"]
+        .as_array()
+        .expect("benchmarks should be a list")
+        .iter()
+        .map(|entry| {
+            assert!(entry["mean_ns"].as_f64().is_some_and(|mean| mean > 0.0));
+            assert!(entry["samples"]
+                .as_u64()
+                .is_some_and(|samples| samples >= 1));
+            entry["id"].as_str().unwrap_or_default().to_string()
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(ids, ["ListBench.bench_append", "ListBench.bench_sum"]);
+}
+
+#[test]
+fn bench_fails_when_a_benchmark_regresses_past_the_threshold() {
+    let fixture_root = write_bench_file("bench-regression", LIST_BENCH);
+    fs::write(
+        fixture_root.join("base.json"),
+        "{\"backend\": \"interpreter\", \"benchmarks\": [{\"id\": \"ListBench.bench_sum\", \"mean_ns\": 1.0}]}\n",
+    )
+    .expect("fixture setup should write baseline");
+
+    let output = run_bench(
+        &fixture_root,
+        &[
+            "list_bench.tn",
+            "--baseline",
+            "base.json",
+            "--max-regression",
+            "10",
+        ],
+    );
+
+    assert_eq!(output.status.code(), Some(1));
+    let stdout = String::from_utf8(output.stdout).expect("stdout should be utf8");
+    assert!(stdout.contains("Baseline (base.json):\n"));
+    assert!(stdout.contains("% (regression)\n"));
+    assert!(stdout.contains("  new\n"));
+    let stderr = String::from_utf8(output.stderr).expect("stderr should be utf8");
+    assert!(stderr
+        .contains("error: ListBench.bench_sum regressed by more than 10% against the baseline"));
+}
+
+#[test]
+fn bench_native_backend_reports_errors_per_benchmark() {
+    let source = "defmodule BoomBench do\n  def bench_ok() do\n    Bench.black_box(20) + 22\n  end\n\n  def bench_boom() do\n    raise(\"boom\")\n  end\nend\n";
+    let fixture_root = write_bench_file("bench-native", source);
+
+    let output = run_bench(&fixture_root, &["list_bench.tn", "--backend", "native"]);
+
+    assert_eq!(output.status.code(), Some(1));
+    let stdout = String::from_utf8(output.stdout).expect("stdout should be utf8");
+    assert!(stdout.contains("backend: native\n"));
+    assert!(stdout.contains("\nBoomBench.bench_ok "));
+    assert!(stdout.contains("Errors:\nBoomBench.bench_boom (list_bench.tn)\n  boom\n"));
+    let stderr = String::from_utf8(output.stderr).expect("stderr should be utf8");
+    assert!(stderr.contains("error: 1 benchmark failed"));
+}
+
+fn run_bench(fixture_root: &Path, args: &[&str]) -> std::process::Output {
+    std::process::Command::new(env!("CARGO_BIN_EXE_tonic"))
+        .current_dir(fixture_root)
+        .env("NO_COLOR", "1")
+        .arg("bench")
+        .args(args)
+        .args(["--warmup", "0", "--time", "0.05"])
+        .output()
+        .expect("bench command should execute")
+}
+
+fn write_bench_file(test_name: &str, source: &str) -> PathBuf {
+    let fixture_root = common::unique_fixture_root(test_name);
+    fs::write(fixture_root.join("list_bench.tn"), source)
+        .expect("fixture setup should write bench file");
+    fixture_root
+}
@@ -0,0 +1,96 @@
+use std::fs;
+mod common;
+
+const MATH_TEST: &str = "defmodule MathTest do\n  @moduletag area: :math\n\n  setup_all do\n    {:ok, %{base: 10}}\n  end\n\n  setup ctx do\n    %{doubled: ctx[:base] * 2}\n  end\n\n  describe \"add\" do\n    setup do\n      ok(%{step: 1})\n    end\n\n    test \"uses the context\", ctx do\n      Assert.assert_equal(ctx[:doubled] + ctx[:step], 21)\n    end\n\n    @tag :slow\n    test \"is slow\" do\n      :ok\n    end\n  end\n\n  @tag flaky: true\n  test \"fails\", ctx do\n    Assert.assert_equal(ctx[:test], \"passes\")\n  end\nend\n";
+
+#[test]
+fn test_runs_blocks_with_setup_context_and_readable_ids() {
+    let fixture_root = common::write_test_file("test-blocks-run", "math_test.tn", MATH_TEST);
+
+    let output = common::run_tonic_test(&fixture_root, &["math_test.tn"]);
+
+    assert_eq!(output.status.code(), Some(1));
+    let stdout = String::from_utf8(output.stdout).expect("stdout should be utf8");
+    assert!(stdout.contains("test MathTest: add uses the context ... ok"));
+    assert!(stdout.contains("test MathTest: add is slow ... ok"));
+    assert!(stdout.contains("test MathTest: fails ... FAILED"));
+    assert!(stdout.contains("left:  \"fails\""));
+    assert!(stdout.contains("2 passed; 1 failed; 3 total"));
+}
+
+#[test]
+fn test_only_and_exclude_select_tests_by_tag() {
+    let fixture_root = common::write_test_file("test-blocks-tags", "math_test.tn", MATH_TEST);
+
+    let list = |args: &[&str]| {
+        let output =
+            common::run_tonic_test(&fixture_root, &[&["math_test.tn", "--list"], args].concat());
+        assert!(output.status.success());
+        String::from_utf8(output.stdout).expect("stdout should be utf8")
+    };
+
+    assert_eq!(list(&["--only", "slow"]), "MathTest: add is slow\n");
+    assert_eq!(
+        list(&["--only", "area:math", "--exclude", "slow"]),
+        "MathTest: add uses the context\nMathTest: fails\n"
+    );
+    assert_eq!(list(&["--only", "area:io"]), "");
+}
+
+#[test]
+fn test_failed_reruns_only_tests_that_failed_last_time() {
+    let fixture_root = common::write_test_file("test-blocks-failed", "math_test.tn", MATH_TEST);
+
+    let run =
+        |args: &[&str]| common::run_tonic_test(&fixture_root, &[&["math_test.tn"], args].concat());
+
+    assert_eq!(run(&[]).status.code(), Some(1));
+    let manifest = fs::read_to_string(fixture_root.join(".tonic/test/failures.json"))
+        .expect("failure manifest should be written");
+    assert_eq!(manifest, "{\"failed\":[\"MathTest: fails\"]}\n");
+
+    let listed = run(&["--failed", "--list"]);
+    assert_eq!(
+        String::from_utf8(listed.stdout).expect("stdout should be utf8"),
+        "MathTest: fails\n"
+    );
+
+    fs::write(
+        fixture_root.join("math_test.tn"),
+        MATH_TEST.replace("\"passes\")", "\"fails\")"),
+    )
+    .expect("fixture update should write test file");
+    assert!(run(&["--failed"]).status.success());
+    let manifest = fs::read_to_string(fixture_root.join(".tonic/test/failures.json"))
+        .expect("failure manifest should be kept");
+    assert_eq!(manifest, "{\"failed\":[]}\n");
+}
+
+#[test]
+fn test_reports_setup_that_returns_an_unexpected_value() {
+    let fixture_root = common::write_test_file(
+        "test-blocks-bad-setup",
+        "bad_test.tn",
+        "defmodule BadTest do\n  setup do\n    42\n  end\n\n  test \"never runs\" do\n    :ok\n  end\nend\n",
+    );
+
+    let output = common::run_tonic_test(&fixture_root, &["bad_test.tn"]);
+
+    assert_eq!(output.status.code(), Some(1));
+    let stdout = String::from_utf8(output.stdout).expect("stdout should be utf8");
+    assert!(stdout.contains("setup failed: setup must return :ok, a map or ok(map), got 42"));
+}
+
+#[test]
+fn test_rejects_invalid_tag_filter() {
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_tonic"))
+        .args(["test", ".", "--only", "area:"])
+        .output()
+        .expect("test command should execute");
+
+    assert_eq!(output.status.code(), Some(64));
+    assert_eq!(
+        String::from_utf8(output.stderr).expect("stderr should be utf8"),
+        "error: invalid tag filter 'area:' (expected 'tag' or 'tag:value')\nusage: tonic test <path> --only <tag[:value]>\n"
+    );
+}
@@ -0,0 +1,142 @@
+use std::fs;
+use std::path::PathBuf;
+mod common;
+
+const MATH_TEST: &str = "defmodule Math do\n  def classify(n) when n > 0 do\n    :positive\n  end\n\n  def classify(_n) do\n    :other\n  end\n\n  def unused() do\n    1\n  end\nend\n\ndefmodule MathTest do\n  def test_positive() do\n    Math.classify(3)\n  end\nend\n";
+
+const OTHER_TEST: &str =
+    "defmodule OtherTest do\n  def test_ok() do\n    if true do\n      1\n    else\n      2\n    end\n  end\nend\n";
+
+#[test]
+fn test_cover_prints_per_module_summary_for_every_test_file() {
+    let fixture_root = write_coverage_fixture("test-cover-text-summary");
+
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_tonic"))
+        .current_dir(&fixture_root)
+        .env("NO_COLOR", "1")
+        .args(["test", "tests", "--cover"])
+        .output()
+        .expect("test command should execute");
+
+    assert!(
+        output.status.success(),
+        "expected success, stderr: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    let stdout = String::from_utf8(output.stdout).expect("stdout should be utf8");
+    let summary = stdout
+        .split_once("\n\nCoverage:\n")
+        .map(|(_, summary)| summary)
+        .expect("coverage summary should follow the test results");
+    assert_eq!(
+        summary,
+        "  tests/math_test.tn\n    \
+         Math  lines 2/6 (33.3%)  functions 1/2 (50.0%)\n    \
+         MathTest  lines 2/2 (100.0%)  functions 1/1 (100.0%)\n  \
+         tests/other_test.tn\n    \
+         OtherTest  lines 3/4 (75.0%)  functions 1/1 (100.0%)\n\
+         coverage: lines 7/12 (58.3%); functions 3/4 (75.0%)\n"
+    );
+}
+
+#[test]
+fn test_cover_writes_lcov_and_cobertura_reports() {
+    let fixture_root = write_coverage_fixture("test-cover-report-files");
+
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_tonic"))
+        .current_dir(&fixture_root)
+        .env("NO_COLOR", "1")
+        .args(["test", "tests/math_test.tn", "--cover-format", "lcov"])
+        .output()
+        .expect("test command should execute");
+
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).expect("stdout should be utf8");
+    assert!(stdout.contains("coverage report written to .tonic/coverage/lcov.info\n"));
+
+    let lcov = fs::read_to_string(fixture_root.join(".tonic/coverage/lcov.info"))
+        .expect("lcov report should be written");
+    assert_eq!(
+        lcov,
+        "TN:\nSF:tests/math_test.tn\n\
+         FN:2,Math.classify/1\nFN:10,Math.unused/0\nFN:16,MathTest.test_positive/0\n\
+         FNDA:1,Math.classify/1\nFNDA:0,Math.unused/0\nFNDA:1,MathTest.test_positive/0\n\
+         FNF:3\nFNH:2\n\
+         DA:2,1\nDA:3,1\nDA:6,0\nDA:7,0\nDA:10,0\nDA:11,0\nDA:16,1\nDA:17,1\n\
+         LF:8\nLH:4\nend_of_record\n"
+    );
+
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_tonic"))
+        .current_dir(&fixture_root)
+        .args([
+            "test",
+            "tests/other_test.tn",
+            "--cover-format",
+            "cobertura",
+            "--cover-output",
+            "reports/coverage.xml",
+        ])
+        .output()
+        .expect("test command should execute");
+
+    assert!(output.status.success());
+    let xml = fs::read_to_string(fixture_root.join("reports/coverage.xml"))
+        .expect("cobertura report should be written");
+    assert!(xml.starts_with("<?xml version=\"1.0\" ?>\n"));
+    assert!(xml.contains("lines-covered=\"3\" lines-valid=\"4\""));
+    assert!(xml.contains(
+        "<class name=\"OtherTest\" filename=\"tests/other_test.tn\" line-rate=\"0.7500\""
+    ));
+    assert!(xml.contains("<line number=\"6\" hits=\"0\"/>"));
+}
+
+#[test]
+fn test_cover_min_fails_run_below_threshold() {
+    let fixture_root = write_coverage_fixture("test-cover-min");
+
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_tonic"))
+        .current_dir(&fixture_root)
+        .args(["test", "tests", "--cover-min", "80", "--format", "json"])
+        .output()
+        .expect("test command should execute");
+
+    assert_eq!(output.status.code(), Some(1));
+
+    let stderr = String::from_utf8(output.stderr).expect("stderr should be utf8");
+    assert_eq!(
+        stderr,
+        "error: line coverage 58.3% is below the --cover-min threshold of 80%\n"
+    );
+
+    let report: serde_json::Value =
+        serde_json::from_slice(&output.stdout).expect("stdout should be json");
+    assert_eq!(report["status"], "ok");
+    assert_eq!(report["coverage"]["lines"]["covered"], 7);
+    assert_eq!(report["coverage"]["lines"]["total"], 12);
+    assert_eq!(
+        report["coverage"]["files"][0]["modules"][0]["uncovered_lines"],
+        serde_json::json!([6, 7, 10, 11])
+    );
+
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_tonic"))
+        .current_dir(&fixture_root)
+        .args(["test", "tests", "--cover-min", "50"])
+        .output()
+        .expect("test command should execute");
+
+    assert!(output.status.success());
+}
+
+fn write_coverage_fixture(test_name: &str) -> PathBuf {
+    let fixture_root = common::unique_fixture_root(test_name);
+    let tests_dir = fixture_root.join("tests");
+
+    fs::create_dir_all(&tests_dir).expect("fixture setup should create tests directory");
+    fs::write(tests_dir.join("math_test.tn"), MATH_TEST)
+        .expect("fixture setup should write math test");
+    fs::write(tests_dir.join("other_test.tn"), OTHER_TEST)
+        .expect("fixture setup should write other test");
+
+    fixture_root
+}
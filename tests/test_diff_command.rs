@@ -0,0 +1,77 @@
+use serde_json::Value;
+use std::path::Path;
+mod common;
+
+const DIFF_TEST: &str = "defmodule DiffTest do\n  test \"maps\" do\n    Assert.assert_equal(%{id: 1, name: \"ada\", tags: [:a, :b]}, %{id: 1, tags: [:a, :x], admin: true})\n  end\n\n  test \"lines\" do\n    Assert.assert_equal(\"one\\ntwo\\nthree\", \"one\\n2\\nthree\")\n  end\n\n  test \"pattern\" do\n    min = 18\n    Assert.assert(match?(%{user: %{age: ^min, roles: [:admin | _]}}, %{user: %{age: 18, roles: [:guest]}}))\n  end\nend\n";
+
+#[test]
+fn test_failures_show_structural_diffs_and_failed_sub_patterns() {
+    let fixture_root = common::write_test_file("test-diff-text", "diff_test.tn", DIFF_TEST);
+
+    let output = run_tests(&fixture_root, &[], true);
+
+    assert_eq!(output.status.code(), Some(1));
+    let stdout = String::from_utf8(output.stdout).expect("stdout should be utf8");
+    assert!(stdout.contains(
+        "  error: assert_equal failed: values are not equal\n  diff (- left, + right):\n      %{\n        :id => 1,\n    -   :name => \"ada\",\n        :tags => [\n          :a,\n    -     :b,\n    +     :x,\n        ],\n    +   :admin => true,\n      }\n"
+    ));
+    assert!(
+        stdout.contains("  diff (- left, + right):\n      one\n    - two\n    + 2\n      three\n")
+    );
+    assert!(stdout.contains(
+        "  error: assert_pattern failed: value does not match pattern\n  pattern: %{user: %{age: ^min, roles: [:admin | _]}}\n  value:   %{:user => %{:age => 18, :roles => [:guest]}}\n  failed:  :admin at value[:user][:roles][0]\n  reason:  expected :admin, got :guest\n"
+    ));
+}
+
+#[test]
+fn test_diff_lines_are_colored_unless_no_color_is_set() {
+    let fixture_root = common::write_test_file("test-diff-color", "diff_test.tn", DIFF_TEST);
+
+    let stdout = String::from_utf8(run_tests(&fixture_root, &["--filter", "maps"], false).stdout)
+        .expect("stdout should be utf8");
+
+    assert!(stdout.contains("    \x1b[31m-   :name => \"ada\",\x1b[0m\n"));
+    assert!(stdout.contains("    \x1b[32m+   :admin => true,\x1b[0m\n"));
+}
+
+#[test]
+fn test_json_output_includes_a_structured_diff() {
+    let fixture_root = common::write_test_file("test-diff-json", "diff_test.tn", DIFF_TEST);
+
+    let output = run_tests(&fixture_root, &["--format", "json"], true);
+
+    let report: Value = serde_json::from_slice(&output.stdout).expect("stdout should be json");
+    let maps = report["failures"]
+        .as_array()
+        .expect("failures should be an array")
+        .iter()
+        .find(|failure| failure["id"] == "DiffTest: maps")
+        .expect("maps failure should be reported");
+    assert_eq!(maps["diff"]["op"], "map");
+    assert_eq!(
+        maps["diff"]["entries"][1],
+        serde_json::json!({ "op": "removed", "key": ":name", "value": "\"ada\"" })
+    );
+    assert_eq!(
+        maps["diff"]["entries"][2]["diff"]["items"][2]["op"],
+        "added"
+    );
+    assert!(maps["error"]
+        .as_str()
+        .expect("error should be a string")
+        .contains("diff (- left, + right):"));
+}
+
+fn run_tests(fixture_root: &Path, args: &[&str], no_color: bool) -> std::process::Output {
+    let args = [&["diff_test.tn"], args].concat();
+    if no_color {
+        return common::run_tonic_test(fixture_root, &args);
+    }
+    std::process::Command::new(env!("CARGO_BIN_EXE_tonic"))
+        .env_remove("NO_COLOR")
+        .current_dir(fixture_root)
+        .arg("test")
+        .args(args)
+        .output()
+        .expect("test command should execute")
+}
@@ -0,0 +1,117 @@
+use std::fs;
+mod common;
+
+const MATH_TEST: &str = "defmodule Math do\n  @moduledoc \"\"\"\n  Small math helpers.\n\n      tonic> Math.add(2, 2)\n      4\n  \"\"\"\n\n  @doc \"\"\"\n  Adds two numbers.\n\n      iex> x = 1\n      iex> add(x,\n      ...>   2)\n      3\n\n      tonic> add(1, 1)\n      3\n  \"\"\"\n  def add(a, b) do\n    a + b\n  end\n\n  @doc \"\"\"\n      tonic> wrap(:a)\n      {:ok,\n       :a}\n  \"\"\"\n  def wrap(value) do\n    {:ok, value}\n  end\nend\n\ndefmodule MathTest do\n  doctest Math\n\n  def test_plain() do\n    :ok\n  end\nend\n";
+
+#[test]
+fn test_runs_doc_examples_and_reports_failures_at_their_doc_line() {
+    let fixture_root = common::write_test_file("test-doctest-run", "math_test.tn", MATH_TEST);
+
+    let output = common::run_tonic_test(&fixture_root, &["math_test.tn"]);
+
+    assert_eq!(output.status.code(), Some(1));
+
+    let stdout = String::from_utf8(output.stdout).expect("stdout should be utf8");
+    assert!(stdout.contains("test MathTest.doctest module Math (1) ... ok"));
+    assert!(stdout.contains("test MathTest.doctest Math.add/2 (1) ... ok"));
+    assert!(stdout.contains("test MathTest.doctest Math.add/2 (2) ... FAILED"));
+    assert!(stdout.contains("test MathTest.doctest Math.wrap/1 (1) ... ok"));
+    assert!(stdout.contains(
+        "doctest failed at math_test.tn:17\n  tonic> add(1, 1)\n  expected: 3\n  actual:   2"
+    ));
+    assert!(stdout.contains("4 passed; 1 failed; 5 total"));
+}
+
+#[test]
+fn test_list_includes_doctests() {
+    let fixture_root = common::write_test_file("test-doctest-list", "math_test.tn", MATH_TEST);
+
+    let output = common::run_tonic_test(&fixture_root, &["math_test.tn", "--list"]);
+
+    assert!(output.status.success());
+    assert_eq!(
+        String::from_utf8(output.stdout).expect("stdout should be utf8"),
+        "MathTest.test_plain\n\
+         MathTest.doctest module Math (1)\n\
+         MathTest.doctest Math.add/2 (1)\n\
+         MathTest.doctest Math.add/2 (2)\n\
+         MathTest.doctest Math.wrap/1 (1)\n"
+    );
+}
+
+#[test]
+fn test_reports_examples_that_do_not_compile_at_their_doc_line() {
+    let fixture_root = common::write_test_file(
+        "test-doctest-compile-error",
+        "shapes_test.tn",
+        "defmodule Shapes do\n  @doc \"\"\"\n      tonic> Shapes.missing(2)\n      4\n  \"\"\"\n  def area(side) do\n    side * side\n  end\nend\n\ndefmodule ShapesTest do\n  doctest Shapes\nend\n",
+    );
+
+    let output = common::run_tonic_test(&fixture_root, &["shapes_test.tn"]);
+
+    assert_eq!(output.status.code(), Some(1));
+    let stderr = String::from_utf8(output.stderr).expect("stderr should be utf8");
+    assert!(stderr.contains(
+        "error: ShapesTest.doctest Shapes.area/1 (1) does not compile: [E1001] undefined symbol 'Shapes.missing'"
+    ));
+    assert!(stderr.contains("--> shapes_test.tn:3:7"));
+    assert!(stderr.contains("3 |       tonic> Shapes.missing(2)"));
+}
+
+#[test]
+fn test_rejects_doctest_of_undefined_module() {
+    let fixture_root = common::write_test_file(
+        "test-doctest-undefined-module",
+        "ghost_test.tn",
+        "defmodule GhostTest do\n  doctest Ghost\nend\n",
+    );
+
+    let output = common::run_tonic_test(&fixture_root, &["ghost_test.tn"]);
+
+    assert_eq!(output.status.code(), Some(1));
+    let stderr = String::from_utf8(output.stderr).expect("stderr should be utf8");
+    assert!(stderr.contains(
+        "[E1016] doctest module 'Ghost' is not defined for GhostTest; add the module or remove doctest"
+    ));
+}
+
+#[test]
+fn test_doctests_project_modules_defined_in_src() {
+    let fixture_root = common::unique_fixture_root("test-doctest-project-module");
+    fs::create_dir_all(fixture_root.join("src")).expect("fixture setup should create src");
+    fs::create_dir_all(fixture_root.join("tests")).expect("fixture setup should create tests");
+    fs::write(
+        fixture_root.join("tonic.toml"),
+        "[project]\nname = \"demo\"\n\n[lib]\npath = \"src\"\n",
+    )
+    .expect("fixture setup should write tonic.toml");
+    fs::write(
+        fixture_root.join("src/greeter.tn"),
+        "defmodule Greeter do\n  @doc \"\"\"\n      tonic> Greeter.greet(\"Ada\")\n      \"Hello, Ada\"\n\n      tonic> Greeter.greet(Names.default())\n      \"Hello, you\"\n  \"\"\"\n  def greet(name) do\n    \"Hello, \" <> name\n  end\nend\n",
+    )
+    .expect("fixture setup should write greeter");
+    fs::write(
+        fixture_root.join("src/names.tn"),
+        "defmodule Names do\n  def default() do\n    \"world\"\n  end\nend\n",
+    )
+    .expect("fixture setup should write names");
+    fs::write(
+        fixture_root.join("tests/greeter_test.tn"),
+        "defmodule GreeterTest do\n  doctest Greeter\nend\n",
+    )
+    .expect("fixture setup should write test file");
+
+    let output = common::run_tonic_test(&fixture_root, &["tests"]);
+
+    assert_eq!(
+        output.status.code(),
+        Some(1),
+        "stderr: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let stdout = String::from_utf8(output.stdout).expect("stdout should be utf8");
+    assert!(stdout.contains("test GreeterTest.doctest Greeter.greet/1 (1) ... ok"));
+    assert!(stdout.contains(
+        "doctest failed at src/greeter.tn:6\n  tonic> Greeter.greet(Names.default())\n  expected: \"Hello, you\"\n  actual:   \"Hello, world\""
+    ), "stdout: {stdout}");
+}
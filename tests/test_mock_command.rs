@@ -0,0 +1,73 @@
+use serde_json::Value;
+use std::fs;
+use std::path::Path;
+mod common;
+
+#[test]
+fn test_stubs_replace_host_functions_until_the_test_ends() {
+    let source = "defmodule IdsTest do\n  test \"stubbed\" do\n    Mock.stub(:uuid_v4, fn _args -> \"id-1\" end)\n    Mock.stub(:random_integer, fn args -> List.first(args) end)\n    Assert.assert_equal(host_call(:uuid_v4), \"id-1\")\n    Assert.assert_equal(host_call(:random_integer, 5, 10), 5)\n  end\n\n  test \"unstubbed\" do\n    Mock.stub(:uuid_v4, fn _args -> \"id-2\" end)\n    Mock.unstub(:uuid_v4)\n    Assert.assert_not_equal(host_call(:uuid_v4), \"id-2\")\n  end\n\n  test \"real\" do\n    Assert.assert_not_equal(host_call(:uuid_v4), \"id-1\")\n  end\nend\n";
+    let fixture_root = common::write_test_file("test-mock-stub", "ids_test.tn", source);
+
+    let output = run_tests(&fixture_root, &["--jobs", "1"]);
+
+    let stdout = String::from_utf8(output.stdout).expect("stdout should be utf8");
+    assert!(output.status.success(), "stdout:\n{stdout}");
+    assert!(stdout.contains("test result: ok. 3 passed; 0 failed; 3 total"));
+}
+
+#[test]
+fn test_cassettes_record_host_calls_once_and_replay_them() {
+    let source = "defmodule DiceTest do\n  test \"roll\" do\n    Mock.cassette(\"dice\", [:random_integer])\n    IO.puts(host_call(:random_integer, 1, 1000000))\n    IO.puts(host_call(:random_integer, 1, 1000000))\n  end\nend\n";
+    let fixture_root = common::write_test_file("test-mock-cassette", "ids_test.tn", source);
+
+    let recorded = run_json(&fixture_root, &[]);
+    let cassette_path = fixture_root.join("__cassettes__/ids_test/dice.json");
+    let cassette: Value = serde_json::from_str(
+        &fs::read_to_string(&cassette_path).expect("cassette should be written"),
+    )
+    .expect("cassette should be json");
+    let calls = cassette["calls"]
+        .as_array()
+        .expect("calls should be a list");
+    assert_eq!(calls.len(), 2);
+    assert_eq!(calls[0]["function"], "random_integer");
+    assert_eq!(calls[0]["args"], serde_json::json!([1, 1000000]));
+    assert_eq!(
+        recorded,
+        format!("{}\n{}\n", calls[0]["result"], calls[1]["result"])
+    );
+
+    assert_eq!(run_json(&fixture_root, &[]), recorded);
+
+    fs::write(
+        fixture_root.join("ids_test.tn"),
+        source.replace("1, 1000000", "1, 6"),
+    )
+    .expect("fixture should update test file");
+    let output = run_tests(&fixture_root, &[]);
+    assert_eq!(output.status.code(), Some(1));
+    let stdout = String::from_utf8(output.stdout).expect("stdout should be utf8");
+    assert!(stdout.contains(
+        "cassette \"dice\" has no unused random_integer call with arguments [1, 6]; run with --record-cassettes to record it"
+    ));
+
+    assert!(run_tests(&fixture_root, &["--record-cassettes"])
+        .status
+        .success());
+    let cassette = fs::read_to_string(&cassette_path).expect("cassette should be rewritten");
+    assert!(cassette.contains("\"args\": [\n        1,\n        6\n      ]"));
+}
+
+fn run_json(fixture_root: &Path, args: &[&str]) -> String {
+    let output = run_tests(fixture_root, &[args, &["--format", "json"]].concat());
+    assert!(output.status.success());
+    let report: Value = serde_json::from_slice(&output.stdout).expect("stdout should be json");
+    report["results"][0]["stdout"]
+        .as_str()
+        .expect("stdout should be captured")
+        .to_string()
+}
+
+fn run_tests(fixture_root: &Path, args: &[&str]) -> std::process::Output {
+    common::run_tonic_test(fixture_root, &[&["ids_test.tn"], args].concat())
+}
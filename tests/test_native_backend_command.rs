@@ -0,0 +1,106 @@
+use serde_json::Value;
+use std::fs;
+use std::path::Path;
+mod common;
+
+const NATIVE_TEST: &str = "defmodule MathTest do\n  setup_all do\n    ok(%{base: 10})\n  end\n\n  describe \"add\" do\n    setup do\n      ok(%{step: 2})\n    end\n\n    test \"uses context\", context do\n      IO.puts(\"adding\")\n      Assert.assert_equal(context[:base] + context[:step], 12)\n    end\n  end\n\n  def test_lists() do\n    Assert.assert_equal(%{items: [1, 2] ++ [3], name: \"tonic\"}, %{items: [1, 2, 3], name: \"tonic\"})\n  end\n\n  def test_fails() do\n    Assert.assert_equal(1 + 1, 3)\n  end\nend\n";
+
+#[test]
+fn test_native_backend_runs_cases_in_a_compiled_harness() {
+    let fixture_root = common::write_test_file("test-native-backend", "math_test.tn", NATIVE_TEST);
+
+    let output = run_tests(&fixture_root, &["--backend", "native", "--format", "json"]);
+
+    assert_eq!(output.status.code(), Some(1));
+    let report: Value = serde_json::from_slice(&output.stdout).expect("stdout should be json");
+    assert_eq!(report["backend"], "native");
+    assert_eq!(report["passed"], 2);
+    assert_eq!(report["failed"], 1);
+    let results = report["results"]
+        .as_array()
+        .expect("results should be a list");
+    let context_case = results
+        .iter()
+        .find(|result| result["id"] == "MathTest: add uses context")
+        .expect("describe case should run");
+    assert_eq!(context_case["status"], "passed");
+    assert_eq!(context_case["stdout"], "adding\n");
+    assert_eq!(
+        report["failures"][0]["error"],
+        "assert_equal failed: values are not equal\n  left:  2\n  right: 3"
+    );
+
+    let executables = fs::read_dir(fixture_root.join(".tonic/build/test"))
+        .expect("harness build directory should exist")
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().is_none())
+        .count();
+    assert_eq!(executables, 1);
+}
+
+#[test]
+fn test_native_harnesses_build_under_the_project_root_and_cache_clean_removes_them() {
+    let fixture_root = common::unique_fixture_root("test-native-project-root");
+    fs::write(
+        fixture_root.join("tonic.toml"),
+        "[project]\nname = \"demo\"\nentry = \"src/main.tn\"\n",
+    )
+    .unwrap();
+    fs::create_dir_all(fixture_root.join("test")).unwrap();
+    fs::write(fixture_root.join("test/math_test.tn"), NATIVE_TEST).unwrap();
+
+    let output = common::run_tonic_test(
+        &fixture_root.join("test"),
+        &["math_test.tn", "--backend", "native"],
+    );
+
+    assert_eq!(output.status.code(), Some(1));
+    assert!(!fixture_root.join("test/.tonic/build").exists());
+    let build_dir = fixture_root.join(".tonic/build/test");
+    assert_eq!(fs::read_dir(&build_dir).unwrap().count(), 2);
+
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_tonic"))
+        .current_dir(&fixture_root)
+        .args(["cache", "clean", "--project"])
+        .output()
+        .expect("cache command should execute");
+
+    assert_eq!(output.status.code(), Some(0));
+    let stdout = String::from_utf8(output.stdout).expect("stdout should be utf8");
+    assert!(
+        stdout.starts_with("cache clean: removed 0 entries (0 B) and 1 native artifact"),
+        "{stdout}"
+    );
+    assert_eq!(fs::read_dir(&build_dir).unwrap().count(), 0);
+}
+
+#[test]
+fn test_both_backends_flag_cases_whose_results_differ() {
+    let source = "defmodule RaiseTest do\n  test \"same\" do\n    Assert.assert_equal(String.upcase(\"tonic\"), \"TONIC\")\n  end\n\n  test \"raises\" do\n    Assert.assert_raises(fn -> raise(\"boom\") end)\n  end\nend\n";
+    let fixture_root = common::write_test_file("test-native-both", "math_test.tn", source);
+
+    let output = run_tests(&fixture_root, &["--backend", "both"]);
+
+    assert_eq!(output.status.code(), Some(1));
+    let stdout = String::from_utf8(output.stdout).expect("stdout should be utf8");
+    assert!(stdout.contains("test RaiseTest: same ... ok"));
+    assert!(stdout.contains("test RaiseTest: raises ... FAILED"));
+    assert!(stdout.contains("  error: backend divergence: interpreter passed, native failed\n"));
+    assert!(stdout.contains("test result: FAILED. 1 passed; 1 failed; 2 total"));
+}
+
+#[test]
+fn test_coverage_requires_the_interpreter_backend() {
+    let fixture_root = common::write_test_file("test-native-cover", "math_test.tn", NATIVE_TEST);
+
+    let output = run_tests(&fixture_root, &["--backend", "native", "--cover"]);
+
+    assert_eq!(output.status.code(), Some(64));
+    let stderr = String::from_utf8(output.stderr).expect("stderr should be utf8");
+    assert!(stderr.contains("--cover requires the interpreter backend"));
+}
+
+fn run_tests(fixture_root: &Path, args: &[&str]) -> std::process::Output {
+    common::run_tonic_test(fixture_root, &[&["math_test.tn"], args].concat())
+}
@@ -0,0 +1,128 @@
+use std::fs;
+use std::path::PathBuf;
+mod common;
+
+const A_TEST: &str = "defmodule ATest do\n  def test_one() do\n    IO.puts(\"hello from one\")\n    :ok\n  end\n\n  @async true\n  def test_two() do\n    :ok\n  end\n\n  def test_three() do\n    IO.puts(\"printed by three\")\n    Assert.assert_equal(3, 4)\n  end\nend\n";
+
+const B_TEST: &str = "defmodule BTest do\n  def test_b() do\n    IO.puts(\"hello from b\")\n    :ok\n  end\n\n  @async true\n  def test_c() do\n    :ok\n  end\nend\n";
+
+const DEEP_TEST: &str = "defmodule DeepTest do\n  def depth(0) do\n    0\n  end\n\n  def depth(n) do\n    1 + depth(n - 1)\n  end\n\n  def test_one() do\n    Assert.assert_equal(depth(5000), 5000)\n  end\n\n  def test_two() do\n    Assert.assert_equal(depth(5000), 5000)\n  end\n\n  def test_three() do\n    Assert.assert_equal(depth(5000), 5000)\n  end\n\n  def test_four() do\n    Assert.assert_equal(depth(5000), 5000)\n  end\nend\n";
+
+#[test]
+fn test_jobs_reports_results_in_seed_order_for_any_job_count() {
+    let fixture_root = write_parallel_fixture("test-jobs-seed-order");
+
+    let run = |jobs: &str| {
+        let output = std::process::Command::new(env!("CARGO_BIN_EXE_tonic"))
+            .current_dir(&fixture_root)
+            .args([
+                "test", "tests", "--jobs", jobs, "--seed", "7", "--format", "json",
+            ])
+            .output()
+            .expect("test command should execute");
+        assert_eq!(output.status.code(), Some(1));
+
+        let report: serde_json::Value =
+            serde_json::from_slice(&output.stdout).expect("stdout should be json");
+        report["results"]
+            .as_array()
+            .expect("results should be an array")
+            .iter()
+            .map(|result| {
+                (
+                    result["id"].as_str().unwrap().to_string(),
+                    result["status"].as_str().unwrap().to_string(),
+                    result["stdout"].as_str().unwrap().to_string(),
+                )
+            })
+            .collect::<Vec<_>>()
+    };
+
+    let serial = run("1");
+    assert_eq!(serial.len(), 5);
+    assert!(serial.contains(&(
+        "ATest.test_three".to_string(),
+        "failed".to_string(),
+        "printed by three\n".to_string()
+    )));
+    assert!(serial.contains(&(
+        "BTest.test_b".to_string(),
+        "passed".to_string(),
+        "hello from b\n".to_string()
+    )));
+
+    for _ in 0..5 {
+        assert_eq!(run("4"), serial);
+    }
+}
+
+#[test]
+fn test_shows_captured_output_of_failed_tests_only() {
+    let fixture_root = write_parallel_fixture("test-jobs-captured-output");
+
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_tonic"))
+        .current_dir(&fixture_root)
+        .env("NO_COLOR", "1")
+        .args(["test", "tests/a_test.tn", "--jobs", "2"])
+        .output()
+        .expect("test command should execute");
+
+    assert_eq!(output.status.code(), Some(1));
+    let stdout = String::from_utf8(output.stdout).expect("stdout should be utf8");
+    assert!(stdout.contains(
+        "  1. ATest.test_three\n     assert_equal failed: values are not equal\n       left:  3\n       right: 4\n     captured stdout:\n       printed by three\n"
+    ));
+    assert!(!stdout.contains("hello from one"));
+}
+
+#[test]
+fn test_jobs_runs_deeply_recursive_tests_on_worker_threads() {
+    let fixture_root = common::unique_fixture_root("test-jobs-deep-recursion");
+    let tests_dir = fixture_root.join("tests");
+    fs::create_dir_all(&tests_dir).expect("fixture setup should create tests directory");
+    fs::write(tests_dir.join("deep_test.tn"), DEEP_TEST).expect("fixture setup should write test");
+
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_tonic"))
+        .current_dir(&fixture_root)
+        .env("NO_COLOR", "1")
+        .args(["test", "tests", "--jobs", "2"])
+        .output()
+        .expect("test command should execute");
+
+    assert_eq!(
+        output.status.code(),
+        Some(0),
+        "stderr: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let stdout = String::from_utf8(output.stdout).expect("stdout should be utf8");
+    assert!(
+        stdout.contains("test result: ok. 4 passed; 0 failed"),
+        "stdout: {stdout}"
+    );
+}
+
+#[test]
+fn test_jobs_rejects_zero() {
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_tonic"))
+        .args(["test", ".", "--jobs", "0"])
+        .output()
+        .expect("test command should execute");
+
+    assert_eq!(output.status.code(), Some(64));
+    assert_eq!(
+        String::from_utf8(output.stderr).expect("stderr should be utf8"),
+        "error: invalid job count '0' (expected a positive integer)\nusage: tonic test <path> --jobs <number>\n"
+    );
+}
+
+fn write_parallel_fixture(test_name: &str) -> PathBuf {
+    let fixture_root = common::unique_fixture_root(test_name);
+    let tests_dir = fixture_root.join("tests");
+
+    fs::create_dir_all(&tests_dir).expect("fixture setup should create tests directory");
+    fs::write(tests_dir.join("a_test.tn"), A_TEST).expect("fixture setup should write a test");
+    fs::write(tests_dir.join("b_test.tn"), B_TEST).expect("fixture setup should write b test");
+
+    fixture_root
+}
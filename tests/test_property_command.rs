@@ -0,0 +1,49 @@
+use std::path::Path;
+mod common;
+
+const PROPERTY_TEST: &str = "defmodule PropertyDemoTest do\n  test \"reverse is an involution\" do\n    check all l <- PropertyTest.list_of(PropertyTest.integer()) do\n      Assert.assert_equal(Enum.reverse(Enum.reverse(l)), l)\n    end\n  end\n\n  test \"sums stay small\" do\n    check all x <- PropertyTest.integer(), y <- PropertyTest.integer(0, 50), max_runs: 200 do\n      Assert.assert(x + y < 30)\n    end\n  end\n\n  test \"short strings\" do\n    check all {flag, s} <- PropertyTest.tuple({PropertyTest.boolean(), PropertyTest.string()}) do\n      if String.length(s) > 2 do\n        raise \"too long\"\n      end\n      flag\n    end\n  end\nend\n";
+
+#[test]
+fn test_check_all_shrinks_failures_to_minimal_counterexamples() {
+    let fixture_root =
+        common::write_test_file("test-property-shrink", "property_test.tn", PROPERTY_TEST);
+
+    let output = run_tests(&fixture_root, &["--seed", "7"]);
+
+    assert_eq!(output.status.code(), Some(1));
+    let stdout = String::from_utf8(output.stdout).expect("stdout should be utf8");
+    assert!(stdout.contains("test PropertyDemoTest: reverse is an involution ... ok"));
+    assert!(stdout.contains(
+        "  counterexample:\n    x <- 0\n    y <- 30\n  assert failed: assertion failed: expected truthy value\n  rerun with --seed 7 to reproduce\n"
+    ));
+    assert!(stdout
+        .contains("  counterexample:\n    {flag, s} <- {false, \"aaa\"}\n  raised: too long\n"));
+    assert!(stdout.contains("1 passed; 2 failed; 3 total"));
+}
+
+#[test]
+fn test_check_all_reproduces_a_run_with_the_same_seed() {
+    let fixture_root =
+        common::write_test_file("test-property-seed", "property_test.tn", PROPERTY_TEST);
+
+    let failures = |args: &[&str]| {
+        let stdout = String::from_utf8(run_tests(&fixture_root, args).stdout)
+            .expect("stdout should be utf8");
+        stdout
+            .lines()
+            .filter(|line| line.contains(" <- ") || line.contains("property failed"))
+            .map(str::to_string)
+            .collect::<Vec<_>>()
+    };
+
+    let first = failures(&["--seed", "1234"]);
+    assert!(!first.is_empty());
+    assert_eq!(failures(&["--seed", "1234"]), first);
+}
+
+fn run_tests(fixture_root: &Path, args: &[&str]) -> std::process::Output {
+    common::run_tonic_test(
+        fixture_root,
+        &[&["property_test.tn", "--jobs", "1"], args].concat(),
+    )
+}
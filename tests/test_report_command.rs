@@ -0,0 +1,69 @@
+use std::fs;
+mod common;
+
+const A_TEST: &str = "defmodule ATest do\n  def test_one() do\n    IO.puts(\"hello\")\n    :ok\n  end\n\n  def test_skip() do\n    Assert.skip(\"not ready\")\n  end\n\n  test \"fails\" do\n    Assert.assert_equal(3, 4)\n  end\nend\n";
+
+#[test]
+fn test_format_junit_prints_xml_with_failures_skips_and_output() {
+    let fixture_root = common::write_test_file("test-report-junit", "a_test.tn", A_TEST);
+
+    let output = common::run_tonic_test(&fixture_root, &["a_test.tn", "--format", "junit"]);
+
+    assert_eq!(output.status.code(), Some(1));
+    let stdout = String::from_utf8(output.stdout).expect("stdout should be utf8");
+    assert!(stdout.starts_with("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n"));
+    assert!(stdout.contains(
+        "<testsuite name=\"ATest\" tests=\"3\" failures=\"1\" errors=\"0\" skipped=\"1\""
+    ));
+    assert!(stdout.contains("<system-out>hello\n</system-out>"));
+    assert!(stdout.contains("<skipped message=\"not ready\"/>"));
+    assert!(stdout.contains(
+        "<failure message=\"assert_equal failed: values are not equal\" type=\"failure\">assert_equal failed: values are not equal\n  left:  3\n  right: 4</failure>"
+    ));
+    assert!(stdout.ends_with("</testsuites>\n"));
+}
+
+#[test]
+fn test_report_file_writes_tap_and_keeps_text_summary_on_console() {
+    let fixture_root = common::write_test_file("test-report-file-tap", "a_test.tn", A_TEST);
+
+    let output = common::run_tonic_test(
+        &fixture_root,
+        &[
+            "a_test.tn",
+            "--format",
+            "tap",
+            "--report-file",
+            "reports/tests.tap",
+        ],
+    );
+
+    assert_eq!(output.status.code(), Some(1));
+    let stdout = String::from_utf8(output.stdout).expect("stdout should be utf8");
+    assert!(stdout.contains("test ATest: fails ... FAILED"));
+    assert!(stdout.contains("1 passed; 1 failed; 1 skipped; 3 total"));
+    assert!(stdout.ends_with("test report written to reports/tests.tap\n"));
+
+    let report = fs::read_to_string(fixture_root.join("reports/tests.tap"))
+        .expect("tap report should be written");
+    assert!(report.starts_with("TAP version 13\n1..3\nok 1 - ATest.test_one\n"));
+    assert!(report.contains("  stdout: |-\n    hello\n"));
+    assert!(report.contains("ok 2 - ATest.test_skip # SKIP not ready\n"));
+    assert!(report.contains("not ok 3 - ATest: fails\n  ---\n  duration_ms: "));
+    assert!(report.contains("  message: |-\n    assert_equal failed: values are not equal\n"));
+    assert!(report.contains("# tests 3\n# pass 1\n# fail 1\n# skip 1\n# duration_ms "));
+}
+
+#[test]
+fn test_report_file_requires_a_machine_format() {
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_tonic"))
+        .args(["test", ".", "--report-file", "report.xml"])
+        .output()
+        .expect("test command should execute");
+
+    assert_eq!(output.status.code(), Some(64));
+    assert_eq!(
+        String::from_utf8(output.stderr).expect("stderr should be utf8"),
+        "error: --report-file requires --format json, junit or tap\nusage: tonic test <path> --format <json|junit|tap> --report-file <path>\n"
+    );
+}
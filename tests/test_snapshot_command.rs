@@ -0,0 +1,95 @@
+use serde_json::Value;
+use std::fs;
+use std::path::Path;
+mod common;
+
+const SNAPSHOT_TEST: &str = "defmodule CliTest do\n  test \"help\" do\n    Assert.snapshot(\"usage: tool <path>\\n  --check  Verify\", \"help_output\")\n  end\n\n  test \"config\" do\n    Assert.snapshot(%{name: \"a fairly long project name here\", deps: [:json, :yaml], version: \"1.0.0\"}, \"config\")\n  end\nend\n";
+
+#[test]
+fn test_snapshots_are_written_on_first_run_and_compared_afterwards() {
+    let fixture_root = common::write_test_file("test-snapshot-write", "cli_test.tn", SNAPSHOT_TEST);
+
+    let first = run_tests(&fixture_root, &[]);
+    assert!(first.status.success());
+    let stdout = String::from_utf8(first.stdout).expect("stdout should be utf8");
+    assert!(stdout.contains(
+        "snapshots: 2 written\n  written: __snapshots__/cli_test/help_output.snap\n  written: __snapshots__/cli_test/config.snap\n"
+    ));
+
+    let snapshots = fixture_root.join("__snapshots__/cli_test");
+    assert_eq!(
+        fs::read_to_string(snapshots.join("help_output.snap")).expect("help snapshot"),
+        "usage: tool <path>\n  --check  Verify\n"
+    );
+    assert_eq!(
+        fs::read_to_string(snapshots.join("config.snap")).expect("config snapshot"),
+        "%{\n  :name => \"a fairly long project name here\",\n  :deps => [:json, :yaml],\n  :version => \"1.0.0\",\n}\n"
+    );
+
+    let second = run_tests(&fixture_root, &[]);
+    assert!(second.status.success());
+    let stdout = String::from_utf8(second.stdout).expect("stdout should be utf8");
+    assert!(!stdout.contains("snapshots:"));
+
+    fs::write(
+        fixture_root.join("cli_test.tn"),
+        SNAPSHOT_TEST.replace("\"1.0.0\"", "\"1.1.0\""),
+    )
+    .expect("fixture should update test file");
+    let changed = run_tests(&fixture_root, &[]);
+    assert_eq!(changed.status.code(), Some(1));
+    let stdout = String::from_utf8(changed.stdout).expect("stdout should be utf8");
+    assert!(stdout.contains(
+        "  error: snapshot failed: snapshot \"config\" does not match __snapshots__/cli_test/config.snap; run with --update-snapshots to accept the new value\n  diff (- left, + right):\n"
+    ));
+    assert!(stdout.contains("    -   :version => \"1.0.0\",\n    +   :version => \"1.1.0\",\n"));
+}
+
+#[test]
+fn test_update_snapshots_accepts_changes_and_removes_obsolete_snapshots() {
+    let fixture_root =
+        common::write_test_file("test-snapshot-update", "cli_test.tn", SNAPSHOT_TEST);
+    assert!(run_tests(&fixture_root, &[]).status.success());
+
+    let snapshots = fixture_root.join("__snapshots__/cli_test");
+    fs::write(snapshots.join("help_output.snap"), "usage: old\n").expect("edit snapshot");
+    fs::write(snapshots.join("removed_test.snap"), "stale\n").expect("write stale snapshot");
+
+    let output = run_tests(&fixture_root, &["--update-snapshots"]);
+
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).expect("stdout should be utf8");
+    assert!(stdout.contains(
+        "snapshots: 1 updated, 1 removed\n  updated: __snapshots__/cli_test/help_output.snap\n  removed: __snapshots__/cli_test/removed_test.snap\n"
+    ));
+    assert_eq!(
+        fs::read_to_string(snapshots.join("help_output.snap")).expect("help snapshot"),
+        "usage: tool <path>\n  --check  Verify\n"
+    );
+    assert!(!snapshots.join("removed_test.snap").exists());
+}
+
+#[test]
+fn test_obsolete_snapshots_are_only_reported_after_a_full_passing_run() {
+    let fixture_root =
+        common::write_test_file("test-snapshot-obsolete", "cli_test.tn", SNAPSHOT_TEST);
+    assert!(run_tests(&fixture_root, &[]).status.success());
+    let stale = fixture_root.join("__snapshots__/cli_test/removed_test.snap");
+    fs::write(&stale, "stale\n").expect("write stale snapshot");
+
+    let filtered = run_tests(&fixture_root, &["--filter", "help", "--format", "json"]);
+    let report: Value = serde_json::from_slice(&filtered.stdout).expect("stdout should be json");
+    assert!(report.get("snapshots").is_none());
+
+    let full = run_tests(&fixture_root, &["--format", "json"]);
+    let report: Value = serde_json::from_slice(&full.stdout).expect("stdout should be json");
+    assert_eq!(
+        report["snapshots"]["obsolete"],
+        serde_json::json!(["__snapshots__/cli_test/removed_test.snap"])
+    );
+    assert!(stale.exists());
+}
+
+fn run_tests(fixture_root: &Path, args: &[&str]) -> std::process::Output {
+    common::run_tonic_test(fixture_root, &[&["cli_test.tn"], args].concat())
+}
@@ -0,0 +1,165 @@
+use std::fs;
+use std::path::Path;
+mod common;
+use common::{tonic_in, write_file};
+
+/// Two apps and a `core` library, all members, plus a non-member `util`
+/// directory that both `web` and `core` depend on by path.
+fn write_workspace(root: &Path) {
+    write_file(
+        root,
+        "tonic.toml",
+        "[workspace]\nmembers = [\"apps/*\", \"libs/core\"]\n",
+    );
+    write_file(
+        root,
+        "libs/core/tonic.toml",
+        "[project]\nentry = \"src/core.tn\"\n\n[dependencies]\nutil = { path = \"../../vendor/util\" }\n",
+    );
+    write_file(
+        root,
+        "libs/core/src/core.tn",
+        "defmodule Core do\n  def greet(name) do\n    Util.shout(name)\n  end\nend\n",
+    );
+    write_file(
+        root,
+        "vendor/util/util.tn",
+        "defmodule Util do\n  def shout(name) do\n    name\n  end\nend\n",
+    );
+    for (app, extra) in [
+        ("web", "util = { path = \"../../vendor/util\" }\n"),
+        ("cli", ""),
+    ] {
+        write_file(
+            root,
+            &format!("apps/{app}/tonic.toml"),
+            &format!(
+                "[project]\nname = \"{app}\"\nentry = \"src/main.tn\"\n\n[dependencies]\ncore = {{ path = \"../../libs/core\" }}\n{extra}"
+            ),
+        );
+        write_file(
+            root,
+            &format!("apps/{app}/src/main.tn"),
+            &format!("defmodule Demo do\n  def run() do\n    Core.greet(\"{app}\")\n  end\nend\n"),
+        );
+    }
+}
+
+#[test]
+fn workspace_shares_one_lockfile_and_loads_member_modules_once() {
+    let root = common::unique_fixture_root("workspace-shared-lock");
+    write_workspace(&root);
+
+    // Locking from inside a member writes the workspace lockfile.
+    let (code, _, stderr) = tonic_in(&root.join("apps/web"), &["deps", "lock"]);
+    assert_eq!(code, 0, "stderr: {stderr}");
+    assert!(root.join("tonic.lock").exists());
+    assert!(!root.join("apps/web/tonic.lock").exists());
+    let lockfile = fs::read_to_string(root.join("tonic.lock")).unwrap();
+    assert!(lockfile.contains("[path_deps.core]"), "{lockfile}");
+    assert!(lockfile.contains("[path_deps.util]"), "{lockfile}");
+
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_tonic"))
+        .current_dir(&root)
+        .args(["run", "apps/web"])
+        .env("TONIC_DEBUG_MODULE_LOADS", "1")
+        .output()
+        .expect("tonic should execute");
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert_eq!(output.status.code(), Some(0), "stderr: {stderr}");
+    assert_eq!(String::from_utf8_lossy(&output.stdout).trim(), "\"web\"");
+    assert!(
+        stderr.contains("module-load dep:member:") && stderr.contains("core.tn"),
+        "stderr: {stderr}"
+    );
+    assert_eq!(
+        stderr.matches("util.tn").count(),
+        1,
+        "util is reached through web and core but loads once: {stderr}"
+    );
+
+    let (code, stdout, stderr) = tonic_in(&root, &["run", "apps/cli"]);
+    assert_eq!(code, 0, "stderr: {stderr}");
+    assert_eq!(stdout.trim(), "\"cli\"");
+}
+
+#[test]
+fn workspace_commands_run_every_member_or_the_one_selected_with_p() {
+    let root = common::unique_fixture_root("workspace-members");
+    write_workspace(&root);
+    assert_eq!(tonic_in(&root, &["deps", "lock"]).0, 0);
+
+    let (code, stdout, stderr) = tonic_in(&root, &["check", "."]);
+    assert_eq!(code, 0, "stderr: {stderr}");
+    assert!(
+        stdout.contains("==> cli (apps/cli)\ncheck: ok\n"),
+        "{stdout}"
+    );
+    assert!(
+        stdout.contains("==> web (apps/web)\ncheck: ok\n"),
+        "{stdout}"
+    );
+    assert!(
+        stdout.contains("==> core (libs/core)\ncheck: ok\n"),
+        "{stdout}"
+    );
+    assert!(stdout.ends_with("workspace: 3 members ok\n"), "{stdout}");
+
+    let (code, stdout, stderr) = tonic_in(&root.join("apps/web"), &["check", "-p", "cli"]);
+    assert_eq!(code, 0, "stderr: {stderr}");
+    assert_eq!(stdout, "check: ok\n");
+
+    let (code, _, stderr) = tonic_in(&root, &["check", "-p", "admin"]);
+    assert_eq!(code, 64);
+    assert!(
+        stderr.contains("no workspace member named 'admin'"),
+        "stderr: {stderr}"
+    );
+    assert!(stderr.contains("cli, web, core"), "stderr: {stderr}");
+
+    // One failing member fails the run but the others still run.
+    write_file(
+        &root,
+        "apps/cli/src/main.tn",
+        "defmodule Demo do\n  def run() do\n    Missing.call()\n  end\nend\n",
+    );
+    let (code, stdout, _) = tonic_in(&root, &["check", "."]);
+    assert_eq!(code, 1);
+    assert!(
+        stdout.contains("==> core (libs/core)\ncheck: ok\n"),
+        "{stdout}"
+    );
+    assert!(
+        stdout.ends_with("workspace: 1 of 3 members failed: cli\n"),
+        "{stdout}"
+    );
+
+    write_file(
+        &root,
+        "apps/web/src/main.tn",
+        "defmodule Demo do\ndef run() do\n1\nend\nend\n",
+    );
+    let (code, stdout, _) = tonic_in(&root, &["fmt", ".", "--check"]);
+    assert_eq!(code, 1);
+    assert!(
+        stdout.ends_with("workspace: 1 of 3 members failed: web\n"),
+        "{stdout}"
+    );
+    let (code, stdout, stderr) = tonic_in(&root, &["fmt", "-p", "web"]);
+    assert_eq!(code, 0, "stderr: {stderr}");
+    assert_eq!(stdout, "fmt: ok\n");
+
+    let (code, _, stderr) = tonic_in(&root, &["compile", ".", "--out", "app"]);
+    assert_eq!(code, 64);
+    assert!(
+        stderr.contains("--out cannot be used when running every workspace member"),
+        "stderr: {stderr}"
+    );
+
+    let (code, _, stderr) = tonic_in(&root, &["run", "."]);
+    assert_eq!(code, 1);
+    assert!(
+        stderr.contains("is a workspace root without a [project]"),
+        "stderr: {stderr}"
+    );
+}